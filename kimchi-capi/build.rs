@@ -0,0 +1,18 @@
+//! Regenerates `include/kimchi.h` from the `extern "C"` functions in this crate.
+
+fn main() {
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    println!("cargo:rerun-if-changed=src/lib.rs");
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir))
+        .expect("invalid cbindgen.toml");
+    match cbindgen::generate_with_config(&crate_dir, config) {
+        Ok(bindings) => {
+            bindings.write_to_file(format!("{}/include/kimchi.h", crate_dir));
+        }
+        // Keep the checked-in header rather than failing the build, e.g. when
+        // cbindgen cannot parse a newer syntax.
+        Err(e) => println!("cargo:warning=cbindgen failed, header not updated: {}", e),
+    }
+}
@@ -0,0 +1,151 @@
+/* Generated by cbindgen from kimchi-capi. Do not edit. */
+
+#ifndef KIMCHI_H
+#define KIMCHI_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Success.
+#define KIMCHI_OK 0
+
+// Setup failed or the prover is not initialized.
+#define KIMCHI_ERR_SETUP 100
+
+// Proof generation failed.
+#define KIMCHI_ERR_PROVING 300
+
+// Verification failed to run.
+#define KIMCHI_ERR_VERIFICATION 400
+
+// An argument was invalid, e.g. a null pointer or unknown proof handle.
+#define KIMCHI_ERR_INVALID_INPUT 500
+
+// The proof handle outlived the TTL set with `kimchi_init_prover`.
+#define KIMCHI_ERR_PROOF_EXPIRED 510
+
+// Encoding or decoding failed.
+#define KIMCHI_ERR_SERIALIZATION 600
+
+// A bug in the library, including caught panics.
+#define KIMCHI_ERR_INTERNAL 900
+
+// Result of proof generation. See `ProofResult` in `kimchi-ffi`.
+typedef struct KimchiProofResult {
+  // Handle to the proof stored in memory (for verification).
+  uint64_t proof_handle;
+  // Serialized proof (hex-encoded).
+  char *proof_bytes;
+  // Public inputs as hex-encoded field elements.
+  char **public_inputs;
+  // Number of entries in `public_inputs`.
+  size_t public_inputs_len;
+  // Time taken in milliseconds.
+  uint64_t generation_time_ms;
+  // Size of the proof in bytes.
+  uint64_t proof_size_bytes;
+  // Hex-encoded MessagePack `ProofEnvelope`.
+  char *envelope;
+} KimchiProofResult;
+
+// Metrics receiver. See `MetricsCallback` in `kimchi-ffi` for the metric
+// names. Any function pointer may be null. The callbacks may be invoked
+// from any thread and receive `user_data` unchanged; strings are only valid
+// for the duration of the call.
+typedef struct KimchiMetricsCallback {
+  void *user_data;
+  void (*counter)(void*, const char*, uint64_t);
+  void (*histogram)(void*, const char*, double);
+  // A phase failed with the given `ProverError` code and message.
+  void (*error)(void*, const char*, uint32_t, const char*);
+} KimchiMetricsCallback;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Free a string returned by this library. Null is ignored.
+//
+// # Safety
+// `s` must be null or a string returned by this library that has not been
+// freed.
+void kimchi_string_free(char *s);
+
+// Message of the last error on the calling thread, or null if none.
+//
+// The pointer stays valid until the next failing call on this thread; do
+// not free it.
+const char *kimchi_last_error_message(void);
+
+// Initialize the prover. Call this once at app startup.
+//
+// `srs_log2_size` of 0 selects the default (14, i.e. 16384 rows). Stored
+// proofs older than `proof_ttl_secs` are freed automatically and their
+// handles fail with `KIMCHI_ERR_PROOF_EXPIRED`; 0 keeps them until
+// `kimchi_free_proof()`.
+int32_t kimchi_init_prover(uint32_t srs_log2_size, uint64_t proof_ttl_secs);
+
+// Prove that `value >= threshold` without revealing `value`.
+//
+// On success `*out` owns the result; release it with
+// `kimchi_proof_result_free()`.
+//
+// # Safety
+// `out` must be null or valid for writes.
+int32_t kimchi_prove_threshold(uint64_t value, uint64_t threshold, struct KimchiProofResult *out);
+
+// Free the strings owned by a proof result and reset it. The proof itself
+// stays stored until `kimchi_free_proof()`. Null is ignored.
+//
+// # Safety
+// `result` must be null or a result filled by this library that has not
+// been freed.
+void kimchi_proof_result_free(struct KimchiProofResult *result);
+
+// Verify a stored proof, writing whether it is valid to `*out_valid`.
+//
+// # Safety
+// `out_valid` must be null or valid for writes.
+int32_t kimchi_verify_proof(uint64_t proof_handle, bool *out_valid);
+
+// Free a stored proof.
+int32_t kimchi_free_proof(uint64_t proof_handle);
+
+// The library version. Free with `kimchi_string_free()`.
+char *kimchi_get_version(void);
+
+// Write the hex-encoded verifier index of a stored proof to `*out`.
+// Free with `kimchi_string_free()`.
+//
+// # Safety
+// `out` must be null or valid for writes.
+int32_t kimchi_export_verifier_index(uint64_t proof_handle, char **out);
+
+// Write the o1js verification key JSON of a stored proof to `*out`.
+// Free with `kimchi_string_free()`.
+//
+// # Safety
+// `out` must be null or valid for writes.
+int32_t kimchi_export_mina_verification_key(uint64_t proof_handle, char **out);
+
+// Write the SRS log2 size used by the prover to `*out`.
+//
+// # Safety
+// `out` must be null or valid for writes.
+int32_t kimchi_get_srs_log2_size(uint32_t *out);
+
+// Install a metrics callback. Replaces any previous one.
+//
+// # Safety
+// The function pointers and `user_data` must remain valid, and be safe to
+// use from any thread, until the callback is replaced.
+int32_t kimchi_set_metrics_callback(struct KimchiMetricsCallback callback);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* KIMCHI_H */
@@ -0,0 +1,407 @@
+//! Plain C bindings for Kimchi mobile prover.
+//!
+//! Mirrors the UniFFI surface of `kimchi-ffi` as `extern "C"` functions for
+//! hosts where the generated Kotlin/Swift bindings don't apply (C++, Unity,
+//! Qt). The header is generated by cbindgen into `include/kimchi.h`.
+//!
+//! Conventions:
+//! - Functions return a status: `KIMCHI_OK` (0) or an error code, which is
+//!   the `ProverError` code when the prover reported one (see
+//!   `ProverError::code`) and otherwise the first code of the matching
+//!   category. `kimchi_last_error_message()` describes the last failure on
+//!   the calling thread.
+//! - Results are written through out-pointers. Strings returned by the
+//!   library are owned by the caller and released with `kimchi_string_free()`;
+//!   proof results with `kimchi_proof_result_free()`.
+//! - Panics are caught at the boundary and reported as `KIMCHI_ERR_INTERNAL`.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_void, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+use std::sync::Arc;
+
+use kimchi_ffi::{KimchiError, MetricsCallback, ProofResult};
+
+/// Success.
+pub const KIMCHI_OK: i32 = 0;
+/// Setup failed or the prover is not initialized.
+pub const KIMCHI_ERR_SETUP: i32 = 100;
+/// Proof generation failed.
+pub const KIMCHI_ERR_PROVING: i32 = 300;
+/// Verification failed to run.
+pub const KIMCHI_ERR_VERIFICATION: i32 = 400;
+/// An argument was invalid, e.g. a null pointer or unknown proof handle.
+pub const KIMCHI_ERR_INVALID_INPUT: i32 = 500;
+/// The proof handle outlived the TTL set with `kimchi_init_prover`.
+pub const KIMCHI_ERR_PROOF_EXPIRED: i32 = 510;
+/// Encoding or decoding failed.
+pub const KIMCHI_ERR_SERIALIZATION: i32 = 600;
+/// A bug in the library, including caught panics.
+pub const KIMCHI_ERR_INTERNAL: i32 = 900;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(message: String) {
+    let message = CString::new(message.replace('\0', " ")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+fn error_code(err: &KimchiError) -> i32 {
+    match err {
+        KimchiError::SetupError(_) => KIMCHI_ERR_SETUP,
+        KimchiError::ProvingError(_) => KIMCHI_ERR_PROVING,
+        KimchiError::VerificationError(_) => KIMCHI_ERR_VERIFICATION,
+        KimchiError::SerializationError(_) => KIMCHI_ERR_SERIALIZATION,
+        KimchiError::InvalidInput(_) | KimchiError::ProofNotFound(_) => KIMCHI_ERR_INVALID_INPUT,
+        KimchiError::ProofExpired(_) => KIMCHI_ERR_PROOF_EXPIRED,
+        KimchiError::Prover { code, .. } => *code as i32,
+    }
+}
+
+/// Run `f`, translating errors and panics into a status code.
+fn guard(f: impl FnOnce() -> Result<(), KimchiError>) -> i32 {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => KIMCHI_OK,
+        Ok(Err(e)) => {
+            let code = error_code(&e);
+            set_last_error(e.to_string());
+            code
+        }
+        Err(_) => {
+            set_last_error("internal error: panic in kimchi".into());
+            KIMCHI_ERR_INTERNAL
+        }
+    }
+}
+
+fn out_ptr<'a, T>(out: *mut T) -> Result<&'a mut T, KimchiError> {
+    // SAFETY: callers pass either null or a pointer valid for writes.
+    unsafe { out.as_mut() }
+        .ok_or_else(|| KimchiError::InvalidInput("output pointer is null".into()))
+}
+
+fn into_c_string(s: String) -> *mut c_char {
+    CString::new(s.replace('\0', " "))
+        .unwrap_or_default()
+        .into_raw()
+}
+
+/// Free a string returned by this library. Null is ignored.
+///
+/// # Safety
+/// `s` must be null or a string returned by this library that has not been
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn kimchi_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Message of the last error on the calling thread, or null if none.
+///
+/// The pointer stays valid until the next failing call on this thread; do
+/// not free it.
+#[no_mangle]
+pub extern "C" fn kimchi_last_error_message() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+/// Result of proof generation. See `ProofResult` in `kimchi-ffi`.
+#[repr(C)]
+pub struct KimchiProofResult {
+    /// Handle to the proof stored in memory (for verification).
+    pub proof_handle: u64,
+    /// Serialized proof (hex-encoded).
+    pub proof_bytes: *mut c_char,
+    /// Public inputs as hex-encoded field elements.
+    pub public_inputs: *mut *mut c_char,
+    /// Number of entries in `public_inputs`.
+    pub public_inputs_len: usize,
+    /// Time taken in milliseconds.
+    pub generation_time_ms: u64,
+    /// Size of the proof in bytes.
+    pub proof_size_bytes: u64,
+    /// Hex-encoded MessagePack `ProofEnvelope`.
+    pub envelope: *mut c_char,
+}
+
+impl From<ProofResult> for KimchiProofResult {
+    fn from(result: ProofResult) -> Self {
+        let public_inputs: Box<[*mut c_char]> = result
+            .public_inputs
+            .into_iter()
+            .map(into_c_string)
+            .collect();
+        let public_inputs_len = public_inputs.len();
+        Self {
+            proof_handle: result.proof_handle,
+            proof_bytes: into_c_string(result.proof_bytes),
+            public_inputs: Box::into_raw(public_inputs) as *mut *mut c_char,
+            public_inputs_len,
+            generation_time_ms: result.generation_time_ms,
+            proof_size_bytes: result.proof_size_bytes,
+            envelope: into_c_string(result.envelope),
+        }
+    }
+}
+
+/// Initialize the prover. Call this once at app startup.
+///
+/// `srs_log2_size` of 0 selects the default (14, i.e. 16384 rows). Stored
+/// proofs older than `proof_ttl_secs` are freed automatically and their
+/// handles fail with `KIMCHI_ERR_PROOF_EXPIRED`; 0 keeps them until
+/// `kimchi_free_proof()`.
+#[no_mangle]
+pub extern "C" fn kimchi_init_prover(srs_log2_size: u32, proof_ttl_secs: u64) -> i32 {
+    guard(|| {
+        kimchi_ffi::init_prover(
+            (srs_log2_size != 0).then_some(srs_log2_size),
+            (proof_ttl_secs != 0).then_some(proof_ttl_secs),
+            None,
+        )
+    })
+}
+
+/// Prove that `value >= threshold` without revealing `value`.
+///
+/// On success `*out` owns the result; release it with
+/// `kimchi_proof_result_free()`.
+///
+/// # Safety
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn kimchi_prove_threshold(
+    value: u64,
+    threshold: u64,
+    out: *mut KimchiProofResult,
+) -> i32 {
+    guard(|| {
+        let out = out_ptr(out)?;
+        *out = kimchi_ffi::prove_threshold(value, threshold)?.into();
+        Ok(())
+    })
+}
+
+/// Free the strings owned by a proof result and reset it. The proof itself
+/// stays stored until `kimchi_free_proof()`. Null is ignored.
+///
+/// # Safety
+/// `result` must be null or a result filled by this library that has not
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn kimchi_proof_result_free(result: *mut KimchiProofResult) {
+    let Some(result) = result.as_mut() else {
+        return;
+    };
+    kimchi_string_free(result.proof_bytes);
+    kimchi_string_free(result.envelope);
+    if !result.public_inputs.is_null() {
+        let inputs = Box::from_raw(ptr::slice_from_raw_parts_mut(
+            result.public_inputs,
+            result.public_inputs_len,
+        ));
+        for &s in inputs.iter() {
+            kimchi_string_free(s);
+        }
+    }
+    result.proof_bytes = ptr::null_mut();
+    result.public_inputs = ptr::null_mut();
+    result.public_inputs_len = 0;
+    result.envelope = ptr::null_mut();
+}
+
+/// Verify a stored proof, writing whether it is valid to `*out_valid`.
+///
+/// # Safety
+/// `out_valid` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn kimchi_verify_proof(proof_handle: u64, out_valid: *mut bool) -> i32 {
+    guard(|| {
+        let out = out_ptr(out_valid)?;
+        *out = kimchi_ffi::verify_proof(proof_handle)?;
+        Ok(())
+    })
+}
+
+/// Free a stored proof.
+#[no_mangle]
+pub extern "C" fn kimchi_free_proof(proof_handle: u64) -> i32 {
+    guard(|| kimchi_ffi::free_proof(proof_handle))
+}
+
+/// The library version. Free with `kimchi_string_free()`.
+#[no_mangle]
+pub extern "C" fn kimchi_get_version() -> *mut c_char {
+    into_c_string(kimchi_ffi::get_version())
+}
+
+/// Write the hex-encoded verifier index of a stored proof to `*out`.
+/// Free with `kimchi_string_free()`.
+///
+/// # Safety
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn kimchi_export_verifier_index(
+    proof_handle: u64,
+    out: *mut *mut c_char,
+) -> i32 {
+    guard(|| {
+        let out = out_ptr(out)?;
+        *out = into_c_string(kimchi_ffi::export_verifier_index(proof_handle)?);
+        Ok(())
+    })
+}
+
+/// Write the o1js verification key JSON of a stored proof to `*out`.
+/// Free with `kimchi_string_free()`.
+///
+/// # Safety
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn kimchi_export_mina_verification_key(
+    proof_handle: u64,
+    out: *mut *mut c_char,
+) -> i32 {
+    guard(|| {
+        let out = out_ptr(out)?;
+        *out = into_c_string(kimchi_ffi::export_mina_verification_key(proof_handle)?);
+        Ok(())
+    })
+}
+
+/// Write the SRS log2 size used by the prover to `*out`.
+///
+/// # Safety
+/// `out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn kimchi_get_srs_log2_size(out: *mut u32) -> i32 {
+    guard(|| {
+        let out = out_ptr(out)?;
+        *out = kimchi_ffi::get_srs_log2_size()?;
+        Ok(())
+    })
+}
+
+/// Metrics receiver. See `MetricsCallback` in `kimchi-ffi` for the metric
+/// names. Any function pointer may be null. The callbacks may be invoked
+/// from any thread and receive `user_data` unchanged; strings are only valid
+/// for the duration of the call.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct KimchiMetricsCallback {
+    pub user_data: *mut c_void,
+    pub counter: Option<unsafe extern "C" fn(*mut c_void, *const c_char, u64)>,
+    pub histogram: Option<unsafe extern "C" fn(*mut c_void, *const c_char, f64)>,
+    /// A phase failed with the given `ProverError` code and message.
+    pub error: Option<unsafe extern "C" fn(*mut c_void, *const c_char, u32, *const c_char)>,
+}
+
+// SAFETY: `kimchi_set_metrics_callback` requires the callbacks and
+// `user_data` to be usable from any thread.
+unsafe impl Send for KimchiMetricsCallback {}
+unsafe impl Sync for KimchiMetricsCallback {}
+
+fn c_str(s: &str) -> CString {
+    CString::new(s.replace('\0', " ")).unwrap_or_default()
+}
+
+impl MetricsCallback for KimchiMetricsCallback {
+    fn counter(&self, name: String, value: u64) {
+        if let Some(f) = self.counter {
+            // SAFETY: the host guarantees the callback is valid.
+            unsafe { f(self.user_data, c_str(&name).as_ptr(), value) }
+        }
+    }
+
+    fn histogram(&self, name: String, value: f64) {
+        if let Some(f) = self.histogram {
+            // SAFETY: the host guarantees the callback is valid.
+            unsafe { f(self.user_data, c_str(&name).as_ptr(), value) }
+        }
+    }
+
+    fn error(&self, phase: String, code: u32, message: String) {
+        if let Some(f) = self.error {
+            // SAFETY: the host guarantees the callback is valid.
+            unsafe {
+                f(
+                    self.user_data,
+                    c_str(&phase).as_ptr(),
+                    code,
+                    c_str(&message).as_ptr(),
+                )
+            }
+        }
+    }
+}
+
+/// Install a metrics callback. Replaces any previous one.
+///
+/// # Safety
+/// The function pointers and `user_data` must remain valid, and be safe to
+/// use from any thread, until the callback is replaced.
+#[no_mangle]
+pub unsafe extern "C" fn kimchi_set_metrics_callback(callback: KimchiMetricsCallback) -> i32 {
+    guard(|| {
+        kimchi_ffi::set_metrics_callback(Arc::new(callback));
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_init() {
+        assert_eq!(kimchi_init_prover(10, 0), KIMCHI_OK);
+        let mut size = 0;
+        assert_eq!(unsafe { kimchi_get_srs_log2_size(&mut size) }, KIMCHI_OK);
+        assert_eq!(size, 10);
+
+        let version = kimchi_get_version();
+        assert_eq!(
+            unsafe { CStr::from_ptr(version) }.to_str().unwrap(),
+            "0.1.0"
+        );
+        unsafe { kimchi_string_free(version) };
+    }
+
+    #[test]
+    fn test_proof_result_free() {
+        let mut result: KimchiProofResult = ProofResult {
+            proof_handle: 1,
+            proof_bytes: "00".into(),
+            public_inputs: vec!["01".into(), "02".into()],
+            generation_time_ms: 0,
+            proof_size_bytes: 1,
+            envelope: "03".into(),
+            signed_envelope: None,
+        }
+        .into();
+        assert_eq!(result.public_inputs_len, 2);
+        let second = unsafe { CStr::from_ptr(*result.public_inputs.add(1)) };
+        assert_eq!(second.to_str().unwrap(), "02");
+
+        unsafe { kimchi_proof_result_free(&mut result) };
+        assert!(result.public_inputs.is_null());
+        assert!(result.envelope.is_null());
+    }
+
+    #[test]
+    fn test_errors() {
+        let status = unsafe { kimchi_verify_proof(u64::MAX, ptr::null_mut()) };
+        assert_eq!(status, KIMCHI_ERR_INVALID_INPUT);
+        let message = unsafe { CStr::from_ptr(kimchi_last_error_message()) };
+        assert!(message.to_str().unwrap().contains("null"));
+
+        let mut valid = false;
+        let status = unsafe { kimchi_verify_proof(u64::MAX, &mut valid) };
+        assert_ne!(status, KIMCHI_OK);
+    }
+}
@@ -6,16 +6,32 @@
 //!
 //! Uses proc-macro approach (no UDL file).
 
-use std::collections::HashMap;
-use std::sync::{Mutex, OnceLock, RwLock};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
 
-use ark_serialize::CanonicalSerialize;
 use kimchi::proof::ProverProof;
 use kimchi::verifier_index::VerifierIndex;
 use kimchi_prover::{
-    Fp, KimchiProver, ProverConfig, ThresholdCircuit, Vesta, VestaOpeningProof, FULL_ROUNDS,
+    circuits::registry, metrics::Phase, profiling, prover::verifier_index_srs_log2_size,
+    srs_provider, types::FIELD_BYTES, AttestationPlatform as Platform, Bytes256, Bytes32, Bytes64,
+    Circuit, CircuitInfo as CircuitDescription, CoseSign1, DeviceAttestation, EnvelopeSigner,
+    FieldElement, Fp, HexWriter, KimchiVerifier, MerkleMembershipCircuit, MerkleStep,
+    MetricsAggregator, MetricsSink, MinaVerificationKeyJson, ProofEnvelope, ProverConfig,
+    ProverError, ProvingSession, ProvingTimeClass, PublicInputSchema, PublicInputsDocument,
+    SignedEnvelope, SrsProvider, ThresholdCircuit, ThresholdOperator as Operator,
+    TimestampedCircuit, ValueWidth as Width, VerifierIndexRegistry, VerifierIndexStore, Vesta,
+    VestaOpeningProof, WideThresholdCircuit, ZkappSubmission, COLUMNS, FULL_ROUNDS,
 };
 use poly_commitment::ipa::SRS;
+use zeroize::{Zeroize, Zeroizing};
+
+#[cfg(feature = "tracing")]
+use kimchi_prover::profiling::ChromeTraceRecorder;
+#[cfg(feature = "tracing")]
+use tracing_subscriber::layer::SubscriberExt;
+
+mod runtime;
 
 // Generate UniFFI scaffolding via proc macros
 uniffi::setup_scaffolding!();
@@ -23,20 +39,239 @@ uniffi::setup_scaffolding!();
 /// Global initialization state.
 static INITIALIZED: OnceLock<bool> = OnceLock::new();
 
-/// Global prover instance (lazy initialized).
-static PROVER: OnceLock<Mutex<KimchiProver>> = OnceLock::new();
+/// Global proving session (lazy initialized), holding the prover and the
+/// indices of the circuits proved so far.
+static SESSION: OnceLock<Mutex<ProvingSession>> = OnceLock::new();
+
+/// Verify-only instance created by `init_verifier`.
+static VERIFIER: OnceLock<Mutex<KimchiVerifier>> = OnceLock::new();
 
 /// Counter for proof handles.
 static PROOF_COUNTER: OnceLock<Mutex<u64>> = OnceLock::new();
 
 /// In-memory storage for proofs (keyed by handle ID).
-static PROOF_STORE: OnceLock<RwLock<HashMap<u64, StoredProof>>> = OnceLock::new();
+static PROOF_STORE: OnceLock<RwLock<ProofStore>> = OnceLock::new();
+
+/// Metrics sink installed with `set_metrics_callback`.
+static METRICS: OnceLock<RwLock<Arc<dyn MetricsSink>>> = OnceLock::new();
+
+/// Totals and latencies returned by `get_metrics`, fed whether or not a
+/// callback is installed.
+static AGGREGATE: OnceLock<Arc<MetricsAggregator>> = OnceLock::new();
+
+/// Key signing the envelopes of new proofs, set with
+/// `set_envelope_signing_key`.
+static ENVELOPE_SIGNER: RwLock<Option<EnvelopeSigner>> = RwLock::new(None);
+
+/// SRS source set with `set_srs_source`, used by `init_prover`.
+static SRS_SOURCE: RwLock<Option<Arc<dyn SrsProvider>>> = RwLock::new(None);
+
+/// Trace recorder installed by `start_trace`, or `None` if another global
+/// `tracing` subscriber was already installed.
+#[cfg(feature = "tracing")]
+static TRACE_RECORDER: OnceLock<Option<ChromeTraceRecorder>> = OnceLock::new();
 
-/// Stored proof data that includes the verifier index with its SRS reference.
+/// Stored proof data, referencing its circuit's shared verifier index.
 struct StoredProof {
     proof: ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
-    verifier_index: VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
-    public_inputs: Vec<Fp>,
+    verifier_index: Arc<VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>>,
+    /// `ProofEnvelope::verifier_index_digest` of `verifier_index`, its key
+    /// in `ProofStore::verifier_indices`.
+    verifier_index_digest: [u8; 32],
+    public_inputs: StoredInputs,
+    /// Serialized size of the proof and public inputs.
+    size_bytes: usize,
+    stored_at: Instant,
+}
+
+/// Public inputs of a stored proof and the schema they decode by.
+struct StoredInputs {
+    circuit_id: &'static str,
+    schema: PublicInputSchema,
+    values: Vec<Fp>,
+}
+
+impl StoredInputs {
+    fn new(circuit: &dyn Circuit, values: Vec<Fp>) -> Self {
+        Self {
+            circuit_id: circuit.id(),
+            schema: circuit.public_input_schema(),
+            values,
+        }
+    }
+}
+
+/// A verifier index shared by stored proofs.
+struct SharedIndex {
+    verifier_index: Arc<VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>>,
+    /// Serialized size of the index.
+    size_bytes: usize,
+}
+
+/// Stored proofs and the verifier indices they share.
+///
+/// Proofs of the same circuit have identical verifier indices, so each is
+/// kept once, keyed by its `ProofEnvelope::verifier_index_digest`, and
+/// dropped with the last proof that references it.
+///
+/// Entry sizes are the serialized sizes of what is kept, which tracks heap
+/// use closely enough to budget by. With `limit_bytes` set, an insert that
+/// would exceed it fails instead of growing the store.
+///
+/// With `ttl` set, proofs older than it are dropped by `sweep`, which runs
+/// on every insert and lookup. Their handles are remembered in `expired`
+/// until freed, so lookups can tell an expired proof from an unknown one;
+/// past `MAX_EXPIRED_HANDLES`, the oldest are forgotten and look unknown.
+#[derive(Default)]
+struct ProofStore {
+    proofs: HashMap<u64, StoredProof>,
+    verifier_indices: HashMap<[u8; 32], SharedIndex>,
+    total_bytes: usize,
+    limit_bytes: Option<usize>,
+    ttl: Option<Duration>,
+    expired: BTreeSet<u64>,
+}
+
+/// Most expired handles `ProofStore` remembers. Handles are never reused,
+/// so without a cap apps that never free expired proofs would grow the set
+/// forever.
+const MAX_EXPIRED_HANDLES: usize = 1024;
+
+impl ProofStore {
+    /// Add a proof of `proof_size_bytes` serialized bytes.
+    ///
+    /// Fails with `ProverError::ResourceExhausted` if the store would exceed
+    /// its limit.
+    fn insert(
+        &mut self,
+        id: u64,
+        proof: ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
+        proof_size_bytes: usize,
+        verifier_index: VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        verifier_index_digest: [u8; 32],
+        public_inputs: StoredInputs,
+    ) -> Result<(), ProverError> {
+        let now = Instant::now();
+        self.sweep(now);
+        let size_bytes = proof_size_bytes + public_inputs.values.len() * FIELD_BYTES;
+        let index_size_bytes = match self.verifier_indices.get(&verifier_index_digest) {
+            Some(_) => 0,
+            None => rmp_serde::to_vec(&verifier_index)
+                .map_err(|e| ProverError::encode("verifier index", e))?
+                .len(),
+        };
+
+        let required = self.total_bytes + size_bytes + index_size_bytes;
+        if let Some(limit) = self.limit_bytes {
+            if required > limit {
+                return Err(ProverError::ResourceExhausted {
+                    what: "proof store",
+                    required,
+                    limit: Some(limit),
+                });
+            }
+        }
+
+        let shared = self
+            .verifier_indices
+            .entry(verifier_index_digest)
+            .or_insert_with(|| SharedIndex {
+                verifier_index: Arc::new(verifier_index),
+                size_bytes: index_size_bytes,
+            });
+        let verifier_index = shared.verifier_index.clone();
+        self.proofs.insert(
+            id,
+            StoredProof {
+                proof,
+                verifier_index,
+                verifier_index_digest,
+                public_inputs,
+                size_bytes,
+                stored_at: now,
+            },
+        );
+        self.total_bytes = required;
+        Ok(())
+    }
+
+    fn remove(&mut self, id: u64) {
+        self.expired.remove(&id);
+        let Some(stored) = self.proofs.remove(&id) else {
+            return;
+        };
+        self.total_bytes -= stored.size_bytes;
+        let verifier_index_digest = stored.verifier_index_digest;
+        drop(stored);
+        if let Some(shared) = self.verifier_indices.get(&verifier_index_digest) {
+            if Arc::strong_count(&shared.verifier_index) == 1 {
+                self.total_bytes -= shared.size_bytes;
+                self.verifier_indices.remove(&verifier_index_digest);
+            }
+        }
+    }
+
+    /// Drop the proofs stored for longer than the TTL as of `now`, returning
+    /// how many were dropped.
+    fn sweep(&mut self, now: Instant) -> usize {
+        let Some(ttl) = self.ttl else {
+            return 0;
+        };
+        let expired: Vec<u64> = self
+            .proofs
+            .iter()
+            .filter(|(_, stored)| now.saturating_duration_since(stored.stored_at) >= ttl)
+            .map(|(id, _)| *id)
+            .collect();
+        for &id in &expired {
+            self.remove(id);
+            self.expired.insert(id);
+        }
+        // Handles increase, so the first are the oldest
+        while self.expired.len() > MAX_EXPIRED_HANDLES {
+            self.expired.pop_first();
+        }
+        if !expired.is_empty() {
+            metrics_sink().counter("kimchi.ffi.proof_store.expired", expired.len() as u64);
+        }
+        expired.len()
+    }
+
+    fn stats(&self) -> ProofStoreStats {
+        ProofStoreStats {
+            proofs: self.proofs.len() as u64,
+            verifier_indices: self.verifier_indices.len() as u64,
+            total_bytes: self.total_bytes as u64,
+            limit_bytes: self.limit_bytes.map(|limit| limit as u64),
+            ttl_secs: self.ttl.map(|ttl| ttl.as_secs()),
+        }
+    }
+
+    /// Report the store's size to the metrics sink.
+    fn report(&self) {
+        let sink = metrics_sink();
+        sink.histogram("kimchi.ffi.proof_store.bytes", self.total_bytes as f64);
+        sink.histogram("kimchi.ffi.proof_store.proofs", self.proofs.len() as f64);
+        sink.histogram(
+            "kimchi.ffi.proof_store.verifier_indices",
+            self.verifier_indices.len() as f64,
+        );
+    }
+}
+
+/// Size of the in-memory proof store.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct ProofStoreStats {
+    /// Number of stored proofs.
+    pub proofs: u64,
+    /// Number of distinct verifier indices they reference.
+    pub verifier_indices: u64,
+    /// Serialized size of everything stored.
+    pub total_bytes: u64,
+    /// Limit set with `set_proof_store_limit`, if any.
+    pub limit_bytes: Option<u64>,
+    /// Proof lifetime set with `init_prover`, if any.
+    pub ttl_secs: Option<u64>,
 }
 
 /// Error types exposed via FFI.
@@ -59,6 +294,248 @@ pub enum KimchiError {
 
     #[error("Proof not found: {0}")]
     ProofNotFound(String),
+
+    /// The proof outlived the TTL set with `init_prover` and was freed.
+    #[error("Proof expired: {0}")]
+    ProofExpired(String),
+
+    /// An error from the prover, with its stable code (see
+    /// `ProverError::code`), typed context as strings and the messages of
+    /// its underlying causes, outermost first.
+    #[error("{message}")]
+    Prover {
+        code: u32,
+        message: String,
+        details: HashMap<String, String>,
+        causes: Vec<String>,
+    },
+}
+
+impl From<ProverError> for KimchiError {
+    fn from(err: ProverError) -> Self {
+        KimchiError::Prover {
+            code: err.code(),
+            details: err
+                .details()
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect(),
+            causes: err.chain().split_off(1),
+            message: err.to_string(),
+        }
+    }
+}
+
+/// Fixed-size byte types cross the FFI boundary as hex strings and are
+/// length-checked when lifted, so foreign callers get an error instead of a
+/// truncated or padded array.
+macro_rules! hex_custom_type {
+    ($name:ident) => {
+        uniffi::custom_type!($name, String);
+
+        impl UniffiCustomTypeConverter for $name {
+            type Builtin = String;
+
+            fn into_custom(val: String) -> uniffi::Result<Self> {
+                Ok($name::from_hex(&val)?)
+            }
+
+            fn from_custom(obj: Self) -> String {
+                obj.to_hex()
+            }
+        }
+    };
+}
+
+hex_custom_type!(Bytes32);
+hex_custom_type!(Bytes64);
+hex_custom_type!(Bytes256);
+
+/// Metrics receiver implemented by the host app, e.g. to forward to its
+/// monitoring SDK.
+///
+/// Metric names are prefixed with `kimchi.`: the prover reports
+/// `kimchi.<phase>.count`, `kimchi.<phase>.duration_ms` and
+/// `kimchi.<phase>.errors` for `srs_init`, `setup`, `prove` and `verify`,
+/// and the bindings add `kimchi.ffi.<function>.*` around each call.
+#[uniffi::export(with_foreign)]
+pub trait MetricsCallback: Send + Sync {
+    fn counter(&self, name: String, value: u64);
+    fn histogram(&self, name: String, value: f64);
+    /// A phase failed with the given `ProverError` code.
+    fn error(&self, phase: String, code: u32, message: String);
+}
+
+/// Adapts a foreign `MetricsCallback` to the prover's `MetricsSink`, also
+/// feeding the aggregate behind `get_metrics`.
+struct ForeignMetrics(Arc<dyn MetricsCallback>);
+
+impl MetricsSink for ForeignMetrics {
+    fn counter(&self, name: &str, value: u64) {
+        aggregate().counter(name, value);
+        self.0.counter(name.to_string(), value);
+    }
+
+    fn histogram(&self, name: &str, value: f64) {
+        aggregate().histogram(name, value);
+        self.0.histogram(name.to_string(), value);
+    }
+
+    fn error(&self, phase: &str, error: &ProverError) {
+        aggregate().error(phase, error);
+        self.0
+            .error(phase.to_string(), error.code(), error.report());
+    }
+}
+
+fn aggregate() -> &'static Arc<MetricsAggregator> {
+    AGGREGATE.get_or_init(|| Arc::new(MetricsAggregator::new()))
+}
+
+fn metrics_sink() -> Arc<dyn MetricsSink> {
+    METRICS
+        .get()
+        .and_then(|sink| sink.read().ok().map(|sink| sink.clone()))
+        .unwrap_or_else(|| aggregate().clone())
+}
+
+/// Report the outcome of an exported function.
+fn record_call<T>(function: &str, duration_ms: f64, result: &Result<T, KimchiError>) {
+    let sink = metrics_sink();
+    sink.counter(&format!("kimchi.ffi.{}.count", function), 1);
+    sink.histogram(&format!("kimchi.ffi.{}.duration_ms", function), duration_ms);
+    if result.is_err() {
+        sink.counter(&format!("kimchi.ffi.{}.errors", function), 1);
+    }
+}
+
+/// Install a metrics callback. Replaces any previous one.
+#[uniffi::export]
+pub fn set_metrics_callback(callback: Arc<dyn MetricsCallback>) {
+    let sink: Arc<dyn MetricsSink> = Arc::new(ForeignMetrics(callback));
+    let lock = METRICS.get_or_init(|| RwLock::new(aggregate().clone()));
+    if let Ok(mut current) = lock.write() {
+        *current = sink.clone();
+    }
+    if let Some(session) = SESSION.get() {
+        if let Ok(mut session) = session.lock() {
+            session.prover_mut().set_metrics(sink.clone());
+        }
+    }
+    if let Some(verifier) = VERIFIER.get() {
+        if let Ok(mut verifier) = verifier.lock() {
+            verifier.set_metrics(sink);
+        }
+    }
+}
+
+/// Latency percentiles of the latest calls, in milliseconds.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct LatencyPercentiles {
+    /// Calls summarized: the latest 1024 at most.
+    pub samples: u64,
+    pub p50_ms: f64,
+    pub p90_ms: f64,
+    pub p99_ms: f64,
+}
+
+/// Prover health since the library was loaded or `reset_metrics`.
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ProverMetrics {
+    /// Proofs generated successfully.
+    pub proofs_generated: u64,
+    /// Proof generations that failed.
+    pub proof_failures: u64,
+    /// Verifications run, whether the proof was valid or not.
+    pub verifications_run: u64,
+    /// Verifications that failed with an error (not an invalid proof).
+    pub verification_failures: u64,
+    /// Failures by `ProverError` code.
+    pub failures_by_code: HashMap<u32, u64>,
+    /// Latencies by metric name, e.g. `kimchi.prove.duration_ms` or
+    /// `kimchi.ffi.verify_proof.duration_ms`.
+    pub latencies: HashMap<String, LatencyPercentiles>,
+    /// Every counter reported to the metrics callback, by name.
+    pub counters: HashMap<String, u64>,
+}
+
+/// Cumulative counters and recent latencies of the prover and verifier, as
+/// also reported to the metrics callback.
+#[uniffi::export]
+pub fn get_metrics() -> ProverMetrics {
+    let snapshot = aggregate().snapshot();
+    ProverMetrics {
+        proofs_generated: snapshot.successes(Phase::Prove),
+        proof_failures: snapshot.counter(&Phase::Prove.metric("errors")),
+        verifications_run: snapshot.counter(&Phase::Verify.metric("count")),
+        verification_failures: snapshot.counter(&Phase::Verify.metric("errors")),
+        failures_by_code: snapshot.errors_by_code.into_iter().collect(),
+        latencies: snapshot
+            .histograms
+            .into_iter()
+            .filter(|(name, _)| name.ends_with(".duration_ms"))
+            .map(|(name, p)| {
+                let latency = LatencyPercentiles {
+                    samples: p.samples as u64,
+                    p50_ms: p.p50,
+                    p90_ms: p.p90,
+                    p99_ms: p.p99,
+                };
+                (name, latency)
+            })
+            .collect(),
+        counters: snapshot.counters.into_iter().collect(),
+    }
+}
+
+/// Start `get_metrics` over from zero.
+#[uniffi::export]
+pub fn reset_metrics() {
+    aggregate().reset();
+}
+
+/// Start recording a timeline of prover phases (SRS creation, setup,
+/// witness generation, proving, verification).
+///
+/// The first call installs the recorder as the global `tracing` subscriber
+/// and fails if the app has already installed its own; in that case, add
+/// `ChromeTraceRecorder::layer` to the app's subscriber instead. Discards
+/// anything recorded before.
+#[cfg(feature = "tracing")]
+#[uniffi::export]
+pub fn start_trace() -> Result<(), KimchiError> {
+    let recorder = TRACE_RECORDER
+        .get_or_init(|| {
+            let recorder = ChromeTraceRecorder::new();
+            let subscriber = tracing_subscriber::registry().with(recorder.layer());
+            tracing::subscriber::set_global_default(subscriber)
+                .ok()
+                .map(|_| recorder)
+        })
+        .as_ref()
+        .ok_or_else(|| {
+            KimchiError::SetupError("Another tracing subscriber is already installed".into())
+        })?;
+    recorder.clear();
+    recorder.set_recording(true);
+    Ok(())
+}
+
+/// Stop recording and return the timeline since `start_trace` as Chrome
+/// trace JSON, for `chrome://tracing` or Perfetto.
+#[cfg(feature = "tracing")]
+#[uniffi::export]
+pub fn finish_trace() -> Result<String, KimchiError> {
+    let recorder = TRACE_RECORDER
+        .get()
+        .and_then(Option::as_ref)
+        .ok_or_else(|| {
+            KimchiError::SetupError("No trace started. Call start_trace() first.".into())
+        })?;
+    recorder.set_recording(false);
+    let json = recorder.to_json()?;
+    recorder.clear();
+    Ok(json)
 }
 
 /// Result of proof generation.
@@ -74,6 +551,12 @@ pub struct ProofResult {
     pub generation_time_ms: u64,
     /// Size of the proof in bytes.
     pub proof_size_bytes: u64,
+    /// Hex-encoded MessagePack `ProofEnvelope` bundling the proof, public
+    /// inputs, circuit id and verifier index digest.
+    pub envelope: String,
+    /// Hex-encoded MessagePack `SignedEnvelope`: `envelope` signed with the
+    /// key set with `set_envelope_signing_key`, if any.
+    pub signed_envelope: Option<String>,
 }
 
 /// Get the next proof ID.
@@ -85,48 +568,196 @@ fn get_next_proof_id() -> u64 {
 }
 
 /// Store a proof and return its handle.
-fn store_proof(proof: StoredProof) -> u64 {
-    let store = PROOF_STORE.get_or_init(|| RwLock::new(HashMap::new()));
+///
+/// Proofs with the same `verifier_index_digest`, the
+/// `ProofEnvelope::verifier_index_digest` of their verifier index, share one
+/// copy of the index.
+fn store_proof(
+    proof: ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
+    proof_size_bytes: usize,
+    verifier_index: VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    verifier_index_digest: [u8; 32],
+    public_inputs: StoredInputs,
+) -> Result<u64, ProverError> {
+    let store = PROOF_STORE.get_or_init(|| RwLock::new(ProofStore::default()));
+    let mut guard = store.write().unwrap();
     let id = get_next_proof_id();
-    store.write().unwrap().insert(id, proof);
-    id
+    guard.insert(
+        id,
+        proof,
+        proof_size_bytes,
+        verifier_index,
+        verifier_index_digest,
+        public_inputs,
+    )?;
+    guard.report();
+    Ok(id)
+}
+
+/// Lock the proving session created by `init_prover`.
+fn lock_session() -> Result<std::sync::MutexGuard<'static, ProvingSession>, KimchiError> {
+    SESSION
+        .get()
+        .ok_or_else(|| KimchiError::SetupError("Prover not initialized".into()))?
+        .lock()
+        .map_err(|e| KimchiError::SetupError(format!("Failed to lock prover: {}", e)))
 }
 
 fn get_stored_proof(
     id: u64,
-) -> Option<std::sync::RwLockReadGuard<'static, HashMap<u64, StoredProof>>> {
-    let store = PROOF_STORE.get()?;
-    let guard = store.read().ok()?;
-    if guard.contains_key(&id) {
-        Some(guard)
+) -> Result<std::sync::RwLockReadGuard<'static, ProofStore>, KimchiError> {
+    let not_found = || KimchiError::ProofNotFound(format!("No proof with handle {}", id));
+    let store = PROOF_STORE.get().ok_or_else(not_found)?;
+
+    let mut guard = store.write().map_err(|_| not_found())?;
+    if guard.sweep(Instant::now()) > 0 {
+        guard.report();
+    }
+    if guard.expired.contains(&id) {
+        return Err(KimchiError::ProofExpired(format!(
+            "Proof with handle {} expired",
+            id
+        )));
+    }
+    drop(guard);
+
+    let guard = store.read().map_err(|_| not_found())?;
+    if guard.proofs.contains_key(&id) {
+        Ok(guard)
     } else {
-        None
+        Err(not_found())
     }
 }
 
+/// Transport for `SrsSource::Download`, implemented by the host app, e.g.
+/// on its HTTP client restricted to Wi-Fi.
+///
+/// Called on the thread initializing the SRS; it may block.
+#[uniffi::export(with_foreign)]
+pub trait SrsFetcher: Send + Sync {
+    /// The body at `url`, or `None` if it cannot be fetched right now.
+    fn fetch(&self, url: String) -> Option<Vec<u8>>;
+}
+
+/// Adapts a foreign `SrsFetcher` to the prover's `Fetch`.
+struct ForeignFetcher(Arc<dyn SrsFetcher>);
+
+impl srs_provider::Fetch for ForeignFetcher {
+    fn fetch(&self, url: &str) -> kimchi_prover::Result<Vec<u8>> {
+        self.0
+            .fetch(url.to_string())
+            .ok_or_else(|| ProverError::SetupError(format!("SRS not available from {}", url)))
+    }
+}
+
+/// Where `init_prover` gets the SRS from when it is not cached.
+#[derive(Debug, Clone, uniffi::Enum)]
+pub enum SrsSource {
+    /// Create it on the device. The default.
+    Generate,
+    /// Load `srs-{n}.bin` from a directory of bundled assets.
+    Bundled { dir: String },
+    /// Download from `url_template`, with `{log2}` standing for log2 of the
+    /// size, keeping assets in `dir`. Only sizes in `pins`, mapped to the
+    /// SHA-256 digest of their asset, are accepted; updating a pin fetches
+    /// the new asset and removes the old one.
+    Download {
+        url_template: String,
+        dir: String,
+        pins: HashMap<u32, Bytes32>,
+    },
+}
+
+/// Select where the SRS comes from. Call before `init_prover`; `fetcher`
+/// is required for `SrsSource::Download`.
+#[uniffi::export]
+pub fn set_srs_source(
+    source: SrsSource,
+    fetcher: Option<Arc<dyn SrsFetcher>>,
+) -> Result<(), KimchiError> {
+    if SESSION.get().is_some() {
+        return Err(KimchiError::InvalidInput(
+            "set_srs_source must be called before init_prover".into(),
+        ));
+    }
+    let provider: Arc<dyn SrsProvider> = match source {
+        SrsSource::Generate => Arc::new(srs_provider::GenerateSrs),
+        SrsSource::Bundled { dir } => Arc::new(srs_provider::BundledSrs::new(dir)),
+        SrsSource::Download {
+            url_template,
+            dir,
+            pins,
+        } => {
+            let fetcher = fetcher.ok_or_else(|| {
+                KimchiError::InvalidInput("downloading the SRS needs a fetcher".into())
+            })?;
+            let fetch = Arc::new(ForeignFetcher(fetcher));
+            Arc::new(pins.into_iter().fold(
+                srs_provider::DownloadSrs::new(url_template, dir, fetch),
+                |download, (log2, digest)| download.with_pin(log2 as usize, digest),
+            ))
+        }
+    };
+    let mut current = SRS_SOURCE
+        .write()
+        .map_err(|e| KimchiError::SetupError(format!("Failed to lock SRS source: {}", e)))?;
+    *current = Some(provider);
+    Ok(())
+}
+
 /// Initialize the prover. Call this once at app startup.
 ///
 /// # Arguments
 /// * `srs_log2_size` - Log2 of the SRS size. Larger values support bigger circuits
 ///   but use more memory. Default is 14 (16384 rows). Use 10-12 for testing.
+/// * `proof_ttl_secs` - Lifetime of stored proofs. Proofs older than this are
+///   freed automatically, and their handles fail with `ProofExpired`. `None`
+///   keeps proofs until `free_proof`. Unlike the SRS size, this is updated on
+///   every call.
+/// * `cache_dir` - Directory to cache the SRS and compiled circuits in, so
+///   later launches skip creating them. Processes sharing the directory,
+///   such as an app and its extensions in an app group container, share the
+///   cache safely. `None` keeps them in memory only.
+///
+/// The SRS is created on the device unless `set_srs_source` was called
+/// first.
 #[uniffi::export]
-pub fn init_prover(srs_log2_size: Option<u32>) -> Result<(), KimchiError> {
+pub fn init_prover(
+    srs_log2_size: Option<u32>,
+    proof_ttl_secs: Option<u64>,
+    cache_dir: Option<String>,
+) -> Result<(), KimchiError> {
     let _ = INITIALIZED.get_or_init(|| {
         log::info!("Kimchi mobile prover initialized");
         true
     });
 
     // Initialize the prover with configuration
-    let _ = PROVER.get_or_init(|| {
+    let _ = SESSION.get_or_init(|| {
         let config = ProverConfig {
             srs_log2_size: srs_log2_size.unwrap_or(14) as usize,
             debug: false,
+            cache_dir: cache_dir.map(Into::into),
+            ..Default::default()
+        };
+        let config = match SRS_SOURCE.read().ok().and_then(|source| source.clone()) {
+            Some(srs_provider) => ProverConfig {
+                srs_provider,
+                ..config
+            },
+            None => config,
         };
-        Mutex::new(KimchiProver::with_config(config))
+        let mut session = ProvingSession::new(config);
+        session.prover_mut().set_metrics(metrics_sink());
+        Mutex::new(session)
     });
 
     // Initialize storage
-    let _ = PROOF_STORE.get_or_init(|| RwLock::new(HashMap::new()));
+    let store = PROOF_STORE.get_or_init(|| RwLock::new(ProofStore::default()));
+    let mut guard = store
+        .write()
+        .map_err(|e| KimchiError::SetupError(format!("Failed to lock store: {}", e)))?;
+    guard.ttl = proof_ttl_secs.map(Duration::from_secs);
 
     Ok(())
 }
@@ -138,8 +769,27 @@ pub fn init_prover(srs_log2_size: Option<u32>) -> Result<(), KimchiError> {
 ///
 /// # Returns
 /// `true` if the proof is valid, `false` otherwise.
-#[uniffi::export]
+///
+/// This blocks until verification is done; Kotlin and Swift get the async
+/// `verify_proof_async` under this name.
 pub fn verify_proof(proof_handle: u64) -> Result<bool, KimchiError> {
+    let start_time = std::time::Instant::now();
+    let result = verify_proof_inner(proof_handle);
+    record_call(
+        "verify_proof",
+        start_time.elapsed().as_secs_f64() * 1000.0,
+        &result,
+    );
+    result
+}
+
+/// `verify_proof` on the worker thread, exported as `verifyProof`.
+#[uniffi::export(name = "verify_proof")]
+pub async fn verify_proof_async(proof_handle: u64) -> Result<bool, KimchiError> {
+    runtime::spawn(move || verify_proof(proof_handle)).await
+}
+
+fn verify_proof_inner(proof_handle: u64) -> Result<bool, KimchiError> {
     if INITIALIZED.get().is_none() {
         return Err(KimchiError::SetupError(
             "Prover not initialized. Call init_prover() first.".into(),
@@ -147,27 +797,19 @@ pub fn verify_proof(proof_handle: u64) -> Result<bool, KimchiError> {
     }
 
     // Get the stored proof
-    let store_guard = get_stored_proof(proof_handle).ok_or_else(|| {
-        KimchiError::ProofNotFound(format!("No proof with handle {}", proof_handle))
-    })?;
+    let store_guard = get_stored_proof(proof_handle)?;
 
-    let stored = store_guard.get(&proof_handle).ok_or_else(|| {
+    let stored = store_guard.proofs.get(&proof_handle).ok_or_else(|| {
         KimchiError::ProofNotFound(format!("No proof with handle {}", proof_handle))
     })?;
 
-    // Get prover to verify
-    let prover_mutex = PROVER
-        .get()
-        .ok_or_else(|| KimchiError::SetupError("Prover not initialized".into()))?;
-
-    let prover = prover_mutex
-        .lock()
-        .map_err(|e| KimchiError::SetupError(format!("Failed to lock prover: {}", e)))?;
-
-    // Verify
-    prover
-        .verify(&stored.verifier_index, &stored.proof, &stored.public_inputs)
-        .map_err(|e| KimchiError::VerificationError(e.to_string()))
+    // Verify with the stored verifier index
+    let session = lock_session()?;
+    Ok(session.prover().verify(
+        &stored.verifier_index,
+        &stored.proof,
+        &stored.public_inputs.values,
+    )?)
 }
 
 /// Free a proof from memory.
@@ -183,10 +825,205 @@ pub fn free_proof(proof_handle: u64) -> Result<(), KimchiError> {
         .write()
         .map_err(|e| KimchiError::SetupError(format!("Failed to lock store: {}", e)))?;
 
-    guard.remove(&proof_handle);
+    guard.remove(proof_handle);
+    guard.report();
     Ok(())
 }
 
+/// Cap the memory used by stored proofs.
+///
+/// Once stored proofs and their verifier indices would exceed `limit_bytes`,
+/// proving fails with a `Prover` error with code 800 (resource exhausted)
+/// instead of the store growing further; free proofs to make room. `None`
+/// removes the cap. Proofs already stored are kept even if they exceed a
+/// new, lower limit.
+#[uniffi::export]
+pub fn set_proof_store_limit(limit_bytes: Option<u64>) {
+    let store = PROOF_STORE.get_or_init(|| RwLock::new(ProofStore::default()));
+    if let Ok(mut guard) = store.write() {
+        guard.limit_bytes = limit_bytes.map(|limit| limit as usize);
+    }
+}
+
+/// Current size of the proof store.
+#[uniffi::export]
+pub fn get_proof_store_stats() -> ProofStoreStats {
+    PROOF_STORE
+        .get()
+        .and_then(|store| store.read().ok().map(|guard| guard.stats()))
+        .unwrap_or_else(|| ProofStore::default().stats())
+}
+
+/// Rough proving time of a circuit on a mid-range phone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum ProvingTime {
+    /// About a second or less.
+    Fast,
+    /// A few seconds.
+    Moderate,
+    /// Ten seconds or more.
+    Slow,
+}
+
+impl From<ProvingTimeClass> for ProvingTime {
+    fn from(class: ProvingTimeClass) -> Self {
+        match class {
+            ProvingTimeClass::Fast => Self::Fast,
+            ProvingTimeClass::Moderate => Self::Moderate,
+            ProvingTimeClass::Slow => Self::Slow,
+        }
+    }
+}
+
+/// Static description of a built-in circuit, from `get_circuit_info`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct CircuitInfo {
+    pub circuit_id: String,
+    /// Stable hash of the gates and wiring, as in proof envelopes.
+    pub circuit_hash: Bytes32,
+    /// Rows, i.e. gates.
+    pub rows: u64,
+    /// Number of public input elements.
+    pub num_public_inputs: u64,
+    /// JSON `{ fields: [{ name, type }] }` naming the public inputs in order.
+    pub public_input_schema: String,
+    /// Smallest `srs_log2_size` for `init_prover` that fits the circuit.
+    pub min_srs_log2_size: u32,
+    pub proving_time: ProvingTime,
+    /// Whether the circuit fits the initialized prover's SRS; `None` before
+    /// `init_prover`.
+    pub fits_srs: Option<bool>,
+}
+
+/// Describe a built-in circuit, e.g. to check the SRS size configured in
+/// `init_prover` before attempting a proof.
+///
+/// # Arguments
+/// * `circuit_id` - Circuit id as in proof envelopes, e.g. `"threshold"`
+#[uniffi::export]
+pub fn get_circuit_info(circuit_id: String) -> Result<CircuitInfo, KimchiError> {
+    let info = CircuitDescription::for_id(&circuit_id)?;
+    let fits_srs = match SESSION.get() {
+        Some(_) => Some(info.fits(lock_session()?.prover().config().srs_log2_size)),
+        None => None,
+    };
+
+    Ok(CircuitInfo {
+        circuit_id: info.id.clone(),
+        circuit_hash: Bytes32::from(info.hash),
+        rows: info.rows as u64,
+        num_public_inputs: info.num_public_inputs() as u64,
+        public_input_schema: serde_json::to_string(&info.public_input_schema)
+            .map_err(ProverError::from)?,
+        min_srs_log2_size: info.min_srs_log2_size as u32,
+        proving_time: info.proving_time_class().into(),
+        fits_srs,
+    })
+}
+
+/// Dump a built-in circuit's gates, wiring and region labels as JSON, for
+/// review by auditors or alternate verifiers. The format is documented in
+/// `kimchi_prover::circuits::gates_json`.
+///
+/// # Arguments
+/// * `circuit_id` - Circuit id as in proof envelopes, e.g. `"threshold"`
+#[uniffi::export]
+pub fn export_circuit_gates(circuit_id: String) -> Result<String, KimchiError> {
+    // Fails for unknown ids
+    CircuitDescription::for_id(&circuit_id)?;
+    let circuit = registry::circuit(&circuit_id).expect("registered circuit");
+    Ok(circuit.export_gates_json()?)
+}
+
+/// Number of gates of one type, in `SetupInfo`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct GateCount {
+    /// Kimchi gate type, e.g. `"Generic"` or `"RangeCheck0"`.
+    pub gate_type: String,
+    pub count: u64,
+}
+
+/// Rows of one lookup table, in `SetupInfo`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct LookupTableSize {
+    pub table_id: i32,
+    pub rows: u64,
+}
+
+/// What setting up a circuit built, from `get_setup_info`.
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct SetupInfo {
+    pub circuit_id: String,
+    /// Evaluation domain size.
+    pub domain_size: u64,
+    /// Largest domain the initialized prover's SRS allows.
+    pub max_domain_size: u64,
+    /// Rows taken by gates.
+    pub rows: u64,
+    /// Rows the circuit needs: its gates, or its lookup tables if larger.
+    pub used_rows: u64,
+    /// Rows left before the domain doubles.
+    pub free_rows: u64,
+    /// Rows left before the circuit no longer fits the SRS.
+    pub rows_to_limit: u64,
+    pub num_public_inputs: u64,
+    /// Gates of each type, most frequent first.
+    pub gate_counts: Vec<GateCount>,
+    pub lookup_tables: Vec<LookupTableSize>,
+}
+
+/// Set up a built-in circuit, if not done already, and describe the
+/// constraint system: how close it is to the SRS ceiling and which gates
+/// take its rows.
+///
+/// # Arguments
+/// * `circuit_id` - Circuit id as in proof envelopes, e.g. `"threshold"`
+#[uniffi::export]
+pub fn get_setup_info(circuit_id: String) -> Result<SetupInfo, KimchiError> {
+    if INITIALIZED.get().is_none() {
+        return Err(KimchiError::SetupError(
+            "Prover not initialized. Call init_prover() first.".into(),
+        ));
+    }
+    // Fails for unknown ids
+    CircuitDescription::for_id(&circuit_id)?;
+
+    let mut session = lock_session()?;
+    if !session.is_registered(&circuit_id) {
+        if let Some(circuit) = registry::circuit(&circuit_id) {
+            session.register_boxed(circuit_id.clone(), circuit);
+        }
+    }
+    let info = session.setup_info(&circuit_id)?;
+
+    Ok(SetupInfo {
+        circuit_id,
+        domain_size: info.domain_size as u64,
+        max_domain_size: info.max_domain_size as u64,
+        rows: info.rows as u64,
+        used_rows: info.used_rows() as u64,
+        free_rows: info.free_rows() as u64,
+        rows_to_limit: info.rows_to_limit() as u64,
+        num_public_inputs: info.num_public_inputs as u64,
+        gate_counts: info
+            .gate_counts
+            .iter()
+            .map(|(typ, count)| GateCount {
+                gate_type: format!("{:?}", typ),
+                count: *count as u64,
+            })
+            .collect(),
+        lookup_tables: info
+            .lookup_tables
+            .iter()
+            .map(|&(table_id, rows)| LookupTableSize {
+                table_id,
+                rows: rows as u64,
+            })
+            .collect(),
+    })
+}
+
 /// Get the library version.
 #[uniffi::export]
 pub fn get_version() -> String {
@@ -195,9 +1032,10 @@ pub fn get_version() -> String {
 
 /// Serialize the verifier index for a stored proof.
 ///
-/// This returns the verifier index in a format suitable for the WASM verifier.
-/// The SRS is NOT included - the WASM verifier will regenerate it based on
-/// the srs_log2_size from get_srs_log2_size().
+/// This returns the verifier index in the format expected by the `kimchi-wasm`
+/// verifier (`verify_kimchi_proof()`, `verify_proof_envelope()`). The SRS is
+/// NOT included - the WASM verifier regenerates it from the srs_log2_size
+/// returned by get_srs_log2_size() or carried in the proof envelope.
 ///
 /// # Arguments
 /// * `proof_handle` - Handle to a stored proof
@@ -206,121 +1044,945 @@ pub fn get_version() -> String {
 /// Hex-encoded MessagePack serialized verifier index (without SRS)
 #[uniffi::export]
 pub fn export_verifier_index(proof_handle: u64) -> Result<String, KimchiError> {
-    let store_guard = get_stored_proof(proof_handle).ok_or_else(|| {
-        KimchiError::ProofNotFound(format!("No proof with handle {}", proof_handle))
-    })?;
+    let store_guard = get_stored_proof(proof_handle)?;
 
-    let stored = store_guard.get(&proof_handle).ok_or_else(|| {
+    let stored = store_guard.proofs.get(&proof_handle).ok_or_else(|| {
         KimchiError::ProofNotFound(format!("No proof with handle {}", proof_handle))
     })?;
 
-    let vi_bytes = rmp_serde::to_vec(&stored.verifier_index).map_err(|e| {
-        KimchiError::SerializationError(format!("Failed to serialize verifier index: {}", e))
-    })?;
+    let mut vi_hex = String::new();
+    rmp_serde::encode::write(&mut HexWriter::new(&mut vi_hex), &*stored.verifier_index)
+        .map_err(|e| ProverError::encode("verifier index", e))?;
 
-    Ok(hex::encode(vi_bytes))
+    Ok(vi_hex)
 }
 
-/// Get the SRS log2 size used by the prover.
-///
-/// Pass this value to the WASM verifier's init_verifier() to ensure
-/// the same SRS is used for verification.
+/// Export a stored proof as raw MessagePack bytes.
 ///
-/// # Returns
-/// The log2 of the SRS size (e.g., 14 means 2^14 = 16384 rows)
+/// The same bytes as `ProofResult::proof_bytes` without the hex encoding,
+/// for callers that write proofs to files or sockets and would otherwise
+/// decode the hex string again.
 #[uniffi::export]
-pub fn get_srs_log2_size() -> Result<u32, KimchiError> {
-    let prover_mutex = PROVER
-        .get()
-        .ok_or_else(|| KimchiError::SetupError("Prover not initialized".into()))?;
+pub fn export_proof(proof_handle: u64) -> Result<Vec<u8>, KimchiError> {
+    let store_guard = get_stored_proof(proof_handle)?;
 
-    let prover = prover_mutex
-        .lock()
-        .map_err(|e| KimchiError::SetupError(format!("Failed to lock prover: {}", e)))?;
+    let stored = store_guard.proofs.get(&proof_handle).ok_or_else(|| {
+        KimchiError::ProofNotFound(format!("No proof with handle {}", proof_handle))
+    })?;
 
-    Ok(prover.config().srs_log2_size as u32)
+    Ok(rmp_serde::to_vec(&stored.proof).map_err(|e| ProverError::encode("proof", e))?)
 }
 
-/// Generate a proof that a private value is less than a public threshold.
-///
-/// This proves: "I know a secret value V such that V < threshold"
-/// without revealing what V actually is.
+/// Export the verifier index for a stored proof as an o1js verification key.
 ///
 /// # Arguments
-/// * `value` - The private value (will not be revealed)
-/// * `threshold` - The public threshold to compare against
+/// * `proof_handle` - Handle to a stored proof
 ///
 /// # Returns
-/// A ProofResult containing the proof handle and serialized proof data.
-///
-/// # Example
-/// ```ignore
-/// // Prove that my secret number (50) is less than 100
-/// let result = prove_threshold(50, 100)?;
-/// assert!(result.is_valid); // true because 50 < 100
-/// ```
+/// JSON `{ data, hash }`, where `hash` is the key's Poseidon hash as a
+/// decimal string
 #[uniffi::export]
-pub fn prove_threshold(value: u64, threshold: u64) -> Result<ProofResult, KimchiError> {
-    if INITIALIZED.get().is_none() {
-        return Err(KimchiError::SetupError(
-            "Prover not initialized. Call init_prover() first.".into(),
-        ));
-    }
-
-    let start_time = std::time::Instant::now();
-
-    // Get the prover
-    let prover_mutex = PROVER
-        .get()
-        .ok_or_else(|| KimchiError::SetupError("Prover not initialized".into()))?;
+pub fn export_mina_verification_key(proof_handle: u64) -> Result<String, KimchiError> {
+    let store_guard = get_stored_proof(proof_handle)?;
 
-    let mut prover = prover_mutex
-        .lock()
-        .map_err(|e| KimchiError::SetupError(format!("Failed to lock prover: {}", e)))?;
+    let stored = store_guard.proofs.get(&proof_handle).ok_or_else(|| {
+        KimchiError::ProofNotFound(format!("No proof with handle {}", proof_handle))
+    })?;
 
-    // Create the threshold circuit
-    let circuit = ThresholdCircuit::new(threshold);
+    let vk = MinaVerificationKeyJson::from_verifier_index(&stored.verifier_index)?;
+    Ok(vk.to_json()?)
+}
 
-    // Setup the circuit (creates prover and verifier indices)
-    let (prover_index, verifier_index) = prover
-        .setup(circuit.gates(), circuit.num_public_inputs())
-        .map_err(|e| KimchiError::SetupError(format!("Circuit setup failed: {}", e)))?;
+/// Export a stored proof as the proof-dependent fields of a Mina zkApp
+/// account update (`appState`, `authorizationKind`, `authorization.proof`).
+///
+/// # Arguments
+/// * `proof_handle` - Handle to a stored proof
+///
+/// # Returns
+/// JSON in the o1js `AccountUpdate` shape, for merging into the account
+/// update submitted to Mina. The proof is not Pickles-wrapped; see the
+/// `zkapp` module of kimchi-prover.
+#[uniffi::export]
+pub fn export_zkapp_submission(proof_handle: u64) -> Result<String, KimchiError> {
+    let store_guard = get_stored_proof(proof_handle)?;
 
-    // Generate witness
-    let (witness, public_inputs) = circuit
-        .generate_witness(value)
-        .map_err(|e| KimchiError::ProvingError(format!("Witness generation failed: {}", e)))?;
+    let stored = store_guard.proofs.get(&proof_handle).ok_or_else(|| {
+        KimchiError::ProofNotFound(format!("No proof with handle {}", proof_handle))
+    })?;
 
-    // Generate proof
-    let proof = prover
-        .prove(&prover_index, witness)
-        .map_err(|e| KimchiError::ProvingError(format!("Proof generation failed: {}", e)))?;
+    let submission = ZkappSubmission::from_proof(
+        &stored.proof,
+        &stored.public_inputs.values,
+        &stored.verifier_index,
+    )?;
+    Ok(submission.to_json()?)
+}
 
-    let generation_time_ms = start_time.elapsed().as_millis() as u64;
+/// Export the public inputs of a stored proof by name, decoded by the
+/// circuit's public-input schema.
+///
+/// # Arguments
+/// * `proof_handle` - Handle to a stored proof
+///
+/// # Returns
+/// JSON `{ circuit_id, inputs: [{ name, type, value }] }` with the inputs in
+/// circuit order, e.g. `{ "name": "is_valid", "type": "bool", "value": true }`.
+/// Field elements are hex strings and bytes are arrays of numbers.
+#[uniffi::export]
+pub fn export_public_inputs(proof_handle: u64) -> Result<String, KimchiError> {
+    let store_guard = get_stored_proof(proof_handle)?;
 
-    // Serialize proof for transmission
-    let proof_bytes = rmp_serde::to_vec(&proof).map_err(|e| {
-        KimchiError::SerializationError(format!("Failed to serialize proof: {}", e))
+    let stored = store_guard.proofs.get(&proof_handle).ok_or_else(|| {
+        KimchiError::ProofNotFound(format!("No proof with handle {}", proof_handle))
     })?;
-    let proof_size_bytes = proof_bytes.len() as u64;
-    let proof_hex = hex::encode(&proof_bytes);
+
+    let inputs = &stored.public_inputs;
+    let document = PublicInputsDocument::decode(inputs.circuit_id, &inputs.schema, &inputs.values)?;
+    Ok(document.to_json()?)
+}
+
+/// Get the SRS log2 size used by the prover.
+///
+/// Pass this value to `kimchi-wasm`'s init_verifier() to ensure
+/// the same SRS is used for verification.
+///
+/// # Returns
+/// The log2 of the SRS size (e.g., 14 means 2^14 = 16384 rows)
+#[uniffi::export]
+pub fn get_srs_log2_size() -> Result<u32, KimchiError> {
+    Ok(lock_session()?.prover().config().srs_log2_size as u32)
+}
+
+/// Initialize verify-only mode, for apps that check proofs made elsewhere
+/// and never generate their own.
+///
+/// Holds only the SRS and the verifier indices added with
+/// `add_verifier_index`, so it needs a fraction of the prover's memory.
+/// It does not require `init_prover`; later calls are no-ops.
+///
+/// # Arguments
+/// * `srs_log2_size` - Log2 of the SRS size the proofs were made with.
+///   Default is 14, matching `init_prover`.
+#[uniffi::export]
+pub fn init_verifier(srs_log2_size: Option<u32>) -> Result<(), KimchiError> {
+    let verifier = VERIFIER.get_or_init(|| {
+        let mut verifier = KimchiVerifier::new(srs_log2_size.unwrap_or(14) as usize);
+        verifier.set_metrics(metrics_sink());
+        Mutex::new(verifier)
+    });
+    lock_verifier(verifier)?.init_srs()?;
+    Ok(())
+}
+
+fn verifier() -> Result<std::sync::MutexGuard<'static, KimchiVerifier>, KimchiError> {
+    let verifier = VERIFIER.get().ok_or_else(|| {
+        KimchiError::SetupError("Verifier not initialized. Call init_verifier() first.".into())
+    })?;
+    lock_verifier(verifier)
+}
+
+fn lock_verifier(
+    verifier: &Mutex<KimchiVerifier>,
+) -> Result<std::sync::MutexGuard<'_, KimchiVerifier>, KimchiError> {
+    verifier
+        .lock()
+        .map_err(|e| KimchiError::SetupError(format!("Failed to lock verifier: {}", e)))
+}
+
+/// Trust a verifier index, as returned by `export_verifier_index`.
+///
+/// # Returns
+/// Its digest, which envelopes made for it carry as `verifier_index_digest`.
+#[uniffi::export]
+pub fn add_verifier_index(verifier_index: String) -> Result<Bytes32, KimchiError> {
+    let bytes = hex::decode(&verifier_index)
+        .map_err(|e| KimchiError::InvalidInput(format!("Invalid verifier index hex: {}", e)))?;
+    Ok(Bytes32::from(verifier()?.add_verifier_index(&bytes)?))
+}
+
+/// Trust a verifier index like `add_verifier_index`, as the index of the
+/// built-in circuit `circuit_id` (e.g. `"timestamped/threshold_lt"`).
+///
+/// `verify_envelope_with_max_age` and `verify_envelope_with_attestation`
+/// need the index bound to its circuit this way, and refuse envelopes that
+/// name another circuit.
+///
+/// # Returns
+/// Its digest, which envelopes made for it carry as `verifier_index_digest`.
+#[uniffi::export]
+pub fn add_circuit_verifier_index(
+    circuit_id: String,
+    verifier_index: String,
+) -> Result<Bytes32, KimchiError> {
+    let bytes = hex::decode(&verifier_index)
+        .map_err(|e| KimchiError::InvalidInput(format!("Invalid verifier index hex: {}", e)))?;
+    Ok(Bytes32::from(
+        verifier()?.add_circuit_verifier_index(&circuit_id, &bytes)?,
+    ))
+}
+
+/// Stop trusting the verifier index with `digest`.
+///
+/// # Returns
+/// Whether it had been added.
+#[uniffi::export]
+pub fn remove_verifier_index(digest: Bytes32) -> Result<bool, KimchiError> {
+    Ok(verifier()?.remove_verifier_index(digest.as_bytes()))
+}
+
+/// Source of verifier indices implemented by the host app, e.g. fetching
+/// them from its backend, so envelopes can be verified without adding
+/// their index with `add_verifier_index` first.
+///
+/// Called on the worker thread during verification; it may block.
+#[uniffi::export(with_foreign)]
+pub trait VerifierIndexSource: Send + Sync {
+    /// The hex verifier index with `digest`, as returned by
+    /// `export_verifier_index`, or `None` if unknown.
+    fn fetch(&self, digest: Bytes32) -> Option<String>;
+}
+
+/// Adapts a foreign `VerifierIndexSource` to the registry's backend.
+struct ForeignIndexSource(Arc<dyn VerifierIndexSource>);
+
+impl VerifierIndexStore for ForeignIndexSource {
+    fn fetch(&self, digest: &[u8; 32]) -> kimchi_prover::Result<Option<Vec<u8>>> {
+        self.0
+            .fetch(Bytes32::from(*digest))
+            .map(|index| hex::decode(index).map_err(|e| ProverError::decode("verifier index", e)))
+            .transpose()
+    }
+}
+
+/// Load verifier indices that were not added with `add_verifier_index`
+/// from `source`, checking that each hashes to the digest the envelope
+/// names. Requires `init_verifier`; replaces any previous source.
+#[uniffi::export]
+pub fn set_verifier_index_source(source: Arc<dyn VerifierIndexSource>) -> Result<(), KimchiError> {
+    let registry = VerifierIndexRegistry::new().with_backend(Arc::new(ForeignIndexSource(source)));
+    verifier()?.set_registry(Arc::new(registry));
+    Ok(())
+}
+
+/// Verify a hex envelope against the verifier index it names, which must
+/// have been added with `add_verifier_index` or be available from the
+/// `VerifierIndexSource`.
+///
+/// This blocks until verification is done; Kotlin and Swift get the async
+/// `verify_envelope_async` under this name.
+pub fn verify_envelope(envelope: String) -> Result<bool, KimchiError> {
+    let start_time = std::time::Instant::now();
+    let result = verify_envelope_inner(&envelope);
+    record_call(
+        "verify_envelope",
+        start_time.elapsed().as_secs_f64() * 1000.0,
+        &result,
+    );
+    result
+}
+
+/// `verify_envelope` on the worker thread, exported as `verifyEnvelope`.
+#[uniffi::export(name = "verify_envelope")]
+pub async fn verify_envelope_async(envelope: String) -> Result<bool, KimchiError> {
+    runtime::spawn(move || verify_envelope(envelope)).await
+}
+
+fn verify_envelope_inner(envelope: &str) -> Result<bool, KimchiError> {
+    let envelope = ProofEnvelope::from_hex(envelope)?;
+    let mut verifier = verifier()?;
+    verifier.resolve_verifier_index(&envelope)?;
+    Ok(verifier.verify_envelope(&envelope)?)
+}
+
+/// Verify a hex envelope like `verify_envelope`, refusing proofs whose
+/// timestamp public input is more than `max_age_secs` before `now_secs`
+/// (Unix seconds) with a `StaleProof` error (code 402).
+///
+/// The envelope's verifier index must have been added with
+/// `add_circuit_verifier_index`, or fails with code 401, for a circuit with
+/// a timestamp, such as those of `prove_timestamped_comparison`; others fail
+/// with code 500.
+///
+/// This blocks until verification is done; Kotlin and Swift get the async
+/// `verify_envelope_with_max_age_async` under this name.
+pub fn verify_envelope_with_max_age(
+    envelope: String,
+    now_secs: u64,
+    max_age_secs: u64,
+) -> Result<bool, KimchiError> {
+    let start_time = std::time::Instant::now();
+    let result = verify_envelope_with_max_age_inner(&envelope, now_secs, max_age_secs);
+    record_call(
+        "verify_envelope_with_max_age",
+        start_time.elapsed().as_secs_f64() * 1000.0,
+        &result,
+    );
+    result
+}
+
+/// `verify_envelope_with_max_age` on the worker thread, exported as
+/// `verifyEnvelopeWithMaxAge`.
+#[uniffi::export(name = "verify_envelope_with_max_age")]
+pub async fn verify_envelope_with_max_age_async(
+    envelope: String,
+    now_secs: u64,
+    max_age_secs: u64,
+) -> Result<bool, KimchiError> {
+    runtime::spawn(move || verify_envelope_with_max_age(envelope, now_secs, max_age_secs)).await
+}
+
+fn verify_envelope_with_max_age_inner(
+    envelope: &str,
+    now_secs: u64,
+    max_age_secs: u64,
+) -> Result<bool, KimchiError> {
+    let envelope = ProofEnvelope::from_hex(envelope)?;
+    let mut verifier = verifier()?;
+    verifier.resolve_verifier_index(&envelope)?;
+    Ok(verifier.verify_with_max_age(&envelope, now_secs, max_age_secs)?)
+}
+
+/// Platform that issued a device attestation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum AttestationPlatform {
+    /// Android Play Integrity API.
+    PlayIntegrity,
+    /// iOS App Attest.
+    AppAttest,
+}
+
+impl From<AttestationPlatform> for Platform {
+    fn from(platform: AttestationPlatform) -> Self {
+        match platform {
+            AttestationPlatform::PlayIntegrity => Self::PlayIntegrity,
+            AttestationPlatform::AppAttest => Self::AppAttest,
+        }
+    }
+}
+
+/// Verify a hex envelope of an attested circuit like `verify_envelope`,
+/// refusing proofs not bound to the attestation whose artifact hashes to
+/// `token_hash` (SHA-256) with an `AttestationMismatch` error (code 403).
+/// Check the artifact itself with Google or Apple first.
+///
+/// The envelope's verifier index must have been added with
+/// `add_circuit_verifier_index`, or fails with code 401.
+///
+/// This blocks until verification is done; Kotlin and Swift get the async
+/// `verify_envelope_with_attestation_async` under this name.
+pub fn verify_envelope_with_attestation(
+    envelope: String,
+    platform: AttestationPlatform,
+    token_hash: Bytes32,
+) -> Result<bool, KimchiError> {
+    let start_time = std::time::Instant::now();
+    let result = verify_envelope_with_attestation_inner(&envelope, platform, &token_hash);
+    record_call(
+        "verify_envelope_with_attestation",
+        start_time.elapsed().as_secs_f64() * 1000.0,
+        &result,
+    );
+    result
+}
+
+/// `verify_envelope_with_attestation` on the worker thread, exported as
+/// `verifyEnvelopeWithAttestation`.
+#[uniffi::export(name = "verify_envelope_with_attestation")]
+pub async fn verify_envelope_with_attestation_async(
+    envelope: String,
+    platform: AttestationPlatform,
+    token_hash: Bytes32,
+) -> Result<bool, KimchiError> {
+    runtime::spawn(move || verify_envelope_with_attestation(envelope, platform, token_hash)).await
+}
+
+fn verify_envelope_with_attestation_inner(
+    envelope: &str,
+    platform: AttestationPlatform,
+    token_hash: &Bytes32,
+) -> Result<bool, KimchiError> {
+    let envelope = ProofEnvelope::from_hex(envelope)?;
+    let attestation = DeviceAttestation::new(platform.into(), *token_hash.as_bytes());
+    let mut verifier = verifier()?;
+    verifier.resolve_verifier_index(&envelope)?;
+    Ok(verifier.verify_with_attestation(&envelope, &attestation)?)
+}
+
+/// Sign the envelopes of proofs generated from now on with a device-held
+/// Pallas key, given as `mina-signer` hex, or stop signing with `None`.
+///
+/// Returns the key's Mina address, which relying parties check signed
+/// envelopes against.
+#[uniffi::export]
+pub fn set_envelope_signing_key(
+    secret_key_hex: Option<String>,
+) -> Result<Option<String>, KimchiError> {
+    let signer = secret_key_hex
+        .as_deref()
+        .map(EnvelopeSigner::from_secret_hex)
+        .transpose()?;
+    let address = signer.as_ref().map(EnvelopeSigner::address);
+    *ENVELOPE_SIGNER.write().unwrap() = signer;
+    Ok(address)
+}
+
+/// Check the signature of a hex `SignedEnvelope` (as in
+/// `ProofResult::signed_envelope`), and that it is by `expected_signer` if
+/// given, without verifying the proof. Fails with `SignatureInvalid`
+/// (code 404) otherwise.
+///
+/// Returns the hex envelope, for `verify_envelope`.
+#[uniffi::export]
+pub fn check_envelope_signature(
+    signed_envelope: String,
+    expected_signer: Option<String>,
+) -> Result<String, KimchiError> {
+    let signed = SignedEnvelope::from_hex(&signed_envelope)?;
+    signed.check_signature(expected_signer.as_deref())?;
+    Ok(signed.envelope.to_hex()?)
+}
+
+/// Verify a hex `SignedEnvelope` like `verify_envelope`, after checking its
+/// signature as `check_envelope_signature` does.
+///
+/// This blocks until verification is done; Kotlin and Swift get the async
+/// `verify_signed_envelope_async` under this name.
+pub fn verify_signed_envelope(
+    signed_envelope: String,
+    expected_signer: Option<String>,
+) -> Result<bool, KimchiError> {
+    let start_time = std::time::Instant::now();
+    let result = verify_signed_envelope_inner(&signed_envelope, expected_signer.as_deref());
+    record_call(
+        "verify_signed_envelope",
+        start_time.elapsed().as_secs_f64() * 1000.0,
+        &result,
+    );
+    result
+}
+
+/// `verify_signed_envelope` on the worker thread, exported as
+/// `verifySignedEnvelope`.
+#[uniffi::export(name = "verify_signed_envelope")]
+pub async fn verify_signed_envelope_async(
+    signed_envelope: String,
+    expected_signer: Option<String>,
+) -> Result<bool, KimchiError> {
+    runtime::spawn(move || verify_signed_envelope(signed_envelope, expected_signer)).await
+}
+
+fn verify_signed_envelope_inner(
+    signed_envelope: &str,
+    expected_signer: Option<&str>,
+) -> Result<bool, KimchiError> {
+    let signed = SignedEnvelope::from_hex(signed_envelope)?;
+    // Before the index is resolved, which may fetch it
+    signed.check_signature(expected_signer)?;
+    let mut verifier = verifier()?;
+    verifier.resolve_verifier_index(&signed.envelope)?;
+    Ok(verifier.verify_envelope(&signed.envelope)?)
+}
+
+/// A `COSE_Sign1`-wrapped envelope opened with `open_cose_envelope`.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct CoseEnvelope {
+    /// Hex-encoded MessagePack `ProofEnvelope`, as in `ProofResult::envelope`.
+    pub envelope: String,
+    /// COSE algorithm identifier from the protected header.
+    pub alg: i32,
+    /// Bytes the signature must verify over.
+    pub to_be_signed: Vec<u8>,
+    /// Signature as carried in the structure.
+    pub signature: Vec<u8>,
+}
+
+/// Convert a hex MessagePack envelope to the compact CBOR transport
+/// encoding, e.g. for a QR code or NFC payload.
+#[uniffi::export]
+pub fn envelope_to_cbor(envelope: String) -> Result<Vec<u8>, KimchiError> {
+    Ok(ProofEnvelope::from_hex(&envelope)?.to_cbor()?)
+}
+
+/// Convert a CBOR envelope back to hex MessagePack.
+#[uniffi::export]
+pub fn envelope_from_cbor(cbor: Vec<u8>) -> Result<String, KimchiError> {
+    Ok(ProofEnvelope::from_cbor(&cbor)?.to_hex()?)
+}
+
+/// Bytes to sign, with the platform key store, before calling
+/// `envelope_to_cose`.
+///
+/// # Arguments
+/// * `envelope` - Hex-encoded MessagePack envelope
+/// * `alg` - COSE algorithm identifier, e.g. -7 for ES256
+#[uniffi::export]
+pub fn envelope_cose_to_be_signed(envelope: String, alg: i32) -> Result<Vec<u8>, KimchiError> {
+    let envelope = ProofEnvelope::from_hex(&envelope)?;
+    Ok(CoseSign1::new(&envelope, alg)?.to_be_signed()?)
+}
+
+/// Wrap an envelope and its signature in a tagged `COSE_Sign1` structure.
+#[uniffi::export]
+pub fn envelope_to_cose(
+    envelope: String,
+    alg: i32,
+    signature: Vec<u8>,
+) -> Result<Vec<u8>, KimchiError> {
+    let envelope = ProofEnvelope::from_hex(&envelope)?;
+    let mut cose = CoseSign1::new(&envelope, alg)?;
+    cose.signature = signature;
+    Ok(cose.to_bytes()?)
+}
+
+/// Parse a `COSE_Sign1` envelope. The signature is not checked; verify it
+/// over `to_be_signed` before trusting the envelope.
+#[uniffi::export]
+pub fn open_cose_envelope(cose: Vec<u8>) -> Result<CoseEnvelope, KimchiError> {
+    let cose = CoseSign1::from_bytes(&cose)?;
+    Ok(CoseEnvelope {
+        envelope: cose.envelope()?.to_hex()?,
+        alg: cose.alg,
+        to_be_signed: cose.to_be_signed()?,
+        signature: cose.signature,
+    })
+}
+
+/// Generate a proof that a private value is less than a public threshold.
+///
+/// This proves: "I know a secret value V such that V < threshold"
+/// without revealing what V actually is.
+///
+/// # Arguments
+/// * `value` - The private value (will not be revealed)
+/// * `threshold` - The public threshold to compare against
+///
+/// # Returns
+/// A ProofResult containing the proof handle and serialized proof data.
+///
+/// # Example
+/// ```ignore
+/// // Prove that my secret number (50) is less than 100
+/// let result = prove_threshold(50, 100)?;
+/// assert!(result.is_valid); // true because 50 < 100
+/// ```
+///
+/// This blocks until the proof is done; Kotlin and Swift get the async
+/// `prove_threshold_async` under this name.
+pub fn prove_threshold(value: u64, threshold: u64) -> Result<ProofResult, KimchiError> {
+    let start_time = std::time::Instant::now();
+    let result = prove_threshold_inner(value, threshold);
+    record_call(
+        "prove_threshold",
+        start_time.elapsed().as_secs_f64() * 1000.0,
+        &result,
+    );
+    if let Ok(proof) = &result {
+        metrics_sink().histogram("kimchi.ffi.proof_size_bytes", proof.proof_size_bytes as f64);
+    }
+    result
+}
+
+/// `prove_threshold` on the worker thread, exported as a suspend function
+/// (Kotlin) or `async` function (Swift) named `proveThreshold`.
+#[uniffi::export(name = "prove_threshold")]
+pub async fn prove_threshold_async(value: u64, threshold: u64) -> Result<ProofResult, KimchiError> {
+    runtime::spawn(move || prove_threshold(value, threshold)).await
+}
+
+fn prove_threshold_inner(value: u64, threshold: u64) -> Result<ProofResult, KimchiError> {
+    let circuit = ThresholdCircuit::new(threshold);
+    // The threshold is a public input, so one registration serves them all
+    prove_circuit("threshold", &circuit, || circuit.generate_witness(value))
+}
+
+/// Comparison proved by `prove_comparison`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum ThresholdOperator {
+    /// value < threshold
+    Lt,
+    /// value <= threshold
+    Le,
+    /// value > threshold
+    Gt,
+    /// value >= threshold
+    Ge,
+    /// value == threshold
+    Eq,
+}
+
+impl From<ThresholdOperator> for Operator {
+    fn from(operator: ThresholdOperator) -> Self {
+        match operator {
+            ThresholdOperator::Lt => Self::Lt,
+            ThresholdOperator::Le => Self::Le,
+            ThresholdOperator::Gt => Self::Gt,
+            ThresholdOperator::Ge => Self::Ge,
+            ThresholdOperator::Eq => Self::Eq,
+        }
+    }
+}
+
+/// Generate a proof of whether a private value compares to a public
+/// threshold with `operator`, e.g. that an age is at least 18 with `Ge`.
+///
+/// The envelope's circuit id names the operator, e.g. `"threshold_ge"`, and
+/// its public inputs are the threshold and whether the comparison holds.
+///
+/// # Arguments
+/// * `value` - The private value (will not be revealed)
+/// * `threshold` - The public threshold to compare against
+/// * `operator` - The comparison, `value operator threshold`
+///
+/// This blocks until the proof is done; Kotlin and Swift get the async
+/// `prove_comparison_async` under this name.
+pub fn prove_comparison(
+    value: u64,
+    threshold: u64,
+    operator: ThresholdOperator,
+) -> Result<ProofResult, KimchiError> {
+    let start_time = std::time::Instant::now();
+    let result = prove_comparison_inner(value, threshold, operator);
+    record_call(
+        "prove_comparison",
+        start_time.elapsed().as_secs_f64() * 1000.0,
+        &result,
+    );
+    if let Ok(proof) = &result {
+        metrics_sink().histogram("kimchi.ffi.proof_size_bytes", proof.proof_size_bytes as f64);
+    }
+    result
+}
+
+/// `prove_comparison` on the worker thread, exported as a suspend function
+/// (Kotlin) or `async` function (Swift) named `proveComparison`.
+#[uniffi::export(name = "prove_comparison")]
+pub async fn prove_comparison_async(
+    value: u64,
+    threshold: u64,
+    operator: ThresholdOperator,
+) -> Result<ProofResult, KimchiError> {
+    runtime::spawn(move || prove_comparison(value, threshold, operator)).await
+}
+
+fn prove_comparison_inner(
+    value: u64,
+    threshold: u64,
+    operator: ThresholdOperator,
+) -> Result<ProofResult, KimchiError> {
+    let circuit = ThresholdCircuit::with_operator(threshold, operator.into());
+    // As for prove_threshold, one registration per operator serves every threshold
+    prove_circuit(circuit.id(), &circuit, || circuit.generate_witness(value))
+}
+
+/// `prove_comparison` with the time of the proof, `timestamp_secs` (Unix
+/// seconds), as one more public input, for relying parties that check
+/// freshness with `verify_envelope_with_max_age`.
+///
+/// The envelope's circuit id is the operator circuit's with a
+/// `timestamped/` prefix, e.g. `"timestamped/threshold_ge"`.
+///
+/// This blocks until the proof is done; Kotlin and Swift get the async
+/// `prove_timestamped_comparison_async` under this name.
+pub fn prove_timestamped_comparison(
+    value: u64,
+    threshold: u64,
+    operator: ThresholdOperator,
+    timestamp_secs: u64,
+) -> Result<ProofResult, KimchiError> {
+    let start_time = std::time::Instant::now();
+    let result = prove_timestamped_comparison_inner(value, threshold, operator, timestamp_secs);
+    record_call(
+        "prove_timestamped_comparison",
+        start_time.elapsed().as_secs_f64() * 1000.0,
+        &result,
+    );
+    if let Ok(proof) = &result {
+        metrics_sink().histogram("kimchi.ffi.proof_size_bytes", proof.proof_size_bytes as f64);
+    }
+    result
+}
+
+/// `prove_timestamped_comparison` on the worker thread, exported as
+/// `proveTimestampedComparison`.
+#[uniffi::export(name = "prove_timestamped_comparison")]
+pub async fn prove_timestamped_comparison_async(
+    value: u64,
+    threshold: u64,
+    operator: ThresholdOperator,
+    timestamp_secs: u64,
+) -> Result<ProofResult, KimchiError> {
+    runtime::spawn(move || prove_timestamped_comparison(value, threshold, operator, timestamp_secs))
+        .await
+}
+
+fn prove_timestamped_comparison_inner(
+    value: u64,
+    threshold: u64,
+    operator: ThresholdOperator,
+    timestamp_secs: u64,
+) -> Result<ProofResult, KimchiError> {
+    let circuit =
+        TimestampedCircuit::new(ThresholdCircuit::with_operator(threshold, operator.into()));
+    prove_circuit(circuit.id(), &circuit, || {
+        let (witness, public_inputs) = circuit.inner().generate_witness(value)?;
+        circuit.bind_witness(witness, &public_inputs, timestamp_secs)
+    })
+}
+
+/// Values compared by `prove_wide_comparison`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum ValueWidth {
+    /// Integers below 2^64.
+    U64,
+    /// Integers below 2^128, e.g. token balances.
+    U128,
+    /// Any field element.
+    Field,
+}
+
+impl From<ValueWidth> for Width {
+    fn from(width: ValueWidth) -> Self {
+        match width {
+            ValueWidth::U64 => Self::U64,
+            ValueWidth::U128 => Self::U128,
+            ValueWidth::Field => Self::Field,
+        }
+    }
+}
+
+/// `prove_comparison` for values that don't fit a u64.
+///
+/// The envelope's circuit id names the width and operator, e.g.
+/// `"threshold_u128_ge"`; a 128-bit threshold is a field element public
+/// input.
+///
+/// # Arguments
+/// * `value_decimal` - The private value, in decimal (wiped once parsed)
+/// * `threshold_decimal` - The public threshold, in decimal
+/// * `operator` - The comparison, `value operator threshold`
+/// * `width` - The values compared; both must fit it
+///
+/// This blocks until the proof is done; Kotlin and Swift get the async
+/// `prove_wide_comparison_async` under this name.
+pub fn prove_wide_comparison(
+    value_decimal: String,
+    threshold_decimal: String,
+    operator: ThresholdOperator,
+    width: ValueWidth,
+) -> Result<ProofResult, KimchiError> {
+    let start_time = std::time::Instant::now();
+    let result = prove_wide_comparison_inner(value_decimal, threshold_decimal, operator, width);
+    record_call(
+        "prove_wide_comparison",
+        start_time.elapsed().as_secs_f64() * 1000.0,
+        &result,
+    );
+    if let Ok(proof) = &result {
+        metrics_sink().histogram("kimchi.ffi.proof_size_bytes", proof.proof_size_bytes as f64);
+    }
+    result
+}
+
+/// `prove_wide_comparison` on the worker thread, exported as
+/// `proveWideComparison`.
+#[uniffi::export(name = "prove_wide_comparison")]
+pub async fn prove_wide_comparison_async(
+    value_decimal: String,
+    threshold_decimal: String,
+    operator: ThresholdOperator,
+    width: ValueWidth,
+) -> Result<ProofResult, KimchiError> {
+    runtime::spawn(move || prove_wide_comparison(value_decimal, threshold_decimal, operator, width))
+        .await
+}
+
+fn prove_wide_comparison_inner(
+    mut value_decimal: String,
+    threshold_decimal: String,
+    operator: ThresholdOperator,
+    width: ValueWidth,
+) -> Result<ProofResult, KimchiError> {
+    let field = |name: &str, decimal: &str| {
+        FieldElement::from_decimal(decimal)
+            .map(|f| f.0)
+            .map_err(|e| KimchiError::InvalidInput(format!("invalid {}: {}", name, e)))
+    };
+    let value = field("value", &value_decimal);
+    value_decimal.zeroize();
+    let circuit = WideThresholdCircuit::new(
+        field("threshold", &threshold_decimal)?,
+        operator.into(),
+        width.into(),
+    )?;
+    let value = value?;
+    prove_circuit(circuit.id(), &circuit, || circuit.generate_witness(value))
+}
+
+/// One level of a Merkle path, from the leaf up.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct MerkleNode {
+    /// Sibling of the current node (hex-encoded field element).
+    pub sibling_hex: String,
+    /// Whether the sibling is the right child, i.e. the current node is on the left.
+    pub is_right: bool,
+}
+
+/// A leaf and its Merkle path, for `prove_merkle_membership`.
+///
+/// The hex strings are wiped once parsed.
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct MerklePathInput {
+    /// The leaf (hex-encoded field element).
+    pub leaf_hex: String,
+    /// One node per level, from the leaf up; the length is the tree depth.
+    pub nodes: Vec<MerkleNode>,
+}
+
+impl Zeroize for MerklePathInput {
+    fn zeroize(&mut self) {
+        self.leaf_hex.zeroize();
+        for node in self.nodes.iter_mut() {
+            node.sibling_hex.zeroize();
+            node.is_right.zeroize();
+        }
+    }
+}
+
+/// Generate a proof that a private leaf is in a Poseidon Merkle tree.
+///
+/// This proves: "I know a leaf and a path from it to this root" without
+/// revealing the leaf or its position. The root is the only public input;
+/// the verifier compares it against the root it trusts.
+///
+/// # Arguments
+/// * `path` - The leaf and its siblings from the leaf up, at most 32 levels
+///
+/// This blocks until the proof is done; Kotlin and Swift get the async
+/// `prove_merkle_membership_async` under this name.
+pub fn prove_merkle_membership(path: MerklePathInput) -> Result<ProofResult, KimchiError> {
+    let start_time = std::time::Instant::now();
+    let result = prove_merkle_membership_inner(path);
+    record_call(
+        "prove_merkle_membership",
+        start_time.elapsed().as_secs_f64() * 1000.0,
+        &result,
+    );
+    if let Ok(proof) = &result {
+        metrics_sink().histogram("kimchi.ffi.proof_size_bytes", proof.proof_size_bytes as f64);
+    }
+    result
+}
+
+/// `prove_merkle_membership` on the worker thread, exported as
+/// `proveMerkleMembership`.
+#[uniffi::export(name = "prove_merkle_membership")]
+pub async fn prove_merkle_membership_async(
+    path: MerklePathInput,
+) -> Result<ProofResult, KimchiError> {
+    runtime::spawn(move || prove_merkle_membership(path)).await
+}
+
+fn prove_merkle_membership_inner(mut path: MerklePathInput) -> Result<ProofResult, KimchiError> {
+    let field = |name: &str, hex: &str| {
+        FieldElement::from_hex(hex)
+            .map(|f| f.0)
+            .map_err(|e| KimchiError::InvalidInput(format!("invalid {}: {}", name, e)))
+    };
+    let parsed = field("leaf", &path.leaf_hex).and_then(|leaf| {
+        let steps = path
+            .nodes
+            .iter()
+            .map(|node| {
+                Ok(MerkleStep {
+                    sibling: field("sibling", &node.sibling_hex)?,
+                    is_right: node.is_right,
+                })
+            })
+            .collect::<Result<Vec<_>, KimchiError>>()?;
+        Ok((leaf, steps))
+    });
+    path.zeroize();
+    let (leaf, steps) = parsed?;
+    let (leaf, steps) = (Zeroizing::new(leaf), Zeroizing::new(steps));
+
+    let circuit = MerkleMembershipCircuit::new(steps.len())?;
+    let name = format!("merkle_membership/{}", circuit.depth);
+    prove_circuit(&name, &circuit, || circuit.generate_witness(*leaf, &steps))
+}
+
+/// Prove `circuit`, registered in the session as `name`, with the witness
+/// from `generate_witness` and store the proof.
+///
+/// `circuit` is registered on first use; circuits whose gates differ need
+/// different names.
+fn prove_circuit<C: Circuit + Clone + Send + Sync + 'static>(
+    name: &str,
+    circuit: &C,
+    generate_witness: impl FnOnce() -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>), ProverError>,
+) -> Result<ProofResult, KimchiError> {
+    if INITIALIZED.get().is_none() {
+        return Err(KimchiError::SetupError(
+            "Prover not initialized. Call init_prover() first.".into(),
+        ));
+    }
+
+    let start_time = std::time::Instant::now();
+
+    let mut session = lock_session()?;
+    if !session.is_registered(name) {
+        session.register(name, circuit.clone());
+    }
+
+    // Setup the circuit on first use (creates prover and verifier indices)
+    let indices = session.indices(name)?;
+    let (prover_index, verifier_index) = &*indices;
+
+    // Generate witness
+    let (witness, public_inputs) = {
+        let _span = profiling::span(Phase::Witness);
+        generate_witness()?
+    };
+
+    // Generate proof
+    let proof = session.prover().prove(prover_index, witness)?;
+
+    let generation_time_ms = start_time.elapsed().as_millis() as u64;
+
+    // The envelope holds the serialized proof; hex-encode that rather than
+    // serializing the proof a second time.
+    let envelope = ProofEnvelope::new(
+        circuit.id(),
+        1,
+        verifier_index_srs_log2_size(verifier_index) as u32,
+        &proof,
+        &public_inputs,
+        verifier_index,
+    )?
+    .with_circuit_hash(circuit.circuit_hash());
+    let proof_size_bytes = envelope.proof.len() as u64;
+    let proof_hex = hex::encode(&envelope.proof);
+    let verifier_index_digest = envelope.verifier_index_digest;
+    let signed_envelope = match &*ENVELOPE_SIGNER.read().unwrap() {
+        Some(signer) => Some(signer.sign(envelope.clone())?.to_hex()?),
+        None => None,
+    };
+    let envelope = envelope.to_hex()?;
 
     // Serialize public inputs
     let public_inputs_hex: Vec<String> = public_inputs
         .iter()
-        .map(|fp| {
-            let mut bytes = Vec::new();
-            fp.serialize_compressed(&mut bytes).unwrap();
-            hex::encode(bytes)
-        })
+        .map(|fp| FieldElement(*fp).to_hex())
         .collect();
 
     // Store proof for later verification
-    let proof_handle = store_proof(StoredProof {
+    let proof_handle = store_proof(
         proof,
-        verifier_index,
-        public_inputs,
-    });
+        proof_size_bytes as usize,
+        verifier_index.clone(),
+        verifier_index_digest,
+        StoredInputs::new(circuit, public_inputs),
+    )?;
 
     Ok(ProofResult {
         proof_handle,
@@ -328,17 +1990,511 @@ pub fn prove_threshold(value: u64, threshold: u64) -> Result<ProofResult, Kimchi
         public_inputs: public_inputs_hex,
         generation_time_ms,
         proof_size_bytes,
+        envelope,
+        signed_envelope,
     })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use kimchi_prover::KimchiProver;
 
     #[test]
     fn test_init() {
         // Use smaller SRS for faster tests
-        init_prover(Some(10)).expect("Failed to initialize");
+        init_prover(Some(10), None, None).expect("Failed to initialize");
+    }
+
+    #[test]
+    fn test_bytes_custom_types() {
+        let digest = Bytes32::from([0x5A; 32]);
+        let hex = <Bytes32 as UniffiCustomTypeConverter>::from_custom(digest);
+        assert_eq!(
+            <Bytes32 as UniffiCustomTypeConverter>::into_custom(hex).unwrap(),
+            digest
+        );
+        assert!(<Bytes256 as UniffiCustomTypeConverter>::into_custom("00".repeat(255)).is_err());
+    }
+
+    #[test]
+    fn test_prover_error_code() {
+        let err: KimchiError = ProverError::PublicInputCount {
+            expected: 2,
+            actual: 1,
+        }
+        .into();
+        match err {
+            KimchiError::Prover {
+                code,
+                details,
+                causes,
+                ..
+            } => {
+                assert_eq!(code, 501);
+                assert_eq!(details["expected"], "2");
+                assert!(causes.is_empty());
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+
+    #[test]
+    fn test_metrics_callback() {
+        #[derive(Default)]
+        struct Recorder(Mutex<Vec<String>>);
+
+        impl MetricsCallback for Recorder {
+            fn counter(&self, name: String, _value: u64) {
+                self.0.lock().unwrap().push(name);
+            }
+            fn histogram(&self, _name: String, _value: f64) {}
+            fn error(&self, phase: String, code: u32, _message: String) {
+                self.0.lock().unwrap().push(format!("{}:{}", phase, code));
+            }
+        }
+
+        let recorder = Arc::new(Recorder::default());
+        set_metrics_callback(recorder.clone());
+        let _ = verify_proof(u64::MAX);
+        let events = recorder.0.lock().unwrap().clone();
+        assert!(events.contains(&"kimchi.ffi.verify_proof.count".to_string()));
+        assert!(events.contains(&"kimchi.ffi.verify_proof.errors".to_string()));
+
+        ForeignMetrics(recorder.clone()).error("prove", &ProverError::VerifierIndexMismatch);
+        assert!(recorder
+            .0
+            .lock()
+            .unwrap()
+            .contains(&"prove:401".to_string()));
+    }
+
+    #[test]
+    fn test_get_metrics() {
+        let _ = verify_proof(u64::MAX);
+        let metrics = get_metrics();
+        assert!(metrics.counters["kimchi.ffi.verify_proof.errors"] >= 1);
+        let latency = &metrics.latencies["kimchi.ffi.verify_proof.duration_ms"];
+        assert!(latency.samples >= 1 && latency.p50_ms <= latency.p99_ms);
+        assert!(!metrics
+            .latencies
+            .contains_key("kimchi.ffi.proof_size_bytes"));
+    }
+
+    #[test]
+    fn test_cbor_envelope() {
+        let envelope = ProofEnvelope {
+            version: kimchi_prover::PROOF_ENVELOPE_VERSION,
+            circuit_id: "threshold".into(),
+            circuit_version: 1,
+            srs_log2_size: 10,
+            proof: vec![0; 16],
+            public_inputs: vec![FieldElement::from_u64(100)],
+            verifier_index_digest: [1; 32],
+            circuit_hash: [0; 32],
+        }
+        .to_hex()
+        .unwrap();
+
+        let cbor = envelope_to_cbor(envelope.clone()).unwrap();
+        assert_eq!(envelope_from_cbor(cbor).unwrap(), envelope);
+
+        let to_be_signed = envelope_cose_to_be_signed(envelope.clone(), -8).unwrap();
+        let cose = envelope_to_cose(envelope.clone(), -8, vec![9; 64]).unwrap();
+        let opened = open_cose_envelope(cose).unwrap();
+        assert_eq!(opened.envelope, envelope);
+        assert_eq!(opened.alg, -8);
+        assert_eq!(opened.to_be_signed, to_be_signed);
+        assert_eq!(opened.signature, vec![9; 64]);
+    }
+
+    #[test]
+    fn test_store_shares_verifier_indices() {
+        use kimchi::circuits::polynomials::generic::GenericGateSpec;
+        use kimchi_prover::{CircuitBuilder, WitnessBuilder};
+
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        for v in [1u64, 2] {
+            let [cell, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(v)));
+            w.set(cell, Fp::from(v));
+        }
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover.setup(cs.build(), 0).unwrap();
+        let proof = prover.prove(&prover_index, w.columns()).unwrap();
+        let hash = ProofEnvelope::verifier_index_digest(&verifier_index).unwrap();
+
+        let vi_size = rmp_serde::to_vec(&verifier_index).unwrap().len();
+        let mut store = ProofStore {
+            limit_bytes: Some(vi_size + 3 * 100 + 2 * FIELD_BYTES),
+            ..Default::default()
+        };
+        for id in 1..=3 {
+            let values = if id == 1 {
+                vec![Fp::from(1u64), Fp::from(2u64)]
+            } else {
+                Vec::new()
+            };
+            let inputs = StoredInputs::new(&ThresholdCircuit::new(0), values);
+            store
+                .insert(id, proof.clone(), 100, verifier_index.clone(), hash, inputs)
+                .unwrap();
+        }
+        assert_eq!(store.verifier_indices.len(), 1);
+        assert!(Arc::ptr_eq(
+            &store.proofs[&1].verifier_index,
+            &store.proofs[&3].verifier_index
+        ));
+        assert_eq!(store.stats().total_bytes as usize, vi_size + 364);
+
+        match store.insert(
+            4,
+            proof.clone(),
+            1,
+            verifier_index.clone(),
+            hash,
+            StoredInputs::new(&ThresholdCircuit::new(0), Vec::new()),
+        ) {
+            Err(err @ ProverError::ResourceExhausted { .. }) => assert_eq!(err.code(), 800),
+            other => panic!("unexpected result: {:?}", other.err()),
+        }
+        assert_eq!(store.proofs.len(), 3);
+
+        let stored = &store.proofs[&1];
+        let mut vi_hex = String::new();
+        rmp_serde::encode::write(&mut HexWriter::new(&mut vi_hex), &*stored.verifier_index)
+            .unwrap();
+        assert_eq!(
+            vi_hex,
+            hex::encode(rmp_serde::to_vec(&*stored.verifier_index).unwrap())
+        );
+
+        store.remove(1);
+        store.remove(2);
+        assert_eq!(store.verifier_indices.len(), 1);
+        store.remove(3);
+        store.remove(3);
+        assert!(store.proofs.is_empty());
+        assert!(store.verifier_indices.is_empty());
+        assert_eq!(store.total_bytes, 0);
+
+        store.ttl = Some(Duration::from_secs(60));
+        let inputs = StoredInputs::new(&ThresholdCircuit::new(0), Vec::new());
+        store
+            .insert(5, proof.clone(), 100, verifier_index.clone(), hash, inputs)
+            .unwrap();
+        let stored_at = store.proofs[&5].stored_at;
+        assert_eq!(store.sweep(stored_at + Duration::from_secs(59)), 0);
+        assert_eq!(store.sweep(stored_at + Duration::from_secs(60)), 1);
+        assert!(store.proofs.is_empty());
+        assert!(store.verifier_indices.is_empty());
+        assert!(store.expired.contains(&5));
+        assert_eq!(store.total_bytes, 0);
+        store.remove(5);
+        assert!(store.expired.is_empty());
+
+        // Unfreed expired handles are remembered up to the cap
+        store.limit_bytes = None;
+        let ids = 10..10 + MAX_EXPIRED_HANDLES as u64 + 5;
+        for id in ids.clone() {
+            let inputs = StoredInputs::new(&ThresholdCircuit::new(0), Vec::new());
+            store
+                .insert(id, proof.clone(), 100, verifier_index.clone(), hash, inputs)
+                .unwrap();
+        }
+        store.sweep(Instant::now() + Duration::from_secs(60));
+        assert_eq!(store.expired.len(), MAX_EXPIRED_HANDLES);
+        assert!(!store.expired.contains(&(ids.start + 4)));
+        assert!(store.expired.contains(&(ids.start + 5)));
+        assert!(store.expired.contains(&(ids.end - 1)));
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_trace() {
+        assert!(finish_trace().is_err());
+        init_prover(Some(10), None, None).unwrap();
+        start_trace().unwrap();
+        let result = prove_threshold(0, 100).unwrap();
+        free_proof(result.proof_handle).unwrap();
+        let json = finish_trace().unwrap();
+        assert!(json.contains("\"name\":\"witness\""));
+        assert!(json.contains("\"name\":\"prove\""));
+    }
+
+    #[test]
+    fn test_export_public_inputs() {
+        use kimchi_prover::PublicValue;
+
+        init_prover(Some(10), None, None).unwrap();
+        let result = prove_threshold(0, 100).unwrap();
+        let json = export_public_inputs(result.proof_handle).unwrap();
+        let document = PublicInputsDocument::from_json(&json).unwrap();
+        assert_eq!(document.circuit_id, "threshold");
+        assert_eq!(document.get("threshold"), Some(&PublicValue::U64(100)));
+        assert!(json.contains("{\"name\":\"is_valid\",\"type\":\"bool\",\"value\":"));
+
+        free_proof(result.proof_handle).unwrap();
+        assert!(export_public_inputs(result.proof_handle).is_err());
+    }
+
+    #[test]
+    fn test_prove_comparison() {
+        use kimchi_prover::PublicValue;
+
+        init_prover(Some(10), None, None).unwrap();
+        let result = prove_comparison(18, 18, ThresholdOperator::Ge).unwrap();
+        assert!(verify_proof(result.proof_handle).unwrap());
+        let json = export_public_inputs(result.proof_handle).unwrap();
+        let document = PublicInputsDocument::from_json(&json).unwrap();
+        assert_eq!(document.circuit_id, "threshold_ge");
+        assert_eq!(document.get("is_valid"), Some(&PublicValue::Bool(true)));
+        free_proof(result.proof_handle).unwrap();
+
+        let result = prove_comparison(17, 18, ThresholdOperator::Ge).unwrap();
+        let json = export_public_inputs(result.proof_handle).unwrap();
+        let document = PublicInputsDocument::from_json(&json).unwrap();
+        assert_eq!(document.get("is_valid"), Some(&PublicValue::Bool(false)));
+        free_proof(result.proof_handle).unwrap();
+    }
+
+    #[test]
+    fn test_prove_wide_comparison() {
+        use kimchi_prover::PublicValue;
+
+        init_prover(Some(10), None, None).unwrap();
+        let balance = u128::MAX.to_string();
+        let result = prove_wide_comparison(
+            balance.clone(),
+            (1u128 << 100).to_string(),
+            ThresholdOperator::Ge,
+            ValueWidth::U128,
+        )
+        .unwrap();
+        assert!(verify_proof(result.proof_handle).unwrap());
+        let json = export_public_inputs(result.proof_handle).unwrap();
+        let document = PublicInputsDocument::from_json(&json).unwrap();
+        assert_eq!(document.circuit_id, "threshold_u128_ge");
+        assert_eq!(document.get("is_valid"), Some(&PublicValue::Bool(true)));
+        free_proof(result.proof_handle).unwrap();
+
+        // Wider than the circuit, and not a number
+        let wide = |value: &str, threshold: &str| {
+            prove_wide_comparison(
+                value.to_string(),
+                threshold.to_string(),
+                ThresholdOperator::Lt,
+                ValueWidth::U128,
+            )
+        };
+        // 2^128
+        match wide("1", "340282366920938463463374607431768211456") {
+            Err(KimchiError::Prover { code, .. }) => assert_eq!(code, 500),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(matches!(wide("x", "1"), Err(KimchiError::InvalidInput(_))));
+    }
+
+    #[test]
+    fn test_prove_merkle_membership() {
+        use kimchi_prover::gadgets::MerkleWitness;
+
+        init_prover(Some(10), None, None).unwrap();
+        let leaves: Vec<Fp> = (1..=4u64).map(Fp::from).collect();
+        let (root, paths) = MerkleWitness::tree(&leaves);
+        let input = MerklePathInput {
+            leaf_hex: FieldElement(leaves[2]).to_hex(),
+            nodes: paths[2]
+                .iter()
+                .map(|step| MerkleNode {
+                    sibling_hex: FieldElement(step.sibling).to_hex(),
+                    is_right: step.is_right,
+                })
+                .collect(),
+        };
+
+        let result = prove_merkle_membership(input.clone()).unwrap();
+        assert_eq!(result.public_inputs, vec![FieldElement(root).to_hex()]);
+        assert!(verify_proof(result.proof_handle).unwrap());
+        free_proof(result.proof_handle).unwrap();
+
+        let mut bad = input.clone();
+        bad.nodes.clear();
+        assert!(prove_merkle_membership(bad).is_err());
+        let mut bad = input;
+        bad.nodes[0].sibling_hex = "zz".into();
+        assert!(matches!(
+            prove_merkle_membership(bad),
+            Err(KimchiError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_async_exports() {
+        use runtime::tests::block_on;
+
+        init_prover(Some(10), None, None).unwrap();
+        let result = block_on(prove_threshold_async(0, 10)).unwrap();
+        assert_eq!(result.public_inputs.len(), 2);
+        assert!(block_on(verify_proof_async(result.proof_handle)).unwrap());
+        free_proof(result.proof_handle).unwrap();
+        assert!(matches!(
+            block_on(verify_proof_async(result.proof_handle)),
+            Err(KimchiError::ProofNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_verifier() {
+        use runtime::tests::block_on;
+
+        init_prover(Some(10), None, None).unwrap();
+        let signer = set_envelope_signing_key(Some(
+            "164244176fddb5d769b7de2027469d027ad428fadcc0c02396e6280142efb718".into(),
+        ))
+        .unwrap()
+        .unwrap();
+        let result = prove_threshold(0, 10).unwrap();
+        assert_eq!(set_envelope_signing_key(None).unwrap(), None);
+        let verifier_index = export_verifier_index(result.proof_handle).unwrap();
+
+        init_verifier(Some(10)).unwrap();
+        let digest = add_verifier_index(verifier_index.clone()).unwrap();
+        assert!(block_on(verify_envelope_async(result.envelope.clone())).unwrap());
+        // Not bound to a circuit
+        match verify_envelope_with_max_age(result.envelope.clone(), 1_700_000_000, 86_400) {
+            Err(KimchiError::Prover { code, .. }) => assert_eq!(code, 401),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        // The threshold circuit has no timestamp
+        assert_eq!(
+            add_circuit_verifier_index("threshold".into(), verifier_index.clone()).unwrap(),
+            digest
+        );
+        match verify_envelope_with_max_age(result.envelope.clone(), 1_700_000_000, 86_400) {
+            Err(KimchiError::Prover { code, .. }) => assert_eq!(code, 500),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let timestamped =
+            prove_timestamped_comparison(3, 10, ThresholdOperator::Lt, 1_700_000_000).unwrap();
+        let timestamped_digest = add_circuit_verifier_index(
+            "timestamped/threshold_lt".into(),
+            export_verifier_index(timestamped.proof_handle).unwrap(),
+        )
+        .unwrap();
+        assert!(
+            verify_envelope_with_max_age(timestamped.envelope.clone(), 1_700_000_060, 86_400)
+                .unwrap()
+        );
+        match verify_envelope_with_max_age(timestamped.envelope, 1_700_100_000, 86_400) {
+            Err(KimchiError::Prover { code, .. }) => assert_eq!(code, 402),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(remove_verifier_index(timestamped_digest).unwrap());
+        // Nor is it attested: its first public input is the threshold
+        match verify_envelope_with_attestation(
+            result.envelope.clone(),
+            AttestationPlatform::AppAttest,
+            Bytes32::from([7; 32]),
+        ) {
+            Err(KimchiError::Prover { code, .. }) => assert_eq!(code, 403),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let signed = result.signed_envelope.clone().unwrap();
+        assert_eq!(
+            check_envelope_signature(signed.clone(), Some(signer.clone())).unwrap(),
+            result.envelope
+        );
+        assert!(verify_signed_envelope(signed.clone(), Some(signer)).unwrap());
+        match verify_signed_envelope(signed, Some("B62other".into())) {
+            Err(KimchiError::Prover { code, .. }) => assert_eq!(code, 404),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(matches!(
+            add_verifier_index("zz".into()),
+            Err(KimchiError::InvalidInput(_))
+        ));
+
+        assert!(remove_verifier_index(digest).unwrap());
+        match verify_envelope(result.envelope.clone()) {
+            Err(KimchiError::Prover { code, .. }) => assert_eq!(code, 401),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        struct Source(Bytes32, String);
+        impl VerifierIndexSource for Source {
+            fn fetch(&self, digest: Bytes32) -> Option<String> {
+                (digest == self.0).then(|| self.1.clone())
+            }
+        }
+        set_verifier_index_source(Arc::new(Source(digest, verifier_index))).unwrap();
+        assert!(verify_envelope(result.envelope).unwrap());
+        assert!(remove_verifier_index(digest).unwrap());
+    }
+
+    #[test]
+    fn test_get_circuit_info() {
+        init_prover(Some(10), None, None).unwrap();
+        let info = get_circuit_info("threshold".into()).unwrap();
+        assert_eq!(info.num_public_inputs, 2);
+        assert_eq!(info.proving_time, ProvingTime::Fast);
+        assert_eq!(info.fits_srs, Some(true));
+        assert!(info.public_input_schema.contains("\"name\":\"threshold\""));
+        assert_eq!(
+            kimchi_prover::circuits::registry::id_for_hash(&info.circuit_hash.0),
+            Some("threshold")
+        );
+        let gates = export_circuit_gates("threshold".into()).unwrap();
+        assert!(gates.contains(&info.circuit_hash.to_hex()));
+        assert!(export_circuit_gates("unknown".into()).is_err());
+
+        match get_circuit_info("unknown".into()) {
+            Err(KimchiError::Prover { code, .. }) => assert_eq!(code, 500),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_get_setup_info() {
+        init_prover(Some(10), None, None).unwrap();
+        let info = get_setup_info("threshold".into()).unwrap();
+        let circuit = get_circuit_info("threshold".into()).unwrap();
+        assert_eq!(info.rows, circuit.rows);
+        assert_eq!(info.num_public_inputs, 2);
+        assert!(info.domain_size <= info.max_domain_size);
+        assert_eq!(
+            info.gate_counts.iter().map(|g| g.count).sum::<u64>(),
+            info.rows
+        );
+        assert!(get_setup_info("unknown".into()).is_err());
+    }
+
+    struct Offline;
+
+    impl SrsFetcher for Offline {
+        fn fetch(&self, _url: String) -> Option<Vec<u8>> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_srs_source() {
+        let download = || SrsSource::Download {
+            url_template: "https://cdn.example/srs-{log2}.bin".into(),
+            dir: std::env::temp_dir().to_string_lossy().into_owned(),
+            pins: HashMap::from([(10, Bytes32([0; 32]))]),
+        };
+        assert!(matches!(
+            set_srs_source(download(), None),
+            Err(KimchiError::InvalidInput(_))
+        ));
+
+        let fetch = ForeignFetcher(Arc::new(Offline));
+        let err = srs_provider::Fetch::fetch(&fetch, "https://cdn.example").unwrap_err();
+        assert_eq!(err.code(), 100);
     }
 
     #[test]
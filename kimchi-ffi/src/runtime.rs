@@ -0,0 +1,143 @@
+//! Worker thread behind the async FFI entry points.
+//!
+//! UniFFI polls async exports from the host's executor (Kotlin coroutines,
+//! Swift concurrency), so a future that proves inline would block one of
+//! its threads. `spawn` instead queues the work on a dedicated thread and
+//! returns a future that completes when it is done.
+//!
+//! There is a single worker: proofs already serialize on the prover lock,
+//! and one thread keeps peak memory to one proof at a time on phones. Jobs
+//! run in the order they were spawned.
+
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Queue of the worker thread, started on first use.
+static WORKER: OnceLock<Mutex<Sender<Job>>> = OnceLock::new();
+
+fn worker() -> &'static Mutex<Sender<Job>> {
+    WORKER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        thread::Builder::new()
+            .name("kimchi-worker".into())
+            .spawn(move || {
+                for job in receiver {
+                    job();
+                }
+            })
+            .expect("failed to start the kimchi worker thread");
+        Mutex::new(sender)
+    })
+}
+
+/// Run `f` on the worker thread.
+///
+/// A panic in `f` is caught on the worker and resumed when the returned
+/// future is polled, where UniFFI reports it to the host.
+pub(crate) fn spawn<T, F>(f: F) -> JobHandle<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let state = Arc::new(Mutex::new(JobState {
+        result: None,
+        waker: None,
+    }));
+
+    let shared = Arc::clone(&state);
+    let job: Job = Box::new(move || {
+        let result = panic::catch_unwind(AssertUnwindSafe(f));
+        let mut state = shared.lock().unwrap_or_else(|e| e.into_inner());
+        state.result = Some(result);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    });
+    worker()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .send(job)
+        .expect("the kimchi worker thread has stopped");
+
+    JobHandle { state }
+}
+
+struct JobState<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// Future of a job queued with `spawn`.
+pub(crate) struct JobHandle<T> {
+    state: Arc<Mutex<JobState<T>>>,
+}
+
+impl<T> Future for JobHandle<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        match state.result.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::task::Wake;
+
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Drive `future` to completion on the current thread.
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(value) => return value,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_spawn() {
+        let handles: Vec<_> = (0..4u64)
+            .map(|i| spawn(move || (i, thread::current().name().map(String::from))))
+            .collect();
+        for (i, handle) in handles.into_iter().enumerate() {
+            let (value, name) = block_on(handle);
+            assert_eq!(value, i as u64);
+            assert_eq!(name.as_deref(), Some("kimchi-worker"));
+        }
+    }
+
+    #[test]
+    fn test_spawn_panic() {
+        let result = panic::catch_unwind(|| block_on(spawn(|| -> u64 { panic!("job failed") })));
+        assert!(result.is_err());
+        // The worker survives the panic
+        assert_eq!(block_on(spawn(|| 7)), 7);
+    }
+}
@@ -0,0 +1,84 @@
+//! Write or check golden test vectors.
+//!
+//! ```text
+//! kimchi-golden [--seed <N>] <DIR>
+//! kimchi-golden --check <DIR>
+//! ```
+//!
+//! Writes fixtures for every built-in circuit to `DIR` (see
+//! `kimchi_prover::golden`), or with `--check` verifies an existing fixture
+//! directory with this build. Exits 0 on success, 1 on failure and 2 on a
+//! usage error.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use kimchi_prover::golden::{self, GOLDEN_SEED};
+
+const USAGE: &str = "usage: kimchi-golden [--seed <N>] <DIR>\n       kimchi-golden --check <DIR>";
+
+struct Args {
+    dir: PathBuf,
+    seed: u64,
+    check: bool,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut dir = None;
+    let mut seed = GOLDEN_SEED;
+    let mut check = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => {
+                let value = args.next().ok_or("--seed needs a value")?;
+                seed = value
+                    .parse()
+                    .map_err(|_| format!("invalid seed '{}'", value))?;
+            }
+            "--check" => check = true,
+            "-h" | "--help" => return Err(USAGE.into()),
+            _ if dir.is_none() && !arg.starts_with('-') => dir = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument '{}'\n{}", arg, USAGE)),
+        }
+    }
+    Ok(Args {
+        dir: dir.ok_or(USAGE)?,
+        seed,
+        check,
+    })
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(2);
+        }
+    };
+
+    let result = if args.check {
+        golden::check(&args.dir)
+    } else {
+        golden::write(&args.dir, args.seed)
+    };
+
+    match result {
+        Ok(manifest) => {
+            for entry in &manifest.vectors {
+                println!(
+                    "{} {} ({} files)",
+                    if args.check { "ok" } else { "wrote" },
+                    entry.circuit_id,
+                    entry.files.len()
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {}", e.report());
+            ExitCode::FAILURE
+        }
+    }
+}
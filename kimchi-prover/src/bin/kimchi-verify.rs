@@ -0,0 +1,107 @@
+//! Verify a `ProofEnvelope` from the command line.
+//!
+//! ```text
+//! kimchi-verify --verifier-index <FILE|HEX> <ENVELOPE FILE|HEX>
+//! ```
+//!
+//! Files may hold raw MessagePack or its hex encoding. The SRS is generated
+//! at the size recorded in the envelope. Prints one JSON object to stdout and
+//! exits 0 if the proof is valid, 1 if it is invalid or cannot be checked,
+//! and 2 on a usage error.
+
+use std::path::Path;
+use std::process::ExitCode;
+
+use kimchi_prover::{FieldElement, KimchiProver, ProofEnvelope, ProverConfig, ProverError};
+use serde_json::{json, Value};
+
+const USAGE: &str = "usage: kimchi-verify --verifier-index <FILE|HEX> <ENVELOPE FILE|HEX>";
+
+struct Args {
+    verifier_index: String,
+    envelope: String,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut verifier_index = None;
+    let mut envelope = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-k" | "--verifier-index" => {
+                verifier_index = Some(args.next().ok_or("--verifier-index needs a value")?);
+            }
+            "-h" | "--help" => return Err(USAGE.into()),
+            _ if envelope.is_none() && !arg.starts_with('-') => envelope = Some(arg),
+            _ => return Err(format!("unexpected argument '{}'\n{}", arg, USAGE)),
+        }
+    }
+    Ok(Args {
+        verifier_index: verifier_index.ok_or(USAGE)?,
+        envelope: envelope.ok_or(USAGE)?,
+    })
+}
+
+/// Read an argument that is either a file (raw or hex) or a hex string.
+fn read_input(arg: &str, what: &'static str) -> Result<Vec<u8>, ProverError> {
+    if Path::new(arg).is_file() {
+        let bytes = std::fs::read(arg)?;
+        return Ok(match std::str::from_utf8(&bytes) {
+            Ok(text) => hex::decode(text.trim()).unwrap_or(bytes),
+            Err(_) => bytes,
+        });
+    }
+    hex::decode(arg.trim()).map_err(|e| ProverError::decode(what, e))
+}
+
+fn verify(args: &Args, report: &mut Value) -> Result<bool, ProverError> {
+    let envelope = ProofEnvelope::from_bytes(&read_input(&args.envelope, "proof envelope")?)?;
+    report["circuit_id"] = json!(envelope.circuit_id);
+    report["circuit_version"] = json!(envelope.circuit_version);
+    report["srs_log2_size"] = json!(envelope.srs_log2_size);
+    report["public_inputs"] = envelope
+        .public_inputs
+        .iter()
+        .map(FieldElement::to_decimal)
+        .collect();
+    report["verifier_index_digest"] = json!(hex::encode(envelope.verifier_index_digest));
+
+    let mut prover = KimchiProver::with_config(ProverConfig {
+        srs_log2_size: envelope.srs_log2_size as usize,
+        ..Default::default()
+    });
+    let verifier_index =
+        prover.load_verifier_index(&read_input(&args.verifier_index, "verifier index")?)?;
+    prover.verify_envelope(&verifier_index, &envelope)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{}", message);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut report = json!({});
+    let valid = match verify(&args, &mut report) {
+        Ok(valid) => valid,
+        Err(e) => {
+            report["error"] = json!({
+                "code": e.code(),
+                "message": e.report(),
+                "details": e.details().into_iter().collect::<std::collections::BTreeMap<_, _>>(),
+            });
+            false
+        }
+    };
+    report["valid"] = json!(valid);
+    println!("{}", report);
+
+    if valid {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
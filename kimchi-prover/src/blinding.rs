@@ -0,0 +1,230 @@
+//! Audit of proof blinding.
+//!
+//! A Kimchi proof hides the witness only if every proof draws fresh blinding
+//! factors. A broken random number generator, or a seeded one left in from
+//! test fixtures, still produces proofs that verify, so nothing else notices
+//! that two proofs of the same witness are linkable, or worse. [`BlindingAudit`]
+//! generates and proves the same witness several times and checks that
+//!
+//! - no two published proofs are equal;
+//! - every run has the same public inputs as the first, i.e. witness
+//!   generation is deterministic in what it publishes;
+//! - every proof verifies against its public inputs.
+//!
+//! Meant for release-qualification tests: run it on the release build, on
+//! the device, with the prover configuration the app ships.
+//!
+//! Available in unit tests and behind the `testing` feature for downstream crates.
+
+use kimchi::prover_index::ProverIndex;
+use kimchi::verifier_index::VerifierIndex;
+use mina_curves::pasta::{Fp, Vesta};
+use poly_commitment::ipa::SRS;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+
+use crate::error::{ProverError, Result};
+use crate::prover::{KimchiProver, COLUMNS, FULL_ROUNDS};
+
+/// Outcome of an audit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlindingReport {
+    /// Number of proofs made.
+    pub runs: usize,
+    /// Pairs of runs that published the same proof.
+    pub repeated_proofs: Vec<(usize, usize)>,
+    /// Runs whose public inputs differ from the first run's.
+    pub changed_public_inputs: Vec<usize>,
+    /// Runs whose proof does not verify.
+    pub unverified: Vec<usize>,
+}
+
+impl BlindingReport {
+    /// Whether every proof was distinct and verified against the same
+    /// public inputs.
+    pub fn is_blinded(&self) -> bool {
+        self.repeated_proofs.is_empty()
+            && self.changed_public_inputs.is_empty()
+            && self.unverified.is_empty()
+    }
+}
+
+/// Proves a witness repeatedly and compares the proofs.
+#[derive(Clone, Debug)]
+pub struct BlindingAudit {
+    runs: usize,
+}
+
+impl Default for BlindingAudit {
+    fn default() -> Self {
+        Self { runs: 3 }
+    }
+}
+
+impl BlindingAudit {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make `runs` proofs, at least two.
+    pub fn with_runs(mut self, runs: usize) -> Self {
+        self.runs = runs.max(2);
+        self
+    }
+
+    /// Audit `prover` on the witness and public inputs from
+    /// `generate_witness`, called once per run.
+    ///
+    /// Fails if a witness cannot be generated or proved.
+    pub fn check(
+        &self,
+        prover: &KimchiProver,
+        prover_index: &ProverIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        generate_witness: impl FnMut() -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)>,
+    ) -> Result<BlindingReport> {
+        self.check_with_rng(
+            prover,
+            prover_index,
+            verifier_index,
+            generate_witness,
+            || rand::rngs::OsRng,
+        )
+    }
+
+    /// Like `check`, with each run's blinding drawn from a generator made
+    /// by `rng`, e.g. to show that the audit catches a fixed seed.
+    pub fn check_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        prover: &KimchiProver,
+        prover_index: &ProverIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        mut generate_witness: impl FnMut() -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)>,
+        mut rng: impl FnMut() -> R,
+    ) -> Result<BlindingReport> {
+        let mut report = BlindingReport {
+            runs: self.runs,
+            repeated_proofs: Vec::new(),
+            changed_public_inputs: Vec::new(),
+            unverified: Vec::new(),
+        };
+        let mut first_public_inputs = None;
+        let mut digests: Vec<[u8; 32]> = Vec::with_capacity(self.runs);
+
+        for run in 0..self.runs {
+            let (witness, public_inputs) = generate_witness()?;
+            let proof = prover.prove_with_rng(prover_index, witness, &mut rng())?;
+
+            // The bytes an envelope publishes
+            let bytes = rmp_serde::to_vec(&proof).map_err(|e| ProverError::encode("proof", e))?;
+            let digest: [u8; 32] = Sha256::digest(&bytes).into();
+            report.repeated_proofs.extend(
+                digests
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, earlier)| **earlier == digest)
+                    .map(|(earlier, _)| (earlier, run)),
+            );
+            digests.push(digest);
+
+            match &first_public_inputs {
+                None => first_public_inputs = Some(public_inputs.clone()),
+                Some(first) if *first != public_inputs => report.changed_public_inputs.push(run),
+                Some(_) => {}
+            }
+            if !matches!(
+                prover.verify(verifier_index, &proof, &public_inputs),
+                Ok(true)
+            ) {
+                report.unverified.push(run);
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Panic unless the proofs of `generate_witness` are blinded.
+    pub fn assert_blinded(
+        &self,
+        prover: &KimchiProver,
+        prover_index: &ProverIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        generate_witness: impl FnMut() -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)>,
+    ) {
+        let report = self
+            .check(prover, prover_index, verifier_index, generate_witness)
+            .expect("proving failed");
+        assert!(report.is_blinded(), "proofs are not blinded: {:?}", report);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::ThresholdCircuit;
+    use crate::prover::{CircuitIndices, ProverConfig};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    fn setup() -> (KimchiProver, CircuitIndices) {
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            ..Default::default()
+        });
+        let circuit = ThresholdCircuit::new(10);
+        let indices = prover
+            .setup(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+        (prover, indices)
+    }
+
+    #[test]
+    fn test_blinded() {
+        let (prover, (prover_index, verifier_index)) = setup();
+        let circuit = ThresholdCircuit::new(10);
+        let report = BlindingAudit::new()
+            .check(&prover, &prover_index, &verifier_index, || {
+                circuit.generate_witness(0)
+            })
+            .unwrap();
+        assert_eq!(report.runs, 3);
+        assert!(report.is_blinded());
+        BlindingAudit::new().with_runs(2).assert_blinded(
+            &prover,
+            &prover_index,
+            &verifier_index,
+            || circuit.generate_witness(0),
+        );
+    }
+
+    #[test]
+    fn test_fixed_seed_and_changing_inputs() {
+        let (prover, (prover_index, verifier_index)) = setup();
+        let circuit = ThresholdCircuit::new(10);
+
+        // The same seed each run gives the same proof each run
+        let report = BlindingAudit::new()
+            .check_with_rng(
+                &prover,
+                &prover_index,
+                &verifier_index,
+                || circuit.generate_witness(0),
+                || StdRng::seed_from_u64(7),
+            )
+            .unwrap();
+        assert_eq!(report.repeated_proofs, vec![(0, 1), (0, 2), (1, 2)]);
+        assert!(!report.is_blinded());
+
+        // A witness whose public inputs depend on the run; the threshold
+        // does not change the gates
+        let mut thresholds = [10, 12].into_iter();
+        let report = BlindingAudit::new()
+            .with_runs(2)
+            .check(&prover, &prover_index, &verifier_index, || {
+                ThresholdCircuit::new(thresholds.next().unwrap()).generate_witness(0)
+            })
+            .unwrap();
+        assert_eq!(report.changed_public_inputs, vec![1]);
+        assert!(report.unverified.is_empty());
+    }
+}
@@ -0,0 +1,1104 @@
+//! Cell allocation and witness layout for gadgets.
+//!
+//! `CircuitBuilder` allocates gate rows and records copy constraints between
+//! cells, and `WitnessBuilder` holds the values assigned to those cells.
+//! Cell-level gadget methods take both, return the `Cell`s holding their
+//! outputs and copy-constrain their inputs to the cells they were given, so a
+//! circuit assembled from gadgets comes with its witness and permutation.
+//!
+//! Gadgets that lay out whole blocks of rows implement `Gadget`: `build`
+//! appends their gates at the builder's current row and `witness` fills the
+//! same rows at the witness builder's current row, so composing gadgets only
+//! requires calling both in the same order.
+//!
+//! Kimchi reads public inputs from column 0 of the first rows, and
+//! subtracts each from the generic constraint of its row whatever gate is
+//! there. `CircuitBuilder::public_input` allocates those rows, and panics
+//! once any other gate has been placed, so a gadget cannot end up on a
+//! public row.
+//!
+//! Rows can be labeled with `CircuitBuilder::region`, so witness checks and
+//! cost reports name the gadget a row belongs to.
+//!
+//! Circuits that assign cells directly use a `WitnessLayout`, which sizes the
+//! columns for the circuit's evaluation domain.
+//!
+//! A Kimchi generic gate holds two independent constraints, one over columns
+//! 0..3 and one over columns 3..6. `CircuitBuilder::generic_packed` and
+//! `pack_generic` place single constraints two per row, which halves the
+//! rows of gadgets made of many small constraints (bit checks, comparisons,
+//! byte checks).
+
+use ark_ff::Zero;
+use kimchi::circuits::gate::{CircuitGate, GateType};
+use kimchi::circuits::polynomials::generic::{GenericGateSpec, GENERIC_COEFFS};
+use kimchi::circuits::wires::{Wire, PERMUTS};
+use mina_curves::pasta::Fp;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::cost::{CostReport, GadgetCost};
+use crate::error::{ProverError, Result};
+use crate::prover::{ProverConfig, COLUMNS, ZK_ROWS};
+
+/// A witness cell: a column of a gate row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cell {
+    pub row: usize,
+    pub col: usize,
+}
+
+impl Cell {
+    pub fn new(row: usize, col: usize) -> Self {
+        Self { row, col }
+    }
+}
+
+/// A labeled range of rows.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Region {
+    /// Dot-separated label, including the labels of enclosing regions.
+    pub name: String,
+    pub rows: Range<usize>,
+}
+
+/// Allocates gate rows and records copy constraints between cells.
+pub struct CircuitBuilder {
+    gates: Vec<CircuitGate<Fp>>,
+    copies: Vec<(Cell, Cell)>,
+    start_row: usize,
+    regions: Vec<Region>,
+    scope: Vec<String>,
+    /// Number of leading public input rows.
+    public_rows: usize,
+    /// Whether the last gate is a generic gate with a free second half.
+    open_half: bool,
+}
+
+impl CircuitBuilder {
+    /// Create a builder whose first gate is placed at `start_row`.
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            gates: Vec::new(),
+            copies: Vec::new(),
+            start_row,
+            regions: Vec::new(),
+            scope: Vec::new(),
+            public_rows: 0,
+            open_half: false,
+        }
+    }
+
+    /// Row of the first gate.
+    pub fn start_row(&self) -> usize {
+        self.start_row
+    }
+
+    /// Row the next gate will be placed at.
+    pub fn current_row(&self) -> usize {
+        self.start_row + self.gates.len()
+    }
+
+    /// Number of rows allocated so far.
+    pub fn num_rows(&self) -> usize {
+        self.gates.len()
+    }
+
+    /// Number of public input rows allocated by `public_input`.
+    pub fn num_public_inputs(&self) -> usize {
+        self.public_rows
+    }
+
+    /// Allocate the next public input row and return the cell holding the
+    /// input.
+    ///
+    /// # Panics
+    ///
+    /// If the builder does not start at row 0 or already holds a gate that
+    /// is not a public input: Kimchi reads public inputs from the first
+    /// rows only.
+    pub fn public_input(&mut self) -> Cell {
+        assert!(
+            self.start_row == 0 && self.gates.len() == self.public_rows,
+            "public inputs must take the first rows, not row {}",
+            self.current_row()
+        );
+        self.public_rows += 1;
+        let [cell, _, _] = self.generic(GenericGateSpec::Pub);
+        cell
+    }
+
+    /// Allocate `n` public input rows with `public_input`.
+    pub fn public_inputs(&mut self, n: usize) -> Vec<Cell> {
+        (0..n).map(|_| self.public_input()).collect()
+    }
+
+    /// Gates allocated so far, without wiring applied.
+    pub fn gates(&self) -> &[CircuitGate<Fp>] {
+        &self.gates
+    }
+
+    /// Static cost of the gates allocated so far.
+    pub fn cost(&self) -> GadgetCost {
+        GadgetCost::from_gates(&self.gates)
+    }
+
+    /// Fail with `CircuitTooLarge` if the rows allocated so far no longer
+    /// fit the SRS of `config`, as setup would. Lets gadget-heavy circuits
+    /// stop early, e.g. between an RSA check and the gadgets after it.
+    pub fn check_row_budget(&self, config: &ProverConfig) -> Result<()> {
+        self.cost()
+            .check_fits_domain(1 << config.max_domain_log2_size())
+    }
+
+    /// Append a gate of any type and return its row.
+    pub fn gate(&mut self, typ: GateType, coeffs: Vec<Fp>) -> usize {
+        self.open_half = false;
+        let row = self.current_row();
+        self.gates
+            .push(CircuitGate::new(typ, Wire::for_row(row), coeffs));
+        row
+    }
+
+    /// Append a single generic gate and return its (left, right, output) cells.
+    pub fn generic(&mut self, spec: GenericGateSpec<Fp>) -> [Cell; 3] {
+        self.open_half = false;
+        let row = self.current_row();
+        self.gates.push(CircuitGate::create_generic_gadget(
+            Wire::for_row(row),
+            spec,
+            None,
+        ));
+        [Cell::new(row, 0), Cell::new(row, 1), Cell::new(row, 2)]
+    }
+
+    /// Append a block of gates laid out from `from_row`, relocated to the current row.
+    ///
+    /// Wires are shifted by the same offset, so wiring inside the block is kept.
+    /// Returns the row of the first appended gate.
+    pub fn extend_gates(&mut self, gates: &[CircuitGate<Fp>], from_row: usize) -> usize {
+        self.open_half = false;
+        let start = self.current_row();
+        self.gates.extend(gates.iter().map(|gate| {
+            let mut gate = gate.clone();
+            for wire in gate.wires.iter_mut() {
+                wire.row = wire.row - from_row + start;
+            }
+            gate
+        }));
+        start
+    }
+
+    /// Append a generic gate `c0*l + c1*r + c2*o + c3*l*r + c4 = 0` with explicit
+    /// coefficients, for constraints no `GenericGateSpec` expresses.
+    pub fn generic_coeffs(&mut self, coeffs: [Fp; GENERIC_COEFFS]) -> [Cell; 3] {
+        self.open_half = false;
+        let row = self.current_row();
+        let mut all = [Fp::zero(); 2 * GENERIC_COEFFS];
+        all[..GENERIC_COEFFS].copy_from_slice(&coeffs);
+        self.gates
+            .push(CircuitGate::create_generic(Wire::for_row(row), all));
+        [Cell::new(row, 0), Cell::new(row, 1), Cell::new(row, 2)]
+    }
+
+    /// Add a single generic constraint, sharing a row with the previous one
+    /// when possible, and return its (left, right, output) cells.
+    ///
+    /// The constraint goes into columns 3..6 of the last row if that row was
+    /// started by `generic_packed` and its second half is still free, and
+    /// into columns 0..3 of a new row otherwise. Allocating any other row or
+    /// opening or closing a region starts a new row, so regions never share
+    /// rows.
+    pub fn generic_packed(&mut self, spec: GenericGateSpec<Fp>) -> [Cell; 3] {
+        self.generic_coeffs_packed(generic_spec_coeffs(spec))
+    }
+
+    /// `generic_packed` with explicit coefficients (see `generic_coeffs`).
+    pub fn generic_coeffs_packed(&mut self, coeffs: [Fp; GENERIC_COEFFS]) -> [Cell; 3] {
+        push_packed(&mut self.gates, self.start_row, &mut self.open_half, coeffs)
+    }
+
+    /// Constrain two cells to hold the same value.
+    ///
+    /// Records permutation wiring between the cells, e.g. from a SHA-256
+    /// digest word to the matching word of an RSA padding check. Both cells
+    /// must be in rows allocated by this builder (see `append` for combining
+    /// builders) and in one of the first `PERMUTS` columns, the only ones
+    /// Kimchi's permutation covers.
+    pub fn connect(&mut self, a: Cell, b: Cell) -> Result<()> {
+        for cell in [a, b] {
+            if cell.col >= PERMUTS {
+                return Err(ProverError::InvalidInput(format!(
+                    "column {} of row {} cannot be wired (only the first {} columns are permuted)",
+                    cell.col, cell.row, PERMUTS
+                )));
+            }
+            if !(self.start_row..self.current_row()).contains(&cell.row) {
+                return Err(ProverError::InvalidInput(format!(
+                    "row {} is outside rows {}..{} of this builder",
+                    cell.row,
+                    self.start_row,
+                    self.current_row()
+                )));
+            }
+        }
+        if a != b {
+            self.copies.push((a, b));
+        }
+        Ok(())
+    }
+
+    /// Copy constraint between cells a gadget has just allocated.
+    pub(crate) fn copy(&mut self, a: Cell, b: Cell) {
+        if let Err(e) = self.connect(a, b) {
+            panic!("invalid copy constraint: {}", e);
+        }
+    }
+
+    /// Append a builder whose rows start where this one ends.
+    ///
+    /// The appended gates and copy constraints keep their absolute rows, so
+    /// cells returned by gadgets built on `other` stay valid and can be
+    /// connected to cells of this builder.
+    pub fn append(&mut self, other: CircuitBuilder) -> Result<()> {
+        if other.start_row != self.current_row() {
+            return Err(ProverError::InvalidInput(format!(
+                "cannot append rows starting at {} after row {}",
+                other.start_row,
+                self.current_row()
+            )));
+        }
+        if self.public_rows == self.gates.len() {
+            self.public_rows += other.public_rows;
+        }
+        self.gates.extend(other.gates);
+        self.copies.extend(other.copies);
+        self.regions.extend(other.regions);
+        self.open_half = false;
+        Ok(())
+    }
+
+    /// Label the rows allocated by `f` with `name`.
+    ///
+    /// Regions nest: a region opened inside another is named
+    /// `outer.inner`.
+    pub fn region<T>(&mut self, name: &str, f: impl FnOnce(&mut Self) -> T) -> T {
+        let name = match self.scope.last() {
+            Some(outer) => format!("{}.{}", outer, name),
+            None => name.to_string(),
+        };
+        self.scope.push(name);
+        self.open_half = false;
+        let start = self.current_row();
+        let result = f(self);
+        self.open_half = false;
+        let name = self.scope.pop().expect("region scope underflow");
+        self.regions.push(Region {
+            name,
+            rows: start..self.current_row(),
+        });
+        result
+    }
+
+    /// Regions recorded so far, in the order they were closed.
+    pub fn regions(&self) -> &[Region] {
+        &self.regions
+    }
+
+    /// Name of the innermost region containing `row`.
+    pub fn region_of(&self, row: usize) -> Option<&str> {
+        self.regions
+            .iter()
+            .filter(|region| region.rows.contains(&row))
+            .min_by_key(|region| region.rows.len())
+            .map(|region| region.name.as_str())
+    }
+
+    /// Cost of each region, in the order they were closed.
+    ///
+    /// Nested regions are reported alongside their enclosing region, so
+    /// entries overlap when regions nest.
+    pub fn region_costs(&self) -> CostReport {
+        let mut report = CostReport::new();
+        for region in &self.regions {
+            let gates =
+                &self.gates[region.rows.start - self.start_row..region.rows.end - self.start_row];
+            report.add(region.name.clone(), GadgetCost::from_gates(gates));
+        }
+        report
+    }
+
+    /// Copy constraints recorded so far.
+    pub fn copies(&self) -> &[(Cell, Cell)] {
+        &self.copies
+    }
+
+    /// Check a witness against the generic gates and copy constraints.
+    ///
+    /// Public input rows hold their input, as Kimchi checks them.
+    pub fn check_witness(&self, witness: &WitnessBuilder) -> Result<()> {
+        for (i, gate) in self.gates.iter().enumerate() {
+            let row = self.start_row + i;
+            if gate.typ != GateType::Generic {
+                continue;
+            }
+            for (half, coeffs) in gate.coeffs.chunks(GENERIC_COEFFS).enumerate() {
+                if coeffs.len() < GENERIC_COEFFS {
+                    continue;
+                }
+                let [l, r, o] = std::array::from_fn(|k| witness.get(Cell::new(row, 3 * half + k)));
+                let mut value =
+                    coeffs[0] * l + coeffs[1] * r + coeffs[2] * o + coeffs[3] * l * r + coeffs[4];
+                if half == 0 && i < self.public_rows {
+                    value -= l;
+                }
+                if !value.is_zero() {
+                    return Err(ProverError::GateNotSatisfied {
+                        row,
+                        half,
+                        region: self.region_of(row).map(String::from),
+                    });
+                }
+            }
+        }
+
+        for (a, b) in &self.copies {
+            if witness.get(*a) != witness.get(*b) {
+                return Err(ProverError::CopyNotSatisfied {
+                    a: *a,
+                    b: *b,
+                    a_region: self.region_of(a.row).map(String::from),
+                    b_region: self.region_of(b.row).map(String::from),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Consume the builder and return its gates with the permutation wiring applied.
+    ///
+    /// Cells connected by copy constraints are grouped into equivalence
+    /// classes, and each class is wired as a cycle in (row, column) order.
+    pub fn build(self) -> Vec<CircuitGate<Fp>> {
+        let mut parent: BTreeMap<Cell, Cell> = BTreeMap::new();
+
+        fn find(parent: &mut BTreeMap<Cell, Cell>, cell: Cell) -> Cell {
+            let mut root = cell;
+            while let Some(&next) = parent.get(&root) {
+                if next == root {
+                    break;
+                }
+                root = next;
+            }
+            parent.insert(cell, root);
+            root
+        }
+
+        for &(a, b) in &self.copies {
+            let ra = find(&mut parent, a);
+            let rb = find(&mut parent, b);
+            if ra != rb {
+                parent.insert(ra.max(rb), ra.min(rb));
+            }
+        }
+
+        let cells: Vec<Cell> = parent.keys().copied().collect();
+        let mut classes: BTreeMap<Cell, Vec<Cell>> = BTreeMap::new();
+        for cell in cells {
+            let root = find(&mut parent, cell);
+            classes.entry(root).or_default().push(cell);
+        }
+
+        let mut gates = self.gates;
+        for class in classes.values() {
+            for (i, cell) in class.iter().enumerate() {
+                let next = class[(i + 1) % class.len()];
+                gates[cell.row - self.start_row].wires[cell.col] = Wire::new(next.row, next.col);
+            }
+        }
+
+        gates
+    }
+}
+
+/// Coefficients `[c0, c1, c2, c3, c4]` of a single generic constraint, as
+/// Kimchi lays them out for `spec`.
+pub fn generic_spec_coeffs(spec: GenericGateSpec<Fp>) -> [Fp; GENERIC_COEFFS] {
+    let gate = CircuitGate::create_generic_gadget(Wire::for_row(0), spec, None);
+    std::array::from_fn(|i| gate.coeffs.get(i).copied().unwrap_or_else(Fp::zero))
+}
+
+/// Generic gates holding `constraints` two per row from `first_row`.
+///
+/// Constraint `2k` uses columns 0..3 of row `first_row + k` and constraint
+/// `2k + 1` columns 3..6. An odd last constraint leaves its row's second
+/// half with zero coefficients, which every witness satisfies.
+pub fn pack_generic(
+    first_row: usize,
+    constraints: &[[Fp; GENERIC_COEFFS]],
+) -> Vec<CircuitGate<Fp>> {
+    constraints
+        .chunks(2)
+        .enumerate()
+        .map(|(i, pair)| {
+            let mut coeffs = [Fp::zero(); 2 * GENERIC_COEFFS];
+            for (half, constraint) in pair.iter().enumerate() {
+                coeffs[half * GENERIC_COEFFS..(half + 1) * GENERIC_COEFFS]
+                    .copy_from_slice(constraint);
+            }
+            CircuitGate::create_generic(Wire::for_row(first_row + i), coeffs)
+        })
+        .collect()
+}
+
+/// Append a single generic constraint to `gates`, laid out from
+/// `first_row`, in the second half of the last gate when `open_half` says it
+/// is free. Shared by `CircuitBuilder` and the row-level gadgets.
+pub(crate) fn push_packed(
+    gates: &mut Vec<CircuitGate<Fp>>,
+    first_row: usize,
+    open_half: &mut bool,
+    coeffs: [Fp; GENERIC_COEFFS],
+) -> [Cell; 3] {
+    if *open_half {
+        if let Some(gate) = gates.last_mut() {
+            *open_half = false;
+            gate.coeffs.resize(2 * GENERIC_COEFFS, Fp::zero());
+            gate.coeffs[GENERIC_COEFFS..].copy_from_slice(&coeffs);
+            let row = first_row + gates.len() - 1;
+            return [Cell::new(row, 3), Cell::new(row, 4), Cell::new(row, 5)];
+        }
+    }
+    let row = first_row + gates.len();
+    gates.extend(pack_generic(row, &[coeffs]));
+    *open_half = true;
+    [Cell::new(row, 0), Cell::new(row, 1), Cell::new(row, 2)]
+}
+
+/// Values assigned to witness cells.
+///
+/// The values are wiped when the builder is dropped, and when its buffer
+/// grows the old one is wiped before it is freed.
+pub struct WitnessBuilder {
+    rows: Vec<[Fp; COLUMNS]>,
+    start_row: usize,
+}
+
+impl WitnessBuilder {
+    /// Create a witness whose first row is `start_row`, matching a `CircuitBuilder`.
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            rows: Vec::new(),
+            start_row,
+        }
+    }
+
+    /// Number of rows with assigned values.
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Row the next block of values will be written at.
+    pub fn current_row(&self) -> usize {
+        self.start_row + self.rows.len()
+    }
+
+    /// Write a block of rows at the current row and return its first row.
+    pub fn fill_rows(&mut self, rows: &[[Fp; COLUMNS]]) -> usize {
+        let start = self.current_row();
+        self.reserve(rows.len());
+        self.rows.extend_from_slice(rows);
+        start
+    }
+
+    /// Extend to `len` rows with zero rows.
+    fn resize(&mut self, len: usize) {
+        self.reserve(len.saturating_sub(self.rows.len()));
+        self.rows.resize(len, [Fp::zero(); COLUMNS]);
+    }
+
+    /// Make room for `additional` rows, moving to a larger buffer by hand
+    /// so the old one is wiped rather than freed as is.
+    fn reserve(&mut self, additional: usize) {
+        if self.rows.capacity() - self.rows.len() >= additional {
+            return;
+        }
+        let capacity = (self.rows.len() + additional).max(2 * self.rows.capacity());
+        let mut rows = Vec::with_capacity(capacity);
+        rows.extend_from_slice(&self.rows);
+        std::mem::swap(&mut self.rows, &mut rows);
+        rows.zeroize();
+    }
+
+    /// Assign a value to a cell.
+    pub fn set(&mut self, cell: Cell, value: Fp) {
+        assert!(
+            cell.row >= self.start_row,
+            "row {} is before the witness start row {}",
+            cell.row,
+            self.start_row
+        );
+        let index = cell.row - self.start_row;
+        if index >= self.rows.len() {
+            self.resize(index + 1);
+        }
+        self.rows[index][cell.col] = value;
+    }
+
+    /// Assign values to the (left, right, output) cells of a generic gate.
+    pub fn set_cells(&mut self, cells: [Cell; 3], values: [Fp; 3]) {
+        for (cell, value) in cells.into_iter().zip(values) {
+            self.set(cell, value);
+        }
+    }
+
+    /// Append the rows of a witness built alongside an appended `CircuitBuilder`.
+    pub fn append(&mut self, other: WitnessBuilder) -> Result<()> {
+        if other.start_row < self.start_row {
+            return Err(ProverError::InvalidInput(format!(
+                "cannot append witness rows starting at {} before row {}",
+                other.start_row, self.start_row
+            )));
+        }
+        let offset = other.start_row - self.start_row;
+        if offset + other.rows.len() > self.rows.len() {
+            self.resize(offset + other.rows.len());
+        }
+        self.rows[offset..offset + other.rows.len()].copy_from_slice(&other.rows);
+        Ok(())
+    }
+
+    /// Value of a cell (zero if unassigned).
+    pub fn get(&self, cell: Cell) -> Fp {
+        cell.row
+            .checked_sub(self.start_row)
+            .and_then(|index| self.rows.get(index))
+            .map_or(Fp::zero(), |row| row[cell.col])
+    }
+
+    /// Witness columns covering rows `0..start_row + num_rows`.
+    ///
+    /// Rows before `start_row` (e.g. public inputs filled by the caller) are zero.
+    pub fn columns(&self) -> [Vec<Fp>; COLUMNS] {
+        let num_rows = self.start_row + self.rows.len();
+        std::array::from_fn(|col| {
+            let mut column = vec![Fp::zero(); num_rows];
+            for (i, row) in self.rows.iter().enumerate() {
+                column[self.start_row + i] = row[col];
+            }
+            column
+        })
+    }
+}
+
+impl Zeroize for WitnessBuilder {
+    fn zeroize(&mut self) {
+        self.rows.zeroize();
+    }
+}
+
+impl Drop for WitnessBuilder {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for WitnessBuilder {}
+
+/// Witness columns sized for a circuit's evaluation domain.
+///
+/// Kimchi proves over a power-of-two domain covering the circuit's rows (and
+/// lookup tables) plus [`ZK_ROWS`], and fills the last `ZK_ROWS` rows with
+/// blinding values itself. A layout derives that size from the gates and
+/// holds columns covering every other row, so circuits need not hard-code a
+/// row count; cells not written are zero.
+///
+/// The values are wiped when the layout is dropped.
+pub struct WitnessLayout {
+    columns: [Vec<Fp>; COLUMNS],
+    circuit_rows: usize,
+    used_rows: usize,
+    domain_size: usize,
+}
+
+impl WitnessLayout {
+    /// An all-zero witness for `gates`.
+    pub fn for_gates(gates: &[CircuitGate<Fp>]) -> Self {
+        let domain_size = GadgetCost::from_gates(gates).domain_size();
+        Self {
+            columns: std::array::from_fn(|_| vec![Fp::zero(); domain_size - ZK_ROWS]),
+            circuit_rows: gates.len(),
+            used_rows: 0,
+            domain_size,
+        }
+    }
+
+    /// Number of gate rows of the circuit.
+    pub fn circuit_rows(&self) -> usize {
+        self.circuit_rows
+    }
+
+    /// Number of rows up to the last one written.
+    pub fn used_rows(&self) -> usize {
+        self.used_rows
+    }
+
+    /// Size of the circuit's evaluation domain.
+    pub fn domain_size(&self) -> usize {
+        self.domain_size
+    }
+
+    /// Length of the columns: the domain without Kimchi's zero-knowledge rows.
+    pub fn num_rows(&self) -> usize {
+        self.domain_size - ZK_ROWS
+    }
+
+    /// Assign a value to a cell.
+    pub fn set(&mut self, cell: Cell, value: Fp) {
+        assert!(
+            cell.row < self.circuit_rows,
+            "row {} is past the circuit's {} rows",
+            cell.row,
+            self.circuit_rows
+        );
+        self.columns[cell.col][cell.row] = value;
+        self.used_rows = self.used_rows.max(cell.row + 1);
+    }
+
+    /// Assign the (left, right, output) cells of single generic constraints
+    /// laid out two per row from `first_row`, as by `pack_generic`. Returns
+    /// the row after them.
+    pub fn set_packed(&mut self, first_row: usize, constraints: &[[Fp; 3]]) -> usize {
+        let mut row = first_row;
+        for pair in constraints.chunks(2) {
+            for (half, values) in pair.iter().enumerate() {
+                for (k, &value) in values.iter().enumerate() {
+                    self.set(Cell::new(row, 3 * half + k), value);
+                }
+            }
+            row += 1;
+        }
+        row
+    }
+
+    /// Value of a cell (zero if unassigned).
+    pub fn get(&self, cell: Cell) -> Fp {
+        self.columns[cell.col]
+            .get(cell.row)
+            .copied()
+            .unwrap_or_else(Fp::zero)
+    }
+
+    /// Copy the rows of `columns`, e.g. the witness of a wrapped circuit,
+    /// from `to_row` on.
+    ///
+    /// Rows past the circuit's must be zero padding.
+    pub fn copy_rows(&mut self, columns: &[Vec<Fp>; COLUMNS], to_row: usize) -> Result<()> {
+        let room = self.circuit_rows.saturating_sub(to_row);
+        for (col, column) in columns.iter().enumerate() {
+            if column.iter().skip(room).any(|value| !value.is_zero()) {
+                return Err(ProverError::WitnessColumnLength {
+                    column: col,
+                    rows: column.len(),
+                    num_rows: room,
+                });
+            }
+            let rows = column.len().min(room);
+            self.columns[col][to_row..to_row + rows].copy_from_slice(&column[..rows]);
+            if rows > 0 {
+                self.used_rows = self.used_rows.max(to_row + rows);
+            }
+        }
+        Ok(())
+    }
+
+    /// The columns as filled so far.
+    pub fn columns(&self) -> &[Vec<Fp>; COLUMNS] {
+        &self.columns
+    }
+
+    /// The columns, for `KimchiProver::prove`.
+    pub fn into_columns(mut self) -> [Vec<Fp>; COLUMNS] {
+        std::mem::take(&mut self.columns)
+    }
+}
+
+impl Zeroize for WitnessLayout {
+    fn zeroize(&mut self) {
+        self.columns.zeroize();
+    }
+}
+
+impl Drop for WitnessLayout {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for WitnessLayout {}
+
+/// A gadget that lays out a block of rows.
+pub trait Gadget {
+    /// Values needed to fill the gadget's witness rows.
+    type Inputs: ?Sized;
+
+    /// Append the gadget's gates at the builder's current row and return that row.
+    fn build(&self, cs: &mut CircuitBuilder) -> usize;
+
+    /// Number of rows `build` appends.
+    fn rows(&self) -> usize;
+
+    /// Fill the gadget's rows at the witness builder's current row.
+    fn witness(&self, w: &mut WitnessBuilder, inputs: &Self::Inputs) -> Result<()>;
+
+    /// Static cost of the gadget's rows.
+    fn cost(&self) -> GadgetCost {
+        let mut cs = CircuitBuilder::new(0);
+        self.build(&mut cs);
+        cs.cost()
+    }
+}
+
+/// Witness for a gate-shape gadget: one row of values per gate.
+///
+/// Shared by the gadgets whose witness is produced as a full row layout.
+pub(crate) fn fill_row_layout(
+    w: &mut WitnessBuilder,
+    expected_rows: usize,
+    rows: &[[Fp; COLUMNS]],
+) -> Result<()> {
+    if rows.len() != expected_rows {
+        return Err(ProverError::WitnessRowCount {
+            expected: expected_rows,
+            actual: rows.len(),
+        });
+    }
+    w.fill_rows(rows);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::One;
+
+    #[test]
+    fn test_copy_cycles() {
+        let mut cs = CircuitBuilder::new(2);
+        let [a, _, b] = cs.generic(GenericGateSpec::Pub);
+        let [c, _, _] = cs.generic(GenericGateSpec::Pub);
+        let [d, _, _] = cs.generic(GenericGateSpec::Pub);
+        cs.copy(a, c);
+        cs.copy(d, c);
+        assert_eq!(cs.current_row(), 5);
+
+        let gates = cs.build();
+        // a -> c -> d -> a, b untouched
+        assert_eq!(gates[0].wires[0], Wire::new(3, 0));
+        assert_eq!(gates[1].wires[0], Wire::new(4, 0));
+        assert_eq!(gates[2].wires[0], Wire::new(2, 0));
+        assert_eq!(gates[0].wires[b.col], Wire::new(b.row, b.col));
+    }
+
+    #[test]
+    fn test_connect_across_builders() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let [digest, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(42u64)));
+        w.set(digest, Fp::from(42u64));
+
+        // A second module built separately, starting after the first
+        let mut other_cs = CircuitBuilder::new(cs.current_row());
+        let mut other_w = WitnessBuilder::new(other_cs.start_row());
+        let [input, _, _] = other_cs.generic(GenericGateSpec::Const(Fp::from(42u64)));
+        other_w.set(input, Fp::from(42u64));
+
+        cs.append(other_cs).unwrap();
+        w.append(other_w).unwrap();
+        cs.connect(digest, input).unwrap();
+        assert!(cs.check_witness(&w).is_ok());
+
+        let gates = cs.build();
+        assert_eq!(gates[0].wires[0], Wire::new(1, 0));
+        assert_eq!(gates[1].wires[0], Wire::new(0, 0));
+    }
+
+    #[test]
+    fn test_connect_rejects_invalid_cells() {
+        let mut cs = CircuitBuilder::new(0);
+        let [a, _, _] = cs.generic(GenericGateSpec::Pub);
+
+        assert!(cs.connect(a, Cell::new(0, PERMUTS)).is_err());
+        assert!(cs.connect(a, Cell::new(1, 0)).is_err());
+        assert!(cs.append(CircuitBuilder::new(5)).is_err());
+        assert!(cs.copies().is_empty());
+    }
+
+    #[test]
+    fn test_public_inputs() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let inputs = cs.public_inputs(2);
+        assert_eq!(inputs, [Cell::new(0, 0), Cell::new(1, 0)]);
+        let [sum, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(7u64)));
+        cs.copy(inputs[1], sum);
+        assert_eq!(cs.num_public_inputs(), 2);
+
+        w.set(inputs[0], Fp::from(3u64));
+        w.set(inputs[1], Fp::from(7u64));
+        w.set(sum, Fp::from(7u64));
+        assert!(cs.check_witness(&w).is_ok());
+
+        let gates = cs.build();
+        assert!(crate::diagnostics::check_public_rows(&gates, 2).is_ok());
+        assert!(crate::diagnostics::check_public_rows(&gates, 3).is_err());
+
+        // Public rows carry over when appended to an empty builder
+        let mut outer = CircuitBuilder::new(0);
+        let mut inner = CircuitBuilder::new(0);
+        inner.public_input();
+        outer.append(inner).unwrap();
+        outer.public_input();
+        assert_eq!(outer.num_public_inputs(), 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "public inputs must take the first rows")]
+    fn test_public_input_after_gadget() {
+        let mut cs = CircuitBuilder::new(0);
+        cs.generic(GenericGateSpec::Const(Fp::zero()));
+        cs.public_input();
+    }
+
+    #[test]
+    fn test_compose_gadgets() {
+        use crate::gadgets::{BooleanGadget, ComparisonGadget};
+
+        let mut boolean = BooleanGadget::new(0);
+        boolean.decompose_u32();
+        let mut comparison = ComparisonGadget::new(0);
+        comparison.range_check(8);
+
+        let mut cs = CircuitBuilder::new(1);
+        // The gadgets' inherent `build(self)` returns raw gates, so call the trait method
+        let first = Gadget::build(&boolean, &mut cs);
+        let second = Gadget::build(&comparison, &mut cs);
+        assert_eq!(first, 1);
+        assert_eq!(second, 1 + Gadget::rows(&boolean));
+        assert_eq!(cs.current_row(), second + Gadget::rows(&comparison));
+        assert_eq!(cs.gates()[second - 1].wires[0], Wire::new(second, 0));
+        // The word's complement stays wired to the generic row, relocated
+        let last = Gadget::rows(&boolean) - 1;
+        assert_eq!(cs.gates()[last].wires[0], Wire::new(first, 4));
+
+        let mut w = WitnessBuilder::new(1);
+        let rows = vec![[Fp::zero(); COLUMNS]; Gadget::rows(&boolean)];
+        boolean.witness(&mut w, &rows).unwrap();
+        assert!(comparison.witness(&mut w, &rows).is_err());
+        assert_eq!(w.current_row(), second);
+    }
+
+    #[test]
+    fn test_row_budget() {
+        let config = ProverConfig {
+            srs_log2_size: 4,
+            max_chunks: 1,
+            ..Default::default()
+        };
+        let mut cs = CircuitBuilder::new(0);
+        for _ in 0..13 {
+            cs.generic(GenericGateSpec::Const(Fp::zero()));
+        }
+        assert!(cs.check_row_budget(&config).is_ok());
+        cs.generic(GenericGateSpec::Const(Fp::zero()));
+        assert!(matches!(
+            cs.check_row_budget(&config),
+            Err(ProverError::CircuitTooLarge {
+                needed: 17,
+                available: 16
+            })
+        ));
+        assert!(cs
+            .check_row_budget(&ProverConfig {
+                max_chunks: 2,
+                ..config
+            })
+            .is_ok());
+    }
+
+    #[test]
+    fn test_check_witness() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+
+        let mul = cs.generic(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(mul, [Fp::from(3u64), Fp::from(5u64), Fp::from(15u64)]);
+        let [copy, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(15u64)));
+        w.set(copy, Fp::from(15u64));
+        cs.copy(mul[2], copy);
+        assert!(cs.check_witness(&w).is_ok());
+
+        w.set(copy, Fp::from(16u64));
+        assert!(cs.check_witness(&w).is_err());
+        w.set(copy, Fp::from(15u64));
+        w.set(mul[2], Fp::from(16u64));
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_regions() {
+        let mut cs = CircuitBuilder::new(1);
+        let mut w = WitnessBuilder::new(1);
+        cs.generic(GenericGateSpec::Const(Fp::zero()));
+
+        let out = cs.region("sha256", |cs| {
+            cs.region("block0", |cs| {
+                let cells = cs.generic(GenericGateSpec::Const(Fp::one()));
+                w.set(cells[0], Fp::from(2u64));
+                cs.generic(GenericGateSpec::Const(Fp::zero()));
+            });
+            cs.generic(GenericGateSpec::Const(Fp::zero()))
+        });
+        assert_eq!(out[0].row, 4);
+
+        assert_eq!(cs.region_of(0), None);
+        assert_eq!(cs.region_of(1), None);
+        assert_eq!(cs.region_of(3), Some("sha256.block0"));
+        assert_eq!(cs.region_of(4), Some("sha256"));
+        assert_eq!(cs.regions()[1].rows, 2..5);
+
+        let report = cs.region_costs();
+        assert_eq!(report.entries()[0].0, "sha256.block0");
+        assert_eq!(report.entries()[0].1.rows, 2);
+        assert_eq!(report.entries()[1].1.rows, 3);
+
+        let err = cs.check_witness(&w).unwrap_err().to_string();
+        assert!(err.contains("row 2 (sha256.block0)"), "{}", err);
+    }
+
+    #[test]
+    fn test_generic_packed() {
+        let constant = |v: u64| GenericGateSpec::Const(Fp::from(v));
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+
+        let cells: Vec<Cell> = [3u64, 5, 7]
+            .into_iter()
+            .map(|v| {
+                let [cell, _, _] = cs.generic_packed(constant(v));
+                w.set(cell, Fp::from(v));
+                cell
+            })
+            .collect();
+        assert_eq!(cells, [Cell::new(0, 0), Cell::new(0, 3), Cell::new(1, 0)]);
+        assert_eq!(
+            cs.gates()[0].coeffs,
+            pack_generic(
+                0,
+                &[
+                    generic_spec_coeffs(constant(3)),
+                    generic_spec_coeffs(constant(5))
+                ]
+            )[0]
+            .coeffs
+        );
+
+        // Regions and full rows start new rows
+        let [inner, _, _] = cs.region("inner", |cs| cs.generic_packed(constant(7)));
+        assert_eq!(inner, Cell::new(2, 0));
+        w.set(inner, Fp::from(7u64));
+        let [full, _, _] = cs.generic(constant(7));
+        w.set(full, Fp::from(7u64));
+        let [last, _, _] = cs.generic_packed(constant(7));
+        assert_eq!(last, Cell::new(4, 0));
+        w.set(last, Fp::from(7u64));
+        assert_eq!(cs.num_rows(), 5);
+
+        cs.copy(cells[2], Cell::new(2, 0));
+        cs.copy(Cell::new(2, 0), last);
+        assert!(cs.check_witness(&w).is_ok());
+
+        w.set(cells[1], Fp::from(6u64));
+        assert!(matches!(
+            cs.check_witness(&w),
+            Err(ProverError::GateNotSatisfied {
+                row: 0,
+                half: 1,
+                ..
+            })
+        ));
+        w.set(cells[1], Fp::from(5u64));
+
+        assert!(crate::testing::GadgetHarness::new()
+            .prove(cs, &w, &[])
+            .unwrap());
+    }
+
+    #[test]
+    fn test_witness_columns() {
+        let mut w = WitnessBuilder::new(2);
+        w.set(Cell::new(3, 4), Fp::one());
+
+        let columns = w.columns();
+        assert_eq!(columns[4].len(), 4);
+        assert_eq!(columns[4][3], Fp::one());
+        assert_eq!(w.get(Cell::new(0, 0)), Fp::zero());
+    }
+
+    #[test]
+    fn test_witness_zeroize() {
+        let mut w = WitnessBuilder::new(0);
+        for row in 0..100 {
+            w.set(Cell::new(row, row % COLUMNS), Fp::from(row as u64 + 1));
+        }
+        w.fill_rows(&[[Fp::one(); COLUMNS]; 3]);
+        // Values survive the buffer being moved as it grows
+        for row in 0..100 {
+            assert_eq!(
+                w.get(Cell::new(row, row % COLUMNS)),
+                Fp::from(row as u64 + 1)
+            );
+        }
+        assert_eq!(w.num_rows(), 103);
+
+        w.zeroize();
+        assert_eq!(w.num_rows(), 0);
+        assert_eq!(w.get(Cell::new(5, 5)), Fp::zero());
+    }
+
+    #[test]
+    fn test_witness_layout() {
+        let mut cs = CircuitBuilder::new(0);
+        for _ in 0..6 {
+            cs.generic(GenericGateSpec::Const(Fp::one()));
+        }
+        let gates = cs.build();
+
+        let mut w = WitnessLayout::for_gates(&gates);
+        assert_eq!(w.domain_size(), 16);
+        assert_eq!(w.num_rows(), 16 - ZK_ROWS);
+        assert_eq!(w.used_rows(), 0);
+        w.set(Cell::new(1, 0), Fp::one());
+        assert_eq!(w.used_rows(), 2);
+
+        let mut inner: [Vec<Fp>; COLUMNS] = Default::default();
+        inner[0] = vec![Fp::from(2u64), Fp::from(3u64), Fp::zero(), Fp::zero()];
+        w.copy_rows(&inner, 3).unwrap();
+        assert_eq!(w.get(Cell::new(4, 0)), Fp::from(3u64));
+        assert_eq!(w.used_rows(), 6);
+        inner[0][3] = Fp::one();
+        assert!(matches!(
+            w.copy_rows(&inner, 3),
+            Err(ProverError::WitnessColumnLength { column: 0, .. })
+        ));
+
+        let columns = w.into_columns();
+        assert!(columns.iter().all(|c| c.len() == 16 - ZK_ROWS));
+        assert_eq!(columns[0][1], Fp::one());
+    }
+}
@@ -0,0 +1,204 @@
+//! Memoized circuit compilation.
+//!
+//! Building the constraint system and prover index dominates `setup()`, and
+//! apps typically set up the same few circuits over and over. With
+//! [`ProverConfig::circuit_cache_capacity`] set, [`KimchiProver::setup`]
+//! keeps the indices of recently compiled circuits in a [`CircuitCache`]
+//! keyed by [`CircuitCache::key`] and returns copies on later calls.
+//!
+//! The key is the circuit's [`circuit_hash`], extended with its lookup
+//! tables if it has any.
+//! A cache belongs to one prover and therefore one SRS.
+//!
+//! [`ProverConfig::circuit_cache_capacity`]: crate::prover::ProverConfig::circuit_cache_capacity
+//! [`KimchiProver::setup`]: crate::prover::KimchiProver::setup
+//! [`circuit_hash`]: crate::circuits::circuit_hash
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::lookup::tables::LookupTable;
+use mina_curves::pasta::Fp;
+use sha2::Digest;
+
+use crate::circuits::{circuit_hash, circuit_hasher, hash_fields};
+use crate::prover::CircuitIndices;
+
+/// Least-recently-used cache of compiled circuits.
+pub struct CircuitCache {
+    capacity: usize,
+    entries: HashMap<[u8; 32], Arc<CircuitIndices>>,
+    /// Keys from least to most recently used.
+    order: VecDeque<[u8; 32]>,
+    hits: u64,
+    misses: u64,
+}
+
+impl CircuitCache {
+    /// A cache holding at most `capacity` circuits.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// [`circuit_hash`] of the gates and public input count, extended with
+    /// the lookup tables if there are any.
+    pub fn key(
+        gates: &[CircuitGate<Fp>],
+        num_public_inputs: usize,
+        lookup_tables: &[LookupTable<Fp>],
+    ) -> [u8; 32] {
+        if lookup_tables.is_empty() {
+            return circuit_hash(gates, num_public_inputs);
+        }
+        let mut hasher = circuit_hasher(gates, num_public_inputs);
+        hasher.update((lookup_tables.len() as u64).to_le_bytes());
+        for table in lookup_tables {
+            hasher.update(table.id.to_le_bytes());
+            hasher.update((table.data.len() as u64).to_le_bytes());
+            for column in &table.data {
+                hash_fields(&mut hasher, column);
+            }
+        }
+        hasher.finalize().into()
+    }
+
+    /// Look up a circuit, marking it most recently used.
+    pub fn get(&mut self, key: &[u8; 32]) -> Option<Arc<CircuitIndices>> {
+        match self.entries.get(key) {
+            Some(indices) => {
+                self.hits += 1;
+                let indices = indices.clone();
+                self.touch(key);
+                Some(indices)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Add a circuit, evicting the least recently used one when full.
+    pub fn insert(&mut self, key: [u8; 32], indices: Arc<CircuitIndices>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key, indices).is_some() {
+            self.touch(&key);
+            return;
+        }
+        self.order.push_back(key);
+        while self.entries.len() > self.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    fn touch(&mut self, key: &[u8; 32]) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            self.order.remove(pos);
+        }
+        self.order.push_back(*key);
+    }
+
+    /// Number of cached circuits.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Lookups that found a cached circuit.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Lookups that did not.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Drop every cached circuit.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::CircuitBuilder;
+    use crate::prover::{KimchiProver, ProverConfig};
+    use kimchi::circuits::polynomials::generic::GenericGateSpec;
+
+    fn constant_circuit(values: &[u64]) -> Vec<CircuitGate<Fp>> {
+        let mut cs = CircuitBuilder::new(0);
+        for &v in values {
+            cs.generic(GenericGateSpec::Const(Fp::from(v)));
+        }
+        cs.build()
+    }
+
+    #[test]
+    fn test_key() {
+        let a = constant_circuit(&[1, 2]);
+        assert_eq!(CircuitCache::key(&a, 0, &[]), CircuitCache::key(&a, 0, &[]));
+        assert_ne!(CircuitCache::key(&a, 0, &[]), CircuitCache::key(&a, 1, &[]));
+        assert_ne!(
+            CircuitCache::key(&a, 0, &[]),
+            CircuitCache::key(&constant_circuit(&[1, 3]), 0, &[])
+        );
+        let table = LookupTable {
+            id: 7,
+            data: vec![vec![Fp::from(0u64)]],
+        };
+        assert_ne!(
+            CircuitCache::key(&a, 0, &[]),
+            CircuitCache::key(&a, 0, &[table])
+        );
+    }
+
+    #[test]
+    fn test_setup_uses_cache() {
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            circuit_cache_capacity: 2,
+            ..Default::default()
+        });
+
+        let (_, first) = prover.setup(constant_circuit(&[1, 2]), 0).unwrap();
+        let (_, again) = prover.setup(constant_circuit(&[1, 2]), 0).unwrap();
+        let cache = prover.circuit_cache().unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+        assert_eq!(
+            crate::ProofEnvelope::verifier_index_digest(&first).unwrap(),
+            crate::ProofEnvelope::verifier_index_digest(&again).unwrap()
+        );
+
+        // [1, 2] is touched last, so [3, 4] is evicted by [5, 6].
+        prover.setup(constant_circuit(&[3, 4]), 0).unwrap();
+        prover.setup(constant_circuit(&[1, 2]), 0).unwrap();
+        prover.setup(constant_circuit(&[5, 6]), 0).unwrap();
+        let cache = prover.circuit_cache().unwrap();
+        assert_eq!(cache.len(), 2);
+        let key = CircuitCache::key(&constant_circuit(&[3, 4]), 0, &[]);
+        assert!(!cache.entries.contains_key(&key));
+
+        let uncached = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            ..Default::default()
+        });
+        assert!(uncached.circuit_cache().is_none());
+    }
+}
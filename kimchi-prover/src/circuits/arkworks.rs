@@ -0,0 +1,211 @@
+//! Arkworks circuits - lowers an ark-relations constraint system to Kimchi.
+//!
+//! Circuits written against arkworks' `ConstraintSynthesizer` are synthesized
+//! into an ark-relations `ConstraintSystem` over the Pallas scalar field, and
+//! its R1CS matrices and assignment are compiled to generic gates the same way
+//! as [circom circuits](super::circom).
+//!
+//! Public inputs: the instance variables, in allocation order.
+//!
+//! Private inputs: the witness variables.
+
+use ark_relations::r1cs::{
+    ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, SynthesisError, SynthesisMode,
+};
+use kimchi::circuits::gate::CircuitGate;
+use mina_curves::pasta::Fp;
+
+use super::circom::R1cs;
+use super::{Circuit, PublicInputSchema};
+use crate::error::{ProverError, Result};
+use crate::prover::COLUMNS;
+
+/// An arkworks circuit compiled to Kimchi gates.
+#[derive(Clone, Debug)]
+pub struct ArkworksCircuit {
+    pub r1cs: R1cs,
+    /// Value of every wire, empty if synthesized in setup mode.
+    values: Vec<Fp>,
+}
+
+impl ArkworksCircuit {
+    /// Synthesize `circuit` with its assignment, for proving.
+    pub fn synthesize<C: ConstraintSynthesizer<Fp>>(circuit: C) -> Result<Self> {
+        let cs = ConstraintSystem::new_ref();
+        circuit
+            .generate_constraints(cs.clone())
+            .map_err(synthesis_error)?;
+        Self::from_constraint_system(&cs)
+    }
+
+    /// Synthesize `circuit` without an assignment, for setup: the circuit's
+    /// gates but no witness.
+    pub fn synthesize_setup<C: ConstraintSynthesizer<Fp>>(circuit: C) -> Result<Self> {
+        let cs = ConstraintSystem::new_ref();
+        cs.set_mode(SynthesisMode::Setup);
+        circuit
+            .generate_constraints(cs.clone())
+            .map_err(synthesis_error)?;
+        Self::from_constraint_system(&cs)
+    }
+
+    /// Lower a synthesized constraint system.
+    pub fn from_constraint_system(cs: &ConstraintSystemRef<Fp>) -> Result<Self> {
+        cs.finalize();
+        let matrices = cs.to_matrices().ok_or_else(|| {
+            ProverError::SetupError("arkworks constraint system has no matrices".into())
+        })?;
+        let values = match cs.borrow() {
+            Some(inner) if !cs.is_in_setup_mode() => inner
+                .instance_assignment
+                .iter()
+                .chain(&inner.witness_assignment)
+                .copied()
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        // Matrix columns are the wires: 1, the instance then the witness
+        let constraints = (0..matrices.num_constraints)
+            .map(|i| {
+                [&matrices.a[i], &matrices.b[i], &matrices.c[i]].map(|row| {
+                    row.iter()
+                        .map(|&(coeff, wire)| (wire, coeff))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let r1cs = R1cs {
+            num_wires: matrices.num_instance_variables + matrices.num_witness_variables,
+            num_public_outputs: 0,
+            num_public_inputs: matrices.num_instance_variables - 1,
+            num_private_inputs: matrices.num_witness_variables,
+            constraints,
+        };
+        Ok(Self { r1cs, values })
+    }
+
+    /// Whether the circuit was synthesized with an assignment.
+    pub fn has_assignment(&self) -> bool {
+        !self.values.is_empty()
+    }
+
+    /// Generate the circuit gates.
+    pub fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        self.r1cs.gates()
+    }
+
+    /// Generate witness from the synthesized assignment.
+    ///
+    /// Returns the witness columns and the public inputs.
+    pub fn generate_witness(&self) -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)> {
+        if !self.has_assignment() {
+            return Err(ProverError::WitnessError(
+                "arkworks circuit was synthesized without an assignment".into(),
+            ));
+        }
+        self.r1cs.generate_witness(&self.values)
+    }
+}
+
+impl Circuit for ArkworksCircuit {
+    fn id(&self) -> &'static str {
+        "arkworks"
+    }
+
+    fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        ArkworksCircuit::gates(self)
+    }
+
+    fn public_input_schema(&self) -> PublicInputSchema {
+        self.r1cs.public_input_schema()
+    }
+}
+
+fn synthesis_error(e: SynthesisError) -> ProverError {
+    match e {
+        SynthesisError::AssignmentMissing => {
+            ProverError::WitnessError(format!("arkworks synthesis failed: {}", e))
+        }
+        _ => ProverError::SetupError(format!("arkworks synthesis failed: {}", e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GadgetHarness;
+    use ark_relations::lc;
+    use ark_relations::r1cs::Variable;
+
+    /// Knows `x` with `x^3 + x + 5 = y` for public `y`.
+    struct Cubic {
+        x: Option<Fp>,
+        y: Option<Fp>,
+    }
+
+    impl ConstraintSynthesizer<Fp> for Cubic {
+        fn generate_constraints(
+            self,
+            cs: ConstraintSystemRef<Fp>,
+        ) -> std::result::Result<(), SynthesisError> {
+            let x_val = self.x;
+            let sq_val = x_val.map(|x| x * x);
+            let y = cs.new_input_variable(|| self.y.ok_or(SynthesisError::AssignmentMissing))?;
+            let x = cs.new_witness_variable(|| x_val.ok_or(SynthesisError::AssignmentMissing))?;
+            let sq = cs.new_witness_variable(|| sq_val.ok_or(SynthesisError::AssignmentMissing))?;
+            let cube = cs.new_witness_variable(|| {
+                x_val
+                    .zip(sq_val)
+                    .map(|(x, sq)| x * sq)
+                    .ok_or(SynthesisError::AssignmentMissing)
+            })?;
+            cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + sq)?;
+            cs.enforce_constraint(lc!() + sq, lc!() + x, lc!() + cube)?;
+            cs.enforce_constraint(
+                lc!() + cube + x + (Fp::from(5u64), Variable::One),
+                lc!() + Variable::One,
+                lc!() + y,
+            )
+        }
+    }
+
+    #[test]
+    fn test_prove_arkworks() {
+        let circuit = ArkworksCircuit::synthesize(Cubic {
+            x: Some(Fp::from(3u64)),
+            y: Some(Fp::from(35u64)),
+        })
+        .unwrap();
+        assert_eq!(circuit.num_public_inputs(), 1);
+        let setup = ArkworksCircuit::synthesize_setup(Cubic { x: None, y: None }).unwrap();
+        assert!(!setup.has_assignment());
+        assert_eq!(setup.gates(), circuit.gates());
+        assert!(matches!(
+            setup.generate_witness(),
+            Err(ProverError::WitnessError(_))
+        ));
+        assert!(matches!(
+            ArkworksCircuit::synthesize(Cubic { x: None, y: None }),
+            Err(ProverError::WitnessError(_))
+        ));
+
+        let (witness, public_inputs) = circuit.generate_witness().unwrap();
+        assert_eq!(public_inputs, vec![Fp::from(35u64)]);
+        let harness = GadgetHarness::new();
+        let proof = harness
+            .prove_circuit(setup.gates(), witness, setup.num_public_inputs())
+            .unwrap();
+        assert!(proof.verifies(&public_inputs));
+
+        let wrong = ArkworksCircuit::synthesize(Cubic {
+            x: Some(Fp::from(4u64)),
+            y: Some(Fp::from(35u64)),
+        })
+        .unwrap();
+        let (witness, _) = wrong.generate_witness().unwrap();
+        assert!(harness
+            .prove_circuit(setup.gates(), witness, setup.num_public_inputs())
+            .is_err());
+    }
+}
@@ -0,0 +1,332 @@
+//! Device attestation binding - ties proofs to a genuine app build.
+//!
+//! Play Integrity and App Attest vouch that a request comes from a genuine
+//! build of the app on a genuine device. An [`AttestedCircuit`] makes such
+//! an attestation part of what a proof proves: it wraps any circuit with one
+//! more public input in front of the circuit's own, the standard
+//! [`ATTESTATION_FIELD`] holding the [`DeviceAttestation::digest`] of the
+//! platform's artifact (the Play Integrity token or the App Attest
+//! assertion).
+//!
+//! Public inputs are part of what the proof commits to, so a proof cannot
+//! be presented with another attestation. The attested circuit has its own
+//! id, `attested/<id>` (see [`attested_id`]), and its own gates, so its
+//! proofs cannot be mistaken for proofs of the wrapped circuit. A relying
+//! party that checked the artifact with Google or Apple then checks the
+//! proof with `KimchiVerifier::verify_with_attestation`. Tying the artifact's nonce to
+//! the request is up to the app, as with any use of these platforms.
+//!
+//! [`ATTESTATION_FIELD`]: super::ATTESTATION_FIELD
+
+use ark_ff::PrimeField;
+use kimchi::circuits::gate::CircuitGate;
+use mina_curves::pasta::Fp;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use zeroize::Zeroizing;
+
+use super::{
+    prefixed_id, Circuit, PublicInputField, PublicInputSchema, PublicInputType, ATTESTATION_FIELD,
+};
+use crate::builder::{Cell, CircuitBuilder, Region, WitnessLayout};
+use crate::error::{ProverError, Result};
+use crate::prover::COLUMNS;
+
+/// Domain separator of attestation digests.
+const DOMAIN: &[u8] = b"kimchi-mobile/attestation/v1";
+
+/// Prefix of the ids of attested circuits.
+pub const ATTESTED_ID_PREFIX: &str = "attested/";
+
+/// Id of the attested variant of the circuit `inner_id`, e.g.
+/// `attested/threshold`.
+pub fn attested_id(inner_id: &'static str) -> &'static str {
+    prefixed_id(ATTESTED_ID_PREFIX, inner_id)
+}
+
+/// Platform that issued an attestation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AttestationPlatform {
+    /// Android Play Integrity API.
+    PlayIntegrity,
+    /// iOS App Attest.
+    AppAttest,
+}
+
+impl AttestationPlatform {
+    fn tag(self) -> u8 {
+        match self {
+            Self::PlayIntegrity => 1,
+            Self::AppAttest => 2,
+        }
+    }
+}
+
+/// A platform attestation, identified by the SHA-256 of its artifact.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeviceAttestation {
+    pub platform: AttestationPlatform,
+    pub token_hash: [u8; 32],
+}
+
+impl DeviceAttestation {
+    /// An attestation whose artifact hashes to `token_hash`.
+    pub fn new(platform: AttestationPlatform, token_hash: [u8; 32]) -> Self {
+        Self {
+            platform,
+            token_hash,
+        }
+    }
+
+    /// An attestation with the artifact `token`, as returned by the platform.
+    pub fn from_token(platform: AttestationPlatform, token: &[u8]) -> Self {
+        Self::new(platform, Sha256::digest(token).into())
+    }
+
+    /// The public input the attestation is bound by: SHA-256 of a domain
+    /// separator, the platform and the token hash, truncated to 248 bits
+    /// to fit the field.
+    pub fn digest(&self) -> Fp {
+        let hash = Sha256::new()
+            .chain_update(DOMAIN)
+            .chain_update([self.platform.tag()])
+            .chain_update(self.token_hash)
+            .finalize();
+        Fp::from_le_bytes_mod_order(&hash[..31])
+    }
+}
+
+/// A circuit with the standard device attestation public input in front of
+/// its own.
+#[derive(Clone, Debug)]
+pub struct AttestedCircuit<C> {
+    inner: C,
+}
+
+impl<C: Circuit> AttestedCircuit<C> {
+    pub fn new(inner: C) -> Self {
+        Self { inner }
+    }
+
+    /// The wrapped circuit.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Generate the circuit gates: the attestation's public row, then the
+    /// wrapped circuit's gates a row down.
+    pub fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        let mut cs = CircuitBuilder::new(0);
+        cs.public_input();
+        cs.extend_gates(&self.inner.gates(), 0);
+        cs.build()
+    }
+
+    /// Extend a witness and public inputs of the wrapped circuit with
+    /// `attestation`.
+    pub fn bind_witness(
+        &self,
+        witness: [Vec<Fp>; COLUMNS],
+        public_inputs: &[Fp],
+        attestation: &DeviceAttestation,
+    ) -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)> {
+        let witness = Zeroizing::new(witness);
+        if public_inputs.len() != self.inner.num_public_inputs() {
+            return Err(ProverError::PublicInputCount {
+                expected: self.inner.num_public_inputs(),
+                actual: public_inputs.len(),
+            });
+        }
+        let digest = attestation.digest();
+        let mut layout = WitnessLayout::for_gates(&self.gates());
+        layout.set(Cell::new(0, 0), digest);
+        layout.copy_rows(&witness, 1)?;
+        let public_inputs = std::iter::once(digest)
+            .chain(public_inputs.iter().copied())
+            .collect();
+        Ok((layout.into_columns(), public_inputs))
+    }
+}
+
+impl<C: Circuit> Circuit for AttestedCircuit<C> {
+    /// `attested/` and the wrapped circuit's id.
+    fn id(&self) -> &'static str {
+        attested_id(self.inner.id())
+    }
+
+    fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        AttestedCircuit::gates(self)
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.inner
+            .regions()
+            .into_iter()
+            .map(|region| Region {
+                name: region.name,
+                rows: region.rows.start + 1..region.rows.end + 1,
+            })
+            .collect()
+    }
+
+    fn public_input_schema(&self) -> PublicInputSchema {
+        let mut schema = self.inner.public_input_schema();
+        schema.fields.insert(
+            0,
+            PublicInputField {
+                name: ATTESTATION_FIELD.into(),
+                ty: PublicInputType::Field,
+            },
+        );
+        schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    use crate::circuits::{ThresholdCircuit, ThresholdOperator, ValueWidth, WideThresholdCircuit};
+    use crate::index_registry::VerifierIndexRegistry;
+    use crate::prover::{KimchiProver, ProverConfig};
+    use crate::types::ProofEnvelope;
+    use crate::verifier::KimchiVerifier;
+
+    #[test]
+    fn test_attestation_digest() {
+        let token = b"play integrity token";
+        let attestation = DeviceAttestation::from_token(AttestationPlatform::PlayIntegrity, token);
+        assert_eq!(
+            attestation,
+            DeviceAttestation::new(
+                AttestationPlatform::PlayIntegrity,
+                Sha256::digest(token).into()
+            )
+        );
+        assert_ne!(
+            attestation.digest(),
+            DeviceAttestation::from_token(AttestationPlatform::AppAttest, token).digest()
+        );
+        assert_ne!(
+            attestation.digest(),
+            DeviceAttestation::from_token(AttestationPlatform::PlayIntegrity, b"other").digest()
+        );
+    }
+
+    #[test]
+    fn test_prove_attested() {
+        let circuit = AttestedCircuit::new(ThresholdCircuit::new(10));
+        let schema = circuit.public_input_schema();
+        assert!(schema.has_attestation());
+        assert_eq!(schema.offset("threshold"), Some(1));
+        assert_eq!(circuit.num_public_inputs(), 3);
+        assert_eq!(circuit.gates().len(), circuit.inner().gates().len() + 1);
+
+        let attestation =
+            DeviceAttestation::from_token(AttestationPlatform::AppAttest, b"assertion");
+        let (witness, public_inputs) = circuit.inner().generate_witness(0).unwrap();
+        assert!(circuit
+            .bind_witness(witness.clone(), &public_inputs[1..], &attestation)
+            .is_err());
+        let (witness, public_inputs) = circuit
+            .bind_witness(witness, &public_inputs, &attestation)
+            .unwrap();
+        assert_eq!(public_inputs[0], attestation.digest());
+
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover
+            .setup(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+        let proof = prover.prove(&prover_index, witness).unwrap();
+        let envelope =
+            ProofEnvelope::new(circuit.id(), 1, 10, &proof, &public_inputs, &verifier_index)
+                .unwrap()
+                .with_circuit_hash(circuit.circuit_hash());
+        assert_eq!(envelope.circuit_id, "attested/threshold");
+
+        let bytes = rmp_serde::to_vec(&verifier_index).unwrap();
+        let mut verifier = KimchiVerifier::new(10);
+        verifier.add_verifier_index(&bytes).unwrap();
+        assert!(matches!(
+            verifier.verify_with_attestation(&envelope, &attestation),
+            Err(ProverError::VerifierIndexMismatch)
+        ));
+        verifier
+            .add_circuit_verifier_index(circuit.id(), &bytes)
+            .unwrap();
+        assert!(verifier
+            .verify_with_attestation(&envelope, &attestation)
+            .unwrap());
+        let other = DeviceAttestation::from_token(AttestationPlatform::AppAttest, b"replayed");
+        let err = verifier
+            .verify_with_attestation(&envelope, &other)
+            .unwrap_err();
+        assert!(matches!(err, ProverError::AttestationMismatch));
+        assert_eq!(err.code(), 403);
+
+        // Swapping in the other digest breaks the proof
+        let mut tampered = envelope.clone();
+        tampered.public_inputs[0] = crate::FieldElement(other.digest());
+        assert!(!matches!(
+            verifier.verify_with_attestation(&tampered, &other),
+            Ok(true)
+        ));
+    }
+
+    #[test]
+    fn test_unattested_circuit_refused() {
+        // A field-width threshold proof whose first public input, the
+        // threshold, is the attestation digest
+        let attestation =
+            DeviceAttestation::from_token(AttestationPlatform::PlayIntegrity, b"token");
+        let circuit = WideThresholdCircuit::new(
+            attestation.digest(),
+            ThresholdOperator::Le,
+            ValueWidth::Field,
+        )
+        .unwrap();
+        let (witness, public_inputs) = circuit.generate_witness(Fp::from(1u64)).unwrap();
+        assert_eq!(public_inputs[0], attestation.digest());
+
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 11,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover
+            .setup(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+        let proof = prover.prove(&prover_index, witness).unwrap();
+        let envelope =
+            ProofEnvelope::new(circuit.id(), 1, 11, &proof, &public_inputs, &verifier_index)
+                .unwrap()
+                .with_circuit_hash(circuit.circuit_hash());
+
+        let registry = Arc::new(VerifierIndexRegistry::new());
+        registry
+            .register(circuit.id(), 1, rmp_serde::to_vec(&verifier_index).unwrap())
+            .unwrap();
+        let mut verifier = KimchiVerifier::new(11);
+        verifier.set_registry(registry);
+        verifier.resolve_verifier_index(&envelope).unwrap();
+        assert!(verifier.verify_envelope(&envelope).unwrap());
+        assert!(matches!(
+            verifier.verify_with_attestation(&envelope, &attestation),
+            Err(ProverError::AttestationMismatch)
+        ));
+
+        // Nor does relabeling it as an attested circuit: its verifier index
+        // is registered for the unattested one
+        let attested = AttestedCircuit::new(circuit);
+        let mut relabeled = envelope.with_circuit_hash(attested.circuit_hash());
+        relabeled.circuit_id = attested.id().into();
+        assert!(matches!(
+            verifier.verify_with_attestation(&relabeled, &attestation),
+            Err(ProverError::VerifierIndexMismatch)
+        ));
+    }
+}
@@ -0,0 +1,610 @@
+//! Circom circuits - imports R1CS constraints compiled by circom.
+//!
+//! Reads the constraints of a circom `.r1cs` file and the assignment of a
+//! `.wtns` file (circom's binary formats, version 1 and 2) and compiles each
+//! rank-1 constraint `A * B = C` into Kimchi generic gates:
+//!
+//! - linear combinations of more than one signal are summed into a cell,
+//!   two signals per generic gate;
+//! - the product is one generic gate `a * b - c = 0`, with single-signal
+//!   combinations used in place and their coefficients folded in;
+//! - every use of a signal is wired to its first cell.
+//!
+//! The circuit must be compiled for the Pallas scalar field, i.e. with
+//! `circom --prime pallas`, since constraints reduced modulo another prime
+//! mean something else here.
+//!
+//! Public inputs: circom's public signals in order, the outputs then the
+//! public inputs (wires `1..=n_public`).
+//!
+//! Private inputs: the rest of the `.wtns` assignment.
+
+use ark_ff::{BigInteger, One, PrimeField, Zero};
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GENERIC_COEFFS;
+use mina_curves::pasta::Fp;
+
+use super::{Circuit, PublicInputSchema, PublicInputType};
+use crate::builder::{Cell, CircuitBuilder, WitnessBuilder};
+use crate::error::{ProverError, Result};
+use crate::prover::COLUMNS;
+use crate::types::FieldElement;
+
+/// A linear combination of wires; wire 0 is the constant 1.
+pub type LinearCombination = Vec<(usize, Fp)>;
+
+/// Rank-1 constraints over numbered wires, as in a circom `.r1cs` file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct R1cs {
+    /// Number of wires, including the constant wire 0.
+    pub num_wires: usize,
+    pub num_public_outputs: usize,
+    pub num_public_inputs: usize,
+    pub num_private_inputs: usize,
+    /// Constraints `A * B = C`.
+    pub constraints: Vec<[LinearCombination; 3]>,
+}
+
+/// Most wires an `.r1cs` file may declare, 2^22. The layout and the
+/// placeholder witness take a slot per wire, so the untrusted count is
+/// checked before allocating them; circuits this size are far beyond what
+/// a device can prove.
+pub const MAX_CIRCOM_WIRES: usize = 1 << 22;
+
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+const SECTION_HEADER: u32 = 1;
+const SECTION_CONSTRAINTS: u32 = 2;
+const SECTION_WITNESS: u32 = 2;
+
+impl R1cs {
+    /// Parse a `.r1cs` file.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let sections = sections(bytes, R1CS_MAGIC, "circom r1cs")?;
+        let mut header = Reader::new(section(&sections, SECTION_HEADER, "circom r1cs")?);
+        let field_size = check_prime(&mut header, "circom r1cs")?;
+        let num_wires = header.u32()? as usize;
+        let num_public_outputs = header.u32()? as usize;
+        let num_public_inputs = header.u32()? as usize;
+        let num_private_inputs = header.u32()? as usize;
+        let _num_labels = header.u64()?;
+        let num_constraints = header.u32()? as usize;
+        if num_wires > MAX_CIRCOM_WIRES {
+            return Err(ProverError::decode(
+                "circom r1cs",
+                "more wires than MAX_CIRCOM_WIRES",
+            ));
+        }
+        if num_wires == 0 || 1 + num_public_outputs + num_public_inputs > num_wires {
+            return Err(ProverError::decode(
+                "circom r1cs",
+                "more public signals than wires",
+            ));
+        }
+
+        let mut reader = Reader::new(section(&sections, SECTION_CONSTRAINTS, "circom r1cs")?);
+        let mut constraints = Vec::new();
+        for _ in 0..num_constraints {
+            let mut lcs: [LinearCombination; 3] = Default::default();
+            for lc in lcs.iter_mut() {
+                for _ in 0..reader.u32()? {
+                    let wire = reader.u32()? as usize;
+                    if wire >= num_wires {
+                        return Err(ProverError::decode("circom r1cs", "wire out of range"));
+                    }
+                    lc.push((wire, reader.field(field_size)?));
+                }
+            }
+            constraints.push(lcs);
+        }
+
+        Ok(Self {
+            num_wires,
+            num_public_outputs,
+            num_public_inputs,
+            num_private_inputs,
+            constraints,
+        })
+    }
+
+    /// Number of public signals, outputs first.
+    pub fn num_public(&self) -> usize {
+        self.num_public_outputs + self.num_public_inputs
+    }
+
+    /// One field per public signal, `output_<i>` then `input_<i>`.
+    pub fn public_input_schema(&self) -> PublicInputSchema {
+        let outputs = (0..self.num_public_outputs).map(|i| format!("output_{}", i));
+        let inputs = (0..self.num_public_inputs).map(|i| format!("input_{}", i));
+        outputs
+            .chain(inputs)
+            .fold(PublicInputSchema::new(), |schema, name| {
+                schema.field(name, PublicInputType::Field)
+            })
+    }
+
+    /// Lay out the constraints with wire values `values`.
+    fn layout(&self, values: &[Fp]) -> (CircuitBuilder, WitnessBuilder) {
+        let mut layout = Layout {
+            cs: CircuitBuilder::new(0),
+            w: WitnessBuilder::new(0),
+            values,
+            cells: vec![None; self.num_wires],
+        };
+
+        // Public signals first, as Kimchi reads public inputs from the
+        // first rows
+        for wire in 1..=self.num_public() {
+            let cell = layout.cs.public_input();
+            layout.place(wire, cell);
+        }
+
+        for [a, b, c] in &self.constraints {
+            layout.constraint(a, b, c);
+        }
+
+        (layout.cs, layout.w)
+    }
+
+    /// Compile the constraints to generic gates.
+    pub fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        let (cs, _) = self.layout(&vec![Fp::zero(); self.num_wires]);
+        cs.build()
+    }
+
+    /// Generate witness from the value of every wire, wire 0 being 1.
+    ///
+    /// Returns the witness columns and the public inputs.
+    pub fn generate_witness(&self, values: &[Fp]) -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)> {
+        if values.len() != self.num_wires {
+            return Err(ProverError::InvalidInput(format!(
+                "expected values for {} wires, got {}",
+                self.num_wires,
+                values.len()
+            )));
+        }
+        if values[0] != Fp::one() {
+            return Err(ProverError::InvalidInput(
+                "wire 0 of an R1CS assignment must be 1".into(),
+            ));
+        }
+        let (_, w) = self.layout(values);
+        let public_inputs = values[1..=self.num_public()].to_vec();
+        Ok((w.columns(), public_inputs))
+    }
+}
+
+/// Parse a `.wtns` file into the value of every wire.
+pub fn read_witness(bytes: &[u8]) -> Result<Vec<Fp>> {
+    let sections = sections(bytes, WTNS_MAGIC, "circom witness")?;
+    let mut header = Reader::new(section(&sections, SECTION_HEADER, "circom witness")?);
+    let field_size = check_prime(&mut header, "circom witness")?;
+    let num_values = header.u32()? as usize;
+
+    let mut reader = Reader::new(section(&sections, SECTION_WITNESS, "circom witness")?);
+    (0..num_values).map(|_| reader.field(field_size)).collect()
+}
+
+/// Split a circom binary file into its `(type, contents)` sections.
+fn sections<'a>(
+    bytes: &'a [u8],
+    magic: &[u8; 4],
+    what: &'static str,
+) -> Result<Vec<(u32, &'a [u8])>> {
+    let mut reader = Reader { bytes, what };
+    if reader.bytes(4)? != magic {
+        return Err(ProverError::decode(what, "bad magic number"));
+    }
+    let _version = reader.u32()?;
+    let num_sections = reader.u32()?;
+    let mut sections = Vec::new();
+    for _ in 0..num_sections {
+        let typ = reader.u32()?;
+        let len = usize::try_from(reader.u64()?)
+            .map_err(|_| ProverError::decode(what, "section too large"))?;
+        sections.push((typ, reader.bytes(len)?));
+    }
+    Ok(sections)
+}
+
+fn section<'a>(
+    sections: &[(u32, &'a [u8])],
+    typ: u32,
+    what: &'static str,
+) -> Result<(&'a [u8], &'static str)> {
+    sections
+        .iter()
+        .find(|(t, _)| *t == typ)
+        .map(|(_, bytes)| (*bytes, what))
+        .ok_or_else(|| ProverError::decode(what, format!("missing section {}", typ)))
+}
+
+/// Read the field size and prime of a header, which must be Fp's.
+fn check_prime(header: &mut Reader, what: &'static str) -> Result<usize> {
+    let field_size = header.u32()? as usize;
+    let modulus = Fp::MODULUS.to_bytes_le();
+    if header.bytes(field_size)? != modulus.as_slice() {
+        return Err(ProverError::InvalidInput(format!(
+            "{} is not over the Pallas scalar field; compile with `circom --prime pallas`",
+            what
+        )));
+    }
+    Ok(field_size)
+}
+
+/// Little-endian reader over a file or section.
+struct Reader<'a> {
+    bytes: &'a [u8],
+    what: &'static str,
+}
+
+impl<'a> Reader<'a> {
+    fn new((bytes, what): (&'a [u8], &'static str)) -> Self {
+        Self { bytes, what }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.bytes.len() < len {
+            return Err(ProverError::decode(self.what, "unexpected end of data"));
+        }
+        let (head, tail) = self.bytes.split_at(len);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()))
+    }
+
+    fn field(&mut self, field_size: usize) -> Result<Fp> {
+        let what = self.what;
+        FieldElement::from_bytes_le(self.bytes(field_size)?)
+            .map(|f| f.0)
+            .map_err(|e| ProverError::decode(what, e))
+    }
+}
+
+/// A circom circuit compiled to Kimchi gates.
+#[derive(Clone, Debug)]
+pub struct CircomCircuit {
+    pub r1cs: R1cs,
+}
+
+impl CircomCircuit {
+    pub fn new(r1cs: R1cs) -> Self {
+        Self { r1cs }
+    }
+
+    /// Parse a `.r1cs` file.
+    pub fn from_r1cs(bytes: &[u8]) -> Result<Self> {
+        R1cs::from_bytes(bytes).map(Self::new)
+    }
+
+    /// Generate the circuit gates.
+    pub fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        self.r1cs.gates()
+    }
+
+    /// Generate witness from the wire values of a `.wtns` file (see
+    /// [`read_witness`]).
+    ///
+    /// Returns the witness columns and the public inputs.
+    pub fn generate_witness(&self, values: &[Fp]) -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)> {
+        self.r1cs.generate_witness(values)
+    }
+}
+
+impl Circuit for CircomCircuit {
+    fn id(&self) -> &'static str {
+        "circom"
+    }
+
+    fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        CircomCircuit::gates(self)
+    }
+
+    fn public_input_schema(&self) -> PublicInputSchema {
+        self.r1cs.public_input_schema()
+    }
+}
+
+/// A value in a generic gate slot.
+#[derive(Clone, Copy)]
+enum Operand {
+    /// A circom wire, wired to its other uses.
+    Wire(usize),
+    /// A cell already laid out, wired to it.
+    Cell(Cell),
+    /// A new cell holding a value.
+    Value(Fp),
+}
+
+/// Compilation state: the builders and the first cell of each wire.
+struct Layout<'a> {
+    cs: CircuitBuilder,
+    w: WitnessBuilder,
+    values: &'a [Fp],
+    cells: Vec<Option<Cell>>,
+}
+
+impl Layout<'_> {
+    /// Use `cell` for `wire`, wired to the wire's first cell.
+    fn place(&mut self, wire: usize, cell: Cell) {
+        self.w.set(cell, self.values[wire]);
+        match self.cells[wire] {
+            Some(first) => self.cs.copy(cell, first),
+            None => self.cells[wire] = Some(cell),
+        }
+    }
+
+    /// One generic constraint `c0*l + c1*r + c2*o + c3*l*r + c4 = 0`, with
+    /// the slots not given left unused.
+    fn generic(&mut self, coeffs: [Fp; GENERIC_COEFFS], slots: [Option<Operand>; 3]) -> [Cell; 3] {
+        let cells = self.cs.generic_coeffs_packed(coeffs);
+        for (cell, slot) in cells.into_iter().zip(slots) {
+            match slot {
+                Some(Operand::Wire(wire)) => self.place(wire, cell),
+                Some(Operand::Cell(from)) => {
+                    let value = self.w.get(from);
+                    self.w.set(cell, value);
+                    self.cs.copy(cell, from);
+                }
+                Some(Operand::Value(value)) => self.w.set(cell, value),
+                None => {}
+            }
+        }
+        cells
+    }
+
+    /// Split `lc` into its constant and its signal terms.
+    fn split(lc: &LinearCombination) -> (Fp, Vec<(usize, Fp)>) {
+        let mut constant = Fp::zero();
+        let mut terms = Vec::with_capacity(lc.len());
+        for &(wire, coeff) in lc {
+            if wire == 0 {
+                constant += coeff;
+            } else if !coeff.is_zero() {
+                terms.push((wire, coeff));
+            }
+        }
+        (constant, terms)
+    }
+
+    /// A cell holding `constant + sum(coeff * wire)`, for nonempty `terms`,
+    /// adding two signals in the first gate and one in each after.
+    fn sum(&mut self, constant: Fp, terms: &[(usize, Fp)]) -> Cell {
+        let (first, rest) = terms.split_at(terms.len().min(2));
+        let mut value = constant;
+        let mut coeffs = [Fp::zero(); GENERIC_COEFFS];
+        let mut slots = [None, None, None];
+        for (k, &(wire, coeff)) in first.iter().enumerate() {
+            value += coeff * self.values[wire];
+            coeffs[k] = coeff;
+            slots[k] = Some(Operand::Wire(wire));
+        }
+        coeffs[2] = -Fp::one();
+        coeffs[4] = constant;
+        slots[2] = Some(Operand::Value(value));
+        let [_, _, mut acc] = self.generic(coeffs, slots);
+
+        for &(wire, coeff) in rest {
+            value += coeff * self.values[wire];
+            let slots = [
+                Some(Operand::Cell(acc)),
+                Some(Operand::Wire(wire)),
+                Some(Operand::Value(value)),
+            ];
+            [_, _, acc] = self.generic(
+                [Fp::one(), coeff, -Fp::one(), Fp::zero(), Fp::zero()],
+                slots,
+            );
+        }
+        acc
+    }
+
+    /// `lc` as a coefficient times an operand, summing it into a cell
+    /// unless it is a single signal; `None` for a constant.
+    fn reduce(&mut self, lc: &LinearCombination) -> Option<(Operand, Fp)> {
+        let (constant, terms) = Self::split(lc);
+        match terms.as_slice() {
+            [] => None,
+            [(wire, coeff)] if constant.is_zero() => Some((Operand::Wire(*wire), *coeff)),
+            _ => Some((Operand::Cell(self.sum(constant, &terms)), Fp::one())),
+        }
+    }
+
+    /// Constrain `a * b = c`.
+    fn constraint(&mut self, a: &LinearCombination, b: &LinearCombination, c: &LinearCombination) {
+        let (ka, ta) = Self::split(a);
+        let (kb, tb) = Self::split(b);
+
+        // A constant factor makes the constraint linear: k * X - C = 0
+        if ta.is_empty() || tb.is_empty() {
+            let (k, kx, tx) = if ta.is_empty() {
+                (ka, kb, tb)
+            } else {
+                (kb, ka, ta)
+            };
+            let (kc, tc) = Self::split(c);
+            let terms: Vec<(usize, Fp)> = tx
+                .iter()
+                .map(|&(wire, coeff)| (wire, k * coeff))
+                .chain(tc.iter().map(|&(wire, coeff)| (wire, -coeff)))
+                .filter(|(_, coeff)| !coeff.is_zero())
+                .collect();
+            self.assert_zero(k * kx - kc, &terms);
+            return;
+        }
+
+        let (a, sa) = self.reduce(a).expect("A has signals");
+        let (b, sb) = self.reduce(b).expect("B has signals");
+        let mut coeffs = [Fp::zero(); GENERIC_COEFFS];
+        coeffs[3] = sa * sb;
+        let o = match self.reduce(c) {
+            Some((operand, sc)) => {
+                coeffs[2] = -sc;
+                Some(operand)
+            }
+            None => {
+                coeffs[4] = -Self::split(c).0;
+                None
+            }
+        };
+        self.generic(coeffs, [Some(a), Some(b), o]);
+    }
+
+    /// Constrain `constant + sum(coeff * wire) = 0`.
+    fn assert_zero(&mut self, constant: Fp, terms: &[(usize, Fp)]) {
+        let mut coeffs = [Fp::zero(); GENERIC_COEFFS];
+        let mut slots = [None, None, None];
+        if terms.len() <= 2 {
+            coeffs[4] = constant;
+            for (k, &(wire, coeff)) in terms.iter().enumerate() {
+                coeffs[k] = coeff;
+                slots[k] = Some(Operand::Wire(wire));
+            }
+        } else {
+            let (last, rest) = terms.split_last().expect("terms are nonempty");
+            let acc = self.sum(constant, rest);
+            coeffs[0] = Fp::one();
+            coeffs[1] = last.1;
+            slots = [Some(Operand::Cell(acc)), Some(Operand::Wire(last.0)), None];
+        }
+        self.generic(coeffs, slots);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::soundness::{SoundnessHarness, SoundnessTarget};
+    use crate::testing::GadgetHarness;
+
+    fn file(magic: &[u8; 4], sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = magic.to_vec();
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend((sections.len() as u32).to_le_bytes());
+        for (typ, contents) in sections {
+            bytes.extend(typ.to_le_bytes());
+            bytes.extend((contents.len() as u64).to_le_bytes());
+            bytes.extend(contents);
+        }
+        bytes
+    }
+
+    fn prime_header(prime: &[u8]) -> Vec<u8> {
+        let mut header = (prime.len() as u32).to_le_bytes().to_vec();
+        header.extend(prime);
+        header
+    }
+
+    fn field(value: Fp) -> Vec<u8> {
+        value.into_bigint().to_bytes_le()
+    }
+
+    fn lc(terms: &[(usize, i64)]) -> Vec<u8> {
+        let mut bytes = (terms.len() as u32).to_le_bytes().to_vec();
+        for &(wire, coeff) in terms {
+            bytes.extend((wire as u32).to_le_bytes());
+            bytes.extend(field(Fp::from(coeff)));
+        }
+        bytes
+    }
+
+    // Wires: 0 one, 1 out, 2 x (public), 3 y, 4 x*y, 5 x^2 - y^2, 6 y^2
+    fn example_r1cs(prime: &[u8]) -> Vec<u8> {
+        let mut header = prime_header(prime);
+        for n in [7u32, 1, 1, 1] {
+            header.extend(n.to_le_bytes());
+        }
+        header.extend(7u64.to_le_bytes());
+        header.extend(5u32.to_le_bytes());
+
+        let constraints = [
+            [lc(&[(2, 1)]), lc(&[(3, 1)]), lc(&[(4, 1)])],
+            [lc(&[(4, 1), (0, 3)]), lc(&[(0, 1)]), lc(&[(1, 1)])],
+            [lc(&[(2, 1), (3, 1)]), lc(&[(2, 1), (3, -1)]), lc(&[(5, 1)])],
+            [lc(&[(3, 1)]), lc(&[(3, 1)]), lc(&[(6, 1)])],
+            [lc(&[(2, 1)]), lc(&[(2, 1)]), lc(&[(5, 1), (6, 1)])],
+        ]
+        .concat()
+        .concat();
+        file(R1CS_MAGIC, &[(1, header), (2, constraints)])
+    }
+
+    fn example_wtns(values: &[u64]) -> Vec<u8> {
+        let mut header = prime_header(&Fp::MODULUS.to_bytes_le());
+        header.extend((values.len() as u32).to_le_bytes());
+        let witness = values.iter().flat_map(|v| field(Fp::from(*v))).collect();
+        file(WTNS_MAGIC, &[(1, header), (2, witness)])
+    }
+
+    #[test]
+    fn test_parse_r1cs() {
+        let r1cs = R1cs::from_bytes(&example_r1cs(&Fp::MODULUS.to_bytes_le())).unwrap();
+        assert_eq!(r1cs.num_wires, 7);
+        assert_eq!(r1cs.num_public(), 2);
+        assert_eq!(r1cs.constraints.len(), 5);
+        assert_eq!(
+            r1cs.constraints[1][0],
+            vec![(4, Fp::one()), (0, Fp::from(3u64))]
+        );
+
+        let mut other_prime = Fp::MODULUS.to_bytes_le();
+        other_prime[0] ^= 1;
+        assert!(matches!(
+            R1cs::from_bytes(&example_r1cs(&other_prime)),
+            Err(ProverError::InvalidInput(_))
+        ));
+        let bytes = example_r1cs(&Fp::MODULUS.to_bytes_le());
+        assert!(R1cs::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut huge = bytes.clone();
+        let wires_at = 4 + 4 + 4 + 4 + 8 + 4 + 32;
+        assert_eq!(huge[wires_at..wires_at + 4], 7u32.to_le_bytes());
+        huge[wires_at..wires_at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            R1cs::from_bytes(&huge),
+            Err(ProverError::Decode { .. })
+        ));
+        assert!(R1cs::from_bytes(&example_wtns(&[1])).is_err());
+        assert!(read_witness(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_prove_circom() {
+        let circuit = CircomCircuit::from_r1cs(&example_r1cs(&Fp::MODULUS.to_bytes_le())).unwrap();
+        assert_eq!(circuit.num_public_inputs(), 2);
+
+        let values = read_witness(&example_wtns(&[1, 18, 5, 3, 15, 16, 9])).unwrap();
+        let (witness, public_inputs) = circuit.generate_witness(&values).unwrap();
+        assert_eq!(public_inputs, vec![Fp::from(18u64), Fp::from(5u64)]);
+        assert!(circuit.generate_witness(&values[1..]).is_err());
+        assert!(circuit.generate_witness(&[Fp::zero(); 7]).is_err());
+
+        let harness = GadgetHarness::new();
+        let proof = harness
+            .prove_circuit(circuit.gates(), witness, circuit.num_public_inputs())
+            .unwrap();
+        assert!(proof.verifies(&public_inputs));
+
+        // An assignment violating x*x = x^2 - y^2 + y^2
+        let bad = read_witness(&example_wtns(&[1, 18, 5, 3, 15, 17, 9])).unwrap();
+        let (witness, _) = circuit.generate_witness(&bad).unwrap();
+        assert!(harness
+            .prove_circuit(circuit.gates(), witness, circuit.num_public_inputs())
+            .is_err());
+
+        let (witness, public_inputs) = circuit.generate_witness(&values).unwrap();
+        SoundnessHarness::new()
+            .with_max_cell_mutations(16)
+            .assert_sound(&SoundnessTarget::from_circuit(
+                &circuit,
+                witness,
+                public_inputs,
+            ));
+    }
+}
@@ -0,0 +1,195 @@
+//! Gate list export, for review outside Rust.
+//!
+//! [`Circuit::export_gates_json`](super::Circuit::export_gates_json) dumps a
+//! circuit as
+//!
+//! ```json
+//! {
+//!   "format": "kimchi-mobile/gates/1",
+//!   "circuitId": "threshold",
+//!   "circuitHash": "9f86d0...",
+//!   "publicInputSize": 2,
+//!   "rows": 4,
+//!   "gates": [
+//!     {
+//!       "row": 0,
+//!       "type": "Generic",
+//!       "wires": [{ "row": 0, "col": 0 }, ...],
+//!       "coeffs": ["1", "0", ...],
+//!       "region": "path"
+//!     }
+//!   ],
+//!   "regions": [{ "name": "path", "start": 1, "end": 3 }]
+//! }
+//! ```
+//!
+//! - `circuitHash` is the hex [`circuit_hash`](super::circuit_hash), as in
+//!   proof envelopes.
+//! - `publicInputSize` is the number of leading public input rows.
+//! - Each gate has its Kimchi gate type, the seven cells its permutation
+//!   wires cycle to, and its coefficients as decimal strings. `region` is the
+//!   innermost labeled region holding the row, omitted if none does.
+//! - `regions` are the labeled row ranges, `start` inclusive and `end`
+//!   exclusive, in the order they were closed; nested labels are
+//!   dot-separated.
+//!
+//! Field names and encodings follow the o1js constraint system JSON, so an
+//! export loads back with `O1jsCircuit::from_json` into the same gates.
+
+use kimchi::circuits::gate::{CircuitGate, GateType};
+use kimchi::circuits::wires::Wire;
+use mina_curves::pasta::Fp;
+use serde::{Deserialize, Serialize};
+
+use super::circuit_hash;
+use crate::builder::Region;
+use crate::error::Result;
+use crate::types::FieldElement;
+
+/// Value of the `format` field.
+pub const GATES_JSON_FORMAT: &str = "kimchi-mobile/gates/1";
+
+/// A circuit's gate list as exported.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GatesDocument {
+    pub format: String,
+    pub circuit_id: String,
+    pub circuit_hash: String,
+    pub public_input_size: usize,
+    pub rows: usize,
+    pub gates: Vec<GateEntry>,
+    pub regions: Vec<RegionEntry>,
+}
+
+/// One gate of a [`GatesDocument`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GateEntry {
+    pub row: usize,
+    #[serde(rename = "type")]
+    pub typ: GateType,
+    pub wires: Vec<Wire>,
+    pub coeffs: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+/// One labeled row range of a [`GatesDocument`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionEntry {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl GatesDocument {
+    /// Describe `gates`, labeled by `regions`.
+    pub fn new(
+        circuit_id: &str,
+        gates: &[CircuitGate<Fp>],
+        public_input_size: usize,
+        regions: &[Region],
+    ) -> Self {
+        // Innermost region of each row: narrower regions are written last
+        let mut labels: Vec<Option<&str>> = vec![None; gates.len()];
+        let mut by_width: Vec<&Region> = regions.iter().collect();
+        by_width.sort_by_key(|region| std::cmp::Reverse(region.rows.len()));
+        for region in by_width {
+            for row in region.rows.clone().filter(|row| *row < gates.len()) {
+                labels[row] = Some(&region.name);
+            }
+        }
+
+        let hash = circuit_hash(gates, public_input_size);
+        let entries = gates
+            .iter()
+            .zip(labels)
+            .enumerate()
+            .map(|(row, (gate, region))| GateEntry {
+                row,
+                typ: gate.typ,
+                wires: gate.wires.to_vec(),
+                coeffs: gate
+                    .coeffs
+                    .iter()
+                    .map(|c| FieldElement(*c).to_decimal())
+                    .collect(),
+                region: region.map(String::from),
+            })
+            .collect::<Vec<_>>();
+
+        Self {
+            format: GATES_JSON_FORMAT.to_string(),
+            circuit_id: circuit_id.to_string(),
+            circuit_hash: hex::encode(hash),
+            public_input_size,
+            rows: entries.len(),
+            gates: entries,
+            regions: regions
+                .iter()
+                .map(|region| RegionEntry {
+                    name: region.name.clone(),
+                    start: region.rows.start,
+                    end: region.rows.end,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::{
+        AttestedCircuit, Circuit, MerkleMembershipCircuit, O1jsCircuit, ThresholdCircuit,
+    };
+
+    #[test]
+    fn test_export_loads_as_o1js() {
+        let circuit = ThresholdCircuit::new(0);
+        let json = circuit.export_gates_json().unwrap();
+        let document = GatesDocument::from_json(&json).unwrap();
+        assert_eq!(document.format, GATES_JSON_FORMAT);
+        assert_eq!(document.circuit_id, "threshold");
+        assert_eq!(document.circuit_hash, hex::encode(circuit.circuit_hash()));
+        assert_eq!(document.rows, circuit.gates().len());
+        assert!(document.regions.is_empty());
+        assert!(!json.contains("\"region\""));
+
+        let loaded = O1jsCircuit::from_json(&json).unwrap();
+        assert_eq!(loaded.gates(), circuit.gates());
+        assert_eq!(loaded.circuit_hash(), circuit.circuit_hash());
+    }
+
+    #[test]
+    fn test_regions() {
+        let circuit = MerkleMembershipCircuit::new(2).unwrap();
+        let document = GatesDocument::from_json(&circuit.export_gates_json().unwrap()).unwrap();
+        let path = document
+            .regions
+            .iter()
+            .find(|region| region.name == "path")
+            .unwrap();
+        assert_eq!(document.gates[0].region, None);
+        assert_eq!(document.gates[path.start].region.as_deref(), Some("path"));
+        assert_eq!(path.end, document.rows);
+
+        // Wrapping shifts the regions with the gates
+        let attested = AttestedCircuit::new(circuit);
+        let regions = attested.regions();
+        assert_eq!(regions[0].rows.start, path.start + 1);
+        let document = GatesDocument::new(attested.id(), &attested.gates(), 2, &regions);
+        assert_eq!(
+            document.gates[path.start + 1].region.as_deref(),
+            Some("path")
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! Merkle membership circuit - proves a private leaf is in a tree with a public root.
+//!
+//! Proves: "I know a leaf L and a path of siblings from L to `root`", with
+//! nodes hashed by the circuit's `HashFunction` (see `gadgets::merkle`).
+//! The leaf, its siblings and its position stay private, so the leaf should
+//! commit to something only the prover knows, e.g.
+//! `PoseidonWitness::hash(&[secret])`.
+//!
+//! Poseidon trees, as built by Mina tooling, have field element nodes.
+//! SHA-256 and Keccak-256 trees, e.g. built by Ethereum tooling, have
+//! 32-byte nodes; a level of those costs a byte hash, so only shallow trees
+//! fit a mobile SRS.
+//!
+//! Public inputs:
+//! - root: The Merkle root, a field element or 32 bytes
+//!
+//! Private inputs:
+//! - leaf: The leaf value
+//! - path: One `MerkleStep` (or `DigestStep` for byte hashes) per level,
+//!   from the leaf up
+
+use ark_ff::Zero;
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GENERIC_COEFFS;
+use mina_curves::pasta::Fp;
+
+use super::{Circuit, PublicInputSchema, PublicInputType};
+use crate::builder::{CircuitBuilder, Region, WitnessBuilder};
+use crate::error::{ProverError, Result};
+use crate::gadgets::digest::{DigestCells, DigestGadget, DigestWitness, HashFunction};
+use crate::gadgets::merkle::{DigestStep, MerkleGadget, MerkleStep, MerkleWitness};
+use crate::prover::COLUMNS;
+use crate::types::Bytes32;
+
+/// Deepest tree the circuit supports.
+pub const MAX_MERKLE_DEPTH: usize = 32;
+
+/// A circuit that proves membership of a private leaf in a tree of fixed depth.
+#[derive(Clone, Debug)]
+pub struct MerkleMembershipCircuit {
+    /// Number of levels between the leaf and the root.
+    pub depth: usize,
+    /// Hash of the tree's nodes.
+    pub hash: HashFunction,
+}
+
+impl MerkleMembershipCircuit {
+    /// Create a circuit for Poseidon trees of `depth` levels.
+    pub fn new(depth: usize) -> Result<Self> {
+        Self::with_hash(depth, HashFunction::Poseidon)
+    }
+
+    /// Create a circuit for trees of `depth` levels hashed with `hash`.
+    pub fn with_hash(depth: usize, hash: HashFunction) -> Result<Self> {
+        if depth == 0 || depth > MAX_MERKLE_DEPTH {
+            return Err(ProverError::InvalidInput(format!(
+                "Merkle depth must be between 1 and {}, got {}",
+                MAX_MERKLE_DEPTH, depth
+            )));
+        }
+        Ok(Self { depth, hash })
+    }
+
+    /// Lay out the circuit for `leaf` at `path`.
+    fn layout(&self, leaf: Fp, path: &[MerkleStep]) -> (CircuitBuilder, WitnessBuilder, Fp) {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let root = MerkleWitness::root(leaf, path);
+
+        // Row 0: root (public input)
+        let root_cell = cs.public_input();
+        w.set(root_cell, root);
+
+        // Row 1: leaf, constrained only through the path
+        let [leaf_cell, _, _] = cs.generic_coeffs([Fp::zero(); GENERIC_COEFFS]);
+        w.set(leaf_cell, leaf);
+
+        let computed = cs.region("path", |cs| {
+            MerkleGadget::root_cells(cs, &mut w, leaf_cell, path)
+        });
+        cs.copy(computed, root_cell);
+
+        (cs, w, root)
+    }
+
+    /// Lay out the circuit for the 32-byte `leaf` at `path`.
+    fn digest_layout(
+        &self,
+        leaf: &Bytes32,
+        path: &[DigestStep],
+    ) -> (CircuitBuilder, WitnessBuilder, Vec<Fp>) {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let root = DigestWitness::pack(&MerkleWitness::digest_root(self.hash, leaf, path));
+
+        // Rows 0-1: root (public input)
+        let root_cells = cs.public_inputs(root.len());
+        for (cell, value) in root_cells.iter().zip(&root) {
+            w.set(*cell, *value);
+        }
+
+        let leaf_bits = cs.region("leaf", |cs| {
+            DigestGadget::alloc_bytes(cs, &mut w, leaf.as_ref())
+        });
+        let leaf_bits: DigestCells = std::array::from_fn(|k| leaf_bits[k]);
+        let computed = cs.region("path", |cs| {
+            MerkleGadget::digest_root_cells(cs, &mut w, self.hash, &leaf_bits, path)
+        });
+        let packed = cs.region("root", |cs| DigestGadget::pack_cells(cs, &mut w, &computed));
+        for (computed, public) in packed.into_iter().zip(root_cells) {
+            cs.copy(computed, public);
+        }
+
+        (cs, w, root)
+    }
+
+    /// Lay out the circuit for a placeholder leaf and path.
+    fn builder(&self) -> CircuitBuilder {
+        if self.hash.is_bytes() {
+            let path = vec![
+                DigestStep {
+                    sibling: Bytes32::zero(),
+                    is_right: false,
+                };
+                self.depth
+            ];
+            let (cs, _, _) = self.digest_layout(&Bytes32::zero(), &path);
+            return cs;
+        }
+
+        let path = vec![
+            MerkleStep {
+                sibling: Fp::zero(),
+                is_right: false,
+            };
+            self.depth
+        ];
+        let (cs, _, _) = self.layout(Fp::zero(), &path);
+        cs
+    }
+
+    /// Generate the circuit gates.
+    pub fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        self.builder().build()
+    }
+
+    /// Generate witness for `leaf` at `path`.
+    ///
+    /// Returns the witness columns and the public inputs.
+    pub fn generate_witness(
+        &self,
+        leaf: Fp,
+        path: &[MerkleStep],
+    ) -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)> {
+        if self.hash.is_bytes() {
+            return Err(ProverError::InvalidInput(format!(
+                "{:?} trees have 32-byte nodes, use generate_digest_witness",
+                self.hash
+            )));
+        }
+        self.check_depth(path.len())?;
+
+        let (_, w, root) = self.layout(leaf, path);
+        Ok((w.columns(), vec![root]))
+    }
+
+    /// Generate witness for the 32-byte `leaf` at `path` in a SHA-256 or
+    /// Keccak-256 tree.
+    ///
+    /// Returns the witness columns and the public inputs, the root packed
+    /// as `DigestWitness::pack` does.
+    pub fn generate_digest_witness(
+        &self,
+        leaf: &Bytes32,
+        path: &[DigestStep],
+    ) -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)> {
+        if !self.hash.is_bytes() {
+            return Err(ProverError::InvalidInput(
+                "Poseidon trees have field element nodes, use generate_witness".to_string(),
+            ));
+        }
+        self.check_depth(path.len())?;
+
+        let (_, w, root) = self.digest_layout(leaf, path);
+        Ok((w.columns(), root))
+    }
+
+    fn check_depth(&self, levels: usize) -> Result<()> {
+        if levels != self.depth {
+            return Err(ProverError::InvalidInput(format!(
+                "expected a Merkle path of {} levels, got {}",
+                self.depth, levels
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Circuit for MerkleMembershipCircuit {
+    fn id(&self) -> &'static str {
+        "merkle_membership"
+    }
+
+    fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        MerkleMembershipCircuit::gates(self)
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.builder().regions().to_vec()
+    }
+
+    fn public_input_schema(&self) -> PublicInputSchema {
+        let ty = if self.hash.is_bytes() {
+            PublicInputType::Bytes { len: Bytes32::LEN }
+        } else {
+            PublicInputType::Field
+        };
+        PublicInputSchema::new().field("root", ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::Cell;
+    use crate::testing::GadgetHarness;
+
+    #[test]
+    fn test_depth_bounds() {
+        assert!(MerkleMembershipCircuit::new(0).is_err());
+        assert!(MerkleMembershipCircuit::new(MAX_MERKLE_DEPTH + 1).is_err());
+        let circuit = MerkleMembershipCircuit::new(4).unwrap();
+        assert_eq!(circuit.num_public_inputs(), 1);
+    }
+
+    #[test]
+    fn test_prove_membership() {
+        let leaves: Vec<Fp> = (0..8u64).map(Fp::from).collect();
+        let (root, paths) = MerkleWitness::tree(&leaves);
+        let circuit = MerkleMembershipCircuit::new(3).unwrap();
+
+        let (witness, public_inputs) = circuit.generate_witness(leaves[5], &paths[5]).unwrap();
+        assert_eq!(public_inputs, vec![root]);
+        assert_eq!(witness[0].len(), circuit.gates().len());
+        assert!(circuit.generate_witness(leaves[5], &paths[5][..2]).is_err());
+
+        let proof = GadgetHarness::new()
+            .prove_circuit(circuit.gates(), witness, circuit.num_public_inputs())
+            .unwrap();
+        assert!(proof.verifies(&[root]));
+        assert!(!proof.verifies(&[root + Fp::from(1u64)]));
+    }
+
+    #[test]
+    fn test_digest_membership() {
+        let leaves: Vec<Bytes32> = (0..2u8).map(|i| Bytes32([i; 32])).collect();
+        let (root, paths) = MerkleWitness::digest_tree(HashFunction::Keccak256, &leaves);
+        let circuit = MerkleMembershipCircuit::with_hash(1, HashFunction::Keccak256).unwrap();
+        assert_eq!(circuit.num_public_inputs(), 2);
+        assert!(circuit.generate_witness(Fp::zero(), &[]).is_err());
+        assert!(MerkleMembershipCircuit::new(1)
+            .unwrap()
+            .generate_digest_witness(&leaves[0], &paths[0])
+            .is_err());
+
+        let (witness, public_inputs) = circuit
+            .generate_digest_witness(&leaves[1], &paths[1])
+            .unwrap();
+        assert_eq!(public_inputs, DigestWitness::pack(&root));
+        assert_eq!(witness[0].len(), circuit.gates().len());
+
+        let (cs, mut w, _) = circuit.digest_layout(&leaves[1], &paths[1]);
+        assert!(cs.check_witness(&w).is_ok());
+        // Another root does not open
+        w.set(Cell::new(0, 0), public_inputs[0] + Fp::from(1u64));
+        assert!(cs.check_witness(&w).is_err());
+    }
+}
@@ -3,6 +3,193 @@
 //! This module contains pre-built circuits that can be used directly,
 //! as well as serving as examples for building custom circuits.
 
+#[cfg(feature = "arkworks")]
+pub mod arkworks;
+pub mod attestation;
+pub mod circom;
+pub mod gates_json;
+pub mod merkle;
+pub mod o1js;
+pub mod registry;
+pub mod schema;
 pub mod threshold;
+pub mod timestamp;
+pub mod trust_list;
 
-pub use threshold::ThresholdCircuit;
+#[cfg(feature = "arkworks")]
+pub use arkworks::ArkworksCircuit;
+pub use attestation::{
+    attested_id, AttestationPlatform, AttestedCircuit, DeviceAttestation, ATTESTED_ID_PREFIX,
+};
+pub use circom::CircomCircuit;
+pub use gates_json::{GateEntry, GatesDocument, RegionEntry, GATES_JSON_FORMAT};
+pub use merkle::MerkleMembershipCircuit;
+pub use o1js::O1jsCircuit;
+pub use registry::CircuitInfo;
+pub use schema::{
+    NamedPublicValue, PublicInputField, PublicInputSchema, PublicInputType, PublicInputsDocument,
+    PublicValue, ATTESTATION_FIELD, TIMESTAMP_FIELD,
+};
+pub use threshold::{ThresholdCircuit, ThresholdOperator, ValueWidth, WideThresholdCircuit};
+pub use timestamp::{timestamped_id, TimestampedCircuit, TIMESTAMPED_ID_PREFIX};
+pub use trust_list::TrustedIssuerCircuit;
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use ark_ff::{BigInteger, PrimeField};
+use kimchi::circuits::gate::CircuitGate;
+use mina_curves::pasta::Fp;
+use sha2::{Digest, Sha256};
+
+use crate::builder::Region;
+use crate::error::Result;
+
+/// A circuit with a described public input.
+pub trait Circuit {
+    /// Stable identifier of the circuit family, e.g. `"threshold"`.
+    fn id(&self) -> &'static str;
+
+    /// The circuit's gates.
+    fn gates(&self) -> Vec<CircuitGate<Fp>>;
+
+    /// Names and types of the public inputs, in order.
+    fn public_input_schema(&self) -> PublicInputSchema;
+
+    /// Number of public input elements.
+    fn num_public_inputs(&self) -> usize {
+        self.public_input_schema().num_elements()
+    }
+
+    /// [`circuit_hash`] of the circuit.
+    fn circuit_hash(&self) -> [u8; 32] {
+        circuit_hash(&self.gates(), self.num_public_inputs())
+    }
+
+    /// Labeled row ranges of the gates, for diagnostics and exports; empty
+    /// if the circuit does not label its rows.
+    fn regions(&self) -> Vec<Region> {
+        Vec::new()
+    }
+
+    /// The gates, wiring and regions as JSON, in the format documented in
+    /// [`gates_json`].
+    fn export_gates_json(&self) -> Result<String> {
+        GatesDocument::new(
+            self.id(),
+            &self.gates(),
+            self.num_public_inputs(),
+            &self.regions(),
+        )
+        .to_json()
+    }
+}
+
+/// `prefix` and `id`, for the ids of wrapping circuits.
+///
+/// Ids are `&'static str`, so each is allocated once and kept, one per
+/// prefix and circuit family wrapped.
+fn prefixed_id(prefix: &'static str, id: &'static str) -> &'static str {
+    static IDS: OnceLock<Mutex<HashMap<(&'static str, &'static str), &'static str>>> =
+        OnceLock::new();
+    let mut ids = IDS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    ids.entry((prefix, id))
+        .or_insert_with(|| Box::leak(format!("{}{}", prefix, id).into_boxed_str()))
+}
+
+impl<C: Circuit + ?Sized> Circuit for Box<C> {
+    fn id(&self) -> &'static str {
+        (**self).id()
+    }
+
+    fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        (**self).gates()
+    }
+
+    fn public_input_schema(&self) -> PublicInputSchema {
+        (**self).public_input_schema()
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        (**self).regions()
+    }
+}
+
+/// SHA-256 identity of a circuit: its public input count and every gate's
+/// type, wiring and coefficients, in order.
+///
+/// Gate types are hashed by name, e.g. `Generic`, not by their position in
+/// kimchi's `GateType`, so the hash survives variants added upstream. The
+/// same gates hash the same in every process and on every platform,
+/// so subsystems agree on which circuit they mean: it is the key of
+/// compiled circuits in `CircuitCache` and `DiskCache`, identifies the
+/// registered circuits (`CircuitInfo::hash`) and is carried in proof
+/// envelopes (`ProofEnvelope::circuit_hash`).
+pub fn circuit_hash(gates: &[CircuitGate<Fp>], public_count: usize) -> [u8; 32] {
+    circuit_hasher(gates, public_count).finalize().into()
+}
+
+/// SHA-256 state after hashing a circuit as `circuit_hash` does, for keys
+/// covering more than the gates.
+pub(crate) fn circuit_hasher(gates: &[CircuitGate<Fp>], public_count: usize) -> Sha256 {
+    let mut hasher = Sha256::new();
+    hasher.update((public_count as u64).to_le_bytes());
+    hasher.update((gates.len() as u64).to_le_bytes());
+    for gate in gates {
+        let name = format!("{:?}", gate.typ);
+        hasher.update((name.len() as u64).to_le_bytes());
+        hasher.update(name);
+        for wire in &gate.wires {
+            hasher.update((wire.row as u64).to_le_bytes());
+            hasher.update((wire.col as u64).to_le_bytes());
+        }
+        hash_fields(&mut hasher, &gate.coeffs);
+    }
+    hasher
+}
+
+/// Hash a length-prefixed list of field elements.
+pub(crate) fn hash_fields(hasher: &mut Sha256, fields: &[Fp]) {
+    hasher.update((fields.len() as u64).to_le_bytes());
+    for f in fields {
+        hasher.update(f.into_bigint().to_bytes_le());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::CircuitCache;
+    use kimchi::circuits::gate::GateType;
+    use kimchi::circuits::wires::Wire;
+
+    #[test]
+    fn test_circuit_hash() {
+        let circuit = ThresholdCircuit::new(100);
+        let (gates, n) = (circuit.gates(), circuit.num_public_inputs());
+        let hash = circuit.circuit_hash();
+        assert_eq!(hash, circuit_hash(&gates, n));
+        assert_eq!(hash, CircuitCache::key(&gates, n, &[]));
+        // The threshold is a witness value, not part of the gates
+        assert_eq!(hash, ThresholdCircuit::new(7).circuit_hash());
+        assert_ne!(hash, circuit_hash(&gates, n + 1));
+        assert_ne!(hash, circuit_hash(&gates[1..], n));
+
+        let mut rewired = gates.clone();
+        rewired[0].wires.swap(0, 1);
+        assert_ne!(hash, circuit_hash(&rewired, n));
+        let mut retyped = gates.clone();
+        retyped[7].typ = GateType::Poseidon;
+        assert_ne!(hash, circuit_hash(&retyped, n));
+
+        // Pinned: the hash keys caches and is carried in envelopes
+        let zero = CircuitGate::new(GateType::Zero, Wire::for_row(0), vec![]);
+        assert_eq!(
+            hex::encode(circuit_hash(&[zero], 0)),
+            "a80739863c8d8641a83bf8b8f8722a294c65ea0236c34cdc0d1a8a7076b62962"
+        );
+    }
+}
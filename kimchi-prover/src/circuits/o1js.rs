@@ -0,0 +1,220 @@
+//! o1js circuits - imports a constraint system dumped by o1js.
+//!
+//! `Provable.constraintSystem(main)` in o1js (as well as each method of
+//! `analyzeMethods()` on a zkApp or ZkProgram) returns the compiled Kimchi
+//! gates of a TypeScript circuit; its `JSON.stringify` output is
+//!
+//! ```json
+//! {
+//!   "rows": 3,
+//!   "digest": "...",
+//!   "publicInputSize": 1,
+//!   "gates": [
+//!     { "type": "Generic", "wires": [{ "row": 0, "col": 0 }, ...], "coeffs": ["1", "0", ...] }
+//!   ]
+//! }
+//! ```
+//!
+//! with seven wires per gate and coefficients as decimal strings. The loader
+//! rebuilds the same gates, so a witness computed by o1js (or by the caller,
+//! following the same layout) proves against them natively.
+//!
+//! Public inputs: the first `publicInputSize` rows, as in o1js.
+
+use kimchi::circuits::gate::{CircuitGate, GateType};
+use kimchi::circuits::wires::{Wire, PERMUTS};
+use mina_curves::pasta::Fp;
+use serde::Deserialize;
+
+use super::{Circuit, PublicInputSchema, PublicInputType};
+use crate::error::{ProverError, Result};
+use crate::prover::COLUMNS;
+use crate::types::FieldElement;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConstraintSystemJson {
+    public_input_size: usize,
+    gates: Vec<GateJson>,
+}
+
+#[derive(Deserialize)]
+struct GateJson {
+    #[serde(rename = "type", alias = "typ")]
+    typ: GateType,
+    wires: Vec<Wire>,
+    coeffs: Vec<String>,
+}
+
+/// A circuit loaded from an o1js constraint system dump.
+#[derive(Clone, Debug)]
+pub struct O1jsCircuit {
+    gates: Vec<CircuitGate<Fp>>,
+    public_input_size: usize,
+}
+
+impl O1jsCircuit {
+    /// Load the JSON of `Provable.constraintSystem`.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let cs: ConstraintSystemJson = serde_json::from_str(json)?;
+        let num_rows = cs.gates.len();
+        if cs.public_input_size > num_rows {
+            return Err(ProverError::decode(
+                "o1js constraint system",
+                "more public inputs than rows",
+            ));
+        }
+
+        let gates = cs
+            .gates
+            .into_iter()
+            .enumerate()
+            .map(|(row, gate)| {
+                let wires: [Wire; PERMUTS] = gate.wires.try_into().map_err(|_| {
+                    ProverError::decode(
+                        "o1js constraint system",
+                        format!("gate {} does not have {} wires", row, PERMUTS),
+                    )
+                })?;
+                if let Some(wire) = wires
+                    .iter()
+                    .find(|wire| wire.row >= num_rows || wire.col >= PERMUTS)
+                {
+                    return Err(ProverError::decode(
+                        "o1js constraint system",
+                        format!("gate {} is wired to missing cell {:?}", row, wire),
+                    ));
+                }
+                let coeffs = gate
+                    .coeffs
+                    .iter()
+                    .map(|c| {
+                        FieldElement::from_decimal(c)
+                            .map(|f| f.0)
+                            .map_err(|e| ProverError::decode("o1js constraint system", e))
+                    })
+                    .collect::<Result<_>>()?;
+                Ok(CircuitGate::new(gate.typ, wires, coeffs))
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            gates,
+            public_input_size: cs.public_input_size,
+        })
+    }
+
+    /// Generate the circuit gates.
+    pub fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        self.gates.clone()
+    }
+
+    /// The public inputs of `witness`: column 0 of the public rows.
+    pub fn public_inputs(&self, witness: &[Vec<Fp>; COLUMNS]) -> Result<Vec<Fp>> {
+        if witness[0].len() < self.public_input_size {
+            return Err(ProverError::InvalidInput(format!(
+                "witness has {} rows, the circuit has {} public inputs",
+                witness[0].len(),
+                self.public_input_size
+            )));
+        }
+        Ok(witness[0][..self.public_input_size].to_vec())
+    }
+}
+
+impl Circuit for O1jsCircuit {
+    fn id(&self) -> &'static str {
+        "o1js"
+    }
+
+    fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        O1jsCircuit::gates(self)
+    }
+
+    fn public_input_schema(&self) -> PublicInputSchema {
+        (0..self.public_input_size).fold(PublicInputSchema::new(), |schema, i| {
+            schema.field(format!("input_{}", i), PublicInputType::Field)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::ThresholdCircuit;
+    use crate::testing::GadgetHarness;
+    use serde_json::json;
+
+    /// `gates` in the shape o1js dumps them.
+    fn o1js_json(gates: &[CircuitGate<Fp>], public_input_size: usize) -> serde_json::Value {
+        let gates: Vec<_> = gates
+            .iter()
+            .map(|gate| {
+                json!({
+                    "type": gate.typ,
+                    "wires": gate.wires,
+                    "coeffs": gate
+                        .coeffs
+                        .iter()
+                        .map(|c| FieldElement(*c).to_decimal())
+                        .collect::<Vec<_>>(),
+                })
+            })
+            .collect();
+        json!({
+            "rows": gates.len(),
+            "digest": "0123456789abcdef",
+            "publicInputSize": public_input_size,
+            "gates": gates,
+        })
+    }
+
+    #[test]
+    fn test_prove_o1js() {
+        let threshold = ThresholdCircuit::new(100);
+        let json = o1js_json(&threshold.gates(), threshold.num_public_inputs());
+        let circuit = O1jsCircuit::from_json(&json.to_string()).unwrap();
+        assert_eq!(circuit.gates(), threshold.gates());
+        assert_eq!(circuit.num_public_inputs(), 2);
+
+        let (witness, public_inputs) = threshold.generate_witness(0).unwrap();
+        assert_eq!(circuit.public_inputs(&witness).unwrap(), public_inputs);
+
+        let proof = GadgetHarness::new()
+            .prove_circuit(circuit.gates(), witness, circuit.num_public_inputs())
+            .unwrap();
+        assert!(proof.verifies(&public_inputs));
+    }
+
+    #[test]
+    fn test_reject_malformed_o1js() {
+        let gates = ThresholdCircuit::new(100).gates();
+        let valid = o1js_json(&gates, 2);
+
+        let mut json = valid.clone();
+        json["publicInputSize"] = json!(gates.len() + 1);
+        assert!(O1jsCircuit::from_json(&json.to_string()).is_err());
+
+        let mut json = valid.clone();
+        json["gates"][1]["wires"][0]["row"] = json!(gates.len());
+        assert!(matches!(
+            O1jsCircuit::from_json(&json.to_string()),
+            Err(ProverError::Decode { .. })
+        ));
+
+        let mut json = valid.clone();
+        json["gates"][1]["wires"] = json!([]);
+        assert!(O1jsCircuit::from_json(&json.to_string()).is_err());
+
+        let mut json = valid.clone();
+        json["gates"][0]["coeffs"][0] = json!("0x01");
+        assert!(O1jsCircuit::from_json(&json.to_string()).is_err());
+
+        let mut json = valid;
+        json["gates"][0]["type"] = json!("NotAGate");
+        assert!(matches!(
+            O1jsCircuit::from_json(&json.to_string()),
+            Err(ProverError::Json(_))
+        ));
+    }
+}
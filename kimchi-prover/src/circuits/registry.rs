@@ -0,0 +1,164 @@
+//! Registry of the built-in circuits, by id.
+//!
+//! Lets hosts look a circuit up by the id carried in envelopes and FFI
+//! calls, or by its [`circuit_hash`], and describe it with [`CircuitInfo`]
+//! before setting it up. Circuit
+//! parameters such as the threshold of [`ThresholdCircuit`] don't change the
+//! gates, so each id maps to one representative instance. The attested
+//! and timestamped variants of a registered circuit resolve by their ids,
+//! `attested/<id>` and `timestamped/<id>`.
+
+use ark_ff::Zero;
+use mina_curves::pasta::Fp;
+
+use super::{
+    circuit_hash, AttestedCircuit, Circuit, PublicInputSchema, ThresholdCircuit, ThresholdOperator,
+    TimestampedCircuit, ValueWidth, WideThresholdCircuit, ATTESTED_ID_PREFIX,
+    TIMESTAMPED_ID_PREFIX,
+};
+use crate::cost::{GadgetCost, ProvingTimeClass};
+use crate::error::{ProverError, Result};
+
+/// Ids of the registered circuits.
+pub const CIRCUIT_IDS: &[&str] = &[
+    "threshold",
+    "threshold_lt",
+    "threshold_le",
+    "threshold_gt",
+    "threshold_ge",
+    "threshold_eq",
+    "threshold_u128_lt",
+    "threshold_u128_le",
+    "threshold_u128_gt",
+    "threshold_u128_ge",
+    "threshold_u128_eq",
+    "threshold_field_lt",
+    "threshold_field_le",
+    "threshold_field_gt",
+    "threshold_field_ge",
+    "threshold_field_eq",
+];
+
+/// A representative instance of the circuit `id`.
+pub fn circuit(id: &str) -> Option<Box<dyn Circuit + Send + Sync>> {
+    if let Some(inner) = id.strip_prefix(ATTESTED_ID_PREFIX) {
+        return circuit(inner).map(|c| Box::new(AttestedCircuit::new(c)) as _);
+    }
+    if let Some(inner) = id.strip_prefix(TIMESTAMPED_ID_PREFIX) {
+        return circuit(inner).map(|c| Box::new(TimestampedCircuit::new(c)) as _);
+    }
+    match id {
+        "threshold" => Some(Box::new(ThresholdCircuit::new(0))),
+        _ => ThresholdOperator::parse_circuit_id(id).map(|(operator, width)| match width {
+            ValueWidth::U64 => Box::new(ThresholdCircuit::with_operator(0, operator))
+                as Box<dyn Circuit + Send + Sync>,
+            _ => Box::new(WideThresholdCircuit {
+                threshold: Fp::zero(),
+                operator,
+                width,
+            }),
+        }),
+    }
+}
+
+/// Id of the registered circuit whose `circuit_hash` is `hash`.
+pub fn id_for_hash(hash: &[u8; 32]) -> Option<&'static str> {
+    CIRCUIT_IDS
+        .iter()
+        .copied()
+        .find(|id| circuit(id).is_some_and(|c| c.circuit_hash() == *hash))
+}
+
+/// Static description of a circuit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitInfo {
+    pub id: String,
+    /// The circuit's `circuit_hash`.
+    pub hash: [u8; 32],
+    /// Rows, i.e. gates.
+    pub rows: usize,
+    pub public_input_schema: PublicInputSchema,
+    /// Smallest `ProverConfig::srs_log2_size` that fits the circuit.
+    pub min_srs_log2_size: usize,
+    pub cost: GadgetCost,
+}
+
+impl CircuitInfo {
+    /// Describe `circuit`.
+    pub fn new(circuit: &dyn Circuit) -> Self {
+        let gates = circuit.gates();
+        let cost = GadgetCost::from_gates(&gates);
+        Self {
+            id: circuit.id().to_string(),
+            hash: circuit_hash(&gates, circuit.num_public_inputs()),
+            rows: cost.rows,
+            public_input_schema: circuit.public_input_schema(),
+            min_srs_log2_size: cost.required_srs_log2_size(),
+            cost,
+        }
+    }
+
+    /// Describe the registered circuit `id`.
+    pub fn for_id(id: &str) -> Result<Self> {
+        circuit(id).map(|c| Self::new(c.as_ref())).ok_or_else(|| {
+            ProverError::InvalidInput(format!(
+                "unknown circuit {:?}, expected one of {:?}",
+                id, CIRCUIT_IDS
+            ))
+        })
+    }
+
+    /// Number of public input elements.
+    pub fn num_public_inputs(&self) -> usize {
+        self.public_input_schema.num_elements()
+    }
+
+    pub fn proving_time_class(&self) -> ProvingTimeClass {
+        self.cost.proving_time_class()
+    }
+
+    /// Whether the circuit fits an SRS of `2^srs_log2_size` points.
+    pub fn fits(&self, srs_log2_size: usize) -> bool {
+        self.min_srs_log2_size <= srs_log2_size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_circuit_info() {
+        for id in CIRCUIT_IDS {
+            assert_eq!(circuit(id).unwrap().id(), *id);
+        }
+
+        let info = CircuitInfo::for_id("threshold").unwrap();
+        let gates = ThresholdCircuit::new(0).gates();
+        assert_eq!(info.rows, gates.len());
+        assert_eq!(info.num_public_inputs(), 2);
+        assert_eq!(info.public_input_schema.fields[1].name, "is_valid");
+        assert_eq!(info.proving_time_class(), ProvingTimeClass::Fast);
+        assert!(info.fits(info.min_srs_log2_size));
+        assert!(!info.fits(info.min_srs_log2_size - 1));
+        assert_eq!(id_for_hash(&info.hash), Some("threshold"));
+        assert_eq!(id_for_hash(&[0; 32]), None);
+
+        let attested = circuit("attested/threshold_u128_lt").unwrap();
+        assert_eq!(attested.id(), "attested/threshold_u128_lt");
+        assert!(attested.public_input_schema().has_attestation());
+        assert_ne!(
+            attested.circuit_hash(),
+            circuit("threshold_u128_lt").unwrap().circuit_hash()
+        );
+        assert!(circuit("attested/unknown").is_none());
+        let timestamped = circuit("timestamped/threshold").unwrap();
+        assert_eq!(timestamped.id(), "timestamped/threshold");
+        assert!(timestamped.public_input_schema().has_timestamp());
+
+        assert!(matches!(
+            CircuitInfo::for_id("unknown"),
+            Err(ProverError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,375 @@
+//! Typed public-input schemas.
+//!
+//! A schema lists a circuit's public inputs in order with a name and a type,
+//! so relying parties can encode and interpret the public input vector
+//! without knowing the circuit's row layout.
+
+use ark_ff::{BigInteger, PrimeField};
+use mina_curves::pasta::Fp;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ProverError, Result};
+use crate::gadgets::poseidon::{PoseidonWitness, BYTES_PER_ELEMENT};
+use crate::types::FieldElement;
+
+/// Name of the standard timestamp field: the prover-asserted time of the
+/// proof in Unix seconds, a `U64` in the last public input.
+///
+/// Relying parties check it with `KimchiVerifier::verify_with_max_age`.
+pub const TIMESTAMP_FIELD: &str = "timestamp";
+
+/// Name of the standard device attestation field: the digest of a platform
+/// attestation, a `Field` in the first public input.
+///
+/// Circuits get it from `AttestedCircuit`; relying parties check it with
+/// `KimchiVerifier::verify_with_attestation`.
+pub const ATTESTATION_FIELD: &str = "attestation";
+
+/// Type of a public input field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum PublicInputType {
+    /// Any field element.
+    Field,
+    /// 0 or 1.
+    Bool,
+    /// An integer below 2^64.
+    U64,
+    /// `len` bytes, packed little-endian 31 bytes per element.
+    Bytes { len: usize },
+}
+
+impl PublicInputType {
+    /// Number of field elements the type occupies.
+    pub fn num_elements(&self) -> usize {
+        match self {
+            Self::Bytes { len } => len.div_ceil(BYTES_PER_ELEMENT),
+            _ => 1,
+        }
+    }
+}
+
+/// A decoded public input value.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "value")]
+pub enum PublicValue {
+    Field(FieldElement),
+    Bool(bool),
+    U64(u64),
+    Bytes(Vec<u8>),
+}
+
+/// A named public input field.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInputField {
+    pub name: String,
+    #[serde(flatten)]
+    pub ty: PublicInputType,
+}
+
+/// Ordered, named and typed description of a circuit's public inputs.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInputSchema {
+    pub fields: Vec<PublicInputField>,
+}
+
+impl PublicInputSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a field.
+    pub fn field(mut self, name: impl Into<String>, ty: PublicInputType) -> Self {
+        self.fields.push(PublicInputField {
+            name: name.into(),
+            ty,
+        });
+        self
+    }
+
+    /// Append the standard [`TIMESTAMP_FIELD`]. Call it last, so the
+    /// timestamp is the last public input.
+    pub fn with_timestamp(self) -> Self {
+        self.field(TIMESTAMP_FIELD, PublicInputType::U64)
+    }
+
+    /// Whether the last field is the standard timestamp.
+    pub fn has_timestamp(&self) -> bool {
+        self.fields
+            .last()
+            .is_some_and(|f| f.name == TIMESTAMP_FIELD && f.ty == PublicInputType::U64)
+    }
+
+    /// Whether the first field is the standard device attestation.
+    pub fn has_attestation(&self) -> bool {
+        self.fields
+            .first()
+            .is_some_and(|f| f.name == ATTESTATION_FIELD && f.ty == PublicInputType::Field)
+    }
+
+    /// Number of field elements in the public input vector.
+    pub fn num_elements(&self) -> usize {
+        self.fields.iter().map(|f| f.ty.num_elements()).sum()
+    }
+
+    /// Position of the first element of the field `name`.
+    pub fn offset(&self, name: &str) -> Option<usize> {
+        let mut offset = 0;
+        for field in &self.fields {
+            if field.name == name {
+                return Some(offset);
+            }
+            offset += field.ty.num_elements();
+        }
+        None
+    }
+
+    /// Encode values, given in schema order, into the public input vector.
+    pub fn encode(&self, values: &[PublicValue]) -> Result<Vec<Fp>> {
+        if values.len() != self.fields.len() {
+            return Err(ProverError::InvalidInput(format!(
+                "expected {} public input values, got {}",
+                self.fields.len(),
+                values.len()
+            )));
+        }
+
+        let mut elements = Vec::with_capacity(self.num_elements());
+        for (field, value) in self.fields.iter().zip(values) {
+            match (field.ty, value) {
+                (PublicInputType::Field, PublicValue::Field(f)) => elements.push(f.0),
+                (PublicInputType::Bool, PublicValue::Bool(b)) => elements.push(Fp::from(*b)),
+                (PublicInputType::U64, PublicValue::U64(v)) => elements.push(Fp::from(*v)),
+                (PublicInputType::Bytes { len }, PublicValue::Bytes(bytes))
+                    if bytes.len() == len =>
+                {
+                    elements.extend(
+                        bytes
+                            .chunks(BYTES_PER_ELEMENT)
+                            .map(PoseidonWitness::pack_bytes),
+                    );
+                }
+                _ => {
+                    return Err(ProverError::InvalidInput(format!(
+                        "public input {:?} does not match type {:?}",
+                        field.name, field.ty
+                    )))
+                }
+            }
+        }
+        Ok(elements)
+    }
+
+    /// Decode a public input vector into named values, in schema order.
+    pub fn decode(&self, elements: &[Fp]) -> Result<Vec<(String, PublicValue)>> {
+        if elements.len() != self.num_elements() {
+            return Err(ProverError::PublicInputCount {
+                expected: self.num_elements(),
+                actual: elements.len(),
+            });
+        }
+
+        let mut rest = elements;
+        let mut values = Vec::with_capacity(self.fields.len());
+        for field in &self.fields {
+            let (head, tail) = rest.split_at(field.ty.num_elements());
+            rest = tail;
+            let invalid = || {
+                ProverError::InvalidInput(format!(
+                    "public input {:?} is not a valid {:?}",
+                    field.name, field.ty
+                ))
+            };
+
+            let value = match field.ty {
+                PublicInputType::Field => PublicValue::Field(FieldElement(head[0])),
+                PublicInputType::Bool => {
+                    let bytes = small_value(head[0], 1).ok_or_else(invalid)?;
+                    PublicValue::Bool(bytes[0] == 1)
+                }
+                PublicInputType::U64 => {
+                    let bytes = small_value(head[0], 8).ok_or_else(invalid)?;
+                    PublicValue::U64(u64::from_le_bytes(std::array::from_fn(|i| bytes[i])))
+                }
+                PublicInputType::Bytes { len } => {
+                    let mut bytes = Vec::with_capacity(len);
+                    for (i, element) in head.iter().enumerate() {
+                        let chunk_len = BYTES_PER_ELEMENT.min(len - i * BYTES_PER_ELEMENT);
+                        let chunk = small_value(*element, chunk_len).ok_or_else(invalid)?;
+                        bytes.extend_from_slice(&chunk[..chunk_len]);
+                    }
+                    PublicValue::Bytes(bytes)
+                }
+            };
+            values.push((field.name.clone(), value));
+        }
+        Ok(values)
+    }
+}
+
+/// A decoded public input with its name.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NamedPublicValue {
+    pub name: String,
+    #[serde(flatten)]
+    pub value: PublicValue,
+}
+
+/// A proof's public inputs decoded by its circuit's schema, for consumers
+/// that read them by name rather than by position:
+///
+/// ```json
+/// {
+///   "circuit_id": "threshold",
+///   "inputs": [
+///     { "name": "threshold", "type": "u64", "value": 18 },
+///     { "name": "is_valid", "type": "bool", "value": true }
+///   ]
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PublicInputsDocument {
+    pub circuit_id: String,
+    /// Inputs in schema order.
+    pub inputs: Vec<NamedPublicValue>,
+}
+
+impl PublicInputsDocument {
+    /// Decode `elements` with `schema`.
+    pub fn decode(
+        circuit_id: impl Into<String>,
+        schema: &PublicInputSchema,
+        elements: &[Fp],
+    ) -> Result<Self> {
+        let inputs = schema
+            .decode(elements)?
+            .into_iter()
+            .map(|(name, value)| NamedPublicValue { name, value })
+            .collect();
+        Ok(Self {
+            circuit_id: circuit_id.into(),
+            inputs,
+        })
+    }
+
+    /// The value of the input `name`.
+    pub fn get(&self, name: &str) -> Option<&PublicValue> {
+        self.inputs
+            .iter()
+            .find(|input| input.name == name)
+            .map(|input| &input.value)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// Little-endian bytes of `value` if it fits in `num_bytes` bytes
+/// (for `num_bytes == 1`, if it is 0 or 1).
+fn small_value(value: Fp, num_bytes: usize) -> Option<Vec<u8>> {
+    let bytes = value.into_bigint().to_bytes_le();
+    if bytes[num_bytes..].iter().any(|b| *b != 0) || (num_bytes == 1 && bytes[0] > 1) {
+        return None;
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> PublicInputSchema {
+        PublicInputSchema::new()
+            .field("min_age", PublicInputType::U64)
+            .field("rp_id", PublicInputType::Bytes { len: 40 })
+            .field("is_valid", PublicInputType::Bool)
+            .field("nullifier", PublicInputType::Field)
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let schema = schema();
+        let values = vec![
+            PublicValue::U64(18),
+            PublicValue::Bytes((0..40).collect()),
+            PublicValue::Bool(true),
+            PublicValue::Field(FieldElement::from_u64(12345)),
+        ];
+
+        let elements = schema.encode(&values).unwrap();
+        assert_eq!(elements.len(), schema.num_elements());
+        assert_eq!(elements.len(), 5);
+        assert_eq!(schema.offset("is_valid"), Some(3));
+        assert_eq!(elements[3], Fp::from(1u64));
+
+        assert!(!schema.has_timestamp());
+        let timestamped = schema.clone().with_timestamp();
+        assert!(timestamped.has_timestamp());
+        assert_eq!(timestamped.offset(TIMESTAMP_FIELD), Some(5));
+
+        let decoded = schema.decode(&elements).unwrap();
+        assert_eq!(decoded[1].0, "rp_id");
+        assert_eq!(
+            decoded.into_iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            values
+        );
+    }
+
+    #[test]
+    fn test_rejects_mismatches() {
+        let schema = schema();
+        assert!(schema.encode(&[PublicValue::U64(18)]).is_err());
+        assert!(schema
+            .encode(&[
+                PublicValue::Bool(true),
+                PublicValue::Bytes(vec![0; 40]),
+                PublicValue::Bool(true),
+                PublicValue::Field(FieldElement::from_u64(0)),
+            ])
+            .is_err());
+
+        let mut elements = vec![Fp::from(0u64); 5];
+        elements[3] = Fp::from(2u64);
+        assert!(schema.decode(&elements).is_err());
+        assert!(schema.decode(&elements[..4]).is_err());
+    }
+
+    #[test]
+    fn test_public_inputs_document() {
+        let schema = schema();
+        let values = vec![
+            PublicValue::U64(18),
+            PublicValue::Bytes(vec![7; 40]),
+            PublicValue::Bool(false),
+            PublicValue::Field(FieldElement::from_u64(1)),
+        ];
+        let elements = schema.encode(&values).unwrap();
+
+        let document = PublicInputsDocument::decode("age", &schema, &elements).unwrap();
+        let json = document.to_json().unwrap();
+        assert!(json.starts_with("{\"circuit_id\":\"age\""));
+        assert!(json.contains("{\"name\":\"min_age\",\"type\":\"u64\",\"value\":18}"));
+        assert!(json.contains("{\"name\":\"is_valid\",\"type\":\"bool\",\"value\":false}"));
+        assert_eq!(PublicInputsDocument::from_json(&json).unwrap(), document);
+        assert_eq!(document.get("min_age"), Some(&PublicValue::U64(18)));
+        assert_eq!(document.get("missing"), None);
+
+        assert!(PublicInputsDocument::decode("age", &schema, &elements[..2]).is_err());
+    }
+
+    #[test]
+    fn test_schema_json() {
+        let schema = schema();
+        let json = serde_json::to_string(&schema).unwrap();
+        assert!(json.contains("\"name\":\"rp_id\",\"type\":\"bytes\",\"len\":40"));
+        assert_eq!(
+            serde_json::from_str::<PublicInputSchema>(&json).unwrap(),
+            schema
+        );
+    }
+}
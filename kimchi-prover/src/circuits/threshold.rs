@@ -9,25 +9,197 @@
 //!
 //! Private inputs:
 //! - value: The secret value being compared
+//!
+//! [`ThresholdCircuit::with_operator`] compares with `<`, `<=`, `>`, `>=`
+//! or `==` instead, with the same public inputs; `is_valid` is then whether
+//! `value op threshold` holds. These circuits range check the value and the
+//! threshold to 64 bits and share one comparison core
+//! (`ComparisonGadget::less_than_cells`): `>` swaps its operands, `>=` and
+//! `<=` negate `<` and `>`. Each operator is its own circuit, with its own
+//! id, e.g. `threshold_ge`; the original `threshold` circuit keeps its
+//! gates, so existing verifier indices stay valid.
+//!
+//! [`WideThresholdCircuit`] compares values that don't fit a u64, such as
+//! token balances: integers below 2^128, range checked like the 64-bit
+//! values, or whole field elements, split into 128-bit limbs checked to be
+//! below the modulus and compared limb by limb. Their ids carry the width,
+//! e.g. `threshold_u128_ge`, and the threshold is a field element public
+//! input.
 
-use ark_ff::{One, Zero};
+use std::cmp::Ordering;
+
+use ark_ff::{BigInteger, One, PrimeField, Zero};
 use kimchi::circuits::gate::{CircuitGate, GateType};
+use kimchi::circuits::polynomials::generic::GENERIC_COEFFS;
 use kimchi::circuits::wires::Wire;
 use mina_curves::pasta::Fp;
 
-use crate::error::Result;
+use super::{Circuit, PublicInputSchema, PublicInputType};
+use crate::builder::{Cell, CircuitBuilder, Region, WitnessBuilder, WitnessLayout};
+use crate::cost::{CostReport, GadgetCost};
+use crate::error::{ProverError, Result};
+use crate::gadgets::boolean::BooleanGadget;
+use crate::gadgets::comparison::ComparisonGadget;
 use crate::prover::COLUMNS;
 
+/// Values an operator circuit compares.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ValueWidth {
+    /// Integers below 2^64.
+    U64,
+    /// Integers below 2^128.
+    U128,
+    /// Any field element, read as an integer below the modulus.
+    Field,
+}
+
+impl ValueWidth {
+    pub const ALL: [Self; 3] = [Self::U64, Self::U128, Self::Field];
+
+    /// Bits of the widest value.
+    pub fn bits(self) -> usize {
+        match self {
+            Self::U64 => 64,
+            Self::U128 => 128,
+            Self::Field => Fp::MODULUS_BIT_SIZE as usize,
+        }
+    }
+
+    /// Whether `value` has this width.
+    pub fn contains(self, value: Fp) -> bool {
+        value.into_bigint().num_bits() as usize <= self.bits()
+    }
+
+    /// Public input type of a threshold of this width.
+    fn input_type(self) -> PublicInputType {
+        match self {
+            Self::U64 => PublicInputType::U64,
+            Self::U128 | Self::Field => PublicInputType::Field,
+        }
+    }
+}
+
+/// Relation an operator circuit proves between the value and the threshold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ThresholdOperator {
+    /// value < threshold
+    Lt,
+    /// value <= threshold
+    Le,
+    /// value > threshold
+    Gt,
+    /// value >= threshold
+    Ge,
+    /// value == threshold
+    Eq,
+}
+
+impl ThresholdOperator {
+    pub const ALL: [Self; 5] = [Self::Lt, Self::Le, Self::Gt, Self::Ge, Self::Eq];
+
+    /// Whether `value op threshold`.
+    pub fn holds(self, value: u64, threshold: u64) -> bool {
+        self.holds_ordering(value.cmp(&threshold))
+    }
+
+    /// Whether `value op threshold`, for `ordering` of value to threshold.
+    pub fn holds_ordering(self, ordering: Ordering) -> bool {
+        match self {
+            Self::Lt => ordering.is_lt(),
+            Self::Le => ordering.is_le(),
+            Self::Gt => ordering.is_gt(),
+            Self::Ge => ordering.is_ge(),
+            Self::Eq => ordering.is_eq(),
+        }
+    }
+
+    /// Id of the 64-bit circuit comparing with this operator.
+    pub fn circuit_id(self) -> &'static str {
+        self.circuit_id_for(ValueWidth::U64)
+    }
+
+    /// Id of the circuit comparing values of `width` with this operator.
+    pub fn circuit_id_for(self, width: ValueWidth) -> &'static str {
+        let ids = match width {
+            ValueWidth::U64 => [
+                "threshold_lt",
+                "threshold_le",
+                "threshold_gt",
+                "threshold_ge",
+                "threshold_eq",
+            ],
+            ValueWidth::U128 => [
+                "threshold_u128_lt",
+                "threshold_u128_le",
+                "threshold_u128_gt",
+                "threshold_u128_ge",
+                "threshold_u128_eq",
+            ],
+            ValueWidth::Field => [
+                "threshold_field_lt",
+                "threshold_field_le",
+                "threshold_field_gt",
+                "threshold_field_ge",
+                "threshold_field_eq",
+            ],
+        };
+        ids[self as usize]
+    }
+
+    /// The operator of the 64-bit circuit `id`, if it is an operator circuit.
+    pub fn from_circuit_id(id: &str) -> Option<Self> {
+        match Self::parse_circuit_id(id) {
+            Some((operator, ValueWidth::U64)) => Some(operator),
+            _ => None,
+        }
+    }
+
+    /// The operator and width of the circuit `id`, if it is an operator
+    /// circuit of any width.
+    pub fn parse_circuit_id(id: &str) -> Option<(Self, ValueWidth)> {
+        ValueWidth::ALL.into_iter().find_map(|width| {
+            Self::ALL
+                .into_iter()
+                .find(|op| op.circuit_id_for(width) == id)
+                .map(|op| (op, width))
+        })
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Self::Lt => "<",
+            Self::Le => "<=",
+            Self::Gt => ">",
+            Self::Ge => ">=",
+            Self::Eq => "==",
+        }
+    }
+}
+
 /// A circuit that proves a private value is below a public threshold.
+#[derive(Clone, Debug)]
 pub struct ThresholdCircuit {
     /// The public threshold value
     pub threshold: u64,
+    /// Comparison of an operator circuit; `None` for the original circuit.
+    pub operator: Option<ThresholdOperator>,
 }
 
 impl ThresholdCircuit {
     /// Create a new threshold circuit.
     pub fn new(threshold: u64) -> Self {
-        Self { threshold }
+        Self {
+            threshold,
+            operator: None,
+        }
+    }
+
+    /// Create a circuit proving whether `value op threshold`.
+    pub fn with_operator(threshold: u64, operator: ThresholdOperator) -> Self {
+        Self {
+            threshold,
+            operator: Some(operator),
+        }
     }
 
     /// Get the number of public inputs for this circuit.
@@ -43,6 +215,12 @@ impl ThresholdCircuit {
     /// 3. Computes whether value < threshold
     /// 4. Outputs the result as a public input
     pub fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        if let Some(operator) = self.operator {
+            let (cs, _, _) =
+                operator_layout(operator, ValueWidth::U64, self.threshold.into(), 0.into());
+            return cs.build();
+        }
+
         let mut gates = Vec::new();
 
         // Row 0: Public input for threshold
@@ -109,10 +287,27 @@ impl ThresholdCircuit {
         gates
     }
 
+    /// Static cost of the circuit's gates.
+    pub fn cost_report(&self) -> CostReport {
+        let mut report = CostReport::new();
+        report.add("threshold", GadgetCost::from_gates(&self.gates()));
+        report
+    }
+
     /// Generate witness for the circuit given a private value.
     ///
     /// Returns the witness columns and the public inputs.
     pub fn generate_witness(&self, value: u64) -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)> {
+        if let Some(operator) = self.operator {
+            let (_, w, public_inputs) = operator_layout(
+                operator,
+                ValueWidth::U64,
+                self.threshold.into(),
+                value.into(),
+            );
+            return Ok((w.columns(), public_inputs));
+        }
+
         let threshold_fp = Fp::from(self.threshold);
         let value_fp = Fp::from(value);
         let is_valid = if value < self.threshold { 1u64 } else { 0u64 };
@@ -125,37 +320,224 @@ impl ThresholdCircuit {
             Fp::zero()
         };
 
-        // Initialize witness columns
-        let num_rows = 8;
-        let mut witness: [Vec<Fp>; COLUMNS] = std::array::from_fn(|_| vec![Fp::zero(); num_rows]);
+        let mut witness = WitnessLayout::for_gates(&self.gates());
 
         // Row 0: threshold (public input)
-        witness[0][0] = threshold_fp;
+        witness.set(Cell::new(0, 0), threshold_fp);
 
         // Row 1: is_valid (public input)
-        witness[0][1] = is_valid_fp;
+        witness.set(Cell::new(1, 0), is_valid_fp);
 
         // Row 2: value (private)
-        witness[0][2] = value_fp;
+        witness.set(Cell::new(2, 0), value_fp);
 
         // Row 3: difference calculation
         // Wire the values for the constraint: threshold - value - difference = 0
-        witness[0][3] = threshold_fp;
-        witness[1][3] = value_fp;
-        witness[2][3] = difference_fp;
+        witness.set(Cell::new(3, 0), threshold_fp);
+        witness.set(Cell::new(3, 1), value_fp);
+        witness.set(Cell::new(3, 2), difference_fp);
 
         // Row 4: boolean constraint for is_valid
         // is_valid * (1 - is_valid) = 0
-        witness[0][4] = is_valid_fp;
-        witness[1][4] = Fp::one() - is_valid_fp;
+        witness.set(Cell::new(4, 0), is_valid_fp);
+        witness.set(Cell::new(4, 1), Fp::one() - is_valid_fp);
 
         // Public inputs: [threshold, is_valid]
         let public_inputs = vec![threshold_fp, is_valid_fp];
 
-        Ok((witness, public_inputs))
+        Ok((witness.into_columns(), public_inputs))
     }
 }
 
+impl Circuit for ThresholdCircuit {
+    fn id(&self) -> &'static str {
+        match self.operator {
+            Some(operator) => operator.circuit_id(),
+            None => "threshold",
+        }
+    }
+
+    fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        ThresholdCircuit::gates(self)
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        match self.operator {
+            Some(operator) => {
+                operator_layout(operator, ValueWidth::U64, self.threshold.into(), 0.into())
+                    .0
+                    .regions()
+                    .to_vec()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn public_input_schema(&self) -> PublicInputSchema {
+        operator_schema(ValueWidth::U64)
+    }
+
+    fn num_public_inputs(&self) -> usize {
+        ThresholdCircuit::num_public_inputs(self)
+    }
+}
+
+/// An operator circuit over values wider than u64.
+#[derive(Clone, Debug)]
+pub struct WideThresholdCircuit {
+    /// The public threshold, of `width`
+    pub threshold: Fp,
+    pub operator: ThresholdOperator,
+    pub width: ValueWidth,
+}
+
+impl WideThresholdCircuit {
+    /// Create a circuit proving whether `value op threshold` for values of
+    /// `width`.
+    ///
+    /// Fails if the threshold is wider.
+    pub fn new(threshold: Fp, operator: ThresholdOperator, width: ValueWidth) -> Result<Self> {
+        if !width.contains(threshold) {
+            return Err(ProverError::InvalidInput(format!(
+                "threshold does not fit {} bits",
+                width.bits()
+            )));
+        }
+        Ok(Self {
+            threshold,
+            operator,
+            width,
+        })
+    }
+
+    /// Create a circuit comparing 128-bit integers.
+    pub fn u128(threshold: u128, operator: ThresholdOperator) -> Self {
+        Self {
+            threshold: threshold.into(),
+            operator,
+            width: ValueWidth::U128,
+        }
+    }
+
+    pub fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        let (cs, _, _) = operator_layout(self.operator, self.width, self.threshold, Fp::zero());
+        cs.build()
+    }
+
+    /// Generate witness for the circuit given a private value.
+    ///
+    /// Fails if the value is wider than the circuit's width.
+    pub fn generate_witness(&self, value: Fp) -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)> {
+        if !self.width.contains(value) {
+            return Err(ProverError::InvalidInput(format!(
+                "value does not fit {} bits",
+                self.width.bits()
+            )));
+        }
+        let (_, w, public_inputs) =
+            operator_layout(self.operator, self.width, self.threshold, value);
+        Ok((w.columns(), public_inputs))
+    }
+}
+
+impl Circuit for WideThresholdCircuit {
+    fn id(&self) -> &'static str {
+        self.operator.circuit_id_for(self.width)
+    }
+
+    fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        WideThresholdCircuit::gates(self)
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        let (cs, _, _) = operator_layout(self.operator, self.width, self.threshold, Fp::zero());
+        cs.regions().to_vec()
+    }
+
+    fn public_input_schema(&self) -> PublicInputSchema {
+        operator_schema(self.width)
+    }
+
+    fn num_public_inputs(&self) -> usize {
+        2
+    }
+}
+
+fn operator_schema(width: ValueWidth) -> PublicInputSchema {
+    PublicInputSchema::new()
+        .field("threshold", width.input_type())
+        .field("is_valid", PublicInputType::Bool)
+}
+
+/// Lay out the operator circuit for values of `width`.
+fn operator_layout(
+    operator: ThresholdOperator,
+    width: ValueWidth,
+    threshold: Fp,
+    value: Fp,
+) -> (CircuitBuilder, WitnessBuilder, Vec<Fp>) {
+    let mut cs = CircuitBuilder::new(0);
+    let mut w = WitnessBuilder::new(0);
+    let ordering = value.into_bigint().cmp(&threshold.into_bigint());
+    let is_valid = Fp::from(operator.holds_ordering(ordering) as u64);
+
+    // Rows 0-1: threshold and is_valid (public inputs)
+    let threshold_cell = cs.public_input();
+    w.set(threshold_cell, threshold);
+    let valid_cell = cs.public_input();
+    w.set(valid_cell, is_valid);
+
+    // Row 2: value, constrained only through the range check and comparison
+    let [value_cell, _, _] = cs.generic_coeffs([Fp::zero(); GENERIC_COEFFS]);
+    w.set(value_cell, value);
+    let operands = [value_cell, threshold_cell];
+
+    // Field elements are compared by limbs; equality needs none
+    let mut limbs = None;
+    match width {
+        ValueWidth::U64 | ValueWidth::U128 => cs.region("range", |cs| {
+            for cell in operands {
+                ComparisonGadget::range_check_cells(cs, &mut w, cell, width.bits());
+            }
+        }),
+        ValueWidth::Field if operator != ThresholdOperator::Eq => {
+            limbs = Some(cs.region("range", |cs| {
+                operands.map(|cell| ComparisonGadget::field_limbs_cells(cs, &mut w, cell))
+            }));
+        }
+        ValueWidth::Field => {}
+    }
+
+    let result = cs.region("compare", |cs| {
+        // Whether operands[a] < operands[b]
+        let less_than =
+            |cs: &mut CircuitBuilder, w: &mut WitnessBuilder, a: usize, b: usize| match limbs {
+                Some(limbs) => ComparisonGadget::less_than_limbs_cells(cs, w, limbs[a], limbs[b]),
+                None => {
+                    ComparisonGadget::less_than_cells(cs, w, operands[a], operands[b], width.bits())
+                }
+            };
+        match operator {
+            ThresholdOperator::Lt => less_than(cs, &mut w, 0, 1),
+            ThresholdOperator::Gt => less_than(cs, &mut w, 1, 0),
+            ThresholdOperator::Ge => {
+                let below = less_than(cs, &mut w, 0, 1);
+                BooleanGadget::not_cell(cs, &mut w, below)
+            }
+            ThresholdOperator::Le => {
+                let above = less_than(cs, &mut w, 1, 0);
+                BooleanGadget::not_cell(cs, &mut w, above)
+            }
+            ThresholdOperator::Eq => {
+                ComparisonGadget::is_equal_cells(cs, &mut w, value_cell, threshold_cell)
+            }
+        }
+    });
+    cs.copy(result, valid_cell);
+
+    (cs, w, vec![threshold, is_valid])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -167,6 +549,23 @@ mod tests {
         assert_eq!(circuit.num_public_inputs(), 2);
     }
 
+    #[test]
+    fn test_public_input_schema() {
+        use crate::circuits::PublicValue;
+
+        let circuit = ThresholdCircuit::new(100);
+        let schema = circuit.public_input_schema();
+        assert_eq!(schema.num_elements(), circuit.num_public_inputs());
+
+        let (_, public_inputs) = circuit.generate_witness(50).unwrap();
+        let decoded = schema.decode(&public_inputs).unwrap();
+        assert_eq!(decoded[0], ("threshold".to_string(), PublicValue::U64(100)));
+        assert_eq!(
+            decoded[1],
+            ("is_valid".to_string(), PublicValue::Bool(true))
+        );
+    }
+
     #[test]
     fn test_gates_generation() {
         let circuit = ThresholdCircuit::new(100);
@@ -174,6 +573,14 @@ mod tests {
         assert!(gates.len() >= 5);
     }
 
+    #[test]
+    fn test_cost_report() {
+        let circuit = ThresholdCircuit::new(100);
+        let report = circuit.cost_report();
+        assert_eq!(report.total().rows, 8);
+        assert_eq!(report.required_srs_log2_size(), 4);
+    }
+
     #[test]
     fn test_witness_below_threshold() {
         let circuit = ThresholdCircuit::new(100);
@@ -205,4 +612,103 @@ mod tests {
         // value == threshold means NOT less than, so is_valid = false
         assert_eq!(public_inputs[1], Fp::from(0u64));
     }
+
+    #[test]
+    fn test_operators() {
+        let cases = [
+            (9, 10),
+            (10, 10),
+            (11, 10),
+            (0, 0),
+            (u64::MAX, 0),
+            (0, u64::MAX),
+        ];
+        for operator in ThresholdOperator::ALL {
+            let circuit = ThresholdCircuit::with_operator(10, operator);
+            assert_eq!(circuit.id(), operator.circuit_id());
+            assert_eq!(
+                ThresholdOperator::from_circuit_id(circuit.id()),
+                Some(operator)
+            );
+            let gates = circuit.gates();
+
+            for (value, threshold) in cases {
+                let circuit = ThresholdCircuit::with_operator(threshold, operator);
+                let (mut witness, public_inputs) = circuit.generate_witness(value).unwrap();
+                let holds = operator.holds(value, threshold);
+                assert_eq!(public_inputs[1], Fp::from(holds as u64));
+                crate::diagnostics::check_witness(&gates, &witness, 2, false).unwrap_or_else(|e| {
+                    panic!("{} {} {}: {}", value, operator.symbol(), threshold, e)
+                });
+
+                // Claiming the other outcome
+                witness[0][1] = Fp::from(!holds as u64);
+                assert!(crate::diagnostics::check_witness(&gates, &witness, 2, false).is_err());
+            }
+        }
+
+        assert_eq!(ThresholdOperator::from_circuit_id("threshold"), None);
+        assert_ne!(
+            ThresholdCircuit::with_operator(10, ThresholdOperator::Lt).gates(),
+            ThresholdCircuit::new(10).gates()
+        );
+    }
+
+    #[test]
+    fn test_wide_operators() {
+        let big = Fp::from(1u128 << 100);
+        let cases = [
+            (ValueWidth::U128, big, big + Fp::one()),
+            (ValueWidth::U128, big, big),
+            (ValueWidth::U128, Fp::from(u128::MAX), Fp::zero()),
+            (ValueWidth::Field, -Fp::from(2u64), -Fp::one()),
+            (ValueWidth::Field, -Fp::one(), -Fp::one()),
+            (ValueWidth::Field, -Fp::one(), Fp::zero()),
+            (ValueWidth::Field, Fp::from(u128::MAX), big),
+        ];
+        for operator in ThresholdOperator::ALL {
+            for (width, value, threshold) in cases {
+                let circuit = WideThresholdCircuit::new(threshold, operator, width).unwrap();
+                assert_eq!(
+                    ThresholdOperator::parse_circuit_id(circuit.id()),
+                    Some((operator, width))
+                );
+                let gates = circuit.gates();
+                let (mut witness, public_inputs) = circuit.generate_witness(value).unwrap();
+                let ordering = value.into_bigint().cmp(&threshold.into_bigint());
+                let holds = operator.holds_ordering(ordering);
+                assert_eq!(public_inputs, vec![threshold, Fp::from(holds as u64)]);
+                crate::diagnostics::check_witness(&gates, &witness, 2, false)
+                    .unwrap_or_else(|e| panic!("{} {:?}: {}", operator.symbol(), width, e));
+
+                witness[0][1] = Fp::from(!holds as u64);
+                assert!(crate::diagnostics::check_witness(&gates, &witness, 2, false).is_err());
+            }
+        }
+
+        // A 64-bit wide circuit is the operator circuit
+        let wide =
+            WideThresholdCircuit::new(Fp::from(10u64), ThresholdOperator::Ge, ValueWidth::U64);
+        let circuit = ThresholdCircuit::with_operator(10, ThresholdOperator::Ge);
+        assert_eq!(wide.as_ref().unwrap().gates(), circuit.gates());
+        assert_eq!(wide.unwrap().id(), circuit.id());
+        assert_eq!(
+            ThresholdOperator::from_circuit_id("threshold_u128_ge"),
+            None
+        );
+
+        // Values wider than the circuit
+        let circuit = WideThresholdCircuit::u128(0, ThresholdOperator::Lt);
+        assert!(matches!(
+            circuit.generate_witness(Fp::from(u128::MAX) + Fp::one()),
+            Err(ProverError::InvalidInput(_))
+        ));
+        assert!(
+            WideThresholdCircuit::new(-Fp::one(), ThresholdOperator::Lt, ValueWidth::U128).is_err()
+        );
+        assert_eq!(
+            circuit.public_input_schema().fields[0].ty,
+            PublicInputType::Field
+        );
+    }
 }
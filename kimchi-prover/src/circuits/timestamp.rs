@@ -0,0 +1,191 @@
+//! Timestamped circuits - proofs that say when they were made.
+//!
+//! A [`TimestampedCircuit`] wraps any circuit with one more public input
+//! after the circuit's own, the standard [`TIMESTAMP_FIELD`] holding the
+//! prover's clock in Unix seconds. Like any public input, the timestamp is
+//! part of what the proof commits to, so it cannot be changed after the
+//! fact; it is only as trustworthy as the prover's clock.
+//!
+//! The timestamped circuit has its own id, `timestamped/<id>` (see
+//! [`timestamped_id`]), and its own gates. Relying parties check it with
+//! `KimchiVerifier::verify_with_max_age`.
+//!
+//! [`TIMESTAMP_FIELD`]: super::TIMESTAMP_FIELD
+
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use kimchi::circuits::wires::Wire;
+use mina_curves::pasta::Fp;
+use zeroize::Zeroizing;
+
+use super::{prefixed_id, Circuit, PublicInputSchema};
+use crate::builder::{Cell, Region, WitnessLayout};
+use crate::error::{ProverError, Result};
+use crate::prover::COLUMNS;
+
+/// Prefix of the ids of timestamped circuits.
+pub const TIMESTAMPED_ID_PREFIX: &str = "timestamped/";
+
+/// Id of the timestamped variant of the circuit `inner_id`, e.g.
+/// `timestamped/threshold`.
+pub fn timestamped_id(inner_id: &'static str) -> &'static str {
+    prefixed_id(TIMESTAMPED_ID_PREFIX, inner_id)
+}
+
+/// A circuit with the standard timestamp public input after its own.
+#[derive(Clone, Debug)]
+pub struct TimestampedCircuit<C> {
+    inner: C,
+}
+
+impl<C: Circuit> TimestampedCircuit<C> {
+    pub fn new(inner: C) -> Self {
+        Self { inner }
+    }
+
+    /// The wrapped circuit.
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Row of the timestamp: right after the wrapped circuit's public rows.
+    fn timestamp_row(&self) -> usize {
+        self.inner.num_public_inputs()
+    }
+
+    /// Row of the wrapped circuit's `row`.
+    fn shift(&self, row: usize) -> usize {
+        if row < self.timestamp_row() {
+            row
+        } else {
+            row + 1
+        }
+    }
+
+    /// Generate the circuit gates: the wrapped circuit's public rows, the
+    /// timestamp's public row, then the rest of the wrapped circuit's gates
+    /// a row down.
+    pub fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        let mut gates: Vec<_> = self
+            .inner
+            .gates()
+            .into_iter()
+            .map(|mut gate| {
+                for wire in gate.wires.iter_mut() {
+                    wire.row = self.shift(wire.row);
+                }
+                gate
+            })
+            .collect();
+        let row = self.timestamp_row();
+        gates.insert(
+            row,
+            CircuitGate::create_generic_gadget(Wire::for_row(row), GenericGateSpec::Pub, None),
+        );
+        gates
+    }
+
+    /// Extend a witness and public inputs of the wrapped circuit with
+    /// `timestamp`, in Unix seconds.
+    pub fn bind_witness(
+        &self,
+        witness: [Vec<Fp>; COLUMNS],
+        public_inputs: &[Fp],
+        timestamp: u64,
+    ) -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)> {
+        let witness = Zeroizing::new(witness);
+        let row = self.timestamp_row();
+        if public_inputs.len() != row {
+            return Err(ProverError::PublicInputCount {
+                expected: row,
+                actual: public_inputs.len(),
+            });
+        }
+        // Rows from..to of the wrapped circuit's witness
+        let rows = |from: usize, to: usize| {
+            Zeroizing::new(std::array::from_fn(|col| {
+                let column = &witness[col];
+                column[from.min(column.len())..to.min(column.len())].to_vec()
+            }))
+        };
+        let mut layout = WitnessLayout::for_gates(&self.gates());
+        layout.copy_rows(&rows(0, row), 0)?;
+        layout.set(Cell::new(row, 0), timestamp.into());
+        layout.copy_rows(&rows(row, usize::MAX), row + 1)?;
+        let public_inputs = public_inputs
+            .iter()
+            .copied()
+            .chain(std::iter::once(timestamp.into()))
+            .collect();
+        Ok((layout.into_columns(), public_inputs))
+    }
+}
+
+impl<C: Circuit> Circuit for TimestampedCircuit<C> {
+    /// `timestamped/` and the wrapped circuit's id.
+    fn id(&self) -> &'static str {
+        timestamped_id(self.inner.id())
+    }
+
+    fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        TimestampedCircuit::gates(self)
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.inner
+            .regions()
+            .into_iter()
+            .map(|region| Region {
+                name: region.name,
+                rows: self.shift(region.rows.start)..self.shift(region.rows.end),
+            })
+            .collect()
+    }
+
+    fn public_input_schema(&self) -> PublicInputSchema {
+        self.inner.public_input_schema().with_timestamp()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::{ThresholdCircuit, ThresholdOperator, TIMESTAMP_FIELD};
+    use crate::prover::{KimchiProver, ProverConfig};
+    use crate::types::ProofEnvelope;
+
+    #[test]
+    fn test_prove_timestamped() {
+        let circuit =
+            TimestampedCircuit::new(ThresholdCircuit::with_operator(10, ThresholdOperator::Lt));
+        assert_eq!(circuit.id(), "timestamped/threshold_lt");
+        let schema = circuit.public_input_schema();
+        assert!(schema.has_timestamp());
+        assert_eq!(schema.offset(TIMESTAMP_FIELD), Some(2));
+        assert_eq!(circuit.gates().len(), circuit.inner().gates().len() + 1);
+
+        let timestamp = 1_700_000_000;
+        let (witness, public_inputs) = circuit.inner().generate_witness(3).unwrap();
+        assert!(circuit
+            .bind_witness(witness.clone(), &public_inputs[1..], timestamp)
+            .is_err());
+        let (witness, public_inputs) = circuit
+            .bind_witness(witness, &public_inputs, timestamp)
+            .unwrap();
+        assert_eq!(public_inputs[2], timestamp.into());
+
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover
+            .setup(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+        let proof = prover.prove(&prover_index, witness).unwrap();
+        let envelope =
+            ProofEnvelope::new(circuit.id(), 1, 10, &proof, &public_inputs, &verifier_index)
+                .unwrap()
+                .with_circuit_hash(circuit.circuit_hash());
+        assert_eq!(envelope.timestamp(&schema).unwrap(), timestamp);
+    }
+}
@@ -0,0 +1,246 @@
+//! Trusted issuer circuit - proves a private issuer key is on a trust list.
+//!
+//! Proves: "I know an issuer key K and a salt s such that K is in the trust
+//! list with root `trust_list_root`, and `issuer_commitment` commits to K
+//! under s". The trust list is a [`KeyRegistry`] of accredited issuer keys,
+//! as in the EU trust-list model where dozens of issuers are acceptable;
+//! which of them signed stays private.
+//!
+//! The commitment is `CommitmentWitness::commit(&[key_digest(K)], s)`.
+//! It binds this proof to the check of the credential's signature, made
+//! against the same key in another proof or by a party the key is disclosed
+//! to. Circuits that check the signature themselves should instead call
+//! `KeyRegistryGadget::assert_registered_cells` on the key cells they use.
+//!
+//! Public inputs:
+//! - trust_list_root: Root of the trust list
+//! - issuer_commitment: Salted commitment to the issuer key digest
+//!
+//! Private inputs:
+//! - key: The issuer key limbs, e.g. the 64-bit limbs of an RSA modulus
+//! - salt: The commitment salt
+//! - path: Membership path of the key in the trust list
+
+use ark_ff::Zero;
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GENERIC_COEFFS;
+use mina_curves::pasta::Fp;
+
+use super::merkle::MAX_MERKLE_DEPTH;
+use super::{Circuit, PublicInputSchema, PublicInputType};
+use crate::builder::{Cell, CircuitBuilder, Region, WitnessBuilder};
+use crate::error::{ProverError, Result};
+use crate::gadgets::commitment::{CommitmentGadget, CommitmentWitness, Salt};
+use crate::gadgets::key_registry::{KeyRegistry, KeyRegistryGadget, KeyRegistryWitness};
+use crate::gadgets::merkle::{MerkleGadget, MerkleStep, MerkleWitness};
+use crate::gadgets::rsa::RSA_LIMBS;
+use crate::prover::COLUMNS;
+
+/// A circuit that proves a committed issuer key is on a trust list of fixed
+/// depth.
+#[derive(Clone, Debug)]
+pub struct TrustedIssuerCircuit {
+    /// Depth of the trust list, which holds up to `2^depth` keys.
+    pub depth: usize,
+    /// Number of limbs of an issuer key.
+    pub key_limbs: usize,
+}
+
+impl TrustedIssuerCircuit {
+    /// Create a circuit for trust lists of `depth` levels of keys with
+    /// `key_limbs` limbs.
+    pub fn new(depth: usize, key_limbs: usize) -> Result<Self> {
+        if depth == 0 || depth > MAX_MERKLE_DEPTH {
+            return Err(ProverError::InvalidInput(format!(
+                "trust list depth must be between 1 and {}, got {}",
+                MAX_MERKLE_DEPTH, depth
+            )));
+        }
+        if key_limbs == 0 {
+            return Err(ProverError::InvalidInput(
+                "issuer keys need at least one limb".into(),
+            ));
+        }
+        Ok(Self { depth, key_limbs })
+    }
+
+    /// Create a circuit for trust lists of RSA-2048 issuer keys.
+    pub fn rsa(depth: usize) -> Result<Self> {
+        Self::new(depth, RSA_LIMBS)
+    }
+
+    /// Lay out the circuit for `key` at `path`.
+    fn layout(
+        &self,
+        key: &[Fp],
+        salt: &Salt,
+        path: &[MerkleStep],
+    ) -> (CircuitBuilder, WitnessBuilder, Vec<Fp>) {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let digest = KeyRegistryWitness::key_digest(key);
+        let root = MerkleWitness::root(digest, path);
+        let commitment = CommitmentWitness::commit(&[digest], salt);
+
+        // Rows 0-1: trust list root and issuer commitment (public inputs)
+        let root_cell = cs.public_input();
+        w.set(root_cell, root);
+        let commitment_cell = cs.public_input();
+        w.set(commitment_cell, commitment);
+
+        // Key limbs and salt, constrained only through the hashes
+        let values: Vec<Fp> = key.iter().copied().chain([salt.value()]).collect();
+        let mut private: Vec<Cell> = Vec::with_capacity(values.len());
+        for chunk in values.chunks(3) {
+            let cells = cs.generic_coeffs([Fp::zero(); GENERIC_COEFFS]);
+            for (cell, value) in cells.into_iter().zip(chunk) {
+                w.set(cell, *value);
+                private.push(cell);
+            }
+        }
+        let (key_cells, salt_cell) = private.split_at(key.len());
+
+        let digest_cell = cs.region("key_digest", |cs| {
+            KeyRegistryGadget::key_digest_cells(cs, &mut w, key_cells)
+        });
+        let computed = cs.region("path", |cs| {
+            MerkleGadget::root_cells(cs, &mut w, digest_cell, path)
+        });
+        cs.copy(computed, root_cell);
+        cs.region("commitment", |cs| {
+            CommitmentGadget::open_cells(cs, &mut w, commitment_cell, &[digest_cell], salt_cell[0])
+        });
+
+        (cs, w, vec![root, commitment])
+    }
+
+    /// Lay out the circuit for a placeholder key and path.
+    fn builder(&self) -> CircuitBuilder {
+        let key = vec![Fp::zero(); self.key_limbs];
+        let path = vec![
+            MerkleStep {
+                sibling: Fp::zero(),
+                is_right: false,
+            };
+            self.depth
+        ];
+        let (cs, _, _) = self.layout(&key, &Salt::placeholder(), &path);
+        cs
+    }
+
+    /// Generate the circuit gates.
+    pub fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        self.builder().build()
+    }
+
+    /// Generate witness for the issuer `key`, committed under `salt`, on
+    /// `trust_list`.
+    ///
+    /// Returns the witness columns and the public inputs.
+    pub fn generate_witness(
+        &self,
+        key: &[Fp],
+        salt: &Salt,
+        trust_list: &KeyRegistry,
+    ) -> Result<([Vec<Fp>; COLUMNS], Vec<Fp>)> {
+        if key.len() != self.key_limbs {
+            return Err(ProverError::InvalidInput(format!(
+                "expected an issuer key of {} limbs, got {}",
+                self.key_limbs,
+                key.len()
+            )));
+        }
+        if trust_list.depth() != self.depth {
+            return Err(ProverError::InvalidInput(format!(
+                "expected a trust list of depth {}, got {}",
+                self.depth,
+                trust_list.depth()
+            )));
+        }
+        let path = trust_list
+            .path(KeyRegistryWitness::key_digest(key))
+            .ok_or_else(|| {
+                ProverError::InvalidInput("issuer key is not on the trust list".into())
+            })?;
+
+        let (_, w, public_inputs) = self.layout(key, salt, &path);
+        Ok((w.columns(), public_inputs))
+    }
+}
+
+impl Circuit for TrustedIssuerCircuit {
+    fn id(&self) -> &'static str {
+        "trusted_issuer"
+    }
+
+    fn gates(&self) -> Vec<CircuitGate<Fp>> {
+        TrustedIssuerCircuit::gates(self)
+    }
+
+    fn regions(&self) -> Vec<Region> {
+        self.builder().regions().to_vec()
+    }
+
+    fn public_input_schema(&self) -> PublicInputSchema {
+        PublicInputSchema::new()
+            .field("trust_list_root", PublicInputType::Field)
+            .field("issuer_commitment", PublicInputType::Field)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::rsa::RsaWitness;
+    use crate::testing::GadgetHarness;
+    use crate::types::Bytes256;
+
+    fn rsa_key(seed: u8) -> Vec<Fp> {
+        RsaWitness::bytes_to_limbs(&Bytes256::from([seed; 256]))
+            .iter()
+            .map(|limb| Fp::from(*limb))
+            .collect()
+    }
+
+    #[test]
+    fn test_prove_trusted_issuer() {
+        let mut trust_list = KeyRegistry::new(3);
+        for seed in 1..=5 {
+            trust_list
+                .insert(KeyRegistryWitness::key_digest(&rsa_key(seed)))
+                .unwrap();
+        }
+        let circuit = TrustedIssuerCircuit::rsa(3).unwrap();
+        assert_eq!(circuit.num_public_inputs(), 2);
+        assert!(TrustedIssuerCircuit::rsa(0).is_err());
+
+        let salt = Salt::random(&mut rand::rngs::OsRng);
+        let (witness, public_inputs) = circuit
+            .generate_witness(&rsa_key(4), &salt, &trust_list)
+            .unwrap();
+        assert_eq!(public_inputs[0], trust_list.root());
+        assert_eq!(
+            public_inputs[1],
+            CommitmentWitness::commit(&[KeyRegistryWitness::key_digest(&rsa_key(4))], &salt)
+        );
+        assert_eq!(witness[0].len(), circuit.gates().len());
+        assert!(matches!(
+            circuit.generate_witness(&rsa_key(9), &salt, &trust_list),
+            Err(ProverError::InvalidInput(_))
+        ));
+        assert!(circuit
+            .generate_witness(&rsa_key(4), &salt, &KeyRegistry::new(2))
+            .is_err());
+
+        let proof = GadgetHarness::new()
+            .prove_circuit(circuit.gates(), witness, circuit.num_public_inputs())
+            .unwrap();
+        assert!(proof.verifies(&public_inputs));
+
+        let mut other_list = trust_list.clone();
+        other_list
+            .insert(KeyRegistryWitness::key_digest(&rsa_key(6)))
+            .unwrap();
+        assert!(!proof.verifies(&[other_list.root(), public_inputs[1]]));
+    }
+}
@@ -0,0 +1,447 @@
+//! Static cost reporting for gadgets and circuits.
+//!
+//! Costs are computed from the gates a gadget lays out, so integrators can
+//! check before setup whether a composed circuit fits in the SRS they ship.
+//! [`SetupInfo`] reports what setup actually built.
+
+use kimchi::circuits::gate::{CircuitGate, GateType};
+use kimchi::circuits::lookup::tables::LookupTable;
+use kimchi::circuits::wires::PERMUTS;
+use kimchi::prover_index::ProverIndex;
+use mina_curves::pasta::{Fp, Vesta};
+use poly_commitment::ipa::SRS;
+use std::fmt;
+use std::ops::{Add, AddAssign};
+
+use crate::error::{ProverError, Result};
+use crate::gadgets::range::{RANGE_CHECK_TABLE_ID, RANGE_CHECK_TABLE_ROWS};
+use crate::prover::{COLUMNS, FULL_ROUNDS, ZK_ROWS};
+
+/// Field elements the prover holds per domain row, as an upper bound: the
+/// witness and permutation columns evaluated over the 8x extended domain,
+/// plus room for the quotient and other intermediate evaluations.
+const PROVING_ELEMENTS_PER_ROW: usize = 8 * (COLUMNS + PERMUTS + 10);
+
+/// Rows of the built-in lookup tables Kimchi adds for `gates`: the 12-bit
+/// range-check table when `RangeCheck0` gates are present.
+fn builtin_table_rows(gates: &[CircuitGate<Fp>]) -> usize {
+    if gates.iter().any(|g| g.typ == GateType::RangeCheck0) {
+        RANGE_CHECK_TABLE_ROWS
+    } else {
+        0
+    }
+}
+
+/// Approximate memory taken by an SRS of `2^srs_log2_size` points, including
+/// the Lagrange basis Kimchi adds for a domain of the same size.
+pub fn srs_memory_bytes(srs_log2_size: usize) -> usize {
+    2 * (1usize << srs_log2_size) * std::mem::size_of::<Vesta>()
+}
+
+/// Approximate peak memory of proof generation over a domain of `domain_size` rows.
+pub fn proving_memory_bytes(domain_size: usize) -> usize {
+    domain_size * PROVING_ELEMENTS_PER_ROW * std::mem::size_of::<Fp>()
+}
+
+/// Row and gate counts of a gadget or circuit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GadgetCost {
+    /// Total rows, including non-generic gates.
+    pub rows: usize,
+    /// Generic gates.
+    pub generic_gates: usize,
+    /// Lookup gates.
+    pub lookup_gates: usize,
+    /// Rows of the fixed lookup tables the gates read from.
+    pub lookup_table_rows: usize,
+}
+
+impl GadgetCost {
+    /// Count the gates of a gate list.
+    pub fn from_gates(gates: &[CircuitGate<Fp>]) -> Self {
+        Self {
+            rows: gates.len(),
+            generic_gates: gates.iter().filter(|g| g.typ == GateType::Generic).count(),
+            lookup_gates: gates.iter().filter(|g| g.typ == GateType::Lookup).count(),
+            lookup_table_rows: builtin_table_rows(gates),
+        }
+    }
+
+    /// Account for the lookup tables passed to `KimchiProver::setup_with_lookups`,
+    /// next to the built-in tables the gates read from.
+    pub fn with_lookup_tables(mut self, tables: &[LookupTable<Fp>]) -> Self {
+        self.lookup_table_rows += tables
+            .iter()
+            .map(|t| t.data.first().map_or(0, |column| column.len()))
+            .sum::<usize>();
+        self
+    }
+
+    /// Whether any lookup gates are used.
+    pub fn uses_lookups(&self) -> bool {
+        self.lookup_gates > 0
+    }
+
+    /// Rows of the evaluation domain taken: the circuit (or lookup tables)
+    /// plus zero-knowledge rows.
+    pub fn needed_rows(&self) -> usize {
+        self.rows.max(self.lookup_table_rows) + ZK_ROWS
+    }
+
+    /// Evaluation domain size: `needed_rows` rounded up to a power of two.
+    pub fn domain_size(&self) -> usize {
+        self.needed_rows().next_power_of_two()
+    }
+
+    /// Smallest `ProverConfig::srs_log2_size` that fits the circuit.
+    pub fn required_srs_log2_size(&self) -> usize {
+        self.domain_size().trailing_zeros() as usize
+    }
+
+    /// Whether the circuit fits an SRS of `2^srs_log2_size` points.
+    pub fn fits(&self, srs_log2_size: usize) -> bool {
+        self.required_srs_log2_size() <= srs_log2_size
+    }
+
+    /// Fail with `CircuitTooLarge` unless the circuit fits a domain of
+    /// `max_domain_size` rows (see `ProverConfig::max_domain_log2_size`).
+    pub fn check_fits_domain(&self, max_domain_size: usize) -> Result<()> {
+        let needed = self.needed_rows();
+        if needed > max_domain_size {
+            return Err(ProverError::CircuitTooLarge {
+                needed,
+                available: max_domain_size,
+            });
+        }
+        Ok(())
+    }
+
+    /// Approximate memory needed to set up and prove the circuit, SRS included.
+    pub fn memory_bytes(&self) -> usize {
+        srs_memory_bytes(self.required_srs_log2_size()) + proving_memory_bytes(self.domain_size())
+    }
+
+    /// Rough proving time on a mid-range phone.
+    pub fn proving_time_class(&self) -> ProvingTimeClass {
+        ProvingTimeClass::for_domain_size(self.domain_size())
+    }
+}
+
+/// Rough proving time of a circuit on a mid-range phone, by domain size.
+///
+/// Proving time grows roughly linearly with the domain, so the classes are
+/// domain size bands; treat them as a UX hint, not a measurement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ProvingTimeClass {
+    /// Domains up to 2^12 rows: about a second or less.
+    Fast,
+    /// Up to 2^15 rows: a few seconds.
+    Moderate,
+    /// Larger domains: ten seconds or more.
+    Slow,
+}
+
+impl ProvingTimeClass {
+    pub fn for_domain_size(domain_size: usize) -> Self {
+        if domain_size <= 1 << 12 {
+            Self::Fast
+        } else if domain_size <= 1 << 15 {
+            Self::Moderate
+        } else {
+            Self::Slow
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Fast => "fast",
+            Self::Moderate => "moderate",
+            Self::Slow => "slow",
+        }
+    }
+}
+
+impl Add for GadgetCost {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            rows: self.rows + other.rows,
+            generic_gates: self.generic_gates + other.generic_gates,
+            lookup_gates: self.lookup_gates + other.lookup_gates,
+            lookup_table_rows: self.lookup_table_rows.max(other.lookup_table_rows),
+        }
+    }
+}
+
+impl AddAssign for GadgetCost {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+/// Per-component costs of a composed circuit.
+#[derive(Clone, Debug, Default)]
+pub struct CostReport {
+    entries: Vec<(String, GadgetCost)>,
+}
+
+impl CostReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a named component.
+    pub fn add(&mut self, name: impl Into<String>, cost: GadgetCost) -> &mut Self {
+        self.entries.push((name.into(), cost));
+        self
+    }
+
+    /// Named component costs, in insertion order.
+    pub fn entries(&self) -> &[(String, GadgetCost)] {
+        &self.entries
+    }
+
+    /// Sum of all component costs.
+    pub fn total(&self) -> GadgetCost {
+        self.entries
+            .iter()
+            .fold(GadgetCost::default(), |acc, (_, cost)| acc + *cost)
+    }
+
+    /// Smallest `ProverConfig::srs_log2_size` that fits the whole circuit.
+    pub fn required_srs_log2_size(&self) -> usize {
+        self.total().required_srs_log2_size()
+    }
+
+    /// Whether the whole circuit fits an SRS of `2^srs_log2_size` points.
+    pub fn fits(&self, srs_log2_size: usize) -> bool {
+        self.total().fits(srs_log2_size)
+    }
+}
+
+impl fmt::Display for CostReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<24} {:>10} {:>10} {:>10}",
+            "component", "rows", "generic", "lookup"
+        )?;
+        for (name, cost) in &self.entries {
+            writeln!(
+                f,
+                "{:<24} {:>10} {:>10} {:>10}",
+                name, cost.rows, cost.generic_gates, cost.lookup_gates
+            )?;
+        }
+        let total = self.total();
+        writeln!(
+            f,
+            "{:<24} {:>10} {:>10} {:>10}",
+            "total", total.rows, total.generic_gates, total.lookup_gates
+        )?;
+        write!(
+            f,
+            "domain size {} (srs_log2_size >= {})",
+            total.domain_size(),
+            total.required_srs_log2_size()
+        )
+    }
+}
+
+/// Statistics of a set-up circuit, from `KimchiProver::setup_with_info`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SetupInfo {
+    /// Evaluation domain size.
+    pub domain_size: usize,
+    /// Largest domain the prover's SRS and `max_chunks` allow.
+    pub max_domain_size: usize,
+    /// Rows taken by gates, public inputs included.
+    pub rows: usize,
+    /// Rows reserved at the end of the domain for zero-knowledge.
+    pub zk_rows: usize,
+    pub num_public_inputs: usize,
+    /// Number of gates of each type, most frequent first.
+    pub gate_counts: Vec<(GateType, usize)>,
+    /// Rows of each lookup table by id: the built-in range-check table if
+    /// used, then the tables passed to setup.
+    pub lookup_tables: Vec<(i32, usize)>,
+}
+
+impl SetupInfo {
+    /// Describe the circuit of `prover_index`, set up with `lookup_tables`
+    /// by a prover allowing domains of up to `max_domain_size`.
+    pub fn new(
+        prover_index: &ProverIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        lookup_tables: &[LookupTable<Fp>],
+        max_domain_size: usize,
+    ) -> Self {
+        let cs = &prover_index.cs;
+        let mut gate_counts: Vec<(GateType, usize)> = Vec::new();
+        for gate in cs.gates.iter() {
+            match gate_counts.iter_mut().find(|(typ, _)| *typ == gate.typ) {
+                Some((_, count)) => *count += 1,
+                None => gate_counts.push((gate.typ, 1)),
+            }
+        }
+        gate_counts.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+
+        let builtin = builtin_table_rows(&cs.gates);
+        let lookup_tables = (builtin > 0)
+            .then_some((RANGE_CHECK_TABLE_ID, builtin))
+            .into_iter()
+            .chain(
+                lookup_tables
+                    .iter()
+                    .map(|t| (t.id, t.data.first().map_or(0, |column| column.len()))),
+            )
+            .collect();
+
+        Self {
+            domain_size: cs.domain.d1.size as usize,
+            max_domain_size,
+            rows: cs.gates.len(),
+            zk_rows: cs.zk_rows as usize,
+            num_public_inputs: cs.public,
+            gate_counts,
+            lookup_tables,
+        }
+    }
+
+    /// Number of gates of type `typ`.
+    pub fn gate_count(&self, typ: GateType) -> usize {
+        self.gate_counts
+            .iter()
+            .find(|(t, _)| *t == typ)
+            .map_or(0, |&(_, count)| count)
+    }
+
+    /// Rows the circuit needs: its gates, or the lookup tables if those are
+    /// larger.
+    pub fn used_rows(&self) -> usize {
+        self.lookup_tables
+            .iter()
+            .map(|&(_, rows)| rows)
+            .sum::<usize>()
+            .max(self.rows)
+    }
+
+    /// Rows left before the domain doubles.
+    pub fn free_rows(&self) -> usize {
+        self.domain_size
+            .saturating_sub(self.zk_rows + self.used_rows())
+    }
+
+    /// Rows left before the circuit no longer fits the SRS.
+    pub fn rows_to_limit(&self) -> usize {
+        self.max_domain_size
+            .saturating_sub(self.zk_rows + self.used_rows())
+    }
+}
+
+impl fmt::Display for SetupInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "domain size {} of at most {}: {} rows used, {} free, {} to the limit",
+            self.domain_size,
+            self.max_domain_size,
+            self.used_rows(),
+            self.free_rows(),
+            self.rows_to_limit()
+        )?;
+        for (typ, count) in &self.gate_counts {
+            writeln!(f, "{:<24} {:>10}", format!("{:?}", typ), count)?;
+        }
+        for (id, rows) in &self.lookup_tables {
+            writeln!(f, "{:<24} {:>10}", format!("lookup table {}", id), rows)?;
+        }
+        write!(f, "{} public inputs", self.num_public_inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::Gadget;
+    use crate::gadgets::lookup::{lookup_tables, LookupGadget};
+    use crate::gadgets::Sha256Gadget;
+
+    #[test]
+    fn test_proving_time_class() {
+        let cost = |rows| GadgetCost {
+            rows,
+            ..Default::default()
+        };
+        assert_eq!(cost(100).proving_time_class(), ProvingTimeClass::Fast);
+        assert_eq!(cost(4094).proving_time_class(), ProvingTimeClass::Moderate);
+        assert_eq!(cost(1 << 15).proving_time_class(), ProvingTimeClass::Slow);
+        assert!(ProvingTimeClass::Fast < ProvingTimeClass::Slow);
+    }
+
+    #[test]
+    fn test_srs_size() {
+        let cost = GadgetCost {
+            rows: 13,
+            ..Default::default()
+        };
+        assert_eq!(cost.domain_size(), 16);
+        assert_eq!(cost.required_srs_log2_size(), 4);
+
+        let cost = GadgetCost {
+            rows: 14,
+            ..Default::default()
+        };
+        assert_eq!(cost.required_srs_log2_size(), 5);
+        assert!(!cost.fits(4));
+        assert!(cost.check_fits_domain(32).is_ok());
+        assert!(matches!(
+            cost.check_fits_domain(16),
+            Err(ProverError::CircuitTooLarge {
+                needed: 17,
+                available: 16
+            })
+        ));
+    }
+
+    #[test]
+    fn test_memory_estimate() {
+        let cost = GadgetCost {
+            rows: 1000,
+            ..Default::default()
+        };
+        assert_eq!(
+            cost.memory_bytes(),
+            srs_memory_bytes(10) + proving_memory_bytes(1024)
+        );
+        assert_eq!(srs_memory_bytes(11), 2 * srs_memory_bytes(10));
+        assert!(proving_memory_bytes(1 << 14) > srs_memory_bytes(14));
+    }
+
+    #[test]
+    fn test_lookup_cost() {
+        let mut gadget = LookupGadget::new(0);
+        gadget.byte_xor();
+        let (gates, _) = gadget.build();
+
+        let cost = GadgetCost::from_gates(&gates).with_lookup_tables(&lookup_tables());
+        assert!(cost.uses_lookups());
+        assert_eq!(cost.lookup_gates, 2);
+        assert_eq!(cost.lookup_table_rows, 512);
+        assert_eq!(cost.required_srs_log2_size(), 10);
+    }
+
+    #[test]
+    fn test_cost_report() {
+        let mut sha = Sha256Gadget::new(0);
+        sha.sha256_block();
+
+        let mut report = CostReport::new();
+        report.add("sha256", sha.cost());
+        report.add("sha256 again", sha.cost());
+
+        assert_eq!(report.total().rows, 2 * Gadget::rows(&sha));
+        assert!(report.to_string().contains("sha256"));
+        assert!(!report.fits(10));
+    }
+}
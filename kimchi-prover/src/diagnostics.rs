@@ -0,0 +1,227 @@
+//! Constraint-failure diagnostics.
+//!
+//! Kimchi reports setup failures as opaque errors and does not check the
+//! witness at all in release builds: an unsatisfied constraint surfaces as a
+//! proof that fails to verify. These checks run over the compiled gates and
+//! locate the failing row so the error says where the circuit broke.
+
+use ark_ff::Zero;
+use kimchi::circuits::gate::{CircuitGate, GateType};
+use kimchi::circuits::polynomials::generic::{GenericGateSpec, GENERIC_COEFFS};
+use kimchi::error::SetupError;
+use mina_curves::pasta::Fp;
+
+use crate::builder::generic_spec_coeffs;
+use crate::error::{ProverError, Result};
+use crate::prover::COLUMNS;
+
+/// Map a constraint system creation failure, pointing at the offending gate
+/// when it can be identified.
+pub fn setup_error(
+    gates: &[CircuitGate<Fp>],
+    num_public_inputs: usize,
+    err: SetupError,
+) -> ProverError {
+    // Public inputs are read from the first column of leading generic rows.
+    let row = (0..num_public_inputs.min(gates.len())).find(|&i| gates[i].typ != GateType::Generic);
+    ProverError::ConstraintError {
+        message: "constraint system creation failed".into(),
+        row,
+        gate_type: row.map(|i| gates[i].typ),
+        wires: None,
+        source: Some(err),
+    }
+}
+
+/// Check that the first `num_public_inputs` rows are public input gates:
+/// generic gates whose first constraint is `1 * w0`.
+///
+/// Kimchi subtracts public input `i` from the first constraint of row `i`
+/// whatever gate is there, so a gadget laid over a public row, or a public
+/// input count that does not match the circuit, would otherwise only show
+/// as proofs that fail to verify.
+pub fn check_public_rows(gates: &[CircuitGate<Fp>], num_public_inputs: usize) -> Result<()> {
+    if num_public_inputs > gates.len() {
+        return Err(ProverError::ConstraintError {
+            message: format!(
+                "{} public inputs but the circuit has {} rows",
+                num_public_inputs,
+                gates.len()
+            ),
+            row: None,
+            gate_type: None,
+            wires: None,
+            source: None,
+        });
+    }
+    let public = generic_spec_coeffs(GenericGateSpec::Pub);
+    let row = (0..num_public_inputs).find(|&row| {
+        let gate = &gates[row];
+        gate.typ != GateType::Generic
+            || gate.coeffs.get(..GENERIC_COEFFS) != Some(public.as_slice())
+    });
+    match row {
+        Some(row) => Err(ProverError::ConstraintError {
+            message: format!(
+                "a gadget overlaps public input {}, or the circuit has fewer than {} \
+                 public inputs: expected a public input gate",
+                row, num_public_inputs
+            ),
+            row: Some(row),
+            gate_type: Some(gates[row].typ),
+            wires: None,
+            source: None,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Check a witness against the wiring of every gate and the equations of the
+/// generic gates.
+///
+/// Rows past the end of a column read as zero, matching how Kimchi pads the
+/// witness. With `include_values`, the error carries the failing row's wire
+/// values; off by default since they may be private.
+pub fn check_witness(
+    gates: &[CircuitGate<Fp>],
+    witness: &[Vec<Fp>; COLUMNS],
+    num_public_inputs: usize,
+    include_values: bool,
+) -> Result<()> {
+    let get = |row: usize, col: usize| witness[col].get(row).copied().unwrap_or_else(Fp::zero);
+
+    for (row, gate) in gates.iter().enumerate() {
+        let failure = |message: String| ProverError::ConstraintError {
+            message,
+            row: Some(row),
+            gate_type: Some(gate.typ),
+            wires: include_values.then(|| (0..COLUMNS).map(|col| get(row, col)).collect()),
+            source: None,
+        };
+
+        for (col, wire) in gate.wires.iter().enumerate() {
+            if get(row, col) != get(wire.row, wire.col) {
+                return Err(failure(format!(
+                    "column {} is wired to column {} of row {} but holds a different value",
+                    col, wire.col, wire.row
+                )));
+            }
+        }
+
+        if gate.typ != GateType::Generic {
+            continue;
+        }
+        for (half, coeffs) in gate.coeffs.chunks(GENERIC_COEFFS).enumerate() {
+            if coeffs.len() < GENERIC_COEFFS {
+                continue;
+            }
+            let [l, r, o] = std::array::from_fn(|k| get(row, 3 * half + k));
+            let mut value =
+                coeffs[0] * l + coeffs[1] * r + coeffs[2] * o + coeffs[3] * l * r + coeffs[4];
+            if half == 0 && row < num_public_inputs {
+                value -= l;
+            }
+            if !value.is_zero() {
+                return Err(failure(format!(
+                    "generic gate half {} is not satisfied",
+                    half
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::One;
+    use kimchi::circuits::wires::Wire;
+
+    fn generic(row: usize, coeffs: [Fp; GENERIC_COEFFS]) -> CircuitGate<Fp> {
+        CircuitGate::new(GateType::Generic, Wire::for_row(row), coeffs.to_vec())
+    }
+
+    #[test]
+    fn test_check_witness() {
+        let one = Fp::one();
+        let zero = Fp::zero();
+        // Row 0: public input. Row 1: w0 + w1 - w2 = 0.
+        let mut gates = vec![
+            generic(0, [one, zero, zero, zero, zero]),
+            generic(1, [one, one, -one, zero, zero]),
+        ];
+        let mut witness: [Vec<Fp>; COLUMNS] = std::array::from_fn(|_| vec![zero; 2]);
+        witness[0] = vec![Fp::from(7u64), Fp::from(2u64)];
+        witness[1][1] = Fp::from(3u64);
+        witness[2][1] = Fp::from(5u64);
+        assert!(check_witness(&gates, &witness, 1, false).is_ok());
+
+        witness[2][1] = Fp::from(6u64);
+        match check_witness(&gates, &witness, 1, true).unwrap_err() {
+            ProverError::ConstraintError {
+                row,
+                gate_type,
+                wires,
+                ..
+            } => {
+                assert_eq!(row, Some(1));
+                assert_eq!(gate_type, Some(GateType::Generic));
+                assert_eq!(wires.unwrap()[2], Fp::from(6u64));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+
+        // Wire row 1 column 0 to row 0 column 0, which holds 7.
+        witness[2][1] = Fp::from(5u64);
+        gates[1].wires[0] = Wire::new(0, 0);
+        let err = check_witness(&gates, &witness, 1, false).unwrap_err();
+        assert!(err.to_string().contains("row 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_check_public_rows() {
+        let one = Fp::one();
+        let zero = Fp::zero();
+        let mut gates = vec![
+            generic(0, [one, zero, zero, zero, zero]),
+            generic(1, [one, one, -one, zero, zero]),
+        ];
+        assert!(check_public_rows(&gates, 0).is_ok());
+        assert!(check_public_rows(&gates, 1).is_ok());
+        assert!(check_public_rows(&gates, 3).is_err());
+
+        // One public input too many lands on the addition
+        match check_public_rows(&gates, 2).unwrap_err() {
+            ProverError::ConstraintError { row, gate_type, .. } => {
+                assert_eq!(row, Some(1));
+                assert_eq!(gate_type, Some(GateType::Generic));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+
+        // A gadget placed on the public row
+        gates[0] = CircuitGate::new(GateType::Poseidon, Wire::for_row(0), vec![]);
+        let err = check_public_rows(&gates, 1).unwrap_err();
+        assert!(err.to_string().contains("row 0"), "{}", err);
+    }
+
+    #[test]
+    fn test_setup_error() {
+        let gates = vec![CircuitGate::zero(Wire::for_row(0))];
+        let err = SetupError::ConstraintSystem("public row 0 is not generic".into());
+        match setup_error(&gates, 1, err) {
+            ProverError::ConstraintError {
+                row,
+                gate_type,
+                source,
+                ..
+            } => {
+                assert_eq!(row, Some(0));
+                assert_eq!(gate_type, Some(GateType::Zero));
+                assert!(source.is_some());
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! SRS and compiled circuits cached on disk, shared between processes.
+//!
+//! Creating the SRS and compiling circuits take seconds on a phone, and an
+//! app's main process and its extensions (e.g. an iOS app extension) would
+//! each pay for them. With [`ProverConfig::cache_dir`] set, [`KimchiProver`]
+//! keeps both in a [`DiskCache`] in a directory every process can reach,
+//! such as an app group container.
+//!
+//! Each entry has a sibling `.lock` file. Readers hold a shared lock on it;
+//! a process that misses takes the exclusive lock, looks again and only
+//! then creates the entry, so processes starting together create it once.
+//! Entries are written to a temporary file and renamed into place, so a
+//! crash never leaves half an entry, and entries that fail to decode (e.g.
+//! written by another version) are replaced.
+//!
+//! The locks are advisory and only coordinate users of this cache. Cache
+//! failures never fail setup: they are logged and the value is created in
+//! memory instead.
+//!
+//! [`ProverConfig::cache_dir`]: crate::prover::ProverConfig::cache_dir
+//! [`KimchiProver`]: crate::prover::KimchiProver
+
+use std::fs::{self, File, OpenOptions};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use kimchi::prover_index::ProverIndex;
+use kimchi::verifier_index::VerifierIndex;
+use mina_curves::pasta::Vesta;
+use poly_commitment::ipa::SRS;
+
+use crate::error::{ProverError, Result};
+use crate::prover::{restore_prover_index, restore_verifier_index, CircuitIndices, FULL_ROUNDS};
+use crate::srs_provider::{decode_srs, encode_srs};
+
+/// Prefix of every entry: a magic number and the format version, bumped
+/// whenever the serialized SRS or indices change.
+const HEADER: [u8; 4] = *b"KMC\x01";
+
+/// A cache directory.
+#[derive(Clone, Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+}
+
+impl DiskCache {
+    /// A cache in `dir`, which is created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The cache directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The SRS of `2^srs_log2_size` points, from the cache or from `create`.
+    pub fn srs(
+        &self,
+        srs_log2_size: usize,
+        create: impl FnOnce() -> Result<SRS<Vesta>>,
+    ) -> Result<SRS<Vesta>> {
+        self.get_or_create(
+            &Self::srs_entry(srs_log2_size),
+            |bytes| decode_srs(srs_log2_size, bytes),
+            encode_srs,
+            create,
+        )
+    }
+
+    /// The indices of the circuit with [`CircuitCache::key`] `key`, set up
+    /// on `srs`, from the cache or from `create`.
+    ///
+    /// [`CircuitCache::key`]: crate::cache::CircuitCache::key
+    pub fn circuit(
+        &self,
+        key: &[u8; 32],
+        srs: &Arc<SRS<Vesta>>,
+        create: impl FnOnce() -> Result<CircuitIndices>,
+    ) -> Result<CircuitIndices> {
+        let srs_log2_size = srs.g.len().trailing_zeros() as usize;
+        self.get_or_create(
+            &Self::circuit_entry(key, srs_log2_size),
+            |bytes| {
+                let (mut prover_index, mut verifier_index): (
+                    ProverIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+                    VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+                ) = rmp_serde::from_slice(bytes)
+                    .map_err(|e| ProverError::decode("circuit indices", e))?;
+                restore_prover_index(&mut prover_index, srs.clone());
+                restore_verifier_index(&mut verifier_index, srs.clone());
+                Ok((prover_index, verifier_index))
+            },
+            |indices| {
+                rmp_serde::to_vec(indices).map_err(|e| ProverError::encode("circuit indices", e))
+            },
+            create,
+        )
+    }
+
+    /// Whether the SRS of `2^srs_log2_size` points is cached.
+    pub fn contains_srs(&self, srs_log2_size: usize) -> bool {
+        self.dir.join(Self::srs_entry(srs_log2_size)).is_file()
+    }
+
+    /// Remove every entry. Lock files are kept, since other processes may
+    /// hold them.
+    pub fn clear(&self) -> Result<()> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "bin") {
+                let lock = self.open_lock(&path)?;
+                lock.lock()?;
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn srs_entry(srs_log2_size: usize) -> String {
+        format!("srs-{}.bin", srs_log2_size)
+    }
+
+    fn circuit_entry(key: &[u8; 32], srs_log2_size: usize) -> String {
+        format!("circuit-{}-{}.bin", srs_log2_size, hex::encode(key))
+    }
+
+    fn get_or_create<T>(
+        &self,
+        entry: &str,
+        decode: impl Fn(&[u8]) -> Result<T>,
+        encode: impl FnOnce(&T) -> Result<Vec<u8>>,
+        create: impl FnOnce() -> Result<T>,
+    ) -> Result<T> {
+        let path = self.dir.join(entry);
+        let lock = match fs::create_dir_all(&self.dir).and_then(|()| self.open_lock(&path)) {
+            Ok(lock) => lock,
+            Err(e) => {
+                log::warn!("Cache directory {} unusable: {}", self.dir.display(), e);
+                return create();
+            }
+        };
+
+        if lock.lock_shared().is_ok() {
+            if let Some(value) = Self::read(&path, &decode) {
+                return Ok(value);
+            }
+        }
+        // Another process may create the entry between the two locks
+        if let Err(e) = lock.lock() {
+            log::warn!("Failed to lock {}: {}", path.display(), e);
+        }
+        if let Some(value) = Self::read(&path, &decode) {
+            return Ok(value);
+        }
+
+        let value = create()?;
+        if let Err(e) = encode(&value).and_then(|bytes| Self::write(&path, &bytes)) {
+            log::warn!("Failed to cache {}: {}", path.display(), e);
+        }
+        Ok(value)
+    }
+
+    fn open_lock(&self, path: &Path) -> std::io::Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(path.with_extension("lock"))
+    }
+
+    fn read<T>(path: &Path, decode: impl Fn(&[u8]) -> Result<T>) -> Option<T> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == ErrorKind::NotFound => return None,
+            Err(e) => {
+                log::warn!("Failed to read {}: {}", path.display(), e);
+                return None;
+            }
+        };
+        let decoded = match bytes.strip_prefix(&HEADER) {
+            Some(payload) => decode(payload),
+            None => Err(ProverError::SerializationError(
+                "unknown cache entry format".into(),
+            )),
+        };
+        decoded
+            .map_err(|e| log::warn!("Replacing cache entry {}: {}", path.display(), e))
+            .ok()
+    }
+
+    fn write(path: &Path, bytes: &[u8]) -> Result<()> {
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, [&HEADER[..], bytes].concat())?;
+        fs::rename(&tmp, path).inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::ThresholdCircuit;
+    use crate::prover::{create_srs, KimchiProver, ProverConfig};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kimchi-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_srs_created_once() {
+        let dir = temp_dir("disk-cache-srs");
+        let created = Arc::new(AtomicUsize::new(0));
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let (dir, created) = (dir.clone(), created.clone());
+                std::thread::spawn(move || {
+                    // A cache per thread, so the threads contend on the lock
+                    // like separate processes
+                    DiskCache::new(dir)
+                        .srs(6, || {
+                            created.fetch_add(1, Ordering::SeqCst);
+                            Ok(create_srs(6))
+                        })
+                        .unwrap()
+                        .g
+                        .len()
+                })
+            })
+            .collect();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), 64);
+        }
+        assert_eq!(created.load(Ordering::SeqCst), 1);
+
+        let cache = DiskCache::new(&dir);
+        assert!(cache.contains_srs(6));
+        fs::write(dir.join(DiskCache::srs_entry(6)), b"corrupt").unwrap();
+        cache.srs(6, || Ok(create_srs(6))).unwrap();
+        cache.srs(6, || panic!("entry was replaced")).unwrap();
+
+        cache.clear().unwrap();
+        assert!(!cache.contains_srs(6));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_setup_uses_disk_cache() {
+        let dir = temp_dir("disk-cache-setup");
+        let config = ProverConfig {
+            srs_log2_size: 10,
+            cache_dir: Some(dir.clone()),
+            ..Default::default()
+        };
+        let circuit = ThresholdCircuit::new(10);
+        let (_, verifier_index) = KimchiProver::with_config(config.clone())
+            .setup(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+
+        // Another process with the same cache directory
+        let mut prover = KimchiProver::with_config(config);
+        let (prover_index, cached) = prover
+            .setup(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+        assert!(prover.disk_cache().unwrap().contains_srs(10));
+        assert!(fs::read_dir(&dir).unwrap().any(|entry| {
+            let name = entry.unwrap().file_name();
+            name.to_string_lossy().starts_with("circuit-")
+        }));
+        assert_eq!(
+            crate::ProofEnvelope::verifier_index_digest(&cached).unwrap(),
+            crate::ProofEnvelope::verifier_index_digest(&verifier_index).unwrap()
+        );
+
+        let (witness, public_inputs) = circuit.generate_witness(0).unwrap();
+        let proof = prover.prove(&prover_index, witness).unwrap();
+        assert!(prover
+            .verify(&verifier_index, &proof, &public_inputs)
+            .unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -2,53 +2,492 @@
 
 use thiserror::Error;
 
+use kimchi::circuits::gate::GateType;
+use mina_curves::pasta::Fp;
+
+use crate::builder::Cell;
+
 /// Result type alias using ProverError
 pub type Result<T> = std::result::Result<T, ProverError>;
 
+/// Underlying error carried as a source by [`ProverError`].
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
 /// Errors that can occur during proof generation and verification.
+///
+/// Every variant has a stable numeric code (see [`ProverError::code`]) and,
+/// where the failure has structure, carries it as typed fields so callers
+/// can react without parsing the message. Errors from kimchi, serde and
+/// the encoding crates are kept as the `source()` rather than flattened
+/// into the message; [`ProverError::report`] renders the whole chain.
 #[derive(Error, Debug)]
 pub enum ProverError {
     /// Error during circuit setup
     #[error("Circuit setup failed: {0}")]
     SetupError(String),
 
+    /// The circuit's evaluation domain does not fit in the SRS
+    #[error(
+        "Circuit setup failed: circuit needs an SRS of {required} points, {available} available"
+    )]
+    SrsTooSmall { required: usize, available: usize },
+
+    /// The circuit has more rows than the largest domain the prover's SRS
+    /// allows, found before compiling its constraints
+    #[error("Circuit setup failed: circuit needs {needed} rows, the SRS allows {available}")]
+    CircuitTooLarge { needed: usize, available: usize },
+
+    /// An SRS asset does not match its pinned SHA-256 digest
+    #[error("Circuit setup failed: SRS of 2^{srs_log2_size} points has digest {actual}, pinned {expected}")]
+    SrsDigestMismatch {
+        srs_log2_size: usize,
+        expected: String,
+        actual: String,
+    },
+
     /// Error during witness generation
     #[error("Witness generation failed: {0}")]
     WitnessError(String),
 
+    /// The witness has more columns than Kimchi supports
+    #[error(
+        "Witness generation failed: witness has {columns} columns, at most {max} are supported"
+    )]
+    WitnessColumnCount { columns: usize, max: usize },
+
+    /// A witness column is longer than the circuit
+    #[error(
+        "Witness generation failed: witness column {column} has {rows} rows, the circuit has {num_rows}"
+    )]
+    WitnessColumnLength {
+        column: usize,
+        rows: usize,
+        num_rows: usize,
+    },
+
+    /// A gadget was given a different number of witness rows than it lays out
+    #[error(
+        "Witness generation failed: gadget lays out {expected} rows but {actual} witness rows were given"
+    )]
+    WitnessRowCount { expected: usize, actual: usize },
+
     /// Error during proof generation
     #[error("Proof generation failed: {0}")]
     ProvingError(String),
 
+    /// Kimchi failed to create the proof
+    #[error("Proof generation failed")]
+    ProofCreation(#[from] kimchi::error::ProverError),
+
     /// Error during proof verification
     #[error("Proof verification failed: {0}")]
     VerificationError(String),
 
+    /// A proof was checked against a different verifier index than it was made for
+    #[error("Proof verification failed: verifier index digest does not match the proof")]
+    VerifierIndexMismatch,
+
+    /// A proof's timestamp is older than the accepted age, or in the future
+    #[error(
+        "Proof verification failed: proof timestamp {timestamp} is not within {max_age}s of {now}"
+    )]
+    StaleProof {
+        timestamp: u64,
+        now: u64,
+        max_age: u64,
+    },
+
+    /// A proof was not bound to the expected device attestation
+    #[error("Proof verification failed: proof is not bound to the device attestation")]
+    AttestationMismatch,
+
+    /// A signed proof envelope does not carry a valid signature by the
+    /// expected key
+    #[error("Proof envelope signature is not valid for {signer}")]
+    SignatureInvalid { signer: String },
+
     /// Invalid input provided
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
+    /// Wrong number of public inputs
+    #[error("Invalid input: expected {expected} public inputs, got {actual}")]
+    PublicInputCount { expected: usize, actual: usize },
+
     /// Serialization/deserialization error
     #[error("Serialization error: {0}")]
     SerializationError(String),
 
-    /// Constraint system error
-    #[error("Constraint system error: {0}")]
-    ConstraintError(String),
+    /// Serializing a value failed
+    #[error("Serialization error: failed to serialize {what}")]
+    Encode {
+        what: &'static str,
+        #[source]
+        source: BoxError,
+    },
+
+    /// Deserializing or decoding a value failed
+    #[error("Serialization error: failed to deserialize {what}")]
+    Decode {
+        what: &'static str,
+        #[source]
+        source: BoxError,
+    },
+
+    /// Invalid JSON
+    #[error("Serialization error: invalid JSON")]
+    Json(#[from] serde_json::Error),
+
+    /// I/O error
+    #[error("I/O error")]
+    Io(#[from] std::io::Error),
+
+    /// A serialized format version this build does not understand
+    #[error("Serialization error: unsupported {format} version {found} (supported: {supported})")]
+    UnsupportedVersion {
+        format: &'static str,
+        found: u32,
+        supported: u32,
+    },
+
+    /// Kimchi rejected the circuit, or the witness does not satisfy it
+    #[error(
+        "Constraint system error: {message}{}",
+        describe_gate(*.row, *.gate_type, .wires.as_deref())
+    )]
+    ConstraintError {
+        message: String,
+        /// Row of the offending gate, when known
+        row: Option<usize>,
+        gate_type: Option<GateType>,
+        /// The row's wire values (only collected in debug mode)
+        wires: Option<Vec<Fp>>,
+        /// Kimchi's error, when it rejected the circuit
+        #[source]
+        source: Option<kimchi::error::SetupError>,
+    },
+
+    /// A generic gate is not satisfied by the witness
+    #[error(
+        "Constraint system error: generic gate at {} (half {half}) is not satisfied",
+        describe_row(*.row, .region.as_deref())
+    )]
+    GateNotSatisfied {
+        row: usize,
+        half: usize,
+        region: Option<String>,
+    },
+
+    /// Two wired cells hold different values
+    #[error(
+        "Constraint system error: copy constraint between column {} of {} and column {} of {} is not satisfied",
+        .a.col,
+        describe_row(.a.row, .a_region.as_deref()),
+        .b.col,
+        describe_row(.b.row, .b_region.as_deref())
+    )]
+    CopyNotSatisfied {
+        a: Cell,
+        b: Cell,
+        a_region: Option<String>,
+        b_region: Option<String>,
+    },
+
+    /// Not enough memory for an allocation the prover is about to make
+    #[error(
+        "Resource exhausted: {what} needs {required} bytes{}",
+        .limit.map(|l| format!(", limit is {} bytes", l)).unwrap_or_default()
+    )]
+    ResourceExhausted {
+        what: &'static str,
+        required: usize,
+        /// The configured memory limit, if that is what was exceeded
+        limit: Option<usize>,
+    },
 
     /// Generic internal error
     #[error("Internal error: {0}")]
     InternalError(String),
 }
 
-impl From<std::io::Error> for ProverError {
-    fn from(err: std::io::Error) -> Self {
-        ProverError::SerializationError(err.to_string())
+impl ProverError {
+    /// Stable numeric code identifying the error.
+    ///
+    /// Codes are grouped by hundreds: 1xx setup, 2xx witness, 3xx proving,
+    /// 4xx verification, 5xx input, 6xx serialization, 7xx constraints, 8xx
+    /// resources and 9xx internal. The `x00` code of each group is its
+    /// general variant. Codes are never reused or renumbered.
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::SetupError(_) => 100,
+            Self::SrsTooSmall { .. } => 101,
+            Self::CircuitTooLarge { .. } => 102,
+            Self::SrsDigestMismatch { .. } => 103,
+            Self::WitnessError(_) => 200,
+            Self::WitnessColumnCount { .. } => 201,
+            Self::WitnessColumnLength { .. } => 202,
+            Self::WitnessRowCount { .. } => 203,
+            Self::ProvingError(_) => 300,
+            Self::ProofCreation(_) => 301,
+            Self::VerificationError(_) => 400,
+            Self::VerifierIndexMismatch => 401,
+            Self::StaleProof { .. } => 402,
+            Self::AttestationMismatch => 403,
+            Self::SignatureInvalid { .. } => 404,
+            Self::InvalidInput(_) => 500,
+            Self::PublicInputCount { .. } => 501,
+            Self::SerializationError(_) => 600,
+            Self::UnsupportedVersion { .. } => 601,
+            Self::Encode { .. } => 602,
+            Self::Decode { .. } => 603,
+            Self::Json(_) => 604,
+            Self::Io(_) => 605,
+            Self::ConstraintError { .. } => 700,
+            Self::GateNotSatisfied { .. } => 701,
+            Self::CopyNotSatisfied { .. } => 702,
+            Self::ResourceExhausted { .. } => 800,
+            Self::InternalError(_) => 900,
+        }
+    }
+
+    /// Wrap a serialization failure.
+    pub fn encode(what: &'static str, source: impl Into<BoxError>) -> Self {
+        Self::Encode {
+            what,
+            source: source.into(),
+        }
+    }
+
+    /// Wrap a deserialization or decoding failure.
+    pub fn decode(what: &'static str, source: impl Into<BoxError>) -> Self {
+        Self::Decode {
+            what,
+            source: source.into(),
+        }
+    }
+
+    /// This error and its chain of sources, outermost first.
+    pub fn chain(&self) -> Vec<String> {
+        let mut chain = vec![self.to_string()];
+        let mut source = std::error::Error::source(self);
+        while let Some(err) = source {
+            chain.push(err.to_string());
+            source = err.source();
+        }
+        chain
+    }
+
+    /// The message followed by its sources, separated by `: `.
+    pub fn report(&self) -> String {
+        self.chain().join(": ")
+    }
+
+    /// Typed context as name/value pairs, for callers that cannot match on
+    /// the variant (e.g. across the FFI boundary).
+    pub fn details(&self) -> Vec<(&'static str, String)> {
+        fn region(name: &'static str, region: &Option<String>) -> Vec<(&'static str, String)> {
+            region.iter().map(|r| (name, r.clone())).collect()
+        }
+
+        match self {
+            Self::SrsTooSmall {
+                required,
+                available,
+            } => vec![
+                ("required", required.to_string()),
+                ("available", available.to_string()),
+            ],
+            Self::CircuitTooLarge { needed, available } => vec![
+                ("needed", needed.to_string()),
+                ("available", available.to_string()),
+            ],
+            Self::SrsDigestMismatch {
+                srs_log2_size,
+                expected,
+                actual,
+            } => vec![
+                ("srs_log2_size", srs_log2_size.to_string()),
+                ("expected", expected.clone()),
+                ("actual", actual.clone()),
+            ],
+            Self::WitnessColumnCount { columns, max } => {
+                vec![("columns", columns.to_string()), ("max", max.to_string())]
+            }
+            Self::WitnessColumnLength {
+                column,
+                rows,
+                num_rows,
+            } => vec![
+                ("column", column.to_string()),
+                ("rows", rows.to_string()),
+                ("num_rows", num_rows.to_string()),
+            ],
+            Self::WitnessRowCount { expected, actual }
+            | Self::PublicInputCount { expected, actual } => vec![
+                ("expected", expected.to_string()),
+                ("actual", actual.to_string()),
+            ],
+            Self::StaleProof {
+                timestamp,
+                now,
+                max_age,
+            } => vec![
+                ("timestamp", timestamp.to_string()),
+                ("now", now.to_string()),
+                ("max_age", max_age.to_string()),
+            ],
+            Self::SignatureInvalid { signer } => vec![("signer", signer.clone())],
+            Self::UnsupportedVersion {
+                format,
+                found,
+                supported,
+            } => vec![
+                ("format", format.to_string()),
+                ("found", found.to_string()),
+                ("supported", supported.to_string()),
+            ],
+            Self::ConstraintError {
+                row,
+                gate_type,
+                wires,
+                ..
+            } => {
+                let mut details: Vec<_> = row.iter().map(|r| ("row", r.to_string())).collect();
+                details.extend(gate_type.iter().map(|g| ("gate_type", format!("{:?}", g))));
+                details.extend(wires.iter().map(|w| ("wires", format_wires(w))));
+                details
+            }
+            Self::GateNotSatisfied {
+                row,
+                half,
+                region: r,
+            } => {
+                let mut details = vec![("row", row.to_string()), ("half", half.to_string())];
+                details.extend(region("region", r));
+                details
+            }
+            Self::CopyNotSatisfied {
+                a,
+                b,
+                a_region,
+                b_region,
+            } => {
+                let mut details = vec![
+                    ("a_row", a.row.to_string()),
+                    ("a_col", a.col.to_string()),
+                    ("b_row", b.row.to_string()),
+                    ("b_col", b.col.to_string()),
+                ];
+                details.extend(region("a_region", a_region));
+                details.extend(region("b_region", b_region));
+                details
+            }
+            Self::ResourceExhausted {
+                what,
+                required,
+                limit,
+            } => {
+                let mut details = vec![
+                    ("what", what.to_string()),
+                    ("required", required.to_string()),
+                ];
+                details.extend(limit.iter().map(|l| ("limit", l.to_string())));
+                details
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// "row N", with the region name when known.
+fn describe_row(row: usize, region: Option<&str>) -> String {
+    match region {
+        Some(name) => format!("row {} ({})", row, name),
+        None => format!("row {}", row),
+    }
+}
+
+/// " at row N (Type gate), wires [...]" for whatever is known.
+fn describe_gate(row: Option<usize>, gate_type: Option<GateType>, wires: Option<&[Fp]>) -> String {
+    let mut out = String::new();
+    if let Some(row) = row {
+        out.push_str(&format!(" at row {}", row));
+    }
+    if let Some(gate_type) = gate_type {
+        out.push_str(&format!(" ({:?} gate)", gate_type));
     }
+    if let Some(wires) = wires {
+        out.push_str(&format!(", wires {}", format_wires(wires)));
+    }
+    out
 }
 
-impl From<serde_json::Error> for ProverError {
-    fn from(err: serde_json::Error) -> Self {
-        ProverError::SerializationError(err.to_string())
+fn format_wires(wires: &[Fp]) -> String {
+    let values: Vec<String> = wires.iter().map(|w| w.to_string()).collect();
+    format!("[{}]", values.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_codes_and_details() {
+        let err = ProverError::SrsTooSmall {
+            required: 1 << 15,
+            available: 1 << 14,
+        };
+        assert_eq!(err.code(), 101);
+        assert_eq!(
+            err.details(),
+            vec![("required", "32768".into()), ("available", "16384".into())]
+        );
+        assert_eq!(
+            err.to_string(),
+            "Circuit setup failed: circuit needs an SRS of 32768 points, 16384 available"
+        );
+
+        let err = ProverError::CircuitTooLarge {
+            needed: 20000,
+            available: 16384,
+        };
+        assert_eq!(err.code(), 102);
+        assert_eq!(err.details()[0], ("needed", "20000".into()));
+
+        let err = ProverError::SrsDigestMismatch {
+            srs_log2_size: 16,
+            expected: "ab".into(),
+            actual: "cd".into(),
+        };
+        assert_eq!(err.code(), 103);
+        assert_eq!(err.details()[2], ("actual", "cd".into()));
+
+        let err = ProverError::GateNotSatisfied {
+            row: 2,
+            half: 1,
+            region: Some("sha256.block0".into()),
+        };
+        assert_eq!(err.code(), 701);
+        assert!(err.to_string().contains("row 2 (sha256.block0) (half 1)"));
+        assert_eq!(err.details()[2], ("region", "sha256.block0".into()));
+
+        assert_eq!(ProverError::InvalidInput("x".into()).code(), 500);
+        assert!(ProverError::InvalidInput("x".into()).details().is_empty());
+    }
+
+    #[test]
+    fn test_source_chain() {
+        let err: ProverError = serde_json::from_str::<u32>("x").unwrap_err().into();
+        assert_eq!(err.code(), 604);
+        let chain = err.chain();
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain[0], "Serialization error: invalid JSON");
+        assert_eq!(err.report(), chain.join(": "));
+
+        let err = ProverError::decode("proof", hex::decode("zz").unwrap_err());
+        assert!(std::error::Error::source(&err).is_some());
+        assert!(err
+            .report()
+            .starts_with("Serialization error: failed to deserialize proof: "));
     }
 }
@@ -0,0 +1,370 @@
+//! Multi-operand modular addition for Kimchi circuits.
+//!
+//! Sums `k` words modulo `2^w` in one block: `k - 1` plain additions, one
+//! reduction `sum - c - 2^w * carry = 0` and a range check on the carry,
+//! which is below `k` and so needs only `ceil(log2(k))` bits. A chain of
+//! pairwise `add_mod32` calls instead pays for a carry and a reduction per
+//! addition. Kimchi's generic gate only reads columns 0-5, so the row layout
+//! packs two additions (or two carry bits) into each row.
+
+use ark_ff::{BigInteger, Field, One, PrimeField};
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use kimchi::circuits::wires::Wire;
+use mina_curves::pasta::Fp;
+
+use super::boolean::BooleanGadget;
+use crate::builder::{
+    fill_row_layout, Cell, CircuitBuilder, Gadget, WitnessBuilder, WitnessLayout,
+};
+use crate::error::Result;
+use crate::prover::COLUMNS;
+
+/// Bits needed for the carry of a sum of `num_operands` words.
+pub fn carry_bits(num_operands: usize) -> usize {
+    assert!(num_operands >= 2, "at least two operands are required");
+    (usize::BITS - (num_operands - 1).leading_zeros()) as usize
+}
+
+/// Gadget for multi-operand modular addition.
+pub struct MultiAddGadget {
+    gates: Vec<CircuitGate<Fp>>,
+    current_row: usize,
+}
+
+impl MultiAddGadget {
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            gates: Vec::new(),
+            current_row: start_row,
+        }
+    }
+
+    pub fn current_row(&self) -> usize {
+        self.current_row
+    }
+
+    /// Push `count` copies of `spec`, two per row.
+    fn push_packed(&mut self, spec: GenericGateSpec<Fp>, count: usize) {
+        for i in (0..count).step_by(2) {
+            let wires = Wire::for_row(self.current_row);
+            let second = (i + 1 < count).then(|| spec.clone());
+            self.gates.push(CircuitGate::create_generic_gadget(
+                wires,
+                spec.clone(),
+                second,
+            ));
+            self.current_row += 1;
+        }
+    }
+
+    /// Sum of `num_operands` words of `word_bits` bits: c = sum(x_i) mod 2^word_bits.
+    pub fn add_mod(&mut self, num_operands: usize, word_bits: usize) -> usize {
+        let start = self.current_row;
+        let carry_bits = carry_bits(num_operands);
+
+        // acc' = acc + x_i
+        self.push_packed(
+            GenericGateSpec::Add {
+                left_coeff: Some(Fp::one()),
+                right_coeff: Some(Fp::one()),
+                output_coeff: Some(-Fp::one()),
+            },
+            num_operands - 1,
+        );
+
+        // sum - c - 2^w * carry = 0
+        self.push_packed(
+            GenericGateSpec::Add {
+                left_coeff: Some(Fp::one()),
+                right_coeff: Some(-Fp::one()),
+                output_coeff: Some(-Fp::from(2u64).pow([word_bits as u64])),
+            },
+            1,
+        );
+
+        // Carry bits, recomposed into the carry when there is more than one
+        self.push_packed(
+            GenericGateSpec::Mul {
+                mul_coeff: Some(Fp::one()),
+                output_coeff: Some(-Fp::one()),
+            },
+            carry_bits,
+        );
+        if carry_bits > 1 {
+            self.push_packed(
+                GenericGateSpec::Add {
+                    left_coeff: Some(Fp::one()),
+                    right_coeff: Some(Fp::one()),
+                    output_coeff: Some(-Fp::one()),
+                },
+                carry_bits - 1,
+            );
+        }
+
+        start
+    }
+
+    /// Sum of 32-bit words modulo 2^32.
+    pub fn add_mod32(&mut self, num_operands: usize) -> usize {
+        self.add_mod(num_operands, 32)
+    }
+
+    /// Sum of 64-bit words modulo 2^64.
+    pub fn add_mod64(&mut self, num_operands: usize) -> usize {
+        self.add_mod(num_operands, 64)
+    }
+
+    /// Consume the gadget and return the gates.
+    pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
+        (self.gates, self.current_row)
+    }
+}
+
+impl Gadget for MultiAddGadget {
+    type Inputs = [[Fp; COLUMNS]];
+
+    fn build(&self, cs: &mut CircuitBuilder) -> usize {
+        cs.extend_gates(&self.gates, self.current_row - self.gates.len())
+    }
+
+    fn rows(&self) -> usize {
+        self.gates.len()
+    }
+
+    fn witness(&self, w: &mut WitnessBuilder, rows: &Self::Inputs) -> Result<()> {
+        fill_row_layout(w, self.rows(), rows)
+    }
+}
+
+/// Cell-level multi-operand addition.
+impl MultiAddGadget {
+    /// Sum word cells modulo `2^word_bits`.
+    ///
+    /// The operands must already be range checked to `word_bits`; the output
+    /// is decomposed, so it is range checked once for the whole sum rather
+    /// than once per pairwise addition.
+    pub fn add_mod_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        operands: &[Cell],
+        word_bits: usize,
+    ) -> Cell {
+        assert!(word_bits <= 64, "words are at most 64 bits");
+        let carry_bits = carry_bits(operands.len());
+
+        let mut acc = operands[0];
+        for &operand in &operands[1..] {
+            let cells = cs.generic(GenericGateSpec::Add {
+                left_coeff: Some(Fp::one()),
+                right_coeff: Some(Fp::one()),
+                output_coeff: Some(-Fp::one()),
+            });
+            let (vacc, vop) = (w.get(acc), w.get(operand));
+            w.set_cells(cells, [vacc, vop, vacc + vop]);
+            cs.copy(acc, cells[0]);
+            cs.copy(operand, cells[1]);
+            acc = cells[2];
+        }
+
+        let values: Vec<u64> = operands.iter().map(|c| word_value(w.get(*c))).collect();
+        let (result, carry) = MultiAddWitness::add_mod(&values, word_bits);
+
+        // sum - c - 2^w * carry = 0
+        let reduce = cs.generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(-Fp::from(2u64).pow([word_bits as u64])),
+        });
+        w.set_cells(reduce, [w.get(acc), Fp::from(result), Fp::from(carry)]);
+        cs.copy(acc, reduce[0]);
+
+        BooleanGadget::decompose_cells(cs, w, reduce[2], carry_bits);
+        BooleanGadget::decompose_cells(cs, w, reduce[1], word_bits);
+
+        reduce[1]
+    }
+
+    /// Sum 32-bit word cells modulo 2^32.
+    pub fn add_mod32_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        operands: &[Cell],
+    ) -> Cell {
+        Self::add_mod_cells(cs, w, operands, 32)
+    }
+
+    /// Sum 64-bit word cells modulo 2^64.
+    pub fn add_mod64_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        operands: &[Cell],
+    ) -> Cell {
+        Self::add_mod_cells(cs, w, operands, 64)
+    }
+}
+
+/// Low 64 bits of a field element holding a word.
+fn word_value(value: Fp) -> u64 {
+    let bytes = value.into_bigint().to_bytes_le();
+    u64::from_le_bytes(std::array::from_fn(|i| bytes[i]))
+}
+
+/// Witness generator for multi-operand addition.
+pub struct MultiAddWitness;
+
+impl MultiAddWitness {
+    /// Sum words modulo `2^word_bits`, returning the result and the carry.
+    pub fn add_mod(values: &[u64], word_bits: usize) -> (u64, u64) {
+        let sum: u128 = values.iter().map(|v| *v as u128).sum();
+        let mask = (1u128 << word_bits) - 1;
+        ((sum & mask) as u64, (sum >> word_bits) as u64)
+    }
+
+    /// Write the rows of `MultiAddGadget::add_mod` summing `values` into
+    /// `layout`, from `row`. Returns the sum and the row after the block.
+    pub fn fill_layout(
+        layout: &mut WitnessLayout,
+        row: usize,
+        values: &[u64],
+        word_bits: usize,
+    ) -> (u64, usize) {
+        let carry_bits = carry_bits(values.len());
+        let (result, carry) = Self::add_mod(values, word_bits);
+
+        let mut acc = Fp::from(values[0]);
+        let additions: Vec<[Fp; 3]> = values[1..]
+            .iter()
+            .map(|&value| {
+                let value = Fp::from(value);
+                acc += value;
+                [acc - value, value, acc]
+            })
+            .collect();
+        let row = layout.set_packed(row, &additions);
+        let row = layout.set_packed(row, &[[acc, Fp::from(result), Fp::from(carry)]]);
+
+        let bits: Vec<Fp> = (0..carry_bits)
+            .map(|i| Fp::from((carry >> i) & 1))
+            .collect();
+        let bit_checks: Vec<[Fp; 3]> = bits.iter().map(|&bit| [bit; 3]).collect();
+        let mut row = layout.set_packed(row, &bit_checks);
+        if carry_bits > 1 {
+            let mut acc = bits[0];
+            let recomposition: Vec<[Fp; 3]> = (1..carry_bits)
+                .map(|i| {
+                    let term = bits[i] * Fp::from(1u64 << i);
+                    acc += term;
+                    [acc - term, term, acc]
+                })
+                .collect();
+            row = layout.set_packed(row, &recomposition);
+        }
+        (result, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, values: &[u64]) -> Vec<Cell> {
+        values
+            .iter()
+            .map(|v| {
+                let [cell, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(*v)));
+                w.set(cell, Fp::from(*v));
+                cell
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_carry_bits() {
+        assert_eq!(carry_bits(2), 1);
+        assert_eq!(carry_bits(3), 2);
+        assert_eq!(carry_bits(5), 3);
+        assert_eq!(carry_bits(8), 3);
+        assert_eq!(carry_bits(9), 4);
+    }
+
+    #[test]
+    fn test_add_mod_cells() {
+        let values = [
+            0xFFFF_FFFFu64,
+            0xFFFF_FFFF,
+            0x8000_0000,
+            0x1234_5678,
+            0xFFFF_FFFF,
+        ];
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let cells = words(&mut cs, &mut w, &values);
+
+        let c = MultiAddGadget::add_mod32_cells(&mut cs, &mut w, &cells);
+        let expected = values
+            .iter()
+            .fold(0u32, |acc, v| acc.wrapping_add(*v as u32));
+        assert_eq!(w.get(c), Fp::from(expected as u64));
+        assert!(cs.check_witness(&w).is_ok());
+
+        w.set(c, w.get(c) + Fp::one());
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_add_mod64_cells() {
+        let values = [u64::MAX, u64::MAX, 3];
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let cells = words(&mut cs, &mut w, &values);
+
+        let c = MultiAddGadget::add_mod64_cells(&mut cs, &mut w, &cells);
+        assert_eq!(w.get(c), Fp::from(1u64));
+        assert!(cs.check_witness(&w).is_ok());
+    }
+
+    #[test]
+    fn test_fill_layout() {
+        for (values, word_bits) in [
+            (
+                vec![0xFFFF_FFFFu64, 0xFFFF_FFFF, 0x8000_0000, 0x1234_5678, 7],
+                32,
+            ),
+            (vec![u64::MAX, 3], 64),
+        ] {
+            let mut gadget = MultiAddGadget::new(1);
+            gadget.add_mod(values.len(), word_bits);
+            let (gates, end) = gadget.build();
+            let mut all = vec![CircuitGate::zero(Wire::for_row(0))];
+            all.extend(gates);
+
+            let mut layout = WitnessLayout::for_gates(&all);
+            let (sum, row) = MultiAddWitness::fill_layout(&mut layout, 1, &values, word_bits);
+            assert_eq!(row, end);
+            assert_eq!(sum, MultiAddWitness::add_mod(&values, word_bits).0);
+            let mut columns = layout.into_columns();
+            crate::diagnostics::check_witness(&all, &columns, 0, false).unwrap();
+
+            columns[1][row - 1] += Fp::one();
+            assert!(crate::diagnostics::check_witness(&all, &columns, 0, false).is_err());
+        }
+    }
+
+    #[test]
+    fn test_fewer_rows_than_pairwise() {
+        let mut multi = MultiAddGadget::new(0);
+        multi.add_mod32(5);
+        let (_, multi_rows) = multi.build();
+
+        let mut pairwise = crate::gadgets::Sha256Gadget::new(0);
+        for _ in 0..4 {
+            pairwise.add_mod32();
+        }
+        let (_, pairwise_rows) = pairwise.build();
+
+        // 2 addition rows, 1 reduction, 2 carry-bit rows, 1 carry recomposition
+        assert_eq!(multi_rows, 6);
+        assert!(multi_rows < pairwise_rows);
+    }
+}
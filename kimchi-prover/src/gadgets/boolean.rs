@@ -3,16 +3,29 @@
 //! Provides bit decomposition and boolean operations (AND, XOR, NOT)
 //! as arithmetic constraints over finite fields.
 
-use ark_ff::{One, Zero};
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
 use kimchi::circuits::gate::CircuitGate;
-use kimchi::circuits::polynomials::generic::GenericGateSpec;
-use kimchi::circuits::wires::Wire;
+use kimchi::circuits::polynomials::generic::{GenericGateSpec, GENERIC_COEFFS};
 use mina_curves::pasta::Fp;
 
+use super::range::RangeCheckGadget;
+use crate::builder::{
+    fill_row_layout, generic_spec_coeffs, push_packed, Cell, CircuitBuilder, Gadget, WitnessBuilder,
+};
+use crate::error::Result;
+use crate::prover::COLUMNS;
+use crate::types::BitVec;
+
 /// Gadget for boolean operations in Kimchi circuits.
+///
+/// Every bitwise operation is a single generic constraint, and constraints
+/// are packed two per row (see `builder::pack_generic`): the first of a pair
+/// uses columns 0..3 of its row and the second columns 3..6. Word
+/// decomposition uses the 15-column layout of `RangeCheckGadget` instead.
 pub struct BooleanGadget {
     gates: Vec<CircuitGate<Fp>>,
     current_row: usize,
+    open_half: bool,
 }
 
 impl BooleanGadget {
@@ -21,6 +34,7 @@ impl BooleanGadget {
         Self {
             gates: Vec::new(),
             current_row: start_row,
+            open_half: false,
         }
     }
 
@@ -29,6 +43,14 @@ impl BooleanGadget {
         self.current_row
     }
 
+    /// Add a constraint and return the row holding it.
+    fn push_constraint(&mut self, coeffs: [Fp; GENERIC_COEFFS]) -> usize {
+        let first_row = self.current_row - self.gates.len();
+        let [cell, _, _] = push_packed(&mut self.gates, first_row, &mut self.open_half, coeffs);
+        self.current_row = first_row + self.gates.len();
+        cell.row
+    }
+
     /// Add a boolean constraint: b * (b - 1) = 0
     /// This ensures the witness value at the specified column is 0 or 1.
     ///
@@ -37,151 +59,65 @@ impl BooleanGadget {
     /// Set l = r = b, so we need: c3*(b*b) + c0*b = 0
     /// With c3 = 1, c0 = -1: b*b - b = 0
     pub fn boolean_constraint(&mut self) -> usize {
-        let row = self.current_row;
-        let wires = Wire::for_row(row);
-
         // b * b - b = 0
         // Using Mul: mul_coeff * l * r + output_coeff * o = 0
         // With l = r = o = b: 1 * b * b + (-1) * b = 0
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Mul {
-                mul_coeff: Some(Fp::one()),
-                output_coeff: Some(-Fp::one()),
-            },
-            None,
-        ));
-
-        self.current_row += 1;
-        row
+        self.push_constraint(generic_spec_coeffs(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        }))
     }
 
     /// XOR of two bits: c = a XOR b
-    /// Arithmetic formula: c = a + b - 2*a*b
+    /// Arithmetic formula: a + b - 2*a*b - c = 0
     ///
     /// Returns the row where the XOR result is placed.
     pub fn xor(&mut self) -> usize {
-        let row = self.current_row;
-        let wires = Wire::for_row(row);
-
-        // a + b - 2*a*b - c = 0
-        // Using Add for: a + b - c = 2*a*b
-        // Then need another gate for the multiplication term
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Add {
-                left_coeff: Some(Fp::one()),
-                right_coeff: Some(Fp::one()),
-                output_coeff: Some(-Fp::one()),
-            },
-            None,
-        ));
-        self.current_row += 1;
-
-        // Add the quadratic term constraint: 2*a*b
-        let wires2 = Wire::for_row(self.current_row);
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires2,
-            GenericGateSpec::Mul {
-                mul_coeff: Some(Fp::from(2u64)),
-                output_coeff: Some(-Fp::one()),
-            },
-            None,
-        ));
-        self.current_row += 1;
-
-        row
+        self.push_constraint([
+            Fp::one(),
+            Fp::one(),
+            -Fp::one(),
+            -Fp::from(2u64),
+            Fp::zero(),
+        ])
     }
 
     /// AND of two bits: c = a AND b
     /// Arithmetic formula: c = a * b
     pub fn and(&mut self) -> usize {
-        let row = self.current_row;
-        let wires = Wire::for_row(row);
-
         // a * b - c = 0
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Mul {
-                mul_coeff: Some(Fp::one()),
-                output_coeff: Some(-Fp::one()),
-            },
-            None,
-        ));
-
-        self.current_row += 1;
-        row
+        self.push_constraint(generic_spec_coeffs(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        }))
     }
 
     /// NOT of a bit: c = 1 - a
     pub fn not(&mut self) -> usize {
-        let row = self.current_row;
-        let wires = Wire::for_row(row);
-
         // 1 - a - c = 0
-        // Use Plus which sets: l - o + constant = 0
-        // We want: -a - c + 1 = 0
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Add {
-                left_coeff: Some(-Fp::one()),
-                right_coeff: Some(Fp::zero()),
-                output_coeff: Some(-Fp::one()),
-            },
-            Some(GenericGateSpec::Const(Fp::one())),
-        ));
-
-        self.current_row += 1;
-        row
+        self.push_constraint([-Fp::one(), Fp::zero(), -Fp::one(), Fp::zero(), Fp::one()])
     }
 
-    /// Decompose a 32-bit word into individual bits.
-    /// Constrains: word = sum(bits[i] * 2^i) for i in 0..32
-    /// Also constrains each bit to be boolean.
+    /// Decompose a 32-bit word into 12-bit and 2-bit limbs spread over one
+    /// `RangeCheck0` row, constraining 0 <= word < 2^32 (see
+    /// `gadgets::range`).
     ///
-    /// Returns the starting row for the bit decomposition.
+    /// Returns the row holding the word in column 0 and its limbs.
     pub fn decompose_u32(&mut self) -> usize {
-        let start_row = self.current_row;
-
-        // First, add boolean constraints for each bit
-        for _ in 0..32 {
-            self.boolean_constraint();
-        }
-
-        // Then add the linear combination constraint
-        // word = sum(bit_i * 2^i)
-        self.linear_combination_32();
-
-        start_row
-    }
-
-    /// Add constraints for a 32-term linear combination.
-    /// Used for bit decomposition: word = sum(bit_i * 2^i)
-    fn linear_combination_32(&mut self) {
-        // With 15 columns, we can sum about 5 terms per row
-        // For 32 bits, we need ~7 rows for the summation
-        let num_gates = 7;
-
-        for _ in 0..num_gates {
-            let wires = Wire::for_row(self.current_row);
-            self.gates.push(CircuitGate::create_generic_gadget(
-                wires,
-                GenericGateSpec::Add {
-                    left_coeff: Some(Fp::one()),
-                    right_coeff: Some(Fp::one()),
-                    output_coeff: Some(-Fp::one()),
-                },
-                None,
-            ));
-            self.current_row += 1;
-        }
+        self.open_half = false;
+        let mut range = RangeCheckGadget::new(self.current_row);
+        let row = range.decompose_u32();
+        let (gates, current_row) = range.build();
+        self.gates.extend(gates);
+        self.current_row = current_row;
+        row
     }
 
     /// XOR of 32-bit words (bit by bit).
     /// Assumes both words have been decomposed to bits.
     pub fn xor_u32(&mut self) -> usize {
-        let start = self.current_row;
-        for _ in 0..32 {
+        let start = self.xor();
+        for _ in 1..32 {
             self.xor();
         }
         start
@@ -189,8 +125,8 @@ impl BooleanGadget {
 
     /// AND of 32-bit words (bit by bit).
     pub fn and_u32(&mut self) -> usize {
-        let start = self.current_row;
-        for _ in 0..32 {
+        let start = self.and();
+        for _ in 1..32 {
             self.and();
         }
         start
@@ -198,8 +134,8 @@ impl BooleanGadget {
 
     /// NOT of a 32-bit word (bit by bit).
     pub fn not_u32(&mut self) -> usize {
-        let start = self.current_row;
-        for _ in 0..32 {
+        let start = self.not();
+        for _ in 1..32 {
             self.not();
         }
         start
@@ -211,76 +147,215 @@ impl BooleanGadget {
     }
 }
 
+impl Gadget for BooleanGadget {
+    type Inputs = [[Fp; COLUMNS]];
+
+    fn build(&self, cs: &mut CircuitBuilder) -> usize {
+        cs.extend_gates(&self.gates, self.current_row - self.gates.len())
+    }
+
+    fn rows(&self) -> usize {
+        self.gates.len()
+    }
+
+    fn witness(&self, w: &mut WitnessBuilder, rows: &Self::Inputs) -> Result<()> {
+        fill_row_layout(w, self.rows(), rows)
+    }
+}
+
+/// Cell-level boolean operations.
+///
+/// These append constraints to `cs`, packed two per row, assign the values
+/// they compute in `w`, copy-constrain their inputs and return the output
+/// cells.
+impl BooleanGadget {
+    /// Allocate a bit and constrain it to be boolean.
+    pub fn alloc_bit(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, bit: bool) -> Cell {
+        let cells = cs.generic_packed(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        let value = Fp::from(bit as u64);
+        w.set_cells(cells, [value; 3]);
+        cs.copy(cells[0], cells[1]);
+        cs.copy(cells[0], cells[2]);
+        cells[0]
+    }
+
+    /// Cell pinned to a constant bit, e.g. for padding.
+    pub fn constant_bit(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, bit: bool) -> Cell {
+        let value = Fp::from(bit as u64);
+        let [cell, _, _] = cs.generic_packed(GenericGateSpec::Const(value));
+        w.set(cell, value);
+        cell
+    }
+
+    /// Constrain an existing cell to be boolean: b * b - b = 0.
+    pub fn assert_boolean(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, b: Cell) {
+        let cells = cs.generic_packed(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(cells, [w.get(b); 3]);
+        for cell in cells {
+            cs.copy(b, cell);
+        }
+    }
+
+    /// AND of two bits: c = a * b.
+    pub fn and_cells(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, a: Cell, b: Cell) -> Cell {
+        let cells = cs.generic_packed(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        let (va, vb) = (w.get(a), w.get(b));
+        w.set_cells(cells, [va, vb, va * vb]);
+        cs.copy(a, cells[0]);
+        cs.copy(b, cells[1]);
+        cells[2]
+    }
+
+    /// XOR of two bits in a single row: a + b - 2*a*b - c = 0.
+    pub fn xor_cells(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, a: Cell, b: Cell) -> Cell {
+        let cells = cs.generic_coeffs_packed([
+            Fp::one(),
+            Fp::one(),
+            -Fp::one(),
+            -Fp::from(2u64),
+            Fp::zero(),
+        ]);
+        let (va, vb) = (w.get(a), w.get(b));
+        w.set_cells(cells, [va, vb, va + vb - Fp::from(2u64) * va * vb]);
+        cs.copy(a, cells[0]);
+        cs.copy(b, cells[1]);
+        cells[2]
+    }
+
+    /// NOT of a bit: 1 - a - c = 0.
+    pub fn not_cell(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, a: Cell) -> Cell {
+        let cells =
+            cs.generic_coeffs_packed([-Fp::one(), Fp::zero(), -Fp::one(), Fp::zero(), Fp::one()]);
+        let va = w.get(a);
+        w.set_cells(cells, [va, Fp::zero(), Fp::one() - va]);
+        cs.copy(a, cells[0]);
+        cells[2]
+    }
+
+    /// Decompose a value into `num_bits` little-endian boolean cells.
+    ///
+    /// Constrains `value = sum(bit_i * 2^i)`, which doubles as a range check
+    /// `0 <= value < 2^num_bits`.
+    pub fn decompose_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        value: Cell,
+        num_bits: usize,
+    ) -> Vec<Cell> {
+        assert!(num_bits > 0, "cannot decompose into zero bits");
+
+        let value_bits = w.get(value).into_bigint().to_bits_le();
+        let bits: Vec<Cell> = (0..num_bits)
+            .map(|i| Self::alloc_bit(cs, w, value_bits.get(i).copied().unwrap_or(false)))
+            .collect();
+
+        let mut acc = bits[0];
+        for (i, bit) in bits.iter().enumerate().skip(1) {
+            let cells = cs.generic_packed(GenericGateSpec::Add {
+                left_coeff: Some(Fp::one()),
+                right_coeff: Some(Fp::from(2u64).pow([i as u64])),
+                output_coeff: Some(-Fp::one()),
+            });
+            let (vacc, vbit) = (w.get(acc), w.get(*bit));
+            w.set_cells(
+                cells,
+                [vacc, vbit, vacc + Fp::from(2u64).pow([i as u64]) * vbit],
+            );
+            cs.copy(acc, cells[0]);
+            cs.copy(*bit, cells[1]);
+            acc = cells[2];
+        }
+        cs.copy(acc, value);
+
+        bits
+    }
+
+    /// Decompose a 32-bit word cell into bit cells.
+    pub fn decompose_u32_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        word: Cell,
+    ) -> [Cell; 32] {
+        let bits = Self::decompose_cells(cs, w, word, 32);
+        std::array::from_fn(|i| bits[i])
+    }
+
+    /// Bitwise XOR of two decomposed words.
+    pub fn xor_u32_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: &[Cell; 32],
+        b: &[Cell; 32],
+    ) -> [Cell; 32] {
+        std::array::from_fn(|i| Self::xor_cells(cs, w, a[i], b[i]))
+    }
+
+    /// Bitwise AND of two decomposed words.
+    pub fn and_u32_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: &[Cell; 32],
+        b: &[Cell; 32],
+    ) -> [Cell; 32] {
+        std::array::from_fn(|i| Self::and_cells(cs, w, a[i], b[i]))
+    }
+
+    /// Bitwise NOT of a decomposed word.
+    pub fn not_u32_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: &[Cell; 32],
+    ) -> [Cell; 32] {
+        std::array::from_fn(|i| Self::not_cell(cs, w, a[i]))
+    }
+}
+
 /// Witness generator for boolean operations.
 pub struct BooleanWitness;
 
 impl BooleanWitness {
-    /// Decompose a u32 into 32 field elements (0 or 1).
-    pub fn decompose_u32(value: u32) -> [Fp; 32] {
-        let mut bits = [Fp::zero(); 32];
-        for i in 0..32 {
-            if (value >> i) & 1 == 1 {
-                bits[i] = Fp::one();
-            }
-        }
-        bits
+    /// Decompose a u32 into 32 bits.
+    pub fn decompose_u32(value: u32) -> BitVec {
+        BitVec::from_u64(value as u64, 32)
     }
 
-    /// Recompose bits into a u32.
-    pub fn recompose_u32(bits: &[Fp; 32]) -> u32 {
-        let mut value = 0u32;
-        for i in 0..32 {
-            if bits[i] == Fp::one() {
-                value |= 1 << i;
-            }
-        }
-        value
+    /// Recompose the low 32 bits into a u32.
+    pub fn recompose_u32(bits: &BitVec) -> u32 {
+        bits.to_u64() as u32
     }
 
-    /// XOR two bit arrays.
-    pub fn xor_bits(a: &[Fp; 32], b: &[Fp; 32]) -> [Fp; 32] {
-        let mut result = [Fp::zero(); 32];
-        for i in 0..32 {
-            // XOR: a + b - 2*a*b
-            result[i] = a[i] + b[i] - (Fp::from(2u64) * a[i] * b[i]);
-        }
-        result
+    /// XOR two bit vectors.
+    pub fn xor_bits(a: &BitVec, b: &BitVec) -> BitVec {
+        a.xor(b)
     }
 
-    /// AND two bit arrays.
-    pub fn and_bits(a: &[Fp; 32], b: &[Fp; 32]) -> [Fp; 32] {
-        let mut result = [Fp::zero(); 32];
-        for i in 0..32 {
-            result[i] = a[i] * b[i];
-        }
-        result
+    /// AND two bit vectors.
+    pub fn and_bits(a: &BitVec, b: &BitVec) -> BitVec {
+        a.and(b)
     }
 
-    /// NOT a bit array.
-    pub fn not_bits(a: &[Fp; 32]) -> [Fp; 32] {
-        let mut result = [Fp::zero(); 32];
-        for i in 0..32 {
-            result[i] = Fp::one() - a[i];
-        }
-        result
+    /// NOT a bit vector.
+    pub fn not_bits(a: &BitVec) -> BitVec {
+        a.not()
     }
 
     /// Right rotation of bits.
-    pub fn rotr(bits: &[Fp; 32], n: usize) -> [Fp; 32] {
-        let mut result = [Fp::zero(); 32];
-        for i in 0..32 {
-            result[i] = bits[(i + n) % 32];
-        }
-        result
+    pub fn rotr(bits: &BitVec, n: usize) -> BitVec {
+        bits.rotr(n)
     }
 
     /// Right shift of bits (introduces zeros).
-    pub fn shr(bits: &[Fp; 32], n: usize) -> [Fp; 32] {
-        let mut result = [Fp::zero(); 32];
-        for i in n..32 {
-            result[i - n] = bits[i];
-        }
-        result
+    pub fn shr(bits: &BitVec, n: usize) -> BitVec {
+        bits.shr(n)
     }
 }
 
@@ -314,6 +389,67 @@ mod tests {
         assert_eq!(value, 0xFF00FF00 & 0x0F0F0F0F);
     }
 
+    #[test]
+    fn test_cell_operations() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+
+        let [a, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(0xFF00FF00u64)));
+        w.set(a, Fp::from(0xFF00FF00u64));
+        let [b, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(0x0F0F0F0Fu64)));
+        w.set(b, Fp::from(0x0F0F0F0Fu64));
+
+        let a_bits = BooleanGadget::decompose_u32_cells(&mut cs, &mut w, a);
+        let b_bits = BooleanGadget::decompose_u32_cells(&mut cs, &mut w, b);
+        let xor = BooleanGadget::xor_u32_cells(&mut cs, &mut w, &a_bits, &b_bits);
+        let and = BooleanGadget::and_u32_cells(&mut cs, &mut w, &a_bits, &b_bits);
+        let not = BooleanGadget::not_u32_cells(&mut cs, &mut w, &a_bits);
+
+        let value = |cells: &[Cell; 32]| {
+            let bits = BitVec::from_bits(cells.iter().map(|c| w.get(*c)).collect()).unwrap();
+            BooleanWitness::recompose_u32(&bits)
+        };
+        assert_eq!(value(&xor), 0xFF00FF00 ^ 0x0F0F0F0F);
+        assert_eq!(value(&and), 0xFF00FF00 & 0x0F0F0F0F);
+        assert_eq!(value(&not), !0xFF00FF00u32);
+        assert!(cs.check_witness(&w).is_ok());
+
+        // A bit that is not boolean breaks the witness
+        w.set(a_bits[3], Fp::from(2u64));
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_packed_rows() {
+        // A range check of the word and its complement, then 32 packed XORs
+        let mut gadget = BooleanGadget::new(0);
+        assert_eq!(gadget.decompose_u32(), 1);
+        assert_eq!(gadget.current_row(), 3);
+        assert_eq!(gadget.xor_u32(), 3);
+        assert_eq!(gadget.build().1, 19);
+
+        // 32 bits and 31 accumulator steps
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let [word, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(7u64)));
+        w.set(word, Fp::from(7u64));
+        BooleanGadget::decompose_u32_cells(&mut cs, &mut w, word);
+        assert_eq!(cs.num_rows(), 1 + 32);
+        assert!(cs.check_witness(&w).is_ok());
+    }
+
+    #[test]
+    fn test_decompose_out_of_range() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+
+        let [v, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(300u64)));
+        w.set(v, Fp::from(300u64));
+        BooleanGadget::decompose_cells(&mut cs, &mut w, v, 8);
+
+        assert!(cs.check_witness(&w).is_err());
+    }
+
     #[test]
     fn test_rotr() {
         let bits = BooleanWitness::decompose_u32(0x80000001);
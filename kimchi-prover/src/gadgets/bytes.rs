@@ -0,0 +1,249 @@
+//! Byte-string gadgets for Kimchi circuits.
+//!
+//! Provides equality of byte arrays and substring matching at a
+//! witness-provided offset, as used when matching names, domains and
+//! document fields inside credential circuits.
+
+use ark_ff::{One, Zero};
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use kimchi::circuits::wires::Wire;
+use mina_curves::pasta::Fp;
+
+/// Gadget for byte-string constraints.
+pub struct BytesGadget {
+    gates: Vec<CircuitGate<Fp>>,
+    current_row: usize,
+}
+
+impl BytesGadget {
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            gates: Vec::new(),
+            current_row: start_row,
+        }
+    }
+
+    pub fn current_row(&self) -> usize {
+        self.current_row
+    }
+
+    fn push_generic(&mut self, spec: GenericGateSpec<Fp>) {
+        let wires = Wire::for_row(self.current_row);
+        self.gates
+            .push(CircuitGate::create_generic_gadget(wires, spec, None));
+        self.current_row += 1;
+    }
+
+    /// Boolean constraint: b * b - b = 0.
+    fn boolean(&mut self) {
+        self.push_generic(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Accumulation: acc_next = acc + term.
+    fn accumulate(&mut self) {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Equality: a - b = 0.
+    fn equal(&mut self) {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(Fp::zero()),
+        });
+    }
+
+    /// Range check a single byte: 0 <= b < 256.
+    pub fn byte_range_check(&mut self) -> usize {
+        let start = self.current_row;
+
+        for _ in 0..8 {
+            self.boolean();
+        }
+
+        // b = sum(bit_i * 2^i)
+        for _ in 0..3 {
+            self.accumulate();
+        }
+
+        start
+    }
+
+    /// Equality of two byte arrays of the same length: a[i] == b[i].
+    pub fn bytes_equal(&mut self, len: usize) -> usize {
+        let start = self.current_row;
+
+        for _ in 0..len {
+            self.equal();
+        }
+
+        start
+    }
+
+    /// Substring match: haystack[offset..offset + needle_len] == needle.
+    ///
+    /// The haystack is private, the needle public and the offset a witness value.
+    /// The offset is encoded as a one-hot selector over every valid start position
+    /// `0..=haystack_len - needle_len`, which both bounds it and lets each needle
+    /// byte be selected as `sum_k s_k * haystack[k + j]`.
+    pub fn contains_substring(&mut self, haystack_len: usize, needle_len: usize) -> usize {
+        assert!(
+            needle_len <= haystack_len,
+            "needle ({} bytes) is longer than haystack ({} bytes)",
+            needle_len,
+            haystack_len
+        );
+
+        let start = self.current_row;
+        let positions = haystack_len - needle_len + 1;
+
+        // Selector bits are boolean
+        for _ in 0..positions {
+            self.boolean();
+        }
+
+        // sum(s_k) accumulation and sum(k * s_k) accumulation
+        for k in 0..positions {
+            self.accumulate();
+            self.push_generic(GenericGateSpec::Add {
+                left_coeff: Some(Fp::one()),
+                right_coeff: Some(Fp::from(k as u64)),
+                output_coeff: Some(-Fp::one()),
+            });
+        }
+
+        // Exactly one position is selected: sum(s_k) = 1
+        self.push_generic(GenericGateSpec::Const(Fp::one()));
+
+        // offset = sum(k * s_k)
+        self.equal();
+
+        // needle[j] = sum_k s_k * haystack[k + j]
+        for _ in 0..needle_len {
+            for _ in 0..positions {
+                self.push_generic(GenericGateSpec::Mul {
+                    mul_coeff: Some(Fp::one()),
+                    output_coeff: Some(-Fp::one()),
+                });
+                self.accumulate();
+            }
+            self.equal();
+        }
+
+        start
+    }
+
+    pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
+        (self.gates, self.current_row)
+    }
+}
+
+/// Witness generator for byte-string gadgets.
+pub struct BytesWitness;
+
+impl BytesWitness {
+    /// Convert bytes to field elements, one per byte.
+    pub fn to_field_elements(bytes: &[u8]) -> Vec<Fp> {
+        bytes.iter().map(|&b| Fp::from(b as u64)).collect()
+    }
+
+    /// Find the first offset at which `needle` occurs in `haystack`.
+    pub fn find_substring(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        if needle.len() > haystack.len() {
+            return None;
+        }
+        if needle.is_empty() {
+            return Some(0);
+        }
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    /// One-hot selector for `offset` over all valid start positions.
+    pub fn offset_selector(
+        offset: usize,
+        haystack_len: usize,
+        needle_len: usize,
+    ) -> Option<Vec<Fp>> {
+        if needle_len > haystack_len || offset > haystack_len - needle_len {
+            return None;
+        }
+
+        let positions = haystack_len - needle_len + 1;
+        let mut selector = vec![Fp::zero(); positions];
+        selector[offset] = Fp::one();
+        Some(selector)
+    }
+
+    /// Compute the selected bytes `sum_k s_k * haystack[k + j]` for each needle position.
+    pub fn select_window(haystack: &[u8], selector: &[Fp], needle_len: usize) -> Vec<Fp> {
+        let haystack = Self::to_field_elements(haystack);
+        (0..needle_len)
+            .map(|j| {
+                selector
+                    .iter()
+                    .enumerate()
+                    .map(|(k, s)| *s * haystack[k + j])
+                    .fold(Fp::zero(), |acc, term| acc + term)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_substring() {
+        let haystack = b"CN=example.com,O=Example";
+        assert_eq!(
+            BytesWitness::find_substring(haystack, b"example.com"),
+            Some(3)
+        );
+        assert_eq!(BytesWitness::find_substring(haystack, b"missing"), None);
+        assert_eq!(BytesWitness::find_substring(b"ab", b"abc"), None);
+    }
+
+    #[test]
+    fn test_select_window() {
+        let haystack = b"hello world";
+        let offset = BytesWitness::find_substring(haystack, b"world").unwrap();
+        let selector = BytesWitness::offset_selector(offset, haystack.len(), 5).unwrap();
+
+        assert_eq!(selector.len(), 7);
+        assert_eq!(selector.iter().filter(|s| **s == Fp::one()).count(), 1);
+        assert_eq!(
+            BytesWitness::select_window(haystack, &selector, 5),
+            BytesWitness::to_field_elements(b"world")
+        );
+    }
+
+    #[test]
+    fn test_offset_out_of_bounds() {
+        assert!(BytesWitness::offset_selector(7, 11, 5).is_none());
+        assert!(BytesWitness::offset_selector(0, 3, 5).is_none());
+    }
+
+    #[test]
+    fn test_gadget_construction() {
+        let mut gadget = BytesGadget::new(0);
+        gadget.bytes_equal(4);
+        let substring_start = gadget.contains_substring(8, 3);
+        let (gates, rows) = gadget.build();
+
+        assert_eq!(substring_start, 4);
+        // 6 positions: 6 boolean + 12 accumulators + 2 checks + 3 * (6 * 2 + 1)
+        assert_eq!(rows, 4 + 6 + 12 + 2 + 39);
+        assert_eq!(gates.len(), rows);
+    }
+}
@@ -0,0 +1,622 @@
+//! CBOR parsing gadgets for Kimchi circuits.
+//!
+//! Constrains a CBOR buffer as a stream of contiguous tokens (item headers,
+//! plus the content of byte and text strings) so that ISO 18013-5 mdoc and
+//! Mobile Security Object structures can be navigated in-circuit.
+//!
+//! Only definite lengths and minimal ("preferred") argument encodings with
+//! at most `CBOR_MAX_ARGUMENT_BYTES` bytes are accepted. Major type 7 is
+//! restricted to immediate simple values (`false`, `true`, `null`, ...).
+//!
+//! Nesting is tracked with an open-item counter: `open_0 = 1` and
+//! `open_{i+1} = open_i - 1 + children_i`, where arrays open `n` children,
+//! maps `2n` and tags one. A token `k` is a direct child of a container `m`
+//! exactly when every token strictly between them has a larger counter than
+//! token `k`, which is what `map_lookup` checks.
+
+use super::bytes::BytesGadget;
+use ark_ff::{One, Zero};
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use kimchi::circuits::wires::Wire;
+use mina_curves::pasta::Fp;
+use std::ops::Range;
+
+/// Maximum number of argument bytes following the initial byte.
+pub const CBOR_MAX_ARGUMENT_BYTES: usize = 4;
+
+/// Maximum CBOR header size.
+pub const CBOR_MAX_HEADER_LEN: usize = 1 + CBOR_MAX_ARGUMENT_BYTES;
+
+/// Bit width of offsets and open-item counters.
+pub const CBOR_OFFSET_BITS: usize = 16;
+
+pub const MAJOR_UNSIGNED: u8 = 0;
+pub const MAJOR_NEGATIVE: u8 = 1;
+pub const MAJOR_BYTES: u8 = 2;
+pub const MAJOR_TEXT: u8 = 3;
+pub const MAJOR_ARRAY: u8 = 4;
+pub const MAJOR_MAP: u8 = 5;
+pub const MAJOR_TAG: u8 = 6;
+pub const MAJOR_SIMPLE: u8 = 7;
+
+/// Argument encodings: (additional info, argument bytes, smallest minimal argument).
+const ARGUMENT_FORMS: [(u8, usize, u64); 3] = [(24, 1, 24), (25, 2, 1 << 8), (26, 4, 1 << 16)];
+
+/// Gadget for CBOR structure constraints.
+pub struct CborGadget {
+    gates: Vec<CircuitGate<Fp>>,
+    current_row: usize,
+}
+
+impl CborGadget {
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            gates: Vec::new(),
+            current_row: start_row,
+        }
+    }
+
+    pub fn current_row(&self) -> usize {
+        self.current_row
+    }
+
+    fn push_generic(&mut self, spec: GenericGateSpec<Fp>) {
+        let wires = Wire::for_row(self.current_row);
+        self.gates
+            .push(CircuitGate::create_generic_gadget(wires, spec, None));
+        self.current_row += 1;
+    }
+
+    /// Boolean constraint: b * b - b = 0.
+    fn boolean(&mut self) {
+        self.push_generic(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Weighted accumulation: acc_next = acc + weight * term.
+    fn accumulate(&mut self, weight: Fp) {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(weight),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Equality: a - b = 0.
+    fn equal(&mut self) {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(Fp::zero()),
+        });
+    }
+
+    /// Multiplication: a * b - c = 0.
+    fn mul(&mut self) {
+        self.push_generic(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Range check: 0 <= value < 2^num_bits.
+    fn range_check(&mut self, num_bits: usize) {
+        for _ in 0..num_bits {
+            self.boolean();
+        }
+        for _ in 0..num_bits.div_ceil(3) {
+            self.accumulate(Fp::one());
+        }
+    }
+
+    /// One-hot selector over `n` positions: booleans with sum(s_k) = 1.
+    fn one_hot(&mut self, n: usize) {
+        for _ in 0..n {
+            self.boolean();
+        }
+        for _ in 0..n {
+            self.accumulate(Fp::one());
+        }
+        self.push_generic(GenericGateSpec::Const(Fp::one()));
+    }
+
+    /// Append the gates of a sub-gadget that was started at `current_row`.
+    fn append(&mut self, (gates, next_row): (Vec<CircuitGate<Fp>>, usize)) {
+        self.gates.extend(gates);
+        self.current_row = next_row;
+    }
+
+    /// CBOR item header: major type, argument, header length and children.
+    ///
+    /// The initial byte is `32 * major + info`. A one-hot selector picks the
+    /// argument form (immediate `info < 24`, or 1, 2 or 4 following bytes),
+    /// which rules out reserved values and indefinite lengths; minimality is
+    /// enforced by range checking `argument - min_argument(form)`.
+    pub fn header(&mut self, major: Option<u8>) -> usize {
+        let start = self.current_row;
+
+        // Initial byte = 32 * major + info, with a one-hot major type selector
+        self.one_hot(8);
+        for k in 0..8u64 {
+            self.accumulate(Fp::from(k));
+        }
+        if let Some(major) = major {
+            self.push_generic(GenericGateSpec::Const(Fp::from(major as u64)));
+        }
+        self.range_check(5);
+        self.accumulate(Fp::from(32u64));
+
+        // Argument form selector: immediate or one of ARGUMENT_FORMS
+        self.one_hot(1 + ARGUMENT_FORMS.len());
+
+        // Immediate: s_imm * (23 - info) >= 0, and imm = s_imm * info
+        self.mul();
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(-Fp::one()),
+            right_coeff: Some(Fp::zero()),
+            output_coeff: Some(-Fp::one()),
+        });
+        self.push_generic(GenericGateSpec::Plus(Fp::from(23u64)));
+        self.mul();
+        self.range_check(5);
+
+        // info = imm + sum(s_f * info_f)
+        for (info, _, _) in ARGUMENT_FORMS {
+            self.accumulate(Fp::from(info as u64));
+        }
+        self.equal();
+
+        // Argument bytes and big-endian prefix values
+        for _ in 0..CBOR_MAX_ARGUMENT_BYTES {
+            self.range_check(8);
+            self.accumulate(Fp::from(256u64));
+        }
+
+        // argument = imm + sum(s_f * prefix_f), min = sum(s_f * min_f)
+        for (_, _, min) in ARGUMENT_FORMS {
+            self.mul();
+            self.accumulate(Fp::one());
+            self.accumulate(Fp::from(min));
+        }
+
+        // Minimal encoding: argument - min >= 0
+        self.accumulate(-Fp::one());
+        self.range_check(8 * CBOR_MAX_ARGUMENT_BYTES);
+
+        // header_len = 1 + sum(s_f * bytes_f)
+        for (_, bytes, _) in ARGUMENT_FORMS {
+            self.accumulate(Fp::from(bytes as u64));
+        }
+        self.push_generic(GenericGateSpec::Plus(Fp::one()));
+
+        // Simple values are immediate only: m_7 * (1 - s_imm) = 0
+        self.mul();
+        self.push_generic(GenericGateSpec::Const(Fp::zero()));
+
+        // children = argument * (m_array + 2 * m_map) + m_tag
+        self.accumulate(Fp::from(2u64));
+        self.mul();
+        self.accumulate(Fp::one());
+
+        start
+    }
+
+    /// A CBOR item at a witness offset in a buffer of `buf_len` bytes.
+    ///
+    /// `end = offset + header_len + (m_bytes + m_text) * argument` and `end <= buf_len`.
+    pub fn item(&mut self, major: Option<u8>, buf_len: usize) -> usize {
+        assert!(
+            buf_len >= CBOR_MAX_HEADER_LEN,
+            "buffer of {} bytes cannot hold a padded CBOR header",
+            buf_len
+        );
+
+        let start = self.current_row;
+
+        let mut bytes = BytesGadget::new(self.current_row);
+        bytes.contains_substring(buf_len, CBOR_MAX_HEADER_LEN);
+        self.append(bytes.build());
+
+        self.header(major);
+
+        // String content length and end
+        self.accumulate(Fp::one());
+        self.mul();
+        self.accumulate(Fp::one());
+        self.accumulate(Fp::one());
+
+        // buf_len - end >= 0
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(-Fp::one()),
+            right_coeff: Some(Fp::zero()),
+            output_coeff: Some(-Fp::one()),
+        });
+        self.push_generic(GenericGateSpec::Plus(Fp::from(buf_len as u64)));
+        self.range_check(CBOR_OFFSET_BITS);
+
+        start
+    }
+
+    /// Tokenize the first `num_tokens` items of the buffer.
+    ///
+    /// Token `i + 1` starts where token `i` ends, and the open-item counter
+    /// `open_{i+1} = open_i - 1 + children_i` is range checked, so the buffer
+    /// prefix is parsed exactly as a native CBOR decoder would.
+    pub fn tokens(&mut self, buf_len: usize, num_tokens: usize) -> usize {
+        let start = self.current_row;
+
+        // open_0 = 1, first token at offset 0
+        self.push_generic(GenericGateSpec::Const(Fp::one()));
+        self.push_generic(GenericGateSpec::Const(Fp::zero()));
+
+        for _ in 0..num_tokens {
+            self.item(None, buf_len);
+            // offset == previous end
+            self.equal();
+            // open_{i+1} = open_i + children_i - 1
+            self.accumulate(Fp::one());
+            self.push_generic(GenericGateSpec::Plus(-Fp::one()));
+            self.range_check(CBOR_OFFSET_BITS);
+        }
+
+        start
+    }
+
+    /// Select token fields with a one-hot selector: value = sum(s_i * field_i).
+    fn select_token(&mut self, num_tokens: usize, num_fields: usize) {
+        self.one_hot(num_tokens);
+        for _ in 0..num_fields {
+            for _ in 0..num_tokens {
+                self.mul();
+                self.accumulate(Fp::one());
+            }
+        }
+    }
+
+    /// Map key lookup: find the value stored under a public text-string key.
+    ///
+    /// Tokenizes `num_tokens` items, selects a map token `m` and a key token
+    /// `k`, and checks that `k` is a direct child of `m` at an even position
+    /// (a key, not a value) before the map is complete, that the key is a text
+    /// string of `key_len` bytes equal to the public key, and exposes the value
+    /// token `k + 1`.
+    pub fn map_lookup(&mut self, buf_len: usize, num_tokens: usize, key_len: usize) -> usize {
+        let start = self.current_row;
+
+        self.tokens(buf_len, num_tokens);
+
+        // Map token: major, argument, open counter after it
+        self.select_token(num_tokens, 3);
+        self.push_generic(GenericGateSpec::Const(Fp::from(MAJOR_MAP as u64)));
+
+        // Key token: major, argument, content offset, open counter
+        self.select_token(num_tokens, 4);
+        self.push_generic(GenericGateSpec::Const(Fp::from(MAJOR_TEXT as u64)));
+        self.push_generic(GenericGateSpec::Const(Fp::from(key_len as u64)));
+
+        // between_i = prefix(s_map)_i - prefix(s_key)_{i+1}, boolean
+        for _ in 0..num_tokens {
+            self.accumulate(Fp::one());
+            self.accumulate(Fp::one());
+            self.accumulate(-Fp::one());
+            self.boolean();
+        }
+
+        // between_i * (open_i - open_k - 1) >= 0
+        for _ in 0..num_tokens {
+            self.accumulate(-Fp::one());
+            self.mul();
+            self.range_check(CBOR_OFFSET_BITS);
+        }
+
+        // open_after_map - open_k = 2 * j with 0 <= j < argument
+        self.accumulate(-Fp::one());
+        self.accumulate(-Fp::from(2u64));
+        self.range_check(CBOR_OFFSET_BITS);
+        self.accumulate(-Fp::one());
+        self.push_generic(GenericGateSpec::Plus(-Fp::one()));
+        self.range_check(CBOR_OFFSET_BITS);
+
+        // Key content equals the public key
+        let mut bytes = BytesGadget::new(self.current_row);
+        bytes.contains_substring(buf_len, key_len);
+        self.append(bytes.build());
+
+        // Value token: offset, header and end
+        self.select_token(num_tokens, 4);
+
+        start
+    }
+
+    pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
+        (self.gates, self.current_row)
+    }
+}
+
+/// A parsed CBOR item header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CborHeader {
+    pub major: u8,
+    /// Immediate or following-byte argument (length, count, value or tag).
+    pub argument: u64,
+    /// Number of header bytes.
+    pub header_len: usize,
+}
+
+impl CborHeader {
+    /// Whether the item is a byte or text string, whose content follows the header.
+    pub fn is_string(&self) -> bool {
+        self.major == MAJOR_BYTES || self.major == MAJOR_TEXT
+    }
+
+    /// Number of nested items this item opens.
+    pub fn children(&self) -> u64 {
+        match self.major {
+            MAJOR_ARRAY => self.argument,
+            MAJOR_MAP => 2 * self.argument,
+            MAJOR_TAG => 1,
+            _ => 0,
+        }
+    }
+
+    /// Size of the token: header plus string content.
+    pub fn token_len(&self) -> usize {
+        if self.is_string() {
+            self.header_len + self.argument as usize
+        } else {
+            self.header_len
+        }
+    }
+}
+
+/// A CBOR token located in a buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CborToken {
+    pub offset: usize,
+    pub header: CborHeader,
+    /// Open-item counter before this token.
+    pub open: u64,
+}
+
+impl CborToken {
+    /// Byte range of the token.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.header.token_len()
+    }
+
+    /// Byte range of string content (empty for non-strings).
+    pub fn content(&self) -> Range<usize> {
+        self.offset + self.header.header_len..self.range().end
+    }
+}
+
+/// Witness generator for CBOR parsing.
+pub struct CborWitness;
+
+impl CborWitness {
+    /// Parse a strict CBOR header at `offset`.
+    pub fn parse_header(buf: &[u8], offset: usize) -> Option<CborHeader> {
+        let initial = *buf.get(offset)?;
+        let major = initial >> 5;
+        let info = initial & 0x1F;
+
+        let header = if info < 24 {
+            CborHeader {
+                major,
+                argument: info as u64,
+                header_len: 1,
+            }
+        } else {
+            let &(_, num_bytes, min) = ARGUMENT_FORMS.iter().find(|(i, _, _)| *i == info)?;
+            if major == MAJOR_SIMPLE {
+                return None;
+            }
+            let bytes = buf.get(offset + 1..offset + 1 + num_bytes)?;
+            let argument = bytes.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+            if argument < min {
+                return None;
+            }
+            CborHeader {
+                major,
+                argument,
+                header_len: 1 + num_bytes,
+            }
+        };
+
+        // A string longer than the buffer would overflow `token_len`
+        if header.is_string() && header.argument > buf.len() as u64 {
+            return None;
+        }
+        (offset + header.token_len() <= buf.len()).then_some(header)
+    }
+
+    /// Tokenize one complete CBOR data item starting at offset 0.
+    pub fn tokenize(buf: &[u8]) -> Option<Vec<CborToken>> {
+        let mut tokens = Vec::new();
+        let mut offset = 0;
+        let mut open = 1u64;
+
+        while open > 0 {
+            let header = Self::parse_header(buf, offset)?;
+            tokens.push(CborToken {
+                offset,
+                header,
+                open,
+            });
+            open = open - 1 + header.children();
+            offset += header.token_len();
+        }
+
+        Some(tokens)
+    }
+
+    /// Index one past the last token of the item starting at `index`.
+    pub fn item_end(tokens: &[CborToken], index: usize) -> Option<usize> {
+        let mut pending = 1u64;
+        let mut i = index;
+        while pending > 0 {
+            pending = pending - 1 + tokens.get(i)?.header.children();
+            i += 1;
+        }
+        Some(i)
+    }
+
+    /// Direct children of the container token at `index`.
+    pub fn children(tokens: &[CborToken], index: usize) -> Option<Vec<usize>> {
+        let count = tokens.get(index)?.header.children();
+        let mut children = Vec::new();
+        let mut i = index + 1;
+        for _ in 0..count {
+            children.push(i);
+            i = Self::item_end(tokens, i)?;
+        }
+        Some(children)
+    }
+
+    /// Token index of the value stored under the text key `key` in the map at `map_index`.
+    pub fn map_lookup(
+        buf: &[u8],
+        tokens: &[CborToken],
+        map_index: usize,
+        key: &str,
+    ) -> Option<usize> {
+        if tokens.get(map_index)?.header.major != MAJOR_MAP {
+            return None;
+        }
+
+        Self::children(tokens, map_index)?
+            .chunks(2)
+            .find(|entry| {
+                let token = &tokens[entry[0]];
+                token.header.major == MAJOR_TEXT && &buf[token.content()] == key.as_bytes()
+            })
+            .map(|entry| entry[1])
+    }
+
+    /// Byte range of the complete item starting at token `index`.
+    pub fn item_range(tokens: &[CborToken], index: usize) -> Option<Range<usize>> {
+        let end = Self::item_end(tokens, index)?;
+        Some(tokens.get(index)?.offset..tokens.get(end - 1)?.range().end)
+    }
+
+    /// Selector `between_i`, set for tokens strictly between the map and the key.
+    pub fn between_selector(num_tokens: usize, map_index: usize, key_index: usize) -> Vec<Fp> {
+        (0..num_tokens)
+            .map(|i| {
+                if map_index < i && i < key_index {
+                    Fp::one()
+                } else {
+                    Fp::zero()
+                }
+            })
+            .collect()
+    }
+
+    /// Zero-pad a buffer so every header window stays in bounds.
+    pub fn padded_buffer(buf: &[u8]) -> Vec<u8> {
+        let mut padded = buf.to_vec();
+        padded.resize(buf.len() + CBOR_MAX_ARGUMENT_BYTES, 0);
+        padded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Vec<u8> {
+        let mut out = vec![0x60 | s.len() as u8];
+        out.extend_from_slice(s.as_bytes());
+        out
+    }
+
+    // {"nested": {"version": 2}, "version": "1.0", "docType": "org.iso.18013.5.1.mDL"}
+    fn sample_document() -> Vec<u8> {
+        let mut doc = vec![0xA3];
+        doc.extend(text("nested"));
+        doc.push(0xA1);
+        doc.extend(text("version"));
+        doc.push(0x02);
+        doc.extend(text("version"));
+        doc.extend(text("1.0"));
+        doc.extend(text("docType"));
+        doc.extend([0x75]);
+        doc.extend_from_slice(b"org.iso.18013.5.1.mDL");
+        doc
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let header = CborWitness::parse_header(&[0x19, 0x01, 0x00], 0).unwrap();
+        assert_eq!(
+            (header.major, header.argument, header.header_len),
+            (0, 256, 3)
+        );
+
+        // Non-minimal, indefinite, reserved and float encodings are rejected
+        assert!(CborWitness::parse_header(&[0x18, 0x05], 0).is_none());
+        assert!(CborWitness::parse_header(&[0x9F, 0x01, 0xFF], 0).is_none());
+        assert!(CborWitness::parse_header(&[0x1C], 0).is_none());
+        assert!(CborWitness::parse_header(&[0xF9, 0x3C, 0x00], 0).is_none());
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let doc = sample_document();
+        let tokens = CborWitness::tokenize(&doc).unwrap();
+
+        assert_eq!(tokens.len(), 9);
+        assert_eq!(tokens.last().unwrap().range().end, doc.len());
+        assert_eq!(CborWitness::item_end(&tokens, 0), Some(tokens.len()));
+        assert_eq!(CborWitness::item_range(&tokens, 2), Some(8..18));
+    }
+
+    #[test]
+    fn test_map_lookup() {
+        let doc = sample_document();
+        let tokens = CborWitness::tokenize(&doc).unwrap();
+
+        // The outer "version" is found, not the nested one
+        let value = CborWitness::map_lookup(&doc, &tokens, 0, "version").unwrap();
+        assert_eq!(&doc[tokens[value].content()], b"1.0");
+
+        let value = CborWitness::map_lookup(&doc, &tokens, 0, "docType").unwrap();
+        assert_eq!(&doc[tokens[value].content()], b"org.iso.18013.5.1.mDL");
+
+        let nested = CborWitness::map_lookup(&doc, &tokens, 2, "version").unwrap();
+        assert_eq!(tokens[nested].header.argument, 2);
+
+        assert!(CborWitness::map_lookup(&doc, &tokens, 0, "missing").is_none());
+        assert!(CborWitness::map_lookup(&doc, &tokens, 1, "version").is_none());
+    }
+
+    #[test]
+    fn test_direct_child_counters() {
+        let doc = sample_document();
+        let tokens = CborWitness::tokenize(&doc).unwrap();
+        let key = 5;
+
+        // Every token between the map and a direct child has a larger counter
+        let between = CborWitness::between_selector(tokens.len(), 0, key);
+        for (token, s) in tokens.iter().zip(&between) {
+            if *s == Fp::one() {
+                assert!(token.open > tokens[key].open);
+            }
+        }
+        // The nested key (token 3) is not a direct child of the outer map
+        assert!(tokens[2].open <= tokens[3].open);
+    }
+
+    #[test]
+    fn test_gadget_construction() {
+        let mut gadget = CborGadget::new(0);
+        gadget.map_lookup(32, 4, 3);
+        let (gates, rows) = gadget.build();
+
+        assert_eq!(gates.len(), rows);
+        assert!(rows > 0);
+    }
+}
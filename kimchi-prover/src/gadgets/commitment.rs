@@ -0,0 +1,395 @@
+//! Salted Poseidon commitments to field elements.
+//!
+//! A commitment binds a prover to values before proving anything about
+//! them: it is registered with a verifier first, and later proofs take it
+//! as a public input and show that their private values open it. The
+//! commitment is
+//!
+//! `hash(tag("commitment"), n, salt, v_1, ..., v_n)`
+//!
+//! with `PoseidonWitness::hash`. The salt hides the values, so it must be
+//! random and kept with them; committing to the same values with the same
+//! salt gives the same commitment.
+//!
+//! Attributes such as birth dates or country codes take few values, so
+//! anyone holding a commitment to them without a salt, or with a guessable
+//! one, can hash every candidate until one matches and link the commitment
+//! to the holder. [`Salt`] refuses salts below 2^128, which catches unset
+//! salts, counters and timestamps, and every commitment and opening here
+//! takes a `Salt`. `CommitmentGadget::open_salted_cells` is the pattern for
+//! circuits over committed attributes.
+//!
+//! Verifiers outside Mina can take a byte hash commitment instead (see
+//! `HashFunction`): the digest of `salt || v_1 || ... || v_n`, each as 32
+//! big-endian bytes. With Keccak-256 that is Solidity's
+//! `keccak256(abi.encode(salt, v_1, ..., v_n))`.
+
+use ark_ff::{BigInteger, PrimeField, UniformRand, Zero};
+use kimchi::circuits::polynomials::generic::{GenericGateSpec, GENERIC_COEFFS};
+use mina_curves::pasta::Fp;
+use rand::{CryptoRng, RngCore};
+
+use super::digest::{DigestGadget, DigestWitness, HashFunction};
+use super::poseidon::{PoseidonGadget, PoseidonWitness};
+use crate::builder::{Cell, CircuitBuilder, WitnessBuilder};
+use crate::error::{ProverError, Result};
+use crate::types::{Bytes32, Secret};
+
+/// Domain tag of commitments.
+pub const COMMITMENT_DOMAIN: &str = "commitment";
+
+/// Bits a salt must span: salts below `2^MIN_SALT_BITS` are refused.
+pub const MIN_SALT_BITS: u32 = 128;
+
+/// A commitment salt large enough to hide low-entropy values.
+///
+/// Held in a [`Secret`], so it is wiped when dropped and cannot be
+/// formatted. A random field element is below 2^128 with
+/// probability 2^-126, so only salts that were not drawn at random fail
+/// the check.
+#[derive(Clone)]
+pub struct Salt(Secret<Fp>);
+
+impl Salt {
+    /// A fresh random salt.
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        loop {
+            if let Ok(salt) = Self::from_field(Fp::rand(rng)) {
+                return salt;
+            }
+        }
+    }
+
+    /// A salt kept from an earlier commitment.
+    ///
+    /// Returns `InvalidInput` if `value` is below `2^MIN_SALT_BITS`.
+    pub fn from_field(value: Fp) -> Result<Self> {
+        let bits = value.into_bigint().num_bits();
+        if bits <= MIN_SALT_BITS {
+            return Err(ProverError::InvalidInput(format!(
+                "commitment salt spans {} bits, at least {} are needed to hide low-entropy values",
+                bits,
+                MIN_SALT_BITS + 1
+            )));
+        }
+        Ok(Self(Secret::new(value)))
+    }
+
+    /// The salt as a field element.
+    pub fn value(&self) -> Fp {
+        *self.0.expose()
+    }
+
+    /// A fixed salt for laying out gates, whose witness is discarded.
+    pub(crate) fn placeholder() -> Self {
+        Self(Secret::new(Fp::from(u128::MAX) + Fp::from(1u64)))
+    }
+}
+
+/// Cell-level commitments.
+pub struct CommitmentGadget;
+
+impl CommitmentGadget {
+    /// Commitment to the values in `values` under the salt in `salt`,
+    /// matching `CommitmentWitness::commit`.
+    pub(crate) fn commit_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        values: &[Cell],
+        salt: Cell,
+    ) -> Cell {
+        let tag = CommitmentWitness::tag();
+        let len = Fp::from(values.len() as u64);
+        let mut elements = vec![Self::constant(cs, w, tag), Self::constant(cs, w, len), salt];
+        elements.extend_from_slice(values);
+        PoseidonGadget::hash_cells(cs, w, &elements)
+    }
+
+    /// Constrain `commitment`, typically a public input cell, to open to
+    /// `values` under `salt`.
+    pub(crate) fn open_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        commitment: Cell,
+        values: &[Cell],
+        salt: Cell,
+    ) {
+        let computed = Self::commit_cells(cs, w, values, salt);
+        cs.copy(computed, commitment);
+    }
+
+    /// Commitment under `hash` to the values in `values` with the salt in
+    /// `salt`, as the field elements `CommitmentWitness::commit_with` returns.
+    pub(crate) fn commit_with_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        hash: HashFunction,
+        values: &[Cell],
+        salt: Cell,
+    ) -> Vec<Cell> {
+        if !hash.is_bytes() {
+            return vec![Self::commit_cells(cs, w, values, salt)];
+        }
+        let mut message = Vec::new();
+        for cell in std::iter::once(&salt).chain(values) {
+            message.extend(DigestGadget::field_bytes_cells(cs, w, *cell));
+        }
+        let digest = DigestGadget::hash_bits_cells(cs, w, hash, &message);
+        DigestGadget::pack_cells(cs, w, &digest)
+    }
+
+    /// Constrain `commitment`, typically `hash.public_len()` public input
+    /// cells, to open under `hash` to `values` under `salt`.
+    pub(crate) fn open_with_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        hash: HashFunction,
+        commitment: &[Cell],
+        values: &[Cell],
+        salt: Cell,
+    ) {
+        assert_eq!(
+            commitment.len(),
+            hash.public_len(),
+            "a {:?} commitment takes {} cells",
+            hash,
+            hash.public_len()
+        );
+        let computed = Self::commit_with_cells(cs, w, hash, values, salt);
+        for (computed, cell) in computed.into_iter().zip(commitment) {
+            cs.copy(computed, *cell);
+        }
+    }
+
+    /// Allocate `values` and `salt` as private cells and constrain
+    /// `commitment`, typically a public input cell, to open to them.
+    ///
+    /// Returns the value cells, for the circuit to prove statements about
+    /// the committed values without revealing them.
+    pub fn open_salted_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        commitment: Cell,
+        values: &[Fp],
+        salt: &Salt,
+    ) -> Vec<Cell> {
+        let (cells, salt_cell) = Self::salted_cells(cs, w, values, salt);
+        Self::open_cells(cs, w, commitment, &cells, salt_cell);
+        cells
+    }
+
+    /// As [`open_salted_cells`](Self::open_salted_cells), for a commitment
+    /// under `hash` in `hash.public_len()` cells.
+    pub fn open_salted_with_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        hash: HashFunction,
+        commitment: &[Cell],
+        values: &[Fp],
+        salt: &Salt,
+    ) -> Vec<Cell> {
+        let (cells, salt_cell) = Self::salted_cells(cs, w, values, salt);
+        Self::open_with_cells(cs, w, hash, commitment, &cells, salt_cell);
+        cells
+    }
+
+    /// Private cells holding `values` followed by `salt`, three to a row.
+    fn salted_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        values: &[Fp],
+        salt: &Salt,
+    ) -> (Vec<Cell>, Cell) {
+        let private: Vec<Fp> = values.iter().copied().chain([salt.value()]).collect();
+        let mut cells = Vec::with_capacity(private.len());
+        for chunk in private.chunks(3) {
+            let row = cs.generic_coeffs([Fp::zero(); GENERIC_COEFFS]);
+            for (cell, value) in row.into_iter().zip(chunk) {
+                w.set(cell, *value);
+                cells.push(cell);
+            }
+        }
+        let salt_cell = cells.pop().expect("the salt has a cell");
+        (cells, salt_cell)
+    }
+
+    fn constant(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, value: Fp) -> Cell {
+        let [cell, _, _] = cs.generic(GenericGateSpec::Const(value));
+        w.set(cell, value);
+        cell
+    }
+}
+
+/// Native commitments matching the gadget.
+pub struct CommitmentWitness;
+
+impl CommitmentWitness {
+    /// Commitment to `values` under `salt`.
+    pub fn commit(values: &[Fp], salt: &Salt) -> Fp {
+        let mut elements = vec![Self::tag(), Fp::from(values.len() as u64), salt.value()];
+        elements.extend_from_slice(values);
+        PoseidonWitness::hash(&elements)
+    }
+
+    /// Byte hash commitment to `values` under `salt`: the digest of
+    /// `salt || v_1 || ... || v_n`, each as 32 big-endian bytes.
+    ///
+    /// # Panics
+    ///
+    /// If `hash` is `Poseidon`; use `commit`.
+    pub fn commit_digest(hash: HashFunction, values: &[Fp], salt: &Salt) -> Bytes32 {
+        let message: Vec<u8> = std::iter::once(&salt.value())
+            .chain(values)
+            .flat_map(|value| DigestWitness::field_bytes(*value).0)
+            .collect();
+        DigestWitness::hash(hash, &message)
+    }
+
+    /// Commitment to `values` under `salt` with `hash`, as the field
+    /// elements a circuit takes as public input: the Poseidon commitment,
+    /// or the packed digest.
+    pub fn commit_with(hash: HashFunction, values: &[Fp], salt: &Salt) -> Vec<Fp> {
+        if hash.is_bytes() {
+            DigestWitness::pack(&Self::commit_digest(hash, values, salt))
+        } else {
+            vec![Self::commit(values, salt)]
+        }
+    }
+
+    fn tag() -> Fp {
+        PoseidonWitness::domain_tag(COMMITMENT_DOMAIN).expect("the commitment tag is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_cells() {
+        let values = [Fp::from(50_000u64), Fp::from(7u64)];
+        let salt = Salt::random(&mut rand::rngs::OsRng);
+        let commitment = CommitmentWitness::commit(&values, &salt);
+        assert_ne!(
+            commitment,
+            CommitmentWitness::commit(&values, &Salt::random(&mut rand::rngs::OsRng))
+        );
+        assert_ne!(commitment, CommitmentWitness::commit(&values[..1], &salt));
+
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let cells: Vec<Cell> = values
+            .iter()
+            .chain([&salt.value()])
+            .map(|v| {
+                let [cell, _, _] = cs.generic(GenericGateSpec::Const(*v));
+                w.set(cell, *v);
+                cell
+            })
+            .collect();
+
+        let out = CommitmentGadget::commit_cells(&mut cs, &mut w, &cells[..2], cells[2]);
+        assert_eq!(w.get(out), commitment);
+        assert!(cs.check_witness(&w).is_ok());
+    }
+
+    #[test]
+    fn test_prove_opening() {
+        use crate::prover::KimchiProver;
+        use crate::testing::GadgetHarness;
+
+        let value = Fp::from(42u64);
+        let salt = Salt::random(&mut rand::rngs::OsRng);
+        // Registered with the verifier ahead of the proof
+        let commitment = KimchiProver::commit(&[value], &salt);
+
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let public = cs.public_input();
+        w.set(public, commitment);
+        CommitmentGadget::open_salted_cells(&mut cs, &mut w, public, &[value], &salt);
+
+        let proof = GadgetHarness::new()
+            .prove_circuit(cs.build(), w.columns(), 1)
+            .unwrap();
+        assert!(proof.verifies(&[commitment]));
+        assert!(!proof.verifies(&[KimchiProver::commit(&[value + Fp::from(1u64)], &salt)]));
+    }
+
+    #[test]
+    fn test_open_with_cells() {
+        let value = Fp::from(21u64);
+        let salt = Salt::random(&mut rand::rngs::OsRng);
+        assert_eq!(
+            CommitmentWitness::commit_with(HashFunction::Poseidon, &[value], &salt),
+            vec![CommitmentWitness::commit(&[value], &salt)]
+        );
+
+        let hash = HashFunction::Keccak256;
+        let digest = CommitmentWitness::commit_digest(hash, &[value], &salt);
+        let mut message = DigestWitness::field_bytes(salt.value()).to_vec();
+        message.extend_from_slice(&[0; 31]);
+        message.push(21);
+        assert_eq!(digest, DigestWitness::hash(hash, &message));
+        let commitment = CommitmentWitness::commit_with(hash, &[value], &salt);
+
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let public = cs.public_inputs(hash.public_len());
+        for (cell, element) in public.iter().zip(&commitment) {
+            w.set(*cell, *element);
+        }
+        let cells = CommitmentGadget::open_salted_with_cells(
+            &mut cs,
+            &mut w,
+            hash,
+            &public,
+            &[value],
+            &salt,
+        );
+        assert_eq!(w.get(cells[0]), value);
+        assert!(cs.check_witness(&w).is_ok());
+
+        w.set(public[1], commitment[1] + Fp::from(1u64));
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_salt_guardrails() {
+        use crate::prover::KimchiProver;
+
+        let birth_date = Fp::from(19_900_101u64);
+        assert!(Salt::from_field(Fp::zero()).is_err());
+        assert!(Salt::from_field(Fp::from(1_700_000_000u64)).is_err());
+        assert!(Salt::from_field(Fp::from(u128::MAX)).is_err());
+        assert!(Salt::from_field(Salt::placeholder().value()).is_ok());
+
+        let salt = Salt::random(&mut rand::rngs::OsRng);
+        assert!(Salt::from_field(salt.value()).is_ok());
+        assert_eq!(
+            CommitmentWitness::commit(&[birth_date], &salt),
+            KimchiProver::commit(&[birth_date], &salt)
+        );
+    }
+
+    #[test]
+    fn test_open_salted_cells() {
+        let attributes = [Fp::from(19_900_101u64), Fp::from(276u64), Fp::from(1u64)];
+        let salt = Salt::random(&mut rand::rngs::OsRng);
+        let commitment = CommitmentWitness::commit(&attributes, &salt);
+
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let public = cs.public_input();
+        w.set(public, commitment);
+        let cells =
+            CommitmentGadget::open_salted_cells(&mut cs, &mut w, public, &attributes, &salt);
+        assert_eq!(cells.len(), attributes.len());
+        assert_eq!(w.get(cells[1]), attributes[1]);
+        assert!(cs.check_witness(&w).is_ok());
+
+        // A dictionary guess under another salt does not match
+        let guess = CommitmentWitness::commit(&attributes, &Salt::random(&mut rand::rngs::OsRng));
+        assert_ne!(guess, commitment);
+    }
+}
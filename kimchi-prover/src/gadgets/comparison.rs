@@ -1,15 +1,120 @@
 //! Comparison gadgets for Kimchi circuits.
 
-use ark_ff::{One, Zero};
+use ark_ff::{Field, One, PrimeField, Zero};
 use kimchi::circuits::gate::CircuitGate;
-use kimchi::circuits::polynomials::generic::GenericGateSpec;
-use kimchi::circuits::wires::Wire;
+use kimchi::circuits::polynomials::generic::{GenericGateSpec, GENERIC_COEFFS};
 use mina_curves::pasta::Fp;
 
+use super::boolean::BooleanGadget;
+use super::digits::DigitsWitness;
+use crate::builder::{
+    fill_row_layout, generic_spec_coeffs, push_packed, Cell, CircuitBuilder, Gadget,
+    WitnessBuilder, WitnessLayout,
+};
+use crate::error::{ProverError, Result};
+use crate::prover::COLUMNS;
+
+/// Bits the slack of `ComparisonGadget::age_check` is range checked to.
+const AGE_SLACK_BITS: usize = 7;
+
+/// Bits of the low limb of `ComparisonGadget::field_limbs_cells`.
+const LIMB_BITS: usize = 128;
+
+/// `[high, low]` 128-bit limbs of a 256-bit integer's little-endian words.
+fn limbs(words: [u64; 4]) -> [Fp; 2] {
+    let limb = |low: u64, high: u64| Fp::from(low as u128 | (high as u128) << 64);
+    [limb(words[2], words[3]), limb(words[0], words[1])]
+}
+
 /// Gadget for comparison operations.
+///
+/// Every check is built from single generic constraints packed two per row
+/// (see `builder::pack_generic`).
 pub struct ComparisonGadget {
     gates: Vec<CircuitGate<Fp>>,
     current_row: usize,
+    open_half: bool,
+    age_checks: Vec<AgeCheckRows>,
+}
+
+/// Where an `age_check` placed its constraints, as (left, right, output)
+/// cells, for wiring it and for `ComparisonWitness::fill_age_check`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AgeCheckRows {
+    pub minimum_age: u32,
+    /// Current year, birth year, and their difference, the base age.
+    pub years: [Cell; 3],
+    /// Current month, birth month, and their difference.
+    pub months: [Cell; 3],
+    /// Current day, birth day, and their difference.
+    pub days: [Cell; 3],
+    /// The adjustment bit, in all three cells.
+    pub adjustment: [Cell; 3],
+    /// Base age, adjustment, and the age.
+    pub age: [Cell; 3],
+    /// Age, unused, and the age above `minimum_age`.
+    pub slack: [Cell; 3],
+    /// The 7 bits of the slack, least significant first.
+    pub bits: Vec<[Cell; 3]>,
+    /// Running sums of the slack's 3-bit groups: previous sum, group value,
+    /// new sum.
+    pub sums: Vec<[Cell; 3]>,
+}
+
+impl AgeCheckRows {
+    /// Birth year, month and day cells.
+    pub fn birth(&self) -> [Cell; 3] {
+        [self.years[1], self.months[1], self.days[1]]
+    }
+
+    /// Current year, month and day cells.
+    pub fn current(&self) -> [Cell; 3] {
+        [self.years[0], self.months[0], self.days[0]]
+    }
+
+    /// Copy constraints between the check's own constraints: the base age,
+    /// adjustment, age and slack carried from one to the next, each bit
+    /// in all cells of its check, and the chain of sums ending at the slack.
+    pub fn copies(&self) -> Vec<(Cell, Cell)> {
+        let mut copies = vec![
+            (self.years[2], self.age[0]),
+            (self.adjustment[0], self.adjustment[1]),
+            (self.adjustment[0], self.adjustment[2]),
+            (self.adjustment[0], self.age[1]),
+            (self.age[2], self.slack[0]),
+        ];
+        for bit in &self.bits {
+            copies.push((bit[0], bit[1]));
+            copies.push((bit[0], bit[2]));
+        }
+        for pair in self.sums.windows(2) {
+            copies.push((pair[0][2], pair[1][0]));
+        }
+        if let Some(last) = self.sums.last() {
+            copies.push((last[2], self.slack[2]));
+        }
+        copies
+    }
+
+    /// Wire the check into `cs`, which holds the gadget's rows (see
+    /// `Gadget::build`), and tie its dates to `birth` and `current`, e.g.
+    /// public input cells.
+    pub fn connect(
+        &self,
+        cs: &mut CircuitBuilder,
+        birth: [Cell; 3],
+        current: [Cell; 3],
+    ) -> Result<()> {
+        for (a, b) in self.copies() {
+            cs.connect(a, b)?;
+        }
+        for (date, cells) in [(birth, self.birth()), (current, self.current())] {
+            for (a, b) in date.into_iter().zip(cells) {
+                cs.connect(a, b)?;
+            }
+        }
+        Ok(())
+    }
 }
 
 impl ComparisonGadget {
@@ -17,6 +122,8 @@ impl ComparisonGadget {
         Self {
             gates: Vec::new(),
             current_row: start_row,
+            open_half: false,
+            age_checks: Vec::new(),
         }
     }
 
@@ -24,40 +131,44 @@ impl ComparisonGadget {
         self.current_row
     }
 
-    /// Equality constraint: a == b.
-    pub fn equal(&mut self) -> usize {
-        let row = self.current_row;
-        let wires = Wire::for_row(row);
+    /// Cells of the age checks laid out so far.
+    pub fn age_checks(&self) -> &[AgeCheckRows] {
+        &self.age_checks
+    }
 
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Add {
-                left_coeff: Some(Fp::one()),
-                right_coeff: Some(-Fp::one()),
-                output_coeff: Some(Fp::zero()),
-            },
-            None,
-        ));
+    /// Add a constraint and return its (left, right, output) cells.
+    fn push_cells(&mut self, coeffs: [Fp; GENERIC_COEFFS]) -> [Cell; 3] {
+        let first_row = self.current_row - self.gates.len();
+        let cells = push_packed(&mut self.gates, first_row, &mut self.open_half, coeffs);
+        self.current_row = first_row + self.gates.len();
+        cells
+    }
 
-        self.current_row += 1;
-        row
+    /// Add a constraint and return the row holding it.
+    fn push_constraint(&mut self, coeffs: [Fp; GENERIC_COEFFS]) -> usize {
+        self.push_cells(coeffs)[0].row
+    }
+
+    fn push_generic(&mut self, spec: GenericGateSpec<Fp>) -> usize {
+        self.push_constraint(generic_spec_coeffs(spec))
+    }
+
+    /// Equality constraint: a == b.
+    pub fn equal(&mut self) -> usize {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(Fp::zero()),
+        })
     }
 
     /// Greater than or equal constraint: a >= b.
     pub fn greater_or_equal(&mut self, max_bits: usize) -> usize {
-        let start = self.current_row;
-
-        let wires = Wire::for_row(self.current_row);
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Add {
-                left_coeff: Some(Fp::one()),
-                right_coeff: Some(-Fp::one()),
-                output_coeff: Some(-Fp::one()),
-            },
-            None,
-        ));
-        self.current_row += 1;
+        let start = self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
 
         self.range_check(max_bits);
 
@@ -66,124 +177,91 @@ impl ComparisonGadget {
 
     /// Range check: 0 <= value < 2^num_bits.
     pub fn range_check(&mut self, num_bits: usize) -> usize {
-        let start = self.current_row;
+        // The first bit check may share the previous constraint's row
+        let (bits, _) = self.range_check_cells_at(num_bits);
+        bits.first().map_or(self.current_row, |cells| cells[0].row)
+    }
 
-        for _ in 0..num_bits {
-            let wires = Wire::for_row(self.current_row);
-            self.gates.push(CircuitGate::create_generic_gadget(
-                wires,
-                GenericGateSpec::Mul {
+    /// `range_check`, returning the cells of its bit checks and sums.
+    fn range_check_cells_at(&mut self, num_bits: usize) -> (Vec<[Cell; 3]>, Vec<[Cell; 3]>) {
+        let bits = (0..num_bits)
+            .map(|_| {
+                self.push_cells(generic_spec_coeffs(GenericGateSpec::Mul {
                     mul_coeff: Some(Fp::one()),
                     output_coeff: Some(-Fp::one()),
-                },
-                None,
-            ));
-            self.current_row += 1;
-        }
-
-        let num_sum_gates = (num_bits + 2) / 3;
-        for _ in 0..num_sum_gates {
-            let wires = Wire::for_row(self.current_row);
-            self.gates.push(CircuitGate::create_generic_gadget(
-                wires,
-                GenericGateSpec::Add {
+                }))
+            })
+            .collect();
+
+        let num_sum_gates = num_bits.div_ceil(3);
+        let sums = (0..num_sum_gates)
+            .map(|_| {
+                self.push_cells(generic_spec_coeffs(GenericGateSpec::Add {
                     left_coeff: Some(Fp::one()),
                     right_coeff: Some(Fp::one()),
                     output_coeff: Some(-Fp::one()),
-                },
-                None,
-            ));
-            self.current_row += 1;
-        }
+                }))
+            })
+            .collect();
 
-        start
+        (bits, sums)
     }
 
     /// Date comparison for age verification.
+    ///
+    /// The cells are recorded in `age_checks`; its `connect` adds the copy
+    /// constraints.
     pub fn age_check(&mut self, minimum_age: u32) -> usize {
-        let start = self.current_row;
-
-        // Subtract years
-        let wires = Wire::for_row(self.current_row);
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Add {
+        let difference = || {
+            generic_spec_coeffs(GenericGateSpec::Add {
                 left_coeff: Some(Fp::one()),
                 right_coeff: Some(-Fp::one()),
                 output_coeff: Some(-Fp::one()),
-            },
-            None,
-        ));
-        self.current_row += 1;
+            })
+        };
+
+        // Subtract years
+        let years = self.push_cells(difference());
 
         // Month comparison
-        let wires = Wire::for_row(self.current_row);
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Add {
-                left_coeff: Some(Fp::one()),
-                right_coeff: Some(-Fp::one()),
-                output_coeff: Some(-Fp::one()),
-            },
-            None,
-        ));
-        self.current_row += 1;
+        let months = self.push_cells(difference());
 
         // Day comparison
-        let wires = Wire::for_row(self.current_row);
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Add {
-                left_coeff: Some(Fp::one()),
-                right_coeff: Some(-Fp::one()),
-                output_coeff: Some(-Fp::one()),
-            },
-            None,
-        ));
-        self.current_row += 1;
+        let days = self.push_cells(difference());
 
         // Adjustment is boolean
-        let wires = Wire::for_row(self.current_row);
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Mul {
-                mul_coeff: Some(Fp::one()),
-                output_coeff: Some(-Fp::one()),
-            },
-            None,
-        ));
-        self.current_row += 1;
+        let adjustment = self.push_cells(generic_spec_coeffs(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        }));
 
         // Final age = base_age - adjustment
-        let wires = Wire::for_row(self.current_row);
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Add {
-                left_coeff: Some(Fp::one()),
-                right_coeff: Some(-Fp::one()),
-                output_coeff: Some(-Fp::one()),
-            },
-            None,
-        ));
-        self.current_row += 1;
-
-        // Check age >= minimum_age
-        let wires = Wire::for_row(self.current_row);
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Add {
-                left_coeff: Some(Fp::one()),
-                right_coeff: Some(Fp::zero()),
-                output_coeff: Some(-Fp::one()),
-            },
-            Some(GenericGateSpec::Const(-Fp::from(minimum_age as u64))),
-        ));
-        self.current_row += 1;
+        let age = self.push_cells(difference());
 
-        // Range check the difference (7 bits for age up to 127)
-        self.range_check(7);
+        // Check age >= minimum_age: age - minimum_age - slack = 0
+        let slack = self.push_cells([
+            Fp::one(),
+            Fp::zero(),
+            -Fp::one(),
+            Fp::zero(),
+            -Fp::from(minimum_age as u64),
+        ]);
 
-        start
+        // Range check the difference (7 bits for age up to 127)
+        let (bits, sums) = self.range_check_cells_at(AGE_SLACK_BITS);
+
+        self.age_checks.push(AgeCheckRows {
+            minimum_age,
+            years,
+            months,
+            days,
+            adjustment,
+            age,
+            slack,
+            bits,
+            sums,
+        });
+        years[0].row
     }
 
     pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
@@ -191,6 +269,273 @@ impl ComparisonGadget {
     }
 }
 
+impl Gadget for ComparisonGadget {
+    type Inputs = [[Fp; COLUMNS]];
+
+    fn build(&self, cs: &mut CircuitBuilder) -> usize {
+        cs.extend_gates(&self.gates, self.current_row - self.gates.len())
+    }
+
+    fn rows(&self) -> usize {
+        self.gates.len()
+    }
+
+    fn witness(&self, w: &mut WitnessBuilder, rows: &Self::Inputs) -> Result<()> {
+        fill_row_layout(w, self.rows(), rows)
+    }
+}
+
+/// Cell-level comparisons.
+impl ComparisonGadget {
+    /// Equality: a - b = 0.
+    pub fn equal_cells(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, a: Cell, b: Cell) {
+        let cells = cs.generic_packed(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(Fp::zero()),
+        });
+        w.set_cells(cells, [w.get(a), w.get(b), Fp::zero()]);
+        cs.copy(a, cells[0]);
+        cs.copy(b, cells[1]);
+    }
+
+    /// Range check: 0 <= value < 2^num_bits. Returns the bit cells.
+    pub fn range_check_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        value: Cell,
+        num_bits: usize,
+    ) -> Vec<Cell> {
+        BooleanGadget::decompose_cells(cs, w, value, num_bits)
+    }
+
+    /// a >= b for values below 2^max_bits: diff = a - b is range checked.
+    ///
+    /// Returns the difference cell.
+    pub fn greater_or_equal_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: Cell,
+        b: Cell,
+        max_bits: usize,
+    ) -> Cell {
+        let cells = cs.generic_packed(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        let (va, vb) = (w.get(a), w.get(b));
+        w.set_cells(cells, [va, vb, va - vb]);
+        cs.copy(a, cells[0]);
+        cs.copy(b, cells[1]);
+
+        Self::range_check_cells(cs, w, cells[2], max_bits);
+        cells[2]
+    }
+
+    /// Bit cell holding whether a < b, for a and b below 2^max_bits.
+    ///
+    /// With s = b - a and the bit c, `diff = c * (s - 1) + (1 - c) * (-s)`,
+    /// i.e. `2cs - s - c`, is range checked to `max_bits`: it is `s - 1`
+    /// when c claims a < b and `a - b` otherwise, and the wrong claim makes
+    /// it negative, which wraps around the field far above the range as
+    /// long as `max_bits` is at most 253. The caller range checks a and b;
+    /// `less_than_limbs_cells` compares whole field elements.
+    pub fn less_than_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: Cell,
+        b: Cell,
+        max_bits: usize,
+    ) -> Cell {
+        assert!(
+            max_bits < Fp::MODULUS_BIT_SIZE as usize - 1,
+            "cannot compare {}-bit values",
+            max_bits
+        );
+        let (va, vb) = (w.get(a), w.get(b));
+        let below = va.into_bigint() < vb.into_bigint();
+        let bit = BooleanGadget::alloc_bit(cs, w, below);
+
+        let s = cs.generic_packed(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(s, [vb, va, vb - va]);
+        cs.copy(b, s[0]);
+        cs.copy(a, s[1]);
+
+        let diff = cs.generic_coeffs_packed([
+            -Fp::one(),
+            -Fp::one(),
+            -Fp::one(),
+            Fp::from(2u64),
+            Fp::zero(),
+        ]);
+        let (vc, vs) = (w.get(bit), vb - va);
+        w.set_cells(diff, [vc, vs, Fp::from(2u64) * vc * vs - vs - vc]);
+        cs.copy(bit, diff[0]);
+        cs.copy(s[2], diff[1]);
+
+        Self::range_check_cells(cs, w, diff[2], max_bits);
+        bit
+    }
+
+    /// Bit cell holding whether a == b.
+    ///
+    /// With d = a - b and a witnessed inverse, `d * inv = 1 - c` and
+    /// `d * c = 0` force c = 1 exactly when d = 0.
+    pub fn is_equal_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: Cell,
+        b: Cell,
+    ) -> Cell {
+        let d = cs.generic_packed(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        let vd = w.get(a) - w.get(b);
+        w.set_cells(d, [w.get(a), w.get(b), vd]);
+        cs.copy(a, d[0]);
+        cs.copy(b, d[1]);
+
+        let equal = Fp::from(vd.is_zero() as u64);
+        let inverse =
+            cs.generic_coeffs_packed([Fp::zero(), Fp::zero(), Fp::one(), Fp::one(), -Fp::one()]);
+        w.set_cells(inverse, [vd, vd.inverse().unwrap_or_else(Fp::zero), equal]);
+        cs.copy(d[2], inverse[0]);
+
+        let product =
+            cs.generic_coeffs_packed([Fp::zero(), Fp::zero(), Fp::zero(), Fp::one(), Fp::zero()]);
+        w.set_cells(product, [vd, equal, Fp::zero()]);
+        cs.copy(d[2], product[0]);
+        cs.copy(inverse[2], product[1]);
+
+        inverse[2]
+    }
+
+    /// Split a field element into `[high, low]` limbs of at most 127 and
+    /// 128 bits, `value = high * 2^128 + low`.
+    ///
+    /// The limbs are range checked and constrained to be below the modulus
+    /// as a pair, so they are the integer value of the element: without
+    /// that, `value + p` would also fit the 255 bits.
+    pub fn field_limbs_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        value: Cell,
+    ) -> [Cell; 2] {
+        let cells = cs.generic_packed(GenericGateSpec::Add {
+            left_coeff: Some(Fp::from(2u64).pow([LIMB_BITS as u64])),
+            right_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        let [high, low] = limbs(w.get(value).into_bigint().0);
+        w.set_cells(cells, [high, low, w.get(value)]);
+        cs.copy(value, cells[2]);
+        Self::range_check_cells(cs, w, cells[0], Fp::MODULUS_BIT_SIZE as usize - LIMB_BITS);
+        Self::range_check_cells(cs, w, cells[1], LIMB_BITS);
+
+        // Below the modulus, split the same way
+        let modulus = limbs(Fp::MODULUS.0).map(|limb| {
+            let [cell, _, _] = cs.generic_packed(GenericGateSpec::Const(limb));
+            w.set(cell, limb);
+            cell
+        });
+        let canonical = Self::less_than_limbs_cells(cs, w, [cells[0], cells[1]], modulus);
+        let one = BooleanGadget::constant_bit(cs, w, true);
+        cs.copy(canonical, one);
+
+        [cells[0], cells[1]]
+    }
+
+    /// Bit cell holding whether a < b, for `[high, low]` limbs below 2^128
+    /// each, e.g. from `field_limbs_cells`: the high limbs compare, or are
+    /// equal and the low limbs compare.
+    pub fn less_than_limbs_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: [Cell; 2],
+        b: [Cell; 2],
+    ) -> Cell {
+        let high_below = Self::less_than_cells(cs, w, a[0], b[0], LIMB_BITS);
+        let high_equal = Self::is_equal_cells(cs, w, a[0], b[0]);
+        let low_below = Self::less_than_cells(cs, w, a[1], b[1], LIMB_BITS);
+
+        let tie_below = BooleanGadget::and_cells(cs, w, high_equal, low_below);
+        // The two cases exclude each other, so their sum is a bit
+        let below = cs.generic_packed(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        let (vh, vt) = (w.get(high_below), w.get(tie_below));
+        w.set_cells(below, [vh, vt, vh + vt]);
+        cs.copy(high_below, below[0]);
+        cs.copy(tie_below, below[1]);
+
+        below[2]
+    }
+
+    /// Pack a (year, month, day) date as `year * 10000 + month * 100 + day`.
+    fn date_cells(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, date: [Cell; 3]) -> Cell {
+        let [year, month, day] = date.map(|c| w.get(c));
+
+        let ym = cs.generic_packed(GenericGateSpec::Add {
+            left_coeff: Some(Fp::from(10000u64)),
+            right_coeff: Some(Fp::from(100u64)),
+            output_coeff: Some(-Fp::one()),
+        });
+        let ym_value = year * Fp::from(10000u64) + month * Fp::from(100u64);
+        w.set_cells(ym, [year, month, ym_value]);
+        cs.copy(date[0], ym[0]);
+        cs.copy(date[1], ym[1]);
+
+        let packed = cs.generic_packed(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(packed, [ym_value, day, ym_value + day]);
+        cs.copy(ym[2], packed[0]);
+        cs.copy(date[2], packed[1]);
+
+        packed[2]
+    }
+
+    /// Age check: the person born on `birth` is at least `minimum_age` on `current`.
+    ///
+    /// With dates packed as `YYYYMMDD`, this holds exactly when
+    /// `current - birth - minimum_age * 10000 >= 0`, which avoids a separate
+    /// month/day adjustment. Month and day cells are assumed to be in range
+    /// (e.g. from `DigitsGadget::parse_mrz_date`).
+    pub fn age_check_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        birth: [Cell; 3],
+        current: [Cell; 3],
+        minimum_age: u32,
+    ) -> Cell {
+        let birth = Self::date_cells(cs, w, birth);
+        let current = Self::date_cells(cs, w, current);
+
+        let offset = Fp::from(minimum_age as u64 * 10000);
+        let cells =
+            cs.generic_coeffs_packed([Fp::one(), -Fp::one(), -Fp::one(), Fp::zero(), -offset]);
+        let (vc, vb) = (w.get(current), w.get(birth));
+        w.set_cells(cells, [vc, vb, vc - vb - offset]);
+        cs.copy(current, cells[0]);
+        cs.copy(birth, cells[1]);
+
+        // Ages up to ~800 years fit in 23 bits of YYYYMMDD difference
+        Self::range_check_cells(cs, w, cells[2], 23);
+        cells[2]
+    }
+}
+
 /// Witness generator for comparisons.
 pub struct ComparisonWitness;
 
@@ -213,18 +558,92 @@ impl ComparisonWitness {
         age
     }
 
+    /// Parse an MRZ `YYMMDD` date.
+    ///
+    /// Uses the same strict digit rules as `DigitsGadget::parse_mrz_date`, so any
+    /// string accepted here can also be parsed in-circuit.
     pub fn parse_mrz_date(date_str: &str) -> Option<(u32, u32, u32)> {
-        if date_str.len() != 6 {
+        let bytes = date_str.as_bytes();
+        if bytes.len() != 6 {
+            return None;
+        }
+
+        let yy = DigitsWitness::parse_digits(&bytes[0..2])? as u32;
+        let mm = DigitsWitness::parse_digits(&bytes[2..4])? as u32;
+        let dd = DigitsWitness::parse_digits(&bytes[4..6])? as u32;
+
+        if !(1..=12).contains(&mm) || !(1..=31).contains(&dd) {
             return None;
         }
 
-        let yy: u32 = date_str[0..2].parse().ok()?;
-        let mm: u32 = date_str[2..4].parse().ok()?;
-        let dd: u32 = date_str[4..6].parse().ok()?;
+        Some((DigitsWitness::mrz_year(yy), mm, dd))
+    }
+
+    /// Write the witness of the age check at `rows` (from
+    /// `ComparisonGadget::age_checks`) into `layout`, for a holder born on
+    /// `birth` as of `current`, both `(year, month, day)`. Returns the age.
+    ///
+    /// The slack's bits are its low 7 bits, so a holder younger than
+    /// `minimum_age` fills rows that do not satisfy the copy constraints.
+    pub fn fill_age_check(
+        layout: &mut WitnessLayout,
+        rows: &AgeCheckRows,
+        birth: (u32, u32, u32),
+        current: (u32, u32, u32),
+    ) -> Result<u32> {
+        if current < birth {
+            return Err(ProverError::InvalidInput(format!(
+                "date {:?} is before the birth date {:?}",
+                current, birth
+            )));
+        }
+        let last = rows.sums.last().unwrap_or(&rows.slack)[2].row;
+        if last >= layout.circuit_rows() {
+            return Err(ProverError::WitnessRowCount {
+                expected: last + 1,
+                actual: layout.circuit_rows(),
+            });
+        }
 
-        let year = if yy <= 29 { 2000 + yy } else { 1900 + yy };
+        let (by, bm, bd) = birth;
+        let (cy, cm, cd) = current;
+        let age = Self::compute_age(by, bm, bd, cy, cm, cd);
+        let adjustment = Fp::from(cy - by - age);
+        let field = |v: u32| Fp::from(v as u64);
+        let slack = field(age) - field(rows.minimum_age);
+        let slack_bits = (age as i64 - rows.minimum_age as i64) as u64;
+
+        let mut set = |cells: [Cell; 3], values: [Fp; 3]| {
+            for (cell, value) in cells.into_iter().zip(values) {
+                layout.set(cell, value);
+            }
+        };
+        set(rows.years, [field(cy), field(by), field(cy - by)]);
+        set(rows.months, [field(cm), field(bm), field(cm) - field(bm)]);
+        set(rows.days, [field(cd), field(bd), field(cd) - field(bd)]);
+        set(rows.adjustment, [adjustment; 3]);
+        set(rows.age, [field(cy - by), adjustment, field(age)]);
+        set(rows.slack, [field(age), Fp::zero(), slack]);
+
+        let bits = Self::decompose_for_range_check(slack_bits, rows.bits.len());
+        for (&cells, bit) in rows.bits.iter().zip(&bits) {
+            set(cells, [*bit; 3]);
+        }
+        let mut sum = Fp::zero();
+        for (k, &cells) in rows.sums.iter().enumerate() {
+            let group = bits
+                .iter()
+                .enumerate()
+                .skip(3 * k)
+                .take(3)
+                .filter(|(_, bit)| !bit.is_zero())
+                .map(|(i, _)| Fp::from(1u64 << i))
+                .sum::<Fp>();
+            set(cells, [sum, group, sum + group]);
+            sum += group;
+        }
 
-        Some((year, mm, dd))
+        Ok(age)
     }
 
     pub fn decompose_for_range_check(value: u64, num_bits: usize) -> Vec<Fp> {
@@ -261,15 +680,171 @@ mod tests {
             ComparisonWitness::parse_mrz_date("050620"),
             Some((2005, 6, 20))
         );
+        assert_eq!(ComparisonWitness::parse_mrz_date("+90115"), None);
+        assert_eq!(ComparisonWitness::parse_mrz_date("901315"), None);
+    }
+
+    fn alloc_date(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, date: [u64; 3]) -> [Cell; 3] {
+        date.map(|v| {
+            let [cell, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(v)));
+            w.set(cell, Fp::from(v));
+            cell
+        })
+    }
+
+    #[test]
+    fn test_age_check_cells() {
+        for (birth, current, expected) in [
+            ([2006, 2, 1], [2024, 2, 1], true),
+            ([2006, 2, 2], [2024, 2, 1], false),
+            ([1990, 12, 31], [2024, 1, 1], true),
+        ] {
+            let mut cs = CircuitBuilder::new(0);
+            let mut w = WitnessBuilder::new(0);
+            let birth = alloc_date(&mut cs, &mut w, birth);
+            let current = alloc_date(&mut cs, &mut w, current);
+
+            ComparisonGadget::age_check_cells(&mut cs, &mut w, birth, current, 18);
+            assert_eq!(cs.check_witness(&w).is_ok(), expected);
+        }
+    }
+
+    #[test]
+    fn test_greater_or_equal_cells() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let [a, b, _] = cs.generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::zero()),
+            right_coeff: Some(Fp::zero()),
+            output_coeff: Some(Fp::zero()),
+        });
+        w.set(a, Fp::from(7u64));
+        w.set(b, Fp::from(9u64));
+
+        ComparisonGadget::greater_or_equal_cells(&mut cs, &mut w, a, b, 8);
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_field_limbs_and_less_than() {
+        let values = [
+            Fp::zero(),
+            Fp::from(u128::MAX),
+            Fp::from(u128::MAX) + Fp::one(),
+            -Fp::from(2u64),
+            -Fp::one(),
+        ];
+        for (i, a) in values.iter().enumerate() {
+            for (j, b) in values.iter().enumerate() {
+                let mut cs = CircuitBuilder::new(0);
+                let mut w = WitnessBuilder::new(0);
+                let [ca, cb, _] = cs.generic(GenericGateSpec::Add {
+                    left_coeff: Some(Fp::zero()),
+                    right_coeff: Some(Fp::zero()),
+                    output_coeff: Some(Fp::zero()),
+                });
+                w.set(ca, *a);
+                w.set(cb, *b);
+                let la = ComparisonGadget::field_limbs_cells(&mut cs, &mut w, ca);
+                let lb = ComparisonGadget::field_limbs_cells(&mut cs, &mut w, cb);
+                let below = ComparisonGadget::less_than_limbs_cells(&mut cs, &mut w, la, lb);
+                assert_eq!(w.get(below), Fp::from((i < j) as u64));
+                cs.check_witness(&w).unwrap();
+
+                // The other outcome
+                w.set(below, Fp::from((i >= j) as u64));
+                assert!(cs.check_witness(&w).is_err());
+            }
+        }
+
+        // p - 1 is the largest element
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let [value, _, _] = cs.generic(GenericGateSpec::Const(-Fp::one()));
+        w.set(value, -Fp::one());
+        let [high, low] = ComparisonGadget::field_limbs_cells(&mut cs, &mut w, value);
+        let [modulus_high, modulus_low] = limbs(Fp::MODULUS.0);
+        assert_eq!(
+            [w.get(high), w.get(low)],
+            [modulus_high, modulus_low - Fp::one()]
+        );
+        cs.check_witness(&w).unwrap();
     }
 
     #[test]
     fn test_gadget_construction() {
         let mut gadget = ComparisonGadget::new(0);
         gadget.age_check(18);
+        let checks = gadget.age_checks().to_vec();
         let (gates, rows) = gadget.build();
 
         assert!(!gates.is_empty());
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].bits.len(), 7);
+        assert_eq!(checks[0].sums.last().unwrap()[0].row, rows - 1);
         println!("Age check gates: {}, rows: {}", gates.len(), rows);
     }
+
+    /// An 18+ check tied to six public inputs: the birth date, then the
+    /// current date.
+    fn age_circuit() -> (CircuitBuilder, AgeCheckRows) {
+        let mut cs = CircuitBuilder::new(0);
+        let inputs = cs.public_inputs(6);
+        let mut gadget = ComparisonGadget::new(cs.current_row());
+        gadget.age_check(18);
+        Gadget::build(&gadget, &mut cs);
+        let rows = gadget.age_checks()[0].clone();
+        rows.connect(
+            &mut cs,
+            [inputs[0], inputs[1], inputs[2]],
+            [inputs[3], inputs[4], inputs[5]],
+        )
+        .unwrap();
+        (cs, rows)
+    }
+
+    fn fill(
+        rows: &AgeCheckRows,
+        gates: &[CircuitGate<Fp>],
+        birth: (u32, u32, u32),
+        current: (u32, u32, u32),
+    ) -> [Vec<Fp>; COLUMNS] {
+        let mut layout = WitnessLayout::for_gates(gates);
+        let (by, bm, bd) = birth;
+        let (cy, cm, cd) = current;
+        for (row, value) in [by, bm, bd, cy, cm, cd].into_iter().enumerate() {
+            layout.set(Cell::new(row, 0), Fp::from(value as u64));
+        }
+        ComparisonWitness::fill_age_check(&mut layout, rows, birth, current).unwrap();
+        layout.into_columns()
+    }
+
+    #[test]
+    fn test_fill_age_check() {
+        let (cs, rows) = age_circuit();
+        let gates = cs.build();
+
+        for (birth, current, expected) in [
+            ((2006, 2, 1), (2024, 2, 1), true),
+            ((2006, 2, 2), (2024, 2, 1), false),
+            ((1990, 12, 31), (2024, 1, 1), true),
+        ] {
+            let columns = fill(&rows, &gates, birth, current);
+            assert_eq!(
+                crate::diagnostics::check_witness(&gates, &columns, 6, false).is_ok(),
+                expected
+            );
+        }
+
+        // Public inputs other than the filled dates break the wiring
+        let mut columns = fill(&rows, &gates, (2000, 1, 1), (2024, 1, 1));
+        columns[0][0] = Fp::from(1990u64);
+        assert!(crate::diagnostics::check_witness(&gates, &columns, 6, false).is_err());
+
+        let mut layout = WitnessLayout::for_gates(&gates);
+        assert!(
+            ComparisonWitness::fill_age_check(&mut layout, &rows, (2024, 1, 1), (2000, 1, 1))
+                .is_err()
+        );
+    }
 }
@@ -0,0 +1,610 @@
+//! ASN.1 DER parsing gadgets for Kimchi circuits.
+//!
+//! Constrains DER tag/length headers and the position of elements inside a
+//! buffer, so that fields of an X.509 certificate (the TBS bytes, validity
+//! dates and subject public key) can be extracted at witness-provided offsets
+//! without trusting a parser outside the proof.
+//!
+//! Lengths are restricted to at most `DER_MAX_LENGTH_BYTES` length octets,
+//! which covers certificates up to 64 KiB. Header windows may read past the
+//! end of the last element, so buffers are zero-padded by
+//! `DER_MAX_LENGTH_BYTES` bytes (see `DerWitness::padded_buffer`).
+
+use super::bytes::BytesGadget;
+use super::sha256::{Sha256Gadget, Sha256Witness};
+use crate::types::Bytes32;
+use ark_ff::{One, Zero};
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use kimchi::circuits::wires::Wire;
+use mina_curves::pasta::Fp;
+use std::ops::Range;
+
+/// Maximum number of length octets in a long-form DER length.
+pub const DER_MAX_LENGTH_BYTES: usize = 2;
+
+/// Maximum DER header size: tag, initial length octet and length octets.
+pub const DER_MAX_HEADER_LEN: usize = 2 + DER_MAX_LENGTH_BYTES;
+
+/// Bit width of offsets and lengths.
+pub const DER_LENGTH_BITS: usize = 8 * DER_MAX_LENGTH_BYTES;
+
+pub const TAG_INTEGER: u8 = 0x02;
+pub const TAG_BIT_STRING: u8 = 0x03;
+pub const TAG_SEQUENCE: u8 = 0x30;
+pub const TAG_UTC_TIME: u8 = 0x17;
+pub const TAG_GENERALIZED_TIME: u8 = 0x18;
+/// Context-specific constructed tag `[0]`, used for the X.509 version.
+pub const TAG_CONTEXT_0: u8 = 0xA0;
+
+/// Length of a UTCTime value (`YYMMDDHHMMSSZ`).
+pub const UTC_TIME_LEN: usize = 13;
+
+/// Tags of the first seven TBSCertificate children of an X.509 v3 certificate:
+/// version, serialNumber, signature, issuer, validity, subject, subjectPublicKeyInfo.
+pub const X509_TBS_TAGS: [u8; 7] = [
+    TAG_CONTEXT_0,
+    TAG_INTEGER,
+    TAG_SEQUENCE,
+    TAG_SEQUENCE,
+    TAG_SEQUENCE,
+    TAG_SEQUENCE,
+    TAG_SEQUENCE,
+];
+
+/// Index of the validity element among the TBS children.
+pub const X509_VALIDITY_INDEX: usize = 4;
+
+/// Index of the subjectPublicKeyInfo element among the TBS children.
+pub const X509_SPKI_INDEX: usize = 6;
+
+/// Smallest length that requires `k` length octets in minimal DER encoding.
+fn min_long_length(k: usize) -> u64 {
+    if k == 1 {
+        128
+    } else {
+        1 << (8 * (k - 1))
+    }
+}
+
+/// Gadget for DER structure constraints.
+pub struct DerGadget {
+    gates: Vec<CircuitGate<Fp>>,
+    current_row: usize,
+}
+
+impl DerGadget {
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            gates: Vec::new(),
+            current_row: start_row,
+        }
+    }
+
+    pub fn current_row(&self) -> usize {
+        self.current_row
+    }
+
+    fn push_generic(&mut self, spec: GenericGateSpec<Fp>) {
+        let wires = Wire::for_row(self.current_row);
+        self.gates
+            .push(CircuitGate::create_generic_gadget(wires, spec, None));
+        self.current_row += 1;
+    }
+
+    /// Boolean constraint: b * b - b = 0.
+    fn boolean(&mut self) {
+        self.push_generic(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Weighted accumulation: acc_next = acc + weight * term.
+    fn accumulate(&mut self, weight: Fp) {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(weight),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Equality: a - b = 0.
+    fn equal(&mut self) {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(Fp::zero()),
+        });
+    }
+
+    /// Multiplication: a * b - c = 0.
+    fn mul(&mut self) {
+        self.push_generic(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Range check: 0 <= value < 2^num_bits.
+    fn range_check(&mut self, num_bits: usize) {
+        for _ in 0..num_bits {
+            self.boolean();
+        }
+        for _ in 0..num_bits.div_ceil(3) {
+            self.accumulate(Fp::one());
+        }
+    }
+
+    /// Append the gates of a sub-gadget that was started at `current_row`.
+    fn append(&mut self, (gates, next_row): (Vec<CircuitGate<Fp>>, usize)) {
+        self.gates.extend(gates);
+        self.current_row = next_row;
+    }
+
+    /// DER header: tag, length and header length.
+    ///
+    /// The initial length octet is split as `128 * long + low7`. In short form
+    /// the length is `low7`; in long form `low7 = n` selects, through a one-hot
+    /// selector, the big-endian value of the first `n` length octets. Minimal
+    /// encoding is enforced by range checking `length - min_length(n)`.
+    pub fn header(&mut self, tag: Option<u8>) -> usize {
+        let start = self.current_row;
+
+        // Tag
+        if let Some(tag) = tag {
+            self.push_generic(GenericGateSpec::Const(Fp::from(tag as u64)));
+        }
+
+        // Initial length octet: L0 = 128 * long + low7
+        self.boolean();
+        self.range_check(7);
+        self.accumulate(Fp::from(128u64));
+
+        // Selector over the number of length octets: sum(s_k) = long, sum(k * s_k) = long * low7
+        for _ in 0..DER_MAX_LENGTH_BYTES {
+            self.boolean();
+        }
+        for k in 1..=DER_MAX_LENGTH_BYTES {
+            self.accumulate(Fp::one());
+            self.accumulate(Fp::from(k as u64));
+        }
+        self.equal();
+        self.mul();
+        self.equal();
+
+        // Length octets and their big-endian prefix values
+        for _ in 0..DER_MAX_LENGTH_BYTES {
+            self.range_check(8);
+            self.accumulate(Fp::from(256u64));
+        }
+
+        // Long-form length: sum(s_k * prefix_k)
+        for _ in 0..DER_MAX_LENGTH_BYTES {
+            self.mul();
+            self.accumulate(Fp::one());
+        }
+
+        // length = long_len + low7 - long * low7
+        self.accumulate(Fp::one());
+        self.accumulate(-Fp::one());
+
+        // Minimal encoding: length - sum(s_k * min_length(k)) >= 0
+        for k in 1..=DER_MAX_LENGTH_BYTES {
+            self.accumulate(Fp::from(min_long_length(k)));
+        }
+        self.accumulate(-Fp::one());
+        self.range_check(DER_LENGTH_BITS);
+
+        // header_len = 2 + sum(k * s_k)
+        self.push_generic(GenericGateSpec::Plus(Fp::from(2u64)));
+
+        start
+    }
+
+    /// A DER element at a witness offset in a buffer of `buf_len` bytes.
+    ///
+    /// Extracts the header window at the offset, parses it, and bounds the
+    /// element: `content_start = offset + header_len`, `end = content_start + length`
+    /// and `end <= buf_len`.
+    pub fn element(&mut self, tag: Option<u8>, buf_len: usize) -> usize {
+        assert!(
+            buf_len >= DER_MAX_HEADER_LEN,
+            "buffer of {} bytes cannot hold a padded DER header",
+            buf_len
+        );
+
+        let start = self.current_row;
+
+        self.extract_field(buf_len, DER_MAX_HEADER_LEN);
+        self.header(tag);
+
+        // content_start and end
+        self.accumulate(Fp::one());
+        self.accumulate(Fp::one());
+
+        // buf_len - end >= 0
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(-Fp::one()),
+            right_coeff: Some(Fp::zero()),
+            output_coeff: Some(-Fp::one()),
+        });
+        self.push_generic(GenericGateSpec::Plus(Fp::from(buf_len as u64)));
+        self.range_check(DER_LENGTH_BITS);
+
+        start
+    }
+
+    /// Containment: parent.content_start <= child.offset and child.end <= parent.end.
+    pub fn within(&mut self) -> usize {
+        let start = self.current_row;
+
+        for _ in 0..2 {
+            self.push_generic(GenericGateSpec::Add {
+                left_coeff: Some(Fp::one()),
+                right_coeff: Some(-Fp::one()),
+                output_coeff: Some(-Fp::one()),
+            });
+            self.range_check(DER_LENGTH_BITS);
+        }
+
+        start
+    }
+
+    /// Consecutive siblings inside a parent, each with the given tag.
+    ///
+    /// The first child starts at the parent's content start, each following
+    /// child starts at the end of the previous one, and all are contained in
+    /// the parent. This pins each child to its position in the structure.
+    pub fn children(&mut self, tags: &[Option<u8>], buf_len: usize) -> usize {
+        let start = self.current_row;
+
+        for &tag in tags {
+            self.element(tag, buf_len);
+            // offset == previous end (or parent content start)
+            self.equal();
+            self.within();
+        }
+
+        start
+    }
+
+    /// Field extraction: buf[offset..offset + field_len] at a witness offset.
+    pub fn extract_field(&mut self, buf_len: usize, field_len: usize) -> usize {
+        let start = self.current_row;
+
+        let mut bytes = BytesGadget::new(self.current_row);
+        bytes.contains_substring(buf_len, field_len);
+        self.append(bytes.build());
+
+        start
+    }
+
+    /// X.509 v3 certificate fields.
+    ///
+    /// Walks Certificate -> TBSCertificate -> {version, serial, signature,
+    /// issuer, validity, subject, subjectPublicKeyInfo}, extracts both UTCTime
+    /// validity dates and the `spki_len`-byte subjectPublicKeyInfo, and hashes
+    /// the TBS element (up to `max_tbs_blocks` SHA-256 blocks) for signature
+    /// verification.
+    pub fn x509_fields(
+        &mut self,
+        cert_len: usize,
+        spki_len: usize,
+        max_tbs_blocks: usize,
+    ) -> usize {
+        let start = self.current_row;
+
+        // Certificate and TBSCertificate
+        self.element(Some(TAG_SEQUENCE), cert_len);
+        self.children(&[Some(TAG_SEQUENCE)], cert_len);
+        let tbs_tags = X509_TBS_TAGS.map(Some);
+        self.children(&tbs_tags, cert_len);
+
+        // Validity: notBefore, notAfter
+        self.children(&[Some(TAG_UTC_TIME), Some(TAG_UTC_TIME)], cert_len);
+        for _ in 0..2 {
+            self.push_generic(GenericGateSpec::Const(Fp::from(UTC_TIME_LEN as u64)));
+            self.extract_field(cert_len, UTC_TIME_LEN);
+        }
+
+        // subjectPublicKeyInfo: header_len + length == spki_len
+        self.accumulate(Fp::one());
+        self.push_generic(GenericGateSpec::Const(Fp::from(spki_len as u64)));
+        self.extract_field(cert_len, spki_len);
+
+        // TBS digest: hash the variable-length TBS element
+        let max_tbs_len = Sha256Witness::max_message_len(max_tbs_blocks);
+        self.extract_field(cert_len, max_tbs_len.min(cert_len));
+        self.accumulate(Fp::one());
+        let mut sha = Sha256Gadget::new(self.current_row);
+        sha.hash_message_var(max_tbs_blocks);
+        self.append(sha.build());
+
+        start
+    }
+
+    pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
+        (self.gates, self.current_row)
+    }
+}
+
+/// A parsed DER header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DerHeader {
+    pub tag: u8,
+    /// Number of header bytes (tag and length octets).
+    pub header_len: usize,
+    /// Number of content bytes.
+    pub length: usize,
+}
+
+impl DerHeader {
+    /// Number of length octets in long form, 0 in short form.
+    pub fn num_length_bytes(&self) -> usize {
+        self.header_len - 2
+    }
+
+    /// Total size of the element.
+    pub fn total_len(&self) -> usize {
+        self.header_len + self.length
+    }
+}
+
+/// A DER element located in a buffer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DerElement {
+    pub offset: usize,
+    pub header: DerHeader,
+}
+
+impl DerElement {
+    /// Byte range of the whole element, header included.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset + self.header.total_len()
+    }
+
+    /// Byte range of the element content.
+    pub fn content(&self) -> Range<usize> {
+        self.offset + self.header.header_len..self.range().end
+    }
+}
+
+/// Byte ranges of the X.509 fields constrained by `DerGadget::x509_fields`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct X509Fields {
+    /// Whole TBSCertificate element, as covered by the signature.
+    pub tbs: Range<usize>,
+    /// notBefore UTCTime content.
+    pub not_before: Range<usize>,
+    /// notAfter UTCTime content.
+    pub not_after: Range<usize>,
+    /// Whole subjectPublicKeyInfo element.
+    pub spki: Range<usize>,
+}
+
+/// Witness generator for DER parsing.
+pub struct DerWitness;
+
+impl DerWitness {
+    /// Parse a strict DER header at `offset`.
+    ///
+    /// Rejects indefinite lengths, non-minimal lengths, more than
+    /// `DER_MAX_LENGTH_BYTES` length octets and elements that overrun the buffer.
+    pub fn parse_header(buf: &[u8], offset: usize) -> Option<DerHeader> {
+        let tag = *buf.get(offset)?;
+        let first = *buf.get(offset + 1)?;
+
+        let (header_len, length) = if first < 0x80 {
+            (2, first as usize)
+        } else {
+            let n = (first & 0x7F) as usize;
+            if n == 0 || n > DER_MAX_LENGTH_BYTES {
+                return None;
+            }
+            let octets = buf.get(offset + 2..offset + 2 + n)?;
+            let length = octets
+                .iter()
+                .fold(0usize, |acc, &b| (acc << 8) | b as usize);
+            if (length as u64) < min_long_length(n) {
+                return None;
+            }
+            (2 + n, length)
+        };
+
+        let header = DerHeader {
+            tag,
+            header_len,
+            length,
+        };
+        (offset + header.total_len() <= buf.len()).then_some(header)
+    }
+
+    /// Parse the element at `offset`.
+    pub fn parse_element(buf: &[u8], offset: usize) -> Option<DerElement> {
+        Some(DerElement {
+            offset,
+            header: Self::parse_header(buf, offset)?,
+        })
+    }
+
+    /// Parse the children of a constructed element.
+    pub fn children(buf: &[u8], parent: &DerElement) -> Option<Vec<DerElement>> {
+        let content = parent.content();
+        let mut children = Vec::new();
+        let mut offset = content.start;
+
+        while offset < content.end {
+            let child = Self::parse_element(buf, offset)?;
+            if child.range().end > content.end {
+                return None;
+            }
+            offset = child.range().end;
+            children.push(child);
+        }
+
+        Some(children)
+    }
+
+    /// Locate the X.509 v3 fields constrained by `DerGadget::x509_fields`.
+    pub fn x509_fields(cert: &[u8]) -> Option<X509Fields> {
+        let certificate = Self::parse_element(cert, 0)?;
+        if certificate.header.tag != TAG_SEQUENCE {
+            return None;
+        }
+
+        let tbs = *Self::children(cert, &certificate)?.first()?;
+        if tbs.header.tag != TAG_SEQUENCE {
+            return None;
+        }
+
+        let tbs_children = Self::children(cert, &tbs)?;
+        if tbs_children.len() < X509_TBS_TAGS.len()
+            || tbs_children
+                .iter()
+                .zip(X509_TBS_TAGS)
+                .any(|(child, tag)| child.header.tag != tag)
+        {
+            return None;
+        }
+
+        let validity = Self::children(cert, &tbs_children[X509_VALIDITY_INDEX])?;
+        let [not_before, not_after] = validity.as_slice() else {
+            return None;
+        };
+        for time in [not_before, not_after] {
+            if time.header.tag != TAG_UTC_TIME || time.header.length != UTC_TIME_LEN {
+                return None;
+            }
+        }
+
+        Some(X509Fields {
+            tbs: tbs.range(),
+            not_before: not_before.content(),
+            not_after: not_after.content(),
+            spki: tbs_children[X509_SPKI_INDEX].range(),
+        })
+    }
+
+    /// SHA-256 digest of the TBSCertificate.
+    pub fn tbs_digest(cert: &[u8]) -> Option<Bytes32> {
+        let fields = Self::x509_fields(cert)?;
+        Some(Sha256Witness::new().compute(&cert[fields.tbs]))
+    }
+
+    /// Zero-pad a buffer so every header window stays in bounds.
+    pub fn padded_buffer(buf: &[u8]) -> Vec<u8> {
+        let mut padded = buf.to_vec();
+        padded.resize(buf.len() + DER_MAX_LENGTH_BYTES, 0);
+        padded
+    }
+
+    /// One-hot selector over the number of long-form length octets (all zero in short form).
+    pub fn length_selector(header: &DerHeader) -> Vec<Fp> {
+        (1..=DER_MAX_LENGTH_BYTES)
+            .map(|k| {
+                if header.num_length_bytes() == k {
+                    Fp::one()
+                } else {
+                    Fp::zero()
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn der(tag: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = vec![tag];
+        match content.len() {
+            len if len < 0x80 => out.push(len as u8),
+            len if len < 0x100 => out.extend([0x81, len as u8]),
+            len => out.extend([0x82, (len >> 8) as u8, len as u8]),
+        }
+        out.extend_from_slice(content);
+        out
+    }
+
+    fn sample_certificate() -> Vec<u8> {
+        let validity = [
+            der(TAG_UTC_TIME, b"240101000000Z"),
+            der(TAG_UTC_TIME, b"341231235959Z"),
+        ]
+        .concat();
+        let spki = der(TAG_SEQUENCE, &der(TAG_BIT_STRING, &[0u8; 140]));
+        let tbs = [
+            der(TAG_CONTEXT_0, &der(TAG_INTEGER, &[2])),
+            der(TAG_INTEGER, &[0x01, 0x23]),
+            der(TAG_SEQUENCE, &[0x05, 0x00]),
+            der(TAG_SEQUENCE, b"issuer"),
+            der(TAG_SEQUENCE, &validity),
+            der(TAG_SEQUENCE, b"subject"),
+            spki,
+        ]
+        .concat();
+        let certificate = [
+            der(TAG_SEQUENCE, &tbs),
+            der(TAG_SEQUENCE, &[0x05, 0x00]),
+            der(TAG_BIT_STRING, &[0u8; 16]),
+        ]
+        .concat();
+        der(TAG_SEQUENCE, &certificate)
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let short = DerWitness::parse_header(&[0x30, 0x03, 1, 2, 3], 0).unwrap();
+        assert_eq!((short.header_len, short.length), (2, 3));
+
+        let long = der(TAG_BIT_STRING, &[0u8; 300]);
+        let header = DerWitness::parse_header(&long, 0).unwrap();
+        assert_eq!((header.header_len, header.length), (4, 300));
+        assert_eq!(
+            DerWitness::length_selector(&header),
+            vec![Fp::zero(), Fp::one()]
+        );
+    }
+
+    #[test]
+    fn test_reject_non_der() {
+        // Non-minimal long form
+        assert!(DerWitness::parse_header(&[0x04, 0x81, 0x05, 0, 0, 0, 0, 0], 0).is_none());
+        // Indefinite length
+        assert!(DerWitness::parse_header(&[0x30, 0x80, 0, 0], 0).is_none());
+        // Overrun
+        assert!(DerWitness::parse_header(&[0x04, 0x05, 0, 0], 0).is_none());
+    }
+
+    #[test]
+    fn test_x509_fields() {
+        let cert = sample_certificate();
+        let fields = DerWitness::x509_fields(&cert).unwrap();
+
+        assert_eq!(&cert[fields.not_before.clone()], b"240101000000Z");
+        assert_eq!(&cert[fields.not_after.clone()], b"341231235959Z");
+        assert_eq!(cert[fields.spki.start], TAG_SEQUENCE);
+        assert_eq!(fields.spki.len(), 146);
+        assert_eq!(
+            DerWitness::tbs_digest(&cert).unwrap(),
+            Sha256Witness::new().compute(&cert[fields.tbs])
+        );
+    }
+
+    #[test]
+    fn test_gadget_construction() {
+        let mut gadget = DerGadget::new(0);
+        gadget.element(Some(TAG_SEQUENCE), 16);
+        let (gates, rows) = gadget.build();
+
+        assert_eq!(gates.len(), rows);
+        assert!(rows > 0);
+
+        let mut gadget = DerGadget::new(0);
+        gadget.x509_fields(64, 8, 1);
+        let (gates, rows) = gadget.build();
+        assert_eq!(gates.len(), rows);
+    }
+}
@@ -0,0 +1,209 @@
+//! Choice of hash for Merkle trees and commitments.
+//!
+//! Poseidon is the hash circuits use natively and the one Mina tooling
+//! builds trees with. Ethereum tooling hashes bytes with Keccak-256, and
+//! other tooling with SHA-256; [`HashFunction`] picks one so the same
+//! circuit code can check nodes and commitments made by either.
+//!
+//! Byte hashes work on 32-byte digests held as 256 bit cells, byte by byte,
+//! least significant bit first, so cell `8 * i + j` is bit `j` of byte `i`.
+//! A digest is public as two field elements, packed like
+//! `PublicInputType::Bytes { len: 32 }`. Expect tens of thousands of rows
+//! per byte hash where Poseidon takes a dozen.
+
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use mina_curves::pasta::Fp;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::boolean::BooleanGadget;
+use super::keccak::{KeccakGadget, KeccakWitness};
+use super::poseidon::{PoseidonWitness, BYTES_PER_ELEMENT};
+use super::sha256::Sha256Gadget;
+use crate::builder::{Cell, CircuitBuilder, WitnessBuilder};
+use crate::types::Bytes32;
+
+/// Bits in a digest.
+pub const DIGEST_BITS: usize = 256;
+
+/// A digest as bit cells.
+pub type DigestCells = [Cell; DIGEST_BITS];
+
+/// Hash of Merkle nodes and commitments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HashFunction {
+    /// Poseidon over field elements, as Mina tooling uses.
+    #[default]
+    Poseidon,
+    /// SHA-256 over bytes.
+    Sha256,
+    /// Keccak-256 over bytes, as Ethereum tooling uses.
+    Keccak256,
+}
+
+impl HashFunction {
+    /// Whether values are 32-byte digests rather than field elements.
+    pub fn is_bytes(self) -> bool {
+        self != Self::Poseidon
+    }
+
+    /// Field elements a root or commitment takes as public input.
+    pub fn public_len(self) -> usize {
+        if self.is_bytes() {
+            Bytes32::LEN.div_ceil(BYTES_PER_ELEMENT)
+        } else {
+            1
+        }
+    }
+}
+
+/// Cell-level byte hashing.
+pub struct DigestGadget;
+
+impl DigestGadget {
+    /// Digest of the message in `bits` under a byte hash.
+    ///
+    /// The message bits must already be constrained to be boolean.
+    ///
+    /// # Panics
+    ///
+    /// If `hash` is `Poseidon`, which hashes field elements.
+    pub fn hash_bits_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        hash: HashFunction,
+        bits: &[Cell],
+    ) -> DigestCells {
+        match hash {
+            HashFunction::Sha256 => Sha256Gadget::hash_bits_cells(cs, w, bits),
+            HashFunction::Keccak256 => KeccakGadget::hash_bits_cells(cs, w, bits),
+            HashFunction::Poseidon => panic!("Poseidon does not hash bytes"),
+        }
+    }
+
+    /// Allocate the bits of `bytes` as boolean cells.
+    pub fn alloc_bytes(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, bytes: &[u8]) -> Vec<Cell> {
+        bytes
+            .iter()
+            .flat_map(|byte| (0..8).map(move |j| byte >> j & 1 == 1))
+            .map(|bit| BooleanGadget::alloc_bit(cs, w, bit))
+            .collect()
+    }
+
+    /// The 32 big-endian bytes of the field element in `value`, matching
+    /// `DigestWitness::field_bytes`.
+    pub fn field_bytes_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        value: Cell,
+    ) -> DigestCells {
+        let bits = BooleanGadget::decompose_cells(cs, w, value, Fp::MODULUS_BIT_SIZE as usize);
+        let zero = BooleanGadget::constant_bit(cs, w, false);
+        std::array::from_fn(|k| {
+            let position = 8 * (Bytes32::LEN - 1 - k / 8) + k % 8;
+            bits.get(position).copied().unwrap_or(zero)
+        })
+    }
+
+    /// Field elements holding `digest`, matching `DigestWitness::pack`.
+    pub fn pack_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        digest: &DigestCells,
+    ) -> Vec<Cell> {
+        digest
+            .chunks(8 * BYTES_PER_ELEMENT)
+            .map(|bits| {
+                let mut acc = bits[0];
+                for (k, bit) in bits.iter().enumerate().skip(1) {
+                    let weight = Fp::from(2u64).pow([k as u64]);
+                    let cells = cs.generic_coeffs_packed([
+                        Fp::one(),
+                        weight,
+                        -Fp::one(),
+                        Fp::zero(),
+                        Fp::zero(),
+                    ]);
+                    let (vacc, vbit) = (w.get(acc), w.get(*bit));
+                    w.set_cells(cells, [vacc, vbit, vacc + weight * vbit]);
+                    cs.copy(acc, cells[0]);
+                    cs.copy(*bit, cells[1]);
+                    acc = cells[2];
+                }
+                acc
+            })
+            .collect()
+    }
+}
+
+/// Native byte hashing matching the gadget.
+pub struct DigestWitness;
+
+impl DigestWitness {
+    /// Digest of `message` under a byte hash.
+    ///
+    /// # Panics
+    ///
+    /// If `hash` is `Poseidon`, which hashes field elements.
+    pub fn hash(hash: HashFunction, message: &[u8]) -> Bytes32 {
+        match hash {
+            HashFunction::Sha256 => Bytes32(Sha256::digest(message).into()),
+            HashFunction::Keccak256 => KeccakWitness::hash(message),
+            HashFunction::Poseidon => panic!("Poseidon does not hash bytes"),
+        }
+    }
+
+    /// The 32 big-endian bytes of `value`, as Solidity encodes a `uint256`.
+    pub fn field_bytes(value: Fp) -> Bytes32 {
+        let mut bytes = [0u8; 32];
+        let be = value.into_bigint().to_bytes_be();
+        bytes[32 - be.len()..].copy_from_slice(&be);
+        Bytes32(bytes)
+    }
+
+    /// Field elements holding `digest` as a public input.
+    pub fn pack(digest: &Bytes32) -> Vec<Fp> {
+        digest
+            .chunks(BYTES_PER_ELEMENT)
+            .map(PoseidonWitness::pack_bytes)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::{PublicInputSchema, PublicInputType, PublicValue};
+    use kimchi::circuits::polynomials::generic::GenericGateSpec;
+
+    #[test]
+    fn test_pack_matches_schema() {
+        let digest = DigestWitness::hash(HashFunction::Sha256, b"abc");
+        let schema = PublicInputSchema::new().field("root", PublicInputType::Bytes { len: 32 });
+        let elements = schema
+            .encode(&[PublicValue::Bytes(digest.to_vec())])
+            .unwrap();
+        assert_eq!(elements, DigestWitness::pack(&digest));
+        assert_eq!(elements.len(), HashFunction::Sha256.public_len());
+    }
+
+    #[test]
+    fn test_field_bytes_cells() {
+        let value = -Fp::from(3u64);
+        let expected = DigestWitness::field_bytes(value);
+        assert_eq!(expected[0] >> 6, 1);
+        assert_eq!(expected[31], 0xfe);
+
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let [cell, _, _] = cs.generic(GenericGateSpec::Const(value));
+        w.set(cell, value);
+        let bytes = DigestGadget::field_bytes_cells(&mut cs, &mut w, cell);
+        let packed = DigestGadget::pack_cells(&mut cs, &mut w, &bytes);
+
+        let values: Vec<Fp> = packed.iter().map(|cell| w.get(*cell)).collect();
+        assert_eq!(values, DigestWitness::pack(&expected));
+        assert!(cs.check_witness(&w).is_ok());
+    }
+}
@@ -0,0 +1,301 @@
+//! ASCII digit parsing gadgets for Kimchi circuits.
+//!
+//! Constrains ASCII bytes to be decimal digits and binds them to the numeric
+//! value they encode, so that fields such as MRZ dates (`YYMMDD`) are parsed
+//! in-circuit rather than trusted from the witness.
+
+use ark_ff::{One, Zero};
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use kimchi::circuits::wires::Wire;
+use mina_curves::pasta::Fp;
+
+/// ASCII code of `'0'`.
+pub const ASCII_ZERO: u8 = b'0';
+
+/// Two-digit MRZ years up to and including this value are in the 2000s.
+pub const MRZ_CENTURY_PIVOT: u32 = 29;
+
+/// Gadget for parsing ASCII decimal digits.
+pub struct DigitsGadget {
+    gates: Vec<CircuitGate<Fp>>,
+    current_row: usize,
+}
+
+impl DigitsGadget {
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            gates: Vec::new(),
+            current_row: start_row,
+        }
+    }
+
+    pub fn current_row(&self) -> usize {
+        self.current_row
+    }
+
+    fn push_generic(&mut self, spec: GenericGateSpec<Fp>) {
+        let wires = Wire::for_row(self.current_row);
+        self.gates
+            .push(CircuitGate::create_generic_gadget(wires, spec, None));
+        self.current_row += 1;
+    }
+
+    /// Range check: 0 <= value < 2^num_bits.
+    fn range_check(&mut self, num_bits: usize) {
+        for _ in 0..num_bits {
+            self.push_generic(GenericGateSpec::Mul {
+                mul_coeff: Some(Fp::one()),
+                output_coeff: Some(-Fp::one()),
+            });
+        }
+
+        for _ in 0..num_bits.div_ceil(3) {
+            self.push_generic(GenericGateSpec::Add {
+                left_coeff: Some(Fp::one()),
+                right_coeff: Some(Fp::one()),
+                output_coeff: Some(-Fp::one()),
+            });
+        }
+    }
+
+    /// Bounds check: lo <= value <= hi.
+    ///
+    /// Range checks both `value - lo` and `hi - value` to the bit width of `hi - lo`.
+    pub fn bounded(&mut self, lo: u64, hi: u64) -> usize {
+        assert!(lo <= hi, "empty range {}..={}", lo, hi);
+
+        let start = self.current_row;
+        let num_bits = (64 - (hi - lo).leading_zeros() as usize).max(1);
+
+        // value - lo - low_slack = 0
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::zero()),
+            output_coeff: Some(-Fp::one()),
+        });
+        self.push_generic(GenericGateSpec::Plus(-Fp::from(lo)));
+        self.range_check(num_bits);
+
+        // hi - value - high_slack = 0
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(-Fp::one()),
+            right_coeff: Some(Fp::zero()),
+            output_coeff: Some(-Fp::one()),
+        });
+        self.push_generic(GenericGateSpec::Plus(Fp::from(hi)));
+        self.range_check(num_bits);
+
+        start
+    }
+
+    /// A single ASCII digit: digit = byte - '0' with 0 <= digit <= 9.
+    pub fn ascii_digit(&mut self) -> usize {
+        let start = self.current_row;
+
+        // byte - 48 - digit = 0
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::zero()),
+            output_coeff: Some(-Fp::one()),
+        });
+        self.push_generic(GenericGateSpec::Plus(-Fp::from(ASCII_ZERO as u64)));
+
+        self.bounded(0, 9);
+
+        start
+    }
+
+    /// Parse `num_digits` ASCII digits (most significant first) into a number.
+    ///
+    /// Each byte is constrained to be a digit and the claimed value is bound
+    /// through the Horner accumulation `acc_{i+1} = 10 * acc_i + digit_i`.
+    pub fn parse_number(&mut self, num_digits: usize) -> usize {
+        assert!(
+            num_digits > 0 && num_digits <= 19,
+            "cannot parse {} digits into a u64",
+            num_digits
+        );
+
+        let start = self.current_row;
+
+        for _ in 0..num_digits {
+            self.ascii_digit();
+        }
+
+        for _ in 0..num_digits {
+            self.push_generic(GenericGateSpec::Add {
+                left_coeff: Some(Fp::from(10u64)),
+                right_coeff: Some(Fp::one()),
+                output_coeff: Some(-Fp::one()),
+            });
+        }
+
+        // value == acc
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(Fp::zero()),
+        });
+
+        start
+    }
+
+    /// Parse an MRZ `YYMMDD` date into (year, month, day).
+    ///
+    /// The century bit `c` is 1 when `YY <= MRZ_CENTURY_PIVOT`; it is tied to
+    /// `YY` by range checking `c * (pivot - YY) + (1 - c) * (YY - pivot - 1)`,
+    /// and the year is `1900 + YY + 100 * c`.
+    pub fn parse_mrz_date(&mut self) -> usize {
+        let start = self.current_row;
+
+        self.parse_number(2);
+        self.parse_number(2);
+        self.parse_number(2);
+
+        // Month and day ranges
+        self.bounded(1, 12);
+        self.bounded(1, 31);
+
+        // Century bit is boolean
+        self.push_generic(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+
+        // Selected slack: c * (pivot - yy) + (1 - c) * (yy - pivot - 1)
+        self.push_generic(GenericGateSpec::Mul {
+            mul_coeff: Some(-Fp::from(2u64)),
+            output_coeff: Some(-Fp::one()),
+        });
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::from(2 * MRZ_CENTURY_PIVOT as u64 + 1)),
+            output_coeff: Some(-Fp::one()),
+        });
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        self.push_generic(GenericGateSpec::Plus(-Fp::from(
+            MRZ_CENTURY_PIVOT as u64 + 1,
+        )));
+        self.range_check(7);
+
+        // year = 1900 + yy + 100 * c
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::from(100u64)),
+            output_coeff: Some(-Fp::one()),
+        });
+        self.push_generic(GenericGateSpec::Plus(Fp::from(1900u64)));
+
+        start
+    }
+
+    pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
+        (self.gates, self.current_row)
+    }
+}
+
+/// Witness generator for digit parsing.
+pub struct DigitsWitness;
+
+impl DigitsWitness {
+    /// Parse a strict ASCII decimal string (digits only, no sign or whitespace).
+    pub fn parse_digits(bytes: &[u8]) -> Option<u64> {
+        if bytes.is_empty() || bytes.len() > 19 {
+            return None;
+        }
+
+        bytes.iter().try_fold(0u64, |acc, &b| {
+            if b.is_ascii_digit() {
+                Some(acc * 10 + (b - ASCII_ZERO) as u64)
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Digit values as field elements.
+    pub fn digit_values(bytes: &[u8]) -> Option<Vec<Fp>> {
+        bytes
+            .iter()
+            .map(|&b| {
+                b.is_ascii_digit()
+                    .then(|| Fp::from((b - ASCII_ZERO) as u64))
+            })
+            .collect()
+    }
+
+    /// Horner accumulators `acc_{i+1} = 10 * acc_i + digit_i`, excluding the initial zero.
+    pub fn horner_accumulators(bytes: &[u8]) -> Option<Vec<Fp>> {
+        let digits = Self::digit_values(bytes)?;
+        let mut acc = Fp::zero();
+        Some(
+            digits
+                .into_iter()
+                .map(|d| {
+                    acc = acc * Fp::from(10u64) + d;
+                    acc
+                })
+                .collect(),
+        )
+    }
+
+    /// Century bit for a two-digit MRZ year.
+    pub fn mrz_century_bit(yy: u32) -> Fp {
+        if yy <= MRZ_CENTURY_PIVOT {
+            Fp::one()
+        } else {
+            Fp::zero()
+        }
+    }
+
+    /// Expand a two-digit MRZ year into a four-digit year.
+    pub fn mrz_year(yy: u32) -> u32 {
+        if yy <= MRZ_CENTURY_PIVOT {
+            2000 + yy
+        } else {
+            1900 + yy
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_digits() {
+        assert_eq!(DigitsWitness::parse_digits(b"0042"), Some(42));
+        assert_eq!(DigitsWitness::parse_digits(b"+42"), None);
+        assert_eq!(DigitsWitness::parse_digits(b"4 2"), None);
+        assert_eq!(DigitsWitness::parse_digits(b""), None);
+    }
+
+    #[test]
+    fn test_horner_accumulators() {
+        let acc = DigitsWitness::horner_accumulators(b"907").unwrap();
+        assert_eq!(acc, vec![Fp::from(9u64), Fp::from(90u64), Fp::from(907u64)]);
+    }
+
+    #[test]
+    fn test_mrz_year() {
+        assert_eq!(DigitsWitness::mrz_year(29), 2029);
+        assert_eq!(DigitsWitness::mrz_year(30), 1930);
+        assert_eq!(DigitsWitness::mrz_century_bit(5), Fp::one());
+        assert_eq!(DigitsWitness::mrz_century_bit(90), Fp::zero());
+    }
+
+    #[test]
+    fn test_gadget_construction() {
+        let mut gadget = DigitsGadget::new(0);
+        gadget.parse_mrz_date();
+        let (gates, rows) = gadget.build();
+
+        assert_eq!(gates.len(), rows);
+        assert!(rows > 0);
+    }
+}
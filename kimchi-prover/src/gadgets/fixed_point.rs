@@ -0,0 +1,459 @@
+//! Fixed-point decimal arithmetic for Kimchi circuits.
+//!
+//! A [`FixedPoint`] format with `d` decimals stores `x` as the integer
+//! `round(x * 10^d)`, negative values as their field negation, e.g. `-1.25`
+//! with two decimals as `-125`. Every value of the format is checked to
+//! `-2^bits <= x < 2^bits` by range checking `x + 2^bits` to `bits + 1`
+//! bits, so:
+//!
+//! - `add` and `sub` range check their result: a sum that overflows the
+//!   format leaves the witness unsatisfiable instead of wrapping;
+//! - `mul` witnesses `c` and `r` with `a * b = c * 10^d + r` and
+//!   `0 <= r < 10^d`, i.e. `c = floor(a * b / 10^d)`, rounding toward
+//!   negative infinity, and range checks `c`;
+//! - `less_than` compares the offset values with
+//!   `ComparisonGadget::less_than_cells`.
+//!
+//! With at most 120 bits and 18 decimals no product or remainder comes near
+//! the field modulus, so these are the integer relations. Operands must be
+//! values of the format: results of the gadget or cells passed through
+//! `range_check_cells`.
+
+use std::fmt::Write;
+
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use mina_curves::pasta::Fp;
+use num_bigint::BigInt;
+
+use super::boolean::BooleanGadget;
+use super::comparison::ComparisonGadget;
+use crate::builder::{Cell, CircuitBuilder, WitnessBuilder};
+use crate::error::{ProverError, Result};
+
+/// Most magnitude bits of a format.
+pub const MAX_FIXED_POINT_BITS: usize = 120;
+
+/// Most decimals of a format.
+pub const MAX_FIXED_POINT_DECIMALS: u32 = 18;
+
+/// Precision and range of fixed-point values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FixedPoint {
+    decimals: u32,
+    bits: usize,
+}
+
+impl FixedPoint {
+    /// Values with `decimals` decimals and magnitude below `2^bits`.
+    ///
+    /// Fails beyond [`MAX_FIXED_POINT_DECIMALS`] or [`MAX_FIXED_POINT_BITS`].
+    pub fn new(decimals: u32, bits: usize) -> Result<Self> {
+        if decimals > MAX_FIXED_POINT_DECIMALS || bits == 0 || bits > MAX_FIXED_POINT_BITS {
+            return Err(ProverError::InvalidInput(format!(
+                "unsupported fixed-point format: {} decimals, {} bits",
+                decimals, bits
+            )));
+        }
+        Ok(Self { decimals, bits })
+    }
+
+    pub fn decimals(&self) -> u32 {
+        self.decimals
+    }
+
+    pub fn bits(&self) -> usize {
+        self.bits
+    }
+
+    /// `10^decimals`, the stored value of 1.
+    pub fn scale(&self) -> i128 {
+        10i128.pow(self.decimals)
+    }
+
+    /// Whether the stored value `raw` is in range.
+    pub fn contains(&self, raw: i128) -> bool {
+        let bound = 1i128 << self.bits;
+        (-bound..bound).contains(&raw)
+    }
+
+    /// Stored value of a decimal string such as `-12.5`.
+    ///
+    /// Fails on more decimals than the format has rather than rounding.
+    pub fn parse(&self, s: &str) -> Result<i128> {
+        let invalid = || ProverError::InvalidInput(format!("invalid fixed-point value {:?}", s));
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+        let is_digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+        if integer.is_empty()
+            || !is_digits(integer)
+            || !is_digits(fraction)
+            || fraction.len() > self.decimals as usize
+            || (digits.contains('.') && fraction.is_empty())
+        {
+            return Err(invalid());
+        }
+
+        let padded = format!(
+            "{}{:0<width$}",
+            integer,
+            fraction,
+            width = self.decimals as usize
+        );
+        let magnitude: i128 = padded.parse().map_err(|_| invalid())?;
+        let raw = if negative { -magnitude } else { magnitude };
+        self.checked(raw)
+    }
+
+    /// Stored value nearest to `value`.
+    pub fn from_f64(&self, value: f64) -> Result<i128> {
+        let scaled = (value * self.scale() as f64).round();
+        if !scaled.is_finite() || scaled.abs() >= 2f64.powi(127) {
+            return Err(ProverError::InvalidInput(format!(
+                "{} does not fit {} bits",
+                value, self.bits
+            )));
+        }
+        self.checked(scaled as i128)
+    }
+
+    /// Decimal string of the stored value `raw`, with all decimals.
+    pub fn format(&self, raw: i128) -> String {
+        let scale = self.scale().unsigned_abs();
+        let magnitude = raw.unsigned_abs();
+        let mut s = String::new();
+        if raw < 0 {
+            s.push('-');
+        }
+        let _ = write!(s, "{}", magnitude / scale);
+        if self.decimals > 0 {
+            let _ = write!(
+                s,
+                ".{:0width$}",
+                magnitude % scale,
+                width = self.decimals as usize
+            );
+        }
+        s
+    }
+
+    fn checked(&self, raw: i128) -> Result<i128> {
+        if self.contains(raw) {
+            Ok(raw)
+        } else {
+            Err(ProverError::InvalidInput(format!(
+                "{} does not fit {} bits",
+                self.format(raw),
+                self.bits
+            )))
+        }
+    }
+
+    /// `2^bits`, the offset that makes values non-negative.
+    fn offset(&self) -> Fp {
+        Fp::from(2u64).pow([self.bits as u64])
+    }
+}
+
+/// Cell-level fixed-point arithmetic.
+pub struct FixedPointGadget;
+
+impl FixedPointGadget {
+    /// Constrain `a` to be a value of `format`.
+    pub fn range_check_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        format: FixedPoint,
+        a: Cell,
+    ) {
+        let offset = Self::offset_cell(cs, w, format, a);
+        BooleanGadget::decompose_cells(cs, w, offset, format.bits + 1);
+    }
+
+    /// a + b, which must not overflow.
+    pub fn add_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        format: FixedPoint,
+        a: Cell,
+        b: Cell,
+    ) -> Cell {
+        Self::linear_cells(cs, w, format, a, b, Fp::one())
+    }
+
+    /// a - b, which must not overflow.
+    pub fn sub_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        format: FixedPoint,
+        a: Cell,
+        b: Cell,
+    ) -> Cell {
+        Self::linear_cells(cs, w, format, a, b, -Fp::one())
+    }
+
+    /// a * b, rounded toward negative infinity, which must not overflow.
+    pub fn mul_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        format: FixedPoint,
+        a: Cell,
+        b: Cell,
+    ) -> Cell {
+        let product = cs.generic_packed(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        let (va, vb) = (w.get(a), w.get(b));
+        w.set_cells(product, [va, vb, va * vb]);
+        cs.copy(a, product[0]);
+        cs.copy(b, product[1]);
+
+        if format.decimals == 0 {
+            Self::range_check_cells(cs, w, format, product[2]);
+            return product[2];
+        }
+
+        // product = c * scale + r
+        let scale = format.scale();
+        let remainder = |v: Fp| {
+            FixedPointWitness::from_field(v)
+                .unwrap_or(0)
+                .rem_euclid(scale)
+        };
+        let r = Fp::from((remainder(va) * remainder(vb)).rem_euclid(scale) as u128);
+        let scale_fp = Fp::from(scale as u128);
+        let c = (va * vb - r) * scale_fp.inverse().expect("the scale is nonzero");
+        let quotient = cs.generic_packed(GenericGateSpec::Add {
+            left_coeff: Some(scale_fp),
+            right_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(quotient, [c, r, va * vb]);
+        cs.copy(product[2], quotient[2]);
+
+        // 0 <= r and scale - 1 - r fit the bits of scale - 1, so r < scale
+        let max = Fp::from(scale as u128 - 1);
+        let remainder_bits = max.into_bigint().num_bits() as usize;
+        let headroom =
+            cs.generic_coeffs_packed([Fp::one(), Fp::one(), Fp::zero(), Fp::zero(), -max]);
+        w.set_cells(headroom, [r, max - r, Fp::zero()]);
+        cs.copy(quotient[1], headroom[0]);
+        BooleanGadget::decompose_cells(cs, w, quotient[1], remainder_bits);
+        BooleanGadget::decompose_cells(cs, w, headroom[1], remainder_bits);
+
+        Self::range_check_cells(cs, w, format, quotient[0]);
+        quotient[0]
+    }
+
+    /// Bit cell holding whether a < b.
+    pub fn less_than_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        format: FixedPoint,
+        a: Cell,
+        b: Cell,
+    ) -> Cell {
+        let a = Self::offset_cell(cs, w, format, a);
+        let b = Self::offset_cell(cs, w, format, b);
+        ComparisonGadget::less_than_cells(cs, w, a, b, format.bits + 1)
+    }
+
+    /// a + sign * b, range checked.
+    fn linear_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        format: FixedPoint,
+        a: Cell,
+        b: Cell,
+        sign: Fp,
+    ) -> Cell {
+        let cells = cs.generic_packed(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(sign),
+            output_coeff: Some(-Fp::one()),
+        });
+        let (va, vb) = (w.get(a), w.get(b));
+        w.set_cells(cells, [va, vb, va + sign * vb]);
+        cs.copy(a, cells[0]);
+        cs.copy(b, cells[1]);
+
+        Self::range_check_cells(cs, w, format, cells[2]);
+        cells[2]
+    }
+
+    /// a + 2^bits.
+    fn offset_cell(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        format: FixedPoint,
+        a: Cell,
+    ) -> Cell {
+        let offset = format.offset();
+        let cells =
+            cs.generic_coeffs_packed([Fp::one(), Fp::zero(), -Fp::one(), Fp::zero(), offset]);
+        let va = w.get(a);
+        w.set_cells(cells, [va, Fp::zero(), va + offset]);
+        cs.copy(a, cells[0]);
+        cells[2]
+    }
+}
+
+/// Native fixed-point arithmetic matching the gadget.
+pub struct FixedPointWitness;
+
+impl FixedPointWitness {
+    /// Field element holding the stored value `raw`.
+    pub fn to_field(raw: i128) -> Fp {
+        let magnitude = Fp::from(raw.unsigned_abs());
+        if raw < 0 {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    /// Stored value held by `value`, if it fits an i128.
+    pub fn from_field(value: Fp) -> Option<i128> {
+        let fits = |v: Fp| v.into_bigint().num_bits() < 128;
+        let magnitude = |v: Fp| {
+            let words = v.into_bigint().0;
+            words[0] as i128 | (words[1] as i128) << 64
+        };
+        if fits(value) {
+            Some(magnitude(value))
+        } else if fits(-value) {
+            Some(-magnitude(-value))
+        } else {
+            None
+        }
+    }
+
+    /// a + b, or `None` on overflow.
+    pub fn add(format: FixedPoint, a: i128, b: i128) -> Option<i128> {
+        a.checked_add(b).filter(|c| format.contains(*c))
+    }
+
+    /// a - b, or `None` on overflow.
+    pub fn sub(format: FixedPoint, a: i128, b: i128) -> Option<i128> {
+        a.checked_sub(b).filter(|c| format.contains(*c))
+    }
+
+    /// a * b rounded toward negative infinity, or `None` on overflow.
+    pub fn mul(format: FixedPoint, a: i128, b: i128) -> Option<i128> {
+        let product = BigInt::from(a) * BigInt::from(b);
+        let scale = BigInt::from(format.scale());
+        // Division truncates toward zero
+        let mut quotient = &product / &scale;
+        if &quotient * &scale > product {
+            quotient -= 1;
+        }
+        i128::try_from(quotient)
+            .ok()
+            .filter(|c| format.contains(*c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kimchi::circuits::polynomials::generic::GENERIC_COEFFS;
+
+    fn alloc(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, raw: i128) -> Cell {
+        let [cell, _, _] = cs.generic_coeffs([Fp::zero(); GENERIC_COEFFS]);
+        w.set(cell, FixedPointWitness::to_field(raw));
+        cell
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        let format = FixedPoint::new(2, 64).unwrap();
+        assert_eq!(format.parse("12.5").unwrap(), 1250);
+        assert_eq!(format.parse("-0.07").unwrap(), -7);
+        assert_eq!(format.parse("3").unwrap(), 300);
+        assert_eq!(format.format(-1250), "-12.50");
+        assert_eq!(format.format(7), "0.07");
+        assert_eq!(format.from_f64(-1.005).unwrap(), -100);
+        for invalid in ["1.234", "", "-", "1.", ".5", "1e3", "+1"] {
+            assert!(format.parse(invalid).is_err(), "{:?}", invalid);
+        }
+        assert!(format.parse("184467440737095516.16").is_err());
+        assert!(format.from_f64(f64::NAN).is_err());
+
+        let integers = FixedPoint::new(0, 8).unwrap();
+        assert_eq!(integers.format(-255), "-255");
+        assert!(FixedPoint::new(19, 64).is_err());
+        assert!(FixedPoint::new(2, 121).is_err());
+
+        for raw in [0, 1, -1, i128::MAX >> 1, -(1 << 120)] {
+            let field = FixedPointWitness::to_field(raw);
+            assert_eq!(FixedPointWitness::from_field(field), Some(raw));
+        }
+    }
+
+    #[test]
+    fn test_arithmetic_cells() {
+        let format = FixedPoint::new(3, 40).unwrap();
+        let values = [0, 1500, -1500, 2_250, -7, 999_999];
+        for a in values {
+            for b in values {
+                let mut cs = CircuitBuilder::new(0);
+                let mut w = WitnessBuilder::new(0);
+                let (ca, cb) = (alloc(&mut cs, &mut w, a), alloc(&mut cs, &mut w, b));
+                FixedPointGadget::range_check_cells(&mut cs, &mut w, format, ca);
+                FixedPointGadget::range_check_cells(&mut cs, &mut w, format, cb);
+
+                let sum = FixedPointGadget::add_cells(&mut cs, &mut w, format, ca, cb);
+                let difference = FixedPointGadget::sub_cells(&mut cs, &mut w, format, ca, cb);
+                let product = FixedPointGadget::mul_cells(&mut cs, &mut w, format, ca, cb);
+                let below = FixedPointGadget::less_than_cells(&mut cs, &mut w, format, ca, cb);
+                cs.check_witness(&w)
+                    .unwrap_or_else(|e| panic!("{} {}: {}", a, b, e));
+
+                let read = |cell| FixedPointWitness::from_field(w.get(cell)).unwrap();
+                assert_eq!(read(sum), a + b);
+                assert_eq!(read(difference), a - b);
+                assert_eq!(Some(read(product)), FixedPointWitness::mul(format, a, b));
+                assert_eq!(read(below), (a < b) as i128);
+            }
+        }
+
+        // Rounding toward negative infinity: 1.5 * -0.007 = -0.0105
+        assert_eq!(FixedPointWitness::mul(format, 1500, -7), Some(-11));
+        assert_eq!(FixedPointWitness::mul(format, 1500, 7), Some(10));
+    }
+
+    #[test]
+    fn test_rejects_overflow_and_forgery() {
+        let format = FixedPoint::new(2, 16).unwrap();
+        let max = (1 << 16) - 1;
+        assert_eq!(FixedPointWitness::add(format, max, 1), None);
+        assert_eq!(FixedPointWitness::sub(format, -max, 2), None);
+        assert_eq!(FixedPointWitness::mul(format, max, 200), None);
+
+        type Op = fn(&mut CircuitBuilder, &mut WitnessBuilder, FixedPoint, Cell, Cell) -> Cell;
+        let circuit = |a: i128, b: i128, op: Op| {
+            let mut cs = CircuitBuilder::new(0);
+            let mut w = WitnessBuilder::new(0);
+            let (ca, cb) = (alloc(&mut cs, &mut w, a), alloc(&mut cs, &mut w, b));
+            let result = op(&mut cs, &mut w, format, ca, cb);
+            (cs, w, result)
+        };
+        let (cs, w, _) = circuit(max, 1, FixedPointGadget::add_cells);
+        assert!(cs.check_witness(&w).is_err());
+        let (cs, w, _) = circuit(max, 200, FixedPointGadget::mul_cells);
+        assert!(cs.check_witness(&w).is_err());
+
+        // 1.50 * 0.33 = 0.495: neither 0.50 nor 0.48 passes for 0.49
+        let (cs, mut w, product) = circuit(150, 33, FixedPointGadget::mul_cells);
+        cs.check_witness(&w).unwrap();
+        assert_eq!(FixedPointWitness::from_field(w.get(product)), Some(49));
+        for forged in [50u64, 48] {
+            w.set(product, Fp::from(forged));
+            assert!(cs.check_witness(&w).is_err());
+        }
+    }
+}
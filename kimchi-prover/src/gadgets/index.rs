@@ -0,0 +1,394 @@
+//! Dynamic array indexing for Kimchi circuits.
+//!
+//! Selects `array[i]` for a witness index `i`, as needed when parsing reads
+//! at a data-dependent offset (the PKCS#1 separator, JWT claim offsets).
+//! A one-hot selector `s_k = [k = i]` is constrained by:
+//!
+//! - every `s_k` is boolean and `sum(s_k) = 1`, so exactly one bit is set;
+//! - `sum(k * s_k) = i`, which ties the set bit to the index and makes an
+//!   out-of-range index unsatisfiable;
+//! - `value = sum(s_k * array[k])`.
+//!
+//! The selector also stands alone, for a choice the circuit keeps private
+//! rather than an index, e.g. a vote or a menu item:
+//! [`IndexGadget::one_hot_cells`] or [`IndexGadget::assert_one_hot_cells`]
+//! constrain it, and [`IndexGadget::select_with_cells`] or, for a public
+//! table, [`IndexGadget::select_constant_cells`] apply it as a dot product.
+
+use ark_ff::{BigInteger, One, PrimeField, Zero};
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use kimchi::circuits::wires::Wire;
+use mina_curves::pasta::Fp;
+
+use super::boolean::BooleanGadget;
+use crate::builder::{fill_row_layout, Cell, CircuitBuilder, Gadget, WitnessBuilder};
+use crate::error::Result;
+use crate::prover::COLUMNS;
+
+/// Gadget for reading an array at a witness index.
+pub struct IndexGadget {
+    gates: Vec<CircuitGate<Fp>>,
+    current_row: usize,
+}
+
+impl IndexGadget {
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            gates: Vec::new(),
+            current_row: start_row,
+        }
+    }
+
+    pub fn current_row(&self) -> usize {
+        self.current_row
+    }
+
+    fn push_generic(&mut self, spec: GenericGateSpec<Fp>) {
+        let wires = Wire::for_row(self.current_row);
+        self.gates
+            .push(CircuitGate::create_generic_gadget(wires, spec, None));
+        self.current_row += 1;
+    }
+
+    /// Boolean constraint: b * b - b = 0.
+    fn boolean(&mut self) {
+        self.push_generic(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Accumulation row: acc' = acc + weight * term.
+    fn accumulate(&mut self, weight: Fp) {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(weight),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Select `array[i]` from an array of `len` elements.
+    pub fn select(&mut self, len: usize) -> usize {
+        assert!(len > 0, "cannot index an empty array");
+        let start = self.current_row;
+
+        for k in 0..len {
+            self.boolean();
+            // sum(s_k) and sum(k * s_k)
+            self.accumulate(Fp::one());
+            self.accumulate(Fp::from(k as u64));
+            // value += s_k * array[k]
+            self.push_generic(GenericGateSpec::Mul {
+                mul_coeff: Some(Fp::one()),
+                output_coeff: Some(-Fp::one()),
+            });
+            self.accumulate(Fp::one());
+        }
+
+        // sum(s_k) = 1
+        self.push_generic(GenericGateSpec::Const(Fp::one()));
+
+        start
+    }
+
+    /// Consume the gadget and return the gates.
+    pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
+        (self.gates, self.current_row)
+    }
+}
+
+impl Gadget for IndexGadget {
+    type Inputs = [[Fp; COLUMNS]];
+
+    fn build(&self, cs: &mut CircuitBuilder) -> usize {
+        cs.extend_gates(&self.gates, self.current_row - self.gates.len())
+    }
+
+    fn rows(&self) -> usize {
+        self.gates.len()
+    }
+
+    fn witness(&self, w: &mut WitnessBuilder, rows: &Self::Inputs) -> Result<()> {
+        fill_row_layout(w, self.rows(), rows)
+    }
+}
+
+/// Cell-level dynamic indexing.
+impl IndexGadget {
+    /// Allocate and constrain the one-hot selector for `index` over `len` positions.
+    ///
+    /// An index outside `0..len` leaves every bit unset, so the witness fails
+    /// the `sum(s_k) = 1` constraint.
+    pub fn selector_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        index: Cell,
+        len: usize,
+    ) -> Vec<Cell> {
+        assert!(len > 0, "cannot index an empty array");
+        let bits = Self::one_hot_cells(cs, w, index_value(w.get(index)), len);
+
+        let selected = Self::weighted_sum(cs, w, &bits, |k| Fp::from(k as u64));
+        cs.copy(selected, index);
+
+        bits
+    }
+
+    /// Allocate a one-hot selector of `len` bits with `position` set.
+    ///
+    /// A position outside `0..len`, or `None`, leaves every bit unset, so
+    /// the witness fails the `sum(s_k) = 1` constraint.
+    pub fn one_hot_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        position: Option<usize>,
+        len: usize,
+    ) -> Vec<Cell> {
+        let bits: Vec<Cell> = (0..len)
+            .map(|k| BooleanGadget::alloc_bit(cs, w, position == Some(k)))
+            .collect();
+        Self::assert_sum_one(cs, w, &bits);
+        bits
+    }
+
+    /// Constrain existing cells to be a one-hot selector: each boolean, and
+    /// exactly one set.
+    pub fn assert_one_hot_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        selector: &[Cell],
+    ) {
+        for &bit in selector {
+            BooleanGadget::assert_boolean(cs, w, bit);
+        }
+        Self::assert_sum_one(cs, w, selector);
+    }
+
+    /// `sum(bits) = 1`.
+    fn assert_sum_one(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, bits: &[Cell]) {
+        let count = Self::weighted_sum(cs, w, bits, |_| Fp::one());
+        let [one, _, _] = cs.generic(GenericGateSpec::Const(Fp::one()));
+        w.set(one, Fp::one());
+        cs.copy(count, one);
+    }
+
+    /// Select `array[index]`.
+    pub fn select_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        array: &[Cell],
+        index: Cell,
+    ) -> Cell {
+        let selector = Self::selector_cells(cs, w, index, array.len());
+        Self::select_with_cells(cs, w, array, &selector)
+    }
+
+    /// Select `sum(s_k * array[k])`, the dot product with an already
+    /// constrained selector.
+    ///
+    /// Reusing one selector for several arrays of the same length costs a
+    /// multiplication and an accumulation per element for each extra read.
+    pub fn select_with_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        array: &[Cell],
+        selector: &[Cell],
+    ) -> Cell {
+        assert_eq!(array.len(), selector.len(), "selector length mismatch");
+
+        let products: Vec<Cell> = array
+            .iter()
+            .zip(selector)
+            .map(|(&a, &s)| BooleanGadget::and_cells(cs, w, s, a))
+            .collect();
+        Self::weighted_sum(cs, w, &products, |_| Fp::one())
+    }
+
+    /// Select `sum(s_k * table[k])` from a table of constants, e.g. the
+    /// price of the chosen item.
+    ///
+    /// The constants are coefficients, so this costs one accumulation per
+    /// element and no multiplications.
+    pub fn select_constant_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        table: &[Fp],
+        selector: &[Cell],
+    ) -> Cell {
+        assert_eq!(table.len(), selector.len(), "selector length mismatch");
+        Self::weighted_sum(cs, w, selector, |k| table[k])
+    }
+
+    /// `sum(weight(k) * terms[k])` as an accumulation chain starting from zero.
+    fn weighted_sum(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        terms: &[Cell],
+        weight: impl Fn(usize) -> Fp,
+    ) -> Cell {
+        let [mut acc, _, _] = cs.generic(GenericGateSpec::Const(Fp::zero()));
+        w.set(acc, Fp::zero());
+        for (k, &term) in terms.iter().enumerate() {
+            let cells = cs.generic(GenericGateSpec::Add {
+                left_coeff: Some(Fp::one()),
+                right_coeff: Some(weight(k)),
+                output_coeff: Some(-Fp::one()),
+            });
+            let (vacc, vterm) = (w.get(acc), w.get(term));
+            w.set_cells(cells, [vacc, vterm, vacc + weight(k) * vterm]);
+            cs.copy(acc, cells[0]);
+            cs.copy(term, cells[1]);
+            acc = cells[2];
+        }
+        acc
+    }
+}
+
+/// Index held in a field element, if it fits in a `usize`.
+fn index_value(value: Fp) -> Option<usize> {
+    let bytes = value.into_bigint().to_bytes_le();
+    if bytes[8..].iter().any(|b| *b != 0) {
+        return None;
+    }
+    usize::try_from(u64::from_le_bytes(std::array::from_fn(|i| bytes[i]))).ok()
+}
+
+/// Witness generator for dynamic indexing.
+pub struct IndexWitness;
+
+impl IndexWitness {
+    /// One-hot selector for `index` over `len` positions.
+    pub fn selector(index: usize, len: usize) -> Option<Vec<Fp>> {
+        if index >= len {
+            return None;
+        }
+        let mut selector = vec![Fp::zero(); len];
+        selector[index] = Fp::one();
+        Some(selector)
+    }
+
+    /// `array[index]` as a field element.
+    pub fn select(array: &[Fp], index: usize) -> Option<Fp> {
+        array.get(index).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn constants(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, values: &[u64]) -> Vec<Cell> {
+        values
+            .iter()
+            .map(|v| {
+                let [cell, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(*v)));
+                w.set(cell, Fp::from(*v));
+                cell
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_witness() {
+        let selector = IndexWitness::selector(2, 4).unwrap();
+        assert_eq!(selector.iter().filter(|s| **s == Fp::one()).count(), 1);
+        assert_eq!(selector[2], Fp::one());
+        assert!(IndexWitness::selector(4, 4).is_none());
+        assert_eq!(
+            IndexWitness::select(&[Fp::from(7u64), Fp::from(9u64)], 1),
+            Some(Fp::from(9u64))
+        );
+    }
+
+    /// Unconstrained witness cell.
+    fn witness_cell(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, value: u64) -> Cell {
+        let [cell, _, _] = cs.generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::zero()),
+            right_coeff: Some(Fp::zero()),
+            output_coeff: Some(Fp::zero()),
+        });
+        w.set(cell, Fp::from(value));
+        cell
+    }
+
+    #[test]
+    fn test_select_cells() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let array = constants(&mut cs, &mut w, &[0x30, 0x00, 0x01, 0xFF, 0x00, 0x2A]);
+        let index = witness_cell(&mut cs, &mut w, 3);
+
+        let value = IndexGadget::select_cells(&mut cs, &mut w, &array, index);
+        assert_eq!(w.get(value), Fp::from(0xFFu64));
+        assert!(cs.check_witness(&w).is_ok());
+
+        // Claiming a different value for the same index fails
+        w.set(value, Fp::zero());
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_out_of_range_index() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let array = constants(&mut cs, &mut w, &[1, 2, 3]);
+        let index = witness_cell(&mut cs, &mut w, 3);
+
+        IndexGadget::select_cells(&mut cs, &mut w, &array, index);
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_one_hot_cells() {
+        let prices = [250u64, 400, 175].map(Fp::from);
+
+        for (choice, expected) in [(Some(1), true), (Some(3), false), (None, false)] {
+            let mut cs = CircuitBuilder::new(0);
+            let mut w = WitnessBuilder::new(0);
+            let selector = IndexGadget::one_hot_cells(&mut cs, &mut w, choice, 3);
+            let price = IndexGadget::select_constant_cells(&mut cs, &mut w, &prices, &selector);
+            assert_eq!(cs.check_witness(&w).is_ok(), expected);
+            if expected {
+                assert_eq!(w.get(price), Fp::from(400u64));
+            }
+        }
+
+        // Votes cast as existing cells
+        let vote = |cs: &mut CircuitBuilder, w: &mut WitnessBuilder, bits: &[u64]| {
+            let selector: Vec<Cell> = bits.iter().map(|b| witness_cell(cs, w, *b)).collect();
+            IndexGadget::assert_one_hot_cells(cs, w, &selector);
+            selector
+        };
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let options = constants(&mut cs, &mut w, &[10, 20, 30, 40]);
+        let selector = vote(&mut cs, &mut w, &[0, 0, 1, 0]);
+        let chosen = IndexGadget::select_with_cells(&mut cs, &mut w, &options, &selector);
+        assert_eq!(w.get(chosen), Fp::from(30u64));
+        assert!(cs.check_witness(&w).is_ok());
+
+        // Two votes, none, and a vote of weight 2 with another of -1
+        for bits in [[1, 0, 1, 0], [0, 0, 0, 0]] {
+            let mut cs = CircuitBuilder::new(0);
+            let mut w = WitnessBuilder::new(0);
+            vote(&mut cs, &mut w, &bits);
+            assert!(cs.check_witness(&w).is_err());
+        }
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let selector = vote(&mut cs, &mut w, &[2, 0, 0, 0]);
+        w.set(selector[1], -Fp::one());
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_gadget_construction() {
+        let mut gadget = IndexGadget::new(0);
+        gadget.select(8);
+        let (gates, rows) = gadget.build();
+
+        assert_eq!(rows, 8 * 5 + 1);
+        assert_eq!(gates.len(), rows);
+    }
+}
@@ -0,0 +1,214 @@
+//! Keccak-256 over bit cells, as used by Ethereum.
+//!
+//! This is the original Keccak padding (`0x01 .. 0x80`), not the SHA-3
+//! padding of FIPS 202, so digests match Solidity's `keccak256`. Lanes are
+//! 64 bit cells, least significant first, and messages and digests are held
+//! byte by byte like in `Sha256Gadget::hash_bits_cells`, which is the
+//! little-endian lane order Keccak absorbs. A permutation costs about
+//! 77,000 rows, one per 136 bytes of message.
+
+use ark_ff::{One, Zero};
+use mina_curves::pasta::Fp;
+use sha3::{Digest, Keccak256};
+
+use super::boolean::BooleanGadget;
+use super::word64::{Word64Gadget, WORD64_BITS};
+use crate::builder::{Cell, CircuitBuilder, WitnessBuilder};
+use crate::types::Bytes32;
+
+/// Bytes absorbed per permutation by Keccak-256.
+pub const RATE_BYTES: usize = 136;
+
+/// Lanes in the state.
+const LANES: usize = 25;
+
+/// Round constants of Keccak-f[1600].
+const RC: [u64; 24] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation of lane `x + 5 * y` in the rho step.
+const ROTATIONS: [usize; LANES] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// A lane as bit cells, least significant first.
+type Lane = [Cell; WORD64_BITS];
+
+/// Cell-level Keccak.
+pub struct KeccakGadget;
+
+impl KeccakGadget {
+    /// Keccak-256 of the message in `bits`, matching `KeccakWitness::hash`.
+    ///
+    /// The message bits must already be constrained to be boolean; the
+    /// digest bits are. Padding is laid out as constant cells.
+    pub fn hash_bits_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        bits: &[Cell],
+    ) -> [Cell; 256] {
+        assert!(bits.len().is_multiple_of(8), "message must be whole bytes");
+        let bit = [
+            BooleanGadget::constant_bit(cs, w, false),
+            BooleanGadget::constant_bit(cs, w, true),
+        ];
+
+        let mut message = bits.to_vec();
+        for byte in Self::padding(bits.len() / 8) {
+            message.extend((0..8).map(|j| bit[(byte >> j & 1) as usize]));
+        }
+
+        let mut state: Option<[Lane; LANES]> = None;
+        for block in message.chunks(RATE_BYTES * 8) {
+            let lane = |i: usize| -> Option<Lane> {
+                (i < RATE_BYTES / 8).then(|| std::array::from_fn(|k| block[64 * i + k]))
+            };
+            let absorbed = match state {
+                // The state starts at zero, so the first block is taken as is
+                None => std::array::from_fn(|i| lane(i).unwrap_or([bit[0]; WORD64_BITS])),
+                Some(state) => std::array::from_fn(|i| match lane(i) {
+                    Some(lane) => Word64Gadget::xor_u64_cells(cs, w, &state[i], &lane),
+                    None => state[i],
+                }),
+            };
+            state = Some(Self::permutation_cells(cs, w, absorbed));
+        }
+
+        let state = state.expect("padding gives at least one block");
+        std::array::from_fn(|k| state[k / WORD64_BITS][k % WORD64_BITS])
+    }
+
+    /// Keccak-f[1600] on a state of 25 lanes, indexed `x + 5 * y`.
+    pub fn permutation_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        mut state: [Lane; LANES],
+    ) -> [Lane; LANES] {
+        for rc in RC {
+            // theta
+            let columns: [Lane; 5] = std::array::from_fn(|x| {
+                (1..5).fold(state[x], |acc, y| {
+                    Word64Gadget::xor_u64_cells(cs, w, &acc, &state[x + 5 * y])
+                })
+            });
+            let effects: [Lane; 5] = std::array::from_fn(|x| {
+                let rotated = Word64Gadget::rotl_bits(&columns[(x + 1) % 5], 1);
+                Word64Gadget::xor_u64_cells(cs, w, &columns[(x + 4) % 5], &rotated)
+            });
+            for (i, lane) in state.iter_mut().enumerate() {
+                *lane = Word64Gadget::xor_u64_cells(cs, w, lane, &effects[i % 5]);
+            }
+
+            // rho and pi
+            let mut moved = state;
+            for (i, lane) in state.iter().enumerate() {
+                let (x, y) = (i % 5, i / 5);
+                moved[y + 5 * ((2 * x + 3 * y) % 5)] = Word64Gadget::rotl_bits(lane, ROTATIONS[i]);
+            }
+
+            // chi
+            state = std::array::from_fn(|i| {
+                let (x, y) = (i % 5, i / 5);
+                let [b, c] = [1, 2].map(|dx| moved[(x + dx) % 5 + 5 * y]);
+                std::array::from_fn(|k| {
+                    let t = Self::and_not_cell(cs, w, b[k], c[k]);
+                    BooleanGadget::xor_cells(cs, w, moved[i][k], t)
+                })
+            });
+
+            // iota
+            for (k, cell) in state[0].iter_mut().enumerate() {
+                if rc >> k & 1 == 1 {
+                    *cell = BooleanGadget::not_cell(cs, w, *cell);
+                }
+            }
+        }
+        state
+    }
+
+    /// `(1 - b) * c` of two bits in a single constraint: c - b * c - out = 0.
+    fn and_not_cell(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, b: Cell, c: Cell) -> Cell {
+        let cells =
+            cs.generic_coeffs_packed([Fp::zero(), Fp::one(), -Fp::one(), -Fp::one(), Fp::zero()]);
+        let (vb, vc) = (w.get(b), w.get(c));
+        w.set_cells(cells, [vb, vc, vc - vb * vc]);
+        cs.copy(b, cells[0]);
+        cs.copy(c, cells[1]);
+        cells[2]
+    }
+
+    /// Padding bytes appended to a message of `len` bytes.
+    fn padding(len: usize) -> Vec<u8> {
+        let mut padding = vec![0u8; RATE_BYTES - len % RATE_BYTES];
+        padding[0] |= 0x01;
+        *padding.last_mut().expect("padding is never empty") |= 0x80;
+        padding
+    }
+}
+
+/// Native Keccak-256 matching the gadget.
+pub struct KeccakWitness;
+
+impl KeccakWitness {
+    /// Keccak-256 of `message`.
+    pub fn hash(message: &[u8]) -> Bytes32 {
+        Bytes32(Keccak256::digest(message).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash() {
+        // keccak256("") as returned by Solidity
+        assert_eq!(
+            KeccakWitness::hash(b"").to_hex(),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn test_hash_bits_cells() {
+        let message = b"kimchi";
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let bits: Vec<Cell> = message
+            .iter()
+            .flat_map(|byte| (0..8).map(move |j| byte >> j & 1 == 1))
+            .map(|bit| BooleanGadget::alloc_bit(&mut cs, &mut w, bit))
+            .collect();
+
+        let digest = KeccakGadget::hash_bits_cells(&mut cs, &mut w, &bits);
+        let expected = KeccakWitness::hash(message);
+        for (k, cell) in digest.iter().enumerate() {
+            assert_eq!(w.get(*cell), Fp::from(expected[k / 8] >> (k % 8) & 1));
+        }
+        assert!(cs.check_witness(&w).is_ok());
+    }
+}
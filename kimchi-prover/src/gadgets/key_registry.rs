@@ -0,0 +1,286 @@
+//! Registries of pinned issuer keys.
+//!
+//! A circuit checking an issuer's signature (e.g. a DKIM RSA signature)
+//! against one built-in key stops working when the issuer rotates it.
+//! Instead, the circuit takes the root of a [`KeyRegistry`] as a public
+//! input and proves that the key it checked is one of those pinned:
+//!
+//! - a key's digest is `hash(tag("issuer_key"), n, k_1, ..., k_n)` over its
+//!   `n` limbs, e.g. the 64-bit limbs of an RSA modulus;
+//! - the registry is a Poseidon Merkle tree (see [`super::merkle`]) of
+//!   depth `d` over the digests, padded with zero leaves.
+//!
+//! Adding or rotating keys changes the root the verifier pins, but not the
+//! circuit, which only depends on `d`. Empty slots are zero leaves, which
+//! no key digest equals short of a Poseidon preimage.
+
+use ark_ff::Zero;
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use mina_curves::pasta::Fp;
+
+use super::merkle::{MerkleGadget, MerkleStep, MerkleWitness};
+use super::poseidon::{PoseidonGadget, PoseidonWitness};
+use super::rsa::RsaWitness;
+use crate::builder::{Cell, CircuitBuilder, WitnessBuilder};
+use crate::error::{ProverError, Result};
+use crate::types::Bytes256;
+
+/// Domain tag of key digests.
+pub const ISSUER_KEY_DOMAIN: &str = "issuer_key";
+
+/// Digests of pinned keys in a Merkle tree of fixed depth.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyRegistry {
+    depth: usize,
+    digests: Vec<Fp>,
+}
+
+impl KeyRegistry {
+    /// An empty registry of `2^depth` slots.
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            digests: Vec::new(),
+        }
+    }
+
+    /// Depth of the tree, i.e. the length of membership paths.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Number of keys the registry holds at most.
+    pub fn capacity(&self) -> usize {
+        1 << self.depth
+    }
+
+    /// Number of pinned keys.
+    pub fn len(&self) -> usize {
+        self.digests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+
+    /// Digests of the pinned keys, in slot order.
+    pub fn digests(&self) -> &[Fp] {
+        &self.digests
+    }
+
+    /// Pin the key with `digest`, e.g. from
+    /// [`KeyRegistryWitness::key_digest`]. Pinning a key twice keeps one slot.
+    pub fn insert(&mut self, digest: Fp) -> Result<()> {
+        if self.contains(digest) {
+            return Ok(());
+        }
+        if self.digests.len() == self.capacity() {
+            return Err(ProverError::InvalidInput(format!(
+                "key registry of depth {} is full ({} keys)",
+                self.depth,
+                self.capacity()
+            )));
+        }
+        self.digests.push(digest);
+        Ok(())
+    }
+
+    /// Unpin the key with `digest`. Returns whether it was pinned.
+    pub fn remove(&mut self, digest: Fp) -> bool {
+        let len = self.digests.len();
+        self.digests.retain(|d| *d != digest);
+        self.digests.len() != len
+    }
+
+    /// Whether the key with `digest` is pinned.
+    pub fn contains(&self, digest: Fp) -> bool {
+        self.digests.contains(&digest)
+    }
+
+    /// Root of the tree, to pin in the verifier as the circuit's public input.
+    pub fn root(&self) -> Fp {
+        MerkleWitness::tree(&self.leaves()).0
+    }
+
+    /// Membership path of the key with `digest`, or `None` if it is not
+    /// pinned.
+    pub fn path(&self, digest: Fp) -> Option<Vec<MerkleStep>> {
+        let slot = self.digests.iter().position(|d| *d == digest)?;
+        let (_, mut paths) = MerkleWitness::tree(&self.leaves());
+        Some(paths.swap_remove(slot))
+    }
+
+    /// Digests padded with zero leaves to the capacity.
+    fn leaves(&self) -> Vec<Fp> {
+        let mut leaves = self.digests.clone();
+        leaves.resize(self.capacity(), Fp::zero());
+        leaves
+    }
+}
+
+/// Cell-level key digests and registry membership.
+pub struct KeyRegistryGadget;
+
+impl KeyRegistryGadget {
+    /// Digest of the key whose limbs are in `limbs`, matching
+    /// `KeyRegistryWitness::key_digest`.
+    pub fn key_digest_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        limbs: &[Cell],
+    ) -> Cell {
+        let tag = KeyRegistryWitness::tag();
+        let len = Fp::from(limbs.len() as u64);
+        let mut elements = vec![Self::constant(cs, w, tag), Self::constant(cs, w, len)];
+        elements.extend_from_slice(limbs);
+        PoseidonGadget::hash_cells(cs, w, &elements)
+    }
+
+    /// Constrain the key whose limbs are in `limbs` to be pinned in the
+    /// registry with root `root`, typically a public input cell.
+    ///
+    /// `path` comes from `KeyRegistry::path`; its length, the registry
+    /// depth, is part of the circuit.
+    pub fn assert_registered_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        limbs: &[Cell],
+        root: Cell,
+        path: &[MerkleStep],
+    ) {
+        let digest = Self::key_digest_cells(cs, w, limbs);
+        let computed = MerkleGadget::root_cells(cs, w, digest, path);
+        cs.copy(computed, root);
+    }
+
+    fn constant(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, value: Fp) -> Cell {
+        let [cell, _, _] = cs.generic(GenericGateSpec::Const(value));
+        w.set(cell, value);
+        cell
+    }
+}
+
+/// Native key digests matching the gadget.
+pub struct KeyRegistryWitness;
+
+impl KeyRegistryWitness {
+    /// Digest of the key with `limbs`.
+    pub fn key_digest(limbs: &[Fp]) -> Fp {
+        let mut elements = vec![Self::tag(), Fp::from(limbs.len() as u64)];
+        elements.extend_from_slice(limbs);
+        PoseidonWitness::hash(&elements)
+    }
+
+    /// Digest of an RSA-2048 key over the 64-bit limbs of its modulus, as
+    /// `RsaGadget` lays them out.
+    pub fn rsa_key_digest(modulus: &Bytes256) -> Fp {
+        let limbs: Vec<Fp> = RsaWitness::bytes_to_limbs(modulus)
+            .iter()
+            .map(|limb| Fp::from(*limb))
+            .collect();
+        Self::key_digest(&limbs)
+    }
+
+    fn tag() -> Fp {
+        PoseidonWitness::domain_tag(ISSUER_KEY_DOMAIN).expect("the issuer key tag is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::rsa::RSA_LIMBS;
+    use crate::testing::GadgetHarness;
+    use kimchi::circuits::polynomials::generic::GENERIC_COEFFS;
+
+    fn modulus(seed: u8) -> Bytes256 {
+        Bytes256::from([seed; 256])
+    }
+
+    #[test]
+    fn test_registry() {
+        let keys: Vec<Fp> = (1..=3)
+            .map(|seed| KeyRegistryWitness::rsa_key_digest(&modulus(seed)))
+            .collect();
+        let mut registry = KeyRegistry::new(2);
+        let empty_root = registry.root();
+        for key in &keys {
+            registry.insert(*key).unwrap();
+        }
+        registry.insert(keys[0]).unwrap();
+        assert_eq!(registry.len(), 3);
+        assert_ne!(registry.root(), empty_root);
+
+        for key in &keys {
+            let path = registry.path(*key).unwrap();
+            assert_eq!(path.len(), 2);
+            assert_eq!(MerkleWitness::root(*key, &path), registry.root());
+        }
+
+        // Rotation: the old key is dropped and a new one pinned
+        let old_root = registry.root();
+        let rotated = KeyRegistryWitness::rsa_key_digest(&modulus(4));
+        assert!(registry.remove(keys[0]));
+        registry.insert(rotated).unwrap();
+        assert_ne!(registry.root(), old_root);
+        assert!(registry.path(keys[0]).is_none());
+
+        registry.insert(keys[0]).unwrap();
+        assert!(matches!(
+            registry.insert(KeyRegistryWitness::rsa_key_digest(&modulus(5))),
+            Err(ProverError::InvalidInput(_))
+        ));
+    }
+
+    /// Circuit proving that the RSA key `modulus` is pinned in `registry`.
+    fn registered_key_circuit(
+        registry: &KeyRegistry,
+        modulus: &Bytes256,
+    ) -> (CircuitBuilder, WitnessBuilder) {
+        let limbs = RsaWitness::bytes_to_limbs(modulus);
+        let digest = KeyRegistryWitness::rsa_key_digest(modulus);
+        let path = registry.path(digest).unwrap();
+
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let root = cs.public_input();
+        w.set(root, registry.root());
+        let mut limb_cells = Vec::with_capacity(RSA_LIMBS);
+        for chunk in limbs.chunks(3) {
+            let cells = cs.generic_coeffs([Fp::zero(); GENERIC_COEFFS]);
+            for (cell, limb) in cells.into_iter().zip(chunk) {
+                w.set(cell, Fp::from(*limb));
+                limb_cells.push(cell);
+            }
+        }
+        KeyRegistryGadget::assert_registered_cells(&mut cs, &mut w, &limb_cells, root, &path);
+        (cs, w)
+    }
+
+    #[test]
+    fn test_prove_registered_key() {
+        let mut registry = KeyRegistry::new(2);
+        registry
+            .insert(KeyRegistryWitness::rsa_key_digest(&modulus(1)))
+            .unwrap();
+        let (cs, w) = registered_key_circuit(&registry, &modulus(1));
+        let gates = cs.build();
+
+        let harness = GadgetHarness::new();
+        let proof = harness
+            .prove_circuit(gates.clone(), w.columns(), 1)
+            .unwrap();
+        assert!(proof.verifies(&[registry.root()]));
+
+        // After rotation, a key pinned since proves with the same circuit
+        let old_root = registry.root();
+        registry
+            .insert(KeyRegistryWitness::rsa_key_digest(&modulus(2)))
+            .unwrap();
+        let (cs, w) = registered_key_circuit(&registry, &modulus(2));
+        assert_eq!(cs.build(), gates);
+        let proof = harness.prove_circuit(gates, w.columns(), 1).unwrap();
+        assert!(proof.verifies(&[registry.root()]));
+        assert!(!proof.verifies(&[old_root]));
+    }
+}
@@ -0,0 +1,305 @@
+//! Lookup-table gadgets for byte-wise XOR, AND and range checks.
+//!
+//! Bytes are split into 4-bit nibbles and each nibble pair is looked up in a
+//! 256-entry table, instead of decomposing every byte into 8 boolean
+//! constraints. The tables are shared by every bitwise gadget (SHA-256,
+//! Keccak, AES) and must be passed to `KimchiProver::setup_with_lookups`.
+//!
+//! Each Kimchi `Lookup` row performs three `(index, value)` lookups in the
+//! table selected by column 0. A nibble pair `(x, y)` is looked up as
+//! `(16 * x + y, x op y)`; a nibble `x` is range checked by looking up
+//! `(x, x)` in the XOR table, which only contains that pair for `x < 16`.
+
+use ark_ff::{One, Zero};
+use kimchi::circuits::gate::{CircuitGate, GateType};
+use kimchi::circuits::lookup::tables::LookupTable;
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use kimchi::circuits::wires::Wire;
+use mina_curves::pasta::Fp;
+
+/// Table id of the 4-bit XOR table.
+///
+/// Ids 0 and 1 are reserved by Kimchi for its built-in XOR and range-check tables.
+pub const NIBBLE_XOR_TABLE_ID: i32 = 16;
+
+/// Table id of the 4-bit AND table.
+pub const NIBBLE_AND_TABLE_ID: i32 = 17;
+
+/// Number of entries in each nibble table.
+pub const NIBBLE_TABLE_SIZE: usize = 256;
+
+/// Build a nibble table with entries `(16 * x + y, op(x, y))`.
+fn nibble_table(id: i32, op: impl Fn(u8, u8) -> u8) -> LookupTable<Fp> {
+    let mut index = Vec::with_capacity(NIBBLE_TABLE_SIZE);
+    let mut value = Vec::with_capacity(NIBBLE_TABLE_SIZE);
+    for x in 0..16u8 {
+        for y in 0..16u8 {
+            index.push(Fp::from(16 * x as u64 + y as u64));
+            value.push(Fp::from(op(x, y) as u64));
+        }
+    }
+    LookupTable {
+        id,
+        data: vec![index, value],
+    }
+}
+
+/// 4-bit XOR table.
+pub fn nibble_xor_table() -> LookupTable<Fp> {
+    nibble_table(NIBBLE_XOR_TABLE_ID, |x, y| x ^ y)
+}
+
+/// 4-bit AND table.
+pub fn nibble_and_table() -> LookupTable<Fp> {
+    nibble_table(NIBBLE_AND_TABLE_ID, |x, y| x & y)
+}
+
+/// All tables used by `LookupGadget`.
+pub fn lookup_tables() -> Vec<LookupTable<Fp>> {
+    vec![nibble_xor_table(), nibble_and_table()]
+}
+
+/// Gadget for lookup-based byte operations.
+pub struct LookupGadget {
+    gates: Vec<CircuitGate<Fp>>,
+    current_row: usize,
+}
+
+impl LookupGadget {
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            gates: Vec::new(),
+            current_row: start_row,
+        }
+    }
+
+    pub fn current_row(&self) -> usize {
+        self.current_row
+    }
+
+    fn push_generic(&mut self, spec: GenericGateSpec<Fp>) {
+        let wires = Wire::for_row(self.current_row);
+        self.gates
+            .push(CircuitGate::create_generic_gadget(wires, spec, None));
+        self.current_row += 1;
+    }
+
+    /// Lookup row against `table_id`, preceded by a row pinning column 0 to the id.
+    fn lookup(&mut self, table_id: i32) {
+        self.push_generic(GenericGateSpec::Const(Fp::from(table_id as u64)));
+
+        let wires = Wire::for_row(self.current_row);
+        self.gates
+            .push(CircuitGate::new(GateType::Lookup, wires, vec![]));
+        self.current_row += 1;
+    }
+
+    /// Composition: value = 16 * hi + lo.
+    fn compose(&mut self) {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::from(16u64)),
+            right_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Range check a single byte: 0 <= b < 256.
+    ///
+    /// Decomposes the byte into nibbles and range checks both with one lookup row.
+    pub fn byte_range_check(&mut self) -> usize {
+        let start = self.current_row;
+
+        self.compose();
+        self.lookup(NIBBLE_XOR_TABLE_ID);
+
+        start
+    }
+
+    /// Byte-wise binary operation c = a op b using the nibble table `table_id`.
+    fn byte_op(&mut self, table_id: i32) -> usize {
+        let start = self.current_row;
+
+        // a, b, c = 16 * hi + lo
+        for _ in 0..3 {
+            self.compose();
+        }
+
+        // Table indices 16 * a_hi + b_hi and 16 * a_lo + b_lo
+        for _ in 0..2 {
+            self.compose();
+        }
+
+        // (idx_hi, c_hi), (idx_lo, c_lo) in the operation table
+        self.lookup(table_id);
+
+        // b_hi and b_lo are nibbles, which with the table index bounds a as well
+        self.lookup(NIBBLE_XOR_TABLE_ID);
+
+        start
+    }
+
+    /// Byte XOR: c = a ^ b.
+    pub fn byte_xor(&mut self) -> usize {
+        self.byte_op(NIBBLE_XOR_TABLE_ID)
+    }
+
+    /// Byte AND: c = a & b.
+    pub fn byte_and(&mut self) -> usize {
+        self.byte_op(NIBBLE_AND_TABLE_ID)
+    }
+
+    /// Word XOR over `num_bytes` bytes.
+    pub fn xor_bytes(&mut self, num_bytes: usize) -> usize {
+        let start = self.current_row;
+        for _ in 0..num_bytes {
+            self.byte_xor();
+        }
+        start
+    }
+
+    /// Word AND over `num_bytes` bytes.
+    pub fn and_bytes(&mut self, num_bytes: usize) -> usize {
+        let start = self.current_row;
+        for _ in 0..num_bytes {
+            self.byte_and();
+        }
+        start
+    }
+
+    pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
+        (self.gates, self.current_row)
+    }
+}
+
+/// Witness generator for lookup gadgets.
+pub struct LookupWitness;
+
+impl LookupWitness {
+    /// Split a byte into (high, low) nibbles.
+    pub fn nibbles(byte: u8) -> (u8, u8) {
+        (byte >> 4, byte & 0x0F)
+    }
+
+    /// Table index for a nibble pair.
+    pub fn table_index(x: u8, y: u8) -> Fp {
+        Fp::from(16 * x as u64 + y as u64)
+    }
+
+    /// Lookup row cells `[table_id, idx0, val0, idx1, val1, idx2, val2]`.
+    ///
+    /// Unused slots are filled with `(0, 0)`, which every nibble table contains.
+    pub fn lookup_row(table_id: i32, entries: &[(Fp, Fp)]) -> [Fp; 7] {
+        assert!(entries.len() <= 3, "a lookup row holds at most 3 lookups");
+
+        let mut row = [Fp::zero(); 7];
+        row[0] = Fp::from(table_id as u64);
+        for (i, (index, value)) in entries.iter().enumerate() {
+            row[1 + 2 * i] = *index;
+            row[2 + 2 * i] = *value;
+        }
+        row
+    }
+
+    /// Lookup rows for `byte_xor`/`byte_and`: the operation row and the nibble range row.
+    pub fn byte_op_rows(table_id: i32, a: u8, b: u8) -> [[Fp; 7]; 2] {
+        let (a_hi, a_lo) = Self::nibbles(a);
+        let (b_hi, b_lo) = Self::nibbles(b);
+        let op = |x: u8, y: u8| {
+            if table_id == NIBBLE_AND_TABLE_ID {
+                x & y
+            } else {
+                x ^ y
+            }
+        };
+
+        [
+            Self::lookup_row(
+                table_id,
+                &[
+                    (
+                        Self::table_index(a_hi, b_hi),
+                        Fp::from(op(a_hi, b_hi) as u64),
+                    ),
+                    (
+                        Self::table_index(a_lo, b_lo),
+                        Fp::from(op(a_lo, b_lo) as u64),
+                    ),
+                ],
+            ),
+            Self::lookup_row(
+                NIBBLE_XOR_TABLE_ID,
+                &[
+                    (Fp::from(b_hi as u64), Fp::from(b_hi as u64)),
+                    (Fp::from(b_lo as u64), Fp::from(b_lo as u64)),
+                ],
+            ),
+        ]
+    }
+
+    /// Check that every lookup in `row` is present in `table`.
+    pub fn row_in_table(row: &[Fp; 7], table: &LookupTable<Fp>) -> bool {
+        if row[0] != Fp::from(table.id as u64) {
+            return false;
+        }
+        (0..3).all(|i| {
+            let (index, value) = (row[1 + 2 * i], row[2 + 2 * i]);
+            table.data[0]
+                .iter()
+                .zip(&table.data[1])
+                .any(|(ti, tv)| *ti == index && *tv == value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tables() {
+        let xor = nibble_xor_table();
+        let and = nibble_and_table();
+
+        assert_eq!(xor.data[0].len(), NIBBLE_TABLE_SIZE);
+        assert_eq!(and.data[1].len(), NIBBLE_TABLE_SIZE);
+        assert_eq!(xor.data[1][16 * 0xA + 0x5], Fp::from(0xFu64));
+        assert_eq!(and.data[1][16 * 0xC + 0xA], Fp::from(0x8u64));
+    }
+
+    #[test]
+    fn test_byte_op_rows() {
+        let tables = lookup_tables();
+        for (a, b) in [(0x00u8, 0x00u8), (0xA5, 0x3C), (0xFF, 0x0F)] {
+            let [op_row, range_row] = LookupWitness::byte_op_rows(NIBBLE_XOR_TABLE_ID, a, b);
+            assert!(LookupWitness::row_in_table(&op_row, &tables[0]));
+            assert!(LookupWitness::row_in_table(&range_row, &tables[0]));
+
+            let [op_row, _] = LookupWitness::byte_op_rows(NIBBLE_AND_TABLE_ID, a, b);
+            assert!(LookupWitness::row_in_table(&op_row, &tables[1]));
+        }
+    }
+
+    #[test]
+    fn test_nibble_range() {
+        let table = nibble_xor_table();
+        let out_of_range =
+            LookupWitness::lookup_row(NIBBLE_XOR_TABLE_ID, &[(Fp::from(16u64), Fp::from(16u64))]);
+        assert!(!LookupWitness::row_in_table(&out_of_range, &table));
+    }
+
+    #[test]
+    fn test_gadget_construction() {
+        let mut gadget = LookupGadget::new(0);
+        gadget.byte_range_check();
+        let xor_start = gadget.byte_xor();
+        let (gates, rows) = gadget.build();
+
+        assert_eq!(xor_start, 3);
+        // 5 compositions + 2 * (id row + lookup row)
+        assert_eq!(rows, 3 + 9);
+        assert_eq!(
+            gates.iter().filter(|g| g.typ == GateType::Lookup).count(),
+            3
+        );
+    }
+}
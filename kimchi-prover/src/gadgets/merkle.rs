@@ -0,0 +1,415 @@
+//! Merkle path verification.
+//!
+//! A node is `PoseidonWitness::hash(&[left, right])` of its children. Each
+//! level of a path gives the sibling of the current node and whether the
+//! sibling is the right child; the gadget orders the pair with a boolean
+//! swap:
+//!
+//! - `left = sibling + b * (node - sibling)` and
+//!   `right = node + sibling - left`, where `b = 1` if the sibling is on
+//!   the right;
+//! - `node' = hash(left, right)`.
+//!
+//! Each level takes six generic constraints, packed into three rows, plus
+//! one Poseidon hash of two elements.
+//!
+//! Trees built by other tooling hash 32-byte nodes with a byte hash (see
+//! `HashFunction`), a node being the digest of `left || right`. Those paths
+//! are checked bit by bit with the same swap, and each level costs a byte
+//! hash of 64 bytes.
+use ark_ff::{One, Zero};
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use mina_curves::pasta::Fp;
+
+use super::boolean::BooleanGadget;
+use super::digest::{DigestCells, DigestGadget, DigestWitness, HashFunction};
+use super::poseidon::{PoseidonGadget, PoseidonWitness};
+use crate::builder::{Cell, CircuitBuilder, WitnessBuilder};
+use crate::types::Bytes32;
+use zeroize::Zeroize;
+
+/// One level of a Merkle path, from the leaf up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MerkleStep {
+    /// Sibling of the current node.
+    pub sibling: Fp,
+    /// Whether the sibling is the right child, i.e. the current node is on the left.
+    pub is_right: bool,
+}
+
+impl Zeroize for MerkleStep {
+    fn zeroize(&mut self) {
+        self.sibling.zeroize();
+        self.is_right.zeroize();
+    }
+}
+
+/// One level of a path through a tree of 32-byte nodes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DigestStep {
+    /// Sibling of the current node.
+    pub sibling: Bytes32,
+    /// Whether the sibling is the right child, i.e. the current node is on the left.
+    pub is_right: bool,
+}
+
+impl Zeroize for DigestStep {
+    fn zeroize(&mut self) {
+        self.sibling.0.zeroize();
+        self.is_right.zeroize();
+    }
+}
+
+/// Cell-level Merkle path verification.
+pub struct MerkleGadget;
+
+impl MerkleGadget {
+    /// Root of the tree containing the leaf in `leaf` at `path`.
+    ///
+    /// Siblings and directions are allocated as private cells; constrain the
+    /// returned cell to a public root to prove membership.
+    pub fn root_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        leaf: Cell,
+        path: &[MerkleStep],
+    ) -> Cell {
+        path.iter().fold(leaf, |node, step| {
+            let [left, right] = Self::order_cells(cs, w, node, step);
+            PoseidonGadget::hash_cells(cs, w, &[left, right])
+        })
+    }
+
+    /// Root of the tree of 32-byte nodes hashed with `hash` containing the
+    /// leaf in `leaf` at `path`.
+    ///
+    /// The leaf bits must already be constrained to be boolean; sibling bits
+    /// and directions are allocated as private cells.
+    ///
+    /// # Panics
+    ///
+    /// If `hash` is `Poseidon`; use `root_cells` for field element trees.
+    pub fn digest_root_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        hash: HashFunction,
+        leaf: &DigestCells,
+        path: &[DigestStep],
+    ) -> DigestCells {
+        path.iter().fold(*leaf, |node, step| {
+            let b = BooleanGadget::alloc_bit(cs, w, step.is_right);
+            let sibling = DigestGadget::alloc_bytes(cs, w, step.sibling.as_ref());
+            let mut children = Vec::with_capacity(2 * node.len());
+            let (left, right): (Vec<Cell>, Vec<Cell>) = node
+                .iter()
+                .zip(&sibling)
+                .map(|(n, s)| Self::swap_cells(cs, w, b, *n, *s))
+                .map(|[l, r]| (l, r))
+                .unzip();
+            children.extend(left);
+            children.extend(right);
+            DigestGadget::hash_bits_cells(cs, w, hash, &children)
+        })
+    }
+
+    /// `[sibling + t, node - t]` with `t = b * (node - sibling)`: the
+    /// (left, right) pair of two bits, in four constraints.
+    fn swap_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        b: Cell,
+        node: Cell,
+        sibling: Cell,
+    ) -> [Cell; 2] {
+        let (vb, vn, vs) = (w.get(b), w.get(node), w.get(sibling));
+        let vt = vb * (vn - vs);
+        let sub = [Fp::one(), -Fp::one(), -Fp::one(), Fp::zero(), Fp::zero()];
+        let add = [Fp::one(), Fp::one(), -Fp::one(), Fp::zero(), Fp::zero()];
+
+        let d = cs.generic_coeffs_packed(sub);
+        w.set_cells(d, [vn, vs, vn - vs]);
+        cs.copy(node, d[0]);
+        cs.copy(sibling, d[1]);
+
+        let t = cs.generic_packed(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(t, [vb, vn - vs, vt]);
+        cs.copy(b, t[0]);
+        cs.copy(d[2], t[1]);
+
+        let left = cs.generic_coeffs_packed(add);
+        w.set_cells(left, [vs, vt, vs + vt]);
+        cs.copy(sibling, left[0]);
+        cs.copy(t[2], left[1]);
+
+        let right = cs.generic_coeffs_packed(sub);
+        w.set_cells(right, [vn, vt, vn - vt]);
+        cs.copy(node, right[0]);
+        cs.copy(t[2], right[1]);
+
+        [left[2], right[2]]
+    }
+
+    /// Root of the tree containing the leaf in `leaf` at the position whose
+    /// little-endian bits are in `position`, one per level, with the given
+    /// siblings.
+    ///
+    /// Unlike `root_cells`, the directions come from existing cells, so a
+    /// proof can tie the path to a position it constrains elsewhere.
+    pub fn root_at_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        leaf: Cell,
+        position: &[Cell],
+        siblings: &[Fp],
+    ) -> Cell {
+        assert_eq!(position.len(), siblings.len(), "one position bit per level");
+        position
+            .iter()
+            .zip(siblings)
+            .fold(leaf, |node, (bit, sibling)| {
+                // The sibling is on the right when the node is a left child
+                let b = BooleanGadget::not_cell(cs, w, *bit);
+                let [left, right] = Self::order_with_cells(cs, w, b, node, *sibling);
+                PoseidonGadget::hash_cells(cs, w, &[left, right])
+            })
+    }
+
+    /// The (left, right) children for `node` and its sibling.
+    fn order_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        node: Cell,
+        step: &MerkleStep,
+    ) -> [Cell; 2] {
+        let b = BooleanGadget::alloc_bit(cs, w, step.is_right);
+        Self::order_with_cells(cs, w, b, node, step.sibling)
+    }
+
+    /// `order_cells` with the direction in `b`, set if the sibling is on
+    /// the right.
+    fn order_with_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        b: Cell,
+        node: Cell,
+        sibling: Fp,
+    ) -> [Cell; 2] {
+        let (vb, vn, vs) = (w.get(b), w.get(node), sibling);
+        let sub = [Fp::one(), -Fp::one(), -Fp::one(), Fp::zero(), Fp::zero()];
+        let add = [Fp::one(), Fp::one(), -Fp::one(), Fp::zero(), Fp::zero()];
+
+        // d = node - sibling
+        let d = cs.generic_coeffs_packed(sub);
+        w.set_cells(d, [vn, vs, vn - vs]);
+        cs.copy(node, d[0]);
+        let sibling = d[1];
+
+        // t = b * d
+        let t = cs.generic_packed(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(t, [vb, vn - vs, vb * (vn - vs)]);
+        cs.copy(b, t[0]);
+        cs.copy(d[2], t[1]);
+
+        // left = sibling + t
+        let left = cs.generic_coeffs_packed(add);
+        let vl = vs + vb * (vn - vs);
+        w.set_cells(left, [vs, vb * (vn - vs), vl]);
+        cs.copy(sibling, left[0]);
+        cs.copy(t[2], left[1]);
+
+        // right = (node + sibling) - left
+        let sum = cs.generic_coeffs_packed(add);
+        w.set_cells(sum, [vn, vs, vn + vs]);
+        cs.copy(node, sum[0]);
+        cs.copy(sibling, sum[1]);
+        let right = cs.generic_coeffs_packed(sub);
+        w.set_cells(right, [vn + vs, vl, vn + vs - vl]);
+        cs.copy(sum[2], right[0]);
+        cs.copy(left[2], right[1]);
+
+        [left[2], right[2]]
+    }
+}
+
+/// Native Merkle path hashing matching the gadget.
+pub struct MerkleWitness;
+
+impl MerkleWitness {
+    /// Parent of two children.
+    pub fn node(left: Fp, right: Fp) -> Fp {
+        PoseidonWitness::hash(&[left, right])
+    }
+
+    /// Root of the tree containing `leaf` at `path`.
+    pub fn root(leaf: Fp, path: &[MerkleStep]) -> Fp {
+        path.iter().fold(leaf, |node, step| {
+            if step.is_right {
+                Self::node(node, step.sibling)
+            } else {
+                Self::node(step.sibling, node)
+            }
+        })
+    }
+
+    /// Root of the tree over `leaves`, padded with zero leaves to a power of
+    /// two, and the path of every leaf.
+    pub fn tree(leaves: &[Fp]) -> (Fp, Vec<Vec<MerkleStep>>) {
+        let (root, paths) = Self::build_tree(leaves, Fp::zero(), Self::node);
+        let paths = paths
+            .into_iter()
+            .map(|path| {
+                path.into_iter()
+                    .map(|(sibling, is_right)| MerkleStep { sibling, is_right })
+                    .collect()
+            })
+            .collect();
+        (root, paths)
+    }
+
+    /// Parent of two 32-byte children: the digest of `left || right`.
+    ///
+    /// # Panics
+    ///
+    /// If `hash` is `Poseidon`; use `node` for field element trees.
+    pub fn digest_node(hash: HashFunction, left: &Bytes32, right: &Bytes32) -> Bytes32 {
+        DigestWitness::hash(hash, &[left.as_ref(), right.as_ref()].concat())
+    }
+
+    /// Root of the tree of 32-byte nodes containing `leaf` at `path`.
+    pub fn digest_root(hash: HashFunction, leaf: &Bytes32, path: &[DigestStep]) -> Bytes32 {
+        path.iter().fold(*leaf, |node, step| {
+            if step.is_right {
+                Self::digest_node(hash, &node, &step.sibling)
+            } else {
+                Self::digest_node(hash, &step.sibling, &node)
+            }
+        })
+    }
+
+    /// Root of the tree of 32-byte nodes over `leaves`, padded with zero
+    /// leaves to a power of two, and the path of every leaf.
+    pub fn digest_tree(hash: HashFunction, leaves: &[Bytes32]) -> (Bytes32, Vec<Vec<DigestStep>>) {
+        let (root, paths) = Self::build_tree(leaves, Bytes32::zero(), |left, right| {
+            Self::digest_node(hash, &left, &right)
+        });
+        let paths = paths
+            .into_iter()
+            .map(|path| {
+                path.into_iter()
+                    .map(|(sibling, is_right)| DigestStep { sibling, is_right })
+                    .collect()
+            })
+            .collect();
+        (root, paths)
+    }
+
+    /// Root over `leaves` padded with `zero`, and the (sibling, is_right)
+    /// levels of every leaf.
+    #[allow(clippy::type_complexity)]
+    fn build_tree<T: Copy>(
+        leaves: &[T],
+        zero: T,
+        node: impl Fn(T, T) -> T,
+    ) -> (T, Vec<Vec<(T, bool)>>) {
+        let width = leaves.len().max(1).next_power_of_two();
+        let mut level: Vec<T> = leaves.to_vec();
+        level.resize(width, zero);
+        let mut paths = vec![Vec::new(); leaves.len()];
+
+        let mut positions: Vec<usize> = (0..leaves.len()).collect();
+        while level.len() > 1 {
+            for (path, position) in paths.iter_mut().zip(positions.iter_mut()) {
+                path.push((level[*position ^ 1], *position % 2 == 0));
+                *position /= 2;
+            }
+            level = level.chunks(2).map(|pair| node(pair[0], pair[1])).collect();
+        }
+
+        (level[0], paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_paths() {
+        let leaves: Vec<Fp> = (1..=5u64).map(Fp::from).collect();
+        let (root, paths) = MerkleWitness::tree(&leaves);
+        assert_eq!(paths[0].len(), 3);
+        for (leaf, path) in leaves.iter().zip(&paths) {
+            assert_eq!(MerkleWitness::root(*leaf, path), root);
+        }
+        assert_ne!(MerkleWitness::root(Fp::from(9u64), &paths[0]), root);
+    }
+
+    #[test]
+    fn test_root_cells() {
+        let leaves: Vec<Fp> = (10..14u64).map(Fp::from).collect();
+        let (root, paths) = MerkleWitness::tree(&leaves);
+
+        for (leaf, path) in leaves.iter().zip(&paths) {
+            let mut cs = CircuitBuilder::new(0);
+            let mut w = WitnessBuilder::new(0);
+            let [leaf_cell, _, _] = cs.generic(GenericGateSpec::Const(*leaf));
+            w.set(leaf_cell, *leaf);
+
+            let root_cell = MerkleGadget::root_cells(&mut cs, &mut w, leaf_cell, path);
+            assert_eq!(w.get(root_cell), root);
+            assert!(cs.check_witness(&w).is_ok());
+        }
+
+        // A flipped direction reaches a different root
+        let mut path = paths[1].clone();
+        path[0].is_right = !path[0].is_right;
+        assert_ne!(MerkleWitness::root(leaves[1], &path), root);
+    }
+
+    #[test]
+    fn test_digest_tree() {
+        let leaves: Vec<Bytes32> = (0..3u8).map(|i| Bytes32([i; 32])).collect();
+        let (root, paths) = MerkleWitness::digest_tree(HashFunction::Keccak256, &leaves);
+        let expected = MerkleWitness::digest_node(
+            HashFunction::Keccak256,
+            &MerkleWitness::digest_node(HashFunction::Keccak256, &leaves[0], &leaves[1]),
+            &MerkleWitness::digest_node(HashFunction::Keccak256, &leaves[2], &Bytes32::zero()),
+        );
+        assert_eq!(root, expected);
+        for (leaf, path) in leaves.iter().zip(&paths) {
+            assert_eq!(
+                MerkleWitness::digest_root(HashFunction::Keccak256, leaf, path),
+                root
+            );
+        }
+        assert_ne!(
+            MerkleWitness::digest_root(HashFunction::Sha256, &leaves[0], &paths[0]),
+            root
+        );
+    }
+
+    #[test]
+    fn test_digest_root_cells() {
+        let leaves = [Bytes32([7; 32]), Bytes32([9; 32])];
+        for hash in [HashFunction::Sha256, HashFunction::Keccak256] {
+            let (root, paths) = MerkleWitness::digest_tree(hash, &leaves);
+            let mut cs = CircuitBuilder::new(0);
+            let mut w = WitnessBuilder::new(0);
+            let leaf = DigestGadget::alloc_bytes(&mut cs, &mut w, leaves[1].as_ref());
+            let leaf: DigestCells = std::array::from_fn(|k| leaf[k]);
+
+            let root_cells =
+                MerkleGadget::digest_root_cells(&mut cs, &mut w, hash, &leaf, &paths[1]);
+            for (k, cell) in root_cells.iter().enumerate() {
+                assert_eq!(w.get(*cell), Fp::from(root[k / 8] >> (k % 8) & 1));
+            }
+            assert!(cs.check_witness(&w).is_ok());
+        }
+    }
+}
@@ -3,12 +3,48 @@
 //! This module provides building blocks for constructing zero-knowledge proofs
 //! that verify cryptographic operations like hashing and signature verification.
 
+pub mod addition;
 pub mod boolean;
+pub mod bytes;
+pub mod cbor;
+pub mod commitment;
 pub mod comparison;
+pub mod der;
+pub mod digest;
+pub mod digits;
+pub mod fixed_point;
+pub mod index;
+pub mod keccak;
+pub mod key_registry;
+pub mod lookup;
+pub mod merkle;
+pub mod poseidon;
+pub mod range;
 pub mod rsa;
 pub mod sha256;
+pub mod sorting;
+pub mod vector_commitment;
+pub mod word64;
 
+pub use addition::{MultiAddGadget, MultiAddWitness};
 pub use boolean::BooleanGadget;
-pub use comparison::ComparisonGadget;
-pub use rsa::{RsaGadget, RsaWitness, RSA_LIMBS};
-pub use sha256::{Sha256Gadget, Sha256Witness};
+pub use bytes::BytesGadget;
+pub use cbor::{CborGadget, CborWitness};
+pub use commitment::{CommitmentGadget, CommitmentWitness, Salt};
+pub use comparison::{AgeCheckRows, ComparisonGadget, ComparisonWitness};
+pub use der::{DerGadget, DerWitness};
+pub use digest::{DigestGadget, DigestWitness, HashFunction};
+pub use digits::DigitsGadget;
+pub use fixed_point::{FixedPoint, FixedPointGadget, FixedPointWitness};
+pub use index::{IndexGadget, IndexWitness};
+pub use keccak::{KeccakGadget, KeccakWitness};
+pub use key_registry::{KeyRegistry, KeyRegistryGadget, KeyRegistryWitness};
+pub use lookup::{lookup_tables, LookupGadget, LookupWitness};
+pub use merkle::{DigestStep, MerkleGadget, MerkleStep, MerkleWitness};
+pub use poseidon::{PoseidonGadget, PoseidonWitness};
+pub use range::{RangeCheckGadget, RangeCheckWitness};
+pub use rsa::{RsaGadget, RsaRowPlan, RsaSteps, RsaWitness, RSA_LIMBS};
+pub use sha256::{Sha256Gadget, Sha256Steps, Sha256Witness};
+pub use sorting::{SortingGadget, SortingNetwork, SortingWitness};
+pub use vector_commitment::{VectorCommitmentGadget, VectorCommitmentWitness};
+pub use word64::{Word64Gadget, Word64Witness};
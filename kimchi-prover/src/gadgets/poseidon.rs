@@ -0,0 +1,387 @@
+//! Domain-separated Poseidon hashing of strings to field elements.
+//!
+//! Public inputs such as relying-party IDs and scopes are bound as the hash
+//! of an ASCII string under an explicit domain tag, so the prover and every
+//! verifier implementation derive the same field element. The encoding is:
+//!
+//! - the domain tag, at most 31 ASCII bytes packed little-endian into one element;
+//! - the string length in bytes;
+//! - the string, in 31-byte chunks packed little-endian.
+//!
+//! The elements are absorbed into the Kimchi Poseidon sponge (rate 2,
+//! `fp_kimchi` parameters) and one element is squeezed.
+
+use ark_ff::{Field, One, Zero};
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use kimchi::circuits::polynomials::poseidon::{generate_witness, POS_ROWS_PER_HASH};
+use kimchi::circuits::wires::Wire;
+use mina_curves::pasta::Fp;
+use mina_poseidon::constants::PlonkSpongeConstantsKimchi;
+use mina_poseidon::pasta::fp_kimchi;
+use mina_poseidon::poseidon::{ArithmeticSponge, Sponge};
+
+use crate::builder::{fill_row_layout, Cell, CircuitBuilder, Gadget, WitnessBuilder};
+use crate::error::{ProverError, Result};
+use crate::prover::{COLUMNS, FULL_ROUNDS};
+
+/// Bytes packed into one field element.
+pub const BYTES_PER_ELEMENT: usize = 31;
+
+/// Maximum length of a domain tag.
+pub const MAX_DOMAIN_LEN: usize = BYTES_PER_ELEMENT;
+
+/// Sponge rate: elements absorbed per permutation.
+pub const SPONGE_RATE: usize = 2;
+
+/// Rows of one permutation: the Poseidon rows and the output row.
+pub const PERMUTATION_ROWS: usize = POS_ROWS_PER_HASH + 1;
+
+/// Number of elements absorbed for a string of `num_bytes` bytes.
+pub fn num_elements(num_bytes: usize) -> usize {
+    2 + num_bytes.div_ceil(BYTES_PER_ELEMENT)
+}
+
+/// Gadget for Poseidon hashing.
+pub struct PoseidonGadget {
+    gates: Vec<CircuitGate<Fp>>,
+    current_row: usize,
+}
+
+impl PoseidonGadget {
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            gates: Vec::new(),
+            current_row: start_row,
+        }
+    }
+
+    pub fn current_row(&self) -> usize {
+        self.current_row
+    }
+
+    fn push_generic(&mut self, spec: GenericGateSpec<Fp>) {
+        let wires = Wire::for_row(self.current_row);
+        self.gates
+            .push(CircuitGate::create_generic_gadget(wires, spec, None));
+        self.current_row += 1;
+    }
+
+    /// Accumulation row: acc' = acc + weight * term.
+    fn accumulate(&mut self, weight: Fp) {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(weight),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// One Poseidon permutation: input in row 0, output in the last row.
+    pub fn permutation(&mut self) -> usize {
+        let start = self.current_row;
+        let params = fp_kimchi::static_params();
+        let (gates, next_row) = CircuitGate::create_poseidon_gadget(
+            start,
+            [
+                Wire::for_row(start),
+                Wire::for_row(start + POS_ROWS_PER_HASH),
+            ],
+            &params.round_constants,
+        );
+        self.gates.extend(gates);
+        self.current_row = next_row;
+        start
+    }
+
+    /// Sponge hash of `num_elements` elements.
+    pub fn hash_elements(&mut self, num_elements: usize) -> usize {
+        assert!(num_elements > 0, "at least one element is required");
+        let start = self.current_row;
+
+        for chunk in 0..num_elements.div_ceil(SPONGE_RATE) {
+            // state[i] += element
+            let absorbed = SPONGE_RATE.min(num_elements - chunk * SPONGE_RATE);
+            for _ in 0..absorbed {
+                self.accumulate(Fp::one());
+            }
+            self.permutation();
+        }
+
+        start
+    }
+
+    /// Domain-separated hash of a string of `num_bytes` range-checked bytes.
+    ///
+    /// `tag` is `PoseidonWitness::domain_tag(domain)`; it and the length are
+    /// constants of the circuit.
+    pub fn hash_string(&mut self, tag: Fp, num_bytes: usize) -> usize {
+        let start = self.current_row;
+
+        self.push_generic(GenericGateSpec::Const(tag));
+        self.push_generic(GenericGateSpec::Const(Fp::from(num_bytes as u64)));
+
+        // chunk = sum(256^j * byte_j)
+        for i in 0..num_bytes {
+            self.accumulate(byte_weight(i % BYTES_PER_ELEMENT));
+        }
+
+        self.hash_elements(num_elements(num_bytes));
+
+        start
+    }
+
+    /// Consume the gadget and return the gates.
+    pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
+        (self.gates, self.current_row)
+    }
+}
+
+impl Gadget for PoseidonGadget {
+    type Inputs = [[Fp; COLUMNS]];
+
+    fn build(&self, cs: &mut CircuitBuilder) -> usize {
+        cs.extend_gates(&self.gates, self.current_row - self.gates.len())
+    }
+
+    fn rows(&self) -> usize {
+        self.gates.len()
+    }
+
+    fn witness(&self, w: &mut WitnessBuilder, rows: &Self::Inputs) -> Result<()> {
+        fill_row_layout(w, self.rows(), rows)
+    }
+}
+
+/// Cell-level Poseidon hashing.
+impl PoseidonGadget {
+    /// One Poseidon permutation of `input`.
+    pub fn permutation_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        input: [Cell; 3],
+    ) -> [Cell; 3] {
+        let mut gadget = PoseidonGadget::new(cs.current_row());
+        let start = gadget.permutation();
+        let (gates, _) = gadget.build();
+        cs.extend_gates(&gates, start);
+
+        // Lay the rounds out in scratch columns and copy them into the witness
+        let mut columns: [Vec<Fp>; COLUMNS] =
+            std::array::from_fn(|_| vec![Fp::zero(); PERMUTATION_ROWS]);
+        generate_witness(
+            0,
+            fp_kimchi::static_params(),
+            &mut columns,
+            input.map(|cell| w.get(cell)),
+        );
+        for (col, column) in columns.iter().enumerate() {
+            for (offset, value) in column.iter().enumerate() {
+                w.set(Cell::new(start + offset, col), *value);
+            }
+        }
+
+        let output_row = start + POS_ROWS_PER_HASH;
+        for (col, cell) in input.into_iter().enumerate() {
+            cs.copy(cell, Cell::new(start, col));
+        }
+        std::array::from_fn(|col| Cell::new(output_row, col))
+    }
+
+    /// Sponge hash of element cells, matching `PoseidonWitness::hash`.
+    pub fn hash_cells(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, elements: &[Cell]) -> Cell {
+        assert!(!elements.is_empty(), "at least one element is required");
+
+        let [zero, _, _] = cs.generic(GenericGateSpec::Const(Fp::zero()));
+        w.set(zero, Fp::zero());
+        let mut state = [zero; 3];
+
+        for chunk in elements.chunks(SPONGE_RATE) {
+            for (i, &element) in chunk.iter().enumerate() {
+                let cells = cs.generic(GenericGateSpec::Add {
+                    left_coeff: Some(Fp::one()),
+                    right_coeff: Some(Fp::one()),
+                    output_coeff: Some(-Fp::one()),
+                });
+                let (vs, ve) = (w.get(state[i]), w.get(element));
+                w.set_cells(cells, [vs, ve, vs + ve]);
+                cs.copy(state[i], cells[0]);
+                cs.copy(element, cells[1]);
+                state[i] = cells[2];
+            }
+            state = Self::permutation_cells(cs, w, state);
+        }
+
+        state[0]
+    }
+
+    /// Domain-separated hash of byte cells, matching `PoseidonWitness::hash_string`.
+    ///
+    /// The bytes must already be range checked; the domain tag and the length
+    /// are fixed by the circuit.
+    pub fn hash_string_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        domain: &str,
+        bytes: &[Cell],
+    ) -> Result<Cell> {
+        let tag = PoseidonWitness::domain_tag(domain)?;
+        let mut elements = vec![
+            Self::constant(cs, w, tag),
+            Self::constant(cs, w, Fp::from(bytes.len() as u64)),
+        ];
+
+        for chunk in bytes.chunks(BYTES_PER_ELEMENT) {
+            let mut acc = chunk[0];
+            for (j, &byte) in chunk.iter().enumerate().skip(1) {
+                let cells = cs.generic(GenericGateSpec::Add {
+                    left_coeff: Some(Fp::one()),
+                    right_coeff: Some(byte_weight(j)),
+                    output_coeff: Some(-Fp::one()),
+                });
+                let (vacc, vbyte) = (w.get(acc), w.get(byte));
+                w.set_cells(cells, [vacc, vbyte, vacc + byte_weight(j) * vbyte]);
+                cs.copy(acc, cells[0]);
+                cs.copy(byte, cells[1]);
+                acc = cells[2];
+            }
+            elements.push(acc);
+        }
+
+        Ok(Self::hash_cells(cs, w, &elements))
+    }
+
+    fn constant(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, value: Fp) -> Cell {
+        let [cell, _, _] = cs.generic(GenericGateSpec::Const(value));
+        w.set(cell, value);
+        cell
+    }
+}
+
+/// Weight `256^j` of byte `j` within a packed element.
+fn byte_weight(j: usize) -> Fp {
+    Fp::from(256u64).pow([j as u64])
+}
+
+/// Native Poseidon hashing matching the gadget.
+pub struct PoseidonWitness;
+
+impl PoseidonWitness {
+    /// Pack up to 31 bytes little-endian into a field element.
+    pub fn pack_bytes(bytes: &[u8]) -> Fp {
+        assert!(
+            bytes.len() <= BYTES_PER_ELEMENT,
+            "too many bytes for one element"
+        );
+        bytes.iter().enumerate().fold(Fp::zero(), |acc, (j, b)| {
+            acc + byte_weight(j) * Fp::from(*b as u64)
+        })
+    }
+
+    /// Field element for a domain tag.
+    pub fn domain_tag(domain: &str) -> Result<Fp> {
+        if !domain.is_ascii() || domain.len() > MAX_DOMAIN_LEN {
+            return Err(ProverError::InvalidInput(format!(
+                "domain tag must be at most {} ASCII bytes, got {:?}",
+                MAX_DOMAIN_LEN, domain
+            )));
+        }
+        Ok(Self::pack_bytes(domain.as_bytes()))
+    }
+
+    /// Elements absorbed for `value` under `domain`.
+    pub fn string_elements(domain: &str, value: &str) -> Result<Vec<Fp>> {
+        if !value.is_ascii() {
+            return Err(ProverError::InvalidInput(format!(
+                "string to hash must be ASCII, got {:?}",
+                value
+            )));
+        }
+
+        let mut elements = vec![Self::domain_tag(domain)?, Fp::from(value.len() as u64)];
+        elements.extend(
+            value
+                .as_bytes()
+                .chunks(BYTES_PER_ELEMENT)
+                .map(Self::pack_bytes),
+        );
+        Ok(elements)
+    }
+
+    /// Sponge hash of field elements.
+    pub fn hash(elements: &[Fp]) -> Fp {
+        let mut sponge = ArithmeticSponge::<Fp, PlonkSpongeConstantsKimchi, FULL_ROUNDS>::new(
+            fp_kimchi::static_params(),
+        );
+        sponge.absorb(elements);
+        sponge.squeeze()
+    }
+
+    /// Domain-separated hash of an ASCII string.
+    pub fn hash_string(domain: &str, value: &str) -> Result<Fp> {
+        Ok(Self::hash(&Self::string_elements(domain, value)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kimchi::circuits::gate::GateType;
+
+    #[test]
+    fn test_domain_separation() {
+        let a = PoseidonWitness::hash_string("rp-id", "example.com").unwrap();
+        let b = PoseidonWitness::hash_string("scope", "example.com").unwrap();
+        let c = PoseidonWitness::hash_string("rp-id", "example.com\0").unwrap();
+        assert_ne!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(
+            a,
+            PoseidonWitness::hash_string("rp-id", "example.com").unwrap()
+        );
+
+        assert!(PoseidonWitness::domain_tag(&"x".repeat(32)).is_err());
+        assert!(PoseidonWitness::hash_string("rp-id", "é").is_err());
+    }
+
+    #[test]
+    fn test_string_elements() {
+        let value = "a".repeat(40);
+        let elements = PoseidonWitness::string_elements("tag", &value).unwrap();
+        assert_eq!(elements.len(), num_elements(40));
+        assert_eq!(elements[1], Fp::from(40u64));
+        assert_eq!(elements[3], PoseidonWitness::pack_bytes(&[b'a'; 9]));
+    }
+
+    #[test]
+    fn test_hash_string_cells() {
+        let value = "https://verifier.example.org/presentation";
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let bytes: Vec<Cell> = value
+            .bytes()
+            .map(|b| PoseidonGadget::constant(&mut cs, &mut w, Fp::from(b as u64)))
+            .collect();
+
+        let digest = PoseidonGadget::hash_string_cells(&mut cs, &mut w, "rp-id", &bytes).unwrap();
+        assert_eq!(
+            w.get(digest),
+            PoseidonWitness::hash_string("rp-id", value).unwrap()
+        );
+        assert!(cs.gates().iter().any(|g| g.typ == GateType::Poseidon));
+        assert!(cs.check_witness(&w).is_ok());
+    }
+
+    #[test]
+    fn test_gadget_construction() {
+        let mut gadget = PoseidonGadget::new(0);
+        gadget.hash_string(PoseidonWitness::domain_tag("scope").unwrap(), 40);
+        let (gates, rows) = gadget.build();
+
+        // 2 constants, 40 packing rows, 4 elements absorbed over 2 permutations
+        assert_eq!(rows, 2 + 40 + 4 + 2 * PERMUTATION_ROWS);
+        assert_eq!(
+            gates.iter().filter(|g| g.typ == GateType::Poseidon).count(),
+            2 * POS_ROWS_PER_HASH
+        );
+    }
+}
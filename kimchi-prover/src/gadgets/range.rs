@@ -0,0 +1,318 @@
+//! Range checks and word decomposition across all 15 witness columns.
+//!
+//! Generic gates only constrain columns 0..6, so decomposing a word one
+//! boolean constraint at a time spends a row per two bits and leaves
+//! columns 7..15 empty. Kimchi's `RangeCheck0` gate decomposes the value in
+//! column 0 over the whole row instead: six 12-bit limbs, checked against
+//! Kimchi's built-in 12-bit lookup table, and eight 2-bit crumbs, for 88
+//! bits, most significant first:
+//!
+//! ```text
+//! column  0      1      2      3      4      5      6      7     ...  14
+//!         value  76..88 64..76 52..64 40..52 28..40 16..28 14..16 ... 0..2
+//! ```
+//!
+//! Columns 1 and 2 are copy-constrained to a zero cell, bounding the value
+//! to 64 bits. A bound of `num_bits < 64` also checks the complement
+//! `2^num_bits - 1 - value`, which is below `2^64` only if `value` is below
+//! `2^num_bits`. With the zero cell and the complement sharing one generic
+//! row, a check takes three rows where the bitwise layout of a 32-bit word
+//! takes twenty.
+//!
+//! The lookup table has 4096 rows, so a circuit using these gates needs a
+//! domain of at least 2^13 rows; `GadgetCost` accounts for it.
+
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use kimchi::circuits::gate::{CircuitGate, GateType};
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use mina_curves::pasta::Fp;
+
+use crate::builder::{fill_row_layout, Cell, CircuitBuilder, Gadget, WitnessBuilder};
+use crate::error::{ProverError, Result};
+use crate::prover::COLUMNS;
+
+/// Largest bound, in bits, a range check supports.
+pub const RANGE_CHECK_MAX_BITS: usize = 64;
+
+/// Rows of Kimchi's built-in 12-bit range-check lookup table.
+pub const RANGE_CHECK_TABLE_ROWS: usize = 1 << 12;
+
+/// Id of Kimchi's built-in range-check lookup table.
+pub const RANGE_CHECK_TABLE_ID: i32 = 1;
+
+/// Bit offset of each `RangeCheck0` column's limb, from column 1.
+const LIMB_OFFSETS: [u32; COLUMNS - 1] = [76, 64, 52, 40, 28, 16, 14, 12, 10, 8, 6, 4, 2, 0];
+
+/// Bit width of each `RangeCheck0` column's limb, from column 1.
+const LIMB_BITS: [u32; COLUMNS - 1] = [12, 12, 12, 12, 12, 12, 2, 2, 2, 2, 2, 2, 2, 2];
+
+/// Cells of a range check laid out by `RangeCheckGadget`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RangeCheckCells {
+    /// The checked value, in column 0 of its `RangeCheck0` row.
+    pub value: Cell,
+    /// The complement `2^num_bits - 1 - value`, for bounds below 64 bits.
+    pub complement: Option<Cell>,
+}
+
+/// Gadget for 15-column range checks.
+pub struct RangeCheckGadget {
+    gates: Vec<CircuitGate<Fp>>,
+    current_row: usize,
+}
+
+impl RangeCheckGadget {
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            gates: Vec::new(),
+            current_row: start_row,
+        }
+    }
+
+    pub fn current_row(&self) -> usize {
+        self.current_row
+    }
+
+    /// Range check: 0 <= value < 2^num_bits, with the value in column 0 of
+    /// the returned row.
+    pub fn range_check(&mut self, num_bits: usize) -> usize {
+        let mut cs = CircuitBuilder::new(self.current_row);
+        let cells = Self::layout(&mut cs, num_bits);
+        self.current_row = cs.current_row();
+        self.gates.extend(cs.build());
+        cells.value.row
+    }
+
+    /// Decompose a 32-bit word into limbs, returning the word's row.
+    pub fn decompose_u32(&mut self) -> usize {
+        self.range_check(32)
+    }
+
+    /// Rows one range check takes.
+    pub fn rows_for(num_bits: usize) -> usize {
+        if num_bits < RANGE_CHECK_MAX_BITS {
+            3
+        } else {
+            2
+        }
+    }
+
+    pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
+        (self.gates, self.current_row)
+    }
+
+    /// Lay out the gates and wiring of a check without assigning a witness.
+    fn layout(cs: &mut CircuitBuilder, num_bits: usize) -> RangeCheckCells {
+        assert!(
+            (1..=RANGE_CHECK_MAX_BITS).contains(&num_bits),
+            "range checks support 1 to {} bits, got {}",
+            RANGE_CHECK_MAX_BITS,
+            num_bits
+        );
+
+        let [zero, _, _] = cs.generic_packed(GenericGateSpec::Const(Fp::zero()));
+        let complement = (num_bits < RANGE_CHECK_MAX_BITS).then(|| {
+            // value + complement - (2^num_bits - 1) = 0
+            let max = Fp::from(u64::MAX >> (RANGE_CHECK_MAX_BITS - num_bits));
+            cs.generic_coeffs_packed([Fp::one(), Fp::one(), Fp::zero(), Fp::zero(), -max])
+        });
+
+        let mut checked = Vec::with_capacity(2);
+        let value_row = cs.gate(GateType::RangeCheck0, vec![Fp::zero()]);
+        checked.push(value_row);
+        if complement.is_some() {
+            checked.push(cs.gate(GateType::RangeCheck0, vec![Fp::zero()]));
+        }
+        for &row in &checked {
+            cs.copy(zero, Cell::new(row, 1));
+            cs.copy(zero, Cell::new(row, 2));
+        }
+
+        let value = Cell::new(value_row, 0);
+        let complement = complement.map(|[left, right, _]| {
+            cs.copy(left, value);
+            let cell = Cell::new(checked[1], 0);
+            cs.copy(right, cell);
+            cell
+        });
+        RangeCheckCells { value, complement }
+    }
+}
+
+impl Gadget for RangeCheckGadget {
+    type Inputs = [[Fp; COLUMNS]];
+
+    fn build(&self, cs: &mut CircuitBuilder) -> usize {
+        cs.extend_gates(&self.gates, self.current_row - self.gates.len())
+    }
+
+    fn rows(&self) -> usize {
+        self.gates.len()
+    }
+
+    fn witness(&self, w: &mut WitnessBuilder, rows: &Self::Inputs) -> Result<()> {
+        fill_row_layout(w, self.rows(), rows)
+    }
+}
+
+/// Cell-level range checks.
+impl RangeCheckGadget {
+    /// Range check `value` to `num_bits` bits, filling the limbs.
+    ///
+    /// A witness value that does not fit leaves the limbs unassigned, so
+    /// the witness fails to prove.
+    pub fn range_check_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        value: Cell,
+        num_bits: usize,
+    ) -> RangeCheckCells {
+        let cells = Self::layout(cs, num_bits);
+        cs.copy(value, cells.value);
+
+        let v = w.get(value);
+        let max = Fp::from(u64::MAX >> (RANGE_CHECK_MAX_BITS - num_bits));
+        if let Some(complement) = cells.complement {
+            let row = complement.row - 2;
+            w.set_cells(
+                [Cell::new(row, 3), Cell::new(row, 4), Cell::new(row, 5)],
+                [v, max - v, Fp::zero()],
+            );
+            Self::fill_row(w, complement.row, max - v);
+        }
+        Self::fill_row(w, cells.value.row, v);
+        cells
+    }
+
+    fn fill_row(w: &mut WitnessBuilder, row: usize, value: Fp) {
+        let limbs = RangeCheckWitness::row(value).unwrap_or_else(|_| {
+            let mut row = [Fp::zero(); COLUMNS];
+            row[0] = value;
+            row
+        });
+        for (col, limb) in limbs.into_iter().enumerate() {
+            w.set(Cell::new(row, col), limb);
+        }
+    }
+}
+
+/// Witness rows for range checks.
+pub struct RangeCheckWitness;
+
+impl RangeCheckWitness {
+    /// The `RangeCheck0` row of a value below 2^64.
+    pub fn row(value: Fp) -> Result<[Fp; COLUMNS]> {
+        let bigint = value.into_bigint();
+        if bigint.num_bits() > RANGE_CHECK_MAX_BITS as u32 {
+            return Err(ProverError::InvalidInput(
+                "range-checked value does not fit in 64 bits".into(),
+            ));
+        }
+        let v = bigint.as_ref()[0];
+        let mut row = [Fp::zero(); COLUMNS];
+        row[0] = value;
+        for col in 1..COLUMNS {
+            let (offset, bits) = (LIMB_OFFSETS[col - 1], LIMB_BITS[col - 1]);
+            let limb = (v as u128 >> offset) & ((1u128 << bits) - 1);
+            row[col] = Fp::from(limb as u64);
+        }
+        Ok(row)
+    }
+
+    /// Rows of a check laid out by `RangeCheckGadget::range_check`.
+    pub fn rows(value: u64, num_bits: usize) -> Result<Vec<[Fp; COLUMNS]>> {
+        if num_bits < RANGE_CHECK_MAX_BITS && value >> num_bits != 0 {
+            return Err(ProverError::InvalidInput(format!(
+                "{} does not fit in {} bits",
+                value, num_bits
+            )));
+        }
+        let mut generic = [Fp::zero(); COLUMNS];
+        let mut rows = Vec::with_capacity(3);
+        if num_bits < RANGE_CHECK_MAX_BITS {
+            let complement = (u64::MAX >> (RANGE_CHECK_MAX_BITS - num_bits)) - value;
+            generic[3] = Fp::from(value);
+            generic[4] = Fp::from(complement);
+            rows.push(generic);
+            rows.push(Self::row(Fp::from(value))?);
+            rows.push(Self::row(Fp::from(complement))?);
+        } else {
+            rows.push(generic);
+            rows.push(Self::row(Fp::from(value))?);
+        }
+        Ok(rows)
+    }
+
+    /// Recompose a `RangeCheck0` row from its limbs.
+    pub fn recompose(row: &[Fp; COLUMNS]) -> Fp {
+        (1..COLUMNS).fold(Fp::zero(), |acc, col| {
+            acc + row[col] * Fp::from(2u64).pow([LIMB_OFFSETS[col - 1] as u64])
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::GadgetCost;
+
+    #[test]
+    fn test_limbs() {
+        let row = RangeCheckWitness::row(Fp::from(0xDEAD_BEEF_0123_4567u64)).unwrap();
+        assert_eq!(RangeCheckWitness::recompose(&row), row[0]);
+        assert_eq!(row[1], Fp::zero());
+        assert_eq!(row[2], Fp::zero());
+        assert_eq!(row[3], Fp::from(0xDEAu64));
+        assert_eq!(row[14], Fp::from(3u64));
+        assert!(RangeCheckWitness::row(-Fp::one()).is_err());
+    }
+
+    #[test]
+    fn test_layout() {
+        let mut gadget = RangeCheckGadget::new(4);
+        assert_eq!(gadget.decompose_u32(), 5);
+        assert_eq!(gadget.range_check(64), 8);
+        assert_eq!(
+            Gadget::rows(&gadget),
+            RangeCheckGadget::rows_for(32) + RangeCheckGadget::rows_for(64)
+        );
+        let (gates, _) = gadget.build();
+        // Limb columns are wired to the zero cell, the value to the complement row
+        assert_eq!(gates[1].typ, GateType::RangeCheck0);
+        assert_eq!(gates[0].wires[3], kimchi::circuits::wires::Wire::new(5, 0));
+
+        let rows = RangeCheckWitness::rows(0xFFFF_FFFF, 32).unwrap();
+        assert_eq!(rows[2][0], Fp::zero());
+        assert!(RangeCheckWitness::rows(1 << 32, 32).is_err());
+
+        let cost = GadgetCost::from_gates(&gates);
+        assert_eq!(cost.lookup_table_rows, RANGE_CHECK_TABLE_ROWS);
+        assert_eq!(cost.required_srs_log2_size(), 13);
+    }
+
+    #[test]
+    fn test_range_check_cells() {
+        for (value, ok) in [(0xFFFF_FFFFu64, true), (1 << 32, false)] {
+            let mut cs = CircuitBuilder::new(0);
+            let mut w = WitnessBuilder::new(0);
+            let [cell, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(value)));
+            w.set(cell, Fp::from(value));
+
+            let cells = RangeCheckGadget::range_check_cells(&mut cs, &mut w, cell, 32);
+            assert_eq!(cs.num_rows(), 1 + RangeCheckGadget::rows_for(32));
+            assert!(cs.check_witness(&w).is_ok());
+
+            // An out-of-range value leaves the complement's limbs empty
+            let complement = cells.complement.unwrap();
+            let limbs = std::array::from_fn(|col| w.get(Cell::new(complement.row, col)));
+            assert_eq!(
+                RangeCheckWitness::recompose(&limbs) == w.get(complement),
+                ok
+            );
+            assert_eq!(
+                w.get(complement),
+                Fp::from(0xFFFF_FFFFu64) - Fp::from(value)
+            );
+        }
+    }
+}
@@ -6,16 +6,53 @@ use kimchi::circuits::polynomials::generic::GenericGateSpec;
 use kimchi::circuits::wires::Wire;
 use mina_curves::pasta::Fp;
 
+use super::boolean::BooleanGadget;
+use crate::builder::{
+    fill_row_layout, generic_spec_coeffs, pack_generic, Cell, CircuitBuilder, Gadget,
+    WitnessBuilder, WitnessLayout,
+};
+use crate::error::{ProverError, Result};
+use crate::incremental::WitnessSteps;
+use crate::prover::COLUMNS;
+use crate::types::{Bytes256, Bytes32};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
 /// Number of 64-bit limbs for RSA-2048.
 pub const RSA_LIMBS: usize = 32;
 
 /// Standard RSA public exponent.
 pub const RSA_EXPONENT: u32 = 65537;
 
+/// DER prefix of a SHA-256 digest in a PKCS#1 v1.5 signature.
+const DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+/// Number of 0xFF padding bytes `verify_pkcs1_padding` checks.
+const PADDING_CHECKED: usize = 8;
+
+/// Number of squarings in an exponentiation by `RSA_EXPONENT`.
+const SQUARINGS: usize = 16;
+
 /// Gadget builder for RSA verification circuits.
 pub struct RsaGadget {
     gates: Vec<CircuitGate<Fp>>,
     current_row: usize,
+    plan: RsaRowPlan,
+}
+
+/// Where an `RsaGadget` placed the blocks of `rsa_verify`, for
+/// `RsaWitness::fill_columns`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RsaRowPlan {
+    /// First row of each modular multiplication: the 16 squarings of the
+    /// exponentiation, then the multiplication by the signature.
+    pub mulmods: Vec<usize>,
+    /// First row of the PKCS#1 padding check.
+    pub padding: Option<usize>,
+    /// First row of the comparison with the expected encoded message.
+    pub equal: Option<usize>,
 }
 
 impl RsaGadget {
@@ -23,6 +60,7 @@ impl RsaGadget {
         Self {
             gates: Vec::new(),
             current_row: start_row,
+            plan: RsaRowPlan::default(),
         }
     }
 
@@ -30,6 +68,11 @@ impl RsaGadget {
         self.current_row
     }
 
+    /// Rows of the blocks laid out so far.
+    pub fn row_plan(&self) -> &RsaRowPlan {
+        &self.plan
+    }
+
     /// Constrain a limb to be in range [0, 2^64).
     pub fn range_check_limb(&mut self) -> usize {
         let start = self.current_row;
@@ -222,17 +265,31 @@ impl RsaGadget {
         start
     }
 
-    /// Modular reduction.
-    pub fn bigint_mod(&mut self) -> usize {
+    /// Division with remainder: a = q * m + r with 0 <= r < m.
+    ///
+    /// `a` is a double-width value (2 * RSA_LIMBS limbs) and `m` the modulus.
+    /// The quotient `q` and remainder `r` are supplied by the witness (see
+    /// `RsaWitness::divmod_witness`); both are range checked limb by limb so
+    /// the recomposition `q * m + r` cannot wrap around the field, and
+    /// `r < m` pins the remainder to the canonical one.
+    pub fn bigint_divmod(&mut self) -> usize {
         let start = self.current_row;
 
+        // q and r limbs are in [0, 2^64)
+        for _ in 0..(2 * RSA_LIMBS) {
+            self.range_check_limb();
+        }
+
+        // q * m
         self.bigint_mul();
 
-        for _ in 0..RSA_LIMBS {
+        // q * m + r, propagating carries through the upper half
+        for _ in 0..(2 * RSA_LIMBS) {
             self.limb_add_with_carry();
         }
 
-        for _ in 0..RSA_LIMBS {
+        // a == q * m + r, limb by limb
+        for _ in 0..(2 * RSA_LIMBS) {
             let wires = Wire::for_row(self.current_row);
             self.gates.push(CircuitGate::create_generic_gadget(
                 wires,
@@ -246,16 +303,23 @@ impl RsaGadget {
             self.current_row += 1;
         }
 
+        // r < m
         self.bigint_less_than();
 
         start
     }
 
+    /// Modular reduction of a double-width value.
+    pub fn bigint_mod(&mut self) -> usize {
+        self.bigint_divmod()
+    }
+
     /// Modular multiplication.
     pub fn bigint_mulmod(&mut self) -> usize {
         let start = self.current_row;
+        self.plan.mulmods.push(start);
         self.bigint_mul();
-        self.bigint_mod();
+        self.bigint_divmod();
         start
     }
 
@@ -268,7 +332,7 @@ impl RsaGadget {
     pub fn modexp_65537(&mut self) -> usize {
         let start = self.current_row;
 
-        for _ in 0..16 {
+        for _ in 0..SQUARINGS {
             self.bigint_sqrmod();
         }
 
@@ -278,74 +342,27 @@ impl RsaGadget {
     }
 
     /// Verify PKCS#1 v1.5 padding.
+    ///
+    /// One constraint `byte = expected` per checked byte, packed two per
+    /// row: byte `2k` is in column 0 of row `start + k` and byte `2k + 1` in
+    /// column 3.
     pub fn verify_pkcs1_padding(&mut self) -> usize {
         let start = self.current_row;
-
-        // Check first byte is 0x00
-        let wires = Wire::for_row(self.current_row);
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Pub,
-            None,
-        ));
-        self.current_row += 1;
-
-        // Check second byte is 0x01
-        let wires = Wire::for_row(self.current_row);
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Add {
-                left_coeff: Some(Fp::one()),
-                right_coeff: Some(Fp::zero()),
-                output_coeff: Some(Fp::zero()),
-            },
-            Some(GenericGateSpec::Const(-Fp::one())),
-        ));
-        self.current_row += 1;
-
-        // Check padding bytes are 0xFF
-        for _ in 0..8 {
-            let wires = Wire::for_row(self.current_row);
-            self.gates.push(CircuitGate::create_generic_gadget(
-                wires,
-                GenericGateSpec::Add {
-                    left_coeff: Some(Fp::one()),
-                    right_coeff: Some(Fp::zero()),
-                    output_coeff: Some(Fp::zero()),
-                },
-                Some(GenericGateSpec::Const(-Fp::from(0xFFu64))),
-            ));
-            self.current_row += 1;
-        }
-
-        // Check separator 0x00
-        let wires = Wire::for_row(self.current_row);
-        self.gates.push(CircuitGate::create_generic_gadget(
-            wires,
-            GenericGateSpec::Pub,
-            None,
-        ));
-        self.current_row += 1;
-
-        // SHA-256 DigestInfo
-        let digest_info: [u8; 19] = [
-            0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
-            0x01, 0x05, 0x00, 0x04, 0x20,
-        ];
-
-        for byte in digest_info {
-            let wires = Wire::for_row(self.current_row);
-            self.gates.push(CircuitGate::create_generic_gadget(
-                wires,
-                GenericGateSpec::Add {
-                    left_coeff: Some(Fp::one()),
-                    right_coeff: Some(Fp::zero()),
-                    output_coeff: Some(Fp::zero()),
-                },
-                Some(GenericGateSpec::Const(-Fp::from(byte as u64))),
-            ));
-            self.current_row += 1;
-        }
+        self.plan.padding = Some(start);
+
+        // 0x00 0x01, padding bytes 0xFF, the 0x00 separator, then DigestInfo
+        let expected = [0x00, 0x01]
+            .into_iter()
+            .chain([0xFF; PADDING_CHECKED])
+            .chain([0x00])
+            .chain(DIGEST_INFO);
+        let constraints: Vec<_> = expected
+            .map(|byte| generic_spec_coeffs(GenericGateSpec::Const(Fp::from(byte as u64))))
+            .collect();
+
+        let gates = pack_generic(self.current_row, &constraints);
+        self.current_row += gates.len();
+        self.gates.extend(gates);
 
         start
     }
@@ -353,6 +370,7 @@ impl RsaGadget {
     /// Compare two big integers for equality.
     pub fn bigint_equal(&mut self) -> usize {
         let start = self.current_row;
+        self.plan.equal = Some(start);
 
         for _ in 0..RSA_LIMBS {
             let wires = Wire::for_row(self.current_row);
@@ -385,25 +403,150 @@ impl RsaGadget {
     }
 }
 
-/// Witness data for RSA verification.
+impl Gadget for RsaGadget {
+    type Inputs = [[Fp; COLUMNS]];
+
+    fn build(&self, cs: &mut CircuitBuilder) -> usize {
+        cs.extend_gates(&self.gates, self.current_row - self.gates.len())
+    }
+
+    fn rows(&self) -> usize {
+        self.gates.len()
+    }
+
+    fn witness(&self, w: &mut WitnessBuilder, rows: &Self::Inputs) -> Result<()> {
+        fill_row_layout(w, self.rows(), rows)
+    }
+}
+
+/// Cell-level limb operations.
+impl RsaGadget {
+    /// Constrain a limb cell to be in range [0, 2^64).
+    pub fn range_check_limb_cells(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, limb: Cell) {
+        BooleanGadget::decompose_cells(cs, w, limb, 64);
+    }
+
+    /// Multiply two limbs: a * b = lo + 2^64 * hi, with both halves range checked.
+    pub fn limb_mul_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: Cell,
+        b: Cell,
+    ) -> (Cell, Cell) {
+        let (va, vb) = (w.get(a), w.get(b));
+        let product = RsaWitness::limb_value(va) as u128 * RsaWitness::limb_value(vb) as u128;
+
+        let mul = cs.generic(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(mul, [va, vb, va * vb]);
+        cs.copy(a, mul[0]);
+        cs.copy(b, mul[1]);
+
+        // product - lo - 2^64 * hi = 0
+        let split = cs.generic_coeffs([
+            Fp::one(),
+            -Fp::one(),
+            -Fp::from(1u64 << 32).square(),
+            Fp::zero(),
+            Fp::zero(),
+        ]);
+        w.set_cells(
+            split,
+            [
+                va * vb,
+                Fp::from(product as u64),
+                Fp::from((product >> 64) as u64),
+            ],
+        );
+        cs.copy(mul[2], split[0]);
+
+        Self::range_check_limb_cells(cs, w, split[1]);
+        Self::range_check_limb_cells(cs, w, split[2]);
+
+        (split[1], split[2])
+    }
+
+    /// Add two limbs and a carry bit: a + b + carry_in = sum + 2^64 * carry_out.
+    pub fn limb_add_with_carry_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: Cell,
+        b: Cell,
+        carry_in: Cell,
+    ) -> (Cell, Cell) {
+        let (va, vb, vc) = (w.get(a), w.get(b), w.get(carry_in));
+        let total = RsaWitness::limb_value(va) as u128
+            + RsaWitness::limb_value(vb) as u128
+            + RsaWitness::limb_value(vc) as u128;
+
+        let add = cs.generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(add, [va, vb, va + vb]);
+        cs.copy(a, add[0]);
+        cs.copy(b, add[1]);
+
+        let add_carry = cs.generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(add_carry, [va + vb, vc, va + vb + vc]);
+        cs.copy(add[2], add_carry[0]);
+        cs.copy(carry_in, add_carry[1]);
+
+        // total - sum - 2^64 * carry_out = 0
+        let split = cs.generic_coeffs([
+            Fp::one(),
+            -Fp::one(),
+            -Fp::from(1u64 << 32).square(),
+            Fp::zero(),
+            Fp::zero(),
+        ]);
+        w.set_cells(
+            split,
+            [
+                va + vb + vc,
+                Fp::from(total as u64),
+                Fp::from((total >> 64) as u64),
+            ],
+        );
+        cs.copy(add_carry[2], split[0]);
+
+        Self::range_check_limb_cells(cs, w, split[1]);
+        BooleanGadget::assert_boolean(cs, w, split[2]);
+
+        (split[1], split[2])
+    }
+}
+
+/// Witness data for RSA verification, wiped when dropped.
 pub struct RsaWitness {
     pub signature: [u64; RSA_LIMBS],
     pub modulus: [u64; RSA_LIMBS],
-    pub hash: [u8; 32],
+    pub hash: Bytes32,
     pub intermediates: Vec<[u64; RSA_LIMBS]>,
+    /// Quotients of each modular reduction, aligned with `intermediates[1..]`.
+    pub quotients: Vec<[u64; RSA_LIMBS]>,
 }
 
 impl RsaWitness {
-    pub fn from_bytes(signature: &[u8; 256], modulus: &[u8; 256], hash: &[u8; 32]) -> Self {
+    pub fn from_bytes(signature: &Bytes256, modulus: &Bytes256, hash: &Bytes32) -> Self {
         Self {
             signature: Self::bytes_to_limbs(signature),
             modulus: Self::bytes_to_limbs(modulus),
             hash: *hash,
             intermediates: Vec::new(),
+            quotients: Vec::new(),
         }
     }
 
-    fn bytes_to_limbs(bytes: &[u8; 256]) -> [u64; RSA_LIMBS] {
+    /// Little-endian 64-bit limbs of a big-endian 2048-bit integer.
+    pub fn bytes_to_limbs(bytes: &[u8; 256]) -> [u64; RSA_LIMBS] {
         let mut limbs = [0u64; RSA_LIMBS];
         for i in 0..RSA_LIMBS {
             let start = i * 8;
@@ -421,11 +564,11 @@ impl RsaWitness {
         limbs
     }
 
-    pub fn limbs_to_bytes(limbs: &[u64; RSA_LIMBS]) -> [u8; 256] {
-        let mut bytes = [0u8; 256];
+    pub fn limbs_to_bytes(limbs: &[u64; RSA_LIMBS]) -> Bytes256 {
+        let mut bytes = Bytes256::zero();
         for i in 0..RSA_LIMBS {
             let limb_bytes = limbs[RSA_LIMBS - 1 - i].to_be_bytes();
-            bytes[i * 8..(i + 1) * 8].copy_from_slice(&limb_bytes);
+            bytes.0[i * 8..(i + 1) * 8].copy_from_slice(&limb_bytes);
         }
         bytes
     }
@@ -433,8 +576,8 @@ impl RsaWitness {
     pub fn compute_modexp(&mut self) -> [u64; RSA_LIMBS] {
         use num_bigint::BigUint;
 
-        let sig = BigUint::from_bytes_be(&Self::limbs_to_bytes(&self.signature));
-        let n = BigUint::from_bytes_be(&Self::limbs_to_bytes(&self.modulus));
+        let sig = BigUint::from_bytes_be(Self::limbs_to_bytes(&self.signature).as_ref());
+        let n = BigUint::from_bytes_be(Self::limbs_to_bytes(&self.modulus).as_ref());
         let e = BigUint::from(RSA_EXPONENT);
 
         let result = sig.modpow(&e, &n);
@@ -452,26 +595,75 @@ impl RsaWitness {
     fn compute_intermediates(&mut self) {
         use num_bigint::BigUint;
 
-        let sig = BigUint::from_bytes_be(&Self::limbs_to_bytes(&self.signature));
-        let n = BigUint::from_bytes_be(&Self::limbs_to_bytes(&self.modulus));
+        let sig = BigUint::from_bytes_be(Self::limbs_to_bytes(&self.signature).as_ref());
+        let n = BigUint::from_bytes_be(Self::limbs_to_bytes(&self.modulus).as_ref());
 
         self.intermediates.clear();
+        self.quotients.clear();
 
         let mut current = sig.clone();
         self.intermediates
             .push(Self::biguint_to_limbs(&current, &n));
 
-        for _ in 0..16 {
-            current = (&current * &current) % &n;
+        for _ in 0..SQUARINGS {
+            let product = &current * &current;
+            self.quotients
+                .push(Self::biguint_to_limbs(&(&product / &n), &n));
+            current = product % &n;
             self.intermediates
                 .push(Self::biguint_to_limbs(&current, &n));
         }
 
-        current = (&current * &sig) % &n;
+        let product = &current * &sig;
+        self.quotients
+            .push(Self::biguint_to_limbs(&(&product / &n), &n));
+        current = product % &n;
         self.intermediates
             .push(Self::biguint_to_limbs(&current, &n));
     }
 
+    /// Quotient and remainder limbs for `a = q * m + r`, as consumed by
+    /// `RsaGadget::bigint_divmod`.
+    ///
+    /// `a` is given as little-endian limbs (up to 2 * RSA_LIMBS). Returns `None`
+    /// if the modulus is zero or the quotient does not fit in RSA_LIMBS limbs.
+    pub fn divmod_witness(
+        a: &[u64],
+        m: &[u64; RSA_LIMBS],
+    ) -> Option<([u64; RSA_LIMBS], [u64; RSA_LIMBS])> {
+        use num_bigint::BigUint;
+
+        let a = Self::limbs_to_biguint(a);
+        let m = BigUint::from_bytes_be(Self::limbs_to_bytes(m).as_ref());
+        if m.bits() == 0 {
+            return None;
+        }
+
+        let q = &a / &m;
+        let r = &a % &m;
+        if q.bits() > (64 * RSA_LIMBS) as u64 {
+            return None;
+        }
+
+        Some((
+            Self::biguint_to_limbs(&q, &m),
+            Self::biguint_to_limbs(&r, &m),
+        ))
+    }
+
+    /// Low 64 bits of a field element holding a limb.
+    pub fn limb_value(value: Fp) -> u64 {
+        use ark_ff::{BigInteger, PrimeField};
+        let bytes = value.into_bigint().to_bytes_le();
+        u64::from_le_bytes(std::array::from_fn(|i| bytes[i]))
+    }
+
+    /// Interpret little-endian 64-bit limbs as a big integer.
+    pub fn limbs_to_biguint(limbs: &[u64]) -> num_bigint::BigUint {
+        let bytes: Vec<u8> = limbs.iter().flat_map(|limb| limb.to_le_bytes()).collect();
+        num_bigint::BigUint::from_bytes_le(&bytes)
+    }
+
     fn biguint_to_limbs(
         value: &num_bigint::BigUint,
         _modulus: &num_bigint::BigUint,
@@ -513,11 +705,6 @@ impl RsaWitness {
             return false;
         }
 
-        let digest_info: [u8; 19] = [
-            0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02,
-            0x01, 0x05, 0x00, 0x04, 0x20,
-        ];
-
         let di_start = sep_idx + 1;
         let di_end = di_start + 19;
         let hash_start = di_end;
@@ -527,17 +714,339 @@ impl RsaWitness {
             return false;
         }
 
-        if decrypted_bytes[di_start..di_end] != digest_info {
+        if decrypted_bytes[di_start..di_end] != DIGEST_INFO {
             return false;
         }
 
-        decrypted_bytes[hash_start..hash_end] == self.hash
+        decrypted_bytes[hash_start..hash_end] == self.hash.0
     }
 }
 
+/// RsaGadget row layout.
+impl RsaWitness {
+    /// Write the witness of `RsaGadget::rsa_verify` into `layout`, at the
+    /// rows of `plan` (from `RsaGadget::row_plan`).
+    ///
+    /// Each modular multiplication gets its limb products, the carries of
+    /// summing them, and its quotient and remainder from
+    /// `compute_intermediates`; then the padding and comparison rows get
+    /// the bytes and limbs of the result. A signature that does not verify
+    /// fills rows that do not satisfy the gates.
+    pub fn fill_columns(&mut self, layout: &mut WitnessLayout, plan: &RsaRowPlan) -> Result<()> {
+        Self::check_plan(plan, layout)?;
+        for k in 0..=SQUARINGS {
+            self.fill_mulmod(layout, plan, k);
+        }
+        self.fill_result(layout, plan);
+        Ok(())
+    }
+
+    /// Fail unless `plan` is that of `rsa_verify` and fits `layout`.
+    fn check_plan(plan: &RsaRowPlan, layout: &WitnessLayout) -> Result<()> {
+        let (Some(padding), Some(equal)) = (plan.padding, plan.equal) else {
+            return Err(ProverError::InvalidInput(
+                "row plan has no padding or comparison rows".into(),
+            ));
+        };
+        if plan.mulmods.len() != SQUARINGS + 1
+            || !plan.mulmods.windows(2).all(|rows| rows[0] < rows[1])
+            || plan.mulmods.last() >= Some(&padding)
+            || padding >= equal
+        {
+            return Err(ProverError::InvalidInput(format!(
+                "row plan is not that of rsa_verify: {:?}",
+                plan
+            )));
+        }
+        if equal + RSA_LIMBS > layout.circuit_rows() {
+            return Err(ProverError::WitnessRowCount {
+                expected: equal + RSA_LIMBS,
+                actual: layout.circuit_rows(),
+            });
+        }
+        Ok(())
+    }
+
+    /// The `k`-th modular multiplication of a checked `plan`.
+    fn fill_mulmod(&mut self, layout: &mut WitnessLayout, plan: &RsaRowPlan, k: usize) {
+        if self.intermediates.len() != SQUARINGS + 2 {
+            self.compute_intermediates();
+        }
+        let y = if k < SQUARINGS {
+            &self.intermediates[k]
+        } else {
+            &self.signature
+        };
+        let mut rows = RowWriter {
+            layout,
+            row: plan.mulmods[k],
+        };
+        rows.bigint_mulmod(
+            &self.intermediates[k],
+            y,
+            &self.modulus,
+            &self.quotients[k],
+            &self.intermediates[k + 1],
+        );
+    }
+
+    /// The padding check and comparison of a checked `plan`.
+    fn fill_result(&mut self, layout: &mut WitnessLayout, plan: &RsaRowPlan) {
+        if self.intermediates.len() != SQUARINGS + 2 {
+            self.compute_intermediates();
+        }
+        let result = &self.intermediates[SQUARINGS + 1];
+        let mut rows = RowWriter {
+            layout,
+            row: plan.padding.expect("checked plan"),
+        };
+        rows.pkcs1_padding(&Self::limbs_to_bytes(result));
+        rows.row = plan.equal.expect("checked plan");
+        let expected = Self::bytes_to_limbs(&Self::encoded_message(&self.hash));
+        rows.bigint_equal(result, &expected);
+    }
+
+    /// The PKCS#1 v1.5 encoding of a SHA-256 `hash` a valid signature
+    /// decrypts to.
+    fn encoded_message(hash: &Bytes32) -> Bytes256 {
+        let mut em = Bytes256::zero();
+        let di_start = 256 - 32 - DIGEST_INFO.len();
+        em.0[1] = 0x01;
+        em.0[2..di_start - 1].fill(0xFF);
+        em.0[di_start..256 - 32].copy_from_slice(&DIGEST_INFO);
+        em.0[256 - 32..].copy_from_slice(&hash.0);
+        em
+    }
+}
+
+/// The witness of `RsaGadget::rsa_verify` as one step per modular
+/// multiplication plus one for the padding check and comparison, matching
+/// `RsaWitness::fill_columns`, for filling it in time slices with an
+/// `IncrementalWitness`.
+pub struct RsaSteps {
+    witness: RsaWitness,
+    plan: RsaRowPlan,
+}
+
+impl RsaSteps {
+    /// Steps filling `witness` at the rows of `plan`.
+    pub fn new(witness: RsaWitness, plan: RsaRowPlan) -> Self {
+        Self { witness, plan }
+    }
+}
+
+impl WitnessSteps for RsaSteps {
+    fn num_steps(&self) -> usize {
+        SQUARINGS + 2
+    }
+
+    fn fill_step(&mut self, layout: &mut WitnessLayout, step: usize) -> Result<()> {
+        RsaWitness::check_plan(&self.plan, layout)?;
+        if step <= SQUARINGS {
+            self.witness.fill_mulmod(layout, &self.plan, step);
+        } else {
+            self.witness.fill_result(layout, &self.plan);
+        }
+        Ok(())
+    }
+}
+
+/// Writes the rows of the `RsaGadget` blocks in the order the gadget lays
+/// them out, from `row` on.
+struct RowWriter<'a> {
+    layout: &'a mut WitnessLayout,
+    row: usize,
+}
+
+impl RowWriter<'_> {
+    /// Fill columns 0..3 of the next row.
+    fn next(&mut self, values: [Fp; 3]) {
+        for (col, value) in values.into_iter().enumerate() {
+            self.layout.set(Cell::new(self.row, col), value);
+        }
+        self.row += 1;
+    }
+
+    /// 64 bit rows, then 8 rows summing the bytes back to `x`.
+    fn range_check_limb(&mut self, x: u64) {
+        for i in 0..64 {
+            let bit = Fp::from((x >> i) & 1);
+            self.next([bit; 3]);
+        }
+        let mut acc = 0u64;
+        for (i, byte) in x.to_le_bytes().into_iter().enumerate() {
+            let term = (byte as u64) << (8 * i);
+            self.next([Fp::from(acc), Fp::from(term), Fp::from(acc + term)]);
+            acc += term;
+        }
+    }
+
+    /// `a * b = lo + 2^64 * hi`.
+    fn limb_mul(&mut self, a: u64, b: u64) -> (u64, u64) {
+        let product = a as u128 * b as u128;
+        let (lo, hi) = (product as u64, (product >> 64) as u64);
+        let value = Fp::from(product);
+        self.next([Fp::from(a), Fp::from(b), value]);
+        self.next([Fp::from(lo), Fp::from(hi), value]);
+        self.range_check_limb(lo);
+        self.range_check_limb(hi);
+        (lo, hi)
+    }
+
+    /// `a + b + carry = sum + 2^64 * carry_out`.
+    fn limb_add_with_carry(&mut self, a: u64, b: u64, carry: bool) -> (u64, bool) {
+        let total = a as u128 + b as u128 + carry as u128;
+        let (sum, carry_out) = (total as u64, total >> 64 == 1);
+        let (a, b, carry) = (Fp::from(a), Fp::from(b), Fp::from(carry));
+        self.next([a, b, a + b]);
+        self.next([a + b, carry, a + b + carry]);
+        self.next([Fp::from(sum), Fp::from(carry_out), Fp::from(total)]);
+        self.next([Fp::from(carry_out); 3]);
+        (sum, carry_out)
+    }
+
+    /// `x * y`, summing the rows of limb products `L_i = x_i * y` (low
+    /// halves, from limb `i`) and `H_i` (high halves, from limb `i + 1`) as
+    /// `L_0 + H_0 + L_1 + ... + H_31`.
+    ///
+    /// Each of the 63 additions covers the 32 limbs of its addend. The
+    /// carry out of an addend ending at limb `n` lands on limb `n + 1`,
+    /// which is still zero: the sum so far is below `2^(64 (n + 1))`.
+    fn bigint_mul(&mut self, x: &[u64; RSA_LIMBS], y: &[u64; RSA_LIMBS]) -> [u64; 2 * RSA_LIMBS] {
+        let mut lo = [[0u64; RSA_LIMBS]; RSA_LIMBS];
+        let mut hi = [[0u64; RSA_LIMBS]; RSA_LIMBS];
+        for i in 0..RSA_LIMBS {
+            for j in 0..RSA_LIMBS {
+                (lo[i][j], hi[i][j]) = self.limb_mul(x[i], y[j]);
+            }
+        }
+
+        let mut acc = [0u64; 2 * RSA_LIMBS];
+        acc[..RSA_LIMBS].copy_from_slice(&lo[0]);
+        let addends = (0..RSA_LIMBS).flat_map(|i| {
+            let high = (&hi[i], i + 1);
+            let low = (i + 1 < RSA_LIMBS).then(|| (&lo[i + 1], i + 1));
+            std::iter::once(high).chain(low)
+        });
+        for (addend, offset) in addends {
+            let mut carry = false;
+            for (j, &limb) in addend.iter().enumerate() {
+                (acc[offset + j], carry) = self.limb_add_with_carry(acc[offset + j], limb, carry);
+            }
+            if offset + RSA_LIMBS < acc.len() {
+                acc[offset + RSA_LIMBS] = carry as u64;
+            }
+        }
+        acc
+    }
+
+    /// `a - b` limb by limb, taking each limb's borrow; returns the final
+    /// borrow, i.e. whether `a < b`.
+    fn bigint_sub(&mut self, a: &[u64; RSA_LIMBS], b: &[u64; RSA_LIMBS]) -> bool {
+        let mut borrow = false;
+        for (&a, &b) in a.iter().zip(b) {
+            let (a_fp, b_fp, borrow_fp) = (Fp::from(a), Fp::from(b), Fp::from(borrow));
+            self.next([a_fp, b_fp, a_fp - b_fp]);
+            self.next([a_fp - b_fp, borrow_fp, a_fp - b_fp - borrow_fp]);
+            borrow = (a as u128) < b as u128 + borrow as u128;
+        }
+        borrow
+    }
+
+    fn bigint_less_than(&mut self, a: &[u64; RSA_LIMBS], b: &[u64; RSA_LIMBS]) {
+        let less = Fp::from(self.bigint_sub(a, b));
+        self.next([less; 3]);
+    }
+
+    /// `a = q * m + r` with `r < m`.
+    fn bigint_divmod(
+        &mut self,
+        a: &[u64; 2 * RSA_LIMBS],
+        m: &[u64; RSA_LIMBS],
+        q: &[u64; RSA_LIMBS],
+        r: &[u64; RSA_LIMBS],
+    ) {
+        for &limb in q.iter().chain(r) {
+            self.range_check_limb(limb);
+        }
+        let qm = self.bigint_mul(q, m);
+        let mut sum = [0u64; 2 * RSA_LIMBS];
+        let mut carry = false;
+        for k in 0..2 * RSA_LIMBS {
+            let r = r.get(k).copied().unwrap_or(0);
+            (sum[k], carry) = self.limb_add_with_carry(qm[k], r, carry);
+        }
+        for (&a, &sum) in a.iter().zip(&sum) {
+            self.next([Fp::from(a), Fp::from(sum), Fp::zero()]);
+        }
+        self.bigint_less_than(r, m);
+    }
+
+    fn bigint_mulmod(
+        &mut self,
+        x: &[u64; RSA_LIMBS],
+        y: &[u64; RSA_LIMBS],
+        m: &[u64; RSA_LIMBS],
+        q: &[u64; RSA_LIMBS],
+        r: &[u64; RSA_LIMBS],
+    ) {
+        let product = self.bigint_mul(x, y);
+        self.bigint_divmod(&product, m, q, r);
+    }
+
+    /// The bytes `verify_pkcs1_padding` compares with its constants, two
+    /// per row: the header, the first padding bytes, the separator and the
+    /// DigestInfo after it.
+    fn pkcs1_padding(&mut self, em: &Bytes256) {
+        let separator = (2..256)
+            .find(|&i| em[i] == 0)
+            .unwrap_or(2 + PADDING_CHECKED);
+        let checked: Vec<u8> = em[..2 + PADDING_CHECKED]
+            .iter()
+            .chain(
+                em.get(separator..separator + 1 + DIGEST_INFO.len())
+                    .unwrap_or(&[]),
+            )
+            .copied()
+            .collect();
+        for pair in checked.chunks(2) {
+            for (half, &byte) in pair.iter().enumerate() {
+                self.layout
+                    .set(Cell::new(self.row, 3 * half), Fp::from(byte as u64));
+            }
+            self.row += 1;
+        }
+    }
+
+    fn bigint_equal(&mut self, a: &[u64; RSA_LIMBS], b: &[u64; RSA_LIMBS]) {
+        for (&a, &b) in a.iter().zip(b) {
+            self.next([Fp::from(a), Fp::from(b), Fp::zero()]);
+        }
+    }
+}
+
+impl Zeroize for RsaWitness {
+    fn zeroize(&mut self) {
+        self.signature.zeroize();
+        self.modulus.zeroize();
+        self.hash.0.zeroize();
+        self.intermediates.zeroize();
+        self.quotients.zeroize();
+    }
+}
+
+impl Drop for RsaWitness {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for RsaWitness {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::incremental::IncrementalWitness;
 
     #[test]
     fn test_bytes_to_limbs_roundtrip() {
@@ -548,16 +1057,216 @@ mod tests {
         let limbs = RsaWitness::bytes_to_limbs(&bytes);
         let recovered = RsaWitness::limbs_to_bytes(&limbs);
 
-        assert_eq!(bytes, recovered);
+        assert_eq!(recovered, bytes);
+    }
+
+    #[test]
+    fn test_divmod_witness() {
+        let mut m = [0u64; RSA_LIMBS];
+        m[0] = 0xFFFF_FFFF_0000_0001;
+        m[3] = 0x1234;
+        let a = [u64::MAX; 2 * RSA_LIMBS];
+
+        // Quotient of a full-width value by a small modulus overflows
+        assert!(RsaWitness::divmod_witness(&a, &m).is_none());
+
+        let mut a = [0u64; 2 * RSA_LIMBS];
+        a[..RSA_LIMBS].copy_from_slice(&[7u64; RSA_LIMBS]);
+        a[RSA_LIMBS] = 5;
+        let (q, r) = RsaWitness::divmod_witness(&a, &m).unwrap();
+
+        let a_big = RsaWitness::limbs_to_biguint(&a);
+        let m_big = RsaWitness::limbs_to_biguint(&m);
+        let q_big = RsaWitness::limbs_to_biguint(&q);
+        let r_big = RsaWitness::limbs_to_biguint(&r);
+        assert_eq!(q_big * &m_big + &r_big, a_big);
+        assert!(r_big < m_big);
+
+        assert!(RsaWitness::divmod_witness(&a, &[0; RSA_LIMBS]).is_none());
+    }
+
+    #[test]
+    fn test_intermediate_quotients() {
+        let mut signature = [0u8; 256];
+        signature[255] = 3;
+        let mut modulus = [0u8; 256];
+        modulus[0] = 0xC1;
+        modulus[255] = 0x01;
+
+        let mut witness =
+            RsaWitness::from_bytes(&signature.into(), &modulus.into(), &Bytes32::zero());
+        witness.compute_modexp();
+
+        assert_eq!(witness.intermediates.len(), 18);
+        assert_eq!(witness.quotients.len(), witness.intermediates.len() - 1);
+    }
+
+    #[test]
+    fn test_limb_cells() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let [a, b, c] = cs.generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::zero()),
+            right_coeff: Some(Fp::zero()),
+            output_coeff: Some(Fp::zero()),
+        });
+        w.set(a, Fp::from(u64::MAX));
+        w.set(b, Fp::from(3u64));
+        w.set(c, Fp::one());
+
+        let (sum, carry) = RsaGadget::limb_add_with_carry_cells(&mut cs, &mut w, a, b, c);
+        assert_eq!(w.get(sum), Fp::from(3u64));
+        assert_eq!(w.get(carry), Fp::one());
+
+        let (lo, hi) = RsaGadget::limb_mul_cells(&mut cs, &mut w, a, b);
+        let product = u64::MAX as u128 * 3;
+        assert_eq!(w.get(lo), Fp::from(product as u64));
+        assert_eq!(w.get(hi), Fp::from((product >> 64) as u64));
+
+        assert!(cs.check_witness(&w).is_ok());
+    }
+
+    /// Fill the rows of the block `lay_out` adds with `write`, and check
+    /// them against the block's gates.
+    fn check_block(
+        lay_out: impl FnOnce(&mut RsaGadget),
+        write: impl FnOnce(&mut RowWriter),
+    ) -> Result<()> {
+        let mut gadget = RsaGadget::new(0);
+        lay_out(&mut gadget);
+        let (gates, rows) = gadget.build();
+        let mut layout = WitnessLayout::for_gates(&gates);
+        let mut writer = RowWriter {
+            layout: &mut layout,
+            row: 0,
+        };
+        write(&mut writer);
+        assert_eq!(writer.row, rows);
+        crate::diagnostics::check_witness(&gates, &layout.into_columns(), 0, false)
+    }
+
+    #[test]
+    fn test_fill_limb_rows() {
+        check_block(
+            |g| {
+                g.limb_mul();
+                g.limb_add_with_carry();
+                g.limb_add_with_carry();
+            },
+            |w| {
+                let (lo, hi) = w.limb_mul(u64::MAX, u64::MAX - 1);
+                let product = u64::MAX as u128 * (u64::MAX - 1) as u128;
+                assert_eq!((lo, hi), (product as u64, (product >> 64) as u64));
+                assert_eq!(w.limb_add_with_carry(u64::MAX, 1, true), (1, true));
+                assert_eq!(w.limb_add_with_carry(5, 6, false), (11, false));
+            },
+        )
+        .unwrap();
+
+        let padding = RsaWitness::encoded_message(&Bytes32([7; 32]));
+        let mut limbs = RsaWitness::bytes_to_limbs(&padding);
+        check_block(
+            |g| {
+                g.verify_pkcs1_padding();
+                g.bigint_equal();
+            },
+            |w| {
+                w.pkcs1_padding(&padding);
+                w.bigint_equal(&limbs, &limbs);
+            },
+        )
+        .unwrap();
+        let expected = limbs;
+        limbs[0] ^= 1;
+        assert!(check_block(
+            |g| {
+                g.bigint_equal();
+            },
+            |w| w.bigint_equal(&limbs, &expected),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_fill_divmod_rows() {
+        let mut a = [0u64; 2 * RSA_LIMBS];
+        a.iter_mut()
+            .enumerate()
+            .for_each(|(i, limb)| *limb = u64::MAX - i as u64);
+        let mut m = [u64::MAX; RSA_LIMBS];
+        m[0] = 0xFFFF_FFFF_0000_0001;
+        let (q, r) = RsaWitness::divmod_witness(&a, &m).unwrap();
+
+        // The comparison rows hold a and q * m + r, normalized
+        check_block(
+            |g| {
+                g.bigint_divmod();
+            },
+            |w| w.bigint_divmod(&a, &m, &q, &r),
+        )
+        .unwrap();
+
+        let mut wrong = r;
+        wrong[0] += 1;
+        assert!(check_block(
+            |g| {
+                g.bigint_divmod();
+            },
+            |w| w.bigint_divmod(&a, &m, &q, &wrong),
+        )
+        .is_err());
     }
 
     #[test]
     fn test_gadget_construction() {
         let mut gadget = RsaGadget::new(0);
         gadget.rsa_verify();
+        let plan = gadget.row_plan().clone();
         let (gates, rows) = gadget.build();
 
         assert!(!gates.is_empty());
         assert!(rows > 0);
+        assert_eq!(plan.mulmods.len(), SQUARINGS + 1);
+        assert_eq!(plan.mulmods[0], 0);
+        assert_eq!(plan.equal, Some(rows - RSA_LIMBS));
+        assert_eq!(plan.padding, Some(rows - RSA_LIMBS - 15));
+
+        // The plan must fit the layout
+        let mut witness =
+            RsaWitness::from_bytes(&Bytes256::zero(), &Bytes256::zero(), &Bytes32::zero());
+        let mut layout = WitnessLayout::for_gates(&gates[..2]);
+        assert!(matches!(
+            witness.fill_columns(&mut layout, &plan),
+            Err(ProverError::WitnessRowCount { .. })
+        ));
+        assert!(witness
+            .fill_columns(&mut layout, &RsaRowPlan::default())
+            .is_err());
+
+        // 30 padding bytes, two per row
+        let mut padding = RsaGadget::new(0);
+        assert_eq!(padding.row_plan(), &RsaRowPlan::default());
+        assert_eq!(padding.verify_pkcs1_padding(), 0);
+        assert_eq!(padding.current_row(), 15);
+    }
+
+    #[test]
+    fn test_steps() {
+        let mut gadget = RsaGadget::new(0);
+        gadget.rsa_verify();
+        let plan = gadget.row_plan().clone();
+        let (gates, _) = gadget.build();
+        let witness =
+            RsaWitness::from_bytes(&Bytes256::zero(), &Bytes256::zero(), &Bytes32::zero());
+
+        // As with `fill_columns`, the plan must fit the layout
+        let steps = RsaSteps::new(witness, plan);
+        assert_eq!(steps.num_steps(), SQUARINGS + 2);
+        let mut incremental = IncrementalWitness::new(steps, WitnessLayout::for_gates(&gates[..2]));
+        assert!(matches!(
+            incremental.step(),
+            Err(ProverError::WitnessRowCount { .. })
+        ));
+        assert_eq!(incremental.progress().done, 0);
     }
 }
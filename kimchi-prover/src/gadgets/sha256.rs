@@ -2,13 +2,23 @@
 //!
 //! Implements SHA-256 as arithmetic constraints over the Pallas scalar field.
 
-use ark_ff::{One, Zero};
+use ark_ff::{BigInteger, One, PrimeField, Zero};
 use kimchi::circuits::gate::CircuitGate;
 use kimchi::circuits::polynomials::generic::GenericGateSpec;
 use kimchi::circuits::wires::Wire;
 use mina_curves::pasta::Fp;
 
-use super::boolean::BooleanWitness;
+use super::addition::{MultiAddGadget, MultiAddWitness};
+use super::boolean::{BooleanGadget, BooleanWitness};
+use super::range::RangeCheckGadget;
+use crate::builder::{
+    fill_row_layout, Cell, CircuitBuilder, Gadget, WitnessBuilder, WitnessLayout,
+};
+use crate::error::{ProverError, Result};
+use crate::incremental::WitnessSteps;
+use crate::prover::COLUMNS;
+use crate::types::{BitVec, Bytes32};
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 /// SHA-256 initial hash values (H0-H7).
 pub const H_INIT: [u32; 8] = [
@@ -27,6 +37,12 @@ pub const K: [u32; 64] = [
     0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
 ];
 
+/// SHA-256 block size in bytes.
+pub const BLOCK_BYTES: usize = 64;
+
+/// Bytes taken by the mandatory padding: the 0x80 marker and the 64-bit length.
+pub const PADDING_OVERHEAD: usize = 9;
+
 /// Gadget builder for SHA-256 circuits.
 pub struct Sha256Gadget {
     gates: Vec<CircuitGate<Fp>>,
@@ -45,40 +61,12 @@ impl Sha256Gadget {
         self.current_row
     }
 
-    /// Add gates for bit decomposition of a 32-bit word.
+    /// Add gates constraining a word to 32 bits, decomposed into limbs over
+    /// one `RangeCheck0` row (see `gadgets::range`).
+    ///
+    /// Returns the row holding the word in column 0.
     pub fn decompose_word(&mut self) -> usize {
-        let start = self.current_row;
-
-        // Boolean constraints for each bit
-        for _ in 0..32 {
-            let wires = Wire::for_row(self.current_row);
-            self.gates.push(CircuitGate::create_generic_gadget(
-                wires,
-                GenericGateSpec::Mul {
-                    mul_coeff: Some(Fp::one()),
-                    output_coeff: Some(-Fp::one()),
-                },
-                None,
-            ));
-            self.current_row += 1;
-        }
-
-        // Linear combination gates
-        for _ in 0..7 {
-            let wires = Wire::for_row(self.current_row);
-            self.gates.push(CircuitGate::create_generic_gadget(
-                wires,
-                GenericGateSpec::Add {
-                    left_coeff: Some(Fp::one()),
-                    right_coeff: Some(Fp::one()),
-                    output_coeff: Some(-Fp::one()),
-                },
-                None,
-            ));
-            self.current_row += 1;
-        }
-
-        start
+        self.range_check(32)
     }
 
     /// Add constraint for modular addition: (a + b) mod 2^32 = result
@@ -256,19 +244,16 @@ impl Sha256Gadget {
     pub fn compression_round(&mut self) -> usize {
         let start = self.current_row;
 
+        // T1 = h + S1 + ch + K + W
         self.sigma1();
         self.ch();
-        self.add_mod32();
-        self.add_mod32();
-        self.add_mod32();
-        self.add_mod32();
+        self.add_many_mod32(5);
 
+        // e = d + T1, a = T1 + S0 + maj
         self.sigma0();
         self.maj();
-        self.add_mod32();
-
-        self.add_mod32();
-        self.add_mod32();
+        self.add_many_mod32(2);
+        self.add_many_mod32(3);
 
         start
     }
@@ -280,9 +265,8 @@ impl Sha256Gadget {
         for _ in 16..64 {
             self.small_sigma1();
             self.small_sigma0();
-            self.add_mod32();
-            self.add_mod32();
-            self.add_mod32();
+            // W = s1 + W[i-7] + s0 + W[i-16]
+            self.add_many_mod32(4);
         }
 
         start
@@ -318,26 +302,481 @@ impl Sha256Gadget {
         start
     }
 
+    /// Compress `num_blocks` blocks starting from a caller-provided chaining value.
+    ///
+    /// The eight input state words are witness values (typically wired to public
+    /// inputs) and are decomposed so they are constrained to 32 bits. The state
+    /// after the last block is left in the final `add_mod32` rows, where it can
+    /// be exposed as public output or fed into the next circuit in a chain.
+    pub fn compress_from_state(&mut self, num_blocks: usize) -> usize {
+        let start = self.current_row;
+
+        for _ in 0..8 {
+            self.decompose_word();
+        }
+
+        for _ in 0..num_blocks {
+            self.sha256_block();
+        }
+
+        start
+    }
+
+    /// Hash the tail of a message whose first `prefix_bytes` were compressed elsewhere.
+    ///
+    /// `prefix_bytes` must be a multiple of the block size; its midstate is the
+    /// caller-provided chaining value and the padding of the suffix encodes the
+    /// total message length `prefix_bytes + suffix_bytes`.
+    pub fn hash_suffix_from_state(&mut self, prefix_bytes: usize, suffix_bytes: usize) -> usize {
+        assert!(
+            prefix_bytes.is_multiple_of(BLOCK_BYTES),
+            "prefix length {} is not a multiple of {} bytes",
+            prefix_bytes,
+            BLOCK_BYTES
+        );
+
+        let total_blocks = Sha256Witness::num_blocks(prefix_bytes + suffix_bytes);
+        self.compress_from_state(total_blocks - prefix_bytes / BLOCK_BYTES)
+    }
+
+    /// Sum of `num_operands` words modulo 2^32 in one `MultiAddGadget` block.
+    fn add_many_mod32(&mut self, num_operands: usize) {
+        let mut adder = MultiAddGadget::new(self.current_row);
+        adder.add_mod32(num_operands);
+        let (gates, next_row) = adder.build();
+        self.gates.extend(gates);
+        self.current_row = next_row;
+    }
+
+    fn push_generic(&mut self, spec: GenericGateSpec<Fp>) {
+        let wires = Wire::for_row(self.current_row);
+        self.gates
+            .push(CircuitGate::create_generic_gadget(wires, spec, None));
+        self.current_row += 1;
+    }
+
+    fn boolean_gate(&mut self) {
+        self.push_generic(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    fn sum_gate(&mut self, left: Fp, right: Fp) {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(left),
+            right_coeff: Some(right),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Range check a value to `num_bits` bits in one `RangeCheckGadget` block.
+    fn range_check(&mut self, num_bits: usize) -> usize {
+        let mut range = RangeCheckGadget::new(self.current_row);
+        let row = range.range_check(num_bits);
+        let (gates, next_row) = range.build();
+        self.gates.extend(gates);
+        self.current_row = next_row;
+        row
+    }
+
+    /// In-circuit padding for a message whose length is a witness value.
+    ///
+    /// The message occupies a buffer of `max_blocks * 64` bytes. For each byte
+    /// position `i` a flag `f_i = [i < len]` is constrained to be boolean and
+    /// non-increasing, so `len = sum(f_i)` and `e_i = f_i - f_{i+1}` marks the
+    /// single position of the 0x80 byte. A one-hot block selector picks the
+    /// final block `n`, tied to the length by range checking
+    /// `64 * n - len - 9` to 6 bits, and the last 8 bytes of that block are
+    /// forced to the big-endian bit length. Every other byte is
+    /// `f_i * msg_i + 0x80 * e_i`, which zero-fills the rest of the buffer.
+    pub fn pad_message_var(&mut self, max_blocks: usize) -> usize {
+        assert!(max_blocks > 0, "at least one block is required");
+
+        let start = self.current_row;
+        let max_bytes = max_blocks * BLOCK_BYTES;
+
+        for _ in 0..max_bytes {
+            // Message byte is a byte
+            self.range_check(8);
+
+            // f_i is boolean
+            self.boolean_gate();
+
+            // f_{i+1} * (1 - f_i) = 0 (flags never switch back on)
+            self.push_generic(GenericGateSpec::Mul {
+                mul_coeff: Some(-Fp::one()),
+                output_coeff: Some(Fp::zero()),
+            });
+
+            // e_i = f_i - f_{i+1}
+            self.sum_gate(Fp::one(), -Fp::one());
+
+            // padded_i = f_i * msg_i + 0x80 * e_i
+            self.push_generic(GenericGateSpec::Mul {
+                mul_coeff: Some(Fp::one()),
+                output_coeff: Some(-Fp::one()),
+            });
+            self.sum_gate(Fp::one(), Fp::from(0x80u64));
+
+            // len accumulation
+            self.sum_gate(Fp::one(), Fp::one());
+        }
+
+        // Block selector is one-hot
+        for _ in 0..max_blocks {
+            self.boolean_gate();
+        }
+        for b in 0..max_blocks {
+            self.sum_gate(Fp::one(), Fp::one());
+            self.sum_gate(Fp::one(), Fp::from(b as u64 + 1));
+        }
+        self.push_generic(GenericGateSpec::Const(Fp::one()));
+
+        // 0 <= 64 * n - len - 9 < 64
+        self.sum_gate(Fp::from(BLOCK_BYTES as u64), -Fp::one());
+        self.push_generic(GenericGateSpec::Plus(-Fp::from(PADDING_OVERHEAD as u64)));
+        self.range_check(6);
+
+        // Bit length = 8 * len as 8 big-endian bytes
+        for _ in 0..8 {
+            self.range_check(8);
+            self.sum_gate(Fp::from(256u64), Fp::one());
+        }
+        self.sum_gate(Fp::from(8u64), -Fp::one());
+
+        // Length field of the selected block:
+        // padded = normal + sel_b * (len_byte - normal)
+        for _ in 0..max_blocks {
+            for _ in 0..8 {
+                self.sum_gate(Fp::one(), -Fp::one());
+                self.push_generic(GenericGateSpec::Mul {
+                    mul_coeff: Some(Fp::one()),
+                    output_coeff: Some(-Fp::one()),
+                });
+                self.sum_gate(Fp::one(), Fp::one());
+            }
+        }
+
+        // Pack padded bytes into big-endian words: w = 256 * acc + b
+        for _ in 0..(max_bytes / 4) {
+            for _ in 0..4 {
+                self.sum_gate(Fp::from(256u64), Fp::one());
+            }
+        }
+
+        start
+    }
+
+    /// Hash a message of witness-determined length up to `max_blocks` blocks.
+    ///
+    /// Every block is compressed, and the digest is the chaining state after
+    /// the block chosen by the padding selector: `sum_b sel_b * H_b`. One
+    /// circuit therefore covers all messages of up to
+    /// `Sha256Witness::max_message_len(max_blocks)` bytes.
+    pub fn hash_message_var(&mut self, max_blocks: usize) -> usize {
+        let start = self.current_row;
+
+        self.pad_message_var(max_blocks);
+
+        for _ in 0..max_blocks {
+            self.sha256_block();
+        }
+
+        // Select the output state: 8 words, each a sum over the blocks
+        for _ in 0..8 {
+            for _ in 0..max_blocks {
+                self.push_generic(GenericGateSpec::Mul {
+                    mul_coeff: Some(Fp::one()),
+                    output_coeff: Some(-Fp::one()),
+                });
+                self.sum_gate(Fp::one(), Fp::one());
+            }
+        }
+
+        start
+    }
+
     pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
         (self.gates, self.current_row)
     }
 }
 
+impl Gadget for Sha256Gadget {
+    type Inputs = [[Fp; COLUMNS]];
+
+    fn build(&self, cs: &mut CircuitBuilder) -> usize {
+        cs.extend_gates(&self.gates, self.current_row - self.gates.len())
+    }
+
+    fn rows(&self) -> usize {
+        self.gates.len()
+    }
+
+    fn witness(&self, w: &mut WitnessBuilder, rows: &Self::Inputs) -> Result<()> {
+        fill_row_layout(w, self.rows(), rows)
+    }
+}
+
+/// Cell-level word operations.
+impl Sha256Gadget {
+    /// Modular addition: c = (a + b) mod 2^32.
+    ///
+    /// Constrains `a + b = c + 2^32 * carry` with a boolean carry and a 32-bit `c`.
+    pub fn add_mod32_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: Cell,
+        b: Cell,
+    ) -> Cell {
+        let modulus = Fp::from(1u64 << 32);
+        let (va, vb) = (w.get(a), w.get(b));
+        let sum = Self::word_value(va) + Self::word_value(vb);
+
+        let add = cs.generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(add, [va, vb, va + vb]);
+        cs.copy(a, add[0]);
+        cs.copy(b, add[1]);
+
+        // sum - c - 2^32 * carry = 0
+        let reduce = cs.generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(-modulus),
+        });
+        w.set_cells(
+            reduce,
+            [va + vb, Fp::from(sum & 0xFFFF_FFFF), Fp::from(sum >> 32)],
+        );
+        cs.copy(add[2], reduce[0]);
+
+        BooleanGadget::assert_boolean(cs, w, reduce[2]);
+        BooleanGadget::decompose_cells(cs, w, reduce[1], 32);
+
+        reduce[1]
+    }
+
+    /// Low 64 bits of a field element holding a word.
+    fn word_value(value: Fp) -> u64 {
+        let bytes = value.into_bigint().to_bytes_le();
+        u64::from_le_bytes(std::array::from_fn(|i| bytes[i]))
+    }
+}
+
+/// A 32-bit word as bit cells, least significant first.
+type WordBits = [Cell; 32];
+
+/// Cell-level hashing over bit cells.
+///
+/// Messages and digests are held byte by byte, each byte least significant
+/// bit first, so cell `8 * i + j` is bit `j` of byte `i`. Words are bits of
+/// the big-endian words SHA-256 reads. Every constraint is packed two per
+/// row; a block costs about 35,000 rows.
+impl Sha256Gadget {
+    /// SHA-256 of the message in `bits`, matching `Sha256Witness::compute`.
+    ///
+    /// The message bits must already be constrained to be boolean; the
+    /// digest bits are. Padding is laid out as constant cells.
+    pub fn hash_bits_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        bits: &[Cell],
+    ) -> [Cell; 256] {
+        assert!(bits.len().is_multiple_of(8), "message must be whole bytes");
+        let len = bits.len() / 8;
+        let bit = [
+            BooleanGadget::constant_bit(cs, w, false),
+            BooleanGadget::constant_bit(cs, w, true),
+        ];
+
+        let mut message = bits.to_vec();
+        for byte in &Sha256Witness::pad_message(&vec![0; len])[len..] {
+            message.extend((0..8).map(|j| bit[(byte >> j & 1) as usize]));
+        }
+
+        let mut state: [WordBits; 8] =
+            H_INIT.map(|h| std::array::from_fn(|k| bit[(h >> k & 1) as usize]));
+        for block in message.chunks(BLOCK_BYTES * 8) {
+            let words: [WordBits; 16] = std::array::from_fn(|i| Self::block_word(block, i));
+            state = Self::compress_bits_cells(cs, w, &state, &words);
+        }
+
+        std::array::from_fn(|k| {
+            let byte = k / 8;
+            state[byte / 4][8 * (3 - byte % 4) + k % 8]
+        })
+    }
+
+    /// One compression of `block` into `state`.
+    pub fn compress_bits_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        state: &[WordBits; 8],
+        block: &[WordBits; 16],
+    ) -> [WordBits; 8] {
+        let mut schedule = block.to_vec();
+        for t in 16..64 {
+            let s0 = Self::sigma_bits(cs, w, &schedule[t - 15], [7, 18, 3], true);
+            let s1 = Self::sigma_bits(cs, w, &schedule[t - 2], [17, 19, 10], true);
+            let next = Self::add_bits(cs, w, &[&s1, &schedule[t - 7], &s0, &schedule[t - 16]], 0);
+            schedule.push(next);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+        for (t, word) in schedule.iter().enumerate() {
+            let s1 = Self::sigma_bits(cs, w, &e, [6, 11, 25], false);
+            let ch = Self::ch_bits(cs, w, &e, &f, &g);
+            let s0 = Self::sigma_bits(cs, w, &a, [2, 13, 22], false);
+            let maj = Self::maj_bits(cs, w, &a, &b, &c);
+            let new_e = Self::add_bits(cs, w, &[&d, &h, &s1, &ch, word], K[t]);
+            let new_a = Self::add_bits(cs, w, &[&h, &s1, &ch, word, &s0, &maj], K[t]);
+            (h, g, f, e, d, c, b, a) = (g, f, e, new_e, c, b, a, new_a);
+        }
+
+        let working = [a, b, c, d, e, f, g, h];
+        std::array::from_fn(|i| Self::add_bits(cs, w, &[&state[i], &working[i]], 0))
+    }
+
+    /// Word `i` of a 512-bit block held byte by byte.
+    fn block_word(block: &[Cell], i: usize) -> WordBits {
+        std::array::from_fn(|k| block[8 * (4 * i + 3 - k / 8) + k % 8])
+    }
+
+    /// `rotr(x, r0) ^ rotr(x, r1) ^ rotr(x, r2)`, or `shr(x, r2)` for the
+    /// last term if `shift` is set.
+    fn sigma_bits(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        x: &WordBits,
+        [r0, r1, r2]: [usize; 3],
+        shift: bool,
+    ) -> WordBits {
+        std::array::from_fn(|i| {
+            let u = BooleanGadget::xor_cells(cs, w, x[(i + r0) % 32], x[(i + r1) % 32]);
+            if shift && i + r2 >= 32 {
+                u
+            } else {
+                BooleanGadget::xor_cells(cs, w, u, x[(i + r2) % 32])
+            }
+        })
+    }
+
+    /// `ch(e, f, g) = g + e * (f - g)`, three constraints per bit.
+    fn ch_bits(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        e: &WordBits,
+        f: &WordBits,
+        g: &WordBits,
+    ) -> WordBits {
+        let sub = [Fp::one(), -Fp::one(), -Fp::one(), Fp::zero(), Fp::zero()];
+        let add = [Fp::one(), Fp::one(), -Fp::one(), Fp::zero(), Fp::zero()];
+        std::array::from_fn(|i| {
+            let (ve, vf, vg) = (w.get(e[i]), w.get(f[i]), w.get(g[i]));
+
+            let d = cs.generic_coeffs_packed(sub);
+            w.set_cells(d, [vf, vg, vf - vg]);
+            cs.copy(f[i], d[0]);
+            cs.copy(g[i], d[1]);
+
+            let t = cs.generic_packed(GenericGateSpec::Mul {
+                mul_coeff: Some(Fp::one()),
+                output_coeff: Some(-Fp::one()),
+            });
+            w.set_cells(t, [ve, vf - vg, ve * (vf - vg)]);
+            cs.copy(e[i], t[0]);
+            cs.copy(d[2], t[1]);
+
+            let out = cs.generic_coeffs_packed(add);
+            w.set_cells(out, [vg, ve * (vf - vg), vg + ve * (vf - vg)]);
+            cs.copy(g[i], out[0]);
+            cs.copy(t[2], out[1]);
+            out[2]
+        })
+    }
+
+    /// `maj(a, b, c) = a * b + c * (a ^ b)`, four constraints per bit.
+    fn maj_bits(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: &WordBits,
+        b: &WordBits,
+        c: &WordBits,
+    ) -> WordBits {
+        let add = [Fp::one(), Fp::one(), -Fp::one(), Fp::zero(), Fp::zero()];
+        std::array::from_fn(|i| {
+            let x = BooleanGadget::xor_cells(cs, w, a[i], b[i]);
+            let y = BooleanGadget::and_cells(cs, w, c[i], x);
+            let p = BooleanGadget::and_cells(cs, w, a[i], b[i]);
+
+            let out = cs.generic_coeffs_packed(add);
+            let (vp, vy) = (w.get(p), w.get(y));
+            w.set_cells(out, [vp, vy, vp + vy]);
+            cs.copy(p, out[0]);
+            cs.copy(y, out[1]);
+            out[2]
+        })
+    }
+
+    /// `(constant + sum(words)) mod 2^32`.
+    ///
+    /// Accumulates every bit with its weight, one constraint each, then
+    /// decomposes the sum with its carry bits and keeps the low 32.
+    fn add_bits(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        words: &[&WordBits],
+        constant: u32,
+    ) -> WordBits {
+        let mut acc = cs.generic_packed(GenericGateSpec::Const(Fp::from(constant)))[0];
+        w.set(acc, Fp::from(constant));
+        for word in words {
+            for (i, bit) in word.iter().enumerate() {
+                let weight = Fp::from(1u64 << i);
+                let cells = cs.generic_coeffs_packed([
+                    Fp::one(),
+                    weight,
+                    -Fp::one(),
+                    Fp::zero(),
+                    Fp::zero(),
+                ]);
+                let (vacc, vbit) = (w.get(acc), w.get(*bit));
+                w.set_cells(cells, [vacc, vbit, vacc + weight * vbit]);
+                cs.copy(acc, cells[0]);
+                cs.copy(*bit, cells[1]);
+                acc = cells[2];
+            }
+        }
+
+        let max = words.len() as u64 * u32::MAX as u64 + constant as u64;
+        let num_bits = (u64::BITS - max.leading_zeros()) as usize;
+        let bits = BooleanGadget::decompose_cells(cs, w, acc, num_bits.max(32));
+        std::array::from_fn(|i| bits[i])
+    }
+}
+
 /// Witness generator for SHA-256.
+///
+/// The round state and message schedule of the last block are wiped when
+/// it is dropped.
 pub struct Sha256Witness {
-    state: [[Fp; 32]; 8],
-    schedule: [[Fp; 32]; 64],
+    state: [BitVec; 8],
+    schedule: [BitVec; 64],
 }
 
 impl Sha256Witness {
     pub fn new() -> Self {
         Self {
-            state: [[Fp::zero(); 32]; 8],
-            schedule: [[Fp::zero(); 32]; 64],
+            state: std::array::from_fn(|_| BitVec::zeros(32)),
+            schedule: std::array::from_fn(|_| BitVec::zeros(32)),
         }
     }
 
-    pub fn compute(&mut self, message: &[u8]) -> [u8; 32] {
+    pub fn compute(&mut self, message: &[u8]) -> Bytes32 {
         let padded = Self::pad_message(message);
         let mut h: [u32; 8] = H_INIT;
 
@@ -345,11 +784,7 @@ impl Sha256Witness {
             h = self.process_block(block, h);
         }
 
-        let mut result = [0u8; 32];
-        for (i, &word) in h.iter().enumerate() {
-            result[i * 4..(i + 1) * 4].copy_from_slice(&word.to_be_bytes());
-        }
-        result
+        Self::state_to_bytes(&h)
     }
 
     pub fn pad_message(message: &[u8]) -> Vec<u8> {
@@ -367,6 +802,137 @@ impl Sha256Witness {
         padded
     }
 
+    /// Compress whole blocks starting from `state` and return the new chaining value.
+    ///
+    /// Returns `None` if `blocks` is not a multiple of the block size.
+    pub fn compress_from(&mut self, state: [u32; 8], blocks: &[u8]) -> Option<[u32; 8]> {
+        if !blocks.len().is_multiple_of(BLOCK_BYTES) {
+            return None;
+        }
+
+        let mut h = state;
+        for block in blocks.chunks(BLOCK_BYTES) {
+            h = self.process_block(block, h);
+        }
+        Some(h)
+    }
+
+    /// Midstate after compressing a block-aligned prefix from the standard IV.
+    pub fn midstate(prefix: &[u8]) -> Option<[u32; 8]> {
+        Self::new().compress_from(H_INIT, prefix)
+    }
+
+    /// Finish hashing from a midstate: pad `suffix` for a message of
+    /// `prefix_len + suffix.len()` bytes and compress the remaining blocks.
+    pub fn finalize_from(
+        &mut self,
+        state: [u32; 8],
+        prefix_len: usize,
+        suffix: &[u8],
+    ) -> Option<Bytes32> {
+        if !prefix_len.is_multiple_of(BLOCK_BYTES) {
+            return None;
+        }
+
+        let total_len_bits = ((prefix_len + suffix.len()) as u64) * 8;
+        let mut padded = Self::pad_message(suffix);
+        let len_offset = padded.len() - 8;
+        padded[len_offset..].copy_from_slice(&total_len_bits.to_be_bytes());
+
+        let h = self.compress_from(state, &padded)?;
+        Some(Self::state_to_bytes(&h))
+    }
+
+    /// Serialize a chaining state as a big-endian digest.
+    pub fn state_to_bytes(state: &[u32; 8]) -> Bytes32 {
+        let mut result = Bytes32::zero();
+        for (i, word) in state.iter().enumerate() {
+            result.0[i * 4..(i + 1) * 4].copy_from_slice(&word.to_be_bytes());
+        }
+        result
+    }
+
+    /// Chaining state after the last processed block.
+    pub fn get_state(&self) -> [u32; 8] {
+        let mut state = [0u32; 8];
+        for (word, bits) in state.iter_mut().zip(self.state.iter()) {
+            *word = BooleanWitness::recompose_u32(bits);
+        }
+        state
+    }
+
+    /// Maximum message length supported by a variable-length circuit.
+    pub fn max_message_len(max_blocks: usize) -> usize {
+        (max_blocks * BLOCK_BYTES).saturating_sub(PADDING_OVERHEAD)
+    }
+
+    /// Number of blocks in the standard padding of a `len`-byte message.
+    pub fn num_blocks(len: usize) -> usize {
+        (len + PADDING_OVERHEAD).div_ceil(BLOCK_BYTES)
+    }
+
+    /// Pad a message and zero-fill it to `max_blocks` blocks.
+    ///
+    /// Returns `None` if the message does not fit.
+    pub fn pad_message_var(message: &[u8], max_blocks: usize) -> Option<Vec<u8>> {
+        if message.len() > Self::max_message_len(max_blocks) {
+            return None;
+        }
+
+        let mut padded = Self::pad_message(message);
+        padded.resize(max_blocks * BLOCK_BYTES, 0);
+        Some(padded)
+    }
+
+    /// Length flags `f_i = [i < len]` over the padded buffer.
+    pub fn length_flags(len: usize, max_blocks: usize) -> Vec<Fp> {
+        (0..max_blocks * BLOCK_BYTES)
+            .map(|i| if i < len { Fp::one() } else { Fp::zero() })
+            .collect()
+    }
+
+    /// One-hot selector for the final block of a `len`-byte message.
+    pub fn block_selector(len: usize, max_blocks: usize) -> Option<Vec<Fp>> {
+        let n = Self::num_blocks(len);
+        if n > max_blocks {
+            return None;
+        }
+
+        let mut selector = vec![Fp::zero(); max_blocks];
+        selector[n - 1] = Fp::one();
+        Some(selector)
+    }
+
+    /// Hash a message through the variable-length layout.
+    ///
+    /// Compresses all `max_blocks` blocks, recording the chaining state after
+    /// each one, and returns the digest at the selected block along with every
+    /// intermediate state.
+    pub fn compute_var(
+        &mut self,
+        message: &[u8],
+        max_blocks: usize,
+    ) -> Option<(Bytes32, Vec<[u32; 8]>)> {
+        let padded = Self::pad_message_var(message, max_blocks)?;
+        let n = Self::num_blocks(message.len());
+
+        let mut h: [u32; 8] = H_INIT;
+        let mut states = Vec::with_capacity(max_blocks);
+        for block in padded.chunks(BLOCK_BYTES) {
+            h = self.process_block(block, h);
+            states.push(h);
+        }
+
+        let result = Self::state_to_bytes(&states[n - 1]);
+
+        // process_block leaves the last block's state; expose the selected one instead
+        for (bits, word) in self.state.iter_mut().zip(states[n - 1]) {
+            *bits = BooleanWitness::decompose_u32(word);
+        }
+
+        Some((result, states))
+    }
+
     fn process_block(&mut self, block: &[u8], h: [u32; 8]) -> [u32; 8] {
         let mut w = [0u32; 64];
         for i in 0..16 {
@@ -443,12 +1009,269 @@ impl Sha256Witness {
     }
 }
 
+/// Sha256Gadget row layout.
+impl Sha256Witness {
+    /// Write the witness of `Sha256Gadget::hash_message(message.len())`,
+    /// laid out from `start_row`, into `layout` and return the digest.
+    ///
+    /// Each block gets its message schedule, the operand bits of every
+    /// bitwise function, the partial sums and carries of every addition,
+    /// and the new chaining value. The state and schedule of the last block
+    /// are kept, as by `compute`.
+    pub fn fill_columns(
+        &mut self,
+        layout: &mut WitnessLayout,
+        start_row: usize,
+        message: &[u8],
+    ) -> Result<Bytes32> {
+        let mut gadget = Sha256Gadget::new(start_row);
+        gadget.hash_message(message.len());
+        if gadget.current_row() > layout.circuit_rows() {
+            return Err(ProverError::WitnessRowCount {
+                expected: gadget.current_row(),
+                actual: layout.circuit_rows(),
+            });
+        }
+
+        let mut rows = RowWriter {
+            layout,
+            row: start_row,
+        };
+        let mut h = H_INIT;
+        for block in Self::pad_message(message).chunks(BLOCK_BYTES) {
+            let schedule;
+            (h, schedule) = rows.block(h, block);
+            for (bits, word) in self.schedule.iter_mut().zip(schedule) {
+                *bits = BooleanWitness::decompose_u32(word);
+            }
+        }
+        for (bits, word) in self.state.iter_mut().zip(h) {
+            *bits = BooleanWitness::decompose_u32(word);
+        }
+        Ok(Self::state_to_bytes(&h))
+    }
+}
+
+/// The witness of `Sha256Gadget::hash_message(message.len())` as one step
+/// per block, matching `Sha256Witness::fill_columns`, for filling it in
+/// time slices with an `IncrementalWitness`.
+///
+/// The padded message and chaining value are wiped when dropped.
+pub struct Sha256Steps {
+    padded: Vec<u8>,
+    start_row: usize,
+    block_rows: usize,
+    /// The next block and the chaining value before it.
+    state: (usize, [u32; 8]),
+}
+
+impl Sha256Steps {
+    /// Steps hashing `message`, laid out from `start_row`.
+    pub fn new(start_row: usize, message: &[u8]) -> Self {
+        let mut block = Sha256Gadget::new(0);
+        block.sha256_block();
+        Self {
+            padded: Sha256Witness::pad_message(message),
+            start_row,
+            block_rows: block.current_row(),
+            state: (0, H_INIT),
+        }
+    }
+}
+
+impl WitnessSteps for Sha256Steps {
+    fn num_steps(&self) -> usize {
+        self.padded.len() / BLOCK_BYTES
+    }
+
+    fn fill_step(&mut self, layout: &mut WitnessLayout, step: usize) -> Result<()> {
+        let end = self.start_row + self.num_steps() * self.block_rows;
+        if end > layout.circuit_rows() {
+            return Err(ProverError::WitnessRowCount {
+                expected: end,
+                actual: layout.circuit_rows(),
+            });
+        }
+
+        let h = match self.state {
+            (next, h) if next == step => h,
+            _ => Sha256Witness::midstate(&self.padded[..step * BLOCK_BYTES]).expect("whole blocks"),
+        };
+        let mut rows = RowWriter {
+            layout,
+            row: self.start_row + step * self.block_rows,
+        };
+        let block = &self.padded[step * BLOCK_BYTES..(step + 1) * BLOCK_BYTES];
+        let (h, _) = rows.block(h, block);
+        self.state = (step + 1, h);
+        Ok(())
+    }
+}
+
+impl Zeroize for Sha256Steps {
+    fn zeroize(&mut self) {
+        self.padded.zeroize();
+        self.state.1.zeroize();
+    }
+}
+
+impl Drop for Sha256Steps {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for Sha256Steps {}
+
+/// Writes the rows of the `Sha256Gadget` blocks in the order the gadget
+/// lays them out, from `row` on. Bits are taken least significant first.
+struct RowWriter<'a> {
+    layout: &'a mut WitnessLayout,
+    row: usize,
+}
+
+impl RowWriter<'_> {
+    /// Fill columns 0..3 of the next row.
+    fn next(&mut self, values: [Fp; 3]) {
+        for (col, value) in values.into_iter().enumerate() {
+            self.layout.set(Cell::new(self.row, col), value);
+        }
+        self.row += 1;
+    }
+
+    fn bit(word: u32, i: usize) -> Fp {
+        Fp::from((word >> i) & 1)
+    }
+
+    /// Per bit, `a + b` and `2ab`: their difference is `a ^ b`.
+    fn xor_words(&mut self, a: u32, b: u32) -> u32 {
+        for i in 0..32 {
+            let (x, y) = (Self::bit(a, i), Self::bit(b, i));
+            self.next([x, y, x + y]);
+            self.next([x, y, x * y + x * y]);
+        }
+        a ^ b
+    }
+
+    fn and_words(&mut self, a: u32, b: u32) -> u32 {
+        for i in 0..32 {
+            let (x, y) = (Self::bit(a, i), Self::bit(b, i));
+            self.next([x, y, x * y]);
+        }
+        a & b
+    }
+
+    /// Per bit, `-a` and the constant 1: their sum is `!a`.
+    fn not_word(&mut self, a: u32) -> u32 {
+        for i in 0..32 {
+            let x = Self::bit(a, i);
+            self.layout.set(Cell::new(self.row, 3), Fp::one());
+            self.next([x, Fp::zero(), -x]);
+        }
+        !a
+    }
+
+    fn ch(&mut self, e: u32, f: u32, g: u32) -> u32 {
+        let ef = self.and_words(e, f);
+        let not_e = self.not_word(e);
+        let not_e_g = self.and_words(not_e, g);
+        self.xor_words(ef, not_e_g)
+    }
+
+    fn maj(&mut self, a: u32, b: u32, c: u32) -> u32 {
+        let ab = self.and_words(a, b);
+        let ac = self.and_words(a, c);
+        let ab_ac = self.xor_words(ab, ac);
+        let bc = self.and_words(b, c);
+        self.xor_words(ab_ac, bc)
+    }
+
+    /// `rotr(x, r0) ^ rotr(x, r1) ^ last`, the shape of all four sigmas.
+    fn sigma(&mut self, x: u32, r0: u32, r1: u32, last: u32) -> u32 {
+        let rotated = self.xor_words(x.rotate_right(r0), x.rotate_right(r1));
+        self.xor_words(rotated, last)
+    }
+
+    fn add_many_mod32(&mut self, values: &[u32]) -> u32 {
+        let values: Vec<u64> = values.iter().map(|&v| v as u64).collect();
+        let (sum, row) = MultiAddWitness::fill_layout(self.layout, self.row, &values, 32);
+        self.row = row;
+        sum as u32
+    }
+
+    fn add_mod32(&mut self, a: u32, b: u32) -> u32 {
+        let total = a as u64 + b as u64;
+        let carry = Fp::from(total >> 32);
+        let (a, b) = (Fp::from(a), Fp::from(b));
+        self.next([a, b, a + b]);
+        self.next([carry; 3]);
+        self.next([a + b, carry, Fp::from(total as u32)]);
+        total as u32
+    }
+
+    fn message_schedule(&mut self, block: &[u8]) -> [u32; 64] {
+        let mut w = [0u32; 64];
+        for (word, bytes) in w.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..64 {
+            let s1 = self.sigma(w[i - 2], 17, 19, w[i - 2] >> 10);
+            let s0 = self.sigma(w[i - 15], 7, 18, w[i - 15] >> 3);
+            w[i] = self.add_many_mod32(&[s1, w[i - 7], s0, w[i - 16]]);
+        }
+        w
+    }
+
+    fn compression_round(&mut self, state: [u32; 8], k: u32, w: u32) -> [u32; 8] {
+        let [a, b, c, d, e, f, g, h] = state;
+        let s1 = self.sigma(e, 6, 11, e.rotate_right(25));
+        let ch = self.ch(e, f, g);
+        let temp1 = self.add_many_mod32(&[h, s1, ch, k, w]);
+
+        let s0 = self.sigma(a, 2, 13, a.rotate_right(22));
+        let maj = self.maj(a, b, c);
+        let new_e = self.add_many_mod32(&[d, temp1]);
+        let new_a = self.add_many_mod32(&[temp1, s0, maj]);
+        [new_a, a, b, c, new_e, e, f, g]
+    }
+
+    /// One `sha256_block`: the chaining value after `block`, and its
+    /// message schedule.
+    fn block(&mut self, h: [u32; 8], block: &[u8]) -> ([u32; 8], [u32; 64]) {
+        let w = self.message_schedule(block);
+        let mut state = h;
+        for i in 0..64 {
+            state = self.compression_round(state, K[i], w[i]);
+        }
+        let mut next = [0u32; 8];
+        for i in 0..8 {
+            next[i] = self.add_mod32(h[i], state[i]);
+        }
+        (next, w)
+    }
+}
+
 impl Default for Sha256Witness {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl Zeroize for Sha256Witness {
+    fn zeroize(&mut self) {
+        self.state.iter_mut().for_each(Zeroize::zeroize);
+        self.schedule.iter_mut().for_each(Zeroize::zeroize);
+    }
+}
+
+impl Drop for Sha256Witness {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for Sha256Witness {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -477,4 +1300,171 @@ mod tests {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_sha256_witness_var_lengths() {
+        for len in [0usize, 1, 54, 55, 56, 63, 64, 119, 120] {
+            let message: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+            let mut witness = Sha256Witness::new();
+            let (result, states) = witness.compute_var(&message, 3).unwrap();
+
+            let expected: [u8; 32] = Sha256::digest(&message).into();
+            assert_eq!(result, expected, "length {}", len);
+            assert_eq!(states.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_pad_message_var_bounds() {
+        assert_eq!(Sha256Witness::max_message_len(2), 119);
+        assert!(Sha256Witness::pad_message_var(&[0u8; 119], 2).is_some());
+        assert!(Sha256Witness::pad_message_var(&[0u8; 120], 2).is_none());
+
+        let flags = Sha256Witness::length_flags(3, 1);
+        assert_eq!(flags.iter().filter(|f| **f == Fp::one()).count(), 3);
+
+        let selector = Sha256Witness::block_selector(56, 2).unwrap();
+        assert_eq!(selector, vec![Fp::zero(), Fp::one()]);
+    }
+
+    #[test]
+    fn test_midstate_split() {
+        let message: Vec<u8> = (0..200u32).map(|i| (i * 13) as u8).collect();
+        let (prefix, suffix) = message.split_at(128);
+
+        let midstate = Sha256Witness::midstate(prefix).unwrap();
+        let mut witness = Sha256Witness::new();
+        let result = witness
+            .finalize_from(midstate, prefix.len(), suffix)
+            .unwrap();
+
+        let expected: [u8; 32] = Sha256::digest(&message).into();
+        assert_eq!(result, expected);
+        assert_eq!(
+            Sha256Witness::state_to_bytes(&witness.get_state()),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_midstate_rejects_unaligned_prefix() {
+        assert!(Sha256Witness::midstate(&[0u8; 65]).is_none());
+        assert!(Sha256Witness::new()
+            .finalize_from(H_INIT, 10, b"abc")
+            .is_none());
+    }
+
+    #[test]
+    fn test_add_mod32_cells() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let [a, b, _] = cs.generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::zero()),
+            right_coeff: Some(Fp::zero()),
+            output_coeff: Some(Fp::zero()),
+        });
+        w.set(a, Fp::from(0xFFFF_FFF0u64));
+        w.set(b, Fp::from(0x20u64));
+
+        let c = Sha256Gadget::add_mod32_cells(&mut cs, &mut w, a, b);
+        assert_eq!(w.get(c), Fp::from(0x10u64));
+        assert!(cs.check_witness(&w).is_ok());
+
+        w.set(c, Fp::from(0x11u64));
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_decompose_word() {
+        let mut gadget = Sha256Gadget::new(0);
+        assert_eq!(gadget.decompose_word(), 1);
+        let (gates, rows) = gadget.build();
+        assert_eq!(rows, RangeCheckGadget::rows_for(32));
+        assert_eq!(gates[2].typ, kimchi::circuits::gate::GateType::RangeCheck0);
+    }
+
+    #[test]
+    fn test_suffix_gadget_construction() {
+        let mut full = Sha256Gadget::new(0);
+        full.compress_from_state(1);
+        let (_, one_block_rows) = full.build();
+
+        let mut suffix = Sha256Gadget::new(0);
+        suffix.hash_suffix_from_state(128, 72);
+        let (_, rows) = suffix.build();
+
+        // 200 bytes pad to 4 blocks, 2 of which are covered by the midstate
+        let mut two = Sha256Gadget::new(0);
+        two.compress_from_state(2);
+        assert_eq!(rows, two.build().1);
+        assert!(rows > one_block_rows);
+    }
+
+    #[test]
+    fn test_fill_columns() {
+        let mut gadget = Sha256Gadget::new(0);
+        gadget.hash_message(3);
+        let (gates, rows) = gadget.build();
+        let mut layout = WitnessLayout::for_gates(&gates);
+
+        let mut witness = Sha256Witness::new();
+        let digest = witness.fill_columns(&mut layout, 0, b"abc").unwrap();
+        let expected: [u8; 32] = Sha256::digest(b"abc").into();
+        assert_eq!(digest, expected);
+        assert_eq!(witness.compute(b"abc"), digest);
+
+        let mut columns = layout.into_columns();
+        crate::diagnostics::check_witness(&gates, &columns, 0, false).unwrap();
+
+        // A wrong carry in the last chaining value addition is caught
+        columns[1][rows - 1] += Fp::one();
+        assert!(crate::diagnostics::check_witness(&gates, &columns, 0, false).is_err());
+    }
+
+    #[test]
+    fn test_fill_columns_too_few_rows() {
+        let mut gadget = Sha256Gadget::new(0);
+        gadget.hash_message(3);
+        let (gates, _) = gadget.build();
+        let mut layout = WitnessLayout::for_gates(&gates);
+
+        // 56 bytes no longer fit one block with their padding
+        let err = Sha256Witness::new()
+            .fill_columns(&mut layout, 0, &[0; 56])
+            .unwrap_err();
+        assert!(matches!(err, ProverError::WitnessRowCount { .. }));
+    }
+
+    #[test]
+    fn test_var_gadget_construction() {
+        let mut gadget = Sha256Gadget::new(0);
+        gadget.pad_message_var(1);
+        let (gates, rows) = gadget.build();
+
+        assert_eq!(gates.len(), rows);
+        assert!(rows > BLOCK_BYTES);
+    }
+
+    #[test]
+    fn test_hash_bits_cells() {
+        let message = b"abc";
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let bits: Vec<Cell> = message
+            .iter()
+            .flat_map(|byte| (0..8).map(move |j| byte >> j & 1 == 1))
+            .map(|bit| BooleanGadget::alloc_bit(&mut cs, &mut w, bit))
+            .collect();
+
+        let digest = Sha256Gadget::hash_bits_cells(&mut cs, &mut w, &bits);
+        let expected = Sha256::digest(message);
+        for (k, cell) in digest.iter().enumerate() {
+            assert_eq!(w.get(*cell), Fp::from(expected[k / 8] >> (k % 8) & 1));
+        }
+        assert!(cs.check_witness(&w).is_ok());
+
+        // A flipped message bit no longer satisfies the copy constraints
+        w.set(bits[0], Fp::zero());
+        assert!(cs.check_witness(&w).is_err());
+    }
 }
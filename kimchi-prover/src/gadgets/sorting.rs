@@ -0,0 +1,314 @@
+//! Sorting networks for Kimchi circuits.
+//!
+//! Sorts a fixed number of values in the circuit, e.g. to state a median,
+//! the top k of a list, or that a list has no duplicates (no two adjacent
+//! sorted values are equal). Two ways:
+//!
+//! - [`SortingGadget::sort_cells`] runs Batcher's odd-even merge sort: a
+//!   [`SortingNetwork`] of compare-exchange units, each of which outputs its
+//!   inputs either as they are or swapped, selected by a `less_than_cells`
+//!   bit. The outputs are a permutation of the inputs by construction and
+//!   sorted because the network sorts; it costs `O(n log^2 n)` comparisons.
+//! - For longer lists, witness the sorted list and constrain it with
+//!   [`SortingGadget::assert_sorted_cells`], `n - 1` comparisons, and
+//!   [`SortingGadget::assert_permutation_cells`], which checks
+//!   `prod(g - a_i) = prod(g - b_i)` at a challenge `g` hashed from both
+//!   lists. Lists that are not permutations of each other agree at fewer
+//!   than `n` points, which the prover cannot aim the hash at.
+//!
+//! Comparisons are unsigned, of values the caller range checks to
+//! `max_bits` bits.
+
+use ark_ff::{One, PrimeField};
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use mina_curves::pasta::Fp;
+
+use super::comparison::ComparisonGadget;
+use super::poseidon::{PoseidonGadget, PoseidonWitness};
+use crate::builder::{Cell, CircuitBuilder, WitnessBuilder};
+
+/// Domain tag of the permutation challenge.
+pub const PERMUTATION_DOMAIN: &str = "permutation";
+
+/// Batcher odd-even merge sort of a fixed number of values.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SortingNetwork {
+    len: usize,
+    comparators: Vec<(usize, usize)>,
+}
+
+impl SortingNetwork {
+    /// The network sorting `len` values.
+    ///
+    /// Built for the next power of two; comparators reaching past `len`
+    /// would compare with padding above every value, so they are dropped.
+    pub fn batcher(len: usize) -> Self {
+        let width = len.next_power_of_two();
+        let mut comparators = Vec::new();
+        let mut p = 1;
+        while p < width {
+            let mut k = p;
+            while k >= 1 {
+                let mut j = k % p;
+                while j + k < width {
+                    for i in 0..k.min(width - j - k) {
+                        let (a, b) = (i + j, i + j + k);
+                        if a / (2 * p) == b / (2 * p) && b < len {
+                            comparators.push((a, b));
+                        }
+                    }
+                    j += 2 * k;
+                }
+                k /= 2;
+            }
+            p *= 2;
+        }
+        Self { len, comparators }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Compare-exchange positions in order, the smaller value going to the
+    /// first.
+    pub fn comparators(&self) -> &[(usize, usize)] {
+        &self.comparators
+    }
+
+    /// Sort `values` with the network.
+    pub fn apply<T: Ord>(&self, values: &mut [T]) {
+        assert_eq!(values.len(), self.len, "the network sorts a fixed length");
+        for &(a, b) in &self.comparators {
+            if values[b] < values[a] {
+                values.swap(a, b);
+            }
+        }
+    }
+}
+
+/// Cell-level sorting.
+pub struct SortingGadget;
+
+impl SortingGadget {
+    /// `(min(a, b), max(a, b))`.
+    pub fn compare_exchange_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: Cell,
+        b: Cell,
+        max_bits: usize,
+    ) -> (Cell, Cell) {
+        let swap = ComparisonGadget::less_than_cells(cs, w, b, a, max_bits);
+        let (va, vb, vs) = (w.get(a), w.get(b), w.get(swap));
+
+        // d = b - a, t = swap * d
+        let d = cs.generic_packed(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(d, [vb, va, vb - va]);
+        cs.copy(b, d[0]);
+        cs.copy(a, d[1]);
+        let t = cs.generic_packed(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(t, [vs, vb - va, vs * (vb - va)]);
+        cs.copy(swap, t[0]);
+        cs.copy(d[2], t[1]);
+
+        // min = a + t, max = b - t
+        let mut out = |x: Cell, sign: Fp| {
+            let cells = cs.generic_packed(GenericGateSpec::Add {
+                left_coeff: Some(Fp::one()),
+                right_coeff: Some(sign),
+                output_coeff: Some(-Fp::one()),
+            });
+            let (vx, vt) = (w.get(x), w.get(t[2]));
+            w.set_cells(cells, [vx, vt, vx + sign * vt]);
+            cs.copy(x, cells[0]);
+            cs.copy(t[2], cells[1]);
+            cells[2]
+        };
+        (out(a, Fp::one()), out(b, -Fp::one()))
+    }
+
+    /// `values` in ascending order, through the Batcher network.
+    pub fn sort_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        values: &[Cell],
+        max_bits: usize,
+    ) -> Vec<Cell> {
+        let mut cells = values.to_vec();
+        for &(a, b) in SortingNetwork::batcher(values.len()).comparators() {
+            let (low, high) = Self::compare_exchange_cells(cs, w, cells[a], cells[b], max_bits);
+            cells[a] = low;
+            cells[b] = high;
+        }
+        cells
+    }
+
+    /// Constrain `values` to be in ascending order.
+    pub fn assert_sorted_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        values: &[Cell],
+        max_bits: usize,
+    ) {
+        for pair in values.windows(2) {
+            ComparisonGadget::greater_or_equal_cells(cs, w, pair[1], pair[0], max_bits);
+        }
+    }
+
+    /// Constrain `b` to be a permutation of `a`.
+    pub fn assert_permutation_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: &[Cell],
+        b: &[Cell],
+    ) {
+        assert_eq!(a.len(), b.len(), "a permutation keeps the length");
+        if a.is_empty() {
+            return;
+        }
+
+        let tag = SortingWitness::tag();
+        let [tag_cell, _, _] = cs.generic(GenericGateSpec::Const(tag));
+        w.set(tag_cell, tag);
+        let inputs: Vec<Cell> = std::iter::once(tag_cell)
+            .chain(a.iter().copied())
+            .chain(b.iter().copied())
+            .collect();
+        let challenge = PoseidonGadget::hash_cells(cs, w, &inputs);
+
+        let products = [a, b].map(|list| {
+            let mut product = None;
+            for &value in list {
+                let factor = cs.generic_packed(GenericGateSpec::Add {
+                    left_coeff: Some(Fp::one()),
+                    right_coeff: Some(-Fp::one()),
+                    output_coeff: Some(-Fp::one()),
+                });
+                let (vg, vv) = (w.get(challenge), w.get(value));
+                w.set_cells(factor, [vg, vv, vg - vv]);
+                cs.copy(challenge, factor[0]);
+                cs.copy(value, factor[1]);
+
+                product = Some(match product {
+                    None => factor[2],
+                    Some(acc) => {
+                        let cells = cs.generic_packed(GenericGateSpec::Mul {
+                            mul_coeff: Some(Fp::one()),
+                            output_coeff: Some(-Fp::one()),
+                        });
+                        let (vacc, vf) = (w.get(acc), w.get(factor[2]));
+                        w.set_cells(cells, [vacc, vf, vacc * vf]);
+                        cs.copy(acc, cells[0]);
+                        cs.copy(factor[2], cells[1]);
+                        cells[2]
+                    }
+                });
+            }
+            product.expect("the lists are not empty")
+        });
+        cs.copy(products[0], products[1]);
+    }
+}
+
+/// Native sorting matching the gadget.
+pub struct SortingWitness;
+
+impl SortingWitness {
+    /// `values` in ascending order of their integer values.
+    pub fn sort(values: &[Fp]) -> Vec<Fp> {
+        let mut sorted = values.to_vec();
+        sorted.sort_by_key(|v| v.into_bigint());
+        sorted
+    }
+
+    fn tag() -> Fp {
+        PoseidonWitness::domain_tag(PERMUTATION_DOMAIN).expect("the permutation tag is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_ff::Zero;
+    use kimchi::circuits::polynomials::generic::GENERIC_COEFFS;
+
+    fn alloc(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, values: &[u64]) -> Vec<Cell> {
+        values
+            .iter()
+            .map(|v| {
+                let [cell, _, _] = cs.generic_coeffs([Fp::zero(); GENERIC_COEFFS]);
+                w.set(cell, Fp::from(*v));
+                cell
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_batcher_sorts() {
+        assert_eq!(SortingNetwork::batcher(4).comparators().len(), 5);
+        assert_eq!(SortingNetwork::batcher(8).comparators().len(), 19);
+        assert!(SortingNetwork::batcher(1).comparators().is_empty());
+
+        // Every 0/1 input sorted means every input sorted
+        for len in 1..=9 {
+            let network = SortingNetwork::batcher(len);
+            for mask in 0u32..1 << len {
+                let mut bits: Vec<u32> = (0..len).map(|i| mask >> i & 1).collect();
+                network.apply(&mut bits);
+                assert!(bits.windows(2).all(|pair| pair[0] <= pair[1]), "{}", len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sort_cells() {
+        let values = [42, 7, 7, 1000, 0, 13, 99];
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let cells = alloc(&mut cs, &mut w, &values);
+        for cell in &cells {
+            ComparisonGadget::range_check_cells(&mut cs, &mut w, *cell, 16);
+        }
+        let sorted = SortingGadget::sort_cells(&mut cs, &mut w, &cells, 16);
+        cs.check_witness(&w).unwrap();
+
+        let expected = SortingWitness::sort(&values.map(Fp::from));
+        let got: Vec<Fp> = sorted.iter().map(|cell| w.get(*cell)).collect();
+        assert_eq!(got, expected);
+        // The median
+        assert_eq!(w.get(sorted[values.len() / 2]), Fp::from(13u64));
+
+        // Claiming the median is another input
+        w.set(sorted[3], Fp::from(42u64));
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_witnessed_sort() {
+        let check = |input: &[u64], output: &[u64]| {
+            let mut cs = CircuitBuilder::new(0);
+            let mut w = WitnessBuilder::new(0);
+            let a = alloc(&mut cs, &mut w, input);
+            let b = alloc(&mut cs, &mut w, output);
+            SortingGadget::assert_sorted_cells(&mut cs, &mut w, &b, 16);
+            SortingGadget::assert_permutation_cells(&mut cs, &mut w, &a, &b);
+            cs.check_witness(&w).is_ok()
+        };
+        assert!(check(&[5, 3, 9, 3], &[3, 3, 5, 9]));
+        // Sorted but not a permutation, and a permutation but not sorted
+        assert!(!check(&[5, 3, 9, 3], &[3, 5, 5, 9]));
+        assert!(!check(&[5, 3, 9, 3], &[3, 5, 3, 9]));
+    }
+}
@@ -0,0 +1,192 @@
+//! Poseidon vector commitments opened at one position.
+//!
+//! Commits to k field elements so that a later proof reveals the element at
+//! one position and nothing about the others, e.g. a single attribute of a
+//! bundle per presentation. The commitment is the root of a Poseidon Merkle
+//! tree (see `gadgets::merkle`) over the leaves
+//!
+//! `leaf_j = hash(tag("vector_commitment"), j, salt, v_j)`
+//!
+//! padded with zero leaves to a power of two, at least two. The salt hides
+//! the values as in `gadgets::commitment`. An opening proves knowledge of
+//! the salt and of the siblings on the path of position `i`; the path's
+//! directions are the bits of `i`, so a leaf cannot be opened at another
+//! position, and the padding leaves, not being hashes, cannot be opened at
+//! all. An opening costs one Poseidon hash per level plus the leaf hash.
+
+use ark_ff::{UniformRand, Zero};
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use mina_curves::pasta::Fp;
+use rand::{CryptoRng, RngCore};
+
+use super::boolean::BooleanGadget;
+use super::merkle::{MerkleGadget, MerkleWitness};
+use super::poseidon::{PoseidonGadget, PoseidonWitness};
+use crate::builder::{Cell, CircuitBuilder, WitnessBuilder};
+
+/// Domain tag of vector commitment leaves.
+pub const VECTOR_COMMITMENT_DOMAIN: &str = "vector_commitment";
+
+/// Cell-level vector commitment openings.
+pub struct VectorCommitmentGadget;
+
+impl VectorCommitmentGadget {
+    /// Constrain `commitment`, typically a public input cell, to hold `value`
+    /// at `index` under `salt`, given the siblings of the opened leaf from
+    /// `VectorCommitmentWitness::open`.
+    ///
+    /// Also range checks `index` to the `siblings.len()` bits of the tree.
+    pub fn open_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        commitment: Cell,
+        index: Cell,
+        value: Cell,
+        salt: Cell,
+        siblings: &[Fp],
+    ) {
+        let tag = VectorCommitmentWitness::tag();
+        let [tag_cell, _, _] = cs.generic(GenericGateSpec::Const(tag));
+        w.set(tag_cell, tag);
+        let leaf = PoseidonGadget::hash_cells(cs, w, &[tag_cell, index, salt, value]);
+
+        let position = BooleanGadget::decompose_cells(cs, w, index, siblings.len());
+        let root = MerkleGadget::root_at_cells(cs, w, leaf, &position, siblings);
+        cs.copy(root, commitment);
+    }
+}
+
+/// Native vector commitments matching the gadget.
+pub struct VectorCommitmentWitness;
+
+impl VectorCommitmentWitness {
+    /// Levels of the tree committing to `len` values.
+    pub fn depth(len: usize) -> usize {
+        len.max(2).next_power_of_two().ilog2() as usize
+    }
+
+    /// Leaf holding `value` at `index`.
+    pub fn leaf(index: usize, value: Fp, salt: Fp) -> Fp {
+        PoseidonWitness::hash(&[Self::tag(), Fp::from(index as u64), salt, value])
+    }
+
+    /// Commitment to `values` under `salt`.
+    pub fn commit(values: &[Fp], salt: Fp) -> Fp {
+        let (root, _) = MerkleWitness::tree(&Self::leaves(values, salt));
+        root
+    }
+
+    /// Siblings of the leaf at `index`, from the leaf up, or `None` if
+    /// `index` is out of range.
+    pub fn open(values: &[Fp], salt: Fp, index: usize) -> Option<Vec<Fp>> {
+        if index >= values.len() {
+            return None;
+        }
+        let (_, paths) = MerkleWitness::tree(&Self::leaves(values, salt));
+        Some(paths[index].iter().map(|step| step.sibling).collect())
+    }
+
+    /// A fresh salt.
+    pub fn random_salt<R: RngCore + CryptoRng>(rng: &mut R) -> Fp {
+        Fp::rand(rng)
+    }
+
+    /// Leaves of `values`, padded to the width of the tree.
+    fn leaves(values: &[Fp], salt: Fp) -> Vec<Fp> {
+        let mut leaves: Vec<Fp> = values
+            .iter()
+            .enumerate()
+            .map(|(j, v)| Self::leaf(j, *v, salt))
+            .collect();
+        leaves.resize(1 << Self::depth(values.len()), Fp::zero());
+        leaves
+    }
+
+    fn tag() -> Fp {
+        PoseidonWitness::domain_tag(VECTOR_COMMITMENT_DOMAIN)
+            .expect("the vector commitment tag is valid")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::GadgetHarness;
+    use kimchi::circuits::polynomials::generic::GENERIC_COEFFS;
+
+    /// Opening circuit with public (commitment, index, value).
+    fn opening(
+        commitment: Fp,
+        index: u64,
+        value: Fp,
+        salt: Fp,
+        siblings: &[Fp],
+    ) -> (CircuitBuilder, WitnessBuilder) {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let public = cs.public_inputs(3);
+        for (cell, v) in public.iter().zip([commitment, Fp::from(index), value]) {
+            w.set(*cell, v);
+        }
+        let [salt_cell, _, _] = cs.generic_coeffs([Fp::zero(); GENERIC_COEFFS]);
+        w.set(salt_cell, salt);
+        VectorCommitmentGadget::open_cells(
+            &mut cs, &mut w, public[0], public[1], public[2], salt_cell, siblings,
+        );
+        (cs, w)
+    }
+
+    #[test]
+    fn test_commit() {
+        let values: Vec<Fp> = (1..=3u64).map(Fp::from).collect();
+        let salt = VectorCommitmentWitness::random_salt(&mut rand::rngs::OsRng);
+        let commitment = VectorCommitmentWitness::commit(&values, salt);
+        assert_eq!(VectorCommitmentWitness::depth(1), 1);
+        assert_eq!(VectorCommitmentWitness::depth(3), 2);
+        assert_ne!(
+            commitment,
+            VectorCommitmentWitness::commit(&values, salt + Fp::from(1u64))
+        );
+        assert!(VectorCommitmentWitness::open(&values, salt, 3).is_none());
+
+        let swapped = [values[1], values[0], values[2]];
+        assert_ne!(commitment, VectorCommitmentWitness::commit(&swapped, salt));
+    }
+
+    #[test]
+    fn test_open_cells() {
+        let values: Vec<Fp> = (10..15u64).map(Fp::from).collect();
+        let salt = VectorCommitmentWitness::random_salt(&mut rand::rngs::OsRng);
+        let commitment = VectorCommitmentWitness::commit(&values, salt);
+
+        for (index, value) in values.iter().enumerate() {
+            let siblings = VectorCommitmentWitness::open(&values, salt, index).unwrap();
+            assert_eq!(siblings.len(), 3);
+            let (cs, w) = opening(commitment, index as u64, *value, salt, &siblings);
+            assert!(cs.check_witness(&w).is_ok());
+        }
+
+        // The value at position 1 does not open at position 2 with its path
+        let siblings = VectorCommitmentWitness::open(&values, salt, 1).unwrap();
+        let (cs, w) = opening(commitment, 2, values[1], salt, &siblings);
+        assert!(cs.check_witness(&w).is_err());
+        // Nor does another value at position 1
+        let (cs, w) = opening(commitment, 1, values[2], salt, &siblings);
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_prove_opening() {
+        let values: Vec<Fp> = (0..4u64).map(|v| Fp::from(100 + v)).collect();
+        let salt = VectorCommitmentWitness::random_salt(&mut rand::rngs::OsRng);
+        let commitment = VectorCommitmentWitness::commit(&values, salt);
+        let siblings = VectorCommitmentWitness::open(&values, salt, 2).unwrap();
+        let (cs, w) = opening(commitment, 2, values[2], salt, &siblings);
+
+        let proof = GadgetHarness::new()
+            .prove_circuit(cs.build(), w.columns(), 3)
+            .unwrap();
+        assert!(proof.verifies(&[commitment, Fp::from(2u64), values[2]]));
+        assert!(!proof.verifies(&[commitment, Fp::from(2u64), values[3]]));
+    }
+}
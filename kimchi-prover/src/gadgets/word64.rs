@@ -0,0 +1,438 @@
+//! 64-bit word gadgets for Kimchi circuits.
+//!
+//! Mirrors the 32-bit helpers in `boolean` and `sha256` for hashes built on
+//! 64-bit words (SHA-512, Blake2b, Keccak). Decomposition doubles as a u64
+//! range check. Rotations and shifts permute bit cells, so they cost nothing
+//! beyond the decomposition and recomposition of the word.
+
+use ark_ff::{BigInteger, Field, One, PrimeField, Zero};
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::polynomials::generic::GenericGateSpec;
+use kimchi::circuits::wires::Wire;
+use mina_curves::pasta::Fp;
+
+use super::boolean::BooleanGadget;
+use crate::builder::{fill_row_layout, Cell, CircuitBuilder, Gadget, WitnessBuilder};
+use crate::error::Result;
+use crate::prover::COLUMNS;
+use crate::types::BitVec;
+
+/// Bits in a word.
+pub const WORD64_BITS: usize = 64;
+
+/// Gadget for 64-bit word operations.
+pub struct Word64Gadget {
+    gates: Vec<CircuitGate<Fp>>,
+    current_row: usize,
+}
+
+impl Word64Gadget {
+    pub fn new(start_row: usize) -> Self {
+        Self {
+            gates: Vec::new(),
+            current_row: start_row,
+        }
+    }
+
+    pub fn current_row(&self) -> usize {
+        self.current_row
+    }
+
+    fn push_generic(&mut self, spec: GenericGateSpec<Fp>) {
+        let wires = Wire::for_row(self.current_row);
+        self.gates
+            .push(CircuitGate::create_generic_gadget(wires, spec, None));
+        self.current_row += 1;
+    }
+
+    /// Boolean constraint: b * b - b = 0.
+    fn boolean(&mut self) {
+        self.push_generic(GenericGateSpec::Mul {
+            mul_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Accumulation row: acc' = acc + weight * term.
+    fn accumulate(&mut self, weight: Fp) {
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(weight),
+            output_coeff: Some(-Fp::one()),
+        });
+    }
+
+    /// Decompose a 64-bit word into bits: word = sum(bit_i * 2^i).
+    pub fn decompose_u64(&mut self) -> usize {
+        let start = self.current_row;
+
+        for _ in 0..WORD64_BITS {
+            self.boolean();
+        }
+        for _ in 0..WORD64_BITS.div_ceil(3) {
+            self.accumulate(Fp::one());
+        }
+
+        start
+    }
+
+    /// Recompose a rotated or shifted bit vector into a word.
+    ///
+    /// The permutation itself is expressed by which bit cells feed the sum.
+    pub fn recompose_u64(&mut self) -> usize {
+        let start = self.current_row;
+        for _ in 0..WORD64_BITS.div_ceil(3) {
+            self.accumulate(Fp::one());
+        }
+        start
+    }
+
+    /// Modular addition: c = (a + b) mod 2^64.
+    pub fn add_mod64(&mut self) -> usize {
+        let start = self.current_row;
+
+        // a + b = sum
+        self.accumulate(Fp::one());
+        // sum - c - 2^64 * carry = 0
+        self.push_generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(-Fp::from(2u64).pow([WORD64_BITS as u64])),
+        });
+        self.boolean();
+        self.decompose_u64();
+
+        start
+    }
+
+    /// XOR of two decomposed words, one row per bit.
+    pub fn xor_u64(&mut self) -> usize {
+        let start = self.current_row;
+        for _ in 0..WORD64_BITS {
+            self.push_generic(GenericGateSpec::Add {
+                left_coeff: Some(Fp::one()),
+                right_coeff: Some(Fp::one()),
+                output_coeff: Some(-Fp::one()),
+            });
+        }
+        start
+    }
+
+    /// Consume the gadget and return the gates.
+    pub fn build(self) -> (Vec<CircuitGate<Fp>>, usize) {
+        (self.gates, self.current_row)
+    }
+}
+
+impl Gadget for Word64Gadget {
+    type Inputs = [[Fp; COLUMNS]];
+
+    fn build(&self, cs: &mut CircuitBuilder) -> usize {
+        cs.extend_gates(&self.gates, self.current_row - self.gates.len())
+    }
+
+    fn rows(&self) -> usize {
+        self.gates.len()
+    }
+
+    fn witness(&self, w: &mut WitnessBuilder, rows: &Self::Inputs) -> Result<()> {
+        fill_row_layout(w, self.rows(), rows)
+    }
+}
+
+/// Cell-level 64-bit word operations.
+///
+/// Words are held either as a single cell or as 64 little-endian bit cells.
+impl Word64Gadget {
+    /// Decompose a word cell into bit cells, range checking it to 64 bits.
+    pub fn decompose_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        word: Cell,
+    ) -> [Cell; WORD64_BITS] {
+        let bits = BooleanGadget::decompose_cells(cs, w, word, WORD64_BITS);
+        std::array::from_fn(|i| bits[i])
+    }
+
+    /// Recompose bit cells into a word cell.
+    ///
+    /// The bits must already be constrained to be boolean.
+    pub fn compose_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        bits: &[Cell; WORD64_BITS],
+    ) -> Cell {
+        let mut acc = bits[0];
+        for (i, bit) in bits.iter().enumerate().skip(1) {
+            let weight = Fp::from(2u64).pow([i as u64]);
+            let cells = cs.generic(GenericGateSpec::Add {
+                left_coeff: Some(Fp::one()),
+                right_coeff: Some(weight),
+                output_coeff: Some(-Fp::one()),
+            });
+            let (vacc, vbit) = (w.get(acc), w.get(*bit));
+            w.set_cells(cells, [vacc, vbit, vacc + weight * vbit]);
+            cs.copy(acc, cells[0]);
+            cs.copy(*bit, cells[1]);
+            acc = cells[2];
+        }
+        acc
+    }
+
+    /// Cell pinned to zero, used to fill shifted-in bits.
+    fn zero_cell(cs: &mut CircuitBuilder, w: &mut WitnessBuilder) -> Cell {
+        let [zero, _, _] = cs.generic(GenericGateSpec::Const(Fp::zero()));
+        w.set(zero, Fp::zero());
+        zero
+    }
+
+    /// Right rotation of bit cells. Costs no rows.
+    pub fn rotr_bits(bits: &[Cell; WORD64_BITS], n: usize) -> [Cell; WORD64_BITS] {
+        std::array::from_fn(|i| bits[(i + n) % WORD64_BITS])
+    }
+
+    /// Left rotation of bit cells. Costs no rows.
+    pub fn rotl_bits(bits: &[Cell; WORD64_BITS], n: usize) -> [Cell; WORD64_BITS] {
+        Self::rotr_bits(bits, WORD64_BITS - n % WORD64_BITS)
+    }
+
+    /// Right shift of bit cells, filling with a constant zero cell.
+    pub fn shr_bits(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        bits: &[Cell; WORD64_BITS],
+        n: usize,
+    ) -> [Cell; WORD64_BITS] {
+        let zero = Self::zero_cell(cs, w);
+        std::array::from_fn(|i| {
+            if i + n < WORD64_BITS {
+                bits[i + n]
+            } else {
+                zero
+            }
+        })
+    }
+
+    /// Left shift of bit cells, filling with a constant zero cell.
+    pub fn shl_bits(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        bits: &[Cell; WORD64_BITS],
+        n: usize,
+    ) -> [Cell; WORD64_BITS] {
+        let zero = Self::zero_cell(cs, w);
+        std::array::from_fn(|i| if i >= n { bits[i - n] } else { zero })
+    }
+
+    /// Right rotation of a word cell.
+    pub fn rotr_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        word: Cell,
+        n: usize,
+    ) -> Cell {
+        let bits = Self::decompose_cells(cs, w, word);
+        Self::compose_cells(cs, w, &Self::rotr_bits(&bits, n))
+    }
+
+    /// Right shift of a word cell.
+    pub fn shr_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        word: Cell,
+        n: usize,
+    ) -> Cell {
+        let bits = Self::decompose_cells(cs, w, word);
+        let shifted = Self::shr_bits(cs, w, &bits, n);
+        Self::compose_cells(cs, w, &shifted)
+    }
+
+    /// Modular addition of two word cells: c = (a + b) mod 2^64.
+    ///
+    /// The inputs must already be range checked to 64 bits; the output is.
+    pub fn add_mod64_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: Cell,
+        b: Cell,
+    ) -> Cell {
+        let modulus = Fp::from(2u64).pow([WORD64_BITS as u64]);
+        let (va, vb) = (w.get(a), w.get(b));
+        let sum = Self::word_value(va) + Self::word_value(vb);
+
+        let add = cs.generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(Fp::one()),
+            output_coeff: Some(-Fp::one()),
+        });
+        w.set_cells(add, [va, vb, va + vb]);
+        cs.copy(a, add[0]);
+        cs.copy(b, add[1]);
+
+        // sum - c - 2^64 * carry = 0
+        let reduce = cs.generic(GenericGateSpec::Add {
+            left_coeff: Some(Fp::one()),
+            right_coeff: Some(-Fp::one()),
+            output_coeff: Some(-modulus),
+        });
+        w.set_cells(
+            reduce,
+            [
+                va + vb,
+                Fp::from(sum as u64),
+                Fp::from((sum >> WORD64_BITS) as u64),
+            ],
+        );
+        cs.copy(add[2], reduce[0]);
+
+        BooleanGadget::assert_boolean(cs, w, reduce[2]);
+        Self::decompose_cells(cs, w, reduce[1]);
+
+        reduce[1]
+    }
+
+    /// Bitwise XOR of two decomposed words.
+    pub fn xor_u64_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: &[Cell; WORD64_BITS],
+        b: &[Cell; WORD64_BITS],
+    ) -> [Cell; WORD64_BITS] {
+        std::array::from_fn(|i| BooleanGadget::xor_cells(cs, w, a[i], b[i]))
+    }
+
+    /// Bitwise AND of two decomposed words.
+    pub fn and_u64_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: &[Cell; WORD64_BITS],
+        b: &[Cell; WORD64_BITS],
+    ) -> [Cell; WORD64_BITS] {
+        std::array::from_fn(|i| BooleanGadget::and_cells(cs, w, a[i], b[i]))
+    }
+
+    /// Bitwise NOT of a decomposed word.
+    pub fn not_u64_cells(
+        cs: &mut CircuitBuilder,
+        w: &mut WitnessBuilder,
+        a: &[Cell; WORD64_BITS],
+    ) -> [Cell; WORD64_BITS] {
+        std::array::from_fn(|i| BooleanGadget::not_cell(cs, w, a[i]))
+    }
+
+    /// Low 128 bits of a field element holding a word.
+    fn word_value(value: Fp) -> u128 {
+        let bytes = value.into_bigint().to_bytes_le();
+        u128::from_le_bytes(std::array::from_fn(|i| bytes[i]))
+    }
+}
+
+/// Witness generator for 64-bit word operations.
+pub struct Word64Witness;
+
+impl Word64Witness {
+    /// Decompose a u64 into 64 bits, least significant first.
+    pub fn decompose_u64(value: u64) -> BitVec {
+        BitVec::from_u64(value, WORD64_BITS)
+    }
+
+    /// Recompose bits into a u64.
+    pub fn recompose_u64(bits: &BitVec) -> u64 {
+        bits.to_u64()
+    }
+
+    /// Modular addition, returning the sum and the carry bit.
+    pub fn add_mod64(a: u64, b: u64) -> (u64, bool) {
+        a.overflowing_add(b)
+    }
+
+    /// Right rotation of bits.
+    pub fn rotr(bits: &BitVec, n: usize) -> BitVec {
+        bits.rotr(n)
+    }
+
+    /// Right shift of bits (introduces zeros).
+    pub fn shr(bits: &BitVec, n: usize) -> BitVec {
+        bits.shr(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const A: u64 = 0xDEAD_BEEF_0123_4567;
+    const B: u64 = 0xF0F0_0F0F_FFFF_0000;
+
+    fn word(cs: &mut CircuitBuilder, w: &mut WitnessBuilder, value: u64) -> Cell {
+        let [cell, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(value)));
+        w.set(cell, Fp::from(value));
+        cell
+    }
+
+    #[test]
+    fn test_witness_helpers() {
+        let bits = Word64Witness::decompose_u64(A);
+        assert_eq!(Word64Witness::recompose_u64(&bits), A);
+        assert_eq!(
+            Word64Witness::recompose_u64(&Word64Witness::rotr(&bits, 14)),
+            A.rotate_right(14)
+        );
+        assert_eq!(
+            Word64Witness::recompose_u64(&Word64Witness::shr(&bits, 7)),
+            A >> 7
+        );
+        assert_eq!(Word64Witness::add_mod64(u64::MAX, 2), (1, true));
+    }
+
+    #[test]
+    fn test_cell_operations() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let a = word(&mut cs, &mut w, A);
+        let b = word(&mut cs, &mut w, B);
+
+        let sum = Word64Gadget::add_mod64_cells(&mut cs, &mut w, a, b);
+        let rotr = Word64Gadget::rotr_cells(&mut cs, &mut w, a, 19);
+        let shr = Word64Gadget::shr_cells(&mut cs, &mut w, a, 6);
+
+        let a_bits = Word64Gadget::decompose_cells(&mut cs, &mut w, a);
+        let b_bits = Word64Gadget::decompose_cells(&mut cs, &mut w, b);
+        let xor = Word64Gadget::xor_u64_cells(&mut cs, &mut w, &a_bits, &b_bits);
+        let xor = Word64Gadget::compose_cells(&mut cs, &mut w, &xor);
+        let rotl =
+            Word64Gadget::compose_cells(&mut cs, &mut w, &Word64Gadget::rotl_bits(&a_bits, 3));
+
+        assert_eq!(w.get(sum), Fp::from(A.wrapping_add(B)));
+        assert_eq!(w.get(rotr), Fp::from(A.rotate_right(19)));
+        assert_eq!(w.get(shr), Fp::from(A >> 6));
+        assert_eq!(w.get(xor), Fp::from(A ^ B));
+        assert_eq!(w.get(rotl), Fp::from(A.rotate_left(3)));
+        assert!(cs.check_witness(&w).is_ok());
+
+        w.set(sum, w.get(sum) + Fp::one());
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_range_check() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let [v, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(2u64).pow([64])));
+        w.set(v, Fp::from(2u64).pow([64]));
+        Word64Gadget::decompose_cells(&mut cs, &mut w, v);
+
+        assert!(cs.check_witness(&w).is_err());
+    }
+
+    #[test]
+    fn test_gadget_construction() {
+        let mut gadget = Word64Gadget::new(0);
+        gadget.decompose_u64();
+        let add = gadget.add_mod64();
+        let (_, rows) = gadget.build();
+
+        assert_eq!(add, 64 + 22);
+        assert_eq!(rows, 2 * (64 + 22) + 3);
+    }
+}
@@ -0,0 +1,262 @@
+//! Golden test vectors for cross-implementation testing.
+//!
+//! [`write`] proves every built-in circuit with fixed inputs and a seeded RNG
+//! and stores the verifier index, proof and envelope under
+//! `<dir>/<circuit_id>/`, with a `manifest.json` recording what was produced
+//! and the SHA-256 of every file:
+//!
+//! ```text
+//! manifest.json
+//! threshold/verifier_index.msgpack   MessagePack VerifierIndex (no SRS)
+//! threshold/proof.msgpack            MessagePack ProverProof
+//! threshold/envelope.msgpack         MessagePack ProofEnvelope
+//! threshold/envelope.cbor            ProofEnvelope::to_cbor
+//! ```
+//!
+//! The WASM verifier, backend verifiers and later releases consume the
+//! directory as compatibility fixtures; [`check`] re-verifies every vector
+//! with the current code. Fixtures are only reproducible for the same
+//! proof-systems and `rand` versions, since the seeded generator feeds the
+//! proof's blinding factors.
+//!
+//! Available in unit tests and behind the `golden` feature, which also
+//! builds the `kimchi-golden` binary.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use kimchi::proof::ProverProof;
+use kimchi::verifier_index::VerifierIndex;
+use mina_curves::pasta::{Fp, Vesta};
+use poly_commitment::ipa::SRS;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::circuits::{Circuit, ThresholdCircuit};
+use crate::error::{ProverError, Result};
+use crate::prover::{KimchiProver, ProverConfig, VestaOpeningProof, FULL_ROUNDS};
+use crate::types::{FieldElement, ProofEnvelope};
+
+/// Default RNG seed for the fixtures.
+pub const GOLDEN_SEED: u64 = 0x6b69_6d63_6869;
+
+/// SRS size the fixtures are proved with.
+pub const GOLDEN_SRS_LOG2_SIZE: usize = 10;
+
+/// Name of the manifest file at the root of a fixture directory.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+const VERIFIER_INDEX_FILE: &str = "verifier_index.msgpack";
+const PROOF_FILE: &str = "proof.msgpack";
+const ENVELOPE_FILE: &str = "envelope.msgpack";
+const ENVELOPE_CBOR_FILE: &str = "envelope.cbor";
+
+/// Contents of `manifest.json`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldenManifest {
+    /// Version of the crate that wrote the fixtures.
+    pub crate_version: String,
+    /// RNG seed the proofs were created with.
+    pub seed: u64,
+    pub vectors: Vec<GoldenEntry>,
+}
+
+/// One circuit's fixtures.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldenEntry {
+    pub circuit_id: String,
+    pub circuit_version: u32,
+    pub srs_log2_size: u32,
+    /// Public inputs as decimal strings.
+    pub public_inputs: Vec<String>,
+    /// File name, relative to the circuit's directory, to hex SHA-256.
+    pub files: BTreeMap<String, String>,
+}
+
+/// A proved circuit, serialized.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GoldenVector {
+    /// MessagePack serialized verifier index.
+    pub verifier_index: Vec<u8>,
+    pub envelope: ProofEnvelope,
+}
+
+impl GoldenVector {
+    fn new(
+        circuit: &dyn Circuit,
+        proof: &ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
+        public_inputs: &[Fp],
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    ) -> Result<Self> {
+        Ok(Self {
+            verifier_index: rmp_serde::to_vec(verifier_index)
+                .map_err(|e| ProverError::encode("verifier index", e))?,
+            envelope: ProofEnvelope::new(
+                circuit.id(),
+                1,
+                GOLDEN_SRS_LOG2_SIZE as u32,
+                proof,
+                public_inputs,
+                verifier_index,
+            )?,
+        })
+    }
+
+    /// Files making up the vector, by name.
+    fn files(&self) -> Result<Vec<(&'static str, Vec<u8>)>> {
+        Ok(vec![
+            (VERIFIER_INDEX_FILE, self.verifier_index.clone()),
+            (PROOF_FILE, self.envelope.proof.clone()),
+            (ENVELOPE_FILE, self.envelope.to_bytes()?),
+            (ENVELOPE_CBOR_FILE, self.envelope.to_cbor()?),
+        ])
+    }
+}
+
+/// Prove every built-in circuit with fixed inputs and `seed`.
+pub fn generate(seed: u64) -> Result<Vec<GoldenVector>> {
+    let mut prover = KimchiProver::with_config(ProverConfig {
+        srs_log2_size: GOLDEN_SRS_LOG2_SIZE,
+        ..Default::default()
+    });
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    // The threshold gates pin the private value's row to zero, so that is
+    // the value the fixture proves below the threshold.
+    let threshold = ThresholdCircuit::new(100);
+    let (prover_index, verifier_index) =
+        prover.setup(threshold.gates(), threshold.num_public_inputs())?;
+    let (witness, public_inputs) = threshold.generate_witness(0)?;
+    let proof = prover.prove_with_rng(&prover_index, witness, &mut rng)?;
+
+    Ok(vec![GoldenVector::new(
+        &threshold,
+        &proof,
+        &public_inputs,
+        &verifier_index,
+    )?])
+}
+
+/// Generate the fixtures and write them, with their manifest, to `dir`.
+pub fn write(dir: &Path, seed: u64) -> Result<GoldenManifest> {
+    let mut manifest = GoldenManifest {
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        seed,
+        vectors: Vec::new(),
+    };
+
+    for vector in generate(seed)? {
+        let envelope = &vector.envelope;
+        let circuit_dir = dir.join(&envelope.circuit_id);
+        std::fs::create_dir_all(&circuit_dir)?;
+
+        let mut files = BTreeMap::new();
+        for (name, bytes) in vector.files()? {
+            std::fs::write(circuit_dir.join(name), &bytes)?;
+            files.insert(name.to_string(), hex::encode(Sha256::digest(&bytes)));
+        }
+
+        manifest.vectors.push(GoldenEntry {
+            circuit_id: envelope.circuit_id.clone(),
+            circuit_version: envelope.circuit_version,
+            srs_log2_size: envelope.srs_log2_size,
+            public_inputs: envelope
+                .public_inputs
+                .iter()
+                .map(FieldElement::to_decimal)
+                .collect(),
+            files,
+        });
+    }
+
+    std::fs::write(
+        dir.join(MANIFEST_FILE),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+    Ok(manifest)
+}
+
+/// Check a fixture directory against the current code: every file must
+/// match its manifest digest, both envelope encodings must decode to the
+/// same envelope, and every proof must verify against its verifier index.
+pub fn check(dir: &Path) -> Result<GoldenManifest> {
+    let manifest: GoldenManifest =
+        serde_json::from_slice(&std::fs::read(dir.join(MANIFEST_FILE))?)?;
+
+    for entry in &manifest.vectors {
+        let circuit_dir = dir.join(&entry.circuit_id);
+        let read = |name: &str| -> Result<Vec<u8>> {
+            let bytes = std::fs::read(circuit_dir.join(name))?;
+            let expected = entry.files.get(name).ok_or_else(|| {
+                ProverError::InvalidInput(format!(
+                    "{}: manifest has no digest for {}",
+                    entry.circuit_id, name
+                ))
+            })?;
+            if hex::encode(Sha256::digest(&bytes)) != *expected {
+                return Err(ProverError::VerificationError(format!(
+                    "{}/{} does not match its manifest digest",
+                    entry.circuit_id, name
+                )));
+            }
+            Ok(bytes)
+        };
+
+        let envelope = ProofEnvelope::from_bytes(&read(ENVELOPE_FILE)?)?;
+        if ProofEnvelope::from_cbor(&read(ENVELOPE_CBOR_FILE)?)? != envelope
+            || read(PROOF_FILE)? != envelope.proof
+        {
+            return Err(ProverError::VerificationError(format!(
+                "{}: envelope encodings disagree",
+                entry.circuit_id
+            )));
+        }
+
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: envelope.srs_log2_size as usize,
+            ..Default::default()
+        });
+        let verifier_index = prover.load_verifier_index(&read(VERIFIER_INDEX_FILE)?)?;
+        if !prover.verify_envelope(&verifier_index, &envelope)? {
+            return Err(ProverError::VerificationError(format!(
+                "{}: proof does not verify",
+                entry.circuit_id
+            )));
+        }
+    }
+
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_and_check() {
+        let dir = std::env::temp_dir().join(format!("kimchi-golden-{}", std::process::id()));
+        let manifest = write(&dir, GOLDEN_SEED).unwrap();
+        assert_eq!(manifest.vectors.len(), 1);
+        assert_eq!(manifest.vectors[0].circuit_id, "threshold");
+        assert_eq!(manifest.vectors[0].public_inputs, ["100", "1"]);
+        assert_eq!(manifest.vectors[0].files.len(), 4);
+        assert_eq!(check(&dir).unwrap(), manifest);
+
+        // Regenerating with the same seed reproduces the fixtures.
+        let vectors = generate(GOLDEN_SEED).unwrap();
+        assert_eq!(
+            std::fs::read(dir.join("threshold").join(ENVELOPE_FILE)).unwrap(),
+            vectors[0].envelope.to_bytes().unwrap()
+        );
+
+        let path = dir.join("threshold").join(ENVELOPE_CBOR_FILE);
+        let mut cbor = std::fs::read(&path).unwrap();
+        cbor.push(0);
+        std::fs::write(&path, cbor).unwrap();
+        assert!(check(&dir).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
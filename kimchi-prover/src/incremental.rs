@@ -0,0 +1,279 @@
+//! Witness generation in bounded time slices.
+//!
+//! Filling the witness of a large circuit, e.g. SHA-256 over a long message
+//! or an RSA verification, takes long enough that Android stops a worker
+//! holding the thread, or reports the app as not responding. An
+//! [`IncrementalWitness`] fills a [`WitnessLayout`] one step at a time (a
+//! hash block, a modular multiplication) and returns to the caller between
+//! steps once its time slice is used, so work can be spread over several
+//! slices and the thread handed back in between.
+//!
+//! Progress survives the worker being stopped: [`IncrementalWitness::checkpoint`]
+//! saves the rows filled so far and [`IncrementalWitness::restore`] resumes
+//! from them, in another process if need be. A checkpoint holds witness
+//! values, so keep it in app-private storage and delete it once the proof
+//! is made.
+
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use mina_curves::pasta::Fp;
+use zeroize::Zeroizing;
+
+use crate::builder::WitnessLayout;
+use crate::error::{ProverError, Result};
+use crate::prover::COLUMNS;
+
+/// Prefix of checkpoints: a magic number and the format version.
+const CHECKPOINT_HEADER: [u8; 4] = *b"KIW\x01";
+
+/// Bytes of a field element in a checkpoint.
+const ELEMENT_BYTES: usize = 32;
+
+/// A witness that can be filled in steps.
+///
+/// Steps are filled in order, except that after
+/// [`IncrementalWitness::restore`] the first step filled may be any; an
+/// implementation recomputes whatever native state a step depends on,
+/// such as a chaining value, when it has not filled the previous step.
+pub trait WitnessSteps {
+    /// Number of steps.
+    fn num_steps(&self) -> usize;
+
+    /// Write the rows of `step` into `layout`.
+    fn fill_step(&mut self, layout: &mut WitnessLayout, step: usize) -> Result<()>;
+}
+
+/// Steps filled out of the total.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WitnessProgress {
+    pub done: usize,
+    pub total: usize,
+}
+
+impl WitnessProgress {
+    /// Whether every step is filled.
+    pub fn is_done(&self) -> bool {
+        self.done == self.total
+    }
+}
+
+/// A witness being filled step by step.
+pub struct IncrementalWitness<S> {
+    steps: S,
+    layout: WitnessLayout,
+    next: usize,
+}
+
+impl<S: WitnessSteps> IncrementalWitness<S> {
+    /// Fill `steps` into `layout`, which may already hold other rows, e.g.
+    /// public inputs.
+    pub fn new(steps: S, layout: WitnessLayout) -> Self {
+        Self {
+            steps,
+            layout,
+            next: 0,
+        }
+    }
+
+    /// Resume from a [`checkpoint`](Self::checkpoint) of the same steps
+    /// into a layout for the same circuit.
+    pub fn restore(steps: S, mut layout: WitnessLayout, checkpoint: &[u8]) -> Result<Self> {
+        let invalid = |reason: &str| ProverError::decode("witness checkpoint", reason.to_string());
+        let payload = checkpoint
+            .strip_prefix(&CHECKPOINT_HEADER)
+            .ok_or_else(|| invalid("unknown format"))?;
+        let (header, values) = payload
+            .split_at_checked(32)
+            .ok_or_else(|| invalid("truncated"))?;
+        let [next, num_steps, circuit_rows, rows] = std::array::from_fn(|i| {
+            let bytes = header[8 * i..8 * (i + 1)].try_into().expect("8 bytes");
+            u64::from_le_bytes(bytes) as usize
+        });
+
+        if num_steps != steps.num_steps() || circuit_rows != layout.circuit_rows() {
+            return Err(ProverError::InvalidInput(format!(
+                "checkpoint of {} steps over {} rows does not match {} steps over {} rows",
+                num_steps,
+                circuit_rows,
+                steps.num_steps(),
+                layout.circuit_rows()
+            )));
+        }
+        if next > num_steps || rows > circuit_rows || values.len() != COLUMNS * rows * ELEMENT_BYTES
+        {
+            return Err(invalid("inconsistent sizes"));
+        }
+
+        let mut elements = values.chunks(ELEMENT_BYTES);
+        let mut columns: [Vec<Fp>; COLUMNS] = Default::default();
+        for column in columns.iter_mut() {
+            *column = elements
+                .by_ref()
+                .take(rows)
+                .map(Fp::deserialize_compressed)
+                .collect::<std::result::Result<_, _>>()
+                .map_err(|e| ProverError::decode("witness checkpoint", e))?;
+        }
+        let copied = layout.copy_rows(&columns, 0);
+        columns.iter_mut().for_each(zeroize::Zeroize::zeroize);
+        copied?;
+
+        Ok(Self {
+            steps,
+            layout,
+            next,
+        })
+    }
+
+    /// Steps filled so far.
+    pub fn progress(&self) -> WitnessProgress {
+        WitnessProgress {
+            done: self.next,
+            total: self.steps.num_steps(),
+        }
+    }
+
+    /// Fill the next step, if any.
+    pub fn step(&mut self) -> Result<WitnessProgress> {
+        if self.next < self.steps.num_steps() {
+            self.steps.fill_step(&mut self.layout, self.next)?;
+            self.next += 1;
+        }
+        Ok(self.progress())
+    }
+
+    /// Fill steps until done or until `should_yield` returns true, which is
+    /// asked after each step, e.g. whether the worker was stopped.
+    pub fn run_until(&mut self, mut should_yield: impl FnMut() -> bool) -> Result<WitnessProgress> {
+        loop {
+            let progress = self.step()?;
+            if progress.is_done() || should_yield() {
+                return Ok(progress);
+            }
+        }
+    }
+
+    /// Fill steps until done or until `slice` has passed. At least one step
+    /// is filled, so a slice shorter than a step still makes progress.
+    ///
+    /// Not available on `wasm32`, where time cannot be measured (see
+    /// [`crate::metrics::Timer`]); use `run_until` there.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn run_for(&mut self, slice: std::time::Duration) -> Result<WitnessProgress> {
+        let start = std::time::Instant::now();
+        self.run_until(|| start.elapsed() >= slice)
+    }
+
+    /// The rows filled so far and the next step, to pass to `restore`.
+    pub fn checkpoint(&self) -> Result<Zeroizing<Vec<u8>>> {
+        let rows = self.layout.used_rows();
+        let mut bytes = Zeroizing::new(Vec::with_capacity(
+            CHECKPOINT_HEADER.len() + 32 + COLUMNS * rows * ELEMENT_BYTES,
+        ));
+        bytes.extend_from_slice(&CHECKPOINT_HEADER);
+        for value in [
+            self.next,
+            self.steps.num_steps(),
+            self.layout.circuit_rows(),
+            rows,
+        ] {
+            bytes.extend_from_slice(&(value as u64).to_le_bytes());
+        }
+        for column in self.layout.columns() {
+            for value in &column[..rows] {
+                value
+                    .serialize_compressed(&mut *bytes)
+                    .map_err(|e| ProverError::encode("witness checkpoint", e))?;
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// The filled layout, once every step is.
+    pub fn finish(self) -> Result<WitnessLayout> {
+        let progress = self.progress();
+        if !progress.is_done() {
+            return Err(ProverError::WitnessError(format!(
+                "{} of {} witness steps filled",
+                progress.done, progress.total
+            )));
+        }
+        Ok(self.layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::Cell;
+    use crate::gadgets::sha256::{Sha256Gadget, Sha256Steps, Sha256Witness};
+    use std::time::Duration;
+
+    fn sha256_layout(len: usize) -> (Vec<kimchi::circuits::gate::CircuitGate<Fp>>, WitnessLayout) {
+        let mut gadget = Sha256Gadget::new(0);
+        gadget.hash_message(len);
+        let (gates, _) = gadget.build();
+        let layout = WitnessLayout::for_gates(&gates);
+        (gates, layout)
+    }
+
+    #[test]
+    fn test_matches_fill_columns() {
+        let message = [7u8; 150];
+        let (gates, mut expected) = sha256_layout(message.len());
+        Sha256Witness::new()
+            .fill_columns(&mut expected, 0, &message)
+            .unwrap();
+
+        let (_, layout) = sha256_layout(message.len());
+        let mut witness = IncrementalWitness::new(Sha256Steps::new(0, &message), layout);
+        let progress = witness.run_until(|| true).unwrap();
+        assert_eq!(progress, WitnessProgress { done: 1, total: 3 });
+        assert!(witness.finish().is_err());
+
+        let (_, layout) = sha256_layout(message.len());
+        let mut witness = IncrementalWitness::new(Sha256Steps::new(0, &message), layout);
+        assert!(witness.run_until(|| false).unwrap().is_done());
+        let columns = witness.finish().unwrap().into_columns();
+        assert_eq!(columns, expected.into_columns());
+        crate::diagnostics::check_witness(&gates, &columns, 0, false).unwrap();
+    }
+
+    #[test]
+    fn test_checkpoint_restore() {
+        let message = [1u8; 200];
+        let (gates, mut layout) = sha256_layout(message.len());
+        // A row outside the steps survives the checkpoint too
+        layout.set(Cell::new(0, 14), Fp::from(5u64));
+        let mut witness = IncrementalWitness::new(Sha256Steps::new(0, &message), layout);
+        witness.step().unwrap();
+        witness.step().unwrap();
+        let checkpoint = witness.checkpoint().unwrap();
+        drop(witness);
+
+        // Resumed in a fresh "process", with no chaining value cached
+        let (_, layout) = sha256_layout(message.len());
+        let mut witness =
+            IncrementalWitness::restore(Sha256Steps::new(0, &message), layout, &checkpoint)
+                .unwrap();
+        assert_eq!(witness.progress().done, 2);
+        while !witness.run_for(Duration::ZERO).unwrap().is_done() {}
+        let mut columns = witness.finish().unwrap().into_columns();
+        assert_eq!(columns[14][0], Fp::from(5u64));
+        columns[14][0] = Fp::from(0u64);
+        crate::diagnostics::check_witness(&gates, &columns, 0, false).unwrap();
+
+        // Checkpoints only resume the steps they were taken from
+        let (_, layout) = sha256_layout(message.len());
+        assert!(matches!(
+            IncrementalWitness::restore(Sha256Steps::new(0, &[1u8; 10]), layout, &checkpoint),
+            Err(ProverError::InvalidInput(_))
+        ));
+        let (_, layout) = sha256_layout(message.len());
+        assert!(IncrementalWitness::restore(
+            Sha256Steps::new(0, &message),
+            layout,
+            &checkpoint[..checkpoint.len() - 1]
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,312 @@
+//! Verifier indices looked up by digest or circuit, so proofs need not
+//! carry them.
+//!
+//! A [`ProofEnvelope`] names its verifier index by digest. A
+//! [`VerifierIndexRegistry`] maps digests, and the circuit id and version
+//! they were registered under, to serialized verifier indices (as from
+//! `export_verifier_index`); [`KimchiVerifier`] resolves the index of each
+//! envelope through it instead of every index being added up front.
+//!
+//! Indices the registry does not hold are fetched from an optional
+//! [`VerifierIndexStore`] backend: a [`FileIndexStore`] directory, or the
+//! app's own (e.g. over HTTP from its backend). Fetched bytes must hash to
+//! the requested digest, so a backend cannot substitute another circuit's
+//! index.
+//!
+//! [`KimchiVerifier`]: crate::verifier::KimchiVerifier
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+use sha2::{Digest, Sha256};
+
+use crate::error::{ProverError, Result};
+use crate::types::ProofEnvelope;
+
+/// Backend holding serialized verifier indices by digest.
+pub trait VerifierIndexStore: Send + Sync {
+    /// The verifier index with `digest`, or `None` if unknown.
+    fn fetch(&self, digest: &[u8; 32]) -> Result<Option<Vec<u8>>>;
+
+    /// Keep a verifier index. Read-only backends ignore it.
+    fn store(&self, _digest: &[u8; 32], _bytes: &[u8]) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Verifier indices as `<hex digest>.vi` files in a directory.
+#[derive(Clone, Debug)]
+pub struct FileIndexStore {
+    dir: PathBuf,
+}
+
+impl FileIndexStore {
+    /// A store in `dir`, which is created on first write.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The store directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, digest: &[u8; 32]) -> PathBuf {
+        self.dir.join(format!("{}.vi", hex::encode(digest)))
+    }
+}
+
+impl VerifierIndexStore for FileIndexStore {
+    fn fetch(&self, digest: &[u8; 32]) -> Result<Option<Vec<u8>>> {
+        match fs::read(self.path(digest)) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn store(&self, digest: &[u8; 32], bytes: &[u8]) -> Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(digest);
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, &path).inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })?;
+        Ok(())
+    }
+}
+
+struct Entry {
+    /// Circuit id and version the index was registered under, if any.
+    circuit: Option<(String, u32)>,
+    bytes: Arc<Vec<u8>>,
+}
+
+/// Serialized verifier indices by digest and circuit.
+#[derive(Default)]
+pub struct VerifierIndexRegistry {
+    entries: RwLock<HashMap<[u8; 32], Entry>>,
+    backend: Option<Arc<dyn VerifierIndexStore>>,
+}
+
+impl VerifierIndexRegistry {
+    /// An empty in-memory registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetch indices the registry does not hold from `backend`, and keep
+    /// registered ones there.
+    pub fn with_backend(mut self, backend: Arc<dyn VerifierIndexStore>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Register the serialized verifier index of version `circuit_version`
+    /// of circuit `circuit_id`, replacing any previous index of it.
+    ///
+    /// Returns its digest.
+    pub fn register(
+        &self,
+        circuit_id: impl Into<String>,
+        circuit_version: u32,
+        bytes: Vec<u8>,
+    ) -> Result<[u8; 32]> {
+        let digest: [u8; 32] = Sha256::digest(&bytes).into();
+        if let Some(backend) = &self.backend {
+            backend.store(&digest, &bytes)?;
+        }
+        let circuit = (circuit_id.into(), circuit_version);
+        let mut entries = self.write()?;
+        entries.retain(|_, entry| entry.circuit.as_ref() != Some(&circuit));
+        entries.insert(
+            digest,
+            Entry {
+                circuit: Some(circuit),
+                bytes: Arc::new(bytes),
+            },
+        );
+        Ok(digest)
+    }
+
+    /// Drop the verifier index with `digest` from memory. Returns whether
+    /// it was held.
+    pub fn remove(&self, digest: &[u8; 32]) -> Result<bool> {
+        Ok(self.write()?.remove(digest).is_some())
+    }
+
+    /// Number of verifier indices held in memory.
+    pub fn len(&self) -> usize {
+        self.entries.read().map_or(0, |entries| entries.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Digest of the index registered for `circuit_id` at `circuit_version`.
+    pub fn digest_for(&self, circuit_id: &str, circuit_version: u32) -> Option<[u8; 32]> {
+        let entries = self.entries.read().ok()?;
+        entries.iter().find_map(|(digest, entry)| {
+            let (id, version) = entry.circuit.as_ref()?;
+            (id == circuit_id && *version == circuit_version).then_some(*digest)
+        })
+    }
+
+    /// Circuit id and version the index with `digest` was registered
+    /// under, if it was.
+    pub fn circuit(&self, digest: &[u8; 32]) -> Option<(String, u32)> {
+        self.entries.read().ok()?.get(digest)?.circuit.clone()
+    }
+
+    /// The verifier index with `digest`, from memory or the backend.
+    pub fn get(&self, digest: &[u8; 32]) -> Result<Option<Arc<Vec<u8>>>> {
+        if let Some(entry) = self.read()?.get(digest) {
+            return Ok(Some(entry.bytes.clone()));
+        }
+        let Some(bytes) = self
+            .backend
+            .as_ref()
+            .map(|backend| backend.fetch(digest))
+            .transpose()?
+            .flatten()
+        else {
+            return Ok(None);
+        };
+        if Sha256::digest(&bytes).as_slice() != digest {
+            return Err(ProverError::VerificationError(format!(
+                "verifier index store returned another index for {}",
+                hex::encode(digest)
+            )));
+        }
+
+        let bytes = Arc::new(bytes);
+        self.write()?.entry(*digest).or_insert(Entry {
+            circuit: None,
+            bytes: bytes.clone(),
+        });
+        Ok(Some(bytes))
+    }
+
+    /// Check that `envelope` names the circuit its verifier index was
+    /// registered under, if it was.
+    pub fn check_circuit(&self, envelope: &ProofEnvelope) -> Result<()> {
+        let entries = self.read()?;
+        match entries
+            .get(&envelope.verifier_index_digest)
+            .and_then(|entry| entry.circuit.as_ref())
+        {
+            Some((id, version))
+                if *id != envelope.circuit_id || *version != envelope.circuit_version =>
+            {
+                Err(ProverError::VerifierIndexMismatch)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The verifier index `envelope` names.
+    ///
+    /// Fails with `VerifierIndexMismatch` if it is unknown or registered
+    /// for another circuit than the envelope's.
+    pub fn resolve(&self, envelope: &ProofEnvelope) -> Result<Arc<Vec<u8>>> {
+        let bytes = self
+            .get(&envelope.verifier_index_digest)?
+            .ok_or(ProverError::VerifierIndexMismatch)?;
+        self.check_circuit(envelope)?;
+        Ok(bytes)
+    }
+
+    fn read(&self) -> Result<std::sync::RwLockReadGuard<'_, HashMap<[u8; 32], Entry>>> {
+        self.entries
+            .read()
+            .map_err(|_| ProverError::InternalError("verifier index registry poisoned".into()))
+    }
+
+    fn write(&self) -> Result<std::sync::RwLockWriteGuard<'_, HashMap<[u8; 32], Entry>>> {
+        self.entries
+            .write()
+            .map_err(|_| ProverError::InternalError("verifier index registry poisoned".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kimchi-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn envelope(circuit_id: &str, circuit_version: u32, digest: [u8; 32]) -> ProofEnvelope {
+        ProofEnvelope {
+            version: crate::PROOF_ENVELOPE_VERSION,
+            circuit_id: circuit_id.into(),
+            circuit_version,
+            srs_log2_size: 10,
+            proof: Vec::new(),
+            public_inputs: Vec::new(),
+            verifier_index_digest: digest,
+            circuit_hash: [0; 32],
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let dir = temp_dir("index-registry");
+        let store = Arc::new(FileIndexStore::new(&dir));
+        let registry = VerifierIndexRegistry::new().with_backend(store.clone());
+        assert!(registry.is_empty());
+
+        let digest = registry
+            .register("threshold", 1, b"index v1".to_vec())
+            .unwrap();
+        assert_eq!(registry.digest_for("threshold", 1), Some(digest));
+        assert_eq!(registry.digest_for("threshold", 2), None);
+        assert_eq!(
+            registry
+                .resolve(&envelope("threshold", 1, digest))
+                .unwrap()
+                .as_slice(),
+            b"index v1"
+        );
+        assert!(matches!(
+            registry.resolve(&envelope("merkle", 1, digest)),
+            Err(ProverError::VerifierIndexMismatch)
+        ));
+        assert!(matches!(
+            registry.resolve(&envelope("threshold", 1, [0; 32])),
+            Err(ProverError::VerifierIndexMismatch)
+        ));
+
+        // Re-registering a circuit replaces its index
+        let digest2 = registry
+            .register("threshold", 1, b"index v2".to_vec())
+            .unwrap();
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.digest_for("threshold", 1), Some(digest2));
+
+        // A fresh registry finds both in the store
+        let other = VerifierIndexRegistry::new().with_backend(store.clone());
+        assert_eq!(other.get(&digest).unwrap().unwrap().as_slice(), b"index v1");
+        assert!(other.resolve(&envelope("any", 7, digest)).is_ok());
+        assert_eq!(other.len(), 1);
+        assert!(other.remove(&digest).unwrap());
+
+        // Bytes that do not hash to the digest are refused
+        fs::write(store.path(&digest), b"another index").unwrap();
+        assert!(matches!(
+            other.get(&digest),
+            Err(ProverError::VerificationError(_))
+        ));
+        assert!(VerifierIndexRegistry::new().get(&digest).unwrap().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -7,6 +7,42 @@
 //! This crate provides the core proving infrastructure for generating
 //! zero-knowledge proofs on mobile devices using the Kimchi proof system.
 //!
+//! ## Platforms
+//!
+//! Everything here, proving included, also builds for
+//! `wasm32-unknown-unknown`:
+//!
+//! ```text
+//! cargo build -p kimchi-prover --target wasm32-unknown-unknown \
+//!     --no-default-features --features wasm
+//! ```
+//!
+//! The `parallel` feature (on by default) creates the SRS on multiple
+//! threads; disable it for single-threaded runtimes. Durations are not
+//! measured on wasm32 (see [`metrics::Timer`]).
+//!
+//! ## Zeroization
+//!
+//! Witnesses hold the secrets being proven about, so the buffers this crate
+//! fills are wiped with `zeroize` rather than just freed:
+//!
+//! - [`WitnessBuilder`], [`types::Witness`], [`Sha256Witness`] and
+//!   [`RsaWitness`] wipe their values when dropped;
+//! - [`KimchiProver::prove`] wipes the witness columns if it fails before
+//!   handing them to Kimchi, e.g. on an unsatisfied constraint.
+//!
+//! Kimchi takes ownership of the columns passed to `prove`: it frees them,
+//! and the polynomials it derives from them, without wiping. Values copied
+//! out of these buffers (e.g. with `WitnessBuilder::get`) are the caller's
+//! to wipe; holding them in a [`Secret`] does that, keeps them out of
+//! `Debug` output and compares them in constant time.
+//!
+//! Wiping does not help with copies the OS made while the values were
+//! alive: with [`ProverConfig::secure_memory`], `prove` locks the witness
+//! columns into RAM so they are not swapped out, and a [`GuardedBuffer`]
+//! keeps secret inputs in locked memory between guard pages (see
+//! [`secure_mem`]).
+//!
 //! ## Example
 //!
 //! ```rust,ignore
@@ -35,21 +71,71 @@
 //! assert!(valid);
 //! ```
 
+#[cfg(any(test, feature = "testing"))]
+pub mod blinding;
+pub mod builder;
+pub mod cache;
 pub mod circuits;
+pub mod cost;
+pub mod diagnostics;
+pub mod disk_cache;
 pub mod error;
 pub mod gadgets;
+#[cfg(any(test, feature = "golden"))]
+pub mod golden;
+pub mod incremental;
+pub mod index_registry;
+pub mod metrics;
+pub mod mina;
+pub mod profiling;
 pub mod prover;
+pub mod secure_mem;
+pub mod session;
+pub mod signing;
+#[cfg(any(test, feature = "soundness"))]
+pub mod soundness;
+pub mod srs_provider;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod types;
+pub mod verifier;
+pub mod zkapp;
 
+pub use builder::{Cell, CircuitBuilder, Gadget, Region, WitnessBuilder, WitnessLayout};
+pub use cache::CircuitCache;
+pub use cost::{CostReport, GadgetCost, ProvingTimeClass, SetupInfo};
+pub use disk_cache::DiskCache;
 pub use error::{ProverError, Result};
+pub use incremental::{IncrementalWitness, WitnessProgress, WitnessSteps};
+pub use index_registry::{FileIndexStore, VerifierIndexRegistry, VerifierIndexStore};
+pub use metrics::{MetricsAggregator, MetricsSink, MetricsSnapshot, NoopMetrics};
+pub use mina::{MinaProofJson, MinaVerificationKeyJson};
 pub use prover::{KimchiProver, ProverConfig, VestaOpeningProof, COLUMNS, FULL_ROUNDS};
-pub use types::FieldElement;
+pub use secure_mem::{GuardedBuffer, SecureMemory};
+pub use session::ProvingSession;
+pub use signing::{EnvelopeSigner, SignedEnvelope};
+pub use srs_provider::{BundledSrs, DownloadSrs, GenerateSrs, SrsProvider};
+pub use types::{
+    BitVec, Bytes256, Bytes32, Bytes64, CoseSign1, FieldElement, HexWriter, ProofEnvelope,
+    ScalarElement, Secret, PROOF_ENVELOPE_VERSION,
+};
+pub use verifier::KimchiVerifier;
+
+pub use zkapp::ZkappSubmission;
 
 // Re-export circuit types
-pub use circuits::ThresholdCircuit;
+pub use circuits::{
+    circuit_hash, AttestationPlatform, AttestedCircuit, CircomCircuit, Circuit, CircuitInfo,
+    DeviceAttestation, MerkleMembershipCircuit, O1jsCircuit, PublicInputSchema, PublicInputType,
+    PublicInputsDocument, PublicValue, ThresholdCircuit, ThresholdOperator, TimestampedCircuit,
+    TrustedIssuerCircuit, ValueWidth, WideThresholdCircuit,
+};
 
 // Re-export gadget types
-pub use gadgets::{RsaGadget, RsaWitness, Sha256Gadget, Sha256Witness};
+pub use gadgets::{
+    DigestStep, HashFunction, MerkleStep, RsaGadget, RsaRowPlan, RsaWitness, Salt, Sha256Gadget,
+    Sha256Witness,
+};
 
 // Re-export key types from the proof-systems crates
 pub use mina_curves::pasta::{Fp, Fq, Pallas, Vesta};
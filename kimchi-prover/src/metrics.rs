@@ -0,0 +1,337 @@
+//! Metrics hooks.
+//!
+//! `KimchiProver` reports each phase (SRS creation, setup, proving,
+//! verification) to a [`MetricsSink`]: a `<phase>.count` counter, a
+//! `<phase>.duration_ms` histogram, and an error event when the phase
+//! fails. The default sink discards everything; a [`MetricsAggregator`]
+//! keeps totals and recent observations for apps to report themselves.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::error::{ProverError, Result};
+
+/// Prefix of every metric name reported by this crate.
+pub const METRIC_PREFIX: &str = "kimchi";
+
+/// Receiver for prover metrics. All methods default to doing nothing.
+pub trait MetricsSink: Send + Sync {
+    /// Add `value` to the counter `name`.
+    fn counter(&self, _name: &str, _value: u64) {}
+
+    /// Record one observation of the histogram `name`.
+    fn histogram(&self, _name: &str, _value: f64) {}
+
+    /// A phase failed.
+    fn error(&self, _phase: &str, _error: &ProverError) {}
+}
+
+/// Sink that discards all metrics.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopMetrics;
+
+impl MetricsSink for NoopMetrics {}
+
+/// A phase the prover reports on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    SrsInit,
+    Setup,
+    /// Witness generation, which callers do outside the prover. Only
+    /// traced (see [`crate::profiling`]), not reported.
+    Witness,
+    Prove,
+    Verify,
+}
+
+impl Phase {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::SrsInit => "srs_init",
+            Self::Setup => "setup",
+            Self::Witness => "witness",
+            Self::Prove => "prove",
+            Self::Verify => "verify",
+        }
+    }
+
+    /// Full metric name for `suffix` in this phase, e.g. `kimchi.prove.count`.
+    pub fn metric(&self, suffix: &str) -> String {
+        format!("{}.{}.{}", METRIC_PREFIX, self.name(), suffix)
+    }
+}
+
+/// Wall-clock timer for a phase.
+///
+/// `std::time::Instant` panics on `wasm32-unknown-unknown`, so durations are
+/// not measured there.
+pub struct Timer {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+}
+
+impl Timer {
+    pub fn start() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Milliseconds since `start`, if measurable on this target.
+    pub fn elapsed_ms(&self) -> Option<f64> {
+        #[cfg(not(target_arch = "wasm32"))]
+        return Some(self.start.elapsed().as_secs_f64() * 1000.0);
+        #[cfg(target_arch = "wasm32")]
+        return None;
+    }
+}
+
+/// Observations of each histogram an aggregator keeps by default.
+pub const DEFAULT_WINDOW: usize = 1024;
+
+/// Sink that keeps what it is sent: counter totals, failures by error
+/// code, and the latest observations of each histogram for percentiles.
+pub struct MetricsAggregator {
+    window: usize,
+    state: Mutex<Aggregate>,
+}
+
+#[derive(Default)]
+struct Aggregate {
+    counters: BTreeMap<String, u64>,
+    errors_by_code: BTreeMap<u32, u64>,
+    histograms: BTreeMap<String, VecDeque<f64>>,
+}
+
+/// Summary of the latest observations of a histogram.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Percentiles {
+    /// Observations summarized, at most the aggregator's window.
+    pub samples: usize,
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+}
+
+impl Percentiles {
+    fn of(values: &VecDeque<f64>) -> Self {
+        let mut sorted: Vec<f64> = values.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        // Nearest rank
+        let rank = |p: f64| {
+            let i = (p * sorted.len() as f64).ceil() as usize;
+            sorted.get(i.saturating_sub(1)).copied().unwrap_or(0.0)
+        };
+        Self {
+            samples: sorted.len(),
+            p50: rank(0.5),
+            p90: rank(0.9),
+            p99: rank(0.99),
+        }
+    }
+}
+
+/// What a [`MetricsAggregator`] holds at one point.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    /// Counter totals by name.
+    pub counters: BTreeMap<String, u64>,
+    /// Failures by `ProverError` code.
+    pub errors_by_code: BTreeMap<u32, u64>,
+    /// Histograms by name.
+    pub histograms: BTreeMap<String, Percentiles>,
+}
+
+impl MetricsSnapshot {
+    /// Total of the counter `name`, 0 if never reported.
+    pub fn counter(&self, name: &str) -> u64 {
+        self.counters.get(name).copied().unwrap_or(0)
+    }
+
+    /// Successful runs of `phase`.
+    pub fn successes(&self, phase: Phase) -> u64 {
+        self.counter(&phase.metric("count"))
+            .saturating_sub(self.counter(&phase.metric("errors")))
+    }
+}
+
+impl MetricsAggregator {
+    /// An aggregator keeping the last [`DEFAULT_WINDOW`] observations of
+    /// each histogram.
+    pub fn new() -> Self {
+        Self::with_window(DEFAULT_WINDOW)
+    }
+
+    /// An aggregator keeping the last `window` observations of each
+    /// histogram.
+    pub fn with_window(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            state: Mutex::default(),
+        }
+    }
+
+    /// Totals and percentiles of everything reported so far.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let Ok(state) = self.state.lock() else {
+            return MetricsSnapshot::default();
+        };
+        MetricsSnapshot {
+            counters: state.counters.clone(),
+            errors_by_code: state.errors_by_code.clone(),
+            histograms: state
+                .histograms
+                .iter()
+                .map(|(name, values)| (name.clone(), Percentiles::of(values)))
+                .collect(),
+        }
+    }
+
+    /// Forget everything reported so far.
+    pub fn reset(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            *state = Aggregate::default();
+        }
+    }
+}
+
+impl Default for MetricsAggregator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricsSink for MetricsAggregator {
+    fn counter(&self, name: &str, value: u64) {
+        if let Ok(mut state) = self.state.lock() {
+            let total = state.counters.entry(name.to_string()).or_default();
+            *total = total.saturating_add(value);
+        }
+    }
+
+    fn histogram(&self, name: &str, value: f64) {
+        if let Ok(mut state) = self.state.lock() {
+            let values = state.histograms.entry(name.to_string()).or_default();
+            if values.len() == self.window {
+                values.pop_front();
+            }
+            values.push_back(value);
+        }
+    }
+
+    fn error(&self, _phase: &str, error: &ProverError) {
+        if let Ok(mut state) = self.state.lock() {
+            *state.errors_by_code.entry(error.code()).or_default() += 1;
+        }
+    }
+}
+
+/// Report the outcome of a phase to `sink`.
+pub fn record<T>(sink: &Arc<dyn MetricsSink>, phase: Phase, timer: &Timer, result: &Result<T>) {
+    sink.counter(&phase.metric("count"), 1);
+    if let Some(ms) = timer.elapsed_ms() {
+        sink.histogram(&phase.metric("duration_ms"), ms);
+    }
+    if let Err(e) = result {
+        sink.counter(&phase.metric("errors"), 1);
+        sink.error(phase.name(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    impl MetricsSink for Recorder {
+        fn counter(&self, name: &str, value: u64) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{}+{}", name, value));
+        }
+
+        fn histogram(&self, name: &str, _value: f64) {
+            self.events.lock().unwrap().push(name.to_string());
+        }
+
+        fn error(&self, phase: &str, error: &ProverError) {
+            self.events
+                .lock()
+                .unwrap()
+                .push(format!("{} failed: {}", phase, error.code()));
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let recorder = Arc::new(Recorder::default());
+        let sink: Arc<dyn MetricsSink> = recorder.clone();
+
+        record(&sink, Phase::Prove, &Timer::start(), &Ok(()));
+        record::<()>(
+            &sink,
+            Phase::Verify,
+            &Timer::start(),
+            &Err(ProverError::VerifierIndexMismatch),
+        );
+
+        assert_eq!(
+            *recorder.events.lock().unwrap(),
+            vec![
+                "kimchi.prove.count+1",
+                "kimchi.prove.duration_ms",
+                "kimchi.verify.count+1",
+                "kimchi.verify.duration_ms",
+                "kimchi.verify.errors+1",
+                "verify failed: 401",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_aggregator() {
+        let aggregator = Arc::new(MetricsAggregator::with_window(100));
+        let sink: Arc<dyn MetricsSink> = aggregator.clone();
+
+        for _ in 0..3 {
+            record(&sink, Phase::Prove, &Timer::start(), &Ok(()));
+        }
+        record::<()>(
+            &sink,
+            Phase::Prove,
+            &Timer::start(),
+            &Err(ProverError::VerifierIndexMismatch),
+        );
+        for ms in 1..=150 {
+            sink.histogram("kimchi.verify.duration_ms", ms as f64);
+        }
+
+        let snapshot = aggregator.snapshot();
+        assert_eq!(snapshot.counter("kimchi.prove.count"), 4);
+        assert_eq!(snapshot.successes(Phase::Prove), 3);
+        assert_eq!(snapshot.successes(Phase::Verify), 0);
+        assert_eq!(
+            snapshot.errors_by_code,
+            BTreeMap::from([(ProverError::VerifierIndexMismatch.code(), 1)])
+        );
+        // Only the last 100 observations, 51 to 150
+        assert_eq!(
+            snapshot.histograms["kimchi.verify.duration_ms"],
+            Percentiles {
+                samples: 100,
+                p50: 100.0,
+                p90: 140.0,
+                p99: 149.0,
+            }
+        );
+
+        aggregator.reset();
+        assert_eq!(aggregator.snapshot(), MetricsSnapshot::default());
+    }
+}
@@ -0,0 +1,282 @@
+//! Proof and verification key JSON in the shapes used by o1js and Mina tooling.
+//!
+//! o1js exchanges proofs as `JsonProof`
+//! (`{ publicInput, publicOutput, maxProofsVerified, proof }`) and
+//! verification keys as `{ data, hash }`, with field elements as decimal
+//! strings and binary payloads as standard base64. The payloads here are the
+//! MessagePack encodings this crate already uses, so tooling that reads the
+//! o1js JSON shape can carry them unchanged. Proofs are plain Kimchi proofs,
+//! not Pickles-wrapped, so `maxProofsVerified` is always 0.
+//!
+//! As in o1js, a verification key's hash is the Poseidon hash of its field
+//! representation ([`MinaVerificationKeyJson::to_fields`]), not of its bytes,
+//! so it identifies the circuit independently of the serialization.
+
+use ark_ff::{BigInteger, PrimeField};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use kimchi::proof::ProverProof;
+use kimchi::verifier_index::VerifierIndex;
+use mina_curves::pasta::{Fp, Fq, Vesta};
+use poly_commitment::ipa::SRS;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ProverError, Result};
+use crate::gadgets::poseidon::PoseidonWitness;
+use crate::prover::{VestaOpeningProof, FULL_ROUNDS};
+use crate::types::{FieldElement, ProofEnvelope};
+
+/// A proof in the o1js `JsonProof` shape.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MinaProofJson {
+    /// Public inputs as decimal strings.
+    pub public_input: Vec<String>,
+    /// Public outputs as decimal strings (always empty).
+    pub public_output: Vec<String>,
+    /// Number of recursively verified proofs (always 0).
+    pub max_proofs_verified: u8,
+    /// Base64 of the MessagePack serialized proof.
+    pub proof: String,
+}
+
+impl MinaProofJson {
+    /// Encode a proof and its public inputs.
+    pub fn from_proof(
+        proof: &ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
+        public_inputs: &[Fp],
+    ) -> Result<Self> {
+        let bytes = rmp_serde::to_vec(proof).map_err(|e| ProverError::encode("proof", e))?;
+        Ok(Self::from_parts(&bytes, public_inputs))
+    }
+
+    /// Encode the proof carried by an envelope.
+    pub fn from_envelope(envelope: &ProofEnvelope) -> Self {
+        Self::from_parts(&envelope.proof, &envelope.public_inputs_fp())
+    }
+
+    fn from_parts(proof_bytes: &[u8], public_inputs: &[Fp]) -> Self {
+        Self {
+            public_input: public_inputs
+                .iter()
+                .map(|fp| FieldElement(*fp).to_decimal())
+                .collect(),
+            public_output: Vec::new(),
+            max_proofs_verified: 0,
+            proof: BASE64.encode(proof_bytes),
+        }
+    }
+
+    /// Decode the proof.
+    pub fn proof(&self) -> Result<ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>> {
+        if self.max_proofs_verified != 0 || !self.public_output.is_empty() {
+            return Err(ProverError::SerializationError(
+                "Recursive proofs and public outputs are not supported".into(),
+            ));
+        }
+        let bytes = decode_base64(&self.proof, "proof base64")?;
+        rmp_serde::from_slice(&bytes).map_err(|e| ProverError::decode("proof", e))
+    }
+
+    /// Decode the public inputs.
+    pub fn public_inputs(&self) -> Result<Vec<Fp>> {
+        self.public_input
+            .iter()
+            .map(|s| {
+                FieldElement::from_decimal(s)
+                    .map(|f| f.0)
+                    .map_err(ProverError::SerializationError)
+            })
+            .collect()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// A verification key in the o1js `{ data, hash }` shape.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MinaVerificationKeyJson {
+    /// Base64 of the MessagePack serialized verifier index (without SRS).
+    pub data: String,
+    /// Poseidon hash of the key's fields, as a decimal string.
+    pub hash: String,
+}
+
+impl MinaVerificationKeyJson {
+    /// Encode a verifier index.
+    pub fn from_verifier_index(
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    ) -> Result<Self> {
+        let bytes = rmp_serde::to_vec(verifier_index)
+            .map_err(|e| ProverError::encode("verifier index", e))?;
+        Ok(Self {
+            data: BASE64.encode(&bytes),
+            hash: FieldElement(Self::hash(verifier_index)).to_decimal(),
+        })
+    }
+
+    /// Decode the verifier index, checking `hash` against it.
+    ///
+    /// The SRS is not serialized; callers inject their own before verifying.
+    pub fn verifier_index(&self) -> Result<VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>> {
+        let bytes = decode_base64(&self.data, "verification key base64")?;
+        let hash =
+            FieldElement::from_decimal(&self.hash).map_err(ProverError::SerializationError)?;
+        let verifier_index: VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>> =
+            rmp_serde::from_slice(&bytes).map_err(|e| ProverError::decode("verifier index", e))?;
+        if hash.0 != Self::hash(&verifier_index) {
+            return Err(ProverError::VerificationError(
+                "Verification key hash does not match its data".into(),
+            ));
+        }
+        Ok(verifier_index)
+    }
+
+    /// Field representation of a verifier index, in the order Pickles lays
+    /// out a side-loaded verification key: `maxProofsVerified` one-hot over
+    /// 0..=2, the log2 domain size, then every chunk of the sigma,
+    /// coefficient, generic, permutation, complete-add, mul, emul and
+    /// endomul-scalar commitments as `x, y`.
+    ///
+    /// Commitment coordinates live in Fq, which is larger than Fp, so each
+    /// is split into its high bits and low bit as Pickles does when passing
+    /// values across the Pasta cycle.
+    pub fn to_fields(verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>) -> Vec<Fp> {
+        let vi = verifier_index;
+        let mut fields = vec![
+            Fp::from(1u64),
+            Fp::from(0u64),
+            Fp::from(0u64),
+            Fp::from(vi.domain.log_size_of_group),
+        ];
+        let commitments = vi.sigma_comm.iter().chain(&vi.coefficients_comm).chain([
+            &vi.generic_comm,
+            &vi.psm_comm,
+            &vi.complete_add_comm,
+            &vi.mul_comm,
+            &vi.emul_comm,
+            &vi.endomul_scalar_comm,
+        ]);
+        for point in commitments.flat_map(|comm| &comm.chunks) {
+            fields.extend(split_fq(point.x));
+            fields.extend(split_fq(point.y));
+        }
+        fields
+    }
+
+    /// Poseidon hash of [`Self::to_fields`].
+    pub fn hash(verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>) -> Fp {
+        PoseidonWitness::hash(&Self::to_fields(verifier_index))
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+/// Split an Fq element into `x >> 1` and its low bit, both of which fit in Fp.
+fn split_fq(x: Fq) -> [Fp; 2] {
+    let mut bits = x.into_bigint();
+    let low = bits.is_odd();
+    bits.div2();
+    [
+        Fp::from_bigint(bits).expect("x >> 1 is below the Fp modulus"),
+        Fp::from(low),
+    ]
+}
+
+fn decode_base64(s: &str, what: &'static str) -> Result<Vec<u8>> {
+    BASE64.decode(s).map_err(|e| ProverError::decode(what, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{CircuitBuilder, WitnessBuilder};
+    use crate::prover::{KimchiProver, ProverConfig};
+    use kimchi::circuits::polynomials::generic::GenericGateSpec;
+
+    #[test]
+    fn test_proof_and_key_round_trip() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        for v in [3u64, 4] {
+            let [cell, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(v)));
+            w.set(cell, Fp::from(v));
+        }
+
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            debug: false,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover.setup(cs.build(), 0).unwrap();
+        let proof = prover.prove(&prover_index, w.columns()).unwrap();
+
+        let json = MinaProofJson::from_proof(&proof, &[])
+            .unwrap()
+            .to_json()
+            .unwrap();
+        assert!(json.contains("\"publicInput\":[]"));
+        assert!(json.contains("\"maxProofsVerified\":0"));
+        let decoded = MinaProofJson::from_json(&json).unwrap();
+
+        let vk = MinaVerificationKeyJson::from_verifier_index(&verifier_index).unwrap();
+        // Header plus x and y, split in two, for 28 single-chunk commitments.
+        assert_eq!(
+            MinaVerificationKeyJson::to_fields(&verifier_index).len(),
+            4 + 28 * 4
+        );
+        let vk = MinaVerificationKeyJson::from_json(&vk.to_json().unwrap()).unwrap();
+        assert_eq!(
+            ProofEnvelope::verifier_index_digest(&vk.verifier_index().unwrap()).unwrap(),
+            ProofEnvelope::verifier_index_digest(&verifier_index).unwrap()
+        );
+
+        assert!(prover
+            .verify(
+                &verifier_index,
+                &decoded.proof().unwrap(),
+                &decoded.public_inputs().unwrap()
+            )
+            .unwrap());
+
+        let tampered = MinaVerificationKeyJson {
+            hash: "1".into(),
+            ..vk
+        };
+        assert!(tampered.verifier_index().is_err());
+    }
+
+    #[test]
+    fn test_split_fq() {
+        for x in [Fq::from(0u64), Fq::from(5u64), -Fq::from(1u64)] {
+            let [high, low] = split_fq(x);
+            let mut joined = high.into_bigint();
+            joined.mul2();
+            joined.add_with_carry(&low.into_bigint());
+            assert_eq!(joined, x.into_bigint());
+        }
+    }
+
+    #[test]
+    fn test_decimal_public_inputs() {
+        let mut json = MinaProofJson::from_parts(&[1, 2, 3], &[Fp::from(42u64), -Fp::from(1u64)]);
+        assert_eq!(json.public_input[0], "42");
+        assert_eq!(json.public_inputs().unwrap()[1], -Fp::from(1u64));
+        assert_eq!(json.proof, "AQID");
+
+        json.max_proofs_verified = 2;
+        assert!(json.proof().is_err());
+    }
+}
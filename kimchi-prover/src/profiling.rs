@@ -0,0 +1,299 @@
+//! Profiling spans and a Chrome trace recorder.
+//!
+//! With the `tracing` feature the prover opens a `tracing` span (target
+//! `kimchi`) around each [`Phase`]: `srs_init`, `setup`, `prove` and
+//! `verify`, plus `witness` around witness generation by callers that use
+//! [`span`]. Any `tracing` subscriber sees them; without the feature the
+//! spans compile to nothing.
+//!
+//! [`ChromeTraceRecorder`] is a subscriber layer that keeps the spans of a
+//! proving session in memory and exports them in the Chrome trace event
+//! format, which `chrome://tracing`, Perfetto and Speedscope open:
+//!
+//! ```rust,ignore
+//! let recorder = ChromeTraceRecorder::new();
+//! let proof = recorder.record(|| {
+//!     let (witness, _) = {
+//!         let _span = profiling::span(Phase::Witness);
+//!         circuit.generate_witness(value)?
+//!     };
+//!     prover.prove(&prover_index, witness)
+//! })?;
+//! std::fs::write("prove.trace.json", recorder.to_json()?)?;
+//! ```
+//!
+//! The recorder measures time with `std::time::Instant` and is not available
+//! on wasm32 (see [`crate::metrics::Timer`]).
+
+use crate::metrics::Phase;
+
+/// An entered span, exited when dropped.
+#[must_use = "the span is exited when this is dropped"]
+pub struct Span {
+    #[cfg(feature = "tracing")]
+    _entered: tracing::span::EnteredSpan,
+}
+
+/// Enter the span of `phase`.
+pub fn span(phase: Phase) -> Span {
+    #[cfg(feature = "tracing")]
+    {
+        let span = match phase {
+            Phase::SrsInit => tracing::info_span!(target: "kimchi", "srs_init"),
+            Phase::Setup => tracing::info_span!(target: "kimchi", "setup"),
+            Phase::Witness => tracing::info_span!(target: "kimchi", "witness"),
+            Phase::Prove => tracing::info_span!(target: "kimchi", "prove"),
+            Phase::Verify => tracing::info_span!(target: "kimchi", "verify"),
+        };
+        Span {
+            _entered: span.entered(),
+        }
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = phase;
+        Span {}
+    }
+}
+
+#[cfg(all(feature = "tracing", not(target_arch = "wasm32")))]
+pub use recorder::{ChromeTraceLayer, ChromeTraceRecorder, TraceEvent};
+
+#[cfg(all(feature = "tracing", not(target_arch = "wasm32")))]
+mod recorder {
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Instant;
+
+    use serde::Serialize;
+    use tracing::span::Id;
+    use tracing::Subscriber;
+    use tracing_subscriber::layer::{Context, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::{Layer, Registry};
+
+    use crate::error::Result;
+
+    /// One span, entered on `thread` for `duration_us` microseconds.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct TraceEvent {
+        pub name: &'static str,
+        pub target: &'static str,
+        /// Small per-process thread number, in order of first use.
+        pub thread: u64,
+        /// Microseconds since the recorder was created.
+        pub start_us: u64,
+        pub duration_us: u64,
+    }
+
+    /// A complete (`"ph": "X"`) event in the Chrome trace format.
+    #[derive(Serialize)]
+    struct ChromeEvent<'a> {
+        name: &'a str,
+        cat: &'a str,
+        ph: &'static str,
+        ts: u64,
+        dur: u64,
+        pid: u32,
+        tid: u64,
+    }
+
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct ChromeTrace<'a> {
+        trace_events: Vec<ChromeEvent<'a>>,
+        display_time_unit: &'static str,
+    }
+
+    struct Shared {
+        start: Instant,
+        recording: AtomicBool,
+        events: Mutex<Vec<TraceEvent>>,
+    }
+
+    /// Records span timings for export as a Chrome trace.
+    #[derive(Clone)]
+    pub struct ChromeTraceRecorder {
+        shared: Arc<Shared>,
+    }
+
+    impl ChromeTraceRecorder {
+        pub fn new() -> Self {
+            Self {
+                shared: Arc::new(Shared {
+                    start: Instant::now(),
+                    recording: AtomicBool::new(true),
+                    events: Mutex::new(Vec::new()),
+                }),
+            }
+        }
+
+        /// A layer feeding this recorder, to combine with the app's own
+        /// subscriber or install globally.
+        pub fn layer(&self) -> ChromeTraceLayer {
+            ChromeTraceLayer {
+                shared: self.shared.clone(),
+            }
+        }
+
+        /// Run `f` with this recorder as the current thread's subscriber.
+        ///
+        /// Spans entered on other threads are not recorded; install
+        /// [`layer`](Self::layer) globally to capture those.
+        pub fn record<T>(&self, f: impl FnOnce() -> T) -> T {
+            let subscriber = Registry::default().with(self.layer());
+            tracing::subscriber::with_default(subscriber, f)
+        }
+
+        /// Pause or resume recording, e.g. between sessions when the layer
+        /// is installed globally.
+        pub fn set_recording(&self, recording: bool) {
+            self.shared.recording.store(recording, Ordering::Relaxed);
+        }
+
+        /// Spans recorded so far, in the order they were exited.
+        pub fn events(&self) -> Vec<TraceEvent> {
+            self.shared.events.lock().unwrap().clone()
+        }
+
+        /// Drop the recorded spans.
+        pub fn clear(&self) {
+            self.shared.events.lock().unwrap().clear();
+        }
+
+        /// The recorded spans as Chrome trace JSON.
+        pub fn to_json(&self) -> Result<String> {
+            let events = self.events();
+            let trace = ChromeTrace {
+                trace_events: events
+                    .iter()
+                    .map(|event| ChromeEvent {
+                        name: event.name,
+                        cat: event.target,
+                        ph: "X",
+                        ts: event.start_us,
+                        dur: event.duration_us,
+                        pid: std::process::id(),
+                        tid: event.thread,
+                    })
+                    .collect(),
+                display_time_unit: "ms",
+            };
+            Ok(serde_json::to_string(&trace)?)
+        }
+    }
+
+    impl Default for ChromeTraceRecorder {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    /// The [`Layer`] behind a [`ChromeTraceRecorder`].
+    pub struct ChromeTraceLayer {
+        shared: Arc<Shared>,
+    }
+
+    /// When the span was last entered.
+    struct Entered(Instant);
+
+    impl<S> Layer<S> for ChromeTraceLayer
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+            if let Some(span) = ctx.span(id) {
+                span.extensions_mut().replace(Entered(Instant::now()));
+            }
+        }
+
+        fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+            let Some(span) = ctx.span(id) else {
+                return;
+            };
+            let Some(Entered(entered)) = span.extensions_mut().remove::<Entered>() else {
+                return;
+            };
+            if !self.shared.recording.load(Ordering::Relaxed) {
+                return;
+            }
+            let metadata = span.metadata();
+            let event = TraceEvent {
+                name: metadata.name(),
+                target: metadata.target(),
+                thread: thread_number(),
+                start_us: entered.duration_since(self.shared.start).as_micros() as u64,
+                duration_us: entered.elapsed().as_micros() as u64,
+            };
+            self.shared.events.lock().unwrap().push(event);
+        }
+    }
+
+    fn thread_number() -> u64 {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        thread_local! {
+            static NUMBER: Cell<u64> = const { Cell::new(0) };
+        }
+        NUMBER.with(|number| {
+            if number.get() == 0 {
+                number.set(NEXT.fetch_add(1, Ordering::Relaxed));
+            }
+            number.get()
+        })
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+    use crate::builder::{CircuitBuilder, WitnessBuilder};
+    use crate::prover::{KimchiProver, ProverConfig};
+    use crate::Fp;
+    use kimchi::circuits::polynomials::generic::GenericGateSpec;
+
+    #[test]
+    fn test_chrome_trace() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        for v in [3u64, 4] {
+            let [cell, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(v)));
+            w.set(cell, Fp::from(v));
+        }
+
+        let recorder = ChromeTraceRecorder::new();
+        let valid = recorder.record(|| {
+            let mut prover = KimchiProver::with_config(ProverConfig {
+                srs_log2_size: 10,
+                ..Default::default()
+            });
+            let (prover_index, verifier_index) = prover.setup(cs.build(), 0).unwrap();
+            let witness = {
+                let _span = span(Phase::Witness);
+                w.columns()
+            };
+            let proof = prover.prove(&prover_index, witness).unwrap();
+            prover.verify(&verifier_index, &proof, &[]).unwrap()
+        });
+        assert!(valid);
+
+        let names: Vec<_> = recorder
+            .events()
+            .iter()
+            .filter(|event| event.target == "kimchi")
+            .map(|event| event.name)
+            .collect();
+        assert_eq!(names, ["srs_init", "setup", "witness", "prove", "verify"]);
+
+        let json: serde_json::Value = serde_json::from_str(&recorder.to_json().unwrap()).unwrap();
+        let events = json["traceEvents"].as_array().unwrap();
+        assert!(events
+            .iter()
+            .any(|e| e["name"] == "prove" && e["ph"] == "X"));
+
+        recorder.clear();
+        recorder.set_recording(false);
+        recorder.record(|| drop(span(Phase::Witness)));
+        assert!(recorder.events().is_empty());
+    }
+}
@@ -3,11 +3,26 @@
 //! This module provides the main prover interface for generating and verifying
 //! Kimchi proofs compatible with Mina.
 
+use crate::cache::CircuitCache;
+use crate::cost::{self, GadgetCost, SetupInfo};
+use crate::diagnostics;
+use crate::disk_cache::DiskCache;
 use crate::error::{ProverError, Result};
-
-use kimchi::circuits::constraints::ConstraintSystem;
+use crate::gadgets::commitment::{CommitmentWitness, Salt};
+use crate::metrics::{self, MetricsSink, NoopMetrics, Phase, Timer};
+use crate::profiling;
+use crate::secure_mem::{self, SecureMemory};
+use crate::srs_provider::{GenerateSrs, SrsProvider};
+use crate::types::ProofEnvelope;
+
+use ark_ec::short_weierstrass::Projective;
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use kimchi::circuits::constraints::{ConstraintSystem, FeatureFlags};
 use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::lookup::lookups::{LookupFeatures, LookupPatterns};
+use kimchi::circuits::lookup::tables::LookupTable;
 use kimchi::groupmap::GroupMap;
+use kimchi::linearization::expr_linearization;
 use kimchi::proof::ProverProof;
 use kimchi::prover_index::ProverIndex;
 use kimchi::verifier::verify;
@@ -16,11 +31,17 @@ use mina_curves::pasta::{Fp, Vesta, VestaParameters};
 use mina_poseidon::constants::PlonkSpongeConstantsKimchi;
 use mina_poseidon::sponge::{DefaultFqSponge, DefaultFrSponge};
 use poly_commitment::ipa::{OpeningProof, SRS};
+use rand::{CryptoRng, RngCore};
+use std::path::PathBuf;
 use std::sync::Arc;
+use zeroize::{Zeroize, Zeroizing};
 
 /// Number of columns in Kimchi witness
 pub const COLUMNS: usize = 15;
 
+/// Rows Kimchi reserves at the end of the domain for zero-knowledge
+pub const ZK_ROWS: usize = 3;
+
 /// Type aliases for Kimchi's sponge types with Mina's parameters
 pub type VestaBaseSponge =
     DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi, FULL_ROUNDS>;
@@ -29,6 +50,12 @@ pub type VestaScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi, FUL
 /// Type alias for the opening proof used by Vesta
 pub type VestaOpeningProof = OpeningProof<Vesta, FULL_ROUNDS>;
 
+/// Prover and verifier indices produced by circuit setup
+pub type CircuitIndices = (
+    ProverIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+);
+
 /// Configuration for the prover.
 #[derive(Clone, Debug)]
 pub struct ProverConfig {
@@ -36,6 +63,64 @@ pub struct ProverConfig {
     pub srs_log2_size: usize,
     /// Enable debug output
     pub debug: bool,
+    /// Memory budget in bytes. SRS creation and proving fail with
+    /// `ProverError::ResourceExhausted` when their estimated size exceeds
+    /// it, instead of the process being killed part way through.
+    pub memory_limit_bytes: Option<usize>,
+    /// Number of compiled circuits `setup()` keeps for reuse (see
+    /// [`crate::cache`]); 0 disables the cache.
+    pub circuit_cache_capacity: usize,
+    /// Directory caching the SRS and compiled circuits on disk, shared with
+    /// other processes using it (see [`crate::disk_cache`]).
+    pub cache_dir: Option<PathBuf>,
+    /// Number of segments of `2^srs_log2_size` coefficients a polynomial
+    /// may be committed in, so circuits up to `max_chunks` times the SRS
+    /// size can be set up. Each extra chunk adds a commitment per committed
+    /// polynomial and an evaluation per opened one to the proof, and time
+    /// to proving and verification, in exchange for a smaller SRS. Rounded
+    /// down to a power of two; must be at least 1.
+    pub max_chunks: usize,
+    /// Compute the prover index's precomputed evaluations when proving
+    /// instead of at setup: less memory held per circuit, slower proofs.
+    pub lazy_mode: bool,
+    /// Lock the witness columns into RAM while proving, keeping them out
+    /// of swap (see [`crate::secure_mem`]).
+    pub secure_memory: SecureMemory,
+    /// Where the SRS comes from when it is not in the disk cache: created
+    /// on the device, bundled or downloaded (see [`crate::srs_provider`]).
+    pub srs_provider: Arc<dyn SrsProvider>,
+}
+
+impl ProverConfig {
+    /// For devices short on memory: a small SRS of 2^12 points, with
+    /// circuits of up to 2^14 rows committed in up to 4 chunks, and prover
+    /// indices computed lazily. Proofs of circuits above 2^12 rows are
+    /// larger and slower to make than with the default configuration.
+    pub fn mobile_low_memory() -> Self {
+        Self {
+            srs_log2_size: 12,
+            max_chunks: 4,
+            lazy_mode: true,
+            ..Default::default()
+        }
+    }
+
+    /// For the smallest proofs: an SRS of 2^16 points, so circuits of up to
+    /// 2^16 rows are committed in one chunk. Needs about four times the
+    /// SRS memory of the default configuration.
+    pub fn smallest_proof() -> Self {
+        Self {
+            srs_log2_size: 16,
+            max_chunks: 1,
+            lazy_mode: false,
+            ..Default::default()
+        }
+    }
+
+    /// Log2 of the largest circuit domain the configuration can set up.
+    pub fn max_domain_log2_size(&self) -> usize {
+        self.srs_log2_size + self.max_chunks.max(1).ilog2() as usize
+    }
 }
 
 impl Default for ProverConfig {
@@ -43,6 +128,13 @@ impl Default for ProverConfig {
         Self {
             srs_log2_size: 14, // 2^14 = 16384 rows
             debug: false,
+            memory_limit_bytes: None,
+            circuit_cache_capacity: 0,
+            cache_dir: None,
+            max_chunks: 1,
+            lazy_mode: false,
+            secure_memory: SecureMemory::Off,
+            srs_provider: Arc::new(GenerateSrs),
         }
     }
 }
@@ -51,20 +143,41 @@ impl Default for ProverConfig {
 pub struct KimchiProver {
     config: ProverConfig,
     srs: Option<Arc<SRS<Vesta>>>,
+    metrics: Arc<dyn MetricsSink>,
+    circuit_cache: Option<CircuitCache>,
+    disk_cache: Option<DiskCache>,
 }
 
 impl KimchiProver {
     /// Create a new prover with default configuration.
     pub fn new() -> Self {
+        Self::with_config(ProverConfig::default())
+    }
+
+    /// Create a new prover with custom configuration.
+    pub fn with_config(config: ProverConfig) -> Self {
+        let circuit_cache = (config.circuit_cache_capacity > 0)
+            .then(|| CircuitCache::new(config.circuit_cache_capacity));
+        let disk_cache = config.cache_dir.clone().map(DiskCache::new);
         Self {
-            config: ProverConfig::default(),
+            config,
+            circuit_cache,
+            disk_cache,
             srs: None,
+            metrics: Arc::new(NoopMetrics),
         }
     }
 
-    /// Create a new prover with custom configuration.
-    pub fn with_config(config: ProverConfig) -> Self {
-        Self { config, srs: None }
+    /// Salted Poseidon commitment to `values`, to register with a verifier
+    /// before proving statements about them. Unlike [`KimchiProver::msm_commit`],
+    /// it can be opened inside a circuit.
+    ///
+    /// Circuits take the commitment as a public input and open it with
+    /// [`CommitmentGadget::open_salted_cells`](crate::gadgets::CommitmentGadget::open_salted_cells).
+    /// `salt` must be kept for proving; see [`Salt`] for why it must be
+    /// random.
+    pub fn commit(values: &[Fp], salt: &Salt) -> Fp {
+        CommitmentWitness::commit(values, salt)
     }
 
     /// Get the prover configuration.
@@ -72,6 +185,46 @@ impl KimchiProver {
         &self.config
     }
 
+    /// The compiled-circuit cache, if enabled in the configuration.
+    pub fn circuit_cache(&self) -> Option<&CircuitCache> {
+        self.circuit_cache.as_ref()
+    }
+
+    /// The on-disk cache, if a cache directory is configured.
+    pub fn disk_cache(&self) -> Option<&DiskCache> {
+        self.disk_cache.as_ref()
+    }
+
+    /// Report phase counts, durations and errors to `sink`.
+    pub fn set_metrics(&mut self, sink: Arc<dyn MetricsSink>) {
+        self.metrics = sink;
+    }
+
+    /// The sink phases are reported to.
+    pub fn metrics(&self) -> &Arc<dyn MetricsSink> {
+        &self.metrics
+    }
+
+    /// Unblinded commitment `sum(values[i] * g[i])` to a vector over the
+    /// SRS bases.
+    ///
+    /// It does not hide `values`; commit to attributes with
+    /// [`KimchiProver::commit`].
+    pub fn msm_commit(&mut self, values: &[Fp]) -> Result<Vesta> {
+        let srs = self.get_srs()?;
+        if values.len() > srs.g.len() {
+            return Err(ProverError::InvalidInput(format!(
+                "cannot commit to {} values with an SRS of {} points",
+                values.len(),
+                srs.g.len()
+            )));
+        }
+        Ok(
+            Projective::<VestaParameters>::msm_unchecked(&srs.g[..values.len()], values)
+                .into_affine(),
+        )
+    }
+
     /// Initialize the SRS (Structured Reference String).
     /// This is a one-time setup that can be reused across multiple proofs.
     pub fn init_srs(&mut self) -> Result<()> {
@@ -79,13 +232,27 @@ impl KimchiProver {
             return Ok(());
         }
 
+        let _span = profiling::span(Phase::SrsInit);
+        let timer = Timer::start();
+        let result = self.create_srs();
+        metrics::record(&self.metrics, Phase::SrsInit, &timer, &result);
+        result
+    }
+
+    fn create_srs(&mut self) -> Result<()> {
         let depth = 1 << self.config.srs_log2_size;
+        self.check_memory("SRS", cost::srs_memory_bytes(self.config.srs_log2_size))?;
 
+        let provider = &self.config.srs_provider;
         if self.config.debug {
-            log::info!("Creating SRS with depth {}...", depth);
+            log::info!("Creating SRS with depth {} ({})...", depth, provider.name());
         }
 
-        let srs = SRS::<Vesta>::create_parallel(depth);
+        let srs_log2_size = self.config.srs_log2_size;
+        let srs = match &self.disk_cache {
+            Some(cache) => cache.srs(srs_log2_size, || provider.srs(srs_log2_size))?,
+            None => provider.srs(srs_log2_size)?,
+        };
 
         if self.config.debug {
             log::info!("SRS created successfully");
@@ -95,6 +262,31 @@ impl KimchiProver {
         Ok(())
     }
 
+    /// Fail early if `required` bytes exceed the memory limit or cannot be
+    /// reserved.
+    ///
+    /// The reservation is released straight away. With overcommit it mostly
+    /// catches address space exhaustion (32-bit targets), which is why the
+    /// configured limit is checked first.
+    fn check_memory(&self, what: &'static str, required: usize) -> Result<()> {
+        if let Some(limit) = self.config.memory_limit_bytes {
+            if required > limit {
+                return Err(ProverError::ResourceExhausted {
+                    what,
+                    required,
+                    limit: Some(limit),
+                });
+            }
+        }
+        Vec::<u8>::new()
+            .try_reserve_exact(required)
+            .map_err(|_| ProverError::ResourceExhausted {
+                what,
+                required,
+                limit: None,
+            })
+    }
+
     /// Get the SRS, initializing if needed
     fn get_srs(&mut self) -> Result<Arc<SRS<Vesta>>> {
         if self.srs.is_none() {
@@ -108,21 +300,152 @@ impl KimchiProver {
         &mut self,
         gates: Vec<CircuitGate<Fp>>,
         num_public_inputs: usize,
-    ) -> Result<(
-        ProverIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
-        VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
-    )> {
+    ) -> Result<CircuitIndices> {
+        self.setup_with_lookups(gates, num_public_inputs, Vec::new())
+    }
+
+    /// Setup a circuit on an SRS sized for it, instead of the configured
+    /// `srs_log2_size`.
+    ///
+    /// Before the SRS is created this picks the smallest size that fits the
+    /// circuit. After that the SRS is only grown, never shrunk, so that
+    /// smaller circuits already set up keep working. The size used ends up
+    /// in `config().srs_log2_size` and in the verifier index (see
+    /// [`verifier_index_srs_log2_size`]).
+    pub fn setup_auto(
+        &mut self,
+        gates: Vec<CircuitGate<Fp>>,
+        num_public_inputs: usize,
+    ) -> Result<CircuitIndices> {
+        let required = GadgetCost::from_gates(&gates)
+            .required_srs_log2_size()
+            .saturating_sub(self.config.max_domain_log2_size() - self.config.srs_log2_size);
+        if self.srs.is_none() {
+            self.config.srs_log2_size = required;
+        } else if required > self.config.srs_log2_size {
+            self.grow_srs(required)?;
+        }
+        self.setup(gates, num_public_inputs)
+    }
+
+    /// Replace the SRS with one of `2^srs_log2_size` points, keeping the old
+    /// one if that fails.
+    fn grow_srs(&mut self, srs_log2_size: usize) -> Result<()> {
+        let previous = (self.config.srs_log2_size, self.srs.take());
+        self.config.srs_log2_size = srs_log2_size;
+        if let Err(e) = self.init_srs() {
+            (self.config.srs_log2_size, self.srs) = previous;
+            return Err(e);
+        }
+        // Cached indices hold the old SRS
+        if let Some(cache) = self.circuit_cache.as_mut() {
+            cache.clear();
+        }
+        Ok(())
+    }
+
+    /// Setup a circuit that uses fixed lookup tables (e.g. the tables from
+    /// `gadgets::lookup::lookup_tables()`).
+    pub fn setup_with_lookups(
+        &mut self,
+        gates: Vec<CircuitGate<Fp>>,
+        num_public_inputs: usize,
+        lookup_tables: Vec<LookupTable<Fp>>,
+    ) -> Result<CircuitIndices> {
+        let _span = profiling::span(Phase::Setup);
         let srs = self.get_srs()?;
 
+        let key = (self.circuit_cache.is_some() || self.disk_cache.is_some())
+            .then(|| CircuitCache::key(&gates, num_public_inputs, &lookup_tables));
+        if let (Some(cache), Some(key)) = (self.circuit_cache.as_mut(), key) {
+            if let Some(indices) = cache.get(&key) {
+                return Ok((*indices).clone());
+            }
+        }
+
+        let timer = Timer::start();
+        let result = match (&self.disk_cache, &key) {
+            (Some(disk_cache), Some(key)) => disk_cache.circuit(key, &srs, || {
+                self.create_indices(srs.clone(), gates, num_public_inputs, lookup_tables)
+            }),
+            _ => self.create_indices(srs, gates, num_public_inputs, lookup_tables),
+        };
+        metrics::record(&self.metrics, Phase::Setup, &timer, &result);
+
+        if let (Some(cache), Some(key), Ok(indices)) =
+            (self.circuit_cache.as_mut(), key, result.as_ref())
+        {
+            cache.insert(key, Arc::new(indices.clone()));
+        }
+        result
+    }
+
+    /// `setup_with_lookups`, also describing what setup built: the domain
+    /// size against the largest the SRS allows, the rows used, the number
+    /// of gates of each type and the lookup table sizes.
+    pub fn setup_with_info(
+        &mut self,
+        gates: Vec<CircuitGate<Fp>>,
+        num_public_inputs: usize,
+        lookup_tables: Vec<LookupTable<Fp>>,
+    ) -> Result<(CircuitIndices, SetupInfo)> {
+        let indices = self.setup_with_lookups(gates, num_public_inputs, lookup_tables.clone())?;
+        let info = SetupInfo::new(
+            &indices.0,
+            &lookup_tables,
+            1 << self.config.max_domain_log2_size(),
+        );
+        Ok((indices, info))
+    }
+
+    fn create_indices(
+        &self,
+        srs: Arc<SRS<Vesta>>,
+        gates: Vec<CircuitGate<Fp>>,
+        num_public_inputs: usize,
+        lookup_tables: Vec<LookupTable<Fp>>,
+    ) -> Result<CircuitIndices> {
         if self.config.debug {
-            log::info!("Creating constraint system with {} gates...", gates.len());
+            log::info!(
+                "Creating constraint system with {} gates and {} lookup tables...",
+                gates.len(),
+                lookup_tables.len()
+            );
         }
 
+        if self.config.max_chunks == 0 {
+            return Err(ProverError::SetupError(
+                "max_chunks must be at least 1".into(),
+            ));
+        }
+
+        // Fail before compiling constraints a circuit the SRS cannot hold
+        let max_domain_size = srs.g.len() << self.config.max_chunks.ilog2();
+        GadgetCost::from_gates(&gates)
+            .with_lookup_tables(&lookup_tables)
+            .check_fits_domain(max_domain_size)?;
+        diagnostics::check_public_rows(&gates, num_public_inputs)?;
+
         // Create constraint system
-        let cs = ConstraintSystem::create(gates)
-            .public(num_public_inputs)
+        let mut builder = ConstraintSystem::create(gates.clone()).public(num_public_inputs);
+        if !lookup_tables.is_empty() {
+            builder = builder.lookup(lookup_tables);
+        }
+        if self.config.max_chunks > 1 {
+            // Polynomials are committed in segments of the SRS size
+            builder = builder.max_poly_size(Some(srs.g.len()));
+        }
+        let cs = builder
             .build()
-            .map_err(|e| ProverError::SetupError(format!("Constraint system error: {:?}", e)))?;
+            .map_err(|e| diagnostics::setup_error(&gates, num_public_inputs, e))?;
+
+        let domain_size = cs.domain.d1.size as usize;
+        if domain_size > max_domain_size {
+            return Err(ProverError::SrsTooSmall {
+                required: domain_size >> self.config.max_chunks.ilog2(),
+                available: srs.g.len(),
+            });
+        }
 
         if self.config.debug {
             log::info!(
@@ -135,7 +458,7 @@ impl KimchiProver {
         let (endo_q, _endo_r) = poly_commitment::ipa::endos::<mina_curves::pasta::Pallas>();
 
         // Create prover index
-        let prover_index = ProverIndex::create(cs, endo_q, srs, false);
+        let prover_index = ProverIndex::create(cs, endo_q, srs, self.config.lazy_mode);
 
         // Create verifier index from prover index
         let verifier_index = prover_index.verifier_index();
@@ -148,27 +471,82 @@ impl KimchiProver {
     }
 
     /// Generate a proof
+    ///
+    /// The witness is wiped if proving fails before it reaches Kimchi (see
+    /// the crate docs on zeroization).
     pub fn prove(
         &self,
         prover_index: &ProverIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
         witness: [Vec<Fp>; COLUMNS],
     ) -> Result<ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>> {
+        self.prove_with_rng(prover_index, witness, &mut rand::rngs::OsRng)
+    }
+
+    /// Generate a proof drawing blinding factors from `rng`.
+    ///
+    /// The proof is zero-knowledge only if `rng` is unpredictable; a seeded
+    /// generator makes proofs reproducible, which is meant for test fixtures.
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        prover_index: &ProverIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        witness: [Vec<Fp>; COLUMNS],
+        rng: &mut R,
+    ) -> Result<ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>> {
+        let _span = profiling::span(Phase::Prove);
+        let timer = Timer::start();
+        let result = self.create_proof(prover_index, witness, rng);
+        metrics::record(&self.metrics, Phase::Prove, &timer, &result);
+        result
+    }
+
+    fn create_proof<R: RngCore + CryptoRng>(
+        &self,
+        prover_index: &ProverIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        witness: [Vec<Fp>; COLUMNS],
+        rng: &mut R,
+    ) -> Result<ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>> {
+        // Wiped on the early returns below
+        let mut witness = Zeroizing::new(witness);
+
         if self.config.debug {
             log::info!("Generating proof...");
         }
 
-        let group_map = <Vesta as poly_commitment::commitment::CommitmentCurve>::Map::setup();
+        self.check_memory(
+            "proof generation",
+            cost::proving_memory_bytes(prover_index.cs.domain.d1.size as usize),
+        )?;
+
+        // Left locked when handed to Kimchi, which keeps them while proving
+        let locked = secure_mem::lock_columns(self.config.secure_memory, &witness)?;
+
+        // Kimchi does not check the witness in release builds, so an
+        // unsatisfied constraint would only show up as a proof that fails
+        // to verify.
+        diagnostics::check_witness(
+            &prover_index.cs.gates,
+            &witness,
+            prover_index.cs.public,
+            self.config.debug,
+        )
+        .inspect_err(|_| {
+            if locked {
+                // Wiped before the pages can be swapped out
+                witness.iter_mut().flatten().for_each(Zeroize::zeroize);
+                secure_mem::unlock_columns(&witness);
+            }
+        })?;
 
-        let mut rng = rand::rngs::OsRng;
+        let group_map = <Vesta as poly_commitment::commitment::CommitmentCurve>::Map::setup();
 
+        // Kimchi takes ownership of the columns and frees them itself
         let proof = ProverProof::create::<VestaBaseSponge, VestaScalarSponge, _>(
             &group_map,
-            witness,
+            std::mem::take(&mut *witness),
             &[], // no runtime tables
             prover_index,
-            &mut rng,
-        )
-        .map_err(|e| ProverError::ProvingError(format!("Proof generation failed: {:?}", e)))?;
+            rng,
+        )?;
 
         if self.config.debug {
             log::info!("Proof generated successfully");
@@ -184,43 +562,173 @@ impl KimchiProver {
         proof: &ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
         public_inputs: &[Fp],
     ) -> Result<bool> {
-        if self.config.debug {
-            log::info!("Verifying proof...");
-        }
+        verify_proof(
+            &self.metrics,
+            self.config.debug,
+            verifier_index,
+            proof,
+            public_inputs,
+        )
+    }
+}
 
-        let group_map = <Vesta as poly_commitment::commitment::CommitmentCurve>::Map::setup();
+/// Create the SRS of `2^srs_log2_size` points.
+pub(crate) fn create_srs(srs_log2_size: usize) -> SRS<Vesta> {
+    let depth = 1 << srs_log2_size;
+    #[cfg(feature = "parallel")]
+    let srs = SRS::<Vesta>::create_parallel(depth);
+    #[cfg(not(feature = "parallel"))]
+    let srs = <SRS<Vesta> as poly_commitment::SRS<Vesta>>::create(depth);
+    srs
+}
+
+/// Verify a proof, reporting to `metrics`. Shared by `KimchiProver` and
+/// `KimchiVerifier`.
+pub(crate) fn verify_proof(
+    metrics: &Arc<dyn MetricsSink>,
+    debug: bool,
+    verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    proof: &ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
+    public_inputs: &[Fp],
+) -> Result<bool> {
+    if debug {
+        log::info!("Verifying proof...");
+    }
+
+    let _span = profiling::span(Phase::Verify);
+    let timer = Timer::start();
+    let group_map = <Vesta as poly_commitment::commitment::CommitmentCurve>::Map::setup();
 
-        let result = verify::<
-            FULL_ROUNDS,
-            Vesta,
-            VestaBaseSponge,
-            VestaScalarSponge,
-            VestaOpeningProof,
-        >(&group_map, verifier_index, proof, public_inputs);
-
-        match result {
-            Ok(_) => {
-                if self.config.debug {
-                    log::info!("Proof verified successfully");
-                }
-                Ok(true)
+    let result = verify::<FULL_ROUNDS, Vesta, VestaBaseSponge, VestaScalarSponge, VestaOpeningProof>(
+        &group_map,
+        verifier_index,
+        proof,
+        public_inputs,
+    );
+
+    metrics::record(metrics, Phase::Verify, &timer, &Ok(()));
+    if result.is_err() {
+        metrics.counter(&Phase::Verify.metric("rejected"), 1);
+    }
+
+    match result {
+        Ok(_) => {
+            if debug {
+                log::info!("Proof verified successfully");
             }
-            Err(e) => {
-                if self.config.debug {
-                    log::warn!("Proof verification failed: {:?}", e);
-                }
-                Ok(false)
+            Ok(true)
+        }
+        Err(e) => {
+            if debug {
+                log::warn!("Proof verification failed: {:?}", e);
             }
+            Ok(false)
         }
     }
 }
 
+impl KimchiProver {
+    /// Deserialize a MessagePack verifier index (as exported over FFI) and
+    /// attach this prover's SRS, creating it first if needed.
+    pub fn load_verifier_index(
+        &mut self,
+        bytes: &[u8],
+    ) -> Result<VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>> {
+        let mut verifier_index: VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>> =
+            rmp_serde::from_slice(bytes).map_err(|e| ProverError::decode("verifier index", e))?;
+        self.init_srs()?;
+        let srs = self
+            .srs
+            .clone()
+            .ok_or_else(|| ProverError::InternalError("SRS missing after initialization".into()))?;
+        restore_verifier_index(&mut verifier_index, srs);
+        Ok(verifier_index)
+    }
+
+    /// Verify a proof envelope against the verifier index it claims.
+    ///
+    /// Fails if the envelope was produced for a different verifier index.
+    pub fn verify_envelope(
+        &self,
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        envelope: &ProofEnvelope,
+    ) -> Result<bool> {
+        envelope.check_verifier_index(verifier_index)?;
+        let proof = envelope.proof()?;
+        self.verify(verifier_index, &proof, &envelope.public_inputs_fp())
+    }
+}
+
 impl Default for KimchiProver {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Log2 of the size of the SRS `verifier_index` was created with, which
+/// verifying its proofs requires.
+pub fn verifier_index_srs_log2_size(
+    verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+) -> usize {
+    verifier_index.max_poly_size.trailing_zeros() as usize
+}
+
+/// Restore the parts of a deserialized verifier index that are not
+/// serialized: the SRS and the linearization of the constraints.
+pub fn restore_verifier_index(
+    verifier_index: &mut VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    srs: Arc<SRS<Vesta>>,
+) {
+    verifier_index.srs = srs;
+    let feature_flags = infer_feature_flags(verifier_index);
+    let (linearization, powers_of_alpha) = expr_linearization(Some(&feature_flags), true);
+    verifier_index.linearization = linearization;
+    verifier_index.powers_of_alpha = powers_of_alpha;
+}
+
+/// Restore the parts of a deserialized prover index that are not
+/// serialized: the SRS and the linearization of the constraints.
+pub fn restore_prover_index(
+    prover_index: &mut ProverIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    srs: Arc<SRS<Vesta>>,
+) {
+    prover_index.srs = srs;
+    let (linearization, powers_of_alpha) =
+        expr_linearization(Some(&prover_index.cs.feature_flags), true);
+    prover_index.linearization = linearization;
+    prover_index.powers_of_alpha = powers_of_alpha;
+}
+
+/// Infer which optional gates a verifier index uses from the commitments it
+/// carries.
+fn infer_feature_flags(
+    verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+) -> FeatureFlags {
+    let lookup_features = match &verifier_index.lookup_index {
+        Some(lookup_index) => LookupFeatures {
+            patterns: LookupPatterns {
+                xor: lookup_index.lookup_selectors.xor.is_some(),
+                lookup: lookup_index.lookup_selectors.lookup.is_some(),
+                range_check: lookup_index.lookup_selectors.range_check.is_some(),
+                foreign_field_mul: lookup_index.lookup_selectors.ffmul.is_some(),
+            },
+            joint_lookup_used: lookup_index.lookup_table.len() > 1,
+            uses_runtime_tables: lookup_index.runtime_tables_selector.is_some(),
+        },
+        None => LookupFeatures::default(),
+    };
+
+    FeatureFlags {
+        range_check0: verifier_index.range_check0_comm.is_some(),
+        range_check1: verifier_index.range_check1_comm.is_some(),
+        foreign_field_add: verifier_index.foreign_field_add_comm.is_some(),
+        foreign_field_mul: verifier_index.foreign_field_mul_comm.is_some(),
+        xor: verifier_index.xor_comm.is_some(),
+        rot: verifier_index.rot_comm.is_some(),
+        lookup_features,
+    }
+}
+
 /// Re-export FULL_ROUNDS constant for use in type signatures
 pub use mina_poseidon::pasta::FULL_ROUNDS;
 
@@ -233,9 +741,214 @@ mod tests {
         let mut prover = KimchiProver::with_config(ProverConfig {
             srs_log2_size: 10, // Smaller for faster tests
             debug: false,
+            ..Default::default()
         });
 
         let result = prover.init_srs();
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_memory_limit() {
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            memory_limit_bytes: Some(cost::srs_memory_bytes(10) - 1),
+            ..Default::default()
+        });
+        match prover.init_srs() {
+            Err(ProverError::ResourceExhausted {
+                required, limit, ..
+            }) => {
+                assert_eq!(required, cost::srs_memory_bytes(10));
+                assert!(limit.is_some());
+            }
+            other => panic!("unexpected result: {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn test_secure_memory() {
+        use crate::circuits::ThresholdCircuit;
+
+        let circuit = ThresholdCircuit::new(10);
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 8,
+            secure_memory: SecureMemory::BestEffort,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover
+            .setup(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+        let (witness, public_inputs) = circuit.generate_witness(0).unwrap();
+        let mut bad = witness.clone();
+        bad.iter_mut()
+            .flat_map(|column| column.iter_mut().skip(2))
+            .for_each(|v| *v += Fp::from(1u64));
+        assert!(prover.prove(&prover_index, bad).is_err());
+        let proof = prover.prove(&prover_index, witness).unwrap();
+        assert!(prover
+            .verify(&verifier_index, &proof, &public_inputs)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_setup_auto() {
+        use crate::circuits::{Circuit, MerkleMembershipCircuit, ThresholdCircuit};
+
+        let mut prover = KimchiProver::new();
+        let threshold = ThresholdCircuit::new(10);
+        let required = GadgetCost::from_gates(&threshold.gates()).required_srs_log2_size();
+        let (prover_index, verifier_index) = prover
+            .setup_auto(threshold.gates(), threshold.num_public_inputs())
+            .unwrap();
+        assert_eq!(prover.config().srs_log2_size, required);
+        assert_eq!(verifier_index_srs_log2_size(&verifier_index), required);
+
+        // A bigger circuit grows the SRS; the first circuit's indices still work
+        let merkle = MerkleMembershipCircuit::new(2).unwrap();
+        let (_, merkle_index) = prover
+            .setup_auto(merkle.gates(), merkle.num_public_inputs())
+            .unwrap();
+        let grown = prover.config().srs_log2_size;
+        assert!(grown > required);
+        assert_eq!(verifier_index_srs_log2_size(&merkle_index), grown);
+
+        let (witness, public_inputs) = threshold.generate_witness(0).unwrap();
+        let proof = prover.prove(&prover_index, witness).unwrap();
+        assert!(prover
+            .verify(&verifier_index, &proof, &public_inputs)
+            .unwrap());
+
+        // ...and smaller circuits no longer shrink it
+        prover
+            .setup_auto(threshold.gates(), threshold.num_public_inputs())
+            .unwrap();
+        assert_eq!(prover.config().srs_log2_size, grown);
+    }
+
+    #[test]
+    fn test_setup_info() {
+        use crate::gadgets::lookup::{lookup_tables, LookupGadget, NIBBLE_XOR_TABLE_ID};
+        use kimchi::circuits::gate::GateType;
+
+        let mut gadget = LookupGadget::new(0);
+        gadget.byte_xor();
+        let (gates, rows) = gadget.build();
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            max_chunks: 2,
+            ..Default::default()
+        });
+        let (_, info) = prover
+            .setup_with_info(gates.clone(), 0, lookup_tables())
+            .unwrap();
+        assert_eq!(info.rows, rows);
+        assert_eq!(info.max_domain_size, 1 << 11);
+        assert_eq!(info.gate_count(GateType::Lookup), 2);
+        assert_eq!(
+            info.gate_counts.iter().map(|&(_, n)| n).sum::<usize>(),
+            rows
+        );
+        assert!(info.lookup_tables.contains(&(NIBBLE_XOR_TABLE_ID, 256)));
+        assert_eq!(info.used_rows(), 512);
+        assert_eq!(info.domain_size, 1024);
+        assert_eq!(info.free_rows(), 1024 - info.zk_rows - 512);
+        assert_eq!(info.rows_to_limit(), 2048 - info.zk_rows - 512);
+        assert!(info.to_string().contains("Lookup"));
+    }
+
+    #[test]
+    fn test_chunked_commitments() {
+        use crate::circuits::ThresholdCircuit;
+
+        // The threshold circuit's domain is twice an SRS of 2^3 points
+        let circuit = ThresholdCircuit::new(10);
+        let required = GadgetCost::from_gates(&circuit.gates()).required_srs_log2_size();
+        let config = ProverConfig {
+            srs_log2_size: required - 1,
+            ..Default::default()
+        };
+        assert!(matches!(
+            KimchiProver::with_config(config.clone())
+                .setup(circuit.gates(), circuit.num_public_inputs()),
+            Err(ProverError::CircuitTooLarge { .. })
+        ));
+
+        let config = ProverConfig {
+            max_chunks: 3,
+            lazy_mode: true,
+            ..config
+        };
+        assert_eq!(config.max_domain_log2_size(), required);
+        let mut prover = KimchiProver::with_config(config);
+        let (prover_index, verifier_index) = prover
+            .setup(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+        assert_eq!(verifier_index_srs_log2_size(&verifier_index), required - 1);
+        let (witness, public_inputs) = circuit.generate_witness(0).unwrap();
+        let proof = prover.prove(&prover_index, witness).unwrap();
+        assert!(prover
+            .verify(&verifier_index, &proof, &public_inputs)
+            .unwrap());
+
+        // setup_auto picks the smallest SRS the chunks allow
+        let mut prover = KimchiProver::with_config(ProverConfig::mobile_low_memory());
+        prover
+            .setup_auto(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+        assert_eq!(prover.config().srs_log2_size, required - 2);
+
+        let invalid = ProverConfig {
+            max_chunks: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            KimchiProver::with_config(invalid).setup(circuit.gates(), 2),
+            Err(ProverError::SetupError(_))
+        ));
+        assert_eq!(ProverConfig::smallest_proof().max_domain_log2_size(), 16);
+        assert_eq!(ProverConfig::mobile_low_memory().max_domain_log2_size(), 14);
+    }
+
+    #[test]
+    fn test_load_verifier_index() {
+        use crate::builder::{CircuitBuilder, WitnessBuilder};
+        use kimchi::circuits::polynomials::generic::GenericGateSpec;
+
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        for v in [3u64, 4] {
+            let [cell, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(v)));
+            w.set(cell, Fp::from(v));
+        }
+
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover.setup(cs.build(), 0).unwrap();
+        let proof = prover.prove(&prover_index, w.columns()).unwrap();
+
+        let bytes = rmp_serde::to_vec(&verifier_index).unwrap();
+        let mut verifier = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            ..Default::default()
+        });
+        let loaded = verifier.load_verifier_index(&bytes).unwrap();
+        assert!(verifier.verify(&loaded, &proof, &[]).unwrap());
+    }
+
+    #[test]
+    fn test_msm_commit() {
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 4,
+            ..Default::default()
+        });
+
+        let values = [Fp::from(2u64), Fp::from(3u64)];
+        let srs = prover.get_srs().unwrap();
+        let expected = (srs.g[0] * values[0] + srs.g[1] * values[1]).into_affine();
+        assert_eq!(prover.msm_commit(&values).unwrap(), expected);
+        assert!(prover.msm_commit(&vec![Fp::from(1u64); 17]).is_err());
+    }
 }
@@ -0,0 +1,389 @@
+//! Locked memory for witness columns and secret inputs.
+//!
+//! Zeroization (see the crate docs) wipes secrets when they are freed, but
+//! while they are alive the OS may page them out to swap, where a rooted or
+//! compromised device can read them long after. With
+//! [`ProverConfig::secure_memory`] set, [`KimchiProver::prove`] `mlock`s the
+//! pages of the witness columns so they stay in RAM, and a [`GuardedBuffer`]
+//! holds secret inputs in a locked mapping of their own between
+//! inaccessible guard pages, so overruns of neighbouring buffers fault
+//! instead of reaching them.
+//!
+//! Locking is available on Unix (including Android and iOS) and limited
+//! per process (`RLIMIT_MEMLOCK`, often 64 KiB for apps). Where it is not
+//! available or the limit is reached, [`SecureMemory::BestEffort`] carries
+//! on unlocked and [`SecureMemory::Required`] fails with
+//! `ResourceExhausted`.
+//!
+//! Kimchi frees the columns passed to `prove` itself: their pages stay
+//! locked until the allocator returns them to the OS, and the polynomials
+//! Kimchi derives from them are not locked. `munlock` does not count, so
+//! unlocking a buffer also unlocks other buffers sharing its first or last
+//! page.
+//!
+//! [`ProverConfig::secure_memory`]: crate::prover::ProverConfig::secure_memory
+//! [`KimchiProver::prove`]: crate::prover::KimchiProver::prove
+
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+use mina_curves::pasta::Fp;
+use zeroize::Zeroize;
+
+use crate::error::{ProverError, Result};
+use crate::prover::COLUMNS;
+
+/// Whether buffers holding secrets are locked into RAM.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SecureMemory {
+    /// Ordinary memory.
+    #[default]
+    Off,
+    /// Lock where the platform allows it, and carry on unlocked otherwise.
+    BestEffort,
+    /// Fail with `ResourceExhausted` if memory cannot be locked.
+    Required,
+}
+
+/// Lock the pages holding `data` into RAM as `mode` asks. Returns whether
+/// they were locked.
+pub fn lock<T>(mode: SecureMemory, data: &[T]) -> Result<bool> {
+    let len = std::mem::size_of_val(data);
+    if mode == SecureMemory::Off || len == 0 {
+        return Ok(false);
+    }
+    if sys::lock(data.as_ptr().cast(), len) {
+        return Ok(true);
+    }
+    match mode {
+        SecureMemory::Required => Err(ProverError::ResourceExhausted {
+            what: "locked memory",
+            required: len,
+            limit: None,
+        }),
+        _ => {
+            log::debug!("Could not lock {} bytes of memory", len);
+            Ok(false)
+        }
+    }
+}
+
+/// Unlock the pages holding `data`.
+pub fn unlock<T>(data: &[T]) {
+    let len = std::mem::size_of_val(data);
+    if len > 0 {
+        sys::unlock(data.as_ptr().cast(), len);
+    }
+}
+
+/// Lock every witness column; on failure, unlock the ones already locked.
+pub(crate) fn lock_columns(mode: SecureMemory, columns: &[Vec<Fp>; COLUMNS]) -> Result<bool> {
+    let mut locked = false;
+    for (i, column) in columns.iter().enumerate() {
+        match lock(mode, column) {
+            Ok(l) => locked |= l,
+            Err(e) => {
+                columns[..i].iter().for_each(|c| unlock(c));
+                return Err(e);
+            }
+        }
+    }
+    Ok(locked)
+}
+
+/// Unlock every witness column.
+pub(crate) fn unlock_columns(columns: &[Vec<Fp>; COLUMNS]) {
+    columns.iter().for_each(|c| unlock(c));
+}
+
+/// A fixed-size buffer for secret values, in its own mapping between guard
+/// pages and locked into RAM, and wiped when dropped.
+///
+/// The values end at the upper guard page, so reads or writes past them
+/// fault. With [`SecureMemory::Off`], or where mappings with guard pages are
+/// not available, the buffer is an ordinary heap allocation (still wiped).
+pub struct GuardedBuffer<T: Copy> {
+    ptr: NonNull<T>,
+    len: usize,
+    storage: Storage,
+    locked: bool,
+    _marker: PhantomData<T>,
+}
+
+enum Storage {
+    Heap(std::alloc::Layout),
+    #[cfg_attr(not(unix), allow(dead_code))]
+    Mapped {
+        base: NonNull<u8>,
+        size: usize,
+    },
+}
+
+// The buffer owns its values like a `Box<[T]>`
+unsafe impl<T: Copy + Send> Send for GuardedBuffer<T> {}
+unsafe impl<T: Copy + Sync> Sync for GuardedBuffer<T> {}
+
+impl<T: Copy + Default> GuardedBuffer<T> {
+    /// A buffer of `len` default values.
+    pub fn new(mode: SecureMemory, len: usize) -> Result<Self> {
+        let layout = std::alloc::Layout::array::<T>(len)
+            .map_err(|_| ProverError::InvalidInput("guarded buffer too large".into()))?;
+        let mut buffer = match sys::map_guarded(mode, layout) {
+            Some((base, size, ptr)) => Self {
+                ptr: ptr.cast(),
+                len,
+                storage: Storage::Mapped { base, size },
+                locked: false,
+                _marker: PhantomData,
+            },
+            None => Self::heap(layout, len),
+        };
+        buffer.as_mut_slice().fill(T::default());
+        buffer.locked = lock(mode, buffer.as_slice())?;
+        Ok(buffer)
+    }
+
+    /// A buffer holding a copy of `values`.
+    pub fn from_slice(mode: SecureMemory, values: &[T]) -> Result<Self> {
+        let mut buffer = Self::new(mode, values.len())?;
+        buffer.as_mut_slice().copy_from_slice(values);
+        Ok(buffer)
+    }
+
+    fn heap(layout: std::alloc::Layout, len: usize) -> Self {
+        let ptr = if layout.size() == 0 {
+            NonNull::dangling()
+        } else {
+            // SAFETY: the layout has a nonzero size
+            let ptr = unsafe { std::alloc::alloc_zeroed(layout) };
+            NonNull::new(ptr.cast()).unwrap_or_else(|| std::alloc::handle_alloc_error(layout))
+        };
+        Self {
+            ptr,
+            len,
+            storage: Storage::Heap(layout),
+            locked: false,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Copy> GuardedBuffer<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Whether the values are locked into RAM.
+    pub fn is_locked(&self) -> bool {
+        self.locked
+    }
+
+    /// Whether the values are between guard pages.
+    pub fn is_guarded(&self) -> bool {
+        matches!(self.storage, Storage::Mapped { .. })
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        // SAFETY: `ptr` points to `len` initialized values owned by `self`
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        // SAFETY: as in `as_slice`, and `&mut self` makes the access unique
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<T: Copy> Zeroize for GuardedBuffer<T> {
+    fn zeroize(&mut self) {
+        let len = std::mem::size_of_val(self.as_slice());
+        // SAFETY: the values are `Copy`, so overwriting their bytes needs no
+        // drop, and all zeros is only observed by `Drop` or by callers of
+        // `zeroize` on types where it is valid (integers, field elements)
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr().cast::<u8>(), len) }.zeroize();
+    }
+}
+
+impl<T: Copy> Drop for GuardedBuffer<T> {
+    fn drop(&mut self) {
+        self.zeroize();
+        if self.locked {
+            unlock(self.as_slice());
+        }
+        match self.storage {
+            Storage::Heap(layout) => {
+                if layout.size() > 0 {
+                    // SAFETY: allocated in `heap` with this layout
+                    unsafe { std::alloc::dealloc(self.ptr.as_ptr().cast(), layout) };
+                }
+            }
+            Storage::Mapped { base, size } => sys::unmap(base, size),
+        }
+    }
+}
+
+impl<T: Copy> std::fmt::Debug for GuardedBuffer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GuardedBuffer")
+            .field("len", &self.len)
+            .field("locked", &self.locked)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::ptr::NonNull;
+
+    use super::SecureMemory;
+
+    fn page_size() -> usize {
+        // SAFETY: sysconf has no preconditions
+        let size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        usize::try_from(size).unwrap_or(4096)
+    }
+
+    /// The page-aligned range covering `len` bytes at `ptr`.
+    fn pages(ptr: *const u8, len: usize) -> (*mut libc::c_void, usize) {
+        let page = page_size();
+        let start = ptr as usize & !(page - 1);
+        let end = (ptr as usize + len).next_multiple_of(page);
+        (start as *mut libc::c_void, end - start)
+    }
+
+    pub(super) fn lock(ptr: *const u8, len: usize) -> bool {
+        let (start, len) = pages(ptr, len);
+        // SAFETY: the range covers memory of a live allocation
+        unsafe { libc::mlock(start, len) == 0 }
+    }
+
+    pub(super) fn unlock(ptr: *const u8, len: usize) {
+        let (start, len) = pages(ptr, len);
+        // SAFETY: as in `lock`
+        unsafe { libc::munlock(start, len) };
+    }
+
+    /// Map pages for `layout` between two guard pages. Returns the mapping
+    /// and where the values start, so that they end at the upper guard.
+    pub(super) fn map_guarded(
+        mode: SecureMemory,
+        layout: std::alloc::Layout,
+    ) -> Option<(NonNull<u8>, usize, NonNull<u8>)> {
+        let page = page_size();
+        if mode == SecureMemory::Off || layout.size() == 0 || layout.align() > page {
+            return None;
+        }
+        let data = layout.size().next_multiple_of(page);
+        let size = data + 2 * page;
+        // SAFETY: an anonymous private mapping, checked below
+        let base = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                size,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_PRIVATE | libc::MAP_ANON,
+                -1,
+                0,
+            )
+        };
+        if base == libc::MAP_FAILED {
+            return None;
+        }
+        let base = base.cast::<u8>();
+        // SAFETY: both guard pages are inside the mapping
+        let guarded = unsafe {
+            libc::mprotect(base.cast(), page, libc::PROT_NONE) == 0
+                && libc::mprotect(base.add(page + data).cast(), page, libc::PROT_NONE) == 0
+        };
+        let base = NonNull::new(base)?;
+        if !guarded {
+            unmap(base, size);
+            return None;
+        }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        // SAFETY: the data pages are inside the mapping
+        unsafe {
+            libc::madvise(base.as_ptr().add(page).cast(), data, libc::MADV_DONTDUMP);
+        }
+        let offset = (page + data - layout.size()) & !(layout.align() - 1);
+        // SAFETY: `offset` is within the data pages
+        let ptr = unsafe { NonNull::new_unchecked(base.as_ptr().add(offset)) };
+        Some((base, size, ptr))
+    }
+
+    pub(super) fn unmap(base: NonNull<u8>, size: usize) {
+        // SAFETY: `base` and `size` describe a mapping from `map_guarded`
+        unsafe { libc::munmap(base.as_ptr().cast(), size) };
+    }
+}
+
+#[cfg(not(unix))]
+mod sys {
+    use std::ptr::NonNull;
+
+    use super::SecureMemory;
+
+    pub(super) fn lock(_ptr: *const u8, _len: usize) -> bool {
+        false
+    }
+
+    pub(super) fn unlock(_ptr: *const u8, _len: usize) {}
+
+    pub(super) fn map_guarded(
+        _mode: SecureMemory,
+        _layout: std::alloc::Layout,
+    ) -> Option<(NonNull<u8>, usize, NonNull<u8>)> {
+        None
+    }
+
+    pub(super) fn unmap(_base: NonNull<u8>, _size: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock() {
+        let column = vec![Fp::from(7u64); 64];
+        assert!(!lock(SecureMemory::Off, &column).unwrap());
+        if lock(SecureMemory::BestEffort, &column).unwrap() {
+            unlock(&column);
+            assert!(lock(SecureMemory::Required, &column).unwrap());
+            unlock(&column);
+        } else {
+            assert!(matches!(
+                lock(SecureMemory::Required, &column),
+                Err(ProverError::ResourceExhausted { .. })
+            ));
+        }
+        assert!(!lock(SecureMemory::Required, &Vec::<Fp>::new()).unwrap());
+    }
+
+    #[test]
+    fn test_guarded_buffer() {
+        let secret = [Fp::from(1u64), Fp::from(2u64), Fp::from(3u64)];
+        let mut buffer = GuardedBuffer::from_slice(SecureMemory::BestEffort, &secret).unwrap();
+        assert_eq!(buffer.as_slice(), &secret);
+        assert_eq!(
+            buffer.as_slice().as_ptr() as usize % std::mem::align_of::<Fp>(),
+            0
+        );
+        assert_eq!(buffer.is_guarded(), cfg!(unix));
+        buffer.as_mut_slice()[1] = Fp::from(5u64);
+        assert_eq!(buffer.as_slice()[1], Fp::from(5u64));
+        assert!(!format!("{:?}", buffer).contains('5'));
+        buffer.zeroize();
+        assert!(buffer.as_slice().iter().all(|v| *v == Fp::from(0u64)));
+
+        let plain = GuardedBuffer::<u8>::from_slice(SecureMemory::Off, b"secret").unwrap();
+        assert!(!plain.is_guarded() && !plain.is_locked());
+        assert_eq!(plain.as_slice(), b"secret");
+        let empty = GuardedBuffer::<u8>::new(SecureMemory::Required, 0).unwrap();
+        assert!(empty.is_empty());
+    }
+}
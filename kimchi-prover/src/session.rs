@@ -0,0 +1,203 @@
+//! Proving several circuits against one SRS.
+//!
+//! A [`ProvingSession`] owns a [`KimchiProver`], and so its SRS, and the
+//! indices of the circuits registered with it. Each circuit is set up the
+//! first time it is needed and its indices kept until it is unregistered,
+//! so callers prove and verify by name instead of passing indices around.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use kimchi::proof::ProverProof;
+use mina_curves::pasta::{Fp, Vesta};
+
+use crate::circuits::Circuit;
+use crate::cost::SetupInfo;
+use crate::error::{ProverError, Result};
+use crate::prover::{
+    CircuitIndices, KimchiProver, ProverConfig, VestaOpeningProof, COLUMNS, FULL_ROUNDS,
+};
+
+struct RegisteredCircuit {
+    circuit: Box<dyn Circuit + Send + Sync>,
+    /// Set up on first use.
+    indices: Option<(Arc<CircuitIndices>, SetupInfo)>,
+}
+
+/// A prover with a set of named circuits.
+pub struct ProvingSession {
+    prover: KimchiProver,
+    circuits: HashMap<String, RegisteredCircuit>,
+}
+
+impl ProvingSession {
+    /// Create a session with a prover using `config`.
+    pub fn new(config: ProverConfig) -> Self {
+        Self::with_prover(KimchiProver::with_config(config))
+    }
+
+    /// Create a session around an existing prover, reusing its SRS.
+    pub fn with_prover(prover: KimchiProver) -> Self {
+        Self {
+            prover,
+            circuits: HashMap::new(),
+        }
+    }
+
+    /// The session's prover.
+    pub fn prover(&self) -> &KimchiProver {
+        &self.prover
+    }
+
+    /// The session's prover, e.g. to install a metrics sink.
+    pub fn prover_mut(&mut self) -> &mut KimchiProver {
+        &mut self.prover
+    }
+
+    /// Initialize the SRS, if not done already.
+    pub fn init_srs(&mut self) -> Result<()> {
+        self.prover.init_srs()
+    }
+
+    /// Register `circuit` under `name`, replacing any circuit registered
+    /// under it before. The circuit is set up on first use.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        circuit: impl Circuit + Send + Sync + 'static,
+    ) {
+        self.register_boxed(name, Box::new(circuit));
+    }
+
+    /// `register` for a boxed circuit, e.g. from `circuits::registry::circuit`.
+    pub fn register_boxed(
+        &mut self,
+        name: impl Into<String>,
+        circuit: Box<dyn Circuit + Send + Sync>,
+    ) {
+        self.circuits.insert(
+            name.into(),
+            RegisteredCircuit {
+                circuit,
+                indices: None,
+            },
+        );
+    }
+
+    /// Drop the circuit registered under `name` and its indices. Returns
+    /// whether it was registered.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        self.circuits.remove(name).is_some()
+    }
+
+    /// Whether a circuit is registered under `name`.
+    pub fn is_registered(&self, name: &str) -> bool {
+        self.circuits.contains_key(name)
+    }
+
+    /// Names of the registered circuits, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.circuits.keys().map(String::as_str)
+    }
+
+    /// The circuit registered under `name`.
+    pub fn circuit(&self, name: &str) -> Option<&(dyn Circuit + Send + Sync)> {
+        self.circuits.get(name).map(|c| c.circuit.as_ref())
+    }
+
+    /// Prover and verifier index of the circuit `name`, setting it up if
+    /// this is its first use.
+    pub fn indices(&mut self, name: &str) -> Result<Arc<CircuitIndices>> {
+        Ok(self.setup(name)?.0.clone())
+    }
+
+    /// Statistics of the circuit `name`, setting it up if this is its
+    /// first use.
+    pub fn setup_info(&mut self, name: &str) -> Result<SetupInfo> {
+        Ok(self.setup(name)?.1.clone())
+    }
+
+    fn setup(&mut self, name: &str) -> Result<&(Arc<CircuitIndices>, SetupInfo)> {
+        let registered = self
+            .circuits
+            .get_mut(name)
+            .ok_or_else(|| ProverError::InvalidInput(format!("unknown circuit {:?}", name)))?;
+        if registered.indices.is_none() {
+            let circuit = &registered.circuit;
+            let (indices, info) = self.prover.setup_with_info(
+                circuit.gates(),
+                circuit.num_public_inputs(),
+                Vec::new(),
+            )?;
+            registered.indices = Some((Arc::new(indices), info));
+        }
+        Ok(registered.indices.as_ref().unwrap())
+    }
+
+    /// Prove the circuit `name` with `witness`.
+    pub fn prove(
+        &mut self,
+        name: &str,
+        witness: [Vec<Fp>; COLUMNS],
+    ) -> Result<ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>> {
+        let indices = self.indices(name)?;
+        self.prover.prove(&indices.0, witness)
+    }
+
+    /// Verify a proof of the circuit `name`.
+    pub fn verify(
+        &mut self,
+        name: &str,
+        proof: &ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
+        public_inputs: &[Fp],
+    ) -> Result<bool> {
+        let indices = self.indices(name)?;
+        self.prover.verify(&indices.1, proof, public_inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::{MerkleMembershipCircuit, ThresholdCircuit};
+    use crate::gadgets::MerkleWitness;
+
+    #[test]
+    fn test_session() {
+        let mut session = ProvingSession::new(ProverConfig {
+            srs_log2_size: 10,
+            ..Default::default()
+        });
+        session.register("threshold", ThresholdCircuit::new(10));
+        session.register("merkle", MerkleMembershipCircuit::new(2).unwrap());
+        assert!(session.is_registered("merkle"));
+        assert_eq!(session.circuit("merkle").unwrap().id(), "merkle_membership");
+
+        let (witness, public_inputs) = ThresholdCircuit::new(10).generate_witness(0).unwrap();
+        let proof = session.prove("threshold", witness).unwrap();
+        assert!(session.verify("threshold", &proof, &public_inputs).unwrap());
+        let indices = session.indices("threshold").unwrap();
+        assert!(Arc::ptr_eq(
+            &indices,
+            &session.indices("threshold").unwrap()
+        ));
+        let info = session.setup_info("threshold").unwrap();
+        assert_eq!(info.rows, ThresholdCircuit::new(10).gates().len());
+        assert_eq!(info.num_public_inputs, 2);
+
+        let leaves: Vec<Fp> = (0..4u64).map(Fp::from).collect();
+        let (root, paths) = MerkleWitness::tree(&leaves);
+        let (witness, _) = MerkleMembershipCircuit::new(2)
+            .unwrap()
+            .generate_witness(leaves[2], &paths[2])
+            .unwrap();
+        let proof = session.prove("merkle", witness).unwrap();
+        assert!(session.verify("merkle", &proof, &[root]).unwrap());
+
+        assert!(session.unregister("merkle"));
+        assert!(matches!(
+            session.verify("merkle", &proof, &[root]),
+            Err(ProverError::InvalidInput(_))
+        ));
+    }
+}
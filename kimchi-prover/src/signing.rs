@@ -0,0 +1,244 @@
+//! Schnorr-signed proof envelopes.
+//!
+//! Verifying a proof needs its verifier index and tens of milliseconds;
+//! checking a signature needs neither. An [`EnvelopeSigner`] holding a
+//! device's Pallas keypair signs [`ProofEnvelope`]s, so a relying party can
+//! check that an envelope arrived intact from a known device, and drop
+//! anything else, before running SNARK verification.
+//!
+//! Signatures are Mina Schnorr signatures over one field element,
+//! [`envelope_message`], with the mainnet signature prefix: what
+//! `mina-signer`, or o1js `Signature.create(key, [message])`, produce and
+//! check. Keys are named by their `B62…` Mina addresses.
+//!
+//! A signature binds an envelope to a key, not the key to a device: the
+//! relying party learns which device holds a key when the app registers it,
+//! e.g. together with a device attestation.
+
+use ark_ff::PrimeField;
+use mina_curves::pasta::Fp;
+use mina_signer::{Keypair, NetworkId, PubKey, Signature, Signer};
+use rand::{CryptoRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{ProverError, Result};
+use crate::types::{FieldElement, ProofEnvelope, ScalarElement};
+
+/// Network whose signature prefix envelope signatures use.
+pub const SIGNATURE_NETWORK: NetworkId = NetworkId::MAINNET;
+
+/// The message an envelope signature is over: SHA-256 of the envelope's
+/// MessagePack, truncated to 248 bits to fit the field.
+pub fn envelope_message(envelope: &ProofEnvelope) -> Result<Fp> {
+    let hash = Sha256::digest(envelope.to_bytes()?);
+    Ok(Fp::from_le_bytes_mod_order(&hash[..31]))
+}
+
+/// One field element signed the way o1js signs field arrays.
+#[derive(Clone)]
+struct Message(Fp);
+
+impl mina_signer::Hashable for Message {
+    type D = NetworkId;
+
+    fn to_roinput(&self) -> mina_signer::ROInput {
+        mina_signer::ROInput::new().append_field(self.0)
+    }
+
+    fn domain_string(network: NetworkId) -> Option<String> {
+        match network {
+            NetworkId::MAINNET => "MinaSignatureMainnet",
+            NetworkId::TESTNET => "CodaSignature",
+        }
+        .to_string()
+        .into()
+    }
+}
+
+/// A Mina Schnorr signature.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvelopeSignature {
+    /// x-coordinate of the commitment.
+    pub rx: FieldElement,
+    pub s: ScalarElement,
+}
+
+impl From<Signature> for EnvelopeSignature {
+    fn from(signature: Signature) -> Self {
+        Self {
+            rx: FieldElement(signature.rx),
+            s: ScalarElement(signature.s),
+        }
+    }
+}
+
+impl From<EnvelopeSignature> for Signature {
+    fn from(signature: EnvelopeSignature) -> Self {
+        Signature::new(signature.rx.0, signature.s.0)
+    }
+}
+
+/// Signs proof envelopes with a device-held keypair.
+pub struct EnvelopeSigner {
+    keypair: Keypair,
+}
+
+impl EnvelopeSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self { keypair }
+    }
+
+    /// A signer with the secret key in `mina-signer`'s hex encoding.
+    pub fn from_secret_hex(secret_key: &str) -> Result<Self> {
+        Keypair::from_hex(secret_key)
+            .map(Self::new)
+            .map_err(|e| ProverError::InvalidInput(format!("invalid signing key: {}", e)))
+    }
+
+    /// A signer with a fresh keypair.
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> Result<Self> {
+        Keypair::rand(rng)
+            .map(Self::new)
+            .map_err(|e| ProverError::InternalError(format!("key generation failed: {}", e)))
+    }
+
+    pub fn public_key(&self) -> &PubKey {
+        &self.keypair.public
+    }
+
+    /// The signer's Mina address.
+    pub fn address(&self) -> String {
+        self.keypair.public.into_address()
+    }
+
+    /// Sign `envelope`.
+    pub fn sign(&self, envelope: ProofEnvelope) -> Result<SignedEnvelope> {
+        let message = Message(envelope_message(&envelope)?);
+        let signature = mina_signer::create_kimchi(SIGNATURE_NETWORK).sign(&self.keypair, &message);
+        Ok(SignedEnvelope {
+            envelope,
+            signer: self.address(),
+            signature: signature.into(),
+        })
+    }
+}
+
+impl std::fmt::Debug for EnvelopeSigner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnvelopeSigner")
+            .field("address", &self.address())
+            .finish_non_exhaustive()
+    }
+}
+
+/// A proof envelope with the signature of the device that made it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedEnvelope {
+    pub envelope: ProofEnvelope,
+    /// Mina address of the signing key.
+    pub signer: String,
+    pub signature: EnvelopeSignature,
+}
+
+impl SignedEnvelope {
+    /// Check the signature, and that the signer is `expected_signer` if
+    /// given. Fails with `SignatureInvalid` otherwise.
+    ///
+    /// This says nothing about the proof; verify it after.
+    pub fn check_signature(&self, expected_signer: Option<&str>) -> Result<()> {
+        if let Some(expected) = expected_signer {
+            if expected != self.signer {
+                return Err(ProverError::SignatureInvalid {
+                    signer: expected.to_string(),
+                });
+            }
+        }
+        let public_key = PubKey::from_address(&self.signer)
+            .map_err(|e| ProverError::decode("signer address", e))?;
+        let message = Message(envelope_message(&self.envelope)?);
+        if !mina_signer::create_kimchi(SIGNATURE_NETWORK).verify(
+            &self.signature.clone().into(),
+            &public_key,
+            &message,
+        ) {
+            return Err(ProverError::SignatureInvalid {
+                signer: self.signer.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Serialize to MessagePack.
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        rmp_serde::to_vec_named(self).map_err(|e| ProverError::encode("signed envelope", e))
+    }
+
+    /// Deserialize from MessagePack, rejecting unknown envelope versions.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let signed: Self =
+            rmp_serde::from_slice(bytes).map_err(|e| ProverError::decode("signed envelope", e))?;
+        signed.envelope.check_version()?;
+        Ok(signed)
+    }
+
+    pub fn to_hex(&self) -> Result<String> {
+        Ok(hex::encode(self.to_bytes()?))
+    }
+
+    pub fn from_hex(s: &str) -> Result<Self> {
+        let bytes = hex::decode(s).map_err(|e| ProverError::decode("signed envelope hex", e))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+
+    fn envelope() -> ProofEnvelope {
+        ProofEnvelope {
+            version: crate::PROOF_ENVELOPE_VERSION,
+            circuit_id: "threshold".into(),
+            circuit_version: 1,
+            srs_log2_size: 10,
+            proof: vec![1, 2, 3],
+            public_inputs: vec![FieldElement::from_u64(100)],
+            verifier_index_digest: [7; 32],
+            circuit_hash: [0; 32],
+        }
+    }
+
+    #[test]
+    fn test_signed_envelope() {
+        let signer = EnvelopeSigner::generate(&mut OsRng).unwrap();
+        let signed = signer.sign(envelope()).unwrap();
+        assert_eq!(signed.signer, signer.address());
+        signed.check_signature(None).unwrap();
+        signed.check_signature(Some(&signer.address())).unwrap();
+
+        let decoded = SignedEnvelope::from_hex(&signed.to_hex().unwrap()).unwrap();
+        assert_eq!(decoded, signed);
+
+        let other = EnvelopeSigner::generate(&mut OsRng).unwrap();
+        let err = signed.check_signature(Some(&other.address())).unwrap_err();
+        assert_eq!(err.code(), 404);
+
+        // Any change to the envelope or the claimed signer breaks it
+        let mut tampered = signed.clone();
+        tampered.envelope.public_inputs[0] = FieldElement::from_u64(101);
+        assert!(matches!(
+            tampered.check_signature(None),
+            Err(ProverError::SignatureInvalid { .. })
+        ));
+        let mut tampered = signed;
+        tampered.signer = other.address();
+        assert!(matches!(
+            tampered.check_signature(None),
+            Err(ProverError::SignatureInvalid { .. })
+        ));
+
+        assert!(EnvelopeSigner::from_secret_hex("zz").is_err());
+    }
+}
@@ -0,0 +1,448 @@
+//! Adversarial soundness checks for circuits.
+//!
+//! A circuit is only as sound as its constraints: a witness cell the gates
+//! never read lets a prover claim anything about it. [`SoundnessHarness`]
+//! sets a circuit up once, checks that its honest witness verifies, then
+//! tries to get it to accept witnesses it must reject:
+//!
+//! - each constrained cell corrupted (plus one) or set to an out-of-range
+//!   value (`-1`, `2^64`);
+//! - each public input changed under the honest proof;
+//! - forgeries supplied by the circuit author, e.g. a complete witness for
+//!   a value that wraps around the field.
+//!
+//! A mutation passes if proving fails or the proof does not verify. Cells
+//! are the columns generic gates read through a nonzero coefficient or a
+//! wire, and the wired or nonzero cells of other gates. Cells a circuit
+//! deliberately leaves free are excluded with [`SoundnessTarget::with_free_cells`].
+//!
+//! Every registered circuit has a target in [`registered_target`]; authors
+//! of other circuits build their own with [`SoundnessTarget::new`].
+//!
+//! Available in unit tests and behind the `soundness` feature for
+//! downstream crates.
+
+use ark_ff::{One, Zero};
+use kimchi::circuits::gate::{CircuitGate, GateType};
+use kimchi::circuits::lookup::tables::LookupTable;
+use kimchi::circuits::polynomials::generic::GENERIC_COEFFS;
+use mina_curves::pasta::Fp;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::builder::Cell;
+use crate::circuits::registry::CIRCUIT_IDS;
+use crate::circuits::{
+    Circuit, ThresholdCircuit, ThresholdOperator, ValueWidth, WideThresholdCircuit,
+};
+use crate::cost::GadgetCost;
+use crate::error::{ProverError, Result};
+use crate::prover::{KimchiProver, ProverConfig, COLUMNS};
+
+/// A circuit with an honest witness and the witnesses it must reject.
+#[derive(Clone, Debug)]
+pub struct SoundnessTarget {
+    pub id: String,
+    pub gates: Vec<CircuitGate<Fp>>,
+    pub lookup_tables: Vec<LookupTable<Fp>>,
+    /// A witness that satisfies the circuit.
+    pub witness: [Vec<Fp>; COLUMNS],
+    pub public_inputs: Vec<Fp>,
+    /// Cells whose value the circuit does not constrain, by design.
+    pub free_cells: Vec<Cell>,
+    pub forgeries: Vec<Forgery>,
+}
+
+/// A complete witness and public inputs the circuit must reject.
+#[derive(Clone, Debug)]
+pub struct Forgery {
+    pub name: String,
+    pub witness: [Vec<Fp>; COLUMNS],
+    pub public_inputs: Vec<Fp>,
+}
+
+impl SoundnessTarget {
+    /// A target for `gates` with the honest `witness` and `public_inputs`.
+    pub fn new(
+        id: impl Into<String>,
+        gates: Vec<CircuitGate<Fp>>,
+        witness: [Vec<Fp>; COLUMNS],
+        public_inputs: Vec<Fp>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            gates,
+            lookup_tables: Vec::new(),
+            witness,
+            public_inputs,
+            free_cells: Vec::new(),
+            forgeries: Vec::new(),
+        }
+    }
+
+    /// A target for `circuit` with the honest `witness` and `public_inputs`.
+    pub fn from_circuit(
+        circuit: &dyn Circuit,
+        witness: [Vec<Fp>; COLUMNS],
+        public_inputs: Vec<Fp>,
+    ) -> Self {
+        Self::new(circuit.id(), circuit.gates(), witness, public_inputs)
+    }
+
+    /// Lookup tables the circuit reads from.
+    pub fn with_lookup_tables(mut self, tables: Vec<LookupTable<Fp>>) -> Self {
+        self.lookup_tables = tables;
+        self
+    }
+
+    /// Leave `cells` unmutated.
+    pub fn with_free_cells(mut self, cells: impl IntoIterator<Item = Cell>) -> Self {
+        self.free_cells.extend(cells);
+        self
+    }
+
+    /// Add a forgery the circuit must reject.
+    pub fn with_forgery(
+        mut self,
+        name: impl Into<String>,
+        witness: [Vec<Fp>; COLUMNS],
+        public_inputs: Vec<Fp>,
+    ) -> Self {
+        self.forgeries.push(Forgery {
+            name: name.into(),
+            witness,
+            public_inputs,
+        });
+        self
+    }
+
+    /// Cells the constraints should pin, in row order.
+    pub fn constrained_cells(&self) -> Vec<Cell> {
+        let get =
+            |row: usize, col: usize| self.witness[col].get(row).copied().unwrap_or_else(Fp::zero);
+        let mut cells = Vec::new();
+        for (row, gate) in self.gates.iter().enumerate() {
+            for col in 0..COLUMNS {
+                // Only the first PERMUTS columns are wired
+                let wired = gate
+                    .wires
+                    .get(col)
+                    .is_some_and(|wire| wire.row != row || wire.col != col);
+                let read = if gate.typ == GateType::Generic {
+                    generic_reads(&gate.coeffs, row < self.public_inputs.len(), col)
+                } else {
+                    gate.typ != GateType::Zero && !get(row, col).is_zero()
+                };
+                let cell = Cell::new(row, col);
+                if (wired || read) && !self.free_cells.contains(&cell) {
+                    cells.push(cell);
+                }
+            }
+        }
+        cells
+    }
+}
+
+/// Whether a generic gate reads column `col`.
+fn generic_reads(coeffs: &[Fp], public: bool, col: usize) -> bool {
+    let half = col / 3;
+    let Some(coeffs) = coeffs.get(half * GENERIC_COEFFS..(half + 1) * GENERIC_COEFFS) else {
+        return false;
+    };
+    let k = col % 3;
+    let multiplied = k < 2 && !coeffs[3].is_zero();
+    !coeffs[k].is_zero() || multiplied || (public && col == 0)
+}
+
+/// A change to the honest witness or public inputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    /// One added to a witness cell.
+    Corrupt(Cell),
+    /// A witness cell set to a value.
+    Set(Cell, Fp),
+    /// A public input changed under the honest proof.
+    PublicInput(usize),
+    /// A forgery, by name.
+    Forgery(String),
+}
+
+/// Outcome of checking a target.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SoundnessReport {
+    pub id: String,
+    /// Number of mutations tried.
+    pub checked: usize,
+    /// Mutations the circuit accepted.
+    pub accepted: Vec<Mutation>,
+}
+
+impl SoundnessReport {
+    /// Whether every mutation was rejected.
+    pub fn is_sound(&self) -> bool {
+        self.accepted.is_empty()
+    }
+}
+
+/// Values constrained cells are set to, besides being corrupted.
+fn out_of_range_values() -> [Fp; 2] {
+    [-Fp::one(), Fp::from(u64::MAX) + Fp::one()]
+}
+
+/// Tries mutated witnesses against circuits.
+#[derive(Clone, Debug)]
+pub struct SoundnessHarness {
+    max_cell_mutations: usize,
+    seed: u64,
+}
+
+impl Default for SoundnessHarness {
+    fn default() -> Self {
+        Self {
+            max_cell_mutations: 64,
+            seed: 0,
+        }
+    }
+}
+
+impl SoundnessHarness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Try at most `max` cell mutations per target, sampled when there are
+    /// more. Each one is a proof, so large circuits need a budget.
+    pub fn with_max_cell_mutations(mut self, max: usize) -> Self {
+        self.max_cell_mutations = max;
+        self
+    }
+
+    /// Seed of the cell mutation sampling.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Check `target`.
+    ///
+    /// Fails if the honest witness does not prove and verify.
+    pub fn check(&self, target: &SoundnessTarget) -> Result<SoundnessReport> {
+        let cost = GadgetCost::from_gates(&target.gates).with_lookup_tables(&target.lookup_tables);
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: cost.required_srs_log2_size(),
+            debug: false,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover.setup_with_lookups(
+            target.gates.clone(),
+            target.public_inputs.len(),
+            target.lookup_tables.clone(),
+        )?;
+        let accepts = |witness: [Vec<Fp>; COLUMNS], public_inputs: &[Fp]| {
+            prover
+                .prove(&prover_index, witness)
+                .and_then(|proof| prover.verify(&verifier_index, &proof, public_inputs))
+                .unwrap_or(false)
+        };
+
+        let proof = prover.prove(&prover_index, target.witness.clone())?;
+        if !prover.verify(&verifier_index, &proof, &target.public_inputs)? {
+            return Err(ProverError::VerificationError(format!(
+                "honest witness of {} does not verify",
+                target.id
+            )));
+        }
+
+        let mut mutations = Vec::new();
+        for cell in target.constrained_cells() {
+            mutations.push(Mutation::Corrupt(cell));
+            mutations.extend(
+                out_of_range_values()
+                    .into_iter()
+                    .filter(|value| *value != target.witness[cell.col][cell.row])
+                    .map(|value| Mutation::Set(cell, value)),
+            );
+        }
+        if mutations.len() > self.max_cell_mutations {
+            let mut rng = StdRng::seed_from_u64(self.seed);
+            mutations = mutations
+                .choose_multiple(&mut rng, self.max_cell_mutations)
+                .cloned()
+                .collect();
+        }
+        mutations.extend((0..target.public_inputs.len()).map(Mutation::PublicInput));
+        mutations.extend(
+            target
+                .forgeries
+                .iter()
+                .map(|f| Mutation::Forgery(f.name.clone())),
+        );
+
+        let mut accepted = Vec::new();
+        for mutation in &mutations {
+            let rejected = match mutation {
+                Mutation::Corrupt(cell) => {
+                    let mut witness = target.witness.clone();
+                    witness[cell.col][cell.row] += Fp::one();
+                    !accepts(witness, &target.public_inputs)
+                }
+                Mutation::Set(cell, value) => {
+                    let mut witness = target.witness.clone();
+                    witness[cell.col][cell.row] = *value;
+                    !accepts(witness, &target.public_inputs)
+                }
+                Mutation::PublicInput(i) => {
+                    let mut public_inputs = target.public_inputs.clone();
+                    public_inputs[*i] += Fp::one();
+                    !matches!(
+                        prover.verify(&verifier_index, &proof, &public_inputs),
+                        Ok(true)
+                    )
+                }
+                Mutation::Forgery(name) => {
+                    let forgery = target.forgeries.iter().find(|f| &f.name == name);
+                    let forgery = forgery.expect("mutations name the target's forgeries");
+                    !accepts(forgery.witness.clone(), &forgery.public_inputs)
+                }
+            };
+            if !rejected {
+                accepted.push(mutation.clone());
+            }
+        }
+
+        Ok(SoundnessReport {
+            id: target.id.clone(),
+            checked: mutations.len(),
+            accepted,
+        })
+    }
+
+    /// Panic unless `target` rejects every mutation.
+    pub fn assert_sound(&self, target: &SoundnessTarget) {
+        let report = self.check(target).expect("honest witness failed");
+        assert!(
+            report.is_sound(),
+            "{} accepted mutated witnesses: {:?}",
+            report.id,
+            report.accepted
+        );
+    }
+
+    /// Check every registered circuit.
+    pub fn check_registered(&self) -> Result<Vec<SoundnessReport>> {
+        CIRCUIT_IDS
+            .iter()
+            .map(|id| {
+                let target = registered_target(id).ok_or_else(|| {
+                    ProverError::InternalError(format!("no soundness target for circuit {}", id))
+                })?;
+                self.check(&target?)
+            })
+            .collect()
+    }
+}
+
+/// The soundness target of the registered circuit `id`.
+pub fn registered_target(id: &str) -> Option<Result<SoundnessTarget>> {
+    match id {
+        "threshold" => Some(threshold_target()),
+        _ => ThresholdOperator::parse_circuit_id(id)
+            .map(|(operator, width)| operator_target(operator, width)),
+    }
+}
+
+fn operator_target(operator: ThresholdOperator, width: ValueWidth) -> Result<SoundnessTarget> {
+    // A threshold that needs the full width
+    let threshold = match width {
+        ValueWidth::U64 => Fp::from(10u64),
+        ValueWidth::U128 => Fp::from(1u128 << 100),
+        ValueWidth::Field => -Fp::from(10u64),
+    };
+    // A value that differs from the threshold, so the inverse is_equal_cells
+    // witnesses is bound
+    let circuit = WideThresholdCircuit::new(threshold, operator, width)?;
+    let (witness, public_inputs) = circuit.generate_witness(threshold - Fp::from(3u64))?;
+    Ok(SoundnessTarget::from_circuit(
+        &circuit,
+        witness,
+        public_inputs,
+    ))
+}
+
+fn threshold_target() -> Result<SoundnessTarget> {
+    // The gates pin the private value's row to zero (see the golden fixture)
+    let circuit = ThresholdCircuit::new(10);
+    let (witness, public_inputs) = circuit.generate_witness(0)?;
+
+    // A value above the threshold whose difference wraps around the field
+    let mut wrapped = witness.clone();
+    wrapped[0][2] = Fp::from(11u64);
+    wrapped[1][3] = Fp::from(11u64);
+    wrapped[2][3] = -Fp::one();
+
+    Ok(
+        SoundnessTarget::from_circuit(&circuit, witness, public_inputs.clone())
+            // Row 4's boolean check is not wired to the is_valid public input,
+            // so the is_valid it reads is free
+            .with_free_cells([Cell::new(4, 0)])
+            .with_forgery("wrapped difference", wrapped, public_inputs),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{CircuitBuilder, WitnessBuilder};
+
+    #[test]
+    fn test_registered_circuits() {
+        let reports = SoundnessHarness::new().check_registered().unwrap();
+        assert_eq!(reports.len(), CIRCUIT_IDS.len());
+        for report in reports {
+            assert!(report.is_sound(), "{:?}", report);
+            assert!(report.checked > 0);
+        }
+    }
+
+    #[test]
+    fn test_finds_unconstrained_cell() {
+        // x * y = 0 with y = 0 leaves x free
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        let public = cs.public_input();
+        w.set(public, Fp::from(3u64));
+        let mut coeffs = [Fp::zero(); GENERIC_COEFFS];
+        coeffs[3] = Fp::one();
+        let [x, y, _] = cs.generic_coeffs(coeffs);
+        w.set(x, Fp::from(5u64));
+        w.set(y, Fp::zero());
+
+        let target = SoundnessTarget::new(
+            "unconstrained",
+            cs.build(),
+            w.columns(),
+            vec![Fp::from(3u64)],
+        );
+        assert_eq!(target.constrained_cells(), vec![Cell::new(0, 0), x, y]);
+        let harness = SoundnessHarness::new();
+        let report = harness.check(&target).unwrap();
+        assert!(!report.is_sound());
+        assert!(report.accepted.contains(&Mutation::Corrupt(x)));
+        assert!(!report.accepted.contains(&Mutation::Corrupt(y)));
+        assert!(!report.accepted.contains(&Mutation::PublicInput(0)));
+
+        harness.assert_sound(&target.clone().with_free_cells([x]));
+        assert!(harness
+            .check(&SoundnessTarget::new(
+                "unconstrained",
+                target.gates.clone(),
+                target.witness.clone(),
+                vec![Fp::from(4u64)]
+            ))
+            .is_err());
+
+        let report = harness.with_max_cell_mutations(2).check(&target).unwrap();
+        assert_eq!(report.checked, 3);
+    }
+}
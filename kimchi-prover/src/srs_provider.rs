@@ -0,0 +1,364 @@
+//! Where the SRS comes from.
+//!
+//! Creating an SRS of 2^16 points takes seconds on a phone, and shipping
+//! one adds megabytes to the app. [`ProverConfig::srs_provider`] picks the
+//! source: [`GenerateSrs`] creates it on the device, [`BundledSrs`] loads an
+//! asset shipped with the app, and [`DownloadSrs`] fetches it on first use
+//! through the host app's [`Fetch`], e.g. only over Wi-Fi, so binaries stay
+//! slim. The SRS of a given size is deterministic, so every source yields
+//! the same points; with [`ProverConfig::cache_dir`] set, the prover keeps
+//! whatever the provider returns in its [`DiskCache`] as before.
+//!
+//! Assets are the [`encode_srs`] bytes of the SRS of `2^n` points, named
+//! `srs-{n}.bin`. Downloads are checked against a SHA-256 digest pinned per
+//! size and kept as `srs-{n}-{digest}.bin`, so publishing a new asset is a
+//! matter of updating the pin: the next load fetches it and removes the
+//! files of older pins.
+//!
+//! [`ProverConfig::srs_provider`]: crate::prover::ProverConfig::srs_provider
+//! [`ProverConfig::cache_dir`]: crate::prover::ProverConfig::cache_dir
+//! [`DiskCache`]: crate::disk_cache::DiskCache
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use mina_curves::pasta::Vesta;
+use poly_commitment::ipa::SRS;
+use sha2::{Digest, Sha256};
+
+use crate::error::{ProverError, Result};
+use crate::prover::create_srs;
+use crate::types::Bytes32;
+
+/// Placeholder of `DownloadSrs` URL templates replaced by log2 of the size.
+pub const LOG2_PLACEHOLDER: &str = "{log2}";
+
+/// Source of the SRS, selected with [`ProverConfig::srs_provider`].
+///
+/// [`ProverConfig::srs_provider`]: crate::prover::ProverConfig::srs_provider
+pub trait SrsProvider: Send + Sync + fmt::Debug {
+    /// Short name for logs, e.g. "download".
+    fn name(&self) -> &str;
+
+    /// The SRS of `2^srs_log2_size` points.
+    fn srs(&self, srs_log2_size: usize) -> Result<SRS<Vesta>>;
+}
+
+/// Creates the SRS on the device. The default.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GenerateSrs;
+
+impl SrsProvider for GenerateSrs {
+    fn name(&self) -> &str {
+        "generate"
+    }
+
+    fn srs(&self, srs_log2_size: usize) -> Result<SRS<Vesta>> {
+        Ok(create_srs(srs_log2_size))
+    }
+}
+
+/// Loads the SRS from assets bundled with the app.
+#[derive(Clone, Debug)]
+pub struct BundledSrs {
+    dir: PathBuf,
+    pins: BTreeMap<usize, Bytes32>,
+}
+
+impl BundledSrs {
+    /// Assets named `srs-{n}.bin` in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            pins: BTreeMap::new(),
+        }
+    }
+
+    /// Also check the asset of `2^srs_log2_size` points against `digest`,
+    /// for bundles that are not covered by the app's signature.
+    pub fn with_pin(mut self, srs_log2_size: usize, digest: Bytes32) -> Self {
+        self.pins.insert(srs_log2_size, digest);
+        self
+    }
+}
+
+impl SrsProvider for BundledSrs {
+    fn name(&self) -> &str {
+        "bundled"
+    }
+
+    fn srs(&self, srs_log2_size: usize) -> Result<SRS<Vesta>> {
+        let bytes = fs::read(self.dir.join(asset_name(srs_log2_size)))?;
+        if let Some(pin) = self.pins.get(&srs_log2_size) {
+            check_digest(srs_log2_size, pin, &bytes)?;
+        }
+        decode_srs(srs_log2_size, &bytes)
+    }
+}
+
+/// Transport of [`DownloadSrs`], implemented by the host app, e.g. on its
+/// HTTP client restricted to unmetered networks.
+///
+/// Called on the thread initializing the SRS; it may block.
+pub trait Fetch: Send + Sync {
+    /// The body at `url`.
+    fn fetch(&self, url: &str) -> Result<Vec<u8>>;
+}
+
+/// Downloads the SRS on first use, keeping it in a directory.
+///
+/// Only sizes with a pinned digest are downloaded; anything else fails
+/// rather than trusting the server.
+#[derive(Clone)]
+pub struct DownloadSrs {
+    url_template: String,
+    dir: PathBuf,
+    pins: BTreeMap<usize, Bytes32>,
+    fetch: Arc<dyn Fetch>,
+}
+
+impl DownloadSrs {
+    /// Download from `url_template`, with [`LOG2_PLACEHOLDER`] standing for
+    /// log2 of the size, into `dir`, which is created on first download.
+    pub fn new(
+        url_template: impl Into<String>,
+        dir: impl Into<PathBuf>,
+        fetch: Arc<dyn Fetch>,
+    ) -> Self {
+        Self {
+            url_template: url_template.into(),
+            dir: dir.into(),
+            pins: BTreeMap::new(),
+            fetch,
+        }
+    }
+
+    /// Accept the SRS of `2^srs_log2_size` points if its asset has SHA-256
+    /// `digest`. A new pin replaces the previous one and expires its file.
+    pub fn with_pin(mut self, srs_log2_size: usize, digest: Bytes32) -> Self {
+        self.pins.insert(srs_log2_size, digest);
+        self
+    }
+
+    /// URL of the SRS of `2^srs_log2_size` points.
+    pub fn url(&self, srs_log2_size: usize) -> String {
+        self.url_template
+            .replace(LOG2_PLACEHOLDER, &srs_log2_size.to_string())
+    }
+
+    /// Path the SRS of `2^srs_log2_size` points is kept at, if pinned.
+    pub fn path(&self, srs_log2_size: usize) -> Option<PathBuf> {
+        self.pins.get(&srs_log2_size).map(|pin| {
+            self.dir
+                .join(format!("srs-{}-{}.bin", srs_log2_size, pin.to_hex()))
+        })
+    }
+
+    /// The pinned asset of `2^srs_log2_size` points, from the directory or
+    /// downloaded into it.
+    fn asset(&self, srs_log2_size: usize, pin: &Bytes32, path: &Path) -> Result<Vec<u8>> {
+        match fs::read(path) {
+            Ok(bytes) if check_digest(srs_log2_size, pin, &bytes).is_ok() => return Ok(bytes),
+            Ok(_) => log::warn!("Replacing corrupted SRS {}", path.display()),
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => log::warn!("Failed to read {}: {}", path.display(), e),
+        }
+
+        let url = self.url(srs_log2_size);
+        log::info!("Downloading SRS of 2^{} points from {}", srs_log2_size, url);
+        let bytes = self.fetch.fetch(&url)?;
+        check_digest(srs_log2_size, pin, &bytes)?;
+
+        if let Err(e) = self.store(path, &bytes) {
+            log::warn!("Failed to keep {}: {}", path.display(), e);
+        }
+        self.expire(srs_log2_size, path);
+        Ok(bytes)
+    }
+
+    fn store(&self, path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, bytes)?;
+        fs::rename(&tmp, path).inspect_err(|_| {
+            let _ = fs::remove_file(&tmp);
+        })
+    }
+
+    /// Remove the files of `2^srs_log2_size` points other than `current`.
+    fn expire(&self, srs_log2_size: usize, current: &Path) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return;
+        };
+        let prefix = format!("srs-{}-", srs_log2_size);
+        for path in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+            let stale = path != current
+                && path.extension().is_some_and(|ext| ext == "bin")
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(&prefix));
+            if stale {
+                if let Err(e) = fs::remove_file(&path) {
+                    log::warn!("Failed to remove expired {}: {}", path.display(), e);
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for DownloadSrs {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DownloadSrs")
+            .field("url_template", &self.url_template)
+            .field("dir", &self.dir)
+            .field("pins", &self.pins)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SrsProvider for DownloadSrs {
+    fn name(&self) -> &str {
+        "download"
+    }
+
+    fn srs(&self, srs_log2_size: usize) -> Result<SRS<Vesta>> {
+        let (Some(pin), Some(path)) = (self.pins.get(&srs_log2_size), self.path(srs_log2_size))
+        else {
+            return Err(ProverError::InvalidInput(format!(
+                "no pinned digest for the SRS of 2^{} points",
+                srs_log2_size
+            )));
+        };
+        let bytes = self.asset(srs_log2_size, pin, &path)?;
+        decode_srs(srs_log2_size, &bytes)
+    }
+}
+
+/// Asset bytes of `srs`, as `BundledSrs` and `DownloadSrs` read them.
+pub fn encode_srs(srs: &SRS<Vesta>) -> Result<Vec<u8>> {
+    rmp_serde::to_vec(srs).map_err(|e| ProverError::encode("SRS", e))
+}
+
+/// The SRS of `2^srs_log2_size` points in asset `bytes`.
+pub fn decode_srs(srs_log2_size: usize, bytes: &[u8]) -> Result<SRS<Vesta>> {
+    let srs: SRS<Vesta> =
+        rmp_serde::from_slice(bytes).map_err(|e| ProverError::decode("SRS", e))?;
+    if srs.g.len() != 1 << srs_log2_size {
+        return Err(ProverError::SetupError(format!(
+            "SRS asset has {} points, expected 2^{}",
+            srs.g.len(),
+            srs_log2_size
+        )));
+    }
+    Ok(srs)
+}
+
+/// SHA-256 digest of asset `bytes`, to pin.
+pub fn asset_digest(bytes: &[u8]) -> Bytes32 {
+    Bytes32(Sha256::digest(bytes).into())
+}
+
+fn asset_name(srs_log2_size: usize) -> String {
+    format!("srs-{}.bin", srs_log2_size)
+}
+
+fn check_digest(srs_log2_size: usize, pin: &Bytes32, bytes: &[u8]) -> Result<()> {
+    let actual = asset_digest(bytes);
+    if actual != *pin {
+        return Err(ProverError::SrsDigestMismatch {
+            srs_log2_size,
+            expected: pin.to_hex(),
+            actual: actual.to_hex(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kimchi-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /// Serves one asset, counting requests.
+    struct Server {
+        asset: Vec<u8>,
+        requests: AtomicUsize,
+    }
+
+    impl Fetch for Server {
+        fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+            assert_eq!(url, "https://cdn.example/srs/4.bin");
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            Ok(self.asset.clone())
+        }
+    }
+
+    #[test]
+    fn test_bundled() {
+        let dir = temp_dir("srs-bundled");
+        fs::create_dir_all(&dir).unwrap();
+        let asset = encode_srs(&create_srs(4)).unwrap();
+        fs::write(dir.join(asset_name(4)), &asset).unwrap();
+
+        let bundled = BundledSrs::new(&dir).with_pin(4, asset_digest(&asset));
+        assert_eq!(bundled.srs(4).unwrap().g, GenerateSrs.srs(4).unwrap().g);
+        assert!(matches!(bundled.srs(5), Err(ProverError::Io(_))));
+
+        let tampered = BundledSrs::new(&dir).with_pin(4, Bytes32([0; 32]));
+        assert_eq!(tampered.srs(4).unwrap_err().code(), 103);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_download() {
+        let dir = temp_dir("srs-download");
+        let asset = encode_srs(&create_srs(4)).unwrap();
+        let pin = asset_digest(&asset);
+        let server = Arc::new(Server {
+            asset,
+            requests: AtomicUsize::new(0),
+        });
+        let download = DownloadSrs::new("https://cdn.example/srs/{log2}.bin", &dir, server.clone());
+
+        // Unpinned sizes are refused
+        assert!(matches!(download.srs(4), Err(ProverError::InvalidInput(_))));
+
+        // A wrong pin fails and keeps nothing
+        let wrong = download.clone().with_pin(4, Bytes32([1; 32]));
+        assert!(matches!(
+            wrong.srs(4),
+            Err(ProverError::SrsDigestMismatch {
+                srs_log2_size: 4,
+                ..
+            })
+        ));
+        assert!(!wrong.path(4).unwrap().exists());
+
+        // Downloaded once, then loaded from the directory
+        let pinned = download.with_pin(4, pin);
+        for _ in 0..2 {
+            assert_eq!(pinned.srs(4).unwrap().g.len(), 16);
+        }
+        assert_eq!(server.requests.load(Ordering::SeqCst), 2);
+
+        // A file of an older pin is expired by the next download
+        let stale = dir.join("srs-4-00.bin");
+        fs::write(&stale, b"old").unwrap();
+        fs::remove_file(pinned.path(4).unwrap()).unwrap();
+        pinned.srs(4).unwrap();
+        assert!(!stale.exists());
+        assert!(pinned.path(4).unwrap().exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
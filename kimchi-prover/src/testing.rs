@@ -0,0 +1,411 @@
+//! End-to-end prove/verify harness for gadgets.
+//!
+//! Construction-shape tests only check row counts; the harness wraps a gadget
+//! in a minimal circuit, proves it with its witness and verifies the proof.
+//! Negative tests corrupt witness cells and require the proof to be rejected,
+//! either by the prover or by the verifier.
+//!
+//! `prove_expect_fail` goes one step further and reports which check caught
+//! the invalid witness, as a [`Rejection`]: a test can then show that a
+//! given constraint (by row or region) is what binds a value, not just that
+//! something failed.
+//!
+//! Available in unit tests and behind the `testing` feature for downstream crates.
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use ark_ff::One;
+use kimchi::circuits::gate::CircuitGate;
+use kimchi::circuits::lookup::tables::LookupTable;
+use kimchi::proof::ProverProof;
+use kimchi::verifier_index::VerifierIndex;
+use mina_curves::pasta::{Fp, Vesta};
+use poly_commitment::ipa::SRS;
+
+use crate::builder::{Cell, CircuitBuilder, Gadget, WitnessBuilder};
+use crate::cost::GadgetCost;
+use crate::error::{ProverError, Result};
+use crate::prover::{KimchiProver, ProverConfig, VestaOpeningProof, COLUMNS, FULL_ROUNDS};
+
+/// The check that rejected an invalid witness.
+#[derive(Debug)]
+pub enum Rejection {
+    /// The witness check found an unsatisfied constraint before proving:
+    /// `GateNotSatisfied` or `CopyNotSatisfied` from the circuit builder,
+    /// `ConstraintError` from the prover.
+    Constraint(ProverError),
+    /// Kimchi failed to create a proof (or panicked in its own debug-build
+    /// witness check, reported as `InternalError`).
+    Prover(ProverError),
+    /// A proof was created but does not verify.
+    Verifier,
+}
+
+impl Rejection {
+    /// Row of the constraint that caught the witness, when known.
+    ///
+    /// For a copy constraint this is the row of its first cell.
+    pub fn row(&self) -> Option<usize> {
+        match self {
+            Self::Constraint(ProverError::GateNotSatisfied { row, .. }) => Some(*row),
+            Self::Constraint(ProverError::CopyNotSatisfied { a, .. }) => Some(a.row),
+            Self::Constraint(ProverError::ConstraintError { row, .. }) => *row,
+            _ => None,
+        }
+    }
+
+    /// Region of the constraint that caught the witness, when the circuit
+    /// labels it.
+    pub fn region(&self) -> Option<&str> {
+        match self {
+            Self::Constraint(ProverError::GateNotSatisfied { region, .. }) => region.as_deref(),
+            Self::Constraint(ProverError::CopyNotSatisfied { a_region, .. }) => a_region.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+/// A proof made by [`GadgetHarness::prove_circuit`], kept to check which
+/// public inputs it verifies against.
+pub struct HarnessProof {
+    prover: KimchiProver,
+    verifier_index: VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    proof: ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
+}
+
+impl HarnessProof {
+    /// Whether the proof verifies against `public_inputs`. A verifier error
+    /// counts as a rejection.
+    pub fn verifies(&self, public_inputs: &[Fp]) -> bool {
+        matches!(
+            self.prover
+                .verify(&self.verifier_index, &self.proof, public_inputs),
+            Ok(true)
+        )
+    }
+}
+
+/// Proves and verifies gadgets and circuits.
+#[derive(Default)]
+pub struct GadgetHarness {
+    lookup_tables: Vec<LookupTable<Fp>>,
+}
+
+impl GadgetHarness {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lookup tables the gadgets under test read from.
+    pub fn with_lookup_tables(mut self, tables: Vec<LookupTable<Fp>>) -> Self {
+        self.lookup_tables = tables;
+        self
+    }
+
+    /// Set up the circuit in `cs`, prove it with `w` and verify the proof
+    /// against `public_inputs`, one per public input row of `cs`.
+    ///
+    /// Returns `Ok(false)` if the proof does not verify and an error if it
+    /// could not be generated.
+    pub fn prove(
+        &self,
+        cs: CircuitBuilder,
+        w: &WitnessBuilder,
+        public_inputs: &[Fp],
+    ) -> Result<bool> {
+        if cs.start_row() != 0 || w.current_row() > cs.current_row() {
+            return Err(ProverError::InvalidInput(format!(
+                "harness expects a circuit starting at row 0 with at most {} witness rows, got start row {} and {} rows",
+                cs.current_row(),
+                cs.start_row(),
+                w.current_row()
+            )));
+        }
+
+        if public_inputs.len() != cs.num_public_inputs() {
+            return Err(ProverError::InvalidInput(format!(
+                "circuit has {} public inputs, got {}",
+                cs.num_public_inputs(),
+                public_inputs.len()
+            )));
+        }
+
+        let proof = self.prove_circuit(cs.build(), w.columns(), public_inputs.len())?;
+        Ok(proof.verifies(public_inputs))
+    }
+
+    /// Set up the circuit `gates` with `num_public_inputs` public inputs and
+    /// prove it with `witness`, e.g. a `Circuit`'s gates and generated
+    /// witness.
+    ///
+    /// Returns an error if the proof could not be generated.
+    pub fn prove_circuit(
+        &self,
+        gates: Vec<CircuitGate<Fp>>,
+        witness: [Vec<Fp>; COLUMNS],
+        num_public_inputs: usize,
+    ) -> Result<HarnessProof> {
+        let cost = GadgetCost::from_gates(&gates).with_lookup_tables(&self.lookup_tables);
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: cost.required_srs_log2_size(),
+            debug: false,
+            ..Default::default()
+        });
+
+        let (prover_index, verifier_index) =
+            prover.setup_with_lookups(gates, num_public_inputs, self.lookup_tables.clone())?;
+        let proof = prover.prove(&prover_index, witness)?;
+        Ok(HarnessProof {
+            prover,
+            verifier_index,
+            proof,
+        })
+    }
+
+    /// Build `gadget` at row 0, fill its witness from `inputs`, then prove and verify.
+    pub fn prove_gadget<G: Gadget + ?Sized>(&self, gadget: &G, inputs: &G::Inputs) -> Result<bool> {
+        let (cs, w) = Self::layout(gadget, inputs)?;
+        self.prove(cs, &w, &[])
+    }
+
+    /// Panic unless `gadget` proves and verifies with the witness for `inputs`.
+    pub fn assert_sound<G: Gadget + ?Sized>(&self, gadget: &G, inputs: &G::Inputs) {
+        let (cs, w) = Self::layout(gadget, inputs).expect("witness generation failed");
+        cs.check_witness(&w)
+            .expect("witness does not satisfy the gadget's constraints");
+        assert!(
+            self.prove(cs, &w, &[]).expect("proof generation failed"),
+            "proof did not verify"
+        );
+    }
+
+    /// Prove `cs` with the invalid witness `w`, returning which check
+    /// rejected it.
+    ///
+    /// The circuit builder's witness check runs first, so a failing generic
+    /// gate or copy constraint is named by row and region. Constraints it
+    /// does not evaluate are left to the prover and the verifier.
+    ///
+    /// # Panics
+    ///
+    /// If the proof verifies, or the circuit cannot be set up.
+    pub fn prove_expect_fail(&self, cs: CircuitBuilder, w: &WitnessBuilder) -> Rejection {
+        if let Err(e) = cs.check_witness(w) {
+            return Rejection::Constraint(e);
+        }
+        let num_public_inputs = cs.num_public_inputs();
+        let columns = w.columns();
+        let public_inputs = columns[0][..num_public_inputs.min(columns[0].len())].to_vec();
+        self.prove_circuit_expect_fail(cs.build(), columns, &public_inputs)
+    }
+
+    /// Prove the circuit `gates` with the invalid `witness`, returning which
+    /// check rejected it. The circuit has `public_inputs.len()` public
+    /// inputs, verified against `public_inputs`.
+    ///
+    /// # Panics
+    ///
+    /// If the proof verifies, or the circuit cannot be set up.
+    pub fn prove_circuit_expect_fail(
+        &self,
+        gates: Vec<CircuitGate<Fp>>,
+        witness: [Vec<Fp>; COLUMNS],
+        public_inputs: &[Fp],
+    ) -> Rejection {
+        let cost = GadgetCost::from_gates(&gates).with_lookup_tables(&self.lookup_tables);
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: cost.required_srs_log2_size(),
+            debug: false,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover
+            .setup_with_lookups(gates, public_inputs.len(), self.lookup_tables.clone())
+            .expect("circuit setup failed");
+
+        let proof = match catch_unwind(AssertUnwindSafe(|| prover.prove(&prover_index, witness))) {
+            Ok(Ok(proof)) => proof,
+            Ok(Err(e @ ProverError::ConstraintError { source: None, .. })) => {
+                return Rejection::Constraint(e)
+            }
+            Ok(Err(e)) => return Rejection::Prover(e),
+            Err(_) => {
+                return Rejection::Prover(ProverError::InternalError(
+                    "kimchi panicked while proving".into(),
+                ))
+            }
+        };
+        match prover.verify(&verifier_index, &proof, public_inputs) {
+            Ok(true) => panic!("proof with an invalid witness verified"),
+            Ok(false) | Err(_) => Rejection::Verifier,
+        }
+    }
+
+    /// Panic unless the proof is rejected when `cs` is proven with `w` and
+    /// verified against `public_inputs`.
+    pub fn assert_rejects(&self, cs: CircuitBuilder, w: &WitnessBuilder, public_inputs: &[Fp]) {
+        if let Ok(true) = self.prove(cs, w, public_inputs) {
+            panic!("proof with an invalid witness verified");
+        }
+    }
+
+    /// Panic unless corrupting any one of `cells` in the witness for `inputs`
+    /// makes the proof fail.
+    pub fn assert_rejects_corrupted<G: Gadget + ?Sized>(
+        &self,
+        gadget: &G,
+        inputs: &G::Inputs,
+        cells: &[Cell],
+    ) {
+        for &cell in cells {
+            let (cs, mut w) = Self::layout(gadget, inputs).expect("witness generation failed");
+            corrupt(&mut w, cell);
+            if let Ok(true) = self.prove(cs, &w, &[]) {
+                panic!("proof verified with cell {:?} corrupted", cell);
+            }
+        }
+    }
+
+    fn layout<G: Gadget + ?Sized>(
+        gadget: &G,
+        inputs: &G::Inputs,
+    ) -> Result<(CircuitBuilder, WitnessBuilder)> {
+        let mut cs = CircuitBuilder::new(0);
+        gadget.build(&mut cs);
+        let mut w = WitnessBuilder::new(0);
+        gadget.witness(&mut w, inputs)?;
+        Ok((cs, w))
+    }
+}
+
+/// Add one to a witness cell.
+pub fn corrupt(w: &mut WitnessBuilder, cell: Cell) {
+    let value = w.get(cell);
+    w.set(cell, value + Fp::one());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gadgets::{BooleanGadget, ComparisonGadget};
+    use crate::prover::COLUMNS;
+    use ark_ff::Zero;
+    use kimchi::circuits::polynomials::generic::GenericGateSpec;
+
+    #[test]
+    fn test_cell_gadgets() {
+        let harness = GadgetHarness::new();
+
+        let layout = || {
+            let mut cs = CircuitBuilder::new(0);
+            let mut w = WitnessBuilder::new(0);
+            let a = BooleanGadget::alloc_bit(&mut cs, &mut w, true);
+            let b = BooleanGadget::alloc_bit(&mut cs, &mut w, false);
+            let c = BooleanGadget::xor_cells(&mut cs, &mut w, a, b);
+            (cs, w, c)
+        };
+
+        let (cs, w, _) = layout();
+        assert!(harness.prove(cs, &w, &[]).unwrap());
+
+        let (cs, mut w, c) = layout();
+        corrupt(&mut w, c);
+        harness.assert_rejects(cs, &w, &[]);
+    }
+
+    #[test]
+    fn test_row_gadget() {
+        let mut gadget = ComparisonGadget::new(0);
+        gadget.range_check(3);
+
+        // Bits 1, 0, 1 as b * b = b, then the unwired sum 1 + 4 = 5, packed
+        // two constraints per row
+        let mut rows = vec![[Fp::zero(); COLUMNS]; Gadget::rows(&gadget)];
+        assert_eq!(rows.len(), 2);
+        rows[0][..3].fill(Fp::one());
+        rows[1][..3].fill(Fp::one());
+        rows[1][3..6].copy_from_slice(&[Fp::from(1u64), Fp::from(4u64), Fp::from(5u64)]);
+
+        let harness = GadgetHarness::new();
+        harness.assert_sound(&gadget, &rows);
+        harness.assert_rejects_corrupted(&gadget, &rows, &[Cell::new(0, 2), Cell::new(1, 4)]);
+
+        let wrong_rows = vec![[Fp::zero(); COLUMNS]; Gadget::rows(&gadget) + 1];
+        assert!(harness.prove_gadget(&gadget, &wrong_rows).is_err());
+    }
+
+    #[test]
+    fn test_prove_expect_fail() {
+        let harness = GadgetHarness::new();
+        let layout = || {
+            let mut cs = CircuitBuilder::new(0);
+            let mut w = WitnessBuilder::new(0);
+            let input = cs.public_input();
+            w.set(input, Fp::one());
+            let bit = cs.region("bit", |cs| BooleanGadget::alloc_bit(cs, &mut w, true));
+            cs.copy(input, bit);
+            (cs, w, bit)
+        };
+
+        // 2 is not a bit: the bit's own constraint catches it
+        let (cs, mut w, bit) = layout();
+        let input = Cell::new(0, 0);
+        w.set(input, Fp::from(2u64));
+        w.set(bit, Fp::from(2u64));
+        let rejection = harness.prove_expect_fail(cs, &w);
+        assert_eq!(rejection.row(), Some(bit.row));
+        assert_eq!(rejection.region(), Some("bit"));
+
+        // The copy catches a bit that differs from the public input
+        let (cs, mut w, _) = layout();
+        w.set(input, Fp::zero());
+        let rejection = harness.prove_expect_fail(cs, &w);
+        assert!(matches!(
+            rejection,
+            Rejection::Constraint(ProverError::CopyNotSatisfied { .. })
+        ));
+        assert_eq!(rejection.row(), Some(0));
+
+        // Compiled gates are checked by the prover
+        let (cs, mut w, _) = layout();
+        w.set(input, Fp::zero());
+        let rejection = harness.prove_circuit_expect_fail(cs.build(), w.columns(), &[Fp::zero()]);
+        assert!(matches!(rejection, Rejection::Constraint(_)));
+        assert_eq!(rejection.row(), Some(0));
+    }
+
+    #[test]
+    fn test_public_inputs() {
+        let harness = GadgetHarness::new();
+        let layout = || {
+            let mut cs = CircuitBuilder::new(0);
+            let mut w = WitnessBuilder::new(0);
+            let input = cs.public_input();
+            w.set(input, Fp::one());
+            let bit = BooleanGadget::alloc_bit(&mut cs, &mut w, true);
+            cs.copy(input, bit);
+            (cs, w)
+        };
+
+        let (cs, w) = layout();
+        let proof = harness.prove_circuit(cs.build(), w.columns(), 1).unwrap();
+        assert!(proof.verifies(&[Fp::one()]));
+        assert!(!proof.verifies(&[Fp::zero()]));
+
+        let (cs, w) = layout();
+        assert!(harness.prove(cs, &w, &[Fp::one()]).unwrap());
+        let (cs, w) = layout();
+        harness.assert_rejects(cs, &w, &[Fp::zero()]);
+        let (cs, w) = layout();
+        assert!(harness.prove(cs, &w, &[]).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "proof with an invalid witness verified")]
+    fn test_prove_expect_fail_valid_witness() {
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        BooleanGadget::alloc_bit(&mut cs, &mut w, true);
+        cs.generic(GenericGateSpec::Const(Fp::zero()));
+        GadgetHarness::new().prove_expect_fail(cs, &w);
+    }
+}
@@ -1,61 +1,660 @@
 //! Core types for the Kimchi mobile prover.
 
+use ark_ff::{AdditiveGroup, BigInteger, Field, One, PrimeField, UniformRand, Zero};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
-use mina_curves::pasta::Fp;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use kimchi::proof::ProverProof;
+use kimchi::verifier_index::VerifierIndex;
+use mina_curves::pasta::{Fp, Fq, Vesta};
+use poly_commitment::ipa::SRS;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::ops::{Add, AddAssign, Deref, Mul, MulAssign, Neg, Range, Sub, SubAssign};
 use std::str::FromStr;
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+use crate::error::ProverError;
+use crate::gadgets::cbor::{
+    CborWitness, MAJOR_ARRAY, MAJOR_BYTES, MAJOR_MAP, MAJOR_NEGATIVE, MAJOR_TAG, MAJOR_TEXT,
+    MAJOR_UNSIGNED,
+};
+use crate::prover::{VestaOpeningProof, COLUMNS, FULL_ROUNDS};
+
+/// Size of a serialized field element in bytes.
+pub const FIELD_BYTES: usize = 32;
+
+/// Current `ProofEnvelope` format version.
+pub const PROOF_ENVELOPE_VERSION: u32 = 1;
+
+/// Binable version tag Mina places after the base58 version byte.
+const BASE58_BINABLE_VERSION: u8 = 0x01;
 
 /// A field element in the Pallas scalar field (used by Kimchi/Mina).
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct FieldElement(pub Fp);
 
-impl FieldElement {
-    /// Create a field element from a u64 value.
-    pub fn from_u64(value: u64) -> Self {
-        Self(Fp::from(value))
-    }
+/// An element of `Fq`, the other Pasta field, with the same API as
+/// `FieldElement`. Used for Pallas-side proving and native curve arithmetic.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ScalarElement(pub Fq);
+
+/// Implement a binary operator for owned and borrowed operands, plus its assigning form.
+macro_rules! impl_field_op {
+    ($name:ident, $trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident, $op:tt) => {
+        impl $trait for $name {
+            type Output = $name;
+
+            fn $method(self, rhs: $name) -> $name {
+                $name(self.0 $op rhs.0)
+            }
+        }
+
+        impl $trait<&$name> for &$name {
+            type Output = $name;
+
+            fn $method(self, rhs: &$name) -> $name {
+                $name(self.0 $op rhs.0)
+            }
+        }
+
+        impl $trait<&$name> for $name {
+            type Output = $name;
+
+            fn $method(self, rhs: &$name) -> $name {
+                $name(self.0 $op rhs.0)
+            }
+        }
+
+        impl $assign_trait for $name {
+            fn $assign_method(&mut self, rhs: $name) {
+                self.0 = self.0 $op rhs.0;
+            }
+        }
+
+        impl $assign_trait<&$name> for $name {
+            fn $assign_method(&mut self, rhs: &$name) {
+                self.0 = self.0 $op rhs.0;
+            }
+        }
+    };
+}
+
+/// Implement the shared API of the field element wrappers for `$name` over `$field`.
+macro_rules! impl_field_element {
+    ($name:ident, $field:ident) => {
+        impl $name {
+            /// Create a field element from a u64 value.
+            pub fn from_u64(value: u64) -> Self {
+                Self($field::from(value))
+            }
+
+            /// Create a field element from a decimal string.
+            pub fn from_decimal(s: &str) -> Result<Self, String> {
+                $field::from_str(s)
+                    .map(Self)
+                    .map_err(|_| format!("Invalid decimal string: {}", s))
+            }
+
+            /// Decimal string, as used by o1js and Mina's JSON formats.
+            pub fn to_decimal(&self) -> String {
+                num_bigint::BigUint::from(self.0.into_bigint()).to_string()
+            }
+
+            /// Get the inner field value.
+            pub fn inner(&self) -> &$field {
+                &self.0
+            }
 
-    /// Create a field element from a decimal string.
-    pub fn from_decimal(s: &str) -> Result<Self, String> {
-        Fp::from_str(s)
-            .map(Self)
-            .map_err(|_| format!("Invalid decimal string: {}", s))
+            /// Convert to bytes.
+            pub fn to_bytes(&self) -> Vec<u8> {
+                let mut bytes = Vec::new();
+                self.0.serialize_compressed(&mut bytes).unwrap();
+                bytes
+            }
+
+            /// Create from bytes.
+            pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+                $field::deserialize_compressed(bytes)
+                    .map(Self)
+                    .map_err(|e| format!("Deserialization error: {}", e))
+            }
+        }
+
+        impl $name {
+            /// Sample a uniformly random field element.
+            pub fn random<R: rand::Rng + ?Sized>(rng: &mut R) -> Self {
+                Self($field::rand(rng))
+            }
+
+            /// Canonical little-endian encoding, identical to `to_bytes`.
+            pub fn to_bytes_le(&self) -> [u8; FIELD_BYTES] {
+                let bytes = self.0.into_bigint().to_bytes_le();
+                std::array::from_fn(|i| bytes[i])
+            }
+
+            /// Big-endian encoding.
+            pub fn to_bytes_be(&self) -> [u8; FIELD_BYTES] {
+                let mut bytes = self.to_bytes_le();
+                bytes.reverse();
+                bytes
+            }
+
+            /// Decode a canonical little-endian encoding.
+            ///
+            /// Rejects inputs that are not exactly 32 bytes or not below the modulus.
+            pub fn from_bytes_le(bytes: &[u8]) -> Result<Self, String> {
+                if bytes.len() != FIELD_BYTES {
+                    return Err(format!(
+                        "Expected {} bytes, got {}",
+                        FIELD_BYTES,
+                        bytes.len()
+                    ));
+                }
+                Self::from_bytes(bytes)
+            }
+
+            /// Decode a canonical big-endian encoding.
+            pub fn from_bytes_be(bytes: &[u8]) -> Result<Self, String> {
+                let mut le = bytes.to_vec();
+                le.reverse();
+                Self::from_bytes_le(&le)
+            }
+
+            /// Hex of the little-endian encoding, as used for public inputs over FFI.
+            pub fn to_hex(&self) -> String {
+                hex::encode(self.to_bytes_le())
+            }
+
+            /// Parse the hex of a little-endian encoding, with or without a `0x` prefix.
+            pub fn from_hex(s: &str) -> Result<Self, String> {
+                let digits = s.strip_prefix("0x").unwrap_or(s);
+                let bytes = hex::decode(digits).map_err(|e| format!("Invalid hex string: {}", e))?;
+                Self::from_bytes_le(&bytes)
+            }
+
+            /// Mina-style base58check: `version_byte`, a `0x01` binable version tag and
+            /// the little-endian encoding, followed by a double-SHA-256 checksum.
+            pub fn to_base58(&self, version_byte: u8) -> String {
+                let mut payload = Vec::with_capacity(FIELD_BYTES + 1);
+                payload.push(BASE58_BINABLE_VERSION);
+                payload.extend_from_slice(&self.to_bytes_le());
+                bs58::encode(payload)
+                    .with_check_version(version_byte)
+                    .into_string()
+            }
+
+            /// Parse a Mina-style base58check string produced with `version_byte`.
+            pub fn from_base58(s: &str, version_byte: u8) -> Result<Self, String> {
+                let decoded = bs58::decode(s)
+                    .with_check(Some(version_byte))
+                    .into_vec()
+                    .map_err(|e| format!("Invalid base58check string: {}", e))?;
+                match decoded.as_slice() {
+                    [_, BASE58_BINABLE_VERSION, rest @ ..] => Self::from_bytes_le(rest),
+                    _ => Err("Unsupported base58 payload version".to_string()),
+                }
+            }
+
+            /// Multiplicative inverse, or `None` for zero.
+            pub fn inverse(&self) -> Option<Self> {
+                self.0.inverse().map(Self)
+            }
+
+            /// Raise to the power `exp`.
+            pub fn pow(&self, exp: u64) -> Self {
+                Self(self.0.pow([exp]))
+            }
+
+            /// Add the element to itself.
+            pub fn double(&self) -> Self {
+                Self(self.0.double())
+            }
+        }
+
+        impl_field_op!($name, Add, add, AddAssign, add_assign, +);
+        impl_field_op!($name, Sub, sub, SubAssign, sub_assign, -);
+        impl_field_op!($name, Mul, mul, MulAssign, mul_assign, *);
+
+        impl Neg for $name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                $name(-self.0)
+            }
+        }
+
+        impl Neg for &$name {
+            type Output = $name;
+
+            fn neg(self) -> $name {
+                $name(-self.0)
+            }
+        }
+
+        /// Serialized as the hex string of `to_hex`.
+        impl Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.to_hex())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                Self::from_hex(&s).map_err(serde::de::Error::custom)
+            }
+        }
+
+        impl From<u64> for $name {
+            fn from(value: u64) -> Self {
+                Self::from_u64(value)
+            }
+        }
+
+        impl From<$field> for $name {
+            fn from(value: $field) -> Self {
+                Self(value)
+            }
+        }
+
+        impl Zeroize for $name {
+            fn zeroize(&mut self) {
+                self.0.zeroize();
+            }
+        }
+
+        impl ConstantTimeEq for $name {
+            /// Compare in time independent of the values, unlike `==`.
+            fn ct_eq(&self, other: &Self) -> Choice {
+                // Elements are stored in reduced Montgomery form, so equal
+                // elements have equal limbs
+                self.0 .0 .0[..].ct_eq(&other.0 .0 .0[..])
+            }
+        }
+    };
+}
+
+impl_field_element!(FieldElement, Fp);
+impl_field_element!(ScalarElement, Fq);
+
+/// A secret value, e.g. a private witness input.
+///
+/// It is wiped when dropped, compared in constant time by `==` and
+/// [`ConstantTimeEq::ct_eq`], and cannot be formatted or serialized by
+/// accident: it implements neither `Debug`, `Display` nor `Serialize`.
+///
+/// ```compile_fail
+/// use kimchi_prover::{FieldElement, Secret};
+///
+/// let secret = Secret::new(FieldElement::from_u64(42));
+/// println!("{:?}", secret);
+/// ```
+pub struct Secret<T: Zeroize>(T);
+
+impl<T: Zeroize> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
     }
 
-    /// Get the inner Fp value.
-    pub fn inner(&self) -> &Fp {
+    /// The secret value. Copies made from it are not wiped with it.
+    pub fn expose(&self) -> &T {
         &self.0
     }
 
-    /// Convert to bytes.
-    pub fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
-        self.0.serialize_compressed(&mut bytes).unwrap();
+    /// The secret value, for updating in place.
+    pub fn expose_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Zeroize> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T: Zeroize + Clone> Clone for Secret<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: Zeroize + ConstantTimeEq> ConstantTimeEq for Secret<T> {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl<T: Zeroize + ConstantTimeEq> PartialEq for Secret<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ct_eq(other).into()
+    }
+}
+
+impl<T: Zeroize + ConstantTimeEq> Eq for Secret<T> {}
+
+impl<T: Zeroize> Zeroize for Secret<T> {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl<T: Zeroize> ZeroizeOnDrop for Secret<T> {}
+
+/// Define a fixed-size byte array newtype with hex, base64 and slice conversions.
+macro_rules! define_fixed_bytes {
+    ($(#[$doc:meta])* $name:ident, $len:expr) => {
+        $(#[$doc])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        pub struct $name(pub [u8; $len]);
+
+        impl $name {
+            /// Length in bytes.
+            pub const LEN: usize = $len;
+
+            /// All-zero value.
+            pub const fn zero() -> Self {
+                Self([0u8; $len])
+            }
+
+            pub fn as_bytes(&self) -> &[u8; $len] {
+                &self.0
+            }
+
+            pub fn to_hex(&self) -> String {
+                hex::encode(self.0)
+            }
+
+            /// Parse hex, with or without a `0x` prefix.
+            pub fn from_hex(s: &str) -> crate::Result<Self> {
+                let digits = s.strip_prefix("0x").unwrap_or(s);
+                let bytes = hex::decode(digits).map_err(|e| {
+                    ProverError::InvalidInput(format!("Invalid hex string: {}", e))
+                })?;
+                Self::try_from(bytes.as_slice())
+            }
+
+            /// Standard base64 with padding.
+            pub fn to_base64(&self) -> String {
+                BASE64.encode(self.0)
+            }
+
+            pub fn from_base64(s: &str) -> crate::Result<Self> {
+                let bytes = BASE64.decode(s).map_err(|e| {
+                    ProverError::InvalidInput(format!("Invalid base64 string: {}", e))
+                })?;
+                Self::try_from(bytes.as_slice())
+            }
+        }
+
+        impl TryFrom<&[u8]> for $name {
+            type Error = ProverError;
+
+            fn try_from(bytes: &[u8]) -> crate::Result<Self> {
+                <[u8; $len]>::try_from(bytes).map(Self).map_err(|_| {
+                    ProverError::InvalidInput(format!(
+                        "Expected {} bytes, got {}",
+                        $len,
+                        bytes.len()
+                    ))
+                })
+            }
+        }
+
+        impl From<[u8; $len]> for $name {
+            fn from(bytes: [u8; $len]) -> Self {
+                Self(bytes)
+            }
+        }
+
+        impl Deref for $name {
+            type Target = [u8; $len];
+
+            fn deref(&self) -> &[u8; $len] {
+                &self.0
+            }
+        }
+
+        impl AsRef<[u8]> for $name {
+            fn as_ref(&self) -> &[u8] {
+                &self.0
+            }
+        }
+
+        impl PartialEq<[u8; $len]> for $name {
+            fn eq(&self, other: &[u8; $len]) -> bool {
+                self.0 == *other
+            }
+        }
+
+        /// Serialized as the hex string of `to_hex`.
+        impl Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.to_hex())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                Self::from_hex(&s).map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+define_fixed_bytes!(
+    /// 32 bytes, e.g. a SHA-256 digest.
+    Bytes32,
+    32
+);
+define_fixed_bytes!(
+    /// 64 bytes, e.g. a SHA-512 digest or an Ed25519 signature.
+    Bytes64,
+    64
+);
+define_fixed_bytes!(
+    /// 256 bytes, e.g. an RSA-2048 signature or modulus.
+    Bytes256,
+    256
+);
+
+/// Bits held as field elements (0 or 1), least significant first.
+///
+/// The witness-side counterpart of a bit decomposition, for any word size.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct BitVec(Vec<Fp>);
+
+impl BitVec {
+    /// `len` zero bits.
+    pub fn zeros(len: usize) -> Self {
+        Self(vec![Fp::zero(); len])
+    }
+
+    /// The low `len` bits of `value`; bits past 64 are zero.
+    pub fn from_u64(value: u64, len: usize) -> Self {
+        (0..len).map(|i| i < 64 && (value >> i) & 1 == 1).collect()
+    }
+
+    /// Bits of `bytes`, least significant bit of the first byte first.
+    pub fn from_bytes_le(bytes: &[u8]) -> Self {
         bytes
+            .iter()
+            .flat_map(|byte| (0..8).map(move |i| (byte >> i) & 1 == 1))
+            .collect()
+    }
+
+    /// Wrap field elements, checking that each one is 0 or 1.
+    pub fn from_bits(bits: Vec<Fp>) -> crate::Result<Self> {
+        if let Some(i) = bits.iter().position(|b| !b.is_zero() && !b.is_one()) {
+            return Err(ProverError::InvalidInput(format!(
+                "bit {} is not 0 or 1",
+                i
+            )));
+        }
+        Ok(Self(bits))
+    }
+
+    /// Decompose `value` into `len` bits, or `None` if it does not fit.
+    pub fn unpack(value: Fp, len: usize) -> Option<Self> {
+        let bits = value.into_bigint().to_bits_le();
+        if bits.iter().skip(len).any(|b| *b) {
+            return None;
+        }
+        Some(
+            (0..len)
+                .map(|i| bits.get(i).copied().unwrap_or(false))
+                .collect(),
+        )
+    }
+
+    /// `sum(b_i * 2^i)` as a field element.
+    pub fn pack(&self) -> Fp {
+        self.0
+            .iter()
+            .rev()
+            .fold(Fp::zero(), |acc, bit| acc.double() + bit)
+    }
+
+    /// The low 64 bits as an integer. Elements other than 1 count as 0.
+    pub fn to_u64(&self) -> u64 {
+        self.0
+            .iter()
+            .take(64)
+            .enumerate()
+            .filter(|(_, bit)| bit.is_one())
+            .fold(0, |acc, (i, _)| acc | (1 << i))
+    }
+
+    /// Bytes in the order of `from_bytes_le`, the last one zero-padded.
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        self.0
+            .chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, bit)| bit.is_one())
+                    .fold(0u8, |acc, (i, _)| acc | (1 << i))
+            })
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Whether bit `i` is set.
+    pub fn get(&self, i: usize) -> bool {
+        self.0[i].is_one()
+    }
+
+    pub fn as_slice(&self) -> &[Fp] {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> Vec<Fp> {
+        self.0
+    }
+
+    /// Bits in `range`.
+    pub fn slice(&self, range: Range<usize>) -> Self {
+        Self(self.0[range].to_vec())
+    }
+
+    /// `self` as the low bits followed by `high`.
+    pub fn concat(&self, high: &Self) -> Self {
+        Self([self.0.as_slice(), high.0.as_slice()].concat())
+    }
+
+    /// Right rotation.
+    pub fn rotr(&self, n: usize) -> Self {
+        let len = self.len();
+        Self((0..len).map(|i| self.0[(i + n) % len]).collect())
+    }
+
+    /// Left rotation.
+    pub fn rotl(&self, n: usize) -> Self {
+        self.rotr(self.len() - n % self.len().max(1))
+    }
+
+    /// Right shift, filling with zeros.
+    pub fn shr(&self, n: usize) -> Self {
+        Self(
+            (0..self.len())
+                .map(|i| self.0.get(i + n).copied().unwrap_or_else(Fp::zero))
+                .collect(),
+        )
+    }
+
+    /// Left shift, filling with zeros.
+    pub fn shl(&self, n: usize) -> Self {
+        Self(
+            (0..self.len())
+                .map(|i| i.checked_sub(n).map_or(Fp::zero(), |j| self.0[j]))
+                .collect(),
+        )
+    }
+
+    /// Bitwise XOR: a + b - 2ab.
+    pub fn xor(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a + b - a * b.double())
+    }
+
+    /// Bitwise AND: ab.
+    pub fn and(&self, other: &Self) -> Self {
+        self.zip_with(other, |a, b| a * b)
+    }
+
+    /// Bitwise NOT: 1 - a.
+    pub fn not(&self) -> Self {
+        Self(self.0.iter().map(|a| Fp::one() - a).collect())
+    }
+
+    fn zip_with(&self, other: &Self, f: impl Fn(Fp, Fp) -> Fp) -> Self {
+        assert_eq!(self.len(), other.len(), "bit vector length mismatch");
+        Self(
+            self.0
+                .iter()
+                .zip(&other.0)
+                .map(|(a, b)| f(*a, *b))
+                .collect(),
+        )
     }
+}
 
-    /// Create from bytes.
-    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
-        Fp::deserialize_compressed(bytes)
-            .map(Self)
-            .map_err(|e| format!("Deserialization error: {}", e))
+impl Zeroize for BitVec {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
     }
 }
 
-impl From<u64> for FieldElement {
-    fn from(value: u64) -> Self {
-        Self::from_u64(value)
+impl FromIterator<bool> for BitVec {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        Self(iter.into_iter().map(Fp::from).collect())
     }
 }
 
-impl From<Fp> for FieldElement {
-    fn from(fp: Fp) -> Self {
-        Self(fp)
+impl From<BitVec> for Vec<Fp> {
+    fn from(bits: BitVec) -> Self {
+        bits.0
     }
 }
 
 /// Public inputs to a circuit.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct PublicInput {
     pub values: Vec<FieldElement>,
 }
@@ -83,7 +682,10 @@ impl PublicInput {
 }
 
 /// Witness (private inputs) for a circuit.
-#[derive(Clone, Debug, Default)]
+///
+/// Serializes with the same shape as `WitnessData`. The values are wiped
+/// when it is dropped.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Witness {
     /// Values for each column in the witness.
     pub columns: Vec<Vec<FieldElement>>,
@@ -119,8 +721,77 @@ impl Witness {
     pub fn get(&self, column: usize, row: usize) -> Option<&FieldElement> {
         self.columns.get(column)?.get(row)
     }
+
+    /// Build a witness from prover columns.
+    pub fn from_columns(columns: &[Vec<Fp>; COLUMNS]) -> Self {
+        Self {
+            columns: columns
+                .iter()
+                .map(|col| col.iter().copied().map(FieldElement).collect())
+                .collect(),
+        }
+    }
+
+    /// Convert to the columns `KimchiProver::prove` consumes, zero-padded to `num_rows`.
+    ///
+    /// Missing columns are treated as all zeros. Fails if there are more than
+    /// `COLUMNS` columns or any column is longer than `num_rows`.
+    pub fn into_columns(self, num_rows: usize) -> crate::Result<[Vec<Fp>; COLUMNS]> {
+        if self.columns.len() > COLUMNS {
+            return Err(ProverError::WitnessColumnCount {
+                columns: self.columns.len(),
+                max: COLUMNS,
+            });
+        }
+        if let Some((col, column)) = self
+            .columns
+            .iter()
+            .enumerate()
+            .find(|(_, column)| column.len() > num_rows)
+        {
+            return Err(ProverError::WitnessColumnLength {
+                column: col,
+                rows: column.len(),
+                num_rows,
+            });
+        }
+
+        let mut columns: [Vec<Fp>; COLUMNS] = Default::default();
+        for column in columns.iter_mut() {
+            column
+                .try_reserve_exact(num_rows)
+                .map_err(|_| ProverError::ResourceExhausted {
+                    what: "witness columns",
+                    required: COLUMNS * num_rows * std::mem::size_of::<Fp>(),
+                    limit: None,
+                })?;
+            column.resize(num_rows, Fp::zero());
+        }
+        for (dst, src) in columns.iter_mut().zip(&self.columns) {
+            for (row, value) in src.iter().enumerate() {
+                dst[row] = value.0;
+            }
+        }
+        Ok(columns)
+    }
+}
+
+impl Zeroize for Witness {
+    fn zeroize(&mut self) {
+        for column in self.columns.iter_mut() {
+            column.zeroize();
+        }
+    }
 }
 
+impl Drop for Witness {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for Witness {}
+
 /// Serializable witness data for transport.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct WitnessData {
@@ -139,3 +810,938 @@ impl From<&Witness> for WitnessData {
         }
     }
 }
+
+/// An `io::Write` that appends the lowercase hex encoding of everything
+/// written to a `String`, so a serializer can produce hex without first
+/// buffering the raw bytes.
+pub struct HexWriter<'a> {
+    out: &'a mut String,
+    written: usize,
+}
+
+impl<'a> HexWriter<'a> {
+    pub fn new(out: &'a mut String) -> Self {
+        Self { out, written: 0 }
+    }
+
+    /// Number of raw bytes written so far.
+    pub fn bytes_written(&self) -> usize {
+        self.written
+    }
+}
+
+impl std::io::Write for HexWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        self.out.reserve(2 * buf.len());
+        for &b in buf {
+            self.out.push(DIGITS[(b >> 4) as usize] as char);
+            self.out.push(DIGITS[(b & 0x0F) as usize] as char);
+        }
+        self.written += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A proof together with everything needed to interpret and verify it.
+///
+/// The envelope is the wire format shared by the FFI, the WASM verifier and
+/// server tooling. It serializes as a MessagePack map keyed by field name, so
+/// every side parses the same bytes. The verifier index itself is not
+/// embedded: `verifier_index_digest` is the SHA-256 of its MessagePack
+/// encoding (as returned by `export_verifier_index`), which a relying party
+/// checks against the index it trusts for `circuit_id`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProofEnvelope {
+    /// Envelope format version.
+    pub version: u32,
+    /// Circuit identifier, as returned by `Circuit::id`.
+    pub circuit_id: String,
+    /// Version of the circuit's gate layout.
+    pub circuit_version: u32,
+    /// Log2 of the SRS size the proof was created with.
+    pub srs_log2_size: u32,
+    /// MessagePack serialized `ProverProof`.
+    #[serde(with = "serde_bytes")]
+    pub proof: Vec<u8>,
+    /// Public inputs, in circuit order.
+    pub public_inputs: Vec<FieldElement>,
+    /// SHA-256 of the MessagePack serialized verifier index.
+    #[serde(with = "serde_bytes")]
+    pub verifier_index_digest: [u8; 32],
+    /// `circuit_hash` of the circuit, or all zero if the producer did not
+    /// record it. Left out of both encodings when zero, so envelopes
+    /// without it keep their bytes and signatures.
+    #[serde(default, with = "serde_bytes", skip_serializing_if = "is_zero")]
+    pub circuit_hash: [u8; 32],
+}
+
+fn is_zero(bytes: &[u8; 32]) -> bool {
+    *bytes == [0; 32]
+}
+
+impl ProofEnvelope {
+    /// Wrap a proof with its public inputs and verifier index digest.
+    pub fn new(
+        circuit_id: impl Into<String>,
+        circuit_version: u32,
+        srs_log2_size: u32,
+        proof: &ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
+        public_inputs: &[Fp],
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    ) -> crate::Result<Self> {
+        let proof = rmp_serde::to_vec(proof).map_err(|e| ProverError::encode("proof", e))?;
+        Ok(Self {
+            version: PROOF_ENVELOPE_VERSION,
+            circuit_id: circuit_id.into(),
+            circuit_version,
+            srs_log2_size,
+            proof,
+            public_inputs: public_inputs.iter().map(|fp| FieldElement(*fp)).collect(),
+            verifier_index_digest: Self::verifier_index_digest(verifier_index)?,
+            circuit_hash: [0; 32],
+        })
+    }
+
+    /// Record the `circuit_hash` of the proof's circuit.
+    pub fn with_circuit_hash(mut self, circuit_hash: [u8; 32]) -> Self {
+        self.circuit_hash = circuit_hash;
+        self
+    }
+
+    /// SHA-256 of the MessagePack serialized verifier index.
+    pub fn verifier_index_digest(
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    ) -> crate::Result<[u8; 32]> {
+        let bytes = rmp_serde::to_vec(verifier_index)
+            .map_err(|e| ProverError::encode("verifier index", e))?;
+        Ok(Sha256::digest(bytes).into())
+    }
+
+    /// Check that the envelope was produced for `verifier_index`.
+    pub fn check_verifier_index(
+        &self,
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    ) -> crate::Result<()> {
+        if Self::verifier_index_digest(verifier_index)? != self.verifier_index_digest {
+            return Err(ProverError::VerifierIndexMismatch);
+        }
+        Ok(())
+    }
+
+    /// Deserialize the proof.
+    pub fn proof(&self) -> crate::Result<ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>> {
+        rmp_serde::from_slice(&self.proof).map_err(|e| ProverError::decode("proof", e))
+    }
+
+    /// Public inputs as field elements.
+    pub fn public_inputs_fp(&self) -> Vec<Fp> {
+        self.public_inputs.iter().map(|f| f.0).collect()
+    }
+
+    /// The prover-asserted timestamp in the last public input, for circuits
+    /// whose public inputs are laid out as `schema`.
+    ///
+    /// `schema` must come from the verifier's own record of the circuit,
+    /// not from the envelope's `circuit_id`, which anyone can relabel; see
+    /// `KimchiVerifier::verify_with_max_age`. Fails unless `schema` ends with
+    /// the standard `timestamp` field. The timestamp is only as trustworthy
+    /// as the prover's clock.
+    pub fn timestamp(&self, schema: &crate::circuits::PublicInputSchema) -> crate::Result<u64> {
+        if !schema.has_timestamp() {
+            return Err(ProverError::InvalidInput(format!(
+                "circuit {:?} has no timestamp public input",
+                self.circuit_id
+            )));
+        }
+        let last = self.public_inputs.last().ok_or_else(|| {
+            ProverError::InvalidInput("proof envelope has no timestamp public input".into())
+        })?;
+        let bytes = last.0.into_bigint().to_bytes_le();
+        if bytes[8..].iter().any(|b| *b != 0) {
+            return Err(ProverError::InvalidInput(
+                "proof envelope timestamp is not a u64".into(),
+            ));
+        }
+        Ok(u64::from_le_bytes(std::array::from_fn(|i| bytes[i])))
+    }
+
+    /// Serialize to MessagePack.
+    pub fn to_bytes(&self) -> crate::Result<Vec<u8>> {
+        rmp_serde::to_vec_named(self).map_err(|e| ProverError::encode("proof envelope", e))
+    }
+
+    /// Deserialize from MessagePack, rejecting unknown format versions.
+    pub fn from_bytes(bytes: &[u8]) -> crate::Result<Self> {
+        let envelope: Self =
+            rmp_serde::from_slice(bytes).map_err(|e| ProverError::decode("proof envelope", e))?;
+        envelope.check_version()?;
+        Ok(envelope)
+    }
+
+    /// Fail with `UnsupportedVersion` unless the envelope is in the current
+    /// format.
+    pub fn check_version(&self) -> crate::Result<()> {
+        if self.version != PROOF_ENVELOPE_VERSION {
+            return Err(ProverError::UnsupportedVersion {
+                format: "proof envelope",
+                found: self.version,
+                supported: PROOF_ENVELOPE_VERSION,
+            });
+        }
+        Ok(())
+    }
+
+    /// Hex-encoded MessagePack, encoded as it is serialized.
+    pub fn to_hex(&self) -> crate::Result<String> {
+        let mut hex = String::with_capacity(2 * (self.proof.len() + 128));
+        rmp_serde::encode::write_named(&mut HexWriter::new(&mut hex), self)
+            .map_err(|e| ProverError::encode("proof envelope", e))?;
+        Ok(hex)
+    }
+
+    /// Parse hex-encoded MessagePack.
+    pub fn from_hex(s: &str) -> crate::Result<Self> {
+        let bytes = hex::decode(s).map_err(|e| ProverError::decode("proof envelope hex", e))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+/// Integer map keys of the compact CBOR envelope encoding, in ascending order.
+const CBOR_KEYS: [u64; 7] = [1, 2, 3, 4, 5, 6, 7];
+
+/// CBOR map key of the optional `circuit_hash`, after `CBOR_KEYS`.
+const CBOR_CIRCUIT_HASH_KEY: u64 = 8;
+
+/// COSE tag for `COSE_Sign1` (RFC 9052).
+const COSE_SIGN1_TAG: u32 = 18;
+
+/// COSE header label for the signature algorithm.
+const COSE_HEADER_ALG: u32 = 1;
+
+/// Write a CBOR item header with the shortest argument encoding.
+fn cbor_head(out: &mut Vec<u8>, major: u8, argument: u32) {
+    let initial = major << 5;
+    match argument {
+        0..=23 => out.push(initial | argument as u8),
+        24..=0xFF => out.extend_from_slice(&[initial | 24, argument as u8]),
+        0x100..=0xFFFF => {
+            out.push(initial | 25);
+            out.extend_from_slice(&(argument as u16).to_be_bytes());
+        }
+        _ => {
+            out.push(initial | 26);
+            out.extend_from_slice(&argument.to_be_bytes());
+        }
+    }
+}
+
+/// Write a definite-length byte or text string.
+fn cbor_string(out: &mut Vec<u8>, major: u8, bytes: &[u8]) -> crate::Result<()> {
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| ProverError::encode("CBOR string", "longer than 2^32 - 1 bytes"))?;
+    cbor_head(out, major, len);
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Write a signed integer as CBOR major type 0 or 1.
+fn cbor_int(out: &mut Vec<u8>, value: i32) {
+    if value >= 0 {
+        cbor_head(out, MAJOR_UNSIGNED, value as u32);
+    } else {
+        cbor_head(out, MAJOR_NEGATIVE, (-1 - value as i64) as u32);
+    }
+}
+
+/// Strict reader for the deterministic encoding: headers go through
+/// [`CborWitness::parse_header`], which rejects indefinite lengths,
+/// non-minimal arguments and truncated strings.
+struct CborReader<'a> {
+    what: &'static str,
+    buf: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> CborReader<'a> {
+    fn new(what: &'static str, buf: &'a [u8]) -> Self {
+        Self {
+            what,
+            buf,
+            offset: 0,
+        }
+    }
+
+    fn error(&self, message: impl std::fmt::Display) -> ProverError {
+        ProverError::decode(self.what, format!("{} at offset {}", message, self.offset))
+    }
+
+    /// Read a header of the expected major type and return its argument.
+    fn head(&mut self, major: u8) -> crate::Result<u64> {
+        let header = CborWitness::parse_header(self.buf, self.offset)
+            .ok_or_else(|| self.error("malformed or non-canonical CBOR header"))?;
+        if header.major != major {
+            return Err(self.error(format_args!(
+                "expected major type {}, found {}",
+                major, header.major
+            )));
+        }
+        self.offset += header.header_len;
+        Ok(header.argument)
+    }
+
+    fn uint(&mut self) -> crate::Result<u32> {
+        // Arguments are at most four bytes, so this never truncates.
+        Ok(self.head(MAJOR_UNSIGNED)? as u32)
+    }
+
+    fn int(&mut self) -> crate::Result<i32> {
+        let major = self.buf.get(self.offset).map(|b| b >> 5);
+        let (negative, argument) = if major == Some(MAJOR_NEGATIVE) {
+            (true, self.head(MAJOR_NEGATIVE)?)
+        } else {
+            (false, self.head(MAJOR_UNSIGNED)?)
+        };
+        let value = if negative {
+            -1 - argument as i64
+        } else {
+            argument as i64
+        };
+        i32::try_from(value).map_err(|_| self.error("integer out of range"))
+    }
+
+    fn string(&mut self, major: u8) -> crate::Result<&'a [u8]> {
+        let len = self.head(major)?;
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| self.offset.checked_add(len))
+            .filter(|&end| end <= self.buf.len())
+            .ok_or_else(|| self.error("string runs past the end of the input"))?;
+        let bytes = &self.buf[self.offset..end];
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn bytes(&mut self) -> crate::Result<&'a [u8]> {
+        self.string(MAJOR_BYTES)
+    }
+
+    fn text(&mut self) -> crate::Result<&'a str> {
+        let bytes = self.string(MAJOR_TEXT)?;
+        std::str::from_utf8(bytes).map_err(|e| ProverError::decode(self.what, e))
+    }
+
+    /// Read a map or array header and require exactly `len` entries.
+    fn container(&mut self, major: u8, len: u64) -> crate::Result<()> {
+        let found = self.head(major)?;
+        if found != len {
+            return Err(self.error(format_args!("expected {} entries, found {}", len, found)));
+        }
+        Ok(())
+    }
+
+    fn key(&mut self, expected: u64) -> crate::Result<()> {
+        let key = self.head(MAJOR_UNSIGNED)?;
+        if key != expected {
+            return Err(self.error(format_args!("expected map key {}, found {}", expected, key)));
+        }
+        Ok(())
+    }
+
+    fn finish(&self) -> crate::Result<()> {
+        if self.offset != self.buf.len() {
+            return Err(self.error("trailing bytes"));
+        }
+        Ok(())
+    }
+}
+
+impl ProofEnvelope {
+    /// Compact CBOR (RFC 8949) transport encoding, for QR codes and NFC
+    /// payloads where hex-encoded MessagePack is too large.
+    ///
+    /// The envelope is a map with integer keys:
+    ///
+    /// | key | field                   | type                               |
+    /// |-----|-------------------------|------------------------------------|
+    /// | 1   | `version`               | uint                               |
+    /// | 2   | `circuit_id`            | tstr                               |
+    /// | 3   | `circuit_version`       | uint                               |
+    /// | 4   | `srs_log2_size`         | uint                               |
+    /// | 5   | `proof`                 | bstr (MessagePack `ProverProof`)   |
+    /// | 6   | `public_inputs`         | array of 32-byte little-endian bstr|
+    /// | 7   | `verifier_index_digest` | bstr, 32 bytes                     |
+    /// | 8   | `circuit_hash`          | bstr, 32 bytes; only if not zero   |
+    ///
+    /// Encoding is deterministic (RFC 8949 §4.2.1): shortest-form arguments,
+    /// definite lengths only and keys in ascending order, so equal envelopes
+    /// always produce equal bytes. Arguments are limited to 32 bits.
+    pub fn to_cbor(&self) -> crate::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(self.proof.len() + 98 + 34 * self.public_inputs.len());
+        let has_circuit_hash = !is_zero(&self.circuit_hash);
+        cbor_head(
+            &mut out,
+            MAJOR_MAP,
+            CBOR_KEYS.len() as u32 + has_circuit_hash as u32,
+        );
+        cbor_head(&mut out, MAJOR_UNSIGNED, CBOR_KEYS[0] as u32);
+        cbor_head(&mut out, MAJOR_UNSIGNED, self.version);
+        cbor_head(&mut out, MAJOR_UNSIGNED, CBOR_KEYS[1] as u32);
+        cbor_string(&mut out, MAJOR_TEXT, self.circuit_id.as_bytes())?;
+        cbor_head(&mut out, MAJOR_UNSIGNED, CBOR_KEYS[2] as u32);
+        cbor_head(&mut out, MAJOR_UNSIGNED, self.circuit_version);
+        cbor_head(&mut out, MAJOR_UNSIGNED, CBOR_KEYS[3] as u32);
+        cbor_head(&mut out, MAJOR_UNSIGNED, self.srs_log2_size);
+        cbor_head(&mut out, MAJOR_UNSIGNED, CBOR_KEYS[4] as u32);
+        cbor_string(&mut out, MAJOR_BYTES, &self.proof)?;
+        cbor_head(&mut out, MAJOR_UNSIGNED, CBOR_KEYS[5] as u32);
+        let count = u32::try_from(self.public_inputs.len())
+            .map_err(|_| ProverError::encode("proof envelope CBOR", "too many public inputs"))?;
+        cbor_head(&mut out, MAJOR_ARRAY, count);
+        for input in &self.public_inputs {
+            cbor_string(&mut out, MAJOR_BYTES, &input.to_bytes_le())?;
+        }
+        cbor_head(&mut out, MAJOR_UNSIGNED, CBOR_KEYS[6] as u32);
+        cbor_string(&mut out, MAJOR_BYTES, &self.verifier_index_digest)?;
+        if has_circuit_hash {
+            cbor_head(&mut out, MAJOR_UNSIGNED, CBOR_CIRCUIT_HASH_KEY as u32);
+            cbor_string(&mut out, MAJOR_BYTES, &self.circuit_hash)?;
+        }
+        Ok(out)
+    }
+
+    /// Decode the CBOR transport encoding.
+    ///
+    /// Only the canonical encoding produced by [`ProofEnvelope::to_cbor`] is
+    /// accepted: non-minimal or indefinite lengths, missing, unknown or
+    /// reordered keys and trailing bytes are all rejected, so a payload has
+    /// exactly one valid byte representation.
+    pub fn from_cbor(bytes: &[u8]) -> crate::Result<Self> {
+        let mut r = CborReader::new("proof envelope CBOR", bytes);
+        let entries = r.head(MAJOR_MAP)?;
+        let has_circuit_hash = entries == CBOR_KEYS.len() as u64 + 1;
+        if entries != CBOR_KEYS.len() as u64 && !has_circuit_hash {
+            return Err(r.error(format_args!(
+                "expected {} or {} entries, found {}",
+                CBOR_KEYS.len(),
+                CBOR_KEYS.len() + 1,
+                entries
+            )));
+        }
+        r.key(CBOR_KEYS[0])?;
+        let version = r.uint()?;
+        if version != PROOF_ENVELOPE_VERSION {
+            return Err(ProverError::UnsupportedVersion {
+                format: "proof envelope",
+                found: version,
+                supported: PROOF_ENVELOPE_VERSION,
+            });
+        }
+        r.key(CBOR_KEYS[1])?;
+        let circuit_id = r.text()?.to_string();
+        r.key(CBOR_KEYS[2])?;
+        let circuit_version = r.uint()?;
+        r.key(CBOR_KEYS[3])?;
+        let srs_log2_size = r.uint()?;
+        r.key(CBOR_KEYS[4])?;
+        let proof = r.bytes()?.to_vec();
+        r.key(CBOR_KEYS[5])?;
+        let count = r.head(MAJOR_ARRAY)?;
+        let mut public_inputs = Vec::new();
+        for _ in 0..count {
+            let bytes = r.bytes()?;
+            if bytes.len() != FIELD_BYTES {
+                return Err(r.error("public input is not 32 bytes"));
+            }
+            let input = FieldElement::from_bytes_le(bytes)
+                .map_err(|e| ProverError::decode("proof envelope CBOR", e))?;
+            public_inputs.push(input);
+        }
+        r.key(CBOR_KEYS[6])?;
+        let verifier_index_digest = r
+            .bytes()?
+            .try_into()
+            .map_err(|_| r.error("verifier index digest is not 32 bytes"))?;
+        let mut circuit_hash = [0; 32];
+        if has_circuit_hash {
+            r.key(CBOR_CIRCUIT_HASH_KEY)?;
+            circuit_hash = r
+                .bytes()?
+                .try_into()
+                .map_err(|_| r.error("circuit hash is not 32 bytes"))?;
+            if is_zero(&circuit_hash) {
+                return Err(r.error("zero circuit hash is encoded by leaving it out"));
+            }
+        }
+        r.finish()?;
+
+        Ok(Self {
+            version,
+            circuit_id,
+            circuit_version,
+            srs_log2_size,
+            proof,
+            public_inputs,
+            verifier_index_digest,
+            circuit_hash,
+        })
+    }
+}
+
+/// A CBOR envelope wrapped in a tagged `COSE_Sign1` structure (RFC 9052).
+///
+/// The prover does not hold signing keys, so signing is two-step: build the
+/// structure with [`CoseSign1::new`], sign [`CoseSign1::to_be_signed`] with
+/// the platform key store, then set `signature`. The protected header
+/// carries only the algorithm (label 1) and the unprotected header is empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoseSign1 {
+    /// COSE algorithm identifier, e.g. -7 (ES256) or -8 (EdDSA).
+    pub alg: i32,
+    /// CBOR-encoded [`ProofEnvelope`].
+    pub payload: Vec<u8>,
+    /// Signature over [`CoseSign1::to_be_signed`]; empty until signed.
+    pub signature: Vec<u8>,
+}
+
+impl CoseSign1 {
+    /// Wrap `envelope` for signing with `alg`.
+    pub fn new(envelope: &ProofEnvelope, alg: i32) -> crate::Result<Self> {
+        Ok(Self {
+            alg,
+            payload: envelope.to_cbor()?,
+            signature: Vec::new(),
+        })
+    }
+
+    /// Serialized protected header, `{1: alg}`.
+    fn protected(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        cbor_head(&mut out, MAJOR_MAP, 1);
+        cbor_head(&mut out, MAJOR_UNSIGNED, COSE_HEADER_ALG);
+        cbor_int(&mut out, self.alg);
+        out
+    }
+
+    /// The `Sig_structure` to sign: `["Signature1", protected, h'', payload]`.
+    pub fn to_be_signed(&self) -> crate::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(self.payload.len() + 32);
+        cbor_head(&mut out, MAJOR_ARRAY, 4);
+        cbor_string(&mut out, MAJOR_TEXT, b"Signature1")?;
+        cbor_string(&mut out, MAJOR_BYTES, &self.protected())?;
+        cbor_string(&mut out, MAJOR_BYTES, &[])?;
+        cbor_string(&mut out, MAJOR_BYTES, &self.payload)?;
+        Ok(out)
+    }
+
+    /// Serialize as `18([protected, {}, payload, signature])`.
+    pub fn to_bytes(&self) -> crate::Result<Vec<u8>> {
+        let mut out = Vec::with_capacity(self.payload.len() + self.signature.len() + 16);
+        cbor_head(&mut out, MAJOR_TAG, COSE_SIGN1_TAG);
+        cbor_head(&mut out, MAJOR_ARRAY, 4);
+        cbor_string(&mut out, MAJOR_BYTES, &self.protected())?;
+        cbor_head(&mut out, MAJOR_MAP, 0);
+        cbor_string(&mut out, MAJOR_BYTES, &self.payload)?;
+        cbor_string(&mut out, MAJOR_BYTES, &self.signature)?;
+        Ok(out)
+    }
+
+    /// Parse a tagged `COSE_Sign1` in the layout written by
+    /// [`CoseSign1::to_bytes`]. The signature is not checked here; verify it
+    /// over [`CoseSign1::to_be_signed`] before trusting the payload.
+    pub fn from_bytes(bytes: &[u8]) -> crate::Result<Self> {
+        let mut r = CborReader::new("COSE_Sign1", bytes);
+        let tag = r.head(MAJOR_TAG)?;
+        if tag != COSE_SIGN1_TAG as u64 {
+            return Err(r.error(format_args!(
+                "expected tag {}, found {}",
+                COSE_SIGN1_TAG, tag
+            )));
+        }
+        r.container(MAJOR_ARRAY, 4)?;
+        let protected = r.bytes()?;
+        r.container(MAJOR_MAP, 0)?;
+        let payload = r.bytes()?.to_vec();
+        let signature = r.bytes()?.to_vec();
+        r.finish()?;
+
+        let mut h = CborReader::new("COSE_Sign1 protected header", protected);
+        h.container(MAJOR_MAP, 1)?;
+        h.key(COSE_HEADER_ALG as u64)?;
+        let alg = h.int()?;
+        h.finish()?;
+
+        Ok(Self {
+            alg,
+            payload,
+            signature,
+        })
+    }
+
+    /// Decode the wrapped envelope.
+    pub fn envelope(&self) -> crate::Result<ProofEnvelope> {
+        ProofEnvelope::from_cbor(&self.payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_arithmetic() {
+        let a = FieldElement::from_u64(7);
+        let b = FieldElement::from_u64(5);
+
+        assert_eq!(&a + &b, FieldElement::from_u64(12));
+        assert_eq!(&a - &b, FieldElement::from_u64(2));
+        assert_eq!(&a * &b, FieldElement::from_u64(35));
+        assert_eq!(-(&b - &a), FieldElement::from_u64(2));
+        assert_eq!(b.clone() - a.clone() + a.clone(), b);
+
+        let mut c = a.clone();
+        c += &b;
+        c *= FieldElement::from_u64(2);
+        c -= &a;
+        assert_eq!(c, FieldElement::from_u64(17));
+    }
+
+    #[test]
+    fn test_constant_time_eq_and_secret() {
+        let a = FieldElement::from_u64(7);
+        assert!(bool::from(a.ct_eq(&FieldElement::from_u64(7))));
+        assert!(!bool::from(a.ct_eq(&FieldElement::from_u64(8))));
+        assert!(bool::from(
+            ScalarElement::from_u64(3).ct_eq(&ScalarElement::from_u64(3))
+        ));
+
+        let mut secret = Secret::new(a.clone());
+        assert_eq!(secret.expose(), &a);
+        assert!(secret == Secret::from(FieldElement::from_u64(7)));
+        assert!(secret != Secret::new(FieldElement::from_u64(8)));
+        *secret.expose_mut() += FieldElement::from_u64(1);
+        assert!(secret.clone() == Secret::new(FieldElement::from_u64(8)));
+        secret.zeroize();
+        assert_eq!(secret.expose(), &FieldElement::from_u64(0));
+    }
+
+    #[test]
+    fn test_byte_and_hex_encodings() {
+        let a = FieldElement::from_u64(0x0102);
+        assert_eq!(a.to_bytes_le()[..2], [0x02, 0x01]);
+        assert_eq!(a.to_bytes_be()[30..], [0x01, 0x02]);
+        assert_eq!(a.to_bytes_le().to_vec(), a.to_bytes());
+        assert_eq!(FieldElement::from_bytes_be(&a.to_bytes_be()).unwrap(), a);
+
+        let hex = a.to_hex();
+        assert_eq!(FieldElement::from_hex(&hex).unwrap(), a);
+        assert_eq!(FieldElement::from_hex(&format!("0x{}", hex)).unwrap(), a);
+        assert!(FieldElement::from_hex("0102").is_err());
+        assert!(FieldElement::from_bytes_le(&[0xFF; FIELD_BYTES]).is_err());
+    }
+
+    #[test]
+    fn test_base58_and_random() {
+        let mut rng = rand::thread_rng();
+        let a = FieldElement::random(&mut rng);
+        assert_ne!(a, FieldElement::random(&mut rng));
+
+        let encoded = a.to_base58(0x05);
+        assert_eq!(FieldElement::from_base58(&encoded, 0x05).unwrap(), a);
+        assert!(FieldElement::from_base58(&encoded, 0x06).is_err());
+
+        let mut corrupted = encoded.into_bytes();
+        corrupted[10] = if corrupted[10] == b'2' { b'3' } else { b'2' };
+        assert!(FieldElement::from_base58(&String::from_utf8(corrupted).unwrap(), 0x05).is_err());
+    }
+
+    #[test]
+    fn test_serde() {
+        let a = FieldElement::from_u64(42);
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(json, format!("\"{}\"", a.to_hex()));
+        assert_eq!(serde_json::from_str::<FieldElement>(&json).unwrap(), a);
+        assert!(serde_json::from_str::<FieldElement>("\"zz\"").is_err());
+
+        let mut public = PublicInput::new();
+        public.push_u64(1);
+        public.push_u64(2);
+        let json = serde_json::to_string(&public).unwrap();
+        let decoded: PublicInput = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.to_fp_vec(), public.to_fp_vec());
+
+        let mut witness = Witness::with_columns(2);
+        witness.set(1, 3, FieldElement::from_u64(7));
+        let json = serde_json::to_string(&witness).unwrap();
+        let data: WitnessData = serde_json::from_str(&json).unwrap();
+        assert_eq!(data.columns[1][3], FieldElement::from_u64(7).to_hex());
+        let decoded: Witness = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.get(1, 3), witness.get(1, 3));
+    }
+
+    #[test]
+    fn test_into_columns() {
+        let mut witness = Witness::new();
+        witness.set(0, 0, FieldElement::from_u64(5));
+        witness.set(2, 1, FieldElement::from_u64(9));
+
+        let columns = witness.clone().into_columns(4).unwrap();
+        assert!(columns.iter().all(|col| col.len() == 4));
+        assert_eq!(columns[0][0], Fp::from(5u64));
+        assert_eq!(columns[2][1], Fp::from(9u64));
+        assert_eq!(columns[COLUMNS - 1][3], Fp::from(0u64));
+        assert_eq!(Witness::from_columns(&columns).get(2, 1), witness.get(2, 1));
+
+        assert!(witness.clone().into_columns(1).is_err());
+        witness.set(COLUMNS, 0, FieldElement::from_u64(1));
+        assert!(witness.into_columns(4).is_err());
+    }
+
+    #[test]
+    fn test_proof_envelope() {
+        use crate::builder::{CircuitBuilder, WitnessBuilder};
+        use crate::prover::{KimchiProver, ProverConfig};
+        use kimchi::circuits::polynomials::generic::GenericGateSpec;
+
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        for v in [7u64, 9] {
+            let [cell, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(v)));
+            w.set(cell, Fp::from(v));
+        }
+
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            debug: false,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover.setup(cs.build(), 0).unwrap();
+        let proof = prover.prove(&prover_index, w.columns()).unwrap();
+        let public_inputs: Vec<Fp> = Vec::new();
+
+        let envelope =
+            ProofEnvelope::new("constant", 1, 10, &proof, &public_inputs, &verifier_index).unwrap();
+        let decoded = ProofEnvelope::from_hex(&envelope.to_hex().unwrap()).unwrap();
+        assert_eq!(decoded, envelope);
+        assert_eq!(decoded.public_inputs_fp(), public_inputs);
+        assert!(prover.verify_envelope(&verifier_index, &decoded).unwrap());
+
+        let mut other = envelope.clone();
+        other.verifier_index_digest[0] ^= 1;
+        assert!(prover.verify_envelope(&verifier_index, &other).is_err());
+
+        other = envelope;
+        other.version = PROOF_ENVELOPE_VERSION + 1;
+        assert!(ProofEnvelope::from_bytes(&other.to_bytes().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_hex_writer() {
+        use std::io::Write;
+
+        let mut hex = String::new();
+        let mut writer = HexWriter::new(&mut hex);
+        writer.write_all(&[0x00, 0xAB]).unwrap();
+        writer.write_all(&[0x7F, 0xFF, 0x10]).unwrap();
+        assert_eq!(writer.bytes_written(), 5);
+        assert_eq!(hex, "00ab7fff10");
+
+        let envelope = ProofEnvelope {
+            version: PROOF_ENVELOPE_VERSION,
+            circuit_id: "threshold".into(),
+            circuit_version: 1,
+            srs_log2_size: 10,
+            proof: vec![0x5A; 40],
+            public_inputs: vec![FieldElement::from_u64(3)],
+            verifier_index_digest: [2; 32],
+            circuit_hash: [0; 32],
+        };
+        assert_eq!(
+            envelope.to_hex().unwrap(),
+            hex::encode(envelope.to_bytes().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_proof_envelope_cbor() {
+        let envelope = ProofEnvelope {
+            version: PROOF_ENVELOPE_VERSION,
+            circuit_id: "threshold".into(),
+            circuit_version: 1,
+            srs_log2_size: 14,
+            proof: vec![0xAB; 300],
+            public_inputs: vec![FieldElement::from_u64(100), -FieldElement::from_u64(1)],
+            verifier_index_digest: [7; 32],
+            circuit_hash: [0; 32],
+        };
+
+        let cbor = envelope.to_cbor().unwrap();
+        assert_eq!(&cbor[..3], &[0xA7, 0x01, 0x01]);
+        assert_eq!(ProofEnvelope::from_cbor(&cbor).unwrap(), envelope);
+        assert!(cbor.len() < envelope.to_bytes().unwrap().len());
+
+        // Trailing bytes
+        let mut bad = cbor.clone();
+        bad.push(0);
+        assert!(ProofEnvelope::from_cbor(&bad).is_err());
+
+        // Non-minimal argument for the version
+        let mut bad = vec![0xA7, 0x01, 0x18, 0x01];
+        bad.extend_from_slice(&cbor[3..]);
+        assert!(ProofEnvelope::from_cbor(&bad).is_err());
+
+        // Indefinite-length map
+        let mut bad = cbor.clone();
+        bad[0] = 0xBF;
+        assert!(ProofEnvelope::from_cbor(&bad).is_err());
+
+        // Unknown key
+        let mut bad = cbor.clone();
+        bad[1] = 0x08;
+        assert!(ProofEnvelope::from_cbor(&bad).is_err());
+
+        // A circuit id of 2^32 - 1 bytes, whose end overflows on 32-bit
+        // targets
+        let mut bad = vec![0xA7, 0x01, 0x01, 0x02, 0x7A];
+        bad.extend_from_slice(&[0xFF; 4]);
+        assert!(ProofEnvelope::from_cbor(&bad).is_err());
+
+        // A circuit hash is an eighth entry, only present when set
+        let hashed = envelope.clone().with_circuit_hash([9; 32]);
+        let hashed_cbor = hashed.to_cbor().unwrap();
+        assert_eq!(hashed_cbor[0], 0xA8);
+        assert_eq!(&hashed_cbor[1..cbor.len()], &cbor[1..]);
+        assert_eq!(ProofEnvelope::from_cbor(&hashed_cbor).unwrap(), hashed);
+        let mut bad = hashed_cbor.clone();
+        let len = bad.len();
+        bad[len - 32..].fill(0);
+        assert!(ProofEnvelope::from_cbor(&bad).is_err());
+
+        // Likewise in MessagePack
+        let bytes = envelope.to_bytes().unwrap();
+        assert!(!bytes.windows(12).any(|w| w == b"circuit_hash"));
+        let decoded = ProofEnvelope::from_bytes(&hashed.to_bytes().unwrap()).unwrap();
+        assert_eq!(decoded.circuit_hash, [9; 32]);
+
+        let mut other = envelope.clone();
+        other.version = PROOF_ENVELOPE_VERSION + 1;
+        assert!(matches!(
+            ProofEnvelope::from_cbor(&other.to_cbor().unwrap()),
+            Err(ProverError::UnsupportedVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_cose_sign1() {
+        let envelope = ProofEnvelope {
+            version: PROOF_ENVELOPE_VERSION,
+            circuit_id: "age".into(),
+            circuit_version: 2,
+            srs_log2_size: 12,
+            proof: vec![1, 2, 3],
+            public_inputs: vec![FieldElement::from_u64(18)],
+            verifier_index_digest: [0; 32],
+            circuit_hash: [0; 32],
+        };
+
+        let mut cose = CoseSign1::new(&envelope, -7).unwrap();
+        let to_be_signed = cose.to_be_signed().unwrap();
+        // ["Signature1", h'A10126', h'', payload]
+        assert_eq!(&to_be_signed[..12], b"\x84\x6aSignature1");
+        assert_eq!(&to_be_signed[12..16], &[0x43, 0xA1, 0x01, 0x26]);
+        cose.signature = Sha256::digest(&to_be_signed).to_vec();
+
+        let bytes = cose.to_bytes().unwrap();
+        assert_eq!(&bytes[..2], &[0xD2, 0x84]);
+        let decoded = CoseSign1::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, cose);
+        assert_eq!(decoded.to_be_signed().unwrap(), to_be_signed);
+        assert_eq!(decoded.envelope().unwrap(), envelope);
+
+        // Untagged COSE_Sign1
+        assert!(CoseSign1::from_bytes(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn test_scalar_element() {
+        let a = ScalarElement::from_u64(7);
+        let b = ScalarElement::from(Fq::from(5u64));
+        assert_eq!(&a * &b, ScalarElement::from_u64(35));
+        assert_eq!(&a * &a.inverse().unwrap(), ScalarElement::from_u64(1));
+        assert_eq!(ScalarElement::from_hex(&a.to_hex()).unwrap(), a);
+        assert_eq!(ScalarElement::from_decimal(&a.to_decimal()).unwrap(), a);
+
+        // -1 differs between the two fields
+        let minus_one_q = (-ScalarElement::from_u64(1)).to_decimal();
+        assert_ne!(minus_one_q, (-FieldElement::from_u64(1)).to_decimal());
+
+        let json = serde_json::to_string(&b).unwrap();
+        assert_eq!(serde_json::from_str::<ScalarElement>(&json).unwrap(), b);
+    }
+
+    #[test]
+    fn test_fixed_bytes() {
+        let digest = Bytes32::from([0xAB; 32]);
+        assert_eq!(Bytes32::from_hex(&digest.to_hex()).unwrap(), digest);
+        assert_eq!(
+            Bytes32::from_hex(&format!("0x{}", digest.to_hex())).unwrap(),
+            digest
+        );
+        assert_eq!(Bytes32::from_base64(&digest.to_base64()).unwrap(), digest);
+        assert_eq!(digest, [0xAB; 32]);
+
+        assert!(Bytes32::try_from(&[0u8; 31][..]).is_err());
+        assert!(Bytes64::from_hex(&digest.to_hex()).is_err());
+        assert_eq!(Bytes256::try_from(&[7u8; 256][..]).unwrap()[255], 7);
+
+        let json = serde_json::to_string(&Bytes64::zero()).unwrap();
+        assert_eq!(json, format!("\"{}\"", "00".repeat(64)));
+        assert_eq!(
+            serde_json::from_str::<Bytes64>(&json).unwrap(),
+            Bytes64::zero()
+        );
+    }
+
+    #[test]
+    fn test_bit_vec() {
+        let bits = BitVec::from_u64(0b1011, 8);
+        assert_eq!(bits.len(), 8);
+        assert_eq!(bits.to_u64(), 0b1011);
+        assert_eq!(bits.pack(), Fp::from(0b1011u64));
+        assert_eq!(BitVec::unpack(Fp::from(0b1011u64), 8).unwrap(), bits);
+        assert!(BitVec::unpack(Fp::from(256u64), 8).is_none());
+
+        assert_eq!(bits.rotr(1).to_u64(), 0b1000_0101);
+        assert_eq!(bits.rotl(1).to_u64(), 0b1_0110);
+        assert_eq!(bits.rotl(1).rotr(1), bits);
+        assert_eq!(bits.shr(1).to_u64(), 0b101);
+        assert_eq!(bits.shl(5).to_u64(), 0b0110_0000);
+        assert_eq!(bits.slice(1..4).to_u64(), 0b101);
+        assert_eq!(bits.slice(0..4).concat(&bits.slice(4..8)), bits);
+
+        let other = BitVec::from_u64(0b0110, 8);
+        assert_eq!(bits.xor(&other).to_u64(), 0b1101);
+        assert_eq!(bits.and(&other).to_u64(), 0b0010);
+        assert_eq!(bits.not().to_u64(), 0b1111_0100);
+
+        let wide = BitVec::from_bytes_le(&[0xFF; 32]);
+        assert_eq!(wide.len(), 256);
+        assert_eq!(wide.to_bytes_le(), vec![0xFF; 32]);
+
+        assert!(BitVec::from_bits(vec![Fp::one(), Fp::from(2u64)]).is_err());
+    }
+
+    #[test]
+    fn test_inverse_pow_double() {
+        let a = FieldElement::from_u64(3);
+        assert_eq!(&a * &a.inverse().unwrap(), FieldElement::from_u64(1));
+        assert!(FieldElement::from_u64(0).inverse().is_none());
+        assert_eq!(a.pow(4), FieldElement::from_u64(81));
+        assert_eq!(a.pow(0), FieldElement::from_u64(1));
+        assert_eq!(a.double(), FieldElement::from_u64(6));
+    }
+}
@@ -0,0 +1,578 @@
+//! Verify-only mode for relying parties.
+//!
+//! Checking a proof needs the SRS and the verifier index of its circuit,
+//! nothing else. `KimchiVerifier` holds just those: no prover indices,
+//! proving buffers or compiled-circuit cache, and circuits are never
+//! compiled on the device, since verifier indices arrive serialized (as
+//! from `export_verifier_index`).
+//!
+//! With a [`VerifierIndexRegistry`] set, the verifier index an envelope
+//! names is loaded from the registry on first use instead.
+//!
+//! Checks that depend on the circuit, such as a proof's age or attestation,
+//! take it from the verifier index the envelope names, never from the
+//! envelope's own `circuit_id`: indices are bound to a circuit when added
+//! with [`KimchiVerifier::add_circuit_verifier_index`] or registered under
+//! one in the registry.
+//!
+//! The SRS must have the size the proofs were made with: the IPA opening
+//! check runs over all of its points, so it cannot be trimmed further.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use kimchi::proof::ProverProof;
+use kimchi::verifier_index::VerifierIndex;
+use mina_curves::pasta::{Fp, Vesta};
+use poly_commitment::ipa::SRS;
+use sha2::{Digest, Sha256};
+
+use crate::circuits::{registry, Circuit, DeviceAttestation};
+use crate::cost;
+use crate::error::{ProverError, Result};
+use crate::index_registry::VerifierIndexRegistry;
+use crate::metrics::{self, MetricsSink, NoopMetrics, Phase, Timer};
+use crate::profiling;
+use crate::prover::{self, restore_verifier_index, VestaOpeningProof, FULL_ROUNDS};
+use crate::signing::SignedEnvelope;
+use crate::types::ProofEnvelope;
+
+/// Verifier index restored against the verifier's SRS.
+pub type SharedVerifierIndex = Arc<VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>>;
+
+/// How far in the future a proof's timestamp may be, in seconds, to allow
+/// for the prover's clock running ahead of the verifier's.
+pub const MAX_CLOCK_SKEW_SECS: u64 = 300;
+
+/// Verifies proofs against verifier indices it has been given.
+pub struct KimchiVerifier {
+    srs_log2_size: usize,
+    srs: Option<Arc<SRS<Vesta>>>,
+    verifier_indices: HashMap<[u8; 32], SharedVerifierIndex>,
+    /// Circuit id of each loaded verifier index added for one.
+    circuit_ids: HashMap<[u8; 32], String>,
+    registry: Option<Arc<VerifierIndexRegistry>>,
+    metrics: Arc<dyn MetricsSink>,
+}
+
+impl KimchiVerifier {
+    /// Create a verifier for proofs made with an SRS of `2^srs_log2_size` points.
+    pub fn new(srs_log2_size: usize) -> Self {
+        Self {
+            srs_log2_size,
+            srs: None,
+            verifier_indices: HashMap::new(),
+            circuit_ids: HashMap::new(),
+            registry: None,
+            metrics: Arc::new(NoopMetrics),
+        }
+    }
+
+    /// Log2 of the SRS size.
+    pub fn srs_log2_size(&self) -> usize {
+        self.srs_log2_size
+    }
+
+    /// Report phase counts, durations and errors to `sink`.
+    pub fn set_metrics(&mut self, sink: Arc<dyn MetricsSink>) {
+        self.metrics = sink;
+    }
+
+    /// The sink phases are reported to.
+    pub fn metrics(&self) -> &Arc<dyn MetricsSink> {
+        &self.metrics
+    }
+
+    /// Resolve verifier indices through `registry` (see
+    /// [`resolve_verifier_index`](Self::resolve_verifier_index)).
+    pub fn set_registry(&mut self, registry: Arc<VerifierIndexRegistry>) {
+        self.registry = Some(registry);
+    }
+
+    /// The registry verifier indices are resolved through.
+    pub fn registry(&self) -> Option<&Arc<VerifierIndexRegistry>> {
+        self.registry.as_ref()
+    }
+
+    /// Initialize the SRS, if not done already.
+    pub fn init_srs(&mut self) -> Result<()> {
+        if self.srs.is_some() {
+            return Ok(());
+        }
+
+        let _span = profiling::span(Phase::SrsInit);
+        let timer = Timer::start();
+        self.srs = Some(Arc::new(prover::create_srs(self.srs_log2_size)));
+        metrics::record(&self.metrics, Phase::SrsInit, &timer, &Ok(()));
+        Ok(())
+    }
+
+    /// Approximate memory held by the SRS once initialized.
+    pub fn memory_bytes(&self) -> usize {
+        cost::srs_memory_bytes(self.srs_log2_size)
+    }
+
+    /// Load a MessagePack serialized verifier index, as returned by
+    /// `export_verifier_index`.
+    ///
+    /// Returns its digest, which is what `ProofEnvelope::verifier_index_digest`
+    /// names. Loading the same index twice keeps a single copy.
+    pub fn add_verifier_index(&mut self, bytes: &[u8]) -> Result<[u8; 32]> {
+        let digest: [u8; 32] = Sha256::digest(bytes).into();
+        if self.verifier_indices.contains_key(&digest) {
+            return Ok(digest);
+        }
+
+        let mut verifier_index: VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>> =
+            rmp_serde::from_slice(bytes).map_err(|e| ProverError::decode("verifier index", e))?;
+        let srs_log2_size = prover::verifier_index_srs_log2_size(&verifier_index);
+        if srs_log2_size != self.srs_log2_size {
+            return Err(ProverError::InvalidInput(format!(
+                "verifier index needs an SRS of 2^{} points, the verifier has 2^{}",
+                srs_log2_size, self.srs_log2_size
+            )));
+        }
+        self.init_srs()?;
+        let srs = self
+            .srs
+            .clone()
+            .ok_or_else(|| ProverError::InternalError("SRS missing after initialization".into()))?;
+        restore_verifier_index(&mut verifier_index, srs);
+
+        self.verifier_indices
+            .insert(digest, Arc::new(verifier_index));
+        Ok(digest)
+    }
+
+    /// Load a serialized verifier index like
+    /// [`add_verifier_index`](Self::add_verifier_index), as the index of the
+    /// registered circuit `circuit_id`.
+    ///
+    /// Envelopes verified against it must name that circuit, and checks
+    /// that depend on the circuit's public inputs (see
+    /// [`verify_with_max_age`](Self::verify_with_max_age) and
+    /// [`verify_with_attestation`](Self::verify_with_attestation)) use it.
+    /// Fails with `InvalidInput` if the circuit is unknown or takes another
+    /// number of public inputs than the index, and with
+    /// `VerifierIndexMismatch` if the index was already added for another
+    /// circuit.
+    pub fn add_circuit_verifier_index(
+        &mut self,
+        circuit_id: &str,
+        bytes: &[u8],
+    ) -> Result<[u8; 32]> {
+        let circuit = registry::circuit(circuit_id).ok_or_else(|| {
+            ProverError::InvalidInput(format!("unknown circuit {:?}", circuit_id))
+        })?;
+        let digest: [u8; 32] = Sha256::digest(bytes).into();
+        if self
+            .circuit_ids
+            .get(&digest)
+            .is_some_and(|bound| bound != circuit_id)
+        {
+            return Err(ProverError::VerifierIndexMismatch);
+        }
+        let fresh = !self.verifier_indices.contains_key(&digest);
+        self.add_verifier_index(bytes)?;
+        let public = self.verifier_indices[&digest].public;
+        if public != circuit.num_public_inputs() {
+            if fresh {
+                self.verifier_indices.remove(&digest);
+            }
+            return Err(ProverError::InvalidInput(format!(
+                "circuit {:?} takes {} public inputs, the verifier index {}",
+                circuit_id,
+                circuit.num_public_inputs(),
+                public
+            )));
+        }
+        self.circuit_ids.insert(digest, circuit_id.to_string());
+        Ok(digest)
+    }
+
+    /// The loaded verifier index with `digest`.
+    pub fn verifier_index(&self, digest: &[u8; 32]) -> Option<SharedVerifierIndex> {
+        self.verifier_indices.get(digest).cloned()
+    }
+
+    /// Drop the verifier index with `digest`. Returns whether it was loaded.
+    pub fn remove_verifier_index(&mut self, digest: &[u8; 32]) -> bool {
+        self.circuit_ids.remove(digest);
+        self.verifier_indices.remove(digest).is_some()
+    }
+
+    /// The verifier index `envelope` names, loading it from the registry
+    /// if it is not loaded yet.
+    ///
+    /// Fails with `VerifierIndexMismatch` if it is neither loaded nor in the
+    /// registry, or the registry holds it for another circuit than the
+    /// envelope's.
+    pub fn resolve_verifier_index(
+        &mut self,
+        envelope: &ProofEnvelope,
+    ) -> Result<SharedVerifierIndex> {
+        let digest = envelope.verifier_index_digest;
+        if let Some(registry) = self.registry.clone() {
+            if self.verifier_indices.contains_key(&digest) {
+                registry.check_circuit(envelope)?;
+            } else {
+                let bytes = registry.resolve(envelope)?;
+                self.add_verifier_index(&bytes)?;
+            }
+        }
+        self.verifier_index(&digest)
+            .ok_or(ProverError::VerifierIndexMismatch)
+    }
+
+    /// Number of loaded verifier indices.
+    pub fn num_verifier_indices(&self) -> usize {
+        self.verifier_indices.len()
+    }
+
+    /// Verify a proof.
+    pub fn verify(
+        &self,
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+        proof: &ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
+        public_inputs: &[Fp],
+    ) -> Result<bool> {
+        prover::verify_proof(&self.metrics, false, verifier_index, proof, public_inputs)
+    }
+
+    /// Verify a proof envelope against the loaded verifier index it names.
+    ///
+    /// Fails with `VerifierIndexMismatch` if that index has not been loaded;
+    /// call [`resolve_verifier_index`](Self::resolve_verifier_index) first to
+    /// load it from the registry.
+    pub fn verify_envelope(&self, envelope: &ProofEnvelope) -> Result<bool> {
+        let verifier_index = self
+            .verifier_indices
+            .get(&envelope.verifier_index_digest)
+            .ok_or(ProverError::VerifierIndexMismatch)?;
+        let proof = envelope.proof()?;
+        self.verify(verifier_index, &proof, &envelope.public_inputs_fp())
+    }
+
+    /// Verify a proof envelope whose circuit ends its public inputs with the
+    /// standard timestamp (see `PublicInputSchema::with_timestamp`), refusing
+    /// proofs made more than `max_age` seconds before `now`.
+    ///
+    /// The envelope's verifier index must be bound to a registered circuit
+    /// with a timestamp, such as a `TimestampedCircuit` (see
+    /// `envelope_circuit`). Times are Unix seconds. Timestamps up to [`MAX_CLOCK_SKEW_SECS`] ahead
+    /// of `now` are accepted. The freshness check runs first, so stale proofs
+    /// fail with `StaleProof` without being verified.
+    pub fn verify_with_max_age(
+        &self,
+        envelope: &ProofEnvelope,
+        now: u64,
+        max_age: u64,
+    ) -> Result<bool> {
+        let schema = self.envelope_circuit(envelope)?.public_input_schema();
+        let timestamp = envelope.timestamp(&schema)?;
+        if timestamp.saturating_add(max_age) < now
+            || timestamp > now.saturating_add(MAX_CLOCK_SKEW_SECS)
+        {
+            return Err(ProverError::StaleProof {
+                timestamp,
+                now,
+                max_age,
+            });
+        }
+        self.verify_envelope(envelope)
+    }
+
+    /// Verify a proof envelope of an `AttestedCircuit`, refusing proofs not
+    /// bound to `attestation`, which the caller has checked with the
+    /// platform.
+    ///
+    /// The envelope's verifier index must be bound to a registered attested
+    /// circuit, whose first public input is the standard attestation field
+    /// (see `envelope_circuit`). The attestation check runs first, so proofs of
+    /// other circuits or bound to another attestation fail with
+    /// `AttestationMismatch` without being verified.
+    pub fn verify_with_attestation(
+        &self,
+        envelope: &ProofEnvelope,
+        attestation: &DeviceAttestation,
+    ) -> Result<bool> {
+        if !self
+            .envelope_circuit(envelope)?
+            .public_input_schema()
+            .has_attestation()
+        {
+            return Err(ProverError::AttestationMismatch);
+        }
+        let bound = envelope.public_inputs.first().map(|f| f.0);
+        if bound != Some(attestation.digest()) {
+            return Err(ProverError::AttestationMismatch);
+        }
+        self.verify_envelope(envelope)
+    }
+
+    /// The registered circuit the envelope's verifier index is bound to, by
+    /// [`add_circuit_verifier_index`](Self::add_circuit_verifier_index) or
+    /// the registry, for checks that depend on its public input schema.
+    ///
+    /// Fails with `VerifierIndexMismatch` if the index is not bound to a
+    /// circuit, or the envelope's `circuit_id` or `circuit_hash` names
+    /// another one, and with `InvalidInput` if the circuit is not a
+    /// registered one.
+    fn envelope_circuit(&self, envelope: &ProofEnvelope) -> Result<Box<dyn Circuit + Send + Sync>> {
+        let digest = &envelope.verifier_index_digest;
+        let circuit_id = match self.circuit_ids.get(digest) {
+            Some(id) => id.clone(),
+            None => self
+                .registry
+                .as_ref()
+                .and_then(|registry| registry.circuit(digest))
+                .map(|(id, _)| id)
+                .ok_or(ProverError::VerifierIndexMismatch)?,
+        };
+        if circuit_id != envelope.circuit_id {
+            return Err(ProverError::VerifierIndexMismatch);
+        }
+        let circuit = registry::circuit(&circuit_id).ok_or_else(|| {
+            ProverError::InvalidInput(format!("unknown circuit {:?}", circuit_id))
+        })?;
+        if envelope.circuit_hash != circuit.circuit_hash() {
+            return Err(ProverError::VerifierIndexMismatch);
+        }
+        if let Some(registry) = &self.registry {
+            registry.check_circuit(envelope)?;
+        }
+        Ok(circuit)
+    }
+
+    /// Verify a signed proof envelope, refusing envelopes not signed by
+    /// `expected_signer` (a Mina address) if given.
+    ///
+    /// The signature check runs first, so envelopes with a bad or
+    /// unexpected signature fail with `SignatureInvalid` without being
+    /// verified.
+    pub fn verify_signed(
+        &self,
+        signed: &SignedEnvelope,
+        expected_signer: Option<&str>,
+    ) -> Result<bool> {
+        signed.check_signature(expected_signer)?;
+        self.verify_envelope(&signed.envelope)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::circuits::{ThresholdCircuit, TimestampedCircuit};
+    use crate::prover::{KimchiProver, ProverConfig};
+
+    #[test]
+    fn test_verify_envelope() {
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            ..Default::default()
+        });
+        let circuit = ThresholdCircuit::new(10);
+        let (prover_index, verifier_index) = prover
+            .setup(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+        let (witness, public_inputs) = circuit.generate_witness(0).unwrap();
+        let proof = prover.prove(&prover_index, witness).unwrap();
+        let envelope =
+            ProofEnvelope::new("threshold", 1, 10, &proof, &public_inputs, &verifier_index)
+                .unwrap();
+
+        let mut verifier = KimchiVerifier::new(10);
+        assert!(matches!(
+            verifier.verify_envelope(&envelope),
+            Err(ProverError::VerifierIndexMismatch)
+        ));
+
+        let bytes = rmp_serde::to_vec(&verifier_index).unwrap();
+        let digest = verifier.add_verifier_index(&bytes).unwrap();
+        assert_eq!(digest, envelope.verifier_index_digest);
+        assert_eq!(verifier.add_verifier_index(&bytes).unwrap(), digest);
+        assert_eq!(verifier.num_verifier_indices(), 1);
+        assert!(verifier.verify_envelope(&envelope).unwrap());
+
+        let signer = crate::EnvelopeSigner::generate(&mut rand::rngs::OsRng).unwrap();
+        let signed = signer.sign(envelope.clone()).unwrap();
+        assert!(verifier
+            .verify_signed(&signed, Some(&signer.address()))
+            .unwrap());
+        assert!(matches!(
+            verifier.verify_signed(&signed, Some("B62other")),
+            Err(ProverError::SignatureInvalid { .. })
+        ));
+
+        let mut tampered = envelope.clone();
+        tampered.public_inputs[0] = crate::FieldElement(Fp::from(11u64));
+        assert!(!matches!(verifier.verify_envelope(&tampered), Ok(true)));
+
+        assert!(verifier.remove_verifier_index(&digest));
+        assert!(verifier.verify_envelope(&envelope).is_err());
+        assert!(verifier.add_verifier_index(b"not an index").is_err());
+        assert!(matches!(
+            KimchiVerifier::new(11).add_verifier_index(&bytes),
+            Err(ProverError::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_verify_with_max_age() {
+        let circuit = TimestampedCircuit::new(ThresholdCircuit::new(10));
+        let timestamp = 1_700_000_000u64;
+        let (witness, public_inputs) = circuit.inner().generate_witness(0).unwrap();
+        let (witness, public_inputs) = circuit
+            .bind_witness(witness, &public_inputs, timestamp)
+            .unwrap();
+
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover
+            .setup(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+        let proof = prover.prove(&prover_index, witness).unwrap();
+        let envelope =
+            ProofEnvelope::new(circuit.id(), 1, 10, &proof, &public_inputs, &verifier_index)
+                .unwrap()
+                .with_circuit_hash(circuit.circuit_hash());
+        assert_eq!(
+            envelope.timestamp(&circuit.public_input_schema()).unwrap(),
+            timestamp
+        );
+
+        // The index must be bound to the circuit the envelope names
+        let bytes = rmp_serde::to_vec(&verifier_index).unwrap();
+        let mut verifier = KimchiVerifier::new(10);
+        verifier.add_verifier_index(&bytes).unwrap();
+        assert!(matches!(
+            verifier.verify_with_max_age(&envelope, timestamp, 60),
+            Err(ProverError::VerifierIndexMismatch)
+        ));
+        assert!(matches!(
+            verifier.add_circuit_verifier_index("threshold", &bytes),
+            Err(ProverError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            verifier.add_circuit_verifier_index("custom", &bytes),
+            Err(ProverError::InvalidInput(_))
+        ));
+        let digest = verifier
+            .add_circuit_verifier_index(circuit.id(), &bytes)
+            .unwrap();
+        assert_eq!(digest, envelope.verifier_index_digest);
+        assert!(matches!(
+            verifier.add_circuit_verifier_index("timestamped/threshold_lt", &bytes),
+            Err(ProverError::VerifierIndexMismatch)
+        ));
+        assert!(verifier
+            .verify_with_max_age(&envelope, timestamp + 60, 60)
+            .unwrap());
+        assert!(verifier
+            .verify_with_max_age(&envelope, timestamp - MAX_CLOCK_SKEW_SECS, 60)
+            .unwrap());
+        let err = verifier
+            .verify_with_max_age(&envelope, timestamp + 61, 60)
+            .unwrap_err();
+        assert!(matches!(err, ProverError::StaleProof { .. }));
+        assert_eq!(err.code(), 402);
+        assert!(matches!(
+            verifier.verify_with_max_age(&envelope, timestamp - MAX_CLOCK_SKEW_SECS - 1, 60),
+            Err(ProverError::StaleProof { .. })
+        ));
+
+        // A tampered timestamp passes the freshness check but not verification
+        let mut tampered = envelope.clone();
+        tampered.public_inputs[2] = crate::FieldElement(Fp::from(timestamp + 100));
+        assert!(!matches!(
+            verifier.verify_with_max_age(&tampered, timestamp + 100, 60),
+            Ok(true)
+        ));
+        tampered.public_inputs[2] = crate::FieldElement(-Fp::from(1u64));
+        assert!(matches!(
+            verifier.verify_with_max_age(&tampered, timestamp, 60),
+            Err(ProverError::InvalidInput(_))
+        ));
+
+        // Schemas without a timestamp are refused, whatever the last public
+        // input
+        assert!(matches!(
+            envelope.timestamp(&ThresholdCircuit::new(10).public_input_schema()),
+            Err(ProverError::InvalidInput(_))
+        ));
+        // Nor may the envelope name another circuit or hash than its index
+        // was added for
+        for (circuit_id, circuit_hash) in [
+            ("timestamped/threshold_lt", circuit.circuit_hash()),
+            ("threshold", [0; 32]),
+            ("custom", [0; 32]),
+            (circuit.id(), [0; 32]),
+            (circuit.id(), ThresholdCircuit::new(10).circuit_hash()),
+        ] {
+            let mut relabeled = envelope.clone();
+            relabeled.circuit_id = circuit_id.into();
+            relabeled.circuit_hash = circuit_hash;
+            assert!(matches!(
+                verifier.verify_with_max_age(&relabeled, timestamp, 60),
+                Err(ProverError::VerifierIndexMismatch)
+            ));
+        }
+
+        assert!(verifier.remove_verifier_index(&digest));
+        verifier.add_verifier_index(&bytes).unwrap();
+        assert!(matches!(
+            verifier.verify_with_max_age(&envelope, timestamp, 60),
+            Err(ProverError::VerifierIndexMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_resolve_through_registry() {
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            ..Default::default()
+        });
+        let circuit = ThresholdCircuit::new(10);
+        let (prover_index, verifier_index) = prover
+            .setup(circuit.gates(), circuit.num_public_inputs())
+            .unwrap();
+        let (witness, public_inputs) = circuit.generate_witness(0).unwrap();
+        let proof = prover.prove(&prover_index, witness).unwrap();
+        let envelope =
+            ProofEnvelope::new("threshold", 1, 10, &proof, &public_inputs, &verifier_index)
+                .unwrap()
+                .with_circuit_hash(circuit.circuit_hash());
+
+        let registry = Arc::new(VerifierIndexRegistry::new());
+        let mut verifier = KimchiVerifier::new(10);
+        verifier.set_registry(registry.clone());
+        assert!(matches!(
+            verifier.resolve_verifier_index(&envelope),
+            Err(ProverError::VerifierIndexMismatch)
+        ));
+
+        registry
+            .register("threshold", 1, rmp_serde::to_vec(&verifier_index).unwrap())
+            .unwrap();
+        verifier.resolve_verifier_index(&envelope).unwrap();
+        assert_eq!(verifier.num_verifier_indices(), 1);
+        assert!(verifier.verify_envelope(&envelope).unwrap());
+        // The registry binds the index to the threshold circuit, which has
+        // no timestamp
+        assert!(matches!(
+            verifier.verify_with_max_age(&envelope, 1_700_000_000, 60),
+            Err(ProverError::InvalidInput(_))
+        ));
+
+        // The loaded index is still refused for another circuit's envelope
+        let mut relabeled = envelope.clone();
+        relabeled.circuit_id = "merkle".into();
+        assert!(matches!(
+            verifier.resolve_verifier_index(&relabeled),
+            Err(ProverError::VerifierIndexMismatch)
+        ));
+    }
+}
@@ -0,0 +1,258 @@
+//! Proof-dependent fields of a Mina zkApp account update.
+//!
+//! A zkApp method call settles on Mina as an account update whose
+//! `authorizationKind` is proved, whose `authorization.proof` carries the
+//! proof and whose `update.appState` receives the method's outputs. This
+//! module fills in exactly those fields, in the JSON shape o1js produces
+//! from `AccountUpdate.toJSON()` (camelCase keys, field elements as decimal
+//! strings, `null` for state slots left unchanged), so a wallet or backend
+//! can merge them into the account update it signs and submits:
+//!
+//! ```json
+//! {
+//!   "body": {
+//!     "update": { "appState": ["18", "1700000000", null, null, null, null, null, null] },
+//!     "authorizationKind": {
+//!       "isSigned": false,
+//!       "isProved": true,
+//!       "verificationKeyHash": "1234..."
+//!     }
+//!   },
+//!   "authorization": { "proof": "..." }
+//! }
+//! ```
+//!
+//! Public inputs fill the eight state slots in circuit order unless placed
+//! explicitly with [`ZkappSubmission::set_app_state`]. The verification key
+//! hash is [`MinaVerificationKeyJson::hash`], matching the key the zkApp
+//! account must have installed.
+//!
+//! Proofs are plain Kimchi proofs (see [`crate::mina`]). The Mina protocol
+//! only accepts Pickles-wrapped proofs in `authorization.proof`, so the
+//! proof must be wrapped by a Pickles-aware service before the transaction
+//! is broadcast; everything else in the output is final.
+
+use kimchi::proof::ProverProof;
+use kimchi::verifier_index::VerifierIndex;
+use mina_curves::pasta::{Fp, Vesta};
+use poly_commitment::ipa::SRS;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ProverError, Result};
+use crate::mina::{MinaProofJson, MinaVerificationKeyJson};
+use crate::prover::{VestaOpeningProof, FULL_ROUNDS};
+use crate::types::{FieldElement, ProofEnvelope};
+
+/// Number of on-chain state fields of a zkApp account.
+pub const ZKAPP_STATE_LENGTH: usize = 8;
+
+/// The proof-dependent part of a zkApp account update.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkappSubmission {
+    pub body: ZkappBody,
+    pub authorization: ZkappAuthorization,
+}
+
+/// Account update `body` fields set by a proved method call.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkappBody {
+    pub update: ZkappUpdate,
+    pub authorization_kind: ZkappAuthorizationKind,
+}
+
+/// Account update `body.update` fields.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkappUpdate {
+    /// New state per slot as decimal strings; `None` leaves the slot unchanged.
+    pub app_state: Vec<Option<String>>,
+}
+
+/// Account update `body.authorizationKind`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkappAuthorizationKind {
+    pub is_signed: bool,
+    pub is_proved: bool,
+    /// Verification key hash as a decimal string.
+    pub verification_key_hash: String,
+}
+
+/// Account update `authorization`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkappAuthorization {
+    /// Base64 of the MessagePack serialized proof, as in [`MinaProofJson`].
+    pub proof: String,
+}
+
+impl ZkappSubmission {
+    /// Build the fields for a proof and its public inputs.
+    pub fn from_proof(
+        proof: &ProverProof<Vesta, VestaOpeningProof, FULL_ROUNDS>,
+        public_inputs: &[Fp],
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    ) -> Result<Self> {
+        let json = MinaProofJson::from_proof(proof, public_inputs)?;
+        Self::from_parts(
+            json.proof,
+            public_inputs,
+            MinaVerificationKeyJson::hash(verifier_index),
+        )
+    }
+
+    /// Build the fields for an envelope, checking that it was produced for
+    /// `verifier_index`.
+    pub fn from_envelope(
+        envelope: &ProofEnvelope,
+        verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>>,
+    ) -> Result<Self> {
+        envelope.check_verifier_index(verifier_index)?;
+        Self::from_parts(
+            MinaProofJson::from_envelope(envelope).proof,
+            &envelope.public_inputs_fp(),
+            MinaVerificationKeyJson::hash(verifier_index),
+        )
+    }
+
+    fn from_parts(proof: String, public_inputs: &[Fp], verification_key_hash: Fp) -> Result<Self> {
+        if public_inputs.len() > ZKAPP_STATE_LENGTH {
+            return Err(ProverError::InvalidInput(format!(
+                "{} public inputs do not fit in {} zkApp state fields",
+                public_inputs.len(),
+                ZKAPP_STATE_LENGTH
+            )));
+        }
+        let mut app_state = vec![None; ZKAPP_STATE_LENGTH];
+        for (slot, input) in app_state.iter_mut().zip(public_inputs) {
+            *slot = Some(FieldElement(*input).to_decimal());
+        }
+        Ok(Self {
+            body: ZkappBody {
+                update: ZkappUpdate { app_state },
+                authorization_kind: ZkappAuthorizationKind {
+                    is_signed: false,
+                    is_proved: true,
+                    verification_key_hash: FieldElement(verification_key_hash).to_decimal(),
+                },
+            },
+            authorization: ZkappAuthorization { proof },
+        })
+    }
+
+    /// Set or clear a state slot, e.g. to match a contract whose fields are
+    /// not laid out in public-input order.
+    pub fn set_app_state(&mut self, slot: usize, value: Option<Fp>) -> Result<()> {
+        let entry = self.body.update.app_state.get_mut(slot).ok_or_else(|| {
+            ProverError::InvalidInput(format!(
+                "zkApp state slot {} out of range 0..{}",
+                slot, ZKAPP_STATE_LENGTH
+            ))
+        })?;
+        *entry = value.map(|fp| FieldElement(fp).to_decimal());
+        Ok(())
+    }
+
+    /// New state per slot.
+    pub fn app_state(&self) -> Result<Vec<Option<Fp>>> {
+        self.body
+            .update
+            .app_state
+            .iter()
+            .map(|slot| slot.as_deref().map(parse_decimal).transpose())
+            .collect()
+    }
+
+    /// Verification key hash the zkApp account must have installed.
+    pub fn verification_key_hash(&self) -> Result<Fp> {
+        parse_decimal(&self.body.authorization_kind.verification_key_hash)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
+fn parse_decimal(s: &str) -> Result<Fp> {
+    FieldElement::from_decimal(s)
+        .map(|f| f.0)
+        .map_err(ProverError::SerializationError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_app_state_layout() {
+        let inputs = [Fp::from(18u64), Fp::from(1_700_000_000u64)];
+        let mut submission =
+            ZkappSubmission::from_parts("AQID".into(), &inputs, Fp::from(5u64)).unwrap();
+
+        let json = submission.to_json().unwrap();
+        assert!(json.contains("\"appState\":[\"18\",\"1700000000\",null,null,null,null,null,null]"));
+        assert!(json.contains("\"isProved\":true"));
+        assert!(json.contains("\"verificationKeyHash\":\"5\""));
+        assert!(json.contains("\"authorization\":{\"proof\":\"AQID\"}"));
+
+        submission.set_app_state(0, None).unwrap();
+        submission.set_app_state(7, Some(inputs[0])).unwrap();
+        assert!(submission.set_app_state(ZKAPP_STATE_LENGTH, None).is_err());
+
+        let decoded = ZkappSubmission::from_json(&submission.to_json().unwrap()).unwrap();
+        let state = decoded.app_state().unwrap();
+        assert_eq!(state[0], None);
+        assert_eq!(state[1], Some(inputs[1]));
+        assert_eq!(state[7], Some(inputs[0]));
+        assert_eq!(decoded.verification_key_hash().unwrap(), Fp::from(5u64));
+
+        let too_many = [Fp::from(0u64); ZKAPP_STATE_LENGTH + 1];
+        assert!(ZkappSubmission::from_parts(String::new(), &too_many, Fp::from(0u64)).is_err());
+    }
+
+    #[test]
+    fn test_from_envelope() {
+        use crate::builder::{CircuitBuilder, WitnessBuilder};
+        use crate::prover::{KimchiProver, ProverConfig};
+        use kimchi::circuits::polynomials::generic::GenericGateSpec;
+
+        let mut cs = CircuitBuilder::new(0);
+        let mut w = WitnessBuilder::new(0);
+        for v in [2u64, 6] {
+            let [cell, _, _] = cs.generic(GenericGateSpec::Const(Fp::from(v)));
+            w.set(cell, Fp::from(v));
+        }
+
+        let mut prover = KimchiProver::with_config(ProverConfig {
+            srs_log2_size: 10,
+            debug: false,
+            ..Default::default()
+        });
+        let (prover_index, verifier_index) = prover.setup(cs.build(), 0).unwrap();
+        let proof = prover.prove(&prover_index, w.columns()).unwrap();
+        let envelope = ProofEnvelope::new("constant", 1, 10, &proof, &[], &verifier_index).unwrap();
+
+        let submission = ZkappSubmission::from_envelope(&envelope, &verifier_index).unwrap();
+        assert_eq!(
+            submission,
+            ZkappSubmission::from_proof(&proof, &[], &verifier_index).unwrap()
+        );
+        assert_eq!(
+            submission.verification_key_hash().unwrap(),
+            MinaVerificationKeyJson::hash(&verifier_index)
+        );
+
+        let mut other = envelope;
+        other.verifier_index_digest[0] ^= 1;
+        assert!(matches!(
+            ZkappSubmission::from_envelope(&other, &verifier_index),
+            Err(ProverError::VerifierIndexMismatch)
+        ));
+    }
+}
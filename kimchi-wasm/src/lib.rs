@@ -25,29 +25,31 @@
 use std::sync::OnceLock;
 
 use ark_serialize::CanonicalDeserialize;
-use kimchi::circuits::constraints::FeatureFlags;
 use kimchi::groupmap::GroupMap;
-use kimchi::linearization::expr_linearization;
 use kimchi::proof::ProverProof;
 use kimchi::verifier::verify;
 use kimchi::verifier_index::VerifierIndex;
-use mina_curves::pasta::{Fp, Vesta, VestaParameters};
-use mina_poseidon::constants::PlonkSpongeConstantsKimchi;
-use mina_poseidon::pasta::FULL_ROUNDS;
-use mina_poseidon::sponge::{DefaultFqSponge, DefaultFrSponge};
-use poly_commitment::ipa::{OpeningProof, SRS};
+use kimchi_prover::prover::{restore_verifier_index, VestaBaseSponge, VestaScalarSponge};
+use kimchi_prover::{ProofEnvelope, VestaOpeningProof, FULL_ROUNDS};
+use mina_curves::pasta::{Fp, Vesta};
+use poly_commitment::ipa::SRS;
 use poly_commitment::SRS as SRSTrait;
 use std::sync::Arc;
 use wasm_bindgen::prelude::*;
 
-// Type aliases (same as kimchi-prover)
-type VestaBaseSponge = DefaultFqSponge<VestaParameters, PlonkSpongeConstantsKimchi, FULL_ROUNDS>;
-type VestaScalarSponge = DefaultFrSponge<Fp, PlonkSpongeConstantsKimchi, FULL_ROUNDS>;
-type VestaOpeningProof = OpeningProof<Vesta, FULL_ROUNDS>;
-
 // Cached SRS (generated once on init)
 static SRS_CACHE: OnceLock<Arc<SRS<Vesta>>> = OnceLock::new();
 
+/// Largest SRS the verifier builds, as log2 of its number of points; the
+/// SRS of 2^20 points already takes about 70 MB.
+pub const MAX_SRS_LOG2_SIZE: u32 = 20;
+
+/// Number of points of an SRS of `2^srs_log2_size`, or `None` above
+/// `MAX_SRS_LOG2_SIZE`.
+fn srs_depth(srs_log2_size: u32) -> Option<usize> {
+    (srs_log2_size <= MAX_SRS_LOG2_SIZE).then(|| 1 << srs_log2_size)
+}
+
 /// Initialize panic hook for better error messages in browser console.
 #[wasm_bindgen(start)]
 pub fn init() {
@@ -63,15 +65,21 @@ pub fn init() {
 ///
 /// # Arguments
 /// * `srs_log2_size` - Log2 of the SRS size. Default is 14 (16,384 rows).
-///   Must match the SRS size used by the prover (from get_srs_log2_size()).
+///   Must match the SRS size used by the prover (from get_srs_log2_size()),
+///   at most `MAX_SRS_LOG2_SIZE`.
 ///
 /// # Returns
 /// * `Ok(())` on success
-/// * `Err(JsError)` if initialization fails
+/// * `Err(JsError)` if the size is above `MAX_SRS_LOG2_SIZE`
 #[wasm_bindgen]
 pub fn init_verifier(srs_log2_size: Option<u32>) -> Result<(), JsError> {
-    let size = srs_log2_size.unwrap_or(14) as usize;
-    let depth = 1 << size;
+    let size = srs_log2_size.unwrap_or(14);
+    let depth = srs_depth(size).ok_or_else(|| {
+        JsError::new(&format!(
+            "SRS size 2^{} is above the maximum of 2^{}",
+            size, MAX_SRS_LOG2_SIZE
+        ))
+    })?;
 
     // Generate SRS if not already cached
     // Note: Using create() instead of create_parallel() since WASM is single-threaded
@@ -136,14 +144,8 @@ pub fn verify_kimchi_proof(
         rmp_serde::from_slice(&vi_bytes)
             .map_err(|e| JsError::new(&format!("Failed to deserialize verifier index: {}", e)))?;
 
-    // Inject the cached SRS
-    verifier_index.srs = srs.clone();
-
-    // Reconstruct powers_of_alpha from feature flags (skipped during serialization)
-    let feature_flags = infer_feature_flags(&verifier_index);
-    let (linearization, powers_of_alpha) = expr_linearization(Some(&feature_flags), true);
-    verifier_index.powers_of_alpha = powers_of_alpha;
-    verifier_index.linearization = linearization;
+    // Inject the cached SRS and reconstruct the skipped linearization
+    restore_verifier_index(&mut verifier_index, srs.clone());
 
     // Decode public inputs
     let mut public_inputs: Vec<Fp> = Vec::with_capacity(public_inputs_hex.len());
@@ -236,14 +238,8 @@ pub fn verify_kimchi_proof_detailed(
             }
         };
 
-    // Inject the cached SRS
-    verifier_index.srs = srs.clone();
-
-    // Reconstruct powers_of_alpha from feature flags (skipped during serialization)
-    let feature_flags = infer_feature_flags(&verifier_index);
-    let (linearization, powers_of_alpha) = expr_linearization(Some(&feature_flags), true);
-    verifier_index.powers_of_alpha = powers_of_alpha;
-    verifier_index.linearization = linearization;
+    // Inject the cached SRS and reconstruct the skipped linearization
+    restore_verifier_index(&mut verifier_index, srs.clone());
 
     // Decode public inputs
     let mut public_inputs: Vec<Fp> = Vec::with_capacity(public_inputs_hex.len());
@@ -297,6 +293,68 @@ pub fn verify_kimchi_proof_detailed(
     }
 }
 
+/// Verify a `ProofEnvelope` produced by kimchi-mobile.
+///
+/// The envelope carries the proof, its public inputs and the SRS size it was
+/// proven with; the verifier index must hash to the envelope's
+/// `verifier_index_digest`. `init_verifier()` must have been called with the
+/// envelope's `srs_log2_size`: the envelope is untrusted, so its size is
+/// never used to build an SRS.
+///
+/// # Arguments
+/// * `envelope_hex` - Hex-encoded MessagePack `ProofEnvelope` (from ProofResult.envelope)
+/// * `verifier_index_hex` - Hex-encoded MessagePack serialized verifier index (without SRS)
+///
+/// # Returns
+/// * `true` if proof is valid
+/// * `false` if proof is invalid
+///
+/// # Errors
+/// * If verifier is not initialized
+/// * If the verifier was initialized with a different SRS size
+/// * If the envelope or verifier index cannot be decoded
+/// * If the verifier index does not match the envelope
+#[wasm_bindgen]
+pub fn verify_proof_envelope(
+    envelope_hex: &str,
+    verifier_index_hex: &str,
+) -> Result<bool, JsError> {
+    let envelope = ProofEnvelope::from_hex(envelope_hex).map_err(|e| JsError::new(&e.report()))?;
+    let srs = SRS_CACHE
+        .get()
+        .ok_or_else(|| JsError::new("Verifier not initialized. Call init_verifier() first."))?;
+    if srs_depth(envelope.srs_log2_size) != Some(srs.size()) {
+        return Err(JsError::new(&format!(
+            "Proof envelope expects an SRS of size 2^{}",
+            envelope.srs_log2_size
+        )));
+    }
+    let proof = envelope.proof().map_err(|e| JsError::new(&e.report()))?;
+
+    let vi_bytes = hex::decode(verifier_index_hex)
+        .map_err(|e| JsError::new(&format!("Invalid verifier index hex: {}", e)))?;
+    let mut verifier_index: VerifierIndex<FULL_ROUNDS, Vesta, SRS<Vesta>> =
+        rmp_serde::from_slice(&vi_bytes)
+            .map_err(|e| JsError::new(&format!("Failed to deserialize verifier index: {}", e)))?;
+    envelope
+        .check_verifier_index(&verifier_index)
+        .map_err(|e| JsError::new(&e.report()))?;
+
+    // Inject the cached SRS and reconstruct the skipped linearization
+    restore_verifier_index(&mut verifier_index, srs.clone());
+
+    let group_map = <Vesta as poly_commitment::commitment::CommitmentCurve>::Map::setup();
+
+    let result = verify::<FULL_ROUNDS, Vesta, VestaBaseSponge, VestaScalarSponge, VestaOpeningProof>(
+        &group_map,
+        &verifier_index,
+        &proof,
+        &envelope.public_inputs_fp(),
+    );
+
+    Ok(result.is_ok())
+}
+
 /// Helper to create a JS result object
 fn make_result_object(valid: bool, error: Option<String>) -> JsValue {
     let obj = js_sys::Object::new();
@@ -307,48 +365,6 @@ fn make_result_object(valid: bool, error: Option<String>) -> JsValue {
     obj.into()
 }
 
-/// Infer feature flags from the verifier index's optional commitment fields.
-/// This is needed because powers_of_alpha is not serialized.
-fn infer_feature_flags<SRS>(
-    verifier_index: &VerifierIndex<FULL_ROUNDS, Vesta, SRS>,
-) -> FeatureFlags {
-    use kimchi::circuits::lookup::lookups::{LookupFeatures, LookupPatterns};
-
-    // Infer which optional gates are used based on which commitments are present
-    let range_check0 = verifier_index.range_check0_comm.is_some();
-    let range_check1 = verifier_index.range_check1_comm.is_some();
-    let foreign_field_add = verifier_index.foreign_field_add_comm.is_some();
-    let foreign_field_mul = verifier_index.foreign_field_mul_comm.is_some();
-    let xor = verifier_index.xor_comm.is_some();
-    let rot = verifier_index.rot_comm.is_some();
-
-    // Infer lookup features from lookup_index
-    let lookup_features = if let Some(ref lookup_index) = verifier_index.lookup_index {
-        LookupFeatures {
-            patterns: LookupPatterns {
-                xor: lookup_index.lookup_selectors.xor.is_some(),
-                lookup: lookup_index.lookup_selectors.lookup.is_some(),
-                range_check: lookup_index.lookup_selectors.range_check.is_some(),
-                foreign_field_mul: lookup_index.lookup_selectors.ffmul.is_some(),
-            },
-            joint_lookup_used: lookup_index.lookup_table.len() > 1,
-            uses_runtime_tables: lookup_index.runtime_tables_selector.is_some(),
-        }
-    } else {
-        LookupFeatures::default()
-    };
-
-    FeatureFlags {
-        range_check0,
-        range_check1,
-        foreign_field_add,
-        foreign_field_mul,
-        xor,
-        rot,
-        lookup_features,
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,4 +376,12 @@ mod tests {
         let srs = SRS::<Vesta>::create(1 << 10); // Small SRS for testing
         assert!(srs.g.len() > 0);
     }
+
+    #[test]
+    fn test_srs_depth() {
+        assert_eq!(srs_depth(10), Some(1 << 10));
+        assert_eq!(srs_depth(MAX_SRS_LOG2_SIZE), Some(1 << MAX_SRS_LOG2_SIZE));
+        assert_eq!(srs_depth(MAX_SRS_LOG2_SIZE + 1), None);
+        assert_eq!(srs_depth(u32::MAX), None);
+    }
 }
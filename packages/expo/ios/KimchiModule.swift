@@ -37,7 +37,7 @@ public class KimchiModule: Module {
       guard let handle = Int64(proofHandle) else {
         throw KimchiModuleError.invalidInput("Invalid proof handle")
       }
-      return try KimchiMobile.verifyProof(proofHandle: handle)
+      return try await KimchiMobile.verifyProof(proofHandle: handle)
     }
 
     // Export verifier index
@@ -8,6 +8,7 @@ package com.kimchi.mobile
 
 import android.content.Context
 import android.util.Log
+import java.io.File
 import uniffi.kimchi_ffi.KimchiException
 
 /**
@@ -25,11 +26,21 @@ object KimchiMobile {
      *
      * @param context Android context (used for native library loading)
      * @param srsLog2Size Log2 of SRS size (default 14 = 16384 rows). Use 10-12 for testing.
+     * @param proofTtlSecs Lifetime of stored proofs in seconds, after which they are freed
+     *   and their handles throw `ProofExpired`. Null keeps proofs until [freeProof].
+     * @param cacheDir Directory to cache the SRS and compiled circuits in across launches,
+     *   e.g. `File(context.filesDir, "kimchi")`. Processes sharing it share the cache
+     *   safely. Null keeps them in memory only.
      * @return true if initialization succeeded
      */
     @JvmStatic
     @JvmOverloads
-    fun initialize(context: Context, srsLog2Size: Int? = null): Boolean {
+    fun initialize(
+        context: Context,
+        srsLog2Size: Int? = null,
+        proofTtlSecs: Long? = null,
+        cacheDir: File? = null
+    ): Boolean {
         if (initialized) {
             Log.d(TAG, "Already initialized")
             return true
@@ -40,7 +51,11 @@ object KimchiMobile {
             System.loadLibrary("kimchi_ffi")
 
             // Initialize the prover
-            uniffi.kimchi_ffi.initProver(srsLog2Size?.toUInt())
+            uniffi.kimchi_ffi.initProver(
+                srsLog2Size?.toUInt(),
+                proofTtlSecs?.toULong(),
+                cacheDir?.absolutePath
+            )
 
             initialized = true
             Log.i(TAG, "Kimchi prover initialized successfully")
@@ -91,8 +106,12 @@ object KimchiMobile {
      * @param threshold The public threshold to compare against
      * @return ProofResult containing the proof and metadata
      *
+     * Proving runs on the library's worker thread, so this suspends without
+     * blocking the calling dispatcher.
+     *
      * Example:
      * ```kotlin
+     * // From a coroutine, e.g. lifecycleScope.launch { ... }
      * // Prove that my secret number (50) is less than 100
      * val result = KimchiMobile.proveThreshold(value = 50, threshold = 100)
      * if (result.isSuccess) {
@@ -109,7 +128,7 @@ object KimchiMobile {
      * ```
      */
     @JvmStatic
-    fun proveThreshold(value: Long, threshold: Long): Result<ProofResult> {
+    suspend fun proveThreshold(value: Long, threshold: Long): Result<ProofResult> {
         if (!initialized) {
             return Result.failure(IllegalStateException("Prover not initialized. Call initialize() first."))
         }
@@ -163,13 +182,14 @@ object KimchiMobile {
     }
 
     /**
-     * Verify a proof by its handle.
+     * Verify a proof by its handle, suspending while it runs on the library's
+     * worker thread.
      *
      * @param proofHandle Handle to the proof to verify
      * @return true if the proof is valid
      */
     @JvmStatic
-    fun verifyProof(proofHandle: Long): Result<Boolean> {
+    suspend fun verifyProof(proofHandle: Long): Result<Boolean> {
         if (!initialized) {
             return Result.failure(IllegalStateException("Prover not initialized. Call initialize() first."))
         }
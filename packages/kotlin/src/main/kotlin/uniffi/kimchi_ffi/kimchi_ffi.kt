@@ -30,6 +30,14 @@ import java.nio.CharBuffer
 import java.nio.charset.CodingErrorAction
 import java.util.concurrent.atomic.AtomicLong
 import java.util.concurrent.ConcurrentHashMap
+import java.util.concurrent.atomic.AtomicBoolean
+import kotlin.coroutines.resume
+import kotlinx.coroutines.CancellableContinuation
+import kotlinx.coroutines.DelicateCoroutinesApi
+import kotlinx.coroutines.GlobalScope
+import kotlinx.coroutines.Job
+import kotlinx.coroutines.launch
+import kotlinx.coroutines.suspendCancellableCoroutine
 
 // This is a helper for safely working with byte buffers returned from the Rust code.
 // A rust-owned buffer is represented by its capacity, its current length, and a
@@ -653,6 +661,156 @@ internal open class UniffiForeignFutureStructVoid(
 internal interface UniffiForeignFutureCompleteVoid : com.sun.jna.Callback {
     fun callback(`callbackData`: Long,`result`: UniffiForeignFutureStructVoid.UniffiByValue,)
 }
+internal interface UniffiCallbackInterfaceMetricsCallbackMethod0 : com.sun.jna.Callback {
+    fun callback(`uniffiHandle`: Long,`name`: RustBuffer.ByValue,`value`: Long,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,)
+}
+internal interface UniffiCallbackInterfaceMetricsCallbackMethod1 : com.sun.jna.Callback {
+    fun callback(`uniffiHandle`: Long,`name`: RustBuffer.ByValue,`value`: Double,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,)
+}
+internal interface UniffiCallbackInterfaceMetricsCallbackMethod2 : com.sun.jna.Callback {
+    fun callback(`uniffiHandle`: Long,`phase`: RustBuffer.ByValue,`code`: Int,`message`: RustBuffer.ByValue,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,)
+}
+internal interface UniffiCallbackInterfaceSrsFetcherMethod0 : com.sun.jna.Callback {
+    fun callback(`uniffiHandle`: Long,`url`: RustBuffer.ByValue,`uniffiOutReturn`: RustBuffer,uniffiCallStatus: UniffiRustCallStatus,)
+}
+internal interface UniffiCallbackInterfaceVerifierIndexSourceMethod0 : com.sun.jna.Callback {
+    fun callback(`uniffiHandle`: Long,`digest`: RustBuffer.ByValue,`uniffiOutReturn`: RustBuffer,uniffiCallStatus: UniffiRustCallStatus,)
+}
+@Structure.FieldOrder("counter", "histogram", "error", "uniffiFree")
+internal open class UniffiVTableCallbackInterfaceMetricsCallback(
+    @JvmField internal var `counter`: UniffiCallbackInterfaceMetricsCallbackMethod0? = null,
+    @JvmField internal var `histogram`: UniffiCallbackInterfaceMetricsCallbackMethod1? = null,
+    @JvmField internal var `error`: UniffiCallbackInterfaceMetricsCallbackMethod2? = null,
+    @JvmField internal var `uniffiFree`: UniffiCallbackInterfaceFree? = null,
+) : Structure() {
+    class UniffiByValue(
+        `counter`: UniffiCallbackInterfaceMetricsCallbackMethod0? = null,
+        `histogram`: UniffiCallbackInterfaceMetricsCallbackMethod1? = null,
+        `error`: UniffiCallbackInterfaceMetricsCallbackMethod2? = null,
+        `uniffiFree`: UniffiCallbackInterfaceFree? = null,
+    ): UniffiVTableCallbackInterfaceMetricsCallback(`counter`,`histogram`,`error`,`uniffiFree`,), Structure.ByValue
+
+   internal fun uniffiSetValue(other: UniffiVTableCallbackInterfaceMetricsCallback) {
+        `counter` = other.`counter`
+        `histogram` = other.`histogram`
+        `error` = other.`error`
+        `uniffiFree` = other.`uniffiFree`
+    }
+
+}
+@Structure.FieldOrder("fetch", "uniffiFree")
+internal open class UniffiVTableCallbackInterfaceSrsFetcher(
+    @JvmField internal var `fetch`: UniffiCallbackInterfaceSrsFetcherMethod0? = null,
+    @JvmField internal var `uniffiFree`: UniffiCallbackInterfaceFree? = null,
+) : Structure() {
+    class UniffiByValue(
+        `fetch`: UniffiCallbackInterfaceSrsFetcherMethod0? = null,
+        `uniffiFree`: UniffiCallbackInterfaceFree? = null,
+    ): UniffiVTableCallbackInterfaceSrsFetcher(`fetch`,`uniffiFree`,), Structure.ByValue
+
+   internal fun uniffiSetValue(other: UniffiVTableCallbackInterfaceSrsFetcher) {
+        `fetch` = other.`fetch`
+        `uniffiFree` = other.`uniffiFree`
+    }
+
+}
+@Structure.FieldOrder("fetch", "uniffiFree")
+internal open class UniffiVTableCallbackInterfaceVerifierIndexSource(
+    @JvmField internal var `fetch`: UniffiCallbackInterfaceVerifierIndexSourceMethod0? = null,
+    @JvmField internal var `uniffiFree`: UniffiCallbackInterfaceFree? = null,
+) : Structure() {
+    class UniffiByValue(
+        `fetch`: UniffiCallbackInterfaceVerifierIndexSourceMethod0? = null,
+        `uniffiFree`: UniffiCallbackInterfaceFree? = null,
+    ): UniffiVTableCallbackInterfaceVerifierIndexSource(`fetch`,`uniffiFree`,), Structure.ByValue
+
+   internal fun uniffiSetValue(other: UniffiVTableCallbackInterfaceVerifierIndexSource) {
+        `fetch` = other.`fetch`
+        `uniffiFree` = other.`uniffiFree`
+    }
+
+}
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
+
 
 
 
@@ -739,29 +897,126 @@ internal interface UniffiLib : Library {
             .also { lib: UniffiLib ->
                 uniffiCheckContractApiVersion(lib)
                 uniffiCheckApiChecksums(lib)
+                uniffiCallbackInterfaceMetricsCallback.register(lib)
+                uniffiCallbackInterfaceSrsFetcher.register(lib)
+                uniffiCallbackInterfaceVerifierIndexSource.register(lib)
                 }
         }
         
+        // The Cleaner for the whole library
+        internal val CLEANER: UniffiCleaner by lazy {
+            UniffiCleaner.create()
+        }
     }
 
+    fun uniffi_kimchi_ffi_fn_clone_metricscallback(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+    ): Pointer
+    fun uniffi_kimchi_ffi_fn_free_metricscallback(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_init_callback_vtable_metricscallback(`vtable`: UniffiVTableCallbackInterfaceMetricsCallback,
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_method_metricscallback_counter(`ptr`: Pointer,`name`: RustBuffer.ByValue,`value`: Long,uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_method_metricscallback_histogram(`ptr`: Pointer,`name`: RustBuffer.ByValue,`value`: Double,uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_method_metricscallback_error(`ptr`: Pointer,`phase`: RustBuffer.ByValue,`code`: Int,`message`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_clone_srsfetcher(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+    ): Pointer
+    fun uniffi_kimchi_ffi_fn_free_srsfetcher(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_init_callback_vtable_srsfetcher(`vtable`: UniffiVTableCallbackInterfaceSrsFetcher,
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_method_srsfetcher_fetch(`ptr`: Pointer,`url`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_clone_verifierindexsource(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+    ): Pointer
+    fun uniffi_kimchi_ffi_fn_free_verifierindexsource(`ptr`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_init_callback_vtable_verifierindexsource(`vtable`: UniffiVTableCallbackInterfaceVerifierIndexSource,
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_method_verifierindexsource_fetch(`ptr`: Pointer,`digest`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_add_circuit_verifier_index(`circuitId`: RustBuffer.ByValue,`verifierIndex`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_add_verifier_index(`verifierIndex`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_check_envelope_signature(`signedEnvelope`: RustBuffer.ByValue,`expectedSigner`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_envelope_cose_to_be_signed(`envelope`: RustBuffer.ByValue,`alg`: Int,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_envelope_from_cbor(`cbor`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_envelope_to_cbor(`envelope`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_envelope_to_cose(`envelope`: RustBuffer.ByValue,`alg`: Int,`signature`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_export_circuit_gates(`circuitId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_export_mina_verification_key(`proofHandle`: Long,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_export_proof(`proofHandle`: Long,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_export_public_inputs(`proofHandle`: Long,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
     fun uniffi_kimchi_ffi_fn_func_export_verifier_index(`proofHandle`: Long,uniffi_out_err: UniffiRustCallStatus, 
     ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_export_zkapp_submission(`proofHandle`: Long,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
     fun uniffi_kimchi_ffi_fn_func_free_proof(`proofHandle`: Long,uniffi_out_err: UniffiRustCallStatus, 
     ): Unit
+    fun uniffi_kimchi_ffi_fn_func_get_circuit_info(`circuitId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_get_metrics(uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_get_proof_store_stats(uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_get_setup_info(`circuitId`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
     fun uniffi_kimchi_ffi_fn_func_get_srs_log2_size(uniffi_out_err: UniffiRustCallStatus, 
     ): Int
     fun uniffi_kimchi_ffi_fn_func_get_version(uniffi_out_err: UniffiRustCallStatus, 
     ): RustBuffer.ByValue
-    fun uniffi_kimchi_ffi_fn_func_init_prover(`srsLog2Size`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    fun uniffi_kimchi_ffi_fn_func_init_prover(`srsLog2Size`: RustBuffer.ByValue,`proofTtlSecs`: RustBuffer.ByValue,`cacheDir`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
     ): Unit
-    fun uniffi_kimchi_ffi_fn_func_prove_distance_under_threshold(`distanceMeters`: Long,`thresholdMeters`: Long,uniffi_out_err: UniffiRustCallStatus, 
-    ): RustBuffer.ByValue
-    fun uniffi_kimchi_ffi_fn_func_prove_passport_age(`input`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    fun uniffi_kimchi_ffi_fn_func_init_verifier(`srsLog2Size`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_func_open_cose_envelope(`cose`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
     ): RustBuffer.ByValue
-    fun uniffi_kimchi_ffi_fn_func_verify_passport_proof(`proofHandle`: Long,uniffi_out_err: UniffiRustCallStatus, 
-    ): Byte
-    fun uniffi_kimchi_ffi_fn_func_verify_proof(`proofHandle`: Long,uniffi_out_err: UniffiRustCallStatus, 
+    fun uniffi_kimchi_ffi_fn_func_prove_comparison(`value`: Long,`threshold`: Long,`operator`: RustBuffer.ByValue,
+    ): Long
+    fun uniffi_kimchi_ffi_fn_func_prove_merkle_membership(`path`: RustBuffer.ByValue,
+    ): Long
+    fun uniffi_kimchi_ffi_fn_func_prove_threshold(`value`: Long,`threshold`: Long,
+    ): Long
+    fun uniffi_kimchi_ffi_fn_func_prove_timestamped_comparison(`value`: Long,`threshold`: Long,`operator`: RustBuffer.ByValue,`timestampSecs`: Long,
+    ): Long
+    fun uniffi_kimchi_ffi_fn_func_prove_wide_comparison(`valueDecimal`: RustBuffer.ByValue,`thresholdDecimal`: RustBuffer.ByValue,`operator`: RustBuffer.ByValue,`width`: RustBuffer.ByValue,
+    ): Long
+    fun uniffi_kimchi_ffi_fn_func_remove_verifier_index(`digest`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
     ): Byte
+    fun uniffi_kimchi_ffi_fn_func_reset_metrics(uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_func_set_envelope_signing_key(`secretKeyHex`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): RustBuffer.ByValue
+    fun uniffi_kimchi_ffi_fn_func_set_metrics_callback(`callback`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_func_set_proof_store_limit(`limitBytes`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_func_set_srs_source(`source`: RustBuffer.ByValue,`fetcher`: RustBuffer.ByValue,uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_func_set_verifier_index_source(`source`: Pointer,uniffi_out_err: UniffiRustCallStatus, 
+    ): Unit
+    fun uniffi_kimchi_ffi_fn_func_verify_envelope(`envelope`: RustBuffer.ByValue,
+    ): Long
+    fun uniffi_kimchi_ffi_fn_func_verify_envelope_with_attestation(`envelope`: RustBuffer.ByValue,`platform`: RustBuffer.ByValue,`tokenHash`: RustBuffer.ByValue,
+    ): Long
+    fun uniffi_kimchi_ffi_fn_func_verify_envelope_with_max_age(`envelope`: RustBuffer.ByValue,`nowSecs`: Long,`maxAgeSecs`: Long,
+    ): Long
+    fun uniffi_kimchi_ffi_fn_func_verify_proof(`proofHandle`: Long,
+    ): Long
+    fun uniffi_kimchi_ffi_fn_func_verify_signed_envelope(`signedEnvelope`: RustBuffer.ByValue,`expectedSigner`: RustBuffer.ByValue,
+    ): Long
     fun ffi_kimchi_ffi_rustbuffer_alloc(`size`: Long,uniffi_out_err: UniffiRustCallStatus, 
     ): RustBuffer.ByValue
     fun ffi_kimchi_ffi_rustbuffer_from_bytes(`bytes`: ForeignBytes.ByValue,uniffi_out_err: UniffiRustCallStatus, 
@@ -874,24 +1129,96 @@ internal interface UniffiLib : Library {
     ): Unit
     fun ffi_kimchi_ffi_rust_future_complete_void(`handle`: Long,uniffi_out_err: UniffiRustCallStatus, 
     ): Unit
+    fun uniffi_kimchi_ffi_checksum_func_add_circuit_verifier_index(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_add_verifier_index(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_check_envelope_signature(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_envelope_cose_to_be_signed(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_envelope_from_cbor(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_envelope_to_cbor(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_envelope_to_cose(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_export_circuit_gates(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_export_mina_verification_key(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_export_proof(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_export_public_inputs(
+    ): Short
     fun uniffi_kimchi_ffi_checksum_func_export_verifier_index(
     ): Short
+    fun uniffi_kimchi_ffi_checksum_func_export_zkapp_submission(
+    ): Short
     fun uniffi_kimchi_ffi_checksum_func_free_proof(
     ): Short
+    fun uniffi_kimchi_ffi_checksum_func_get_circuit_info(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_get_metrics(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_get_proof_store_stats(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_get_setup_info(
+    ): Short
     fun uniffi_kimchi_ffi_checksum_func_get_srs_log2_size(
     ): Short
     fun uniffi_kimchi_ffi_checksum_func_get_version(
     ): Short
     fun uniffi_kimchi_ffi_checksum_func_init_prover(
     ): Short
-    fun uniffi_kimchi_ffi_checksum_func_prove_distance_under_threshold(
+    fun uniffi_kimchi_ffi_checksum_func_init_verifier(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_open_cose_envelope(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_prove_comparison(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_prove_merkle_membership(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_prove_threshold(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_prove_timestamped_comparison(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_prove_wide_comparison(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_remove_verifier_index(
     ): Short
-    fun uniffi_kimchi_ffi_checksum_func_prove_passport_age(
+    fun uniffi_kimchi_ffi_checksum_func_reset_metrics(
     ): Short
-    fun uniffi_kimchi_ffi_checksum_func_verify_passport_proof(
+    fun uniffi_kimchi_ffi_checksum_func_set_envelope_signing_key(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_set_metrics_callback(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_set_proof_store_limit(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_set_srs_source(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_set_verifier_index_source(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_verify_envelope(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_verify_envelope_with_attestation(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_func_verify_envelope_with_max_age(
     ): Short
     fun uniffi_kimchi_ffi_checksum_func_verify_proof(
     ): Short
+    fun uniffi_kimchi_ffi_checksum_func_verify_signed_envelope(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_method_metricscallback_counter(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_method_metricscallback_histogram(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_method_metricscallback_error(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_method_srsfetcher_fetch(
+    ): Short
+    fun uniffi_kimchi_ffi_checksum_method_verifierindexsource_fetch(
+    ): Short
     fun ffi_kimchi_ffi_uniffi_contract_version(
     ): Int
     
@@ -909,103 +1236,274 @@ private fun uniffiCheckContractApiVersion(lib: UniffiLib) {
 
 @Suppress("UNUSED_PARAMETER")
 private fun uniffiCheckApiChecksums(lib: UniffiLib) {
-    if (lib.uniffi_kimchi_ffi_checksum_func_export_verifier_index() != 30987.toShort()) {
+    if (lib.uniffi_kimchi_ffi_checksum_func_add_circuit_verifier_index() != 61396.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_kimchi_ffi_checksum_func_free_proof() != 35229.toShort()) {
+    if (lib.uniffi_kimchi_ffi_checksum_func_add_verifier_index() != 1091.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_kimchi_ffi_checksum_func_get_srs_log2_size() != 6043.toShort()) {
+    if (lib.uniffi_kimchi_ffi_checksum_func_check_envelope_signature() != 55674.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_kimchi_ffi_checksum_func_get_version() != 49808.toShort()) {
+    if (lib.uniffi_kimchi_ffi_checksum_func_envelope_cose_to_be_signed() != 38395.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_kimchi_ffi_checksum_func_init_prover() != 5452.toShort()) {
+    if (lib.uniffi_kimchi_ffi_checksum_func_envelope_from_cbor() != 54648.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_kimchi_ffi_checksum_func_prove_distance_under_threshold() != 39483.toShort()) {
+    if (lib.uniffi_kimchi_ffi_checksum_func_envelope_to_cbor() != 52673.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_kimchi_ffi_checksum_func_prove_passport_age() != 22415.toShort()) {
+    if (lib.uniffi_kimchi_ffi_checksum_func_envelope_to_cose() != 61603.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_kimchi_ffi_checksum_func_verify_passport_proof() != 42550.toShort()) {
+    if (lib.uniffi_kimchi_ffi_checksum_func_export_circuit_gates() != 17754.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-    if (lib.uniffi_kimchi_ffi_checksum_func_verify_proof() != 30611.toShort()) {
+    if (lib.uniffi_kimchi_ffi_checksum_func_export_mina_verification_key() != 55942.toShort()) {
         throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-}
-
-// Async support
-
-// Public interface members begin here.
-
-
-// Interface implemented by anything that can contain an object reference.
-//
-// Such types expose a `destroy()` method that must be called to cleanly
-// dispose of the contained objects. Failure to call this method may result
-// in memory leaks.
-//
-// The easiest way to ensure this method is called is to use the `.use`
-// helper method to execute a block and destroy the object at the end.
-interface Disposable {
-    fun destroy()
-    companion object {
-        fun destroy(vararg args: Any?) {
-            args.filterIsInstance<Disposable>()
-                .forEach(Disposable::destroy)
-        }
+    if (lib.uniffi_kimchi_ffi_checksum_func_export_proof() != 7734.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-}
-
-/**
- * @suppress
- */
-inline fun <T : Disposable?, R> T.use(block: (T) -> R) =
-    try {
-        block(this)
-    } finally {
-        try {
-            // N.B. our implementation is on the nullable type `Disposable?`.
-            this?.destroy()
-        } catch (e: Throwable) {
-            // swallow
-        }
+    if (lib.uniffi_kimchi_ffi_checksum_func_export_public_inputs() != 18336.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-
-/** 
- * Used to instantiate an interface without an actual pointer, for fakes in tests, mostly.
- *
- * @suppress
- * */
-object NoPointer
-
-/**
- * @suppress
- */
-public object FfiConverterUInt: FfiConverter<UInt, Int> {
-    override fun lift(value: Int): UInt {
-        return value.toUInt()
+    if (lib.uniffi_kimchi_ffi_checksum_func_export_verifier_index() != 20658.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-
-    override fun read(buf: ByteBuffer): UInt {
-        return lift(buf.getInt())
+    if (lib.uniffi_kimchi_ffi_checksum_func_export_zkapp_submission() != 17397.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-
-    override fun lower(value: UInt): Int {
-        return value.toInt()
+    if (lib.uniffi_kimchi_ffi_checksum_func_free_proof() != 35229.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
     }
-
-    override fun allocationSize(value: UInt) = 4UL
+    if (lib.uniffi_kimchi_ffi_checksum_func_get_circuit_info() != 752.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_get_metrics() != 19126.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_get_proof_store_stats() != 45632.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_get_setup_info() != 7206.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_get_srs_log2_size() != 10569.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_get_version() != 49808.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_init_prover() != 45181.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_init_verifier() != 20149.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_open_cose_envelope() != 40943.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_prove_comparison() != 43729.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_prove_merkle_membership() != 28715.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_prove_threshold() != 51344.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_prove_timestamped_comparison() != 46300.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_prove_wide_comparison() != 58097.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_remove_verifier_index() != 25271.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_reset_metrics() != 21468.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_set_envelope_signing_key() != 32855.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_set_metrics_callback() != 6627.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_set_proof_store_limit() != 63310.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_set_srs_source() != 37106.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_set_verifier_index_source() != 8605.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_verify_envelope() != 57784.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_verify_envelope_with_attestation() != 20956.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_verify_envelope_with_max_age() != 53742.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_verify_proof() != 48198.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_func_verify_signed_envelope() != 64350.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_method_metricscallback_counter() != 48573.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_method_metricscallback_histogram() != 48679.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_method_metricscallback_error() != 31763.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_method_srsfetcher_fetch() != 19633.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+    if (lib.uniffi_kimchi_ffi_checksum_method_verifierindexsource_fetch() != 2130.toShort()) {
+        throw RuntimeException("UniFFI API checksum mismatch: try cleaning and rebuilding your project")
+    }
+}
+
+// Async support
+// Async return type handlers
+
+internal const val UNIFFI_RUST_FUTURE_POLL_READY = 0.toByte()
+internal const val UNIFFI_RUST_FUTURE_POLL_MAYBE_READY = 1.toByte()
+
+internal val uniffiContinuationHandleMap = UniffiHandleMap<CancellableContinuation<Byte>>()
+
+// FFI type for Rust future continuations
+internal object uniffiRustFutureContinuationCallbackImpl: UniffiRustFutureContinuationCallback {
+    override fun callback(data: Long, pollResult: Byte) {
+        uniffiContinuationHandleMap.remove(data).resume(pollResult)
+    }
+}
+
+internal suspend fun<T, F, E: kotlin.Exception> uniffiRustCallAsync(
+    rustFuture: Long,
+    pollFunc: (Long, UniffiRustFutureContinuationCallback, Long) -> Unit,
+    completeFunc: (Long, UniffiRustCallStatus) -> F,
+    freeFunc: (Long) -> Unit,
+    liftFunc: (F) -> T,
+    errorHandler: UniffiRustCallStatusErrorHandler<E>
+): T {
+    try {
+        do {
+            val pollResult = suspendCancellableCoroutine<Byte> { continuation ->
+                pollFunc(
+                    rustFuture,
+                    uniffiRustFutureContinuationCallbackImpl,
+                    uniffiContinuationHandleMap.insert(continuation)
+                )
+            }
+        } while (pollResult != UNIFFI_RUST_FUTURE_POLL_READY);
+
+        return liftFunc(
+            uniffiRustCallWithError(errorHandler, { status -> completeFunc(rustFuture, status) })
+        )
+    } finally {
+        freeFunc(rustFuture)
+    }
+}
+
+// Public interface members begin here.
+
+
+// Interface implemented by anything that can contain an object reference.
+//
+// Such types expose a `destroy()` method that must be called to cleanly
+// dispose of the contained objects. Failure to call this method may result
+// in memory leaks.
+//
+// The easiest way to ensure this method is called is to use the `.use`
+// helper method to execute a block and destroy the object at the end.
+interface Disposable {
+    fun destroy()
+    companion object {
+        fun destroy(vararg args: Any?) {
+            args.filterIsInstance<Disposable>()
+                .forEach(Disposable::destroy)
+        }
+    }
+}
+
+/**
+ * @suppress
+ */
+inline fun <T : Disposable?, R> T.use(block: (T) -> R) =
+    try {
+        block(this)
+    } finally {
+        try {
+            // N.B. our implementation is on the nullable type `Disposable?`.
+            this?.destroy()
+        } catch (e: Throwable) {
+            // swallow
+        }
+    }
+
+/** 
+ * Used to instantiate an interface without an actual pointer, for fakes in tests, mostly.
+ *
+ * @suppress
+ * */
+object NoPointer
+
+/**
+ * @suppress
+ */
+public object FfiConverterUInt: FfiConverter<UInt, Int> {
+    override fun lift(value: Int): UInt {
+        return value.toUInt()
+    }
+
+    override fun read(buf: ByteBuffer): UInt {
+        return lift(buf.getInt())
+    }
+
+    override fun lower(value: UInt): Int {
+        return value.toInt()
+    }
+
+    override fun allocationSize(value: UInt) = 4UL
 
     override fun write(value: UInt, buf: ByteBuffer) {
         buf.putInt(value.toInt())
     }
 }
 
+/**
+ * @suppress
+ */
+public object FfiConverterInt: FfiConverter<Int, Int> {
+    override fun lift(value: Int): Int {
+        return value
+    }
+
+    override fun read(buf: ByteBuffer): Int {
+        return buf.getInt()
+    }
+
+    override fun lower(value: Int): Int {
+        return value
+    }
+
+    override fun allocationSize(value: Int) = 4UL
+
+    override fun write(value: Int, buf: ByteBuffer) {
+        buf.putInt(value)
+    }
+}
+
 /**
  * @suppress
  */
@@ -1029,6 +1527,29 @@ public object FfiConverterULong: FfiConverter<ULong, Long> {
     }
 }
 
+/**
+ * @suppress
+ */
+public object FfiConverterDouble: FfiConverter<Double, Double> {
+    override fun lift(value: Double): Double {
+        return value
+    }
+
+    override fun read(buf: ByteBuffer): Double {
+        return buf.getDouble()
+    }
+
+    override fun lower(value: Double): Double {
+        return value
+    }
+
+    override fun allocationSize(value: Double) = 8UL
+
+    override fun write(value: Double, buf: ByteBuffer) {
+        buf.putDouble(value)
+    }
+}
+
 /**
  * @suppress
  */
@@ -1109,423 +1630,2362 @@ public object FfiConverterString: FfiConverter<String, RustBuffer.ByValue> {
     }
 }
 
-
-
 /**
- * Input data for passport age proof generation.
+ * @suppress
  */
-data class PassportProofInput (
-    /**
-     * Minimum age to prove (e.g., 18).
-     */
-    var `minimumAge`: kotlin.UInt, 
-    /**
-     * Current date as YYYYMMDD (e.g., 20240115).
-     */
-    var `currentDate`: kotlin.UInt, 
-    /**
-     * SHA-256 hash of trusted Document Signer public key (hex encoded).
-     */
-    var `trustedDsKeyHash`: kotlin.String, 
-    /**
-     * Verifier-provided scope for domain separation (hex encoded, 32 bytes).
-     * Different verifiers use different scopes to prevent cross-service linking.
-     */
-    var `scope`: kotlin.String, 
-    /**
-     * Verifier-provided challenge/nonce for replay protection (hex encoded, 32 bytes).
-     */
-    var `challenge`: kotlin.String, 
-    /**
-     * Raw DG1 bytes (hex encoded).
-     */
-    var `dg1Bytes`: kotlin.String, 
-    /**
-     * Hash of DG1 from SOD (hex encoded).
-     */
-    var `dg1Hash`: kotlin.String, 
-    /**
-     * Signed attributes from SOD (hex encoded).
-     */
-    var `sodSignedAttrs`: kotlin.String, 
-    /**
-     * RSA-2048 signature from SOD (hex encoded, 512 hex chars = 256 bytes).
-     */
-    var `sodSignature`: kotlin.String, 
-    /**
-     * Document Signer RSA-2048 public key modulus (hex encoded, 512 hex chars).
-     */
-    var `dsPublicKeyN`: kotlin.String, 
-    /**
-     * Birthdate from MRZ in YYMMDD format.
-     */
-    var `birthdate`: kotlin.String
-) {
-    
-    companion object
+public object FfiConverterByteArray: FfiConverterRustBuffer<ByteArray> {
+    override fun read(buf: ByteBuffer): ByteArray {
+        val len = buf.getInt()
+        val byteArr = ByteArray(len)
+        buf.get(byteArr)
+        return byteArr
+    }
+    override fun allocationSize(value: ByteArray): ULong {
+        return 4UL + value.size.toULong()
+    }
+    override fun write(value: ByteArray, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        buf.put(value)
+    }
 }
 
+
+// This template implements a class for working with a Rust struct via a Pointer/Arc<T>
+// to the live Rust struct on the other side of the FFI.
+//
+// Each instance implements core operations for working with the Rust `Arc<T>` and the
+// Kotlin Pointer to work with the live Rust struct on the other side of the FFI.
+//
+// There's some subtlety here, because we have to be careful not to operate on a Rust
+// struct after it has been dropped, and because we must expose a public API for freeing
+// theq Kotlin wrapper object in lieu of reliable finalizers. The core requirements are:
+//
+//   * Each instance holds an opaque pointer to the underlying Rust struct.
+//     Method calls need to read this pointer from the object's state and pass it in to
+//     the Rust FFI.
+//
+//   * When an instance is no longer needed, its pointer should be passed to a
+//     special destructor function provided by the Rust FFI, which will drop the
+//     underlying Rust struct.
+//
+//   * Given an instance, calling code is expected to call the special
+//     `destroy` method in order to free it after use, either by calling it explicitly
+//     or by using a higher-level helper like the `use` method. Failing to do so risks
+//     leaking the underlying Rust struct.
+//
+//   * We can't assume that calling code will do the right thing, and must be prepared
+//     to handle Kotlin method calls executing concurrently with or even after a call to
+//     `destroy`, and to handle multiple (possibly concurrent!) calls to `destroy`.
+//
+//   * We must never allow Rust code to operate on the underlying Rust struct after
+//     the destructor has been called, and must never call the destructor more than once.
+//     Doing so may trigger memory unsafety.
+//
+//   * To mitigate many of the risks of leaking memory and use-after-free unsafety, a `Cleaner`
+//     is implemented to call the destructor when the Kotlin object becomes unreachable.
+//     This is done in a background thread. This is not a panacea, and client code should be aware that
+//      1. the thread may starve if some there are objects that have poorly performing
+//     `drop` methods or do significant work in their `drop` methods.
+//      2. the thread is shared across the whole library. This can be tuned by using `android_cleaner = true`,
+//         or `android = true` in the [`kotlin` section of the `uniffi.toml` file](https://mozilla.github.io/uniffi-rs/kotlin/configuration.html).
+//
+// If we try to implement this with mutual exclusion on access to the pointer, there is the
+// possibility of a race between a method call and a concurrent call to `destroy`:
+//
+//    * Thread A starts a method call, reads the value of the pointer, but is interrupted
+//      before it can pass the pointer over the FFI to Rust.
+//    * Thread B calls `destroy` and frees the underlying Rust struct.
+//    * Thread A resumes, passing the already-read pointer value to Rust and triggering
+//      a use-after-free.
+//
+// One possible solution would be to use a `ReadWriteLock`, with each method call taking
+// a read lock (and thus allowed to run concurrently) and the special `destroy` method
+// taking a write lock (and thus blocking on live method calls). However, we aim not to
+// generate methods with any hidden blocking semantics, and a `destroy` method that might
+// block if called incorrectly seems to meet that bar.
+//
+// So, we achieve our goals by giving each instance an associated `AtomicLong` counter to track
+// the number of in-flight method calls, and an `AtomicBoolean` flag to indicate whether `destroy`
+// has been called. These are updated according to the following rules:
+//
+//    * The initial value of the counter is 1, indicating a live object with no in-flight calls.
+//      The initial value for the flag is false.
+//
+//    * At the start of each method call, we atomically check the counter.
+//      If it is 0 then the underlying Rust struct has already been destroyed and the call is aborted.
+//      If it is nonzero them we atomically increment it by 1 and proceed with the method call.
+//
+//    * At the end of each method call, we atomically decrement and check the counter.
+//      If it has reached zero then we destroy the underlying Rust struct.
+//
+//    * When `destroy` is called, we atomically flip the flag from false to true.
+//      If the flag was already true we silently fail.
+//      Otherwise we atomically decrement and check the counter.
+//      If it has reached zero then we destroy the underlying Rust struct.
+//
+// Astute readers may observe that this all sounds very similar to the way that Rust's `Arc<T>` works,
+// and indeed it is, with the addition of a flag to guard against multiple calls to `destroy`.
+//
+// The overall effect is that the underlying Rust struct is destroyed only when `destroy` has been
+// called *and* all in-flight method calls have completed, avoiding violating any of the expectations
+// of the underlying Rust code.
+//
+// This makes a cleaner a better alternative to _not_ calling `destroy()` as
+// and when the object is finished with, but the abstraction is not perfect: if the Rust object's `drop`
+// method is slow, and/or there are many objects to cleanup, and it's on a low end Android device, then the cleaner
+// thread may be starved, and the app will leak memory.
+//
+// In this case, `destroy`ing manually may be a better solution.
+//
+// The cleaner can live side by side with the manual calling of `destroy`. In the order of responsiveness, uniffi objects
+// with Rust peers are reclaimed:
+//
+// 1. By calling the `destroy` method of the object, which calls `rustObject.free()`. If that doesn't happen:
+// 2. When the object becomes unreachable, AND the Cleaner thread gets to call `rustObject.free()`. If the thread is starved then:
+// 3. The memory is reclaimed when the process terminates.
+//
+// [1] https://stackoverflow.com/questions/24376768/can-java-finalize-an-object-when-it-is-still-in-scope/24380219
+//
+
+
 /**
+ * The cleaner interface for Object finalization code to run.
+ * This is the entry point to any implementation that we're using.
+ *
+ * The cleaner registers objects and returns cleanables, so now we are
+ * defining a `UniffiCleaner` with a `UniffiClenaer.Cleanable` to abstract the
+ * different implmentations available at compile time.
+ *
  * @suppress
  */
-public object FfiConverterTypePassportProofInput: FfiConverterRustBuffer<PassportProofInput> {
-    override fun read(buf: ByteBuffer): PassportProofInput {
-        return PassportProofInput(
-            FfiConverterUInt.read(buf),
-            FfiConverterUInt.read(buf),
-            FfiConverterString.read(buf),
-            FfiConverterString.read(buf),
-            FfiConverterString.read(buf),
-            FfiConverterString.read(buf),
-            FfiConverterString.read(buf),
-            FfiConverterString.read(buf),
-            FfiConverterString.read(buf),
-            FfiConverterString.read(buf),
-            FfiConverterString.read(buf),
-        )
+interface UniffiCleaner {
+    interface Cleanable {
+        fun clean()
     }
 
-    override fun allocationSize(value: PassportProofInput) = (
-            FfiConverterUInt.allocationSize(value.`minimumAge`) +
-            FfiConverterUInt.allocationSize(value.`currentDate`) +
-            FfiConverterString.allocationSize(value.`trustedDsKeyHash`) +
-            FfiConverterString.allocationSize(value.`scope`) +
-            FfiConverterString.allocationSize(value.`challenge`) +
-            FfiConverterString.allocationSize(value.`dg1Bytes`) +
-            FfiConverterString.allocationSize(value.`dg1Hash`) +
-            FfiConverterString.allocationSize(value.`sodSignedAttrs`) +
-            FfiConverterString.allocationSize(value.`sodSignature`) +
-            FfiConverterString.allocationSize(value.`dsPublicKeyN`) +
-            FfiConverterString.allocationSize(value.`birthdate`)
-    )
+    fun register(value: Any, cleanUpTask: Runnable): UniffiCleaner.Cleanable
 
-    override fun write(value: PassportProofInput, buf: ByteBuffer) {
-            FfiConverterUInt.write(value.`minimumAge`, buf)
-            FfiConverterUInt.write(value.`currentDate`, buf)
-            FfiConverterString.write(value.`trustedDsKeyHash`, buf)
-            FfiConverterString.write(value.`scope`, buf)
-            FfiConverterString.write(value.`challenge`, buf)
-            FfiConverterString.write(value.`dg1Bytes`, buf)
-            FfiConverterString.write(value.`dg1Hash`, buf)
-            FfiConverterString.write(value.`sodSignedAttrs`, buf)
-            FfiConverterString.write(value.`sodSignature`, buf)
-            FfiConverterString.write(value.`dsPublicKeyN`, buf)
-            FfiConverterString.write(value.`birthdate`, buf)
-    }
+    companion object
 }
 
+// The fallback Jna cleaner, which is available for both Android, and the JVM.
+private class UniffiJnaCleaner : UniffiCleaner {
+    private val cleaner = com.sun.jna.internal.Cleaner.getCleaner()
 
+    override fun register(value: Any, cleanUpTask: Runnable): UniffiCleaner.Cleanable =
+        UniffiJnaCleanable(cleaner.register(value, cleanUpTask))
+}
 
-/**
- * Result of passport age proof generation.
- */
-data class PassportProofResult (
-    /**
-     * Handle to the proof stored in memory.
-     */
-    var `proofHandle`: kotlin.ULong, 
-    /**
-     * Serialized proof (hex encoded).
-     */
-    var `proofBytes`: kotlin.String, 
-    /**
-     * Public inputs as hex-encoded field elements.
-     */
-    var `publicInputs`: List<kotlin.String>, 
-    /**
-     * Time taken in milliseconds.
-     */
-    var `generationTimeMs`: kotlin.ULong, 
-    /**
-     * Size of proof in bytes.
-     */
-    var `proofSizeBytes`: kotlin.ULong, 
-    /**
-     * The computed age.
-     */
-    var `computedAge`: kotlin.UInt, 
-    /**
-     * Whether the age meets the minimum requirement.
-     */
-    var `ageVerified`: kotlin.Boolean, 
-    /**
-     * The nullifier for this proof (hex encoded, 32 bytes).
-     * Derived as SHA256(dg1_hash || scope || challenge).
-     * Verifiers use this to detect reuse without linking across services.
-     */
-    var `nullifier`: kotlin.String
-) {
-    
-    companion object
+private class UniffiJnaCleanable(
+    private val cleanable: com.sun.jna.internal.Cleaner.Cleanable,
+) : UniffiCleaner.Cleanable {
+    override fun clean() = cleanable.clean()
 }
 
-/**
- * @suppress
- */
-public object FfiConverterTypePassportProofResult: FfiConverterRustBuffer<PassportProofResult> {
-    override fun read(buf: ByteBuffer): PassportProofResult {
-        return PassportProofResult(
-            FfiConverterULong.read(buf),
-            FfiConverterString.read(buf),
-            FfiConverterSequenceString.read(buf),
-            FfiConverterULong.read(buf),
-            FfiConverterULong.read(buf),
-            FfiConverterUInt.read(buf),
-            FfiConverterBoolean.read(buf),
-            FfiConverterString.read(buf),
-        )
+// We decide at uniffi binding generation time whether we were
+// using Android or not.
+// There are further runtime checks to chose the correct implementation
+// of the cleaner.
+private fun UniffiCleaner.Companion.create(): UniffiCleaner =
+    try {
+        // For safety's sake: if the library hasn't been run in android_cleaner = true
+        // mode, but is being run on Android, then we still need to think about
+        // Android API versions.
+        // So we check if java.lang.ref.Cleaner is there, and use that…
+        java.lang.Class.forName("java.lang.ref.Cleaner")
+        JavaLangRefCleaner()
+    } catch (e: ClassNotFoundException) {
+        // … otherwise, fallback to the JNA cleaner.
+        UniffiJnaCleaner()
     }
 
-    override fun allocationSize(value: PassportProofResult) = (
-            FfiConverterULong.allocationSize(value.`proofHandle`) +
-            FfiConverterString.allocationSize(value.`proofBytes`) +
-            FfiConverterSequenceString.allocationSize(value.`publicInputs`) +
-            FfiConverterULong.allocationSize(value.`generationTimeMs`) +
-            FfiConverterULong.allocationSize(value.`proofSizeBytes`) +
-            FfiConverterUInt.allocationSize(value.`computedAge`) +
-            FfiConverterBoolean.allocationSize(value.`ageVerified`) +
-            FfiConverterString.allocationSize(value.`nullifier`)
-    )
+private class JavaLangRefCleaner : UniffiCleaner {
+    val cleaner = java.lang.ref.Cleaner.create()
 
-    override fun write(value: PassportProofResult, buf: ByteBuffer) {
-            FfiConverterULong.write(value.`proofHandle`, buf)
-            FfiConverterString.write(value.`proofBytes`, buf)
-            FfiConverterSequenceString.write(value.`publicInputs`, buf)
-            FfiConverterULong.write(value.`generationTimeMs`, buf)
-            FfiConverterULong.write(value.`proofSizeBytes`, buf)
-            FfiConverterUInt.write(value.`computedAge`, buf)
-            FfiConverterBoolean.write(value.`ageVerified`, buf)
-            FfiConverterString.write(value.`nullifier`, buf)
-    }
+    override fun register(value: Any, cleanUpTask: Runnable): UniffiCleaner.Cleanable =
+        JavaLangRefCleanable(cleaner.register(value, cleanUpTask))
 }
 
-
-
+private class JavaLangRefCleanable(
+    val cleanable: java.lang.ref.Cleaner.Cleanable
+) : UniffiCleaner.Cleanable {
+    override fun clean() = cleanable.clean()
+}
 /**
- * Result of proof generation.
+ * Metrics receiver implemented by the host app, e.g. to forward to its
+ * monitoring SDK.
+ *
+ * Metric names are prefixed with `kimchi.`: the prover reports
+ * `kimchi.<phase>.count`, `kimchi.<phase>.duration_ms` and
+ * `kimchi.<phase>.errors` for `srs_init`, `setup`, `prove` and `verify`,
+ * and the bindings add `kimchi.ffi.<function>.*` around each call.
  */
-data class ProofResult (
-    /**
-     * Handle to the proof stored in memory (for verification).
-     */
-    var `proofHandle`: kotlin.ULong, 
-    /**
-     * Serialized proof (hex-encoded, for transmission/storage).
-     */
-    var `proofBytes`: kotlin.String, 
-    /**
-     * Public inputs as hex-encoded field elements.
-     */
-    var `publicInputs`: List<kotlin.String>, 
-    /**
-     * Time taken in milliseconds.
-     */
-    var `generationTimeMs`: kotlin.ULong, 
+public interface MetricsCallback {
+    
+    fun `counter`(`name`: kotlin.String, `value`: kotlin.ULong)
+    
+    fun `histogram`(`name`: kotlin.String, `value`: kotlin.Double)
+    
     /**
-     * Size of the proof in bytes.
+     * A phase failed with the given `ProverError` code.
      */
-    var `proofSizeBytes`: kotlin.ULong
-) {
+    fun `error`(`phase`: kotlin.String, `code`: kotlin.UInt, `message`: kotlin.String)
     
     companion object
 }
 
 /**
- * @suppress
+ * Metrics receiver implemented by the host app, e.g. to forward to its
+ * monitoring SDK.
+ *
+ * Metric names are prefixed with `kimchi.`: the prover reports
+ * `kimchi.<phase>.count`, `kimchi.<phase>.duration_ms` and
+ * `kimchi.<phase>.errors` for `srs_init`, `setup`, `prove` and `verify`,
+ * and the bindings add `kimchi.ffi.<function>.*` around each call.
  */
-public object FfiConverterTypeProofResult: FfiConverterRustBuffer<ProofResult> {
-    override fun read(buf: ByteBuffer): ProofResult {
-        return ProofResult(
-            FfiConverterULong.read(buf),
-            FfiConverterString.read(buf),
-            FfiConverterSequenceString.read(buf),
-            FfiConverterULong.read(buf),
-            FfiConverterULong.read(buf),
-        )
+open class MetricsCallbackImpl: Disposable, AutoCloseable, MetricsCallback {
+
+    constructor(pointer: Pointer) {
+        this.pointer = pointer
+        this.cleanable = UniffiLib.CLEANER.register(this, UniffiCleanAction(pointer))
     }
 
-    override fun allocationSize(value: ProofResult) = (
-            FfiConverterULong.allocationSize(value.`proofHandle`) +
-            FfiConverterString.allocationSize(value.`proofBytes`) +
-            FfiConverterSequenceString.allocationSize(value.`publicInputs`) +
-            FfiConverterULong.allocationSize(value.`generationTimeMs`) +
-            FfiConverterULong.allocationSize(value.`proofSizeBytes`)
-    )
+    /**
+     * This constructor can be used to instantiate a fake object. Only used for tests. Any
+     * attempt to actually use an object constructed this way will fail as there is no
+     * connected Rust object.
+     */
+    @Suppress("UNUSED_PARAMETER")
+    constructor(noPointer: NoPointer) {
+        this.pointer = null
+        this.cleanable = UniffiLib.CLEANER.register(this, UniffiCleanAction(pointer))
+    }
 
-    override fun write(value: ProofResult, buf: ByteBuffer) {
-            FfiConverterULong.write(value.`proofHandle`, buf)
-            FfiConverterString.write(value.`proofBytes`, buf)
-            FfiConverterSequenceString.write(value.`publicInputs`, buf)
-            FfiConverterULong.write(value.`generationTimeMs`, buf)
-            FfiConverterULong.write(value.`proofSizeBytes`, buf)
+    protected val pointer: Pointer?
+    protected val cleanable: UniffiCleaner.Cleanable
+
+    private val wasDestroyed = AtomicBoolean(false)
+    private val callCounter = AtomicLong(1)
+
+    override fun destroy() {
+        // Only allow a single call to this method.
+        // TODO: maybe we should log a warning if called more than once?
+        if (this.wasDestroyed.compareAndSet(false, true)) {
+            // This decrement always matches the initial count of 1 given at creation time.
+            if (this.callCounter.decrementAndGet() == 0L) {
+                cleanable.clean()
+            }
+        }
     }
-}
 
+    @Synchronized
+    override fun close() {
+        this.destroy()
+    }
 
+    internal inline fun <R> callWithPointer(block: (ptr: Pointer) -> R): R {
+        // Check and increment the call counter, to keep the object alive.
+        // This needs a compare-and-set retry loop in case of concurrent updates.
+        do {
+            val c = this.callCounter.get()
+            if (c == 0L) {
+                throw IllegalStateException("${this.javaClass.simpleName} object has already been destroyed")
+            }
+            if (c == Long.MAX_VALUE) {
+                throw IllegalStateException("${this.javaClass.simpleName} call counter would overflow")
+            }
+        } while (! this.callCounter.compareAndSet(c, c + 1L))
+        // Now we can safely do the method call without the pointer being freed concurrently.
+        try {
+            return block(this.uniffiClonePointer())
+        } finally {
+            // This decrement always matches the increment we performed above.
+            if (this.callCounter.decrementAndGet() == 0L) {
+                cleanable.clean()
+            }
+        }
+    }
 
+    // Use a static inner class instead of a closure so as not to accidentally
+    // capture `this` as part of the cleanable's action.
+    private class UniffiCleanAction(private val pointer: Pointer?) : Runnable {
+        override fun run() {
+            pointer?.let { ptr ->
+                uniffiRustCall { status ->
+                    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_free_metricscallback(ptr, status)
+                }
+            }
+        }
+    }
 
+    fun uniffiClonePointer(): Pointer {
+        return uniffiRustCall() { status ->
+            UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_clone_metricscallback(pointer!!, status)
+        }
+    }
 
-/**
- * Error types exposed via FFI.
- */
-sealed class KimchiException: kotlin.Exception() {
-    
-    class SetupException(
-        
-        val v1: kotlin.String
-        ) : KimchiException() {
-        override val message
-            get() = "v1=${ v1 }"
+    override fun `counter`(`name`: kotlin.String, `value`: kotlin.ULong)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_method_metricscallback_counter(
+        it, FfiConverterString.lower(`name`),FfiConverterULong.lower(`value`),_status)
+}
     }
     
-    class ProvingException(
-        
-        val v1: kotlin.String
-        ) : KimchiException() {
-        override val message
-            get() = "v1=${ v1 }"
-    }
     
-    class VerificationException(
-        
-        val v1: kotlin.String
-        ) : KimchiException() {
-        override val message
-            get() = "v1=${ v1 }"
+
+    override fun `histogram`(`name`: kotlin.String, `value`: kotlin.Double)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_method_metricscallback_histogram(
+        it, FfiConverterString.lower(`name`),FfiConverterDouble.lower(`value`),_status)
+}
     }
     
-    class SerializationException(
-        
-        val v1: kotlin.String
-        ) : KimchiException() {
-        override val message
-            get() = "v1=${ v1 }"
-    }
     
-    class InvalidInput(
-        
-        val v1: kotlin.String
-        ) : KimchiException() {
-        override val message
-            get() = "v1=${ v1 }"
-    }
+
     
-    class ProofNotFound(
-        
-        val v1: kotlin.String
-        ) : KimchiException() {
-        override val message
-            get() = "v1=${ v1 }"
+    /**
+     * A phase failed with the given `ProverError` code.
+     */override fun `error`(`phase`: kotlin.String, `code`: kotlin.UInt, `message`: kotlin.String)
+        = 
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_method_metricscallback_error(
+        it, FfiConverterString.lower(`phase`),FfiConverterUInt.lower(`code`),FfiConverterString.lower(`message`),_status)
+}
     }
     
+    
 
-    companion object ErrorHandler : UniffiRustCallStatusErrorHandler<KimchiException> {
-        override fun lift(error_buf: RustBuffer.ByValue): KimchiException = FfiConverterTypeKimchiError.lift(error_buf)
-    }
+    
 
     
+    
+    companion object
+    
 }
+// Magic number for the Rust proxy to call using the same mechanism as every other method,
+// to free the callback once it's dropped by Rust.
+internal const val IDX_CALLBACK_FREE = 0
+// Callback return codes
+internal const val UNIFFI_CALLBACK_SUCCESS = 0
+internal const val UNIFFI_CALLBACK_ERROR = 1
+internal const val UNIFFI_CALLBACK_UNEXPECTED_ERROR = 2
 
 /**
  * @suppress
  */
-public object FfiConverterTypeKimchiError : FfiConverterRustBuffer<KimchiException> {
-    override fun read(buf: ByteBuffer): KimchiException {
-        
+public abstract class FfiConverterCallbackInterface<CallbackInterface: Any>: FfiConverter<CallbackInterface, Long> {
+    internal val handleMap = UniffiHandleMap<CallbackInterface>()
 
-        return when(buf.getInt()) {
-            1 -> KimchiException.SetupException(
-                FfiConverterString.read(buf),
-                )
-            2 -> KimchiException.ProvingException(
-                FfiConverterString.read(buf),
-                )
-            3 -> KimchiException.VerificationException(
-                FfiConverterString.read(buf),
-                )
-            4 -> KimchiException.SerializationException(
-                FfiConverterString.read(buf),
+    internal fun drop(handle: Long) {
+        handleMap.remove(handle)
+    }
+
+    override fun lift(value: Long): CallbackInterface {
+        return handleMap.get(value)
+    }
+
+    override fun read(buf: ByteBuffer) = lift(buf.getLong())
+
+    override fun lower(value: CallbackInterface) = handleMap.insert(value)
+
+    override fun allocationSize(value: CallbackInterface) = 8UL
+
+    override fun write(value: CallbackInterface, buf: ByteBuffer) {
+        buf.putLong(lower(value))
+    }
+}
+
+// Put the implementation in an object so we don't pollute the top-level namespace
+internal object uniffiCallbackInterfaceMetricsCallback {
+    internal object `counter`: UniffiCallbackInterfaceMetricsCallbackMethod0 {
+        override fun callback(`uniffiHandle`: Long,`name`: RustBuffer.ByValue,`value`: Long,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,) {
+            val uniffiObj = FfiConverterTypeMetricsCallback.handleMap.get(uniffiHandle)
+            val makeCall = { ->
+                uniffiObj.`counter`(
+                    FfiConverterString.lift(`name`),
+                    FfiConverterULong.lift(`value`),
                 )
-            5 -> KimchiException.InvalidInput(
-                FfiConverterString.read(buf),
+            }
+            val writeReturn = { _: Unit -> Unit }
+            uniffiTraitInterfaceCall(uniffiCallStatus, makeCall, writeReturn)
+        }
+    }
+    internal object `histogram`: UniffiCallbackInterfaceMetricsCallbackMethod1 {
+        override fun callback(`uniffiHandle`: Long,`name`: RustBuffer.ByValue,`value`: Double,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,) {
+            val uniffiObj = FfiConverterTypeMetricsCallback.handleMap.get(uniffiHandle)
+            val makeCall = { ->
+                uniffiObj.`histogram`(
+                    FfiConverterString.lift(`name`),
+                    FfiConverterDouble.lift(`value`),
                 )
-            6 -> KimchiException.ProofNotFound(
-                FfiConverterString.read(buf),
+            }
+            val writeReturn = { _: Unit -> Unit }
+            uniffiTraitInterfaceCall(uniffiCallStatus, makeCall, writeReturn)
+        }
+    }
+    internal object `error`: UniffiCallbackInterfaceMetricsCallbackMethod2 {
+        override fun callback(`uniffiHandle`: Long,`phase`: RustBuffer.ByValue,`code`: Int,`message`: RustBuffer.ByValue,`uniffiOutReturn`: Pointer,uniffiCallStatus: UniffiRustCallStatus,) {
+            val uniffiObj = FfiConverterTypeMetricsCallback.handleMap.get(uniffiHandle)
+            val makeCall = { ->
+                uniffiObj.`error`(
+                    FfiConverterString.lift(`phase`),
+                    FfiConverterUInt.lift(`code`),
+                    FfiConverterString.lift(`message`),
                 )
-            else -> throw RuntimeException("invalid error enum value, something is very wrong!!")
+            }
+            val writeReturn = { _: Unit -> Unit }
+            uniffiTraitInterfaceCall(uniffiCallStatus, makeCall, writeReturn)
         }
     }
 
-    override fun allocationSize(value: KimchiException): ULong {
-        return when(value) {
-            is KimchiException.SetupException -> (
-                // Add the size for the Int that specifies the variant plus the size needed for all fields
-                4UL
-                + FfiConverterString.allocationSize(value.v1)
-            )
-            is KimchiException.ProvingException -> (
-                // Add the size for the Int that specifies the variant plus the size needed for all fields
-                4UL
-                + FfiConverterString.allocationSize(value.v1)
-            )
-            is KimchiException.VerificationException -> (
-                // Add the size for the Int that specifies the variant plus the size needed for all fields
-                4UL
-                + FfiConverterString.allocationSize(value.v1)
-            )
-            is KimchiException.SerializationException -> (
-                // Add the size for the Int that specifies the variant plus the size needed for all fields
-                4UL
-                + FfiConverterString.allocationSize(value.v1)
-            )
-            is KimchiException.InvalidInput -> (
-                // Add the size for the Int that specifies the variant plus the size needed for all fields
-                4UL
-                + FfiConverterString.allocationSize(value.v1)
-            )
-            is KimchiException.ProofNotFound -> (
-                // Add the size for the Int that specifies the variant plus the size needed for all fields
-                4UL
-                + FfiConverterString.allocationSize(value.v1)
-            )
+    internal object uniffiFree: UniffiCallbackInterfaceFree {
+        override fun callback(handle: Long) {
+            FfiConverterTypeMetricsCallback.handleMap.remove(handle)
         }
     }
 
-    override fun write(value: KimchiException, buf: ByteBuffer) {
-        when(value) {
+    internal var vtable = UniffiVTableCallbackInterfaceMetricsCallback.UniffiByValue(
+        `counter`,
+        `histogram`,
+        `error`,
+        uniffiFree,
+    )
+
+    // Registers the foreign callback with the Rust side.
+    // This method is generated for each callback interface.
+    internal fun register(lib: UniffiLib) {
+        lib.uniffi_kimchi_ffi_fn_init_callback_vtable_metricscallback(vtable)
+    }
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeMetricsCallback: FfiConverter<MetricsCallback, Pointer> {
+    internal val handleMap = UniffiHandleMap<MetricsCallback>()
+
+    override fun lower(value: MetricsCallback): Pointer {
+        return Pointer(handleMap.insert(value))
+    }
+
+    override fun lift(value: Pointer): MetricsCallback {
+        return MetricsCallbackImpl(value)
+    }
+
+    override fun read(buf: ByteBuffer): MetricsCallback {
+        // The Rust code always writes pointers as 8 bytes, and will
+        // fail to compile if they don't fit.
+        return lift(Pointer(buf.getLong()))
+    }
+
+    override fun allocationSize(value: MetricsCallback) = 8UL
+
+    override fun write(value: MetricsCallback, buf: ByteBuffer) {
+        // The Rust code always expects pointers written as 8 bytes,
+        // and will fail to compile if they don't fit.
+        buf.putLong(Pointer.nativeValue(lower(value)))
+    }
+}
+
+
+// This template implements a class for working with a Rust struct via a Pointer/Arc<T>
+// to the live Rust struct on the other side of the FFI.
+//
+// Each instance implements core operations for working with the Rust `Arc<T>` and the
+// Kotlin Pointer to work with the live Rust struct on the other side of the FFI.
+//
+// There's some subtlety here, because we have to be careful not to operate on a Rust
+// struct after it has been dropped, and because we must expose a public API for freeing
+// theq Kotlin wrapper object in lieu of reliable finalizers. The core requirements are:
+//
+//   * Each instance holds an opaque pointer to the underlying Rust struct.
+//     Method calls need to read this pointer from the object's state and pass it in to
+//     the Rust FFI.
+//
+//   * When an instance is no longer needed, its pointer should be passed to a
+//     special destructor function provided by the Rust FFI, which will drop the
+//     underlying Rust struct.
+//
+//   * Given an instance, calling code is expected to call the special
+//     `destroy` method in order to free it after use, either by calling it explicitly
+//     or by using a higher-level helper like the `use` method. Failing to do so risks
+//     leaking the underlying Rust struct.
+//
+//   * We can't assume that calling code will do the right thing, and must be prepared
+//     to handle Kotlin method calls executing concurrently with or even after a call to
+//     `destroy`, and to handle multiple (possibly concurrent!) calls to `destroy`.
+//
+//   * We must never allow Rust code to operate on the underlying Rust struct after
+//     the destructor has been called, and must never call the destructor more than once.
+//     Doing so may trigger memory unsafety.
+//
+//   * To mitigate many of the risks of leaking memory and use-after-free unsafety, a `Cleaner`
+//     is implemented to call the destructor when the Kotlin object becomes unreachable.
+//     This is done in a background thread. This is not a panacea, and client code should be aware that
+//      1. the thread may starve if some there are objects that have poorly performing
+//     `drop` methods or do significant work in their `drop` methods.
+//      2. the thread is shared across the whole library. This can be tuned by using `android_cleaner = true`,
+//         or `android = true` in the [`kotlin` section of the `uniffi.toml` file](https://mozilla.github.io/uniffi-rs/kotlin/configuration.html).
+//
+// If we try to implement this with mutual exclusion on access to the pointer, there is the
+// possibility of a race between a method call and a concurrent call to `destroy`:
+//
+//    * Thread A starts a method call, reads the value of the pointer, but is interrupted
+//      before it can pass the pointer over the FFI to Rust.
+//    * Thread B calls `destroy` and frees the underlying Rust struct.
+//    * Thread A resumes, passing the already-read pointer value to Rust and triggering
+//      a use-after-free.
+//
+// One possible solution would be to use a `ReadWriteLock`, with each method call taking
+// a read lock (and thus allowed to run concurrently) and the special `destroy` method
+// taking a write lock (and thus blocking on live method calls). However, we aim not to
+// generate methods with any hidden blocking semantics, and a `destroy` method that might
+// block if called incorrectly seems to meet that bar.
+//
+// So, we achieve our goals by giving each instance an associated `AtomicLong` counter to track
+// the number of in-flight method calls, and an `AtomicBoolean` flag to indicate whether `destroy`
+// has been called. These are updated according to the following rules:
+//
+//    * The initial value of the counter is 1, indicating a live object with no in-flight calls.
+//      The initial value for the flag is false.
+//
+//    * At the start of each method call, we atomically check the counter.
+//      If it is 0 then the underlying Rust struct has already been destroyed and the call is aborted.
+//      If it is nonzero them we atomically increment it by 1 and proceed with the method call.
+//
+//    * At the end of each method call, we atomically decrement and check the counter.
+//      If it has reached zero then we destroy the underlying Rust struct.
+//
+//    * When `destroy` is called, we atomically flip the flag from false to true.
+//      If the flag was already true we silently fail.
+//      Otherwise we atomically decrement and check the counter.
+//      If it has reached zero then we destroy the underlying Rust struct.
+//
+// Astute readers may observe that this all sounds very similar to the way that Rust's `Arc<T>` works,
+// and indeed it is, with the addition of a flag to guard against multiple calls to `destroy`.
+//
+// The overall effect is that the underlying Rust struct is destroyed only when `destroy` has been
+// called *and* all in-flight method calls have completed, avoiding violating any of the expectations
+// of the underlying Rust code.
+//
+// This makes a cleaner a better alternative to _not_ calling `destroy()` as
+// and when the object is finished with, but the abstraction is not perfect: if the Rust object's `drop`
+// method is slow, and/or there are many objects to cleanup, and it's on a low end Android device, then the cleaner
+// thread may be starved, and the app will leak memory.
+//
+// In this case, `destroy`ing manually may be a better solution.
+//
+// The cleaner can live side by side with the manual calling of `destroy`. In the order of responsiveness, uniffi objects
+// with Rust peers are reclaimed:
+//
+// 1. By calling the `destroy` method of the object, which calls `rustObject.free()`. If that doesn't happen:
+// 2. When the object becomes unreachable, AND the Cleaner thread gets to call `rustObject.free()`. If the thread is starved then:
+// 3. The memory is reclaimed when the process terminates.
+//
+// [1] https://stackoverflow.com/questions/24376768/can-java-finalize-an-object-when-it-is-still-in-scope/24380219
+//
+
+
+/**
+ * Transport for `SrsSource::Download`, implemented by the host app, e.g.
+ * on its HTTP client restricted to Wi-Fi.
+ *
+ * Called on the thread initializing the SRS; it may block.
+ */
+public interface SrsFetcher {
+    
+    /**
+     * The body at `url`, or `None` if it cannot be fetched right now.
+     */
+    fun `fetch`(`url`: kotlin.String): kotlin.ByteArray?
+    
+    companion object
+}
+
+/**
+ * Transport for `SrsSource::Download`, implemented by the host app, e.g.
+ * on its HTTP client restricted to Wi-Fi.
+ *
+ * Called on the thread initializing the SRS; it may block.
+ */
+open class SrsFetcherImpl: Disposable, AutoCloseable, SrsFetcher {
+
+    constructor(pointer: Pointer) {
+        this.pointer = pointer
+        this.cleanable = UniffiLib.CLEANER.register(this, UniffiCleanAction(pointer))
+    }
+
+    /**
+     * This constructor can be used to instantiate a fake object. Only used for tests. Any
+     * attempt to actually use an object constructed this way will fail as there is no
+     * connected Rust object.
+     */
+    @Suppress("UNUSED_PARAMETER")
+    constructor(noPointer: NoPointer) {
+        this.pointer = null
+        this.cleanable = UniffiLib.CLEANER.register(this, UniffiCleanAction(pointer))
+    }
+
+    protected val pointer: Pointer?
+    protected val cleanable: UniffiCleaner.Cleanable
+
+    private val wasDestroyed = AtomicBoolean(false)
+    private val callCounter = AtomicLong(1)
+
+    override fun destroy() {
+        // Only allow a single call to this method.
+        // TODO: maybe we should log a warning if called more than once?
+        if (this.wasDestroyed.compareAndSet(false, true)) {
+            // This decrement always matches the initial count of 1 given at creation time.
+            if (this.callCounter.decrementAndGet() == 0L) {
+                cleanable.clean()
+            }
+        }
+    }
+
+    @Synchronized
+    override fun close() {
+        this.destroy()
+    }
+
+    internal inline fun <R> callWithPointer(block: (ptr: Pointer) -> R): R {
+        // Check and increment the call counter, to keep the object alive.
+        // This needs a compare-and-set retry loop in case of concurrent updates.
+        do {
+            val c = this.callCounter.get()
+            if (c == 0L) {
+                throw IllegalStateException("${this.javaClass.simpleName} object has already been destroyed")
+            }
+            if (c == Long.MAX_VALUE) {
+                throw IllegalStateException("${this.javaClass.simpleName} call counter would overflow")
+            }
+        } while (! this.callCounter.compareAndSet(c, c + 1L))
+        // Now we can safely do the method call without the pointer being freed concurrently.
+        try {
+            return block(this.uniffiClonePointer())
+        } finally {
+            // This decrement always matches the increment we performed above.
+            if (this.callCounter.decrementAndGet() == 0L) {
+                cleanable.clean()
+            }
+        }
+    }
+
+    // Use a static inner class instead of a closure so as not to accidentally
+    // capture `this` as part of the cleanable's action.
+    private class UniffiCleanAction(private val pointer: Pointer?) : Runnable {
+        override fun run() {
+            pointer?.let { ptr ->
+                uniffiRustCall { status ->
+                    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_free_srsfetcher(ptr, status)
+                }
+            }
+        }
+    }
+
+    fun uniffiClonePointer(): Pointer {
+        return uniffiRustCall() { status ->
+            UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_clone_srsfetcher(pointer!!, status)
+        }
+    }
+
+    
+    /**
+     * The body at `url`, or `None` if it cannot be fetched right now.
+     */override fun `fetch`(`url`: kotlin.String): kotlin.ByteArray? {
+            return FfiConverterOptionalByteArray.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_method_srsfetcher_fetch(
+        it, FfiConverterString.lower(`url`),_status)
+}
+    }
+    )
+    }
+    
+
+    
+
+    
+    
+    companion object
+    
+}
+
+
+// Put the implementation in an object so we don't pollute the top-level namespace
+internal object uniffiCallbackInterfaceSrsFetcher {
+    internal object `fetch`: UniffiCallbackInterfaceSrsFetcherMethod0 {
+        override fun callback(`uniffiHandle`: Long,`url`: RustBuffer.ByValue,`uniffiOutReturn`: RustBuffer,uniffiCallStatus: UniffiRustCallStatus,) {
+            val uniffiObj = FfiConverterTypeSrsFetcher.handleMap.get(uniffiHandle)
+            val makeCall = { ->
+                uniffiObj.`fetch`(
+                    FfiConverterString.lift(`url`),
+                )
+            }
+            val writeReturn = { value: kotlin.ByteArray? -> uniffiOutReturn.setValue(FfiConverterOptionalByteArray.lower(value)) }
+            uniffiTraitInterfaceCall(uniffiCallStatus, makeCall, writeReturn)
+        }
+    }
+
+    internal object uniffiFree: UniffiCallbackInterfaceFree {
+        override fun callback(handle: Long) {
+            FfiConverterTypeSrsFetcher.handleMap.remove(handle)
+        }
+    }
+
+    internal var vtable = UniffiVTableCallbackInterfaceSrsFetcher.UniffiByValue(
+        `fetch`,
+        uniffiFree,
+    )
+
+    // Registers the foreign callback with the Rust side.
+    // This method is generated for each callback interface.
+    internal fun register(lib: UniffiLib) {
+        lib.uniffi_kimchi_ffi_fn_init_callback_vtable_srsfetcher(vtable)
+    }
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeSrsFetcher: FfiConverter<SrsFetcher, Pointer> {
+    internal val handleMap = UniffiHandleMap<SrsFetcher>()
+
+    override fun lower(value: SrsFetcher): Pointer {
+        return Pointer(handleMap.insert(value))
+    }
+
+    override fun lift(value: Pointer): SrsFetcher {
+        return SrsFetcherImpl(value)
+    }
+
+    override fun read(buf: ByteBuffer): SrsFetcher {
+        // The Rust code always writes pointers as 8 bytes, and will
+        // fail to compile if they don't fit.
+        return lift(Pointer(buf.getLong()))
+    }
+
+    override fun allocationSize(value: SrsFetcher) = 8UL
+
+    override fun write(value: SrsFetcher, buf: ByteBuffer) {
+        // The Rust code always expects pointers written as 8 bytes,
+        // and will fail to compile if they don't fit.
+        buf.putLong(Pointer.nativeValue(lower(value)))
+    }
+}
+
+
+// This template implements a class for working with a Rust struct via a Pointer/Arc<T>
+// to the live Rust struct on the other side of the FFI.
+//
+// Each instance implements core operations for working with the Rust `Arc<T>` and the
+// Kotlin Pointer to work with the live Rust struct on the other side of the FFI.
+//
+// There's some subtlety here, because we have to be careful not to operate on a Rust
+// struct after it has been dropped, and because we must expose a public API for freeing
+// theq Kotlin wrapper object in lieu of reliable finalizers. The core requirements are:
+//
+//   * Each instance holds an opaque pointer to the underlying Rust struct.
+//     Method calls need to read this pointer from the object's state and pass it in to
+//     the Rust FFI.
+//
+//   * When an instance is no longer needed, its pointer should be passed to a
+//     special destructor function provided by the Rust FFI, which will drop the
+//     underlying Rust struct.
+//
+//   * Given an instance, calling code is expected to call the special
+//     `destroy` method in order to free it after use, either by calling it explicitly
+//     or by using a higher-level helper like the `use` method. Failing to do so risks
+//     leaking the underlying Rust struct.
+//
+//   * We can't assume that calling code will do the right thing, and must be prepared
+//     to handle Kotlin method calls executing concurrently with or even after a call to
+//     `destroy`, and to handle multiple (possibly concurrent!) calls to `destroy`.
+//
+//   * We must never allow Rust code to operate on the underlying Rust struct after
+//     the destructor has been called, and must never call the destructor more than once.
+//     Doing so may trigger memory unsafety.
+//
+//   * To mitigate many of the risks of leaking memory and use-after-free unsafety, a `Cleaner`
+//     is implemented to call the destructor when the Kotlin object becomes unreachable.
+//     This is done in a background thread. This is not a panacea, and client code should be aware that
+//      1. the thread may starve if some there are objects that have poorly performing
+//     `drop` methods or do significant work in their `drop` methods.
+//      2. the thread is shared across the whole library. This can be tuned by using `android_cleaner = true`,
+//         or `android = true` in the [`kotlin` section of the `uniffi.toml` file](https://mozilla.github.io/uniffi-rs/kotlin/configuration.html).
+//
+// If we try to implement this with mutual exclusion on access to the pointer, there is the
+// possibility of a race between a method call and a concurrent call to `destroy`:
+//
+//    * Thread A starts a method call, reads the value of the pointer, but is interrupted
+//      before it can pass the pointer over the FFI to Rust.
+//    * Thread B calls `destroy` and frees the underlying Rust struct.
+//    * Thread A resumes, passing the already-read pointer value to Rust and triggering
+//      a use-after-free.
+//
+// One possible solution would be to use a `ReadWriteLock`, with each method call taking
+// a read lock (and thus allowed to run concurrently) and the special `destroy` method
+// taking a write lock (and thus blocking on live method calls). However, we aim not to
+// generate methods with any hidden blocking semantics, and a `destroy` method that might
+// block if called incorrectly seems to meet that bar.
+//
+// So, we achieve our goals by giving each instance an associated `AtomicLong` counter to track
+// the number of in-flight method calls, and an `AtomicBoolean` flag to indicate whether `destroy`
+// has been called. These are updated according to the following rules:
+//
+//    * The initial value of the counter is 1, indicating a live object with no in-flight calls.
+//      The initial value for the flag is false.
+//
+//    * At the start of each method call, we atomically check the counter.
+//      If it is 0 then the underlying Rust struct has already been destroyed and the call is aborted.
+//      If it is nonzero them we atomically increment it by 1 and proceed with the method call.
+//
+//    * At the end of each method call, we atomically decrement and check the counter.
+//      If it has reached zero then we destroy the underlying Rust struct.
+//
+//    * When `destroy` is called, we atomically flip the flag from false to true.
+//      If the flag was already true we silently fail.
+//      Otherwise we atomically decrement and check the counter.
+//      If it has reached zero then we destroy the underlying Rust struct.
+//
+// Astute readers may observe that this all sounds very similar to the way that Rust's `Arc<T>` works,
+// and indeed it is, with the addition of a flag to guard against multiple calls to `destroy`.
+//
+// The overall effect is that the underlying Rust struct is destroyed only when `destroy` has been
+// called *and* all in-flight method calls have completed, avoiding violating any of the expectations
+// of the underlying Rust code.
+//
+// This makes a cleaner a better alternative to _not_ calling `destroy()` as
+// and when the object is finished with, but the abstraction is not perfect: if the Rust object's `drop`
+// method is slow, and/or there are many objects to cleanup, and it's on a low end Android device, then the cleaner
+// thread may be starved, and the app will leak memory.
+//
+// In this case, `destroy`ing manually may be a better solution.
+//
+// The cleaner can live side by side with the manual calling of `destroy`. In the order of responsiveness, uniffi objects
+// with Rust peers are reclaimed:
+//
+// 1. By calling the `destroy` method of the object, which calls `rustObject.free()`. If that doesn't happen:
+// 2. When the object becomes unreachable, AND the Cleaner thread gets to call `rustObject.free()`. If the thread is starved then:
+// 3. The memory is reclaimed when the process terminates.
+//
+// [1] https://stackoverflow.com/questions/24376768/can-java-finalize-an-object-when-it-is-still-in-scope/24380219
+//
+
+
+/**
+ * Source of verifier indices implemented by the host app, e.g. fetching
+ * them from its backend, so envelopes can be verified without adding
+ * their index with `add_verifier_index` first.
+ *
+ * Called on the worker thread during verification; it may block.
+ */
+public interface VerifierIndexSource {
+    
+    /**
+     * The hex verifier index with `digest`, as returned by
+     * `export_verifier_index`, or `None` if unknown.
+     */
+    fun `fetch`(`digest`: Bytes32): kotlin.String?
+    
+    companion object
+}
+
+/**
+ * Source of verifier indices implemented by the host app, e.g. fetching
+ * them from its backend, so envelopes can be verified without adding
+ * their index with `add_verifier_index` first.
+ *
+ * Called on the worker thread during verification; it may block.
+ */
+open class VerifierIndexSourceImpl: Disposable, AutoCloseable, VerifierIndexSource {
+
+    constructor(pointer: Pointer) {
+        this.pointer = pointer
+        this.cleanable = UniffiLib.CLEANER.register(this, UniffiCleanAction(pointer))
+    }
+
+    /**
+     * This constructor can be used to instantiate a fake object. Only used for tests. Any
+     * attempt to actually use an object constructed this way will fail as there is no
+     * connected Rust object.
+     */
+    @Suppress("UNUSED_PARAMETER")
+    constructor(noPointer: NoPointer) {
+        this.pointer = null
+        this.cleanable = UniffiLib.CLEANER.register(this, UniffiCleanAction(pointer))
+    }
+
+    protected val pointer: Pointer?
+    protected val cleanable: UniffiCleaner.Cleanable
+
+    private val wasDestroyed = AtomicBoolean(false)
+    private val callCounter = AtomicLong(1)
+
+    override fun destroy() {
+        // Only allow a single call to this method.
+        // TODO: maybe we should log a warning if called more than once?
+        if (this.wasDestroyed.compareAndSet(false, true)) {
+            // This decrement always matches the initial count of 1 given at creation time.
+            if (this.callCounter.decrementAndGet() == 0L) {
+                cleanable.clean()
+            }
+        }
+    }
+
+    @Synchronized
+    override fun close() {
+        this.destroy()
+    }
+
+    internal inline fun <R> callWithPointer(block: (ptr: Pointer) -> R): R {
+        // Check and increment the call counter, to keep the object alive.
+        // This needs a compare-and-set retry loop in case of concurrent updates.
+        do {
+            val c = this.callCounter.get()
+            if (c == 0L) {
+                throw IllegalStateException("${this.javaClass.simpleName} object has already been destroyed")
+            }
+            if (c == Long.MAX_VALUE) {
+                throw IllegalStateException("${this.javaClass.simpleName} call counter would overflow")
+            }
+        } while (! this.callCounter.compareAndSet(c, c + 1L))
+        // Now we can safely do the method call without the pointer being freed concurrently.
+        try {
+            return block(this.uniffiClonePointer())
+        } finally {
+            // This decrement always matches the increment we performed above.
+            if (this.callCounter.decrementAndGet() == 0L) {
+                cleanable.clean()
+            }
+        }
+    }
+
+    // Use a static inner class instead of a closure so as not to accidentally
+    // capture `this` as part of the cleanable's action.
+    private class UniffiCleanAction(private val pointer: Pointer?) : Runnable {
+        override fun run() {
+            pointer?.let { ptr ->
+                uniffiRustCall { status ->
+                    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_free_verifierindexsource(ptr, status)
+                }
+            }
+        }
+    }
+
+    fun uniffiClonePointer(): Pointer {
+        return uniffiRustCall() { status ->
+            UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_clone_verifierindexsource(pointer!!, status)
+        }
+    }
+
+    
+    /**
+     * The hex verifier index with `digest`, as returned by
+     * `export_verifier_index`, or `None` if unknown.
+     */override fun `fetch`(`digest`: Bytes32): kotlin.String? {
+            return FfiConverterOptionalString.lift(
+    callWithPointer {
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_method_verifierindexsource_fetch(
+        it, FfiConverterTypeBytes32.lower(`digest`),_status)
+}
+    }
+    )
+    }
+    
+
+    
+
+    
+    
+    companion object
+    
+}
+
+
+// Put the implementation in an object so we don't pollute the top-level namespace
+internal object uniffiCallbackInterfaceVerifierIndexSource {
+    internal object `fetch`: UniffiCallbackInterfaceVerifierIndexSourceMethod0 {
+        override fun callback(`uniffiHandle`: Long,`digest`: RustBuffer.ByValue,`uniffiOutReturn`: RustBuffer,uniffiCallStatus: UniffiRustCallStatus,) {
+            val uniffiObj = FfiConverterTypeVerifierIndexSource.handleMap.get(uniffiHandle)
+            val makeCall = { ->
+                uniffiObj.`fetch`(
+                    FfiConverterTypeBytes32.lift(`digest`),
+                )
+            }
+            val writeReturn = { value: kotlin.String? -> uniffiOutReturn.setValue(FfiConverterOptionalString.lower(value)) }
+            uniffiTraitInterfaceCall(uniffiCallStatus, makeCall, writeReturn)
+        }
+    }
+
+    internal object uniffiFree: UniffiCallbackInterfaceFree {
+        override fun callback(handle: Long) {
+            FfiConverterTypeVerifierIndexSource.handleMap.remove(handle)
+        }
+    }
+
+    internal var vtable = UniffiVTableCallbackInterfaceVerifierIndexSource.UniffiByValue(
+        `fetch`,
+        uniffiFree,
+    )
+
+    // Registers the foreign callback with the Rust side.
+    // This method is generated for each callback interface.
+    internal fun register(lib: UniffiLib) {
+        lib.uniffi_kimchi_ffi_fn_init_callback_vtable_verifierindexsource(vtable)
+    }
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeVerifierIndexSource: FfiConverter<VerifierIndexSource, Pointer> {
+    internal val handleMap = UniffiHandleMap<VerifierIndexSource>()
+
+    override fun lower(value: VerifierIndexSource): Pointer {
+        return Pointer(handleMap.insert(value))
+    }
+
+    override fun lift(value: Pointer): VerifierIndexSource {
+        return VerifierIndexSourceImpl(value)
+    }
+
+    override fun read(buf: ByteBuffer): VerifierIndexSource {
+        // The Rust code always writes pointers as 8 bytes, and will
+        // fail to compile if they don't fit.
+        return lift(Pointer(buf.getLong()))
+    }
+
+    override fun allocationSize(value: VerifierIndexSource) = 8UL
+
+    override fun write(value: VerifierIndexSource, buf: ByteBuffer) {
+        // The Rust code always expects pointers written as 8 bytes,
+        // and will fail to compile if they don't fit.
+        buf.putLong(Pointer.nativeValue(lower(value)))
+    }
+}
+
+
+
+/**
+ * Static description of a built-in circuit, from `get_circuit_info`.
+ */
+data class CircuitInfo (
+    var `circuitId`: kotlin.String, 
+    /**
+     * Stable hash of the gates and wiring, as in proof envelopes.
+     */
+    var `circuitHash`: Bytes32, 
+    /**
+     * Rows, i.e. gates.
+     */
+    var `rows`: kotlin.ULong, 
+    /**
+     * Number of public input elements.
+     */
+    var `numPublicInputs`: kotlin.ULong, 
+    /**
+     * JSON `{ fields: [{ name, type }] }` naming the public inputs in order.
+     */
+    var `publicInputSchema`: kotlin.String, 
+    /**
+     * Smallest `srs_log2_size` for `init_prover` that fits the circuit.
+     */
+    var `minSrsLog2Size`: kotlin.UInt, 
+    var `provingTime`: ProvingTime, 
+    /**
+     * Whether the circuit fits the initialized prover's SRS; `None` before
+     * `init_prover`.
+     */
+    var `fitsSrs`: kotlin.Boolean?
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeCircuitInfo: FfiConverterRustBuffer<CircuitInfo> {
+    override fun read(buf: ByteBuffer): CircuitInfo {
+        return CircuitInfo(
+            FfiConverterString.read(buf),
+            FfiConverterTypeBytes32.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterUInt.read(buf),
+            FfiConverterTypeProvingTime.read(buf),
+            FfiConverterOptionalBoolean.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: CircuitInfo) = (
+            FfiConverterString.allocationSize(value.`circuitId`) +
+            FfiConverterTypeBytes32.allocationSize(value.`circuitHash`) +
+            FfiConverterULong.allocationSize(value.`rows`) +
+            FfiConverterULong.allocationSize(value.`numPublicInputs`) +
+            FfiConverterString.allocationSize(value.`publicInputSchema`) +
+            FfiConverterUInt.allocationSize(value.`minSrsLog2Size`) +
+            FfiConverterTypeProvingTime.allocationSize(value.`provingTime`) +
+            FfiConverterOptionalBoolean.allocationSize(value.`fitsSrs`)
+    )
+
+    override fun write(value: CircuitInfo, buf: ByteBuffer) {
+            FfiConverterString.write(value.`circuitId`, buf)
+            FfiConverterTypeBytes32.write(value.`circuitHash`, buf)
+            FfiConverterULong.write(value.`rows`, buf)
+            FfiConverterULong.write(value.`numPublicInputs`, buf)
+            FfiConverterString.write(value.`publicInputSchema`, buf)
+            FfiConverterUInt.write(value.`minSrsLog2Size`, buf)
+            FfiConverterTypeProvingTime.write(value.`provingTime`, buf)
+            FfiConverterOptionalBoolean.write(value.`fitsSrs`, buf)
+    }
+}
+
+
+
+/**
+ * A `COSE_Sign1`-wrapped envelope opened with `open_cose_envelope`.
+ */
+data class CoseEnvelope (
+    /**
+     * Hex-encoded MessagePack `ProofEnvelope`, as in `ProofResult::envelope`.
+     */
+    var `envelope`: kotlin.String, 
+    /**
+     * COSE algorithm identifier from the protected header.
+     */
+    var `alg`: kotlin.Int, 
+    /**
+     * Bytes the signature must verify over.
+     */
+    var `toBeSigned`: kotlin.ByteArray, 
+    /**
+     * Signature as carried in the structure.
+     */
+    var `signature`: kotlin.ByteArray
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeCoseEnvelope: FfiConverterRustBuffer<CoseEnvelope> {
+    override fun read(buf: ByteBuffer): CoseEnvelope {
+        return CoseEnvelope(
+            FfiConverterString.read(buf),
+            FfiConverterInt.read(buf),
+            FfiConverterByteArray.read(buf),
+            FfiConverterByteArray.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: CoseEnvelope) = (
+            FfiConverterString.allocationSize(value.`envelope`) +
+            FfiConverterInt.allocationSize(value.`alg`) +
+            FfiConverterByteArray.allocationSize(value.`toBeSigned`) +
+            FfiConverterByteArray.allocationSize(value.`signature`)
+    )
+
+    override fun write(value: CoseEnvelope, buf: ByteBuffer) {
+            FfiConverterString.write(value.`envelope`, buf)
+            FfiConverterInt.write(value.`alg`, buf)
+            FfiConverterByteArray.write(value.`toBeSigned`, buf)
+            FfiConverterByteArray.write(value.`signature`, buf)
+    }
+}
+
+
+
+/**
+ * Number of gates of one type, in `SetupInfo`.
+ */
+data class GateCount (
+    /**
+     * Kimchi gate type, e.g. `"Generic"` or `"RangeCheck0"`.
+     */
+    var `gateType`: kotlin.String, 
+    var `count`: kotlin.ULong
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeGateCount: FfiConverterRustBuffer<GateCount> {
+    override fun read(buf: ByteBuffer): GateCount {
+        return GateCount(
+            FfiConverterString.read(buf),
+            FfiConverterULong.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: GateCount) = (
+            FfiConverterString.allocationSize(value.`gateType`) +
+            FfiConverterULong.allocationSize(value.`count`)
+    )
+
+    override fun write(value: GateCount, buf: ByteBuffer) {
+            FfiConverterString.write(value.`gateType`, buf)
+            FfiConverterULong.write(value.`count`, buf)
+    }
+}
+
+
+
+/**
+ * Latency percentiles of the latest calls, in milliseconds.
+ */
+data class LatencyPercentiles (
+    /**
+     * Calls summarized: the latest 1024 at most.
+     */
+    var `samples`: kotlin.ULong, 
+    var `p50Ms`: kotlin.Double, 
+    var `p90Ms`: kotlin.Double, 
+    var `p99Ms`: kotlin.Double
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeLatencyPercentiles: FfiConverterRustBuffer<LatencyPercentiles> {
+    override fun read(buf: ByteBuffer): LatencyPercentiles {
+        return LatencyPercentiles(
+            FfiConverterULong.read(buf),
+            FfiConverterDouble.read(buf),
+            FfiConverterDouble.read(buf),
+            FfiConverterDouble.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: LatencyPercentiles) = (
+            FfiConverterULong.allocationSize(value.`samples`) +
+            FfiConverterDouble.allocationSize(value.`p50Ms`) +
+            FfiConverterDouble.allocationSize(value.`p90Ms`) +
+            FfiConverterDouble.allocationSize(value.`p99Ms`)
+    )
+
+    override fun write(value: LatencyPercentiles, buf: ByteBuffer) {
+            FfiConverterULong.write(value.`samples`, buf)
+            FfiConverterDouble.write(value.`p50Ms`, buf)
+            FfiConverterDouble.write(value.`p90Ms`, buf)
+            FfiConverterDouble.write(value.`p99Ms`, buf)
+    }
+}
+
+
+
+/**
+ * Rows of one lookup table, in `SetupInfo`.
+ */
+data class LookupTableSize (
+    var `tableId`: kotlin.Int, 
+    var `rows`: kotlin.ULong
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeLookupTableSize: FfiConverterRustBuffer<LookupTableSize> {
+    override fun read(buf: ByteBuffer): LookupTableSize {
+        return LookupTableSize(
+            FfiConverterInt.read(buf),
+            FfiConverterULong.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: LookupTableSize) = (
+            FfiConverterInt.allocationSize(value.`tableId`) +
+            FfiConverterULong.allocationSize(value.`rows`)
+    )
+
+    override fun write(value: LookupTableSize, buf: ByteBuffer) {
+            FfiConverterInt.write(value.`tableId`, buf)
+            FfiConverterULong.write(value.`rows`, buf)
+    }
+}
+
+
+
+/**
+ * One level of a Merkle path, from the leaf up.
+ */
+data class MerkleNode (
+    /**
+     * Sibling of the current node (hex-encoded field element).
+     */
+    var `siblingHex`: kotlin.String, 
+    /**
+     * Whether the sibling is the right child, i.e. the current node is on the left.
+     */
+    var `isRight`: kotlin.Boolean
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeMerkleNode: FfiConverterRustBuffer<MerkleNode> {
+    override fun read(buf: ByteBuffer): MerkleNode {
+        return MerkleNode(
+            FfiConverterString.read(buf),
+            FfiConverterBoolean.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: MerkleNode) = (
+            FfiConverterString.allocationSize(value.`siblingHex`) +
+            FfiConverterBoolean.allocationSize(value.`isRight`)
+    )
+
+    override fun write(value: MerkleNode, buf: ByteBuffer) {
+            FfiConverterString.write(value.`siblingHex`, buf)
+            FfiConverterBoolean.write(value.`isRight`, buf)
+    }
+}
+
+
+
+/**
+ * A leaf and its Merkle path, for `prove_merkle_membership`.
+ *
+ * The hex strings are wiped once parsed.
+ */
+data class MerklePathInput (
+    /**
+     * The leaf (hex-encoded field element).
+     */
+    var `leafHex`: kotlin.String, 
+    /**
+     * One node per level, from the leaf up; the length is the tree depth.
+     */
+    var `nodes`: List<MerkleNode>
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeMerklePathInput: FfiConverterRustBuffer<MerklePathInput> {
+    override fun read(buf: ByteBuffer): MerklePathInput {
+        return MerklePathInput(
+            FfiConverterString.read(buf),
+            FfiConverterSequenceTypeMerkleNode.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: MerklePathInput) = (
+            FfiConverterString.allocationSize(value.`leafHex`) +
+            FfiConverterSequenceTypeMerkleNode.allocationSize(value.`nodes`)
+    )
+
+    override fun write(value: MerklePathInput, buf: ByteBuffer) {
+            FfiConverterString.write(value.`leafHex`, buf)
+            FfiConverterSequenceTypeMerkleNode.write(value.`nodes`, buf)
+    }
+}
+
+
+
+/**
+ * Result of proof generation.
+ */
+data class ProofResult (
+    /**
+     * Handle to the proof stored in memory (for verification).
+     */
+    var `proofHandle`: kotlin.ULong, 
+    /**
+     * Serialized proof (hex-encoded, for transmission/storage).
+     */
+    var `proofBytes`: kotlin.String, 
+    /**
+     * Public inputs as hex-encoded field elements.
+     */
+    var `publicInputs`: List<kotlin.String>, 
+    /**
+     * Time taken in milliseconds.
+     */
+    var `generationTimeMs`: kotlin.ULong, 
+    /**
+     * Size of the proof in bytes.
+     */
+    var `proofSizeBytes`: kotlin.ULong, 
+    /**
+     * Hex-encoded MessagePack `ProofEnvelope` bundling the proof, public
+     * inputs, circuit id and verifier index digest.
+     */
+    var `envelope`: kotlin.String, 
+    /**
+     * Hex-encoded MessagePack `SignedEnvelope`: `envelope` signed with the
+     * key set with `set_envelope_signing_key`, if any.
+     */
+    var `signedEnvelope`: kotlin.String?
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeProofResult: FfiConverterRustBuffer<ProofResult> {
+    override fun read(buf: ByteBuffer): ProofResult {
+        return ProofResult(
+            FfiConverterULong.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterSequenceString.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterString.read(buf),
+            FfiConverterOptionalString.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: ProofResult) = (
+            FfiConverterULong.allocationSize(value.`proofHandle`) +
+            FfiConverterString.allocationSize(value.`proofBytes`) +
+            FfiConverterSequenceString.allocationSize(value.`publicInputs`) +
+            FfiConverterULong.allocationSize(value.`generationTimeMs`) +
+            FfiConverterULong.allocationSize(value.`proofSizeBytes`) +
+            FfiConverterString.allocationSize(value.`envelope`) +
+            FfiConverterOptionalString.allocationSize(value.`signedEnvelope`)
+    )
+
+    override fun write(value: ProofResult, buf: ByteBuffer) {
+            FfiConverterULong.write(value.`proofHandle`, buf)
+            FfiConverterString.write(value.`proofBytes`, buf)
+            FfiConverterSequenceString.write(value.`publicInputs`, buf)
+            FfiConverterULong.write(value.`generationTimeMs`, buf)
+            FfiConverterULong.write(value.`proofSizeBytes`, buf)
+            FfiConverterString.write(value.`envelope`, buf)
+            FfiConverterOptionalString.write(value.`signedEnvelope`, buf)
+    }
+}
+
+
+
+/**
+ * Size of the in-memory proof store.
+ */
+data class ProofStoreStats (
+    /**
+     * Number of stored proofs.
+     */
+    var `proofs`: kotlin.ULong, 
+    /**
+     * Number of distinct verifier indices they reference.
+     */
+    var `verifierIndices`: kotlin.ULong, 
+    /**
+     * Serialized size of everything stored.
+     */
+    var `totalBytes`: kotlin.ULong, 
+    /**
+     * Limit set with `set_proof_store_limit`, if any.
+     */
+    var `limitBytes`: kotlin.ULong?, 
+    /**
+     * Proof lifetime set with `init_prover`, if any.
+     */
+    var `ttlSecs`: kotlin.ULong?
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeProofStoreStats: FfiConverterRustBuffer<ProofStoreStats> {
+    override fun read(buf: ByteBuffer): ProofStoreStats {
+        return ProofStoreStats(
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterOptionalULong.read(buf),
+            FfiConverterOptionalULong.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: ProofStoreStats) = (
+            FfiConverterULong.allocationSize(value.`proofs`) +
+            FfiConverterULong.allocationSize(value.`verifierIndices`) +
+            FfiConverterULong.allocationSize(value.`totalBytes`) +
+            FfiConverterOptionalULong.allocationSize(value.`limitBytes`) +
+            FfiConverterOptionalULong.allocationSize(value.`ttlSecs`)
+    )
+
+    override fun write(value: ProofStoreStats, buf: ByteBuffer) {
+            FfiConverterULong.write(value.`proofs`, buf)
+            FfiConverterULong.write(value.`verifierIndices`, buf)
+            FfiConverterULong.write(value.`totalBytes`, buf)
+            FfiConverterOptionalULong.write(value.`limitBytes`, buf)
+            FfiConverterOptionalULong.write(value.`ttlSecs`, buf)
+    }
+}
+
+
+
+/**
+ * Prover health since the library was loaded or `reset_metrics`.
+ */
+data class ProverMetrics (
+    /**
+     * Proofs generated successfully.
+     */
+    var `proofsGenerated`: kotlin.ULong, 
+    /**
+     * Proof generations that failed.
+     */
+    var `proofFailures`: kotlin.ULong, 
+    /**
+     * Verifications run, whether the proof was valid or not.
+     */
+    var `verificationsRun`: kotlin.ULong, 
+    /**
+     * Verifications that failed with an error (not an invalid proof).
+     */
+    var `verificationFailures`: kotlin.ULong, 
+    /**
+     * Failures by `ProverError` code.
+     */
+    var `failuresByCode`: Map<kotlin.UInt, kotlin.ULong>, 
+    /**
+     * Latencies by metric name, e.g. `kimchi.prove.duration_ms` or
+     * `kimchi.ffi.verify_proof.duration_ms`.
+     */
+    var `latencies`: Map<kotlin.String, LatencyPercentiles>, 
+    /**
+     * Every counter reported to the metrics callback, by name.
+     */
+    var `counters`: Map<kotlin.String, kotlin.ULong>
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeProverMetrics: FfiConverterRustBuffer<ProverMetrics> {
+    override fun read(buf: ByteBuffer): ProverMetrics {
+        return ProverMetrics(
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterMapUIntULong.read(buf),
+            FfiConverterMapStringTypeLatencyPercentiles.read(buf),
+            FfiConverterMapStringULong.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: ProverMetrics) = (
+            FfiConverterULong.allocationSize(value.`proofsGenerated`) +
+            FfiConverterULong.allocationSize(value.`proofFailures`) +
+            FfiConverterULong.allocationSize(value.`verificationsRun`) +
+            FfiConverterULong.allocationSize(value.`verificationFailures`) +
+            FfiConverterMapUIntULong.allocationSize(value.`failuresByCode`) +
+            FfiConverterMapStringTypeLatencyPercentiles.allocationSize(value.`latencies`) +
+            FfiConverterMapStringULong.allocationSize(value.`counters`)
+    )
+
+    override fun write(value: ProverMetrics, buf: ByteBuffer) {
+            FfiConverterULong.write(value.`proofsGenerated`, buf)
+            FfiConverterULong.write(value.`proofFailures`, buf)
+            FfiConverterULong.write(value.`verificationsRun`, buf)
+            FfiConverterULong.write(value.`verificationFailures`, buf)
+            FfiConverterMapUIntULong.write(value.`failuresByCode`, buf)
+            FfiConverterMapStringTypeLatencyPercentiles.write(value.`latencies`, buf)
+            FfiConverterMapStringULong.write(value.`counters`, buf)
+    }
+}
+
+
+
+/**
+ * What setting up a circuit built, from `get_setup_info`.
+ */
+data class SetupInfo (
+    var `circuitId`: kotlin.String, 
+    /**
+     * Evaluation domain size.
+     */
+    var `domainSize`: kotlin.ULong, 
+    /**
+     * Largest domain the initialized prover's SRS allows.
+     */
+    var `maxDomainSize`: kotlin.ULong, 
+    /**
+     * Rows taken by gates.
+     */
+    var `rows`: kotlin.ULong, 
+    /**
+     * Rows the circuit needs: its gates, or its lookup tables if larger.
+     */
+    var `usedRows`: kotlin.ULong, 
+    /**
+     * Rows left before the domain doubles.
+     */
+    var `freeRows`: kotlin.ULong, 
+    /**
+     * Rows left before the circuit no longer fits the SRS.
+     */
+    var `rowsToLimit`: kotlin.ULong, 
+    var `numPublicInputs`: kotlin.ULong, 
+    /**
+     * Gates of each type, most frequent first.
+     */
+    var `gateCounts`: List<GateCount>, 
+    var `lookupTables`: List<LookupTableSize>
+) {
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeSetupInfo: FfiConverterRustBuffer<SetupInfo> {
+    override fun read(buf: ByteBuffer): SetupInfo {
+        return SetupInfo(
+            FfiConverterString.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterULong.read(buf),
+            FfiConverterSequenceTypeGateCount.read(buf),
+            FfiConverterSequenceTypeLookupTableSize.read(buf),
+        )
+    }
+
+    override fun allocationSize(value: SetupInfo) = (
+            FfiConverterString.allocationSize(value.`circuitId`) +
+            FfiConverterULong.allocationSize(value.`domainSize`) +
+            FfiConverterULong.allocationSize(value.`maxDomainSize`) +
+            FfiConverterULong.allocationSize(value.`rows`) +
+            FfiConverterULong.allocationSize(value.`usedRows`) +
+            FfiConverterULong.allocationSize(value.`freeRows`) +
+            FfiConverterULong.allocationSize(value.`rowsToLimit`) +
+            FfiConverterULong.allocationSize(value.`numPublicInputs`) +
+            FfiConverterSequenceTypeGateCount.allocationSize(value.`gateCounts`) +
+            FfiConverterSequenceTypeLookupTableSize.allocationSize(value.`lookupTables`)
+    )
+
+    override fun write(value: SetupInfo, buf: ByteBuffer) {
+            FfiConverterString.write(value.`circuitId`, buf)
+            FfiConverterULong.write(value.`domainSize`, buf)
+            FfiConverterULong.write(value.`maxDomainSize`, buf)
+            FfiConverterULong.write(value.`rows`, buf)
+            FfiConverterULong.write(value.`usedRows`, buf)
+            FfiConverterULong.write(value.`freeRows`, buf)
+            FfiConverterULong.write(value.`rowsToLimit`, buf)
+            FfiConverterULong.write(value.`numPublicInputs`, buf)
+            FfiConverterSequenceTypeGateCount.write(value.`gateCounts`, buf)
+            FfiConverterSequenceTypeLookupTableSize.write(value.`lookupTables`, buf)
+    }
+}
+
+
+
+/**
+ * Platform that issued a device attestation.
+ */
+
+enum class AttestationPlatform {
+    
+    /**
+     * Android Play Integrity API.
+     */
+    PLAY_INTEGRITY,
+    /**
+     * iOS App Attest.
+     */
+    APP_ATTEST;
+    companion object
+}
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeAttestationPlatform: FfiConverterRustBuffer<AttestationPlatform> {
+    override fun read(buf: ByteBuffer) = try {
+        AttestationPlatform.values()[buf.getInt() - 1]
+    } catch (e: IndexOutOfBoundsException) {
+        throw RuntimeException("invalid enum value, something is very wrong!!", e)
+    }
+
+    override fun allocationSize(value: AttestationPlatform) = 4UL
+
+    override fun write(value: AttestationPlatform, buf: ByteBuffer) {
+        buf.putInt(value.ordinal + 1)
+    }
+}
+
+
+
+
+
+
+
+/**
+ * Error types exposed via FFI.
+ */
+sealed class KimchiException: kotlin.Exception() {
+    
+    class SetupException(
+        
+        val v1: kotlin.String
+        ) : KimchiException() {
+        override val message
+            get() = "v1=${ v1 }"
+    }
+    
+    class ProvingException(
+        
+        val v1: kotlin.String
+        ) : KimchiException() {
+        override val message
+            get() = "v1=${ v1 }"
+    }
+    
+    class VerificationException(
+        
+        val v1: kotlin.String
+        ) : KimchiException() {
+        override val message
+            get() = "v1=${ v1 }"
+    }
+    
+    class SerializationException(
+        
+        val v1: kotlin.String
+        ) : KimchiException() {
+        override val message
+            get() = "v1=${ v1 }"
+    }
+    
+    class InvalidInput(
+        
+        val v1: kotlin.String
+        ) : KimchiException() {
+        override val message
+            get() = "v1=${ v1 }"
+    }
+    
+    class ProofNotFound(
+        
+        val v1: kotlin.String
+        ) : KimchiException() {
+        override val message
+            get() = "v1=${ v1 }"
+    }
+    
+    /**
+     * The proof outlived the TTL set with `init_prover` and was freed.
+     */
+    class ProofExpired(
+        
+        val v1: kotlin.String
+        ) : KimchiException() {
+        override val message
+            get() = "v1=${ v1 }"
+    }
+    
+    /**
+     * An error from the prover, with its stable code (see
+     * `ProverError::code`), typed context as strings and the messages of
+     * its underlying causes, outermost first.
+     */
+    class Prover(
+        
+        val `code`: kotlin.UInt, 
+        
+        val `message`: kotlin.String, 
+        
+        val `details`: Map<kotlin.String, kotlin.String>, 
+        
+        val `causes`: List<kotlin.String>
+        ) : KimchiException() {
+        override val message
+            get() = "code=${ `code` }, message=${ `message` }, details=${ `details` }, causes=${ `causes` }"
+    }
+    
+
+    companion object ErrorHandler : UniffiRustCallStatusErrorHandler<KimchiException> {
+        override fun lift(error_buf: RustBuffer.ByValue): KimchiException = FfiConverterTypeKimchiError.lift(error_buf)
+    }
+
+    
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeKimchiError : FfiConverterRustBuffer<KimchiException> {
+    override fun read(buf: ByteBuffer): KimchiException {
+        
+
+        return when(buf.getInt()) {
+            1 -> KimchiException.SetupException(
+                FfiConverterString.read(buf),
+                )
+            2 -> KimchiException.ProvingException(
+                FfiConverterString.read(buf),
+                )
+            3 -> KimchiException.VerificationException(
+                FfiConverterString.read(buf),
+                )
+            4 -> KimchiException.SerializationException(
+                FfiConverterString.read(buf),
+                )
+            5 -> KimchiException.InvalidInput(
+                FfiConverterString.read(buf),
+                )
+            6 -> KimchiException.ProofNotFound(
+                FfiConverterString.read(buf),
+                )
+            7 -> KimchiException.ProofExpired(
+                FfiConverterString.read(buf),
+                )
+            8 -> KimchiException.Prover(
+                FfiConverterUInt.read(buf),
+                FfiConverterString.read(buf),
+                FfiConverterMapStringString.read(buf),
+                FfiConverterSequenceString.read(buf),
+                )
+            else -> throw RuntimeException("invalid error enum value, something is very wrong!!")
+        }
+    }
+
+    override fun allocationSize(value: KimchiException): ULong {
+        return when(value) {
+            is KimchiException.SetupException -> (
+                // Add the size for the Int that specifies the variant plus the size needed for all fields
+                4UL
+                + FfiConverterString.allocationSize(value.v1)
+            )
+            is KimchiException.ProvingException -> (
+                // Add the size for the Int that specifies the variant plus the size needed for all fields
+                4UL
+                + FfiConverterString.allocationSize(value.v1)
+            )
+            is KimchiException.VerificationException -> (
+                // Add the size for the Int that specifies the variant plus the size needed for all fields
+                4UL
+                + FfiConverterString.allocationSize(value.v1)
+            )
+            is KimchiException.SerializationException -> (
+                // Add the size for the Int that specifies the variant plus the size needed for all fields
+                4UL
+                + FfiConverterString.allocationSize(value.v1)
+            )
+            is KimchiException.InvalidInput -> (
+                // Add the size for the Int that specifies the variant plus the size needed for all fields
+                4UL
+                + FfiConverterString.allocationSize(value.v1)
+            )
+            is KimchiException.ProofNotFound -> (
+                // Add the size for the Int that specifies the variant plus the size needed for all fields
+                4UL
+                + FfiConverterString.allocationSize(value.v1)
+            )
+            is KimchiException.ProofExpired -> (
+                // Add the size for the Int that specifies the variant plus the size needed for all fields
+                4UL
+                + FfiConverterString.allocationSize(value.v1)
+            )
+            is KimchiException.Prover -> (
+                // Add the size for the Int that specifies the variant plus the size needed for all fields
+                4UL
+                + FfiConverterUInt.allocationSize(value.`code`)
+                + FfiConverterString.allocationSize(value.`message`)
+                + FfiConverterMapStringString.allocationSize(value.`details`)
+                + FfiConverterSequenceString.allocationSize(value.`causes`)
+            )
+        }
+    }
+
+    override fun write(value: KimchiException, buf: ByteBuffer) {
+        when(value) {
             is KimchiException.SetupException -> {
                 buf.putInt(1)
-                FfiConverterString.write(value.v1, buf)
+                FfiConverterString.write(value.v1, buf)
+                Unit
+            }
+            is KimchiException.ProvingException -> {
+                buf.putInt(2)
+                FfiConverterString.write(value.v1, buf)
+                Unit
+            }
+            is KimchiException.VerificationException -> {
+                buf.putInt(3)
+                FfiConverterString.write(value.v1, buf)
+                Unit
+            }
+            is KimchiException.SerializationException -> {
+                buf.putInt(4)
+                FfiConverterString.write(value.v1, buf)
+                Unit
+            }
+            is KimchiException.InvalidInput -> {
+                buf.putInt(5)
+                FfiConverterString.write(value.v1, buf)
+                Unit
+            }
+            is KimchiException.ProofNotFound -> {
+                buf.putInt(6)
+                FfiConverterString.write(value.v1, buf)
+                Unit
+            }
+            is KimchiException.ProofExpired -> {
+                buf.putInt(7)
+                FfiConverterString.write(value.v1, buf)
+                Unit
+            }
+            is KimchiException.Prover -> {
+                buf.putInt(8)
+                FfiConverterUInt.write(value.`code`, buf)
+                FfiConverterString.write(value.`message`, buf)
+                FfiConverterMapStringString.write(value.`details`, buf)
+                FfiConverterSequenceString.write(value.`causes`, buf)
+                Unit
+            }
+        }.let { /* this makes the `when` an expression, which ensures it is exhaustive */ }
+    }
+
+}
+
+
+
+/**
+ * Rough proving time of a circuit on a mid-range phone.
+ */
+
+enum class ProvingTime {
+    
+    /**
+     * About a second or less.
+     */
+    FAST,
+    /**
+     * A few seconds.
+     */
+    MODERATE,
+    /**
+     * Ten seconds or more.
+     */
+    SLOW;
+    companion object
+}
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeProvingTime: FfiConverterRustBuffer<ProvingTime> {
+    override fun read(buf: ByteBuffer) = try {
+        ProvingTime.values()[buf.getInt() - 1]
+    } catch (e: IndexOutOfBoundsException) {
+        throw RuntimeException("invalid enum value, something is very wrong!!", e)
+    }
+
+    override fun allocationSize(value: ProvingTime) = 4UL
+
+    override fun write(value: ProvingTime, buf: ByteBuffer) {
+        buf.putInt(value.ordinal + 1)
+    }
+}
+
+
+
+
+
+/**
+ * Where `init_prover` gets the SRS from when it is not cached.
+ */
+sealed class SrsSource {
+    
+    /**
+     * Create it on the device. The default.
+     */
+    object Generate : SrsSource()
+    
+    
+    /**
+     * Load `srs-{n}.bin` from a directory of bundled assets.
+     */
+    data class Bundled(
+        val `dir`: kotlin.String) : SrsSource() {
+        companion object
+    }
+    
+    /**
+     * Download from `url_template`, with `{log2}` standing for log2 of the
+     * size, keeping assets in `dir`. Only sizes in `pins`, mapped to the
+     * SHA-256 digest of their asset, are accepted; updating a pin fetches
+     * the new asset and removes the old one.
+     */
+    data class Download(
+        val `urlTemplate`: kotlin.String, 
+        val `dir`: kotlin.String, 
+        val `pins`: Map<kotlin.UInt, Bytes32>) : SrsSource() {
+        companion object
+    }
+    
+
+    
+    companion object
+}
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeSrsSource : FfiConverterRustBuffer<SrsSource>{
+    override fun read(buf: ByteBuffer): SrsSource {
+        return when(buf.getInt()) {
+            1 -> SrsSource.Generate
+            2 -> SrsSource.Bundled(
+                FfiConverterString.read(buf),
+                )
+            3 -> SrsSource.Download(
+                FfiConverterString.read(buf),
+                FfiConverterString.read(buf),
+                FfiConverterMapUIntTypeBytes32.read(buf),
+                )
+            else -> throw RuntimeException("invalid enum value, something is very wrong!!")
+        }
+    }
+
+    override fun allocationSize(value: SrsSource) = when(value) {
+        is SrsSource.Generate -> {
+            // Add the size for the Int that specifies the variant plus the size needed for all fields
+            (
+                4UL
+            )
+        }
+        is SrsSource.Bundled -> {
+            // Add the size for the Int that specifies the variant plus the size needed for all fields
+            (
+                4UL
+                + FfiConverterString.allocationSize(value.`dir`)
+            )
+        }
+        is SrsSource.Download -> {
+            // Add the size for the Int that specifies the variant plus the size needed for all fields
+            (
+                4UL
+                + FfiConverterString.allocationSize(value.`urlTemplate`)
+                + FfiConverterString.allocationSize(value.`dir`)
+                + FfiConverterMapUIntTypeBytes32.allocationSize(value.`pins`)
+            )
+        }
+    }
+
+    override fun write(value: SrsSource, buf: ByteBuffer) {
+        when(value) {
+            is SrsSource.Generate -> {
+                buf.putInt(1)
                 Unit
             }
-            is KimchiException.ProvingException -> {
+            is SrsSource.Bundled -> {
                 buf.putInt(2)
-                FfiConverterString.write(value.v1, buf)
+                FfiConverterString.write(value.`dir`, buf)
                 Unit
             }
-            is KimchiException.VerificationException -> {
+            is SrsSource.Download -> {
                 buf.putInt(3)
-                FfiConverterString.write(value.v1, buf)
-                Unit
-            }
-            is KimchiException.SerializationException -> {
-                buf.putInt(4)
-                FfiConverterString.write(value.v1, buf)
-                Unit
-            }
-            is KimchiException.InvalidInput -> {
-                buf.putInt(5)
-                FfiConverterString.write(value.v1, buf)
-                Unit
-            }
-            is KimchiException.ProofNotFound -> {
-                buf.putInt(6)
-                FfiConverterString.write(value.v1, buf)
+                FfiConverterString.write(value.`urlTemplate`, buf)
+                FfiConverterString.write(value.`dir`, buf)
+                FfiConverterMapUIntTypeBytes32.write(value.`pins`, buf)
                 Unit
             }
         }.let { /* this makes the `when` an expression, which ensures it is exhaustive */ }
     }
+}
+
+
+
+
+
+/**
+ * Comparison proved by `prove_comparison`.
+ */
+
+enum class ThresholdOperator {
+    
+    /**
+     * value < threshold
+     */
+    LT,
+    /**
+     * value <= threshold
+     */
+    LE,
+    /**
+     * value > threshold
+     */
+    GT,
+    /**
+     * value >= threshold
+     */
+    GE,
+    /**
+     * value == threshold
+     */
+    EQ;
+    companion object
+}
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeThresholdOperator: FfiConverterRustBuffer<ThresholdOperator> {
+    override fun read(buf: ByteBuffer) = try {
+        ThresholdOperator.values()[buf.getInt() - 1]
+    } catch (e: IndexOutOfBoundsException) {
+        throw RuntimeException("invalid enum value, something is very wrong!!", e)
+    }
+
+    override fun allocationSize(value: ThresholdOperator) = 4UL
+
+    override fun write(value: ThresholdOperator, buf: ByteBuffer) {
+        buf.putInt(value.ordinal + 1)
+    }
+}
+
+
+
+
+
+/**
+ * Values compared by `prove_wide_comparison`.
+ */
+
+enum class ValueWidth {
+    
+    /**
+     * Integers below 2^64.
+     */
+    U64,
+    /**
+     * Integers below 2^128, e.g. token balances.
+     */
+    U128,
+    /**
+     * Any field element.
+     */
+    FIELD;
+    companion object
+}
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterTypeValueWidth: FfiConverterRustBuffer<ValueWidth> {
+    override fun read(buf: ByteBuffer) = try {
+        ValueWidth.values()[buf.getInt() - 1]
+    } catch (e: IndexOutOfBoundsException) {
+        throw RuntimeException("invalid enum value, something is very wrong!!", e)
+    }
+
+    override fun allocationSize(value: ValueWidth) = 4UL
+
+    override fun write(value: ValueWidth, buf: ByteBuffer) {
+        buf.putInt(value.ordinal + 1)
+    }
+}
+
+
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterOptionalUInt: FfiConverterRustBuffer<kotlin.UInt?> {
+    override fun read(buf: ByteBuffer): kotlin.UInt? {
+        if (buf.get().toInt() == 0) {
+            return null
+        }
+        return FfiConverterUInt.read(buf)
+    }
+
+    override fun allocationSize(value: kotlin.UInt?): ULong {
+        if (value == null) {
+            return 1UL
+        } else {
+            return 1UL + FfiConverterUInt.allocationSize(value)
+        }
+    }
+
+    override fun write(value: kotlin.UInt?, buf: ByteBuffer) {
+        if (value == null) {
+            buf.put(0)
+        } else {
+            buf.put(1)
+            FfiConverterUInt.write(value, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterOptionalULong: FfiConverterRustBuffer<kotlin.ULong?> {
+    override fun read(buf: ByteBuffer): kotlin.ULong? {
+        if (buf.get().toInt() == 0) {
+            return null
+        }
+        return FfiConverterULong.read(buf)
+    }
+
+    override fun allocationSize(value: kotlin.ULong?): ULong {
+        if (value == null) {
+            return 1UL
+        } else {
+            return 1UL + FfiConverterULong.allocationSize(value)
+        }
+    }
+
+    override fun write(value: kotlin.ULong?, buf: ByteBuffer) {
+        if (value == null) {
+            buf.put(0)
+        } else {
+            buf.put(1)
+            FfiConverterULong.write(value, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterOptionalBoolean: FfiConverterRustBuffer<kotlin.Boolean?> {
+    override fun read(buf: ByteBuffer): kotlin.Boolean? {
+        if (buf.get().toInt() == 0) {
+            return null
+        }
+        return FfiConverterBoolean.read(buf)
+    }
+
+    override fun allocationSize(value: kotlin.Boolean?): ULong {
+        if (value == null) {
+            return 1UL
+        } else {
+            return 1UL + FfiConverterBoolean.allocationSize(value)
+        }
+    }
+
+    override fun write(value: kotlin.Boolean?, buf: ByteBuffer) {
+        if (value == null) {
+            buf.put(0)
+        } else {
+            buf.put(1)
+            FfiConverterBoolean.write(value, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterOptionalString: FfiConverterRustBuffer<kotlin.String?> {
+    override fun read(buf: ByteBuffer): kotlin.String? {
+        if (buf.get().toInt() == 0) {
+            return null
+        }
+        return FfiConverterString.read(buf)
+    }
+
+    override fun allocationSize(value: kotlin.String?): ULong {
+        if (value == null) {
+            return 1UL
+        } else {
+            return 1UL + FfiConverterString.allocationSize(value)
+        }
+    }
+
+    override fun write(value: kotlin.String?, buf: ByteBuffer) {
+        if (value == null) {
+            buf.put(0)
+        } else {
+            buf.put(1)
+            FfiConverterString.write(value, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterOptionalByteArray: FfiConverterRustBuffer<kotlin.ByteArray?> {
+    override fun read(buf: ByteBuffer): kotlin.ByteArray? {
+        if (buf.get().toInt() == 0) {
+            return null
+        }
+        return FfiConverterByteArray.read(buf)
+    }
+
+    override fun allocationSize(value: kotlin.ByteArray?): ULong {
+        if (value == null) {
+            return 1UL
+        } else {
+            return 1UL + FfiConverterByteArray.allocationSize(value)
+        }
+    }
 
+    override fun write(value: kotlin.ByteArray?, buf: ByteBuffer) {
+        if (value == null) {
+            buf.put(0)
+        } else {
+            buf.put(1)
+            FfiConverterByteArray.write(value, buf)
+        }
+    }
 }
 
 
@@ -1534,65 +3994,553 @@ public object FfiConverterTypeKimchiError : FfiConverterRustBuffer<KimchiExcepti
 /**
  * @suppress
  */
-public object FfiConverterOptionalUInt: FfiConverterRustBuffer<kotlin.UInt?> {
-    override fun read(buf: ByteBuffer): kotlin.UInt? {
+public object FfiConverterOptionalTypeSrsFetcher: FfiConverterRustBuffer<SrsFetcher?> {
+    override fun read(buf: ByteBuffer): SrsFetcher? {
         if (buf.get().toInt() == 0) {
             return null
         }
-        return FfiConverterUInt.read(buf)
+        return FfiConverterTypeSrsFetcher.read(buf)
     }
 
-    override fun allocationSize(value: kotlin.UInt?): ULong {
+    override fun allocationSize(value: SrsFetcher?): ULong {
         if (value == null) {
             return 1UL
         } else {
-            return 1UL + FfiConverterUInt.allocationSize(value)
+            return 1UL + FfiConverterTypeSrsFetcher.allocationSize(value)
+        }
+    }
+
+    override fun write(value: SrsFetcher?, buf: ByteBuffer) {
+        if (value == null) {
+            buf.put(0)
+        } else {
+            buf.put(1)
+            FfiConverterTypeSrsFetcher.write(value, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterSequenceString: FfiConverterRustBuffer<List<kotlin.String>> {
+    override fun read(buf: ByteBuffer): List<kotlin.String> {
+        val len = buf.getInt()
+        return List<kotlin.String>(len) {
+            FfiConverterString.read(buf)
+        }
+    }
+
+    override fun allocationSize(value: List<kotlin.String>): ULong {
+        val sizeForLength = 4UL
+        val sizeForItems = value.map { FfiConverterString.allocationSize(it) }.sum()
+        return sizeForLength + sizeForItems
+    }
+
+    override fun write(value: List<kotlin.String>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        value.iterator().forEach {
+            FfiConverterString.write(it, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterSequenceTypeGateCount: FfiConverterRustBuffer<List<GateCount>> {
+    override fun read(buf: ByteBuffer): List<GateCount> {
+        val len = buf.getInt()
+        return List<GateCount>(len) {
+            FfiConverterTypeGateCount.read(buf)
+        }
+    }
+
+    override fun allocationSize(value: List<GateCount>): ULong {
+        val sizeForLength = 4UL
+        val sizeForItems = value.map { FfiConverterTypeGateCount.allocationSize(it) }.sum()
+        return sizeForLength + sizeForItems
+    }
+
+    override fun write(value: List<GateCount>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        value.iterator().forEach {
+            FfiConverterTypeGateCount.write(it, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterSequenceTypeLookupTableSize: FfiConverterRustBuffer<List<LookupTableSize>> {
+    override fun read(buf: ByteBuffer): List<LookupTableSize> {
+        val len = buf.getInt()
+        return List<LookupTableSize>(len) {
+            FfiConverterTypeLookupTableSize.read(buf)
+        }
+    }
+
+    override fun allocationSize(value: List<LookupTableSize>): ULong {
+        val sizeForLength = 4UL
+        val sizeForItems = value.map { FfiConverterTypeLookupTableSize.allocationSize(it) }.sum()
+        return sizeForLength + sizeForItems
+    }
+
+    override fun write(value: List<LookupTableSize>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        value.iterator().forEach {
+            FfiConverterTypeLookupTableSize.write(it, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterSequenceTypeMerkleNode: FfiConverterRustBuffer<List<MerkleNode>> {
+    override fun read(buf: ByteBuffer): List<MerkleNode> {
+        val len = buf.getInt()
+        return List<MerkleNode>(len) {
+            FfiConverterTypeMerkleNode.read(buf)
+        }
+    }
+
+    override fun allocationSize(value: List<MerkleNode>): ULong {
+        val sizeForLength = 4UL
+        val sizeForItems = value.map { FfiConverterTypeMerkleNode.allocationSize(it) }.sum()
+        return sizeForLength + sizeForItems
+    }
+
+    override fun write(value: List<MerkleNode>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        value.iterator().forEach {
+            FfiConverterTypeMerkleNode.write(it, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterMapUIntULong: FfiConverterRustBuffer<Map<kotlin.UInt, kotlin.ULong>> {
+    override fun read(buf: ByteBuffer): Map<kotlin.UInt, kotlin.ULong> {
+        val len = buf.getInt()
+        return buildMap<kotlin.UInt, kotlin.ULong>(len) {
+            repeat(len) {
+                val k = FfiConverterUInt.read(buf)
+                val v = FfiConverterULong.read(buf)
+                this[k] = v
+            }
+        }
+    }
+
+    override fun allocationSize(value: Map<kotlin.UInt, kotlin.ULong>): ULong {
+        val spaceForMapSize = 4UL
+        val spaceForChildren = value.map { (k, v) ->
+            FfiConverterUInt.allocationSize(k) +
+            FfiConverterULong.allocationSize(v)
+        }.sum()
+        return spaceForMapSize + spaceForChildren
+    }
+
+    override fun write(value: Map<kotlin.UInt, kotlin.ULong>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        // The parens on `(k, v)` here ensure we're calling the right method,
+        // which is important for compatibility with older android devices.
+        // Ref https://blog.danlew.net/2017/03/16/kotlin-puzzler-whose-line-is-it-anyways/
+        value.forEach { (k, v) ->
+            FfiConverterUInt.write(k, buf)
+            FfiConverterULong.write(v, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterMapUIntTypeBytes32: FfiConverterRustBuffer<Map<kotlin.UInt, Bytes32>> {
+    override fun read(buf: ByteBuffer): Map<kotlin.UInt, Bytes32> {
+        val len = buf.getInt()
+        return buildMap<kotlin.UInt, Bytes32>(len) {
+            repeat(len) {
+                val k = FfiConverterUInt.read(buf)
+                val v = FfiConverterTypeBytes32.read(buf)
+                this[k] = v
+            }
+        }
+    }
+
+    override fun allocationSize(value: Map<kotlin.UInt, Bytes32>): ULong {
+        val spaceForMapSize = 4UL
+        val spaceForChildren = value.map { (k, v) ->
+            FfiConverterUInt.allocationSize(k) +
+            FfiConverterTypeBytes32.allocationSize(v)
+        }.sum()
+        return spaceForMapSize + spaceForChildren
+    }
+
+    override fun write(value: Map<kotlin.UInt, Bytes32>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        // The parens on `(k, v)` here ensure we're calling the right method,
+        // which is important for compatibility with older android devices.
+        // Ref https://blog.danlew.net/2017/03/16/kotlin-puzzler-whose-line-is-it-anyways/
+        value.forEach { (k, v) ->
+            FfiConverterUInt.write(k, buf)
+            FfiConverterTypeBytes32.write(v, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterMapStringULong: FfiConverterRustBuffer<Map<kotlin.String, kotlin.ULong>> {
+    override fun read(buf: ByteBuffer): Map<kotlin.String, kotlin.ULong> {
+        val len = buf.getInt()
+        return buildMap<kotlin.String, kotlin.ULong>(len) {
+            repeat(len) {
+                val k = FfiConverterString.read(buf)
+                val v = FfiConverterULong.read(buf)
+                this[k] = v
+            }
+        }
+    }
+
+    override fun allocationSize(value: Map<kotlin.String, kotlin.ULong>): ULong {
+        val spaceForMapSize = 4UL
+        val spaceForChildren = value.map { (k, v) ->
+            FfiConverterString.allocationSize(k) +
+            FfiConverterULong.allocationSize(v)
+        }.sum()
+        return spaceForMapSize + spaceForChildren
+    }
+
+    override fun write(value: Map<kotlin.String, kotlin.ULong>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        // The parens on `(k, v)` here ensure we're calling the right method,
+        // which is important for compatibility with older android devices.
+        // Ref https://blog.danlew.net/2017/03/16/kotlin-puzzler-whose-line-is-it-anyways/
+        value.forEach { (k, v) ->
+            FfiConverterString.write(k, buf)
+            FfiConverterULong.write(v, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterMapStringString: FfiConverterRustBuffer<Map<kotlin.String, kotlin.String>> {
+    override fun read(buf: ByteBuffer): Map<kotlin.String, kotlin.String> {
+        val len = buf.getInt()
+        return buildMap<kotlin.String, kotlin.String>(len) {
+            repeat(len) {
+                val k = FfiConverterString.read(buf)
+                val v = FfiConverterString.read(buf)
+                this[k] = v
+            }
+        }
+    }
+
+    override fun allocationSize(value: Map<kotlin.String, kotlin.String>): ULong {
+        val spaceForMapSize = 4UL
+        val spaceForChildren = value.map { (k, v) ->
+            FfiConverterString.allocationSize(k) +
+            FfiConverterString.allocationSize(v)
+        }.sum()
+        return spaceForMapSize + spaceForChildren
+    }
+
+    override fun write(value: Map<kotlin.String, kotlin.String>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        // The parens on `(k, v)` here ensure we're calling the right method,
+        // which is important for compatibility with older android devices.
+        // Ref https://blog.danlew.net/2017/03/16/kotlin-puzzler-whose-line-is-it-anyways/
+        value.forEach { (k, v) ->
+            FfiConverterString.write(k, buf)
+            FfiConverterString.write(v, buf)
+        }
+    }
+}
+
+
+
+
+/**
+ * @suppress
+ */
+public object FfiConverterMapStringTypeLatencyPercentiles: FfiConverterRustBuffer<Map<kotlin.String, LatencyPercentiles>> {
+    override fun read(buf: ByteBuffer): Map<kotlin.String, LatencyPercentiles> {
+        val len = buf.getInt()
+        return buildMap<kotlin.String, LatencyPercentiles>(len) {
+            repeat(len) {
+                val k = FfiConverterString.read(buf)
+                val v = FfiConverterTypeLatencyPercentiles.read(buf)
+                this[k] = v
+            }
+        }
+    }
+
+    override fun allocationSize(value: Map<kotlin.String, LatencyPercentiles>): ULong {
+        val spaceForMapSize = 4UL
+        val spaceForChildren = value.map { (k, v) ->
+            FfiConverterString.allocationSize(k) +
+            FfiConverterTypeLatencyPercentiles.allocationSize(v)
+        }.sum()
+        return spaceForMapSize + spaceForChildren
+    }
+
+    override fun write(value: Map<kotlin.String, LatencyPercentiles>, buf: ByteBuffer) {
+        buf.putInt(value.size)
+        // The parens on `(k, v)` here ensure we're calling the right method,
+        // which is important for compatibility with older android devices.
+        // Ref https://blog.danlew.net/2017/03/16/kotlin-puzzler-whose-line-is-it-anyways/
+        value.forEach { (k, v) ->
+            FfiConverterString.write(k, buf)
+            FfiConverterTypeLatencyPercentiles.write(v, buf)
         }
     }
+}
+
+
+
+/**
+ * Typealias from the type name used in the UDL file to the builtin type.  This
+ * is needed because the UDL type name is used in function/method signatures.
+ * It's also what we have an external type that references a custom type.
+ */
+public typealias Bytes32 = kotlin.String
+public typealias FfiConverterTypeBytes32 = FfiConverterString
+
+
+
+
+
+
+
+
+        /**
+         * Trust a verifier index like `add_verifier_index`, as the index of the
+         * built-in circuit `circuit_id` (e.g. `"timestamped/threshold_lt"`).
+         *
+         * `verify_envelope_with_max_age` and `verify_envelope_with_attestation`
+         * need the index bound to its circuit this way, and refuse envelopes that
+         * name another circuit.
+         *
+         * # Returns
+         * Its digest, which envelopes made for it carry as `verifier_index_digest`.
+         */
+    @Throws(KimchiException::class) fun `addCircuitVerifierIndex`(`circuitId`: kotlin.String, `verifierIndex`: kotlin.String): Bytes32 {
+            return FfiConverterTypeBytes32.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_add_circuit_verifier_index(
+        FfiConverterString.lower(`circuitId`),FfiConverterString.lower(`verifierIndex`),_status)
+}
+    )
+    }
+    
+
+        /**
+         * Trust a verifier index, as returned by `export_verifier_index`.
+         *
+         * # Returns
+         * Its digest, which envelopes made for it carry as `verifier_index_digest`.
+         */
+    @Throws(KimchiException::class) fun `addVerifierIndex`(`verifierIndex`: kotlin.String): Bytes32 {
+            return FfiConverterTypeBytes32.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_add_verifier_index(
+        FfiConverterString.lower(`verifierIndex`),_status)
+}
+    )
+    }
+    
+
+        /**
+         * Check the signature of a hex `SignedEnvelope` (as in
+         * `ProofResult::signed_envelope`), and that it is by `expected_signer` if
+         * given, without verifying the proof. Fails with `SignatureInvalid`
+         * (code 404) otherwise.
+         *
+         * Returns the hex envelope, for `verify_envelope`.
+         */
+    @Throws(KimchiException::class) fun `checkEnvelopeSignature`(`signedEnvelope`: kotlin.String, `expectedSigner`: kotlin.String?): kotlin.String {
+            return FfiConverterString.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_check_envelope_signature(
+        FfiConverterString.lower(`signedEnvelope`),FfiConverterOptionalString.lower(`expectedSigner`),_status)
+}
+    )
+    }
+    
+
+        /**
+         * Bytes to sign, with the platform key store, before calling
+         * `envelope_to_cose`.
+         *
+         * # Arguments
+         * * `envelope` - Hex-encoded MessagePack envelope
+         * * `alg` - COSE algorithm identifier, e.g. -7 for ES256
+         */
+    @Throws(KimchiException::class) fun `envelopeCoseToBeSigned`(`envelope`: kotlin.String, `alg`: kotlin.Int): kotlin.ByteArray {
+            return FfiConverterByteArray.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_envelope_cose_to_be_signed(
+        FfiConverterString.lower(`envelope`),FfiConverterInt.lower(`alg`),_status)
+}
+    )
+    }
+    
 
-    override fun write(value: kotlin.UInt?, buf: ByteBuffer) {
-        if (value == null) {
-            buf.put(0)
-        } else {
-            buf.put(1)
-            FfiConverterUInt.write(value, buf)
-        }
-    }
+        /**
+         * Convert a CBOR envelope back to hex MessagePack.
+         */
+    @Throws(KimchiException::class) fun `envelopeFromCbor`(`cbor`: kotlin.ByteArray): kotlin.String {
+            return FfiConverterString.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_envelope_from_cbor(
+        FfiConverterByteArray.lower(`cbor`),_status)
 }
+    )
+    }
+    
 
+        /**
+         * Convert a hex MessagePack envelope to the compact CBOR transport
+         * encoding, e.g. for a QR code or NFC payload.
+         */
+    @Throws(KimchiException::class) fun `envelopeToCbor`(`envelope`: kotlin.String): kotlin.ByteArray {
+            return FfiConverterByteArray.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_envelope_to_cbor(
+        FfiConverterString.lower(`envelope`),_status)
+}
+    )
+    }
+    
 
+        /**
+         * Wrap an envelope and its signature in a tagged `COSE_Sign1` structure.
+         */
+    @Throws(KimchiException::class) fun `envelopeToCose`(`envelope`: kotlin.String, `alg`: kotlin.Int, `signature`: kotlin.ByteArray): kotlin.ByteArray {
+            return FfiConverterByteArray.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_envelope_to_cose(
+        FfiConverterString.lower(`envelope`),FfiConverterInt.lower(`alg`),FfiConverterByteArray.lower(`signature`),_status)
+}
+    )
+    }
+    
 
-
-/**
- * @suppress
- */
-public object FfiConverterSequenceString: FfiConverterRustBuffer<List<kotlin.String>> {
-    override fun read(buf: ByteBuffer): List<kotlin.String> {
-        val len = buf.getInt()
-        return List<kotlin.String>(len) {
-            FfiConverterString.read(buf)
-        }
+        /**
+         * Dump a built-in circuit's gates, wiring and region labels as JSON, for
+         * review by auditors or alternate verifiers. The format is documented in
+         * `kimchi_prover::circuits::gates_json`.
+         *
+         * # Arguments
+         * * `circuit_id` - Circuit id as in proof envelopes, e.g. `"threshold"`
+         */
+    @Throws(KimchiException::class) fun `exportCircuitGates`(`circuitId`: kotlin.String): kotlin.String {
+            return FfiConverterString.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_export_circuit_gates(
+        FfiConverterString.lower(`circuitId`),_status)
+}
+    )
     }
+    
 
-    override fun allocationSize(value: List<kotlin.String>): ULong {
-        val sizeForLength = 4UL
-        val sizeForItems = value.map { FfiConverterString.allocationSize(it) }.sum()
-        return sizeForLength + sizeForItems
+        /**
+         * Export the verifier index for a stored proof as an o1js verification key.
+         *
+         * # Arguments
+         * * `proof_handle` - Handle to a stored proof
+         *
+         * # Returns
+         * JSON `{ data, hash }`, where `hash` is the key's Poseidon hash as a
+         * decimal string
+         */
+    @Throws(KimchiException::class) fun `exportMinaVerificationKey`(`proofHandle`: kotlin.ULong): kotlin.String {
+            return FfiConverterString.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_export_mina_verification_key(
+        FfiConverterULong.lower(`proofHandle`),_status)
+}
+    )
     }
+    
 
-    override fun write(value: List<kotlin.String>, buf: ByteBuffer) {
-        buf.putInt(value.size)
-        value.iterator().forEach {
-            FfiConverterString.write(it, buf)
-        }
+        /**
+         * Export a stored proof as raw MessagePack bytes.
+         *
+         * The same bytes as `ProofResult::proof_bytes` without the hex encoding,
+         * for callers that write proofs to files or sockets and would otherwise
+         * decode the hex string again.
+         */
+    @Throws(KimchiException::class) fun `exportProof`(`proofHandle`: kotlin.ULong): kotlin.ByteArray {
+            return FfiConverterByteArray.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_export_proof(
+        FfiConverterULong.lower(`proofHandle`),_status)
+}
+    )
     }
+    
+
+        /**
+         * Export the public inputs of a stored proof by name, decoded by the
+         * circuit's public-input schema.
+         *
+         * # Arguments
+         * * `proof_handle` - Handle to a stored proof
+         *
+         * # Returns
+         * JSON `{ circuit_id, inputs: [{ name, type, value }] }` with the inputs in
+         * circuit order, e.g. `{ "name": "is_valid", "type": "bool", "value": true }`.
+         * Field elements are hex strings and bytes are arrays of numbers.
+         */
+    @Throws(KimchiException::class) fun `exportPublicInputs`(`proofHandle`: kotlin.ULong): kotlin.String {
+            return FfiConverterString.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_export_public_inputs(
+        FfiConverterULong.lower(`proofHandle`),_status)
 }
+    )
+    }
+    
+
         /**
          * Serialize the verifier index for a stored proof.
          *
-         * This returns the verifier index in a format suitable for the WASM verifier.
-         * The SRS is NOT included - the WASM verifier will regenerate it based on
-         * the srs_log2_size from get_srs_log2_size().
+         * This returns the verifier index in the format expected by the `kimchi-wasm`
+         * verifier (`verify_kimchi_proof()`, `verify_proof_envelope()`). The SRS is
+         * NOT included - the WASM verifier regenerates it from the srs_log2_size
+         * returned by get_srs_log2_size() or carried in the proof envelope.
          *
          * # Arguments
          * * `proof_handle` - Handle to a stored proof
@@ -1610,6 +4558,28 @@ public object FfiConverterSequenceString: FfiConverterRustBuffer<List<kotlin.Str
     }
     
 
+        /**
+         * Export a stored proof as the proof-dependent fields of a Mina zkApp
+         * account update (`appState`, `authorizationKind`, `authorization.proof`).
+         *
+         * # Arguments
+         * * `proof_handle` - Handle to a stored proof
+         *
+         * # Returns
+         * JSON in the o1js `AccountUpdate` shape, for merging into the account
+         * update submitted to Mina. The proof is not Pickles-wrapped; see the
+         * `zkapp` module of kimchi-prover.
+         */
+    @Throws(KimchiException::class) fun `exportZkappSubmission`(`proofHandle`: kotlin.ULong): kotlin.String {
+            return FfiConverterString.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_export_zkapp_submission(
+        FfiConverterULong.lower(`proofHandle`),_status)
+}
+    )
+    }
+    
+
         /**
          * Free a proof from memory.
          *
@@ -1624,10 +4594,70 @@ public object FfiConverterSequenceString: FfiConverterRustBuffer<List<kotlin.Str
     
     
 
+        /**
+         * Describe a built-in circuit, e.g. to check the SRS size configured in
+         * `init_prover` before attempting a proof.
+         *
+         * # Arguments
+         * * `circuit_id` - Circuit id as in proof envelopes, e.g. `"threshold"`
+         */
+    @Throws(KimchiException::class) fun `getCircuitInfo`(`circuitId`: kotlin.String): CircuitInfo {
+            return FfiConverterTypeCircuitInfo.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_get_circuit_info(
+        FfiConverterString.lower(`circuitId`),_status)
+}
+    )
+    }
+    
+
+        /**
+         * Cumulative counters and recent latencies of the prover and verifier, as
+         * also reported to the metrics callback.
+         */ fun `getMetrics`(): ProverMetrics {
+            return FfiConverterTypeProverMetrics.lift(
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_get_metrics(
+        _status)
+}
+    )
+    }
+    
+
+        /**
+         * Current size of the proof store.
+         */ fun `getProofStoreStats`(): ProofStoreStats {
+            return FfiConverterTypeProofStoreStats.lift(
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_get_proof_store_stats(
+        _status)
+}
+    )
+    }
+    
+
+        /**
+         * Set up a built-in circuit, if not done already, and describe the
+         * constraint system: how close it is to the SRS ceiling and which gates
+         * take its rows.
+         *
+         * # Arguments
+         * * `circuit_id` - Circuit id as in proof envelopes, e.g. `"threshold"`
+         */
+    @Throws(KimchiException::class) fun `getSetupInfo`(`circuitId`: kotlin.String): SetupInfo {
+            return FfiConverterTypeSetupInfo.lift(
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_get_setup_info(
+        FfiConverterString.lower(`circuitId`),_status)
+}
+    )
+    }
+    
+
         /**
          * Get the SRS log2 size used by the prover.
          *
-         * Pass this value to the WASM verifier's init_verifier() to ensure
+         * Pass this value to `kimchi-wasm`'s init_verifier() to ensure
          * the same SRS is used for verification.
          *
          * # Returns
@@ -1661,109 +4691,347 @@ public object FfiConverterSequenceString: FfiConverterRustBuffer<List<kotlin.Str
          * # Arguments
          * * `srs_log2_size` - Log2 of the SRS size. Larger values support bigger circuits
          * but use more memory. Default is 14 (16384 rows). Use 10-12 for testing.
+         * * `proof_ttl_secs` - Lifetime of stored proofs. Proofs older than this are
+         * freed automatically, and their handles fail with `ProofExpired`. `None`
+         * keeps proofs until `free_proof`. Unlike the SRS size, this is updated on
+         * every call.
+         * * `cache_dir` - Directory to cache the SRS and compiled circuits in, so
+         * later launches skip creating them. Processes sharing the directory,
+         * such as an app and its extensions in an app group container, share the
+         * cache safely. `None` keeps them in memory only.
+         *
+         * The SRS is created on the device unless `set_srs_source` was called
+         * first.
          */
-    @Throws(KimchiException::class) fun `initProver`(`srsLog2Size`: kotlin.UInt?)
+    @Throws(KimchiException::class) fun `initProver`(`srsLog2Size`: kotlin.UInt?, `proofTtlSecs`: kotlin.ULong?, `cacheDir`: kotlin.String?)
         = 
     uniffiRustCallWithError(KimchiException) { _status ->
     UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_init_prover(
-        FfiConverterOptionalUInt.lower(`srsLog2Size`),_status)
+        FfiConverterOptionalUInt.lower(`srsLog2Size`),FfiConverterOptionalULong.lower(`proofTtlSecs`),FfiConverterOptionalString.lower(`cacheDir`),_status)
 }
     
     
 
         /**
-         * Generate a proof that the distance traveled is under a threshold.
+         * Initialize verify-only mode, for apps that check proofs made elsewhere
+         * and never generate their own.
          *
-         * This generates a real Kimchi zero-knowledge proof that can be verified
-         * without revealing the actual distance value.
+         * Holds only the SRS and the verifier indices added with
+         * `add_verifier_index`, so it needs a fraction of the prover's memory.
+         * It does not require `init_prover`; later calls are no-ops.
          *
          * # Arguments
-         * * `distance_meters` - The actual distance traveled (private input)
-         * * `threshold_meters` - The maximum allowed distance (public input)
-         *
-         * # Returns
-         * A `ProofResult` containing the proof handle and serialized data.
+         * * `srs_log2_size` - Log2 of the SRS size the proofs were made with.
+         * Default is 14, matching `init_prover`.
+         */
+    @Throws(KimchiException::class) fun `initVerifier`(`srsLog2Size`: kotlin.UInt?)
+        = 
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_init_verifier(
+        FfiConverterOptionalUInt.lower(`srsLog2Size`),_status)
+}
+    
+    
+
+        /**
+         * Parse a `COSE_Sign1` envelope. The signature is not checked; verify it
+         * over `to_be_signed` before trusting the envelope.
          */
-    @Throws(KimchiException::class) fun `proveDistanceUnderThreshold`(`distanceMeters`: kotlin.ULong, `thresholdMeters`: kotlin.ULong): ProofResult {
-            return FfiConverterTypeProofResult.lift(
+    @Throws(KimchiException::class) fun `openCoseEnvelope`(`cose`: kotlin.ByteArray): CoseEnvelope {
+            return FfiConverterTypeCoseEnvelope.lift(
     uniffiRustCallWithError(KimchiException) { _status ->
-    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_prove_distance_under_threshold(
-        FfiConverterULong.lower(`distanceMeters`),FfiConverterULong.lower(`thresholdMeters`),_status)
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_open_cose_envelope(
+        FfiConverterByteArray.lower(`cose`),_status)
 }
     )
     }
     
 
         /**
-         * Generate a zero-knowledge proof that a passport holder is at least minimum_age years old.
-         *
-         * This proof uses a simplified circuit that proves age verification while keeping
-         * the birthdate private. The DG1 hash (from Passive Authentication) is included
-         * as a public input so verifiers can confirm the proof is bound to the correct passport.
-         *
-         * # Arguments
-         *
-         * * `input` - Passport data and proof parameters
+         * `prove_comparison` on the worker thread, exported as a suspend function
+         * (Kotlin) or `async` function (Swift) named `proveComparison`.
+         */
+    @Throws(KimchiException::class)
+    @Suppress("ASSIGNED_BUT_NEVER_ACCESSED_VARIABLE")
+     suspend fun `proveComparison`(`value`: kotlin.ULong, `threshold`: kotlin.ULong, `operator`: ThresholdOperator) : ProofResult {
+        return uniffiRustCallAsync(
+        UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_prove_comparison(FfiConverterULong.lower(`value`),FfiConverterULong.lower(`threshold`),FfiConverterTypeThresholdOperator.lower(`operator`),),
+        { future, callback, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_poll_rust_buffer(future, callback, continuation) },
+        { future, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_complete_rust_buffer(future, continuation) },
+        { future -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_free_rust_buffer(future) },
+        // lift function
+        { FfiConverterTypeProofResult.lift(it) },
+        // Error FFI converter
+        KimchiException.ErrorHandler,
+    )
+    }
+
+        /**
+         * `prove_merkle_membership` on the worker thread, exported as
+         * `proveMerkleMembership`.
+         */
+    @Throws(KimchiException::class)
+    @Suppress("ASSIGNED_BUT_NEVER_ACCESSED_VARIABLE")
+     suspend fun `proveMerkleMembership`(`path`: MerklePathInput) : ProofResult {
+        return uniffiRustCallAsync(
+        UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_prove_merkle_membership(FfiConverterTypeMerklePathInput.lower(`path`),),
+        { future, callback, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_poll_rust_buffer(future, callback, continuation) },
+        { future, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_complete_rust_buffer(future, continuation) },
+        { future -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_free_rust_buffer(future) },
+        // lift function
+        { FfiConverterTypeProofResult.lift(it) },
+        // Error FFI converter
+        KimchiException.ErrorHandler,
+    )
+    }
+
+        /**
+         * `prove_threshold` on the worker thread, exported as a suspend function
+         * (Kotlin) or `async` function (Swift) named `proveThreshold`.
+         */
+    @Throws(KimchiException::class)
+    @Suppress("ASSIGNED_BUT_NEVER_ACCESSED_VARIABLE")
+     suspend fun `proveThreshold`(`value`: kotlin.ULong, `threshold`: kotlin.ULong) : ProofResult {
+        return uniffiRustCallAsync(
+        UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_prove_threshold(FfiConverterULong.lower(`value`),FfiConverterULong.lower(`threshold`),),
+        { future, callback, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_poll_rust_buffer(future, callback, continuation) },
+        { future, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_complete_rust_buffer(future, continuation) },
+        { future -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_free_rust_buffer(future) },
+        // lift function
+        { FfiConverterTypeProofResult.lift(it) },
+        // Error FFI converter
+        KimchiException.ErrorHandler,
+    )
+    }
+
+        /**
+         * `prove_timestamped_comparison` on the worker thread, exported as
+         * `proveTimestampedComparison`.
+         */
+    @Throws(KimchiException::class)
+    @Suppress("ASSIGNED_BUT_NEVER_ACCESSED_VARIABLE")
+     suspend fun `proveTimestampedComparison`(`value`: kotlin.ULong, `threshold`: kotlin.ULong, `operator`: ThresholdOperator, `timestampSecs`: kotlin.ULong) : ProofResult {
+        return uniffiRustCallAsync(
+        UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_prove_timestamped_comparison(FfiConverterULong.lower(`value`),FfiConverterULong.lower(`threshold`),FfiConverterTypeThresholdOperator.lower(`operator`),FfiConverterULong.lower(`timestampSecs`),),
+        { future, callback, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_poll_rust_buffer(future, callback, continuation) },
+        { future, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_complete_rust_buffer(future, continuation) },
+        { future -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_free_rust_buffer(future) },
+        // lift function
+        { FfiConverterTypeProofResult.lift(it) },
+        // Error FFI converter
+        KimchiException.ErrorHandler,
+    )
+    }
+
+        /**
+         * `prove_wide_comparison` on the worker thread, exported as
+         * `proveWideComparison`.
+         */
+    @Throws(KimchiException::class)
+    @Suppress("ASSIGNED_BUT_NEVER_ACCESSED_VARIABLE")
+     suspend fun `proveWideComparison`(`valueDecimal`: kotlin.String, `thresholdDecimal`: kotlin.String, `operator`: ThresholdOperator, `width`: ValueWidth) : ProofResult {
+        return uniffiRustCallAsync(
+        UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_prove_wide_comparison(FfiConverterString.lower(`valueDecimal`),FfiConverterString.lower(`thresholdDecimal`),FfiConverterTypeThresholdOperator.lower(`operator`),FfiConverterTypeValueWidth.lower(`width`),),
+        { future, callback, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_poll_rust_buffer(future, callback, continuation) },
+        { future, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_complete_rust_buffer(future, continuation) },
+        { future -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_free_rust_buffer(future) },
+        // lift function
+        { FfiConverterTypeProofResult.lift(it) },
+        // Error FFI converter
+        KimchiException.ErrorHandler,
+    )
+    }
+
+        /**
+         * Stop trusting the verifier index with `digest`.
          *
          * # Returns
-         *
-         * A `PassportProofResult` containing the proof and verification status.
-         *
-         * # Privacy
-         *
-         * The proof reveals ONLY:
-         * - That the holder is >= minimum_age
-         * - The minimum age threshold used
-         * - The current date used for verification
-         * - The DG1 hash (which doesn't reveal personal data)
-         *
-         * The proof does NOT reveal:
-         * - The actual birthdate
-         * - The exact age
-         * - Any other passport data (name, nationality, etc.)
+         * Whether it had been added.
          */
-    @Throws(KimchiException::class) fun `provePassportAge`(`input`: PassportProofInput): PassportProofResult {
-            return FfiConverterTypePassportProofResult.lift(
+    @Throws(KimchiException::class) fun `removeVerifierIndex`(`digest`: Bytes32): kotlin.Boolean {
+            return FfiConverterBoolean.lift(
     uniffiRustCallWithError(KimchiException) { _status ->
-    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_prove_passport_age(
-        FfiConverterTypePassportProofInput.lower(`input`),_status)
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_remove_verifier_index(
+        FfiConverterTypeBytes32.lower(`digest`),_status)
 }
     )
     }
     
 
         /**
-         * Verify a passport age proof.
+         * Start `get_metrics` over from zero.
+         */ fun `resetMetrics`()
+        = 
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_reset_metrics(
+        _status)
+}
+    
+    
+
+        /**
+         * Sign the envelopes of proofs generated from now on with a device-held
+         * Pallas key, given as `mina-signer` hex, or stop signing with `None`.
          *
-         * This performs full cryptographic verification of the proof.
+         * Returns the key's Mina address, which relying parties check signed
+         * envelopes against.
          */
-    @Throws(KimchiException::class) fun `verifyPassportProof`(`proofHandle`: kotlin.ULong): kotlin.Boolean {
-            return FfiConverterBoolean.lift(
+    @Throws(KimchiException::class) fun `setEnvelopeSigningKey`(`secretKeyHex`: kotlin.String?): kotlin.String? {
+            return FfiConverterOptionalString.lift(
     uniffiRustCallWithError(KimchiException) { _status ->
-    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_verify_passport_proof(
-        FfiConverterULong.lower(`proofHandle`),_status)
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_set_envelope_signing_key(
+        FfiConverterOptionalString.lower(`secretKeyHex`),_status)
 }
     )
     }
     
 
         /**
-         * Verify a proof using its handle.
-         *
-         * # Arguments
-         * * `proof_handle` - Handle returned from prove_distance_under_threshold
+         * Install a metrics callback. Replaces any previous one.
+         */ fun `setMetricsCallback`(`callback`: MetricsCallback)
+        = 
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_set_metrics_callback(
+        FfiConverterTypeMetricsCallback.lower(`callback`),_status)
+}
+    
+    
+
+        /**
+         * Cap the memory used by stored proofs.
          *
-         * # Returns
-         * `true` if the proof is valid, `false` otherwise.
+         * Once stored proofs and their verifier indices would exceed `limit_bytes`,
+         * proving fails with a `Prover` error with code 800 (resource exhausted)
+         * instead of the store growing further; free proofs to make room. `None`
+         * removes the cap. Proofs already stored are kept even if they exceed a
+         * new, lower limit.
+         */ fun `setProofStoreLimit`(`limitBytes`: kotlin.ULong?)
+        = 
+    uniffiRustCall() { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_set_proof_store_limit(
+        FfiConverterOptionalULong.lower(`limitBytes`),_status)
+}
+    
+    
+
+        /**
+         * Select where the SRS comes from. Call before `init_prover`; `fetcher`
+         * is required for `SrsSource::Download`.
          */
-    @Throws(KimchiException::class) fun `verifyProof`(`proofHandle`: kotlin.ULong): kotlin.Boolean {
-            return FfiConverterBoolean.lift(
+    @Throws(KimchiException::class) fun `setSrsSource`(`source`: SrsSource, `fetcher`: SrsFetcher?)
+        = 
     uniffiRustCallWithError(KimchiException) { _status ->
-    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_verify_proof(
-        FfiConverterULong.lower(`proofHandle`),_status)
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_set_srs_source(
+        FfiConverterTypeSrsSource.lower(`source`),FfiConverterOptionalTypeSrsFetcher.lower(`fetcher`),_status)
+}
+    
+    
+
+        /**
+         * Load verifier indices that were not added with `add_verifier_index`
+         * from `source`, checking that each hashes to the digest the envelope
+         * names. Requires `init_verifier`; replaces any previous source.
+         */
+    @Throws(KimchiException::class) fun `setVerifierIndexSource`(`source`: VerifierIndexSource)
+        = 
+    uniffiRustCallWithError(KimchiException) { _status ->
+    UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_set_verifier_index_source(
+        FfiConverterTypeVerifierIndexSource.lower(`source`),_status)
 }
+    
+    
+
+        /**
+         * `verify_envelope` on the worker thread, exported as `verifyEnvelope`.
+         */
+    @Throws(KimchiException::class)
+    @Suppress("ASSIGNED_BUT_NEVER_ACCESSED_VARIABLE")
+     suspend fun `verifyEnvelope`(`envelope`: kotlin.String) : kotlin.Boolean {
+        return uniffiRustCallAsync(
+        UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_verify_envelope(FfiConverterString.lower(`envelope`),),
+        { future, callback, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_poll_i8(future, callback, continuation) },
+        { future, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_complete_i8(future, continuation) },
+        { future -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_free_i8(future) },
+        // lift function
+        { FfiConverterBoolean.lift(it) },
+        // Error FFI converter
+        KimchiException.ErrorHandler,
+    )
+    }
+
+        /**
+         * `verify_envelope_with_attestation` on the worker thread, exported as
+         * `verifyEnvelopeWithAttestation`.
+         */
+    @Throws(KimchiException::class)
+    @Suppress("ASSIGNED_BUT_NEVER_ACCESSED_VARIABLE")
+     suspend fun `verifyEnvelopeWithAttestation`(`envelope`: kotlin.String, `platform`: AttestationPlatform, `tokenHash`: Bytes32) : kotlin.Boolean {
+        return uniffiRustCallAsync(
+        UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_verify_envelope_with_attestation(FfiConverterString.lower(`envelope`),FfiConverterTypeAttestationPlatform.lower(`platform`),FfiConverterTypeBytes32.lower(`tokenHash`),),
+        { future, callback, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_poll_i8(future, callback, continuation) },
+        { future, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_complete_i8(future, continuation) },
+        { future -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_free_i8(future) },
+        // lift function
+        { FfiConverterBoolean.lift(it) },
+        // Error FFI converter
+        KimchiException.ErrorHandler,
+    )
+    }
+
+        /**
+         * `verify_envelope_with_max_age` on the worker thread, exported as
+         * `verifyEnvelopeWithMaxAge`.
+         */
+    @Throws(KimchiException::class)
+    @Suppress("ASSIGNED_BUT_NEVER_ACCESSED_VARIABLE")
+     suspend fun `verifyEnvelopeWithMaxAge`(`envelope`: kotlin.String, `nowSecs`: kotlin.ULong, `maxAgeSecs`: kotlin.ULong) : kotlin.Boolean {
+        return uniffiRustCallAsync(
+        UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_verify_envelope_with_max_age(FfiConverterString.lower(`envelope`),FfiConverterULong.lower(`nowSecs`),FfiConverterULong.lower(`maxAgeSecs`),),
+        { future, callback, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_poll_i8(future, callback, continuation) },
+        { future, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_complete_i8(future, continuation) },
+        { future -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_free_i8(future) },
+        // lift function
+        { FfiConverterBoolean.lift(it) },
+        // Error FFI converter
+        KimchiException.ErrorHandler,
+    )
+    }
+
+        /**
+         * `verify_proof` on the worker thread, exported as `verifyProof`.
+         */
+    @Throws(KimchiException::class)
+    @Suppress("ASSIGNED_BUT_NEVER_ACCESSED_VARIABLE")
+     suspend fun `verifyProof`(`proofHandle`: kotlin.ULong) : kotlin.Boolean {
+        return uniffiRustCallAsync(
+        UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_verify_proof(FfiConverterULong.lower(`proofHandle`),),
+        { future, callback, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_poll_i8(future, callback, continuation) },
+        { future, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_complete_i8(future, continuation) },
+        { future -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_free_i8(future) },
+        // lift function
+        { FfiConverterBoolean.lift(it) },
+        // Error FFI converter
+        KimchiException.ErrorHandler,
+    )
+    }
+
+        /**
+         * `verify_signed_envelope` on the worker thread, exported as
+         * `verifySignedEnvelope`.
+         */
+    @Throws(KimchiException::class)
+    @Suppress("ASSIGNED_BUT_NEVER_ACCESSED_VARIABLE")
+     suspend fun `verifySignedEnvelope`(`signedEnvelope`: kotlin.String, `expectedSigner`: kotlin.String?) : kotlin.Boolean {
+        return uniffiRustCallAsync(
+        UniffiLib.INSTANCE.uniffi_kimchi_ffi_fn_func_verify_signed_envelope(FfiConverterString.lower(`signedEnvelope`),FfiConverterOptionalString.lower(`expectedSigner`),),
+        { future, callback, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_poll_i8(future, callback, continuation) },
+        { future, continuation -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_complete_i8(future, continuation) },
+        { future -> UniffiLib.INSTANCE.ffi_kimchi_ffi_rust_future_free_i8(future) },
+        // lift function
+        { FfiConverterBoolean.lift(it) },
+        // Error FFI converter
+        KimchiException.ErrorHandler,
     )
     }
-    
 
 
@@ -0,0 +1,87 @@
+package com.kimchi.reactnative
+
+import com.facebook.react.bridge.Arguments
+import com.facebook.react.bridge.Promise
+import com.facebook.react.bridge.ReactApplicationContext
+import com.facebook.react.module.annotations.ReactModule
+import com.kimchi.mobile.KimchiMobile
+import kotlinx.coroutines.CoroutineScope
+import kotlinx.coroutines.Dispatchers
+import kotlinx.coroutines.SupervisorJob
+import kotlinx.coroutines.cancel
+import kotlinx.coroutines.launch
+
+@ReactModule(name = KimchiModule.NAME)
+class KimchiModule(reactContext: ReactApplicationContext) : NativeKimchiSpec(reactContext) {
+    private val scope = CoroutineScope(SupervisorJob() + Dispatchers.Default)
+
+    override fun getName() = NAME
+
+    override fun invalidate() {
+        scope.cancel()
+        super.invalidate()
+    }
+
+    /** Run `block` off the JS thread, rejecting `promise` with `code` on failure. */
+    private fun launch(promise: Promise, code: String, block: suspend () -> Unit) {
+        scope.launch {
+            try {
+                block()
+            } catch (e: Exception) {
+                promise.reject(code, e.message, e)
+            }
+        }
+    }
+
+    override fun initProver(srsLog2Size: Double?, promise: Promise) =
+        launch(promise, "INIT_ERROR") {
+            if (KimchiMobile.initialize(reactApplicationContext, srsLog2Size?.toInt())) {
+                promise.resolve(null)
+            } else {
+                promise.reject("INIT_ERROR", "Failed to initialize Kimchi prover")
+            }
+        }
+
+    override fun isInitialized() = KimchiMobile.isInitialized()
+
+    override fun proveThreshold(value: String, threshold: String, promise: Promise) =
+        launch(promise, "PROVE_ERROR") {
+            val v = value.toULong().toLong()
+            val t = threshold.toULong().toLong()
+            val result = KimchiMobile.proveThreshold(v, t).getOrThrow()
+            promise.resolve(Arguments.createMap().apply {
+                putString("proofHandle", result.proofHandle.toString())
+                putString("proofBytes", result.proofBytes)
+                putArray("publicInputs", Arguments.fromList(result.publicInputs))
+                putDouble("generationTimeMs", result.generationTimeMs.toDouble())
+                putDouble("proofSizeBytes", result.proofSizeBytes.toDouble())
+            })
+        }
+
+    override fun verifyProof(proofHandle: String, promise: Promise) =
+        launch(promise, "VERIFY_ERROR") {
+            promise.resolve(KimchiMobile.verifyProof(proofHandle.toLong()).getOrThrow())
+        }
+
+    override fun exportVerifierIndex(proofHandle: String, promise: Promise) =
+        launch(promise, "EXPORT_ERROR") {
+            promise.resolve(KimchiMobile.exportVerifierIndex(proofHandle.toLong()).getOrThrow())
+        }
+
+    override fun freeProof(proofHandle: String, promise: Promise) =
+        launch(promise, "FREE_ERROR") {
+            if (KimchiMobile.freeProof(proofHandle.toLong())) {
+                promise.resolve(null)
+            } else {
+                promise.reject("FREE_ERROR", "Failed to free proof")
+            }
+        }
+
+    override fun getSrsLog2Size(): Double? = KimchiMobile.getSrsLog2Size()?.toDouble()
+
+    override fun getVersion() = KimchiMobile.getLibraryVersion()
+
+    companion object {
+        const val NAME = "Kimchi"
+    }
+}
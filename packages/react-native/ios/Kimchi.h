@@ -0,0 +1,4 @@
+#import <RNKimchiSpec/RNKimchiSpec.h>
+
+@interface Kimchi : NSObject <NativeKimchiSpec>
+@end
@@ -0,0 +1,80 @@
+#import "Kimchi.h"
+
+#if __has_include("KimchiReactNative/KimchiReactNative-Swift.h")
+#import "KimchiReactNative/KimchiReactNative-Swift.h"
+#else
+#import "KimchiReactNative-Swift.h"
+#endif
+
+@implementation Kimchi {
+  KimchiImpl *_impl;
+}
+
+RCT_EXPORT_MODULE(Kimchi)
+
+- (instancetype)init
+{
+  if (self = [super init]) {
+    _impl = [KimchiImpl new];
+  }
+  return self;
+}
+
+- (void)initProver:(NSNumber *)srsLog2Size
+           resolve:(RCTPromiseResolveBlock)resolve
+            reject:(RCTPromiseRejectBlock)reject
+{
+  [_impl initProver:srsLog2Size resolve:resolve reject:reject];
+}
+
+- (NSNumber *)isInitialized
+{
+  return @([_impl isInitialized]);
+}
+
+- (void)proveThreshold:(NSString *)value
+             threshold:(NSString *)threshold
+               resolve:(RCTPromiseResolveBlock)resolve
+                reject:(RCTPromiseRejectBlock)reject
+{
+  [_impl proveThreshold:value threshold:threshold resolve:resolve reject:reject];
+}
+
+- (void)verifyProof:(NSString *)proofHandle
+            resolve:(RCTPromiseResolveBlock)resolve
+             reject:(RCTPromiseRejectBlock)reject
+{
+  [_impl verifyProof:proofHandle resolve:resolve reject:reject];
+}
+
+- (void)exportVerifierIndex:(NSString *)proofHandle
+                    resolve:(RCTPromiseResolveBlock)resolve
+                     reject:(RCTPromiseRejectBlock)reject
+{
+  [_impl exportVerifierIndex:proofHandle resolve:resolve reject:reject];
+}
+
+- (void)freeProof:(NSString *)proofHandle
+          resolve:(RCTPromiseResolveBlock)resolve
+           reject:(RCTPromiseRejectBlock)reject
+{
+  [_impl freeProof:proofHandle resolve:resolve reject:reject];
+}
+
+- (NSNumber *)getSrsLog2Size
+{
+  return [_impl getSrsLog2Size];
+}
+
+- (NSString *)getVersion
+{
+  return [_impl getVersion];
+}
+
+- (std::shared_ptr<facebook::react::TurboModule>)getTurboModule:
+    (const facebook::react::ObjCTurboModule::InitParams &)params
+{
+  return std::make_shared<facebook::react::NativeKimchiSpecJSI>(params);
+}
+
+@end
@@ -0,0 +1,101 @@
+import Foundation
+import KimchiMobile
+
+/// Swift side of the TurboModule; `Kimchi.mm` forwards to it since the
+/// codegen'd spec is Objective-C++.
+@objc(KimchiImpl)
+public class KimchiImpl: NSObject {
+  public typealias Resolve = (Any?) -> Void
+  public typealias Reject = (String, String, Error?) -> Void
+
+  /// Run `body` off the JS thread, rejecting with `code` on failure.
+  private func run(_ code: String, _ resolve: @escaping Resolve, _ reject: @escaping Reject,
+                   _ body: @escaping () async throws -> Any?) {
+    Task.detached(priority: .userInitiated) {
+      do {
+        resolve(try await body())
+      } catch {
+        reject(code, error.localizedDescription, error)
+      }
+    }
+  }
+
+  private func handle(_ proofHandle: String) throws -> Int64 {
+    guard let handle = Int64(proofHandle) else {
+      throw KimchiModuleError.invalidInput("Invalid proof handle")
+    }
+    return handle
+  }
+
+  @objc public func initProver(_ srsLog2Size: NSNumber?, resolve: @escaping Resolve,
+                               reject: @escaping Reject) {
+    run("INIT_ERROR", resolve, reject) {
+      try KimchiMobile.initialize(srsLog2Size: srsLog2Size?.uint32Value)
+      return nil
+    }
+  }
+
+  @objc public func isInitialized() -> Bool {
+    KimchiMobile.isInitialized()
+  }
+
+  @objc public func proveThreshold(_ value: String, threshold: String,
+                                   resolve: @escaping Resolve, reject: @escaping Reject) {
+    run("PROVE_ERROR", resolve, reject) {
+      guard let v = UInt64(value), let t = UInt64(threshold) else {
+        throw KimchiModuleError.invalidInput("Invalid number format")
+      }
+      let result = try await KimchiMobile.proveThreshold(value: v, threshold: t)
+      return [
+        "proofHandle": String(result.proofHandle),
+        "proofBytes": result.proofBytes,
+        "publicInputs": result.publicInputs,
+        "generationTimeMs": result.generationTimeMs,
+        "proofSizeBytes": result.proofSizeBytes,
+      ] as [String: Any]
+    }
+  }
+
+  @objc public func verifyProof(_ proofHandle: String, resolve: @escaping Resolve,
+                                reject: @escaping Reject) {
+    run("VERIFY_ERROR", resolve, reject) {
+      try await KimchiMobile.verifyProof(proofHandle: try self.handle(proofHandle))
+    }
+  }
+
+  @objc public func exportVerifierIndex(_ proofHandle: String, resolve: @escaping Resolve,
+                                        reject: @escaping Reject) {
+    run("EXPORT_ERROR", resolve, reject) {
+      try KimchiMobile.exportVerifierIndex(proofHandle: try self.handle(proofHandle))
+    }
+  }
+
+  @objc public func freeProof(_ proofHandle: String, resolve: @escaping Resolve,
+                              reject: @escaping Reject) {
+    run("FREE_ERROR", resolve, reject) {
+      try KimchiMobile.freeProof(proofHandle: try self.handle(proofHandle))
+      return nil
+    }
+  }
+
+  @objc public func getSrsLog2Size() -> NSNumber? {
+    (try? KimchiMobile.getSrsLog2Size()).map { NSNumber(value: $0) }
+  }
+
+  @objc public func getVersion() -> String {
+    KimchiMobile.version()
+  }
+}
+
+enum KimchiModuleError: Error {
+  case invalidInput(String)
+}
+
+extension KimchiModuleError: LocalizedError {
+  var errorDescription: String? {
+    switch self {
+    case .invalidInput(let message):
+      return "Invalid input: \(message)"
+    }
+  }
+}
@@ -0,0 +1,28 @@
+import type { TurboModule } from 'react-native';
+import { TurboModuleRegistry } from 'react-native';
+
+/**
+ * Codegen spec for the native module.
+ *
+ * 64-bit values (inputs and proof handles) cross the bridge as decimal
+ * strings, since JS numbers cannot represent them exactly. `initProver` is
+ * not named `initialize` to avoid the native module lifecycle method.
+ */
+export interface Spec extends TurboModule {
+  initProver(srsLog2Size: number | null): Promise<void>;
+  isInitialized(): boolean;
+  proveThreshold(value: string, threshold: string): Promise<{
+    proofHandle: string;
+    proofBytes: string;
+    publicInputs: string[];
+    generationTimeMs: number;
+    proofSizeBytes: number;
+  }>;
+  verifyProof(proofHandle: string): Promise<boolean>;
+  exportVerifierIndex(proofHandle: string): Promise<string>;
+  freeProof(proofHandle: string): Promise<void>;
+  getSrsLog2Size(): number | null;
+  getVersion(): string;
+}
+
+export default TurboModuleRegistry.getEnforcing<Spec>('Kimchi');
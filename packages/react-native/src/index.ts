@@ -0,0 +1,118 @@
+import NativeKimchi from './NativeKimchi';
+
+/**
+ * Result of a successful proof generation
+ */
+export interface ProofResult {
+  /** Handle for referencing the proof in memory */
+  proofHandle: string;
+  /** Hex-encoded proof bytes */
+  proofBytes: string;
+  /** Hex-encoded public inputs */
+  publicInputs: string[];
+  /** Time taken to generate proof in milliseconds */
+  generationTimeMs: number;
+  /** Size of the proof in bytes */
+  proofSizeBytes: number;
+}
+
+/**
+ * Shareable proof format for transmission/verification
+ */
+export interface ShareableProof {
+  /** Hex-encoded proof bytes */
+  proof: string;
+  /** Hex-encoded public inputs */
+  publicInputs: string[];
+  /** Hex-encoded verifier index */
+  verifierIndex: string;
+}
+
+/**
+ * Initialize the Kimchi prover with optional SRS size configuration.
+ * Must be called before generating proofs.
+ *
+ * @param srsLog2Size - Log2 of SRS size (default: 14 = 16384 rows)
+ *                      Smaller values (10-12) are faster for testing
+ */
+export async function initialize(srsLog2Size?: number): Promise<void> {
+  return NativeKimchi.initProver(srsLog2Size ?? null);
+}
+
+/**
+ * Check if the prover has been initialized
+ */
+export function isInitialized(): boolean {
+  return NativeKimchi.isInitialized();
+}
+
+/**
+ * Generate a zero-knowledge proof that a value is at least a threshold.
+ * Runs off the JS thread.
+ *
+ * @param value - The secret value to prove
+ * @param threshold - The public threshold
+ * @returns Proof result containing handle and encoded proof data
+ */
+export async function proveThreshold(value: bigint, threshold: bigint): Promise<ProofResult> {
+  return NativeKimchi.proveThreshold(value.toString(), threshold.toString());
+}
+
+/**
+ * Verify a proof using its handle.
+ *
+ * @param proofHandle - Handle returned from proveThreshold
+ * @returns True if the proof is valid
+ */
+export async function verifyProof(proofHandle: string): Promise<boolean> {
+  return NativeKimchi.verifyProof(proofHandle);
+}
+
+/**
+ * Export the verifier index for a proof, for remote verification with the
+ * WASM verifier.
+ *
+ * @param proofHandle - Handle returned from proveThreshold
+ * @returns Hex-encoded verifier index
+ */
+export async function exportVerifierIndex(proofHandle: string): Promise<string> {
+  return NativeKimchi.exportVerifierIndex(proofHandle);
+}
+
+/**
+ * Free a proof from memory.
+ *
+ * @param proofHandle - Handle returned from proveThreshold
+ */
+export async function freeProof(proofHandle: string): Promise<void> {
+  return NativeKimchi.freeProof(proofHandle);
+}
+
+/**
+ * Get the current SRS log2 size, or null if not initialized
+ */
+export function getSrsLog2Size(): number | null {
+  return NativeKimchi.getSrsLog2Size();
+}
+
+/**
+ * Get the library version string
+ */
+export function getVersion(): string {
+  return NativeKimchi.getVersion();
+}
+
+/**
+ * Create a shareable proof object for transmission.
+ *
+ * @param proofResult - Result from proveThreshold
+ * @returns ShareableProof suitable for JSON serialization
+ */
+export async function createShareableProof(proofResult: ProofResult): Promise<ShareableProof> {
+  const verifierIndex = await exportVerifierIndex(proofResult.proofHandle);
+  return {
+    proof: proofResult.proofBytes,
+    publicInputs: proofResult.publicInputs,
+    verifierIndex,
+  };
+}
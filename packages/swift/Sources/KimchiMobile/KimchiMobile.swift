@@ -15,7 +15,7 @@ import CommonCrypto
 /// let proof = try await KimchiMobile.proveThreshold(value: 50, threshold: 100)
 ///
 /// // Verify locally
-/// let valid = try KimchiMobile.verifyProof(proofHandle: proof.proofHandle)
+/// let valid = try await KimchiMobile.verifyProof(proofHandle: proof.proofHandle)
 ///
 /// // Or share for remote verification
 /// let shareable = proof.toShareable(metadata: ["app": "MyApp"])
@@ -28,15 +28,31 @@ public final class KimchiMobile {
 
     /// Initialize the Kimchi prover. Must be called once before generating proofs.
     ///
-    /// - Parameter srsLog2Size: Log2 of the SRS size (default: 14 = 16384 rows).
-    ///   Larger values support bigger circuits but use more memory.
-    public static func initialize(srsLog2Size: UInt32? = nil) throws {
+    /// - Parameters:
+    ///   - srsLog2Size: Log2 of the SRS size (default: 14 = 16384 rows).
+    ///     Larger values support bigger circuits but use more memory.
+    ///   - proofTtlSecs: Lifetime of stored proofs in seconds, after which they
+    ///     are freed and their handles throw `ProofExpired`. `nil` keeps proofs
+    ///     until `freeProof`.
+    ///   - cacheDirectory: Directory to cache the SRS and compiled circuits in
+    ///     across launches. Use an app group container to share the cache with
+    ///     app extensions; access is coordinated with file locks. `nil` keeps
+    ///     them in memory only.
+    public static func initialize(
+        srsLog2Size: UInt32? = nil,
+        proofTtlSecs: UInt64? = nil,
+        cacheDirectory: URL? = nil
+    ) throws {
         initLock.lock()
         defer { initLock.unlock() }
 
         guard !initialized else { return }
 
-        try initProver(srsLog2Size: srsLog2Size)
+        try initProver(
+            srsLog2Size: srsLog2Size,
+            proofTtlSecs: proofTtlSecs,
+            cacheDir: cacheDirectory?.path
+        )
         initialized = true
     }
 
@@ -62,7 +78,7 @@ public final class KimchiMobile {
     /// print("Proof generated in \(proof.generationTimeMs)ms")
     ///
     /// // Verify locally
-    /// let valid = try KimchiMobile.verifyProof(proofHandle: proof.proofHandle)
+    /// let valid = try await KimchiMobile.verifyProof(proofHandle: proof.proofHandle)
     ///
     /// // Or share for remote verification
     /// let shareable = proof.toShareable()
@@ -73,34 +89,32 @@ public final class KimchiMobile {
             throw KimchiMobileError.notInitialized
         }
 
-        return try await Task.detached(priority: .userInitiated) {
-            // Generate the proof
-            let ffiResult = try KimchiFfi.proveThreshold(value: value, threshold: threshold)
-
-            // Export verifier index for WASM verification
-            let verifierIndex = try KimchiFfi.exportVerifierIndex(proofHandle: ffiResult.proofHandle)
-
-            return ProofResult(
-                proofHandle: Int64(ffiResult.proofHandle),
-                proofBytes: ffiResult.proofBytes,
-                verifierIndex: verifierIndex,
-                publicInputs: ffiResult.publicInputs,
-                generationTimeMs: Int64(ffiResult.generationTimeMs),
-                proofSizeBytes: Int64(ffiResult.proofSizeBytes)
-            )
-        }.value
+        // Proving runs on the library's worker thread
+        let ffiResult = try await KimchiFfi.proveThreshold(value: value, threshold: threshold)
+
+        // Export verifier index for WASM verification
+        let verifierIndex = try KimchiFfi.exportVerifierIndex(proofHandle: ffiResult.proofHandle)
+
+        return ProofResult(
+            proofHandle: Int64(ffiResult.proofHandle),
+            proofBytes: ffiResult.proofBytes,
+            verifierIndex: verifierIndex,
+            publicInputs: ffiResult.publicInputs,
+            generationTimeMs: Int64(ffiResult.generationTimeMs),
+            proofSizeBytes: Int64(ffiResult.proofSizeBytes)
+        )
     }
 
     /// Verify a proof by its handle.
     ///
     /// - Parameter proofHandle: Handle returned from proof generation
     /// - Returns: true if the proof is valid
-    public static func verifyProof(proofHandle: Int64) throws -> Bool {
+    public static func verifyProof(proofHandle: Int64) async throws -> Bool {
         guard initialized else {
             throw KimchiMobileError.notInitialized
         }
 
-        return try KimchiFfi.verifyProof(proofHandle: UInt64(proofHandle))
+        return try await KimchiFfi.verifyProof(proofHandle: UInt64(proofHandle))
     }
 
     /// Export the verifier index for a stored proof.
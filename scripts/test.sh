@@ -49,6 +49,17 @@ echo "Running unit tests..."
 echo "----------------------------------------"
 cargo test --workspace --all-features
 
+echo ""
+echo "----------------------------------------"
+echo "Checking kimchi-prover builds for wasm32..."
+echo "----------------------------------------"
+if rustup target list --installed | grep -q wasm32-unknown-unknown; then
+    cargo check -p kimchi-prover --target wasm32-unknown-unknown --no-default-features --features wasm
+    echo "✓ wasm32 build OK"
+else
+    echo "- wasm32-unknown-unknown target not installed, skipping"
+fi
+
 echo ""
 echo "========================================"
 echo "All tests passed!"